@@ -1,6 +1,12 @@
+use hxo_compiler::{CompileOptions, CompileResult, Compiler, SharedStyleEngine, TailwindConfig};
 use hxo_ir::{IRModule, TemplateNodeIR};
-use hxo_types::Result;
-use std::collections::HashSet;
+use hxo_types::{Error, Result, Span};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct FeatureSet {
@@ -14,11 +20,58 @@ pub struct FeatureSet {
 
 pub struct Bundler {
     pub feature_set: FeatureSet,
+    /// Utility rules of every component compiled with [`Bundler::compiler`].
+    pub styles: SharedStyleEngine,
+    tailwind: Arc<TailwindConfig>,
+    /// Project root that component paths, and so their scope ids, are relative to.
+    root: PathBuf,
 }
 
 impl Bundler {
     pub fn new() -> Self {
-        Self { feature_set: FeatureSet::default() }
+        Self::with_tailwind_config(Arc::new(TailwindConfig::new()))
+    }
+
+    pub fn with_tailwind_config(tailwind: Arc<TailwindConfig>) -> Self {
+        Self {
+            feature_set: FeatureSet::default(),
+            styles: SharedStyleEngine::new(tailwind.clone()),
+            tailwind,
+            root: PathBuf::from("."),
+        }
+    }
+
+    /// Takes component paths relative to `root` instead of the working directory.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// A compiler whose utility classes go to the bundle's shared stylesheet.
+    pub fn compiler(&self) -> Compiler {
+        Compiler::with_tailwind_config(self.tailwind.clone()).with_shared_styles(self.styles.clone())
+    }
+
+    /// Compiles the component at `path` with [`Bundler::compiler`], passing its path relative to the
+    /// project root so that components of the same name in different directories get distinct scope ids.
+    pub fn compile_file(&self, path: &Path, options: CompileOptions) -> Result<CompileResult> {
+        let root = fs::canonicalize(&self.root)?;
+        let absolute = fs::canonicalize(path)?;
+        let Ok(relative) = absolute.strip_prefix(&root)
+        else {
+            let details = format!("outside the project root {}", root.display());
+            return Err(Error::external_error(path.display().to_string(), details, Span::unknown()));
+        };
+        let file_path = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        let name = absolute.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let source = fs::read_to_string(&absolute)?;
+        let options = CompileOptions { file_path: Some(file_path), root: Some(root), ..options };
+        self.compiler().compile_with_options(&name, &source, options)
+    }
+
+    /// One deduplicated utility stylesheet for the whole app, in layer order.
+    pub fn utility_css(&self) -> String {
+        self.styles.generate_css()
     }
 }
 
@@ -1,4 +1,5 @@
 use hxo_bundler::Bundler;
+use hxo_compiler::CompileOptions;
 use hxo_ir::IRModule;
 use hxo_types::Span;
 use std::collections::HashMap;
@@ -23,3 +24,36 @@ fn test_bundler_basic() {
     let runtime = bundler.generate_custom_runtime();
     assert!(runtime.contains("HXO Custom Runtime"));
 }
+
+#[test]
+fn test_bundler_utility_stylesheet() {
+    let bundler = Bundler::new();
+    let mut compiler = bundler.compiler();
+    compiler.compile("A", "<template><div class=\"flex p-4\"></div></template>").unwrap();
+    bundler.compiler().compile("B", "<template><div class=\"p-4 hover:font-bold\"></div></template>").unwrap();
+
+    let css = bundler.utility_css();
+    assert_eq!(css.matches(".p-4 {").count(), 1);
+    assert_eq!(css.matches(".flex {").count(), 1);
+    assert!(css.contains(".hover\\:font-bold:hover"));
+}
+
+#[test]
+fn test_bundler_scope_ids_follow_component_paths() {
+    let root = std::env::temp_dir().join(format!("hxo-bundler-paths-{}", std::process::id()));
+    let source = "<template><button class=\"btn\">Go</button></template>\n<style scoped>\n.btn { color: red; }\n</style>\n";
+    for dir in ["forms", "admin"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+        std::fs::write(root.join(dir).join("Button.hxo"), source).unwrap();
+    }
+
+    let bundler = Bundler::new().with_root(&root);
+    let forms = bundler.compile_file(&root.join("forms/Button.hxo"), CompileOptions::default()).unwrap();
+    let admin = bundler.compile_file(&root.join("admin/Button.hxo"), CompileOptions::default()).unwrap();
+    let outside = bundler.compile_file(&std::env::temp_dir(), CompileOptions::default());
+    std::fs::remove_dir_all(&root).ok();
+
+    assert_ne!(forms.css, admin.css);
+    assert!(forms.css.starts_with(".btn[data-h-"), "{}", forms.css);
+    assert!(outside.is_err());
+}
@@ -1,9 +1,17 @@
 use clap::{Parser, Subcommand};
 use color_eyre::eyre::Result;
 use console::style;
-use hxo_compiler::{CompileOptions, Compiler};
+use hxo_compiler::{
+    CompileOptions, Compiler, ScopeIdOptions, TailwindConfig, Targets,
+    catalog::{CatalogSync, Extraction, lang_for_extension},
+    router,
+};
 use hxo_lsp::run_server;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 #[derive(Parser)]
 #[command(name = "hxo")]
@@ -48,6 +56,35 @@ enum Commands {
         /// Locale for zero-runtime i18n optimization
         #[arg(long)]
         locale: Option<String>,
+
+        /// Directory of project locale files such as `en.json` or `zh-CN.ftl` (repeatable)
+        #[arg(long = "locale-dir")]
+        locale_dirs: Vec<PathBuf>,
+
+        /// Browsers the CSS written to `<output>/<name>.css` must support, as a browserslist-style query (e.g. "defaults, safari 14")
+        #[arg(long)]
+        browsers: Option<String>,
+
+        /// Directory searched by style `@use`, `@forward` and `@import` in SCSS, Sass, Less and Stylus (repeatable)
+        #[arg(long = "load-path")]
+        load_paths: Vec<PathBuf>,
+
+        /// Attribute prefix for scoped style ids
+        #[arg(long, default_value = "data-h-")]
+        scope_prefix: String,
+
+        /// Number of hex digits in scoped style ids
+        #[arg(long, default_value_t = 8)]
+        scope_length: usize,
+
+        /// Also hash the component source into its scope id
+        #[arg(long)]
+        scope_content_hash: bool,
+
+        /// Project root that scope ids are relative to; defaults to the directory of the nearest `hxo.config.toml`,
+        /// else the working directory
+        #[arg(long)]
+        root: Option<PathBuf>,
     },
     /// Initialize a new HXO project
     Init {
@@ -62,22 +99,138 @@ enum Commands {
     },
     /// Start Language Server
     Lsp,
+    /// Manage message catalogs
+    I18n {
+        #[command(subcommand)]
+        command: I18nCommands,
+    },
+    /// Validate routes and generate the route table
+    Router {
+        #[command(subcommand)]
+        command: RouterCommands,
+    },
 }
 
+#[derive(Subcommand)]
+enum RouterCommands {
+    /// Write `routes.js` and `routes.d.ts` from a router file or a `pages/` directory
+    Build {
+        /// Router file (YAML or JSON, with component paths relative to it) or a directory of pages
+        #[arg(default_value = "router.yaml")]
+        input: PathBuf,
+
+        /// Router mode, hash or history, overriding the router file
+        #[arg(long)]
+        mode: Option<String>,
+
+        /// Base path of the app, overriding the router file
+        #[arg(long)]
+        base: Option<String>,
+
+        /// Output directory
+        #[arg(short, long, default_value = "dist")]
+        output: PathBuf,
+
+        /// Validate without writing
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum I18nCommands {
+    /// Collect `$t` keys from components and sync the locale catalogs with them
+    Extract {
+        /// Component file or directory to scan for `.hxo` files
+        #[arg(default_value = ".")]
+        input: PathBuf,
+
+        /// Directory of locale catalogs such as `en.json` or `zh-CN.ftl`
+        #[arg(long = "locale-dir", default_value = "locales")]
+        locale_dir: PathBuf,
+
+        /// Locales to sync besides those with a catalog already (e.g. "en,zh-CN")
+        #[arg(long, value_delimiter = ',')]
+        locales: Vec<String>,
+
+        /// Format of new catalogs: json, yaml, toml, ftl or properties
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// Report without writing; fails when keys are missing
+        #[arg(long)]
+        check: bool,
+
+        /// Add keys to existing JSON, YAML and TOML catalogs by writing them anew, which drops their
+        /// comments, key order and anchors
+        #[arg(long)]
+        rewrite: bool,
+
+        /// Project root that component paths are relative to; defaults to the directory of the nearest
+        /// `hxo.config.toml`, else the working directory
+        #[arg(long)]
+        root: Option<PathBuf>,
+    },
+}
+
+/// Directories never scanned for components.
+const IGNORED_DIRS: [&str; 5] = ["node_modules", "target", "dist", ".git", ".hxo"];
+
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Build { input, output, prod, ssr, hydrate, minify, target, locale } => {
+        Commands::Build {
+            input,
+            output,
+            prod,
+            ssr,
+            hydrate,
+            minify,
+            target,
+            locale,
+            locale_dirs,
+            browsers,
+            load_paths,
+            scope_prefix,
+            scope_length,
+            scope_content_hash,
+            root,
+        } => {
             println!("{} Building project...", style("●").blue());
 
             if input.is_file() {
                 let source = fs::read_to_string(&input)?;
                 let component_name = input.file_stem().unwrap().to_string_lossy();
+                let paths =
+                    project_root(&input, root.as_deref()).and_then(|root| Ok((project_relative_path(&input, &root)?, root)));
+                let (file_path, root) = match paths {
+                    Ok(paths) => paths,
+                    Err(e) => {
+                        eprintln!("{} {}", style("✘").red(), e);
+                        std::process::exit(1);
+                    }
+                };
 
-                let mut compiler = Compiler::new();
+                let tailwind = match TailwindConfig::load(&root) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        eprintln!("{} Invalid hxo.config.toml: {:?}", style("✘").red(), e);
+                        std::process::exit(1);
+                    }
+                };
+                let css_targets = match browsers.as_deref().map(Targets::parse).transpose() {
+                    Ok(targets) => targets,
+                    Err(e) => {
+                        eprintln!("{} Invalid --browsers query: {:?}", style("✘").red(), e);
+                        std::process::exit(1);
+                    }
+                };
+                let mut compiler = Compiler::with_tailwind_config(Arc::new(tailwind))
+                    .with_style_load_paths(load_paths)
+                    .with_locale_dirs(locale_dirs);
                 let options = CompileOptions {
                     ssr,
                     hydrate: if hydrate { true } else { !ssr },
@@ -85,6 +238,10 @@ async fn main() -> Result<()> {
                     is_prod: prod,
                     target,
                     i18n_locale: locale,
+                    file_path: Some(file_path),
+                    root: Some(root),
+                    css_targets,
+                    scope: ScopeIdOptions { prefix: scope_prefix, length: scope_length, hash_content: scope_content_hash },
                     ..Default::default()
                 };
 
@@ -99,6 +256,10 @@ async fn main() -> Result<()> {
                             fs::write(map_file, map.to_json()?)?;
                         }
 
+                        if !result.css.is_empty() {
+                            fs::write(output.join(format!("{}.css", component_name)), &result.css)?;
+                        }
+
                         println!("{} Build complete!", style("✔").green());
                     }
                     Err(e) => {
@@ -123,7 +284,207 @@ async fn main() -> Result<()> {
         Commands::Lsp => {
             run_server().await;
         }
+        Commands::I18n { command: I18nCommands::Extract { input, locale_dir, locales, format, check, rewrite, root } } => {
+            println!("{} Extracting messages...", style("●").blue());
+            let compiler = Compiler::new();
+            let mut files = Vec::new();
+            collect_components(&input, &mut files)?;
+            files.sort();
+            let root = match project_root(&input, root.as_deref()) {
+                Ok(root) => root,
+                Err(e) => {
+                    eprintln!("{} {}", style("✘").red(), e);
+                    std::process::exit(1);
+                }
+            };
+
+            let mut extraction = Extraction::default();
+            for file in &files {
+                let source = fs::read_to_string(file)?;
+                let component_name = file.file_stem().unwrap().to_string_lossy();
+                let file_path = match project_relative_path(file, &root) {
+                    Ok(file_path) => file_path,
+                    Err(e) => {
+                        eprintln!("{} {}", style("✘").red(), e);
+                        std::process::exit(1);
+                    }
+                };
+                if let Err(e) = compiler.extract_i18n(&component_name, &source, &file_path, &root, &mut extraction) {
+                    eprintln!("{} {}: {:?}", style("✘").red(), file_path, e);
+                    std::process::exit(1);
+                }
+            }
+            println!("{} {} keys in {} components", style("●").blue(), extraction.keys.len(), files.len());
+
+            let catalogs = match locale_catalogs(&locale_dir, &locales, &format) {
+                Ok(catalogs) => catalogs,
+                Err(e) => {
+                    eprintln!("{} {}", style("✘").red(), e);
+                    std::process::exit(1);
+                }
+            };
+            let mut missing = 0;
+            for (locale, path) in catalogs {
+                match compiler.sync_catalog(&extraction, &path, &locale, !check, rewrite) {
+                    Ok(sync) => {
+                        missing += sync.added.len() + sync.skipped.len();
+                        report_catalog(&sync, check);
+                    }
+                    Err(e) => {
+                        eprintln!("{} {}: {:?}", style("✘").red(), path.display(), e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if check && missing > 0 {
+                eprintln!("{} {} missing messages", style("✘").red(), missing);
+                std::process::exit(1);
+            }
+        }
+        Commands::Router { command: RouterCommands::Build { input, mode, base, output, check } } => {
+            println!("{} Building routes...", style("●").blue());
+            let loaded = if input.is_dir() { Compiler::new().scan_pages(&input) } else { router::load_router(&input) };
+            let mut config = match loaded {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("{} {}: {:?}", style("✘").red(), input.display(), e);
+                    std::process::exit(1);
+                }
+            };
+            config.mode = mode.unwrap_or(config.mode);
+            config.base = base.or(config.base);
+            let base_dir = input.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            let problems = router::validate_routes(&config, base_dir);
+            if !problems.is_empty() {
+                for problem in &problems {
+                    eprintln!("{} {} {}: {}", style("✘").red(), input.display(), problem.route, problem.message);
+                }
+                std::process::exit(1);
+            }
+            if !check {
+                let source_name = input.file_name().unwrap_or_default().to_string_lossy();
+                fs::create_dir_all(&output)?;
+                if let Err(e) = router::relocate_components(&mut config, base_dir, &output) {
+                    eprintln!("{} {}: {:?}", style("✘").red(), output.display(), e);
+                    std::process::exit(1);
+                }
+                fs::write(output.join("routes.js"), router::route_table(&config, &source_name))?;
+                fs::write(output.join("routes.d.ts"), router::route_types(&config, &source_name))?;
+            }
+            println!("{} Routes OK!", style("✔").green());
+        }
     }
 
     Ok(())
 }
+
+/// The `.hxo` files under `input`, or `input` itself when it is a file.
+fn collect_components(input: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if input.is_file() {
+        files.push(input.to_path_buf());
+        return Ok(());
+    }
+    for entry in fs::read_dir(input)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if !IGNORED_DIRS.contains(&name.as_ref()) {
+                collect_components(&path, files)?;
+            }
+        }
+        else if path.extension().is_some_and(|extension| extension == "hxo") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The catalog of each locale: the first file in `dir` named after it, or a new `<locale>.<format>`
+/// for requested locales without one.
+fn locale_catalogs(dir: &Path, locales: &[String], format: &str) -> std::result::Result<Vec<(String, PathBuf)>, String> {
+    let mut catalogs: Vec<(String, PathBuf)> = Vec::new();
+    if dir.is_dir() {
+        let mut paths: Vec<PathBuf> =
+            fs::read_dir(dir).map_err(|e| e.to_string())?.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+        paths.sort();
+        for path in paths {
+            let known = path.extension().and_then(|extension| lang_for_extension(&extension.to_string_lossy()));
+            let (Some(_), Some(locale)) = (known, path.file_stem())
+            else {
+                continue;
+            };
+            let locale = locale.to_string_lossy().into_owned();
+            if !catalogs.iter().any(|(existing, _)| *existing == locale) {
+                catalogs.push((locale, path));
+            }
+        }
+    }
+    if lang_for_extension(format).is_none() {
+        return Err(format!("unknown catalog format `{}`; use json, yaml, toml, ftl or properties", format));
+    }
+    for locale in locales {
+        if !catalogs.iter().any(|(existing, _)| existing == locale) {
+            catalogs.push((locale.clone(), dir.join(format!("{}.{}", locale, format))));
+        }
+    }
+    if catalogs.is_empty() {
+        return Err(format!("no catalogs in {}; name the locales with --locales", dir.display()));
+    }
+    Ok(catalogs)
+}
+
+fn report_catalog(sync: &CatalogSync, check: bool) {
+    let verb = if check { "missing" } else { "added" };
+    println!(
+        "{} {} ({}): {} {}, {} unused, {} untranslated",
+        style("✔").green(),
+        sync.path.display(),
+        sync.locale,
+        sync.added.len(),
+        verb,
+        sync.unused.len(),
+        sync.untranslated.len()
+    );
+    for key in &sync.added {
+        println!("    {} {}", style("+").green(), key);
+    }
+    for (key, reason) in &sync.skipped {
+        println!("    {} {}: {}", style("!").yellow(), key, reason);
+    }
+    for key in &sync.unused {
+        println!("    {} {} (unused)", style("-").dim(), key);
+    }
+}
+
+/// The project root: `root` when given, otherwise the nearest directory above `input` that holds an
+/// `hxo.config.toml`. Without one, the working directory, or the directory of `input` when it lies
+/// outside of it.
+fn project_root(input: &Path, root: Option<&Path>) -> std::result::Result<PathBuf, String> {
+    if let Some(root) = root {
+        return fs::canonicalize(root).map_err(|e| format!("invalid --root {}: {}", root.display(), e));
+    }
+    let absolute = fs::canonicalize(input).map_err(|e| format!("{}: {}", input.display(), e))?;
+    if let Some(dir) = absolute.ancestors().find(|dir| dir.join("hxo.config.toml").is_file()) {
+        return Ok(dir.to_path_buf());
+    }
+    let current = std::env::current_dir().and_then(fs::canonicalize).map_err(|e| e.to_string())?;
+    if absolute.starts_with(&current) {
+        Ok(current)
+    }
+    else if absolute.is_dir() {
+        Ok(absolute)
+    }
+    else {
+        Ok(absolute.parent().unwrap_or(&absolute).to_path_buf())
+    }
+}
+
+/// Path of `input` relative to the project root, with `/` separators, so scope ids do not depend on
+/// where the project is checked out or which directory `hxo` runs in.
+fn project_relative_path(input: &Path, root: &Path) -> std::result::Result<String, String> {
+    let absolute = fs::canonicalize(input).map_err(|e| format!("{}: {}", input.display(), e))?;
+    let relative = absolute
+        .strip_prefix(root)
+        .map_err(|_| format!("{} is outside the project root {}", input.display(), root.display()))?;
+    Ok(relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"))
+}
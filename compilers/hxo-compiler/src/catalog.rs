@@ -0,0 +1,220 @@
+//! Message catalog sync behind `hxo i18n extract`: the `$t` keys of every component are compared with
+//! each locale catalog, missing keys are written with their key as placeholder text, and catalog keys
+//! that no component uses are reported. Keys built at runtime (`$t(name)`) cannot be seen, so they
+//! show up as unused.
+
+use crate::Compiler;
+use hxo_ir::I18nMessage;
+use hxo_optimizer::Optimizer;
+use hxo_parser::{Parser, i18n};
+use hxo_types::{Error, HxoValue, Result, Span};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub use hxo_parser::i18n::lang_for_extension;
+
+/// Where a component calls `$t` with a key.
+#[derive(Debug, Clone)]
+pub struct KeyUse {
+    pub file: String,
+    pub span: Span,
+    /// Locales for which the component's own `<i18n>` defines the key.
+    pub local_locales: Vec<String>,
+}
+
+/// The `$t` keys of a project, sorted, with their uses.
+#[derive(Debug, Clone, Default)]
+pub struct Extraction {
+    pub keys: BTreeMap<String, Vec<KeyUse>>,
+}
+
+impl Extraction {
+    /// Whether some use of `key` reads its `locale` message from the catalogs.
+    pub fn needs(&self, key: &str, locale: &str) -> bool {
+        self.keys.get(key).is_some_and(|uses| uses.iter().any(|usage| !usage.local_locales.iter().any(|l| l == locale)))
+    }
+}
+
+/// What syncing one catalog found, and did unless it was a dry run.
+#[derive(Debug, Clone)]
+pub struct CatalogSync {
+    pub path: PathBuf,
+    pub locale: String,
+    /// Keys the components need that the catalog lacked, written with placeholders.
+    pub added: Vec<String>,
+    /// Catalog keys that no component uses.
+    pub unused: Vec<String>,
+    /// Catalog keys whose message is still the placeholder, added ones included.
+    pub untranslated: Vec<String>,
+    /// Missing keys left out, because the format cannot hold them where they belong or adding them
+    /// needs a rewrite, with the reason.
+    pub skipped: Vec<(String, String)>,
+}
+
+impl Compiler {
+    /// Adds the `$t` keys of the component at `file_path`, relative to the project `root`, to `extraction`.
+    pub fn extract_i18n(
+        &self,
+        name: &str,
+        source: &str,
+        file_path: &str,
+        root: &Path,
+        extraction: &mut Extraction,
+    ) -> Result<()> {
+        let mut parser = Parser::new(name.to_string(), source, self.registry.clone());
+        if let Some(dir) = Path::new(file_path).parent() {
+            parser = parser.with_base_dir(root.join(dir));
+        }
+        let ir = parser.parse_all()?;
+        for (key, span) in Optimizer::collect_i18n_keys(&ir) {
+            let local_locales = ir
+                .i18n
+                .iter()
+                .flatten()
+                .filter(|(_, messages)| messages.contains_key(&key))
+                .map(|(locale, _)| locale.clone())
+                .collect();
+            extraction.keys.entry(key).or_default().push(KeyUse { file: file_path.to_string(), span, local_locales });
+        }
+        Ok(())
+    }
+
+    /// Syncs the `locale` catalog at `path` with `extraction`, creating it when missing. `.ftl` and
+    /// `.properties` entries are appended, keeping comments. JSON, YAML and TOML catalogs can only be
+    /// serialized whole, with the keys nested by their dots, which drops comments, key order and anchors,
+    /// so an existing one is only rewritten when `rewrite`; otherwise its missing keys are skipped.
+    /// Nothing is written unless `write`.
+    pub fn sync_catalog(
+        &self,
+        extraction: &Extraction,
+        path: &Path,
+        locale: &str,
+        write: bool,
+        rewrite: bool,
+    ) -> Result<CatalogSync> {
+        let span = Span::default();
+        let extension = path.extension().map(|extension| extension.to_string_lossy().into_owned()).unwrap_or_default();
+        let serializer =
+            i18n::lang_for_extension(&extension).and_then(|lang| Some((lang, self.registry.get_metadata_serializer(lang)?)));
+        let Some((lang, serializer)) = serializer
+        else {
+            return Err(Error::unsupported_i18n_format(extension, span));
+        };
+        let source = if path.exists() { fs::read_to_string(path)? } else { String::new() };
+        let mut existing =
+            if source.trim().is_empty() { HashMap::new() } else { i18n::parse_catalog(&self.registry, &source, lang)? };
+
+        let missing: Vec<&String> =
+            extraction.keys.keys().filter(|key| !existing.contains_key(*key) && extraction.needs(key, locale)).collect();
+        let mut unused: Vec<String> = existing.keys().filter(|key| !extraction.keys.contains_key(*key)).cloned().collect();
+        unused.sort();
+        let mut added = Vec::new();
+        let mut skipped = Vec::new();
+
+        let text = if serializer.appends() {
+            // Appended per top-level name, so that one entry the format cannot hold skips only its group
+            let mut groups: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+            for key in &missing {
+                groups.entry(key.split('.').next().unwrap_or_default()).or_default().push(key);
+            }
+            let mut text = source.clone();
+            for keys in groups.into_values() {
+                let mut table = HxoValue::Object(HashMap::new());
+                for key in &keys {
+                    insert(&mut table, key).map_err(|reason| Error::invalid_i18n(reason, span))?;
+                }
+                let appended = serializer.serialize(&table, lang).and_then(|entries| {
+                    let mut candidate = text.clone();
+                    if !candidate.is_empty() && !candidate.ends_with('\n') {
+                        candidate.push('\n');
+                    }
+                    candidate.push_str(&entries);
+                    // The file must read back with every earlier message intact and the new keys present
+                    let messages = i18n::parse_catalog(&self.registry, &candidate, lang)?;
+                    let intact = existing.iter().all(|(key, message)| messages.get(key) == Some(message));
+                    if !intact || !keys.iter().all(|key| messages.contains_key(*key)) {
+                        let reason = format!(
+                            "{}: entries under `{}` exist; add it by hand",
+                            lang,
+                            keys[0].split('.').next().unwrap_or_default()
+                        );
+                        return Err(Error::invalid_i18n(reason, span));
+                    }
+                    Ok((candidate, messages))
+                });
+                match appended {
+                    Ok((candidate, messages)) => {
+                        text = candidate;
+                        existing = messages;
+                        added.extend(keys.into_iter().cloned());
+                    }
+                    Err(err) => skipped.extend(keys.into_iter().map(|key| (key.clone(), err.to_string()))),
+                }
+            }
+            text
+        }
+        else if !source.trim().is_empty() && !rewrite {
+            let reason =
+                format!("{}: adding it rewrites the whole catalog, dropping comments and key order; pass --rewrite", lang);
+            skipped.extend(missing.into_iter().map(|key| (key.clone(), reason.clone())));
+            source.clone()
+        }
+        else {
+            let mut table = if source.trim().is_empty() {
+                HxoValue::Object(HashMap::new())
+            }
+            else {
+                HxoValue::Object(i18n::parse_i18n(&self.registry, &source, lang)?)
+            };
+            for key in missing {
+                match insert(&mut table, key) {
+                    Ok(()) => added.push(key.clone()),
+                    Err(reason) => skipped.push((key.clone(), reason)),
+                }
+            }
+            serializer.serialize(&table, lang)?
+        };
+
+        let mut untranslated: Vec<String> =
+            existing.iter().filter(|(key, message)| is_placeholder(key, message)).map(|(key, _)| key.clone()).collect();
+        untranslated.extend(added.iter().filter(|key| !existing.contains_key(*key)).cloned());
+        untranslated.sort();
+
+        if write && !added.is_empty() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(path, text)?;
+        }
+        Ok(CatalogSync { path: path.to_path_buf(), locale: locale.to_string(), added, unused, untranslated, skipped })
+    }
+}
+
+/// Sets `key`, split at its dots into nested tables, to the key itself.
+fn insert(table: &mut HxoValue, key: &str) -> std::result::Result<(), String> {
+    let mut current = table;
+    let mut path = Vec::new();
+    for name in key.split('.') {
+        let HxoValue::Object(map) = current
+        else {
+            return Err(format!("`{}` is a message, not a table", path.join(".")));
+        };
+        path.push(name);
+        current = map.entry(name.to_string()).or_insert_with(|| HxoValue::Object(HashMap::new()));
+    }
+    match current {
+        HxoValue::Object(map) if map.is_empty() => {
+            *current = HxoValue::String(key.to_string());
+            Ok(())
+        }
+        _ => Err(format!("`{}` is a table of messages", key)),
+    }
+}
+
+/// Whether `message` is still the placeholder it was created with.
+fn is_placeholder(key: &str, message: &I18nMessage) -> bool {
+    message.as_text() == Some(key)
+}
@@ -1,10 +1,20 @@
-use hxo_optimizer::Optimizer;
-use hxo_parser::{Parser, ParserRegistry};
+use hxo_optimizer::{Optimizer, scope_id};
+use hxo_parser::{Parser, ParserRegistry, i18n};
 use hxo_source_map::SourceMap;
+use hxo_style_processor::{FsImportResolver, StyleProcessor};
 use hxo_types::Result;
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
+pub mod catalog;
 pub mod codegen;
+pub mod router;
+
+pub use hxo_optimizer::ScopeIdOptions;
+pub use hxo_parser_tailwind::{Layer, SharedStyleEngine, StyleEngine, TailwindConfig};
+pub use hxo_style_processor::Targets;
 
 use crate::codegen::JsBackend;
 use hxo_hydrate::HydrateBackend;
@@ -19,6 +29,13 @@ pub struct CompileResult {
 pub struct Compiler {
     pub registry: Arc<ParserRegistry>,
     pub last_css: String,
+    /// Theme used by `<style lang="tailwind">`, `class` extraction and `addStyle`.
+    pub tailwind: Arc<TailwindConfig>,
+    /// Project-wide utility stylesheet. When set, utility rules are merged into it instead of being
+    /// emitted with each component, and `css` only carries the component's own `<style>` blocks.
+    pub shared_styles: Option<SharedStyleEngine>,
+    /// Project locale directories, read on each compile and overridden by the component's `<i18n>`.
+    pub locale_dirs: Vec<PathBuf>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -29,7 +46,17 @@ pub struct CompileOptions {
     pub is_prod: bool,
     pub target: Option<String>,
     pub scope_id: Option<String>,
+    /// Path of the component relative to [`root`](Self::root), with `/` separators. Scope ids and CSS
+    /// Module names are derived from it; without it they fall back to the component name, so that two
+    /// components of the same name in different directories get the same scope id.
+    pub file_path: Option<String>,
+    /// Project root that `file_path` is relative to; `<i18n src>` files and style `@import`s are
+    /// resolved against it. Defaults to the directory the compiler runs in.
+    pub root: Option<PathBuf>,
+    pub scope: ScopeIdOptions,
     pub i18n_locale: Option<String>,
+    /// Browsers the CSS must work in; prefixes and lowering of nesting and colors follow them.
+    pub css_targets: Option<Targets>,
 }
 
 impl Default for Compiler {
@@ -40,6 +67,12 @@ impl Default for Compiler {
 
 impl Compiler {
     pub fn new() -> Self {
+        Self::with_tailwind_config(Arc::new(TailwindConfig::new()))
+    }
+
+    /// A compiler that resolves Tailwind utilities against `tailwind`, usually loaded with
+    /// [`TailwindConfig::load`] from the project's `hxo.config.toml`.
+    pub fn with_tailwind_config(tailwind: Arc<TailwindConfig>) -> Self {
         let mut registry = ParserRegistry::new();
 
         // Register default parsers
@@ -57,64 +90,121 @@ impl Compiler {
 
         let yaml_parser = Arc::new(hxo_parser_yaml::YamlParser);
         registry.register_metadata_parser("yaml", yaml_parser.clone());
-        registry.register_metadata_parser("yml", yaml_parser);
+        registry.register_metadata_parser("yml", yaml_parser.clone());
+        registry.register_metadata_serializer("yaml", yaml_parser.clone());
+        registry.register_metadata_serializer("yml", yaml_parser);
 
         let json_parser = Arc::new(hxo_parser_json::JsonParser);
-        registry.register_metadata_parser("json", json_parser);
+        registry.register_metadata_parser("json", json_parser.clone());
+        registry.register_metadata_serializer("json", json_parser);
 
         let toml_parser = Arc::new(hxo_parser_toml::TomlParser);
-        registry.register_metadata_parser("toml", toml_parser);
+        registry.register_metadata_parser("toml", toml_parser.clone());
+        registry.register_metadata_serializer("toml", toml_parser);
 
         let props_parser = Arc::new(hxo_parser_properties::PropertiesParser);
-        registry.register_metadata_parser("properties", props_parser);
+        registry.register_metadata_parser("properties", props_parser.clone());
+        registry.register_metadata_serializer("properties", props_parser);
 
         let fluent_parser = Arc::new(hxo_parser_fluent::FluentParser);
         registry.register_metadata_parser("fluent", fluent_parser.clone());
-        registry.register_metadata_parser("ftl", fluent_parser);
+        registry.register_metadata_parser("ftl", fluent_parser.clone());
+        registry.register_metadata_serializer("fluent", fluent_parser.clone());
+        registry.register_metadata_serializer("ftl", fluent_parser);
 
         let css_parser = Arc::new(hxo_parser_css::CssParser);
         registry.register_style_parser("css", css_parser);
 
-        let scss_parser = Arc::new(hxo_parser_scss::ScssParser);
+        let scss_parser = Arc::new(hxo_parser_scss::ScssParser::new());
         registry.register_style_parser("scss", scss_parser);
 
-        let sass_parser = Arc::new(hxo_parser_sass::SassParser);
+        let sass_parser = Arc::new(hxo_parser_sass::SassParser::new());
         registry.register_style_parser("sass", sass_parser);
 
-        let less_parser = Arc::new(hxo_parser_less::LessParser);
+        let less_parser = Arc::new(hxo_parser_less::LessParser::new());
         registry.register_style_parser("less", less_parser);
 
-        let stylus_parser = Arc::new(hxo_parser_stylus::StylusParser);
+        let stylus_parser = Arc::new(hxo_parser_stylus::StylusParser::new());
         registry.register_style_parser("stylus", stylus_parser);
 
-        let tailwind_parser = Arc::new(hxo_parser_tailwind::TailwindParser);
+        let tailwind_parser = Arc::new(hxo_parser_tailwind::TailwindParser::with_config(tailwind.clone()));
         registry.register_style_parser("tailwind", tailwind_parser);
 
-        Self { registry: Arc::new(registry), last_css: String::new() }
+        Self { registry: Arc::new(registry), last_css: String::new(), tailwind, shared_styles: None, locale_dirs: Vec::new() }
+    }
+
+    /// Routes utility rules into `styles`, shared with the other compilers of the project.
+    pub fn with_shared_styles(mut self, styles: SharedStyleEngine) -> Self {
+        self.shared_styles = Some(styles);
+        self
+    }
+
+    /// Resolves SCSS and Sass `@use`, `@forward` and `@import`, and Less and Stylus `@import`, against
+    /// `load_paths`, after the directory the compiler runs in.
+    pub fn with_style_load_paths(mut self, load_paths: Vec<PathBuf>) -> Self {
+        let scss = hxo_parser_scss::ScssParserOptions { load_paths: load_paths.clone(), ..Default::default() };
+        let sass = hxo_parser_sass::SassParserOptions { load_paths: load_paths.clone(), ..Default::default() };
+        let less = hxo_parser_less::LessParserOptions { load_paths: load_paths.clone(), ..Default::default() };
+        let stylus = hxo_parser_stylus::StylusParserOptions { load_paths, ..Default::default() };
+        let registry = Arc::make_mut(&mut self.registry);
+        registry.register_style_parser("scss", Arc::new(hxo_parser_scss::ScssParser::with_options(scss)));
+        registry.register_style_parser("sass", Arc::new(hxo_parser_sass::SassParser::with_options(sass)));
+        registry.register_style_parser("less", Arc::new(hxo_parser_less::LessParser::with_options(less)));
+        registry.register_style_parser("stylus", Arc::new(hxo_parser_stylus::StylusParser::with_options(stylus)));
+        self
+    }
+
+    /// Adds the messages of the locale files in `locale_dirs` (`en.json`, `zh-CN.ftl`, ...) to every
+    /// component. Later directories override earlier ones.
+    pub fn with_locale_dirs(mut self, locale_dirs: Vec<PathBuf>) -> Self {
+        self.locale_dirs = locale_dirs;
+        self
     }
 
+    /// Compiles with default options, so scope ids derive from `name` alone; see [`CompileOptions::file_path`].
     pub fn compile(&mut self, name: &str, source: &str) -> Result<CompileResult> {
         self.compile_with_options(name, source, CompileOptions::default())
     }
 
     pub fn compile_with_options(&mut self, name: &str, source: &str, mut options: CompileOptions) -> Result<CompileResult> {
         // 1. Parse source to IR (Now includes Script Analysis inside)
+        let root = options.root.clone().unwrap_or_else(|| PathBuf::from("."));
         let mut parser = Parser::new(name.to_string(), source, self.registry.clone());
+        if let Some(dir) = options.file_path.as_deref().and_then(|path| Path::new(path).parent()) {
+            parser = parser.with_base_dir(root.join(dir));
+        }
         let mut ir = parser.parse_all()?;
+        if !self.locale_dirs.is_empty() {
+            let mut messages = i18n::Messages::new();
+            for dir in &self.locale_dirs {
+                i18n::merge(&mut messages, i18n::load_locale_dir(&self.registry, dir)?);
+            }
+            if let Some(component) = ir.i18n.take() {
+                i18n::merge(&mut messages, component);
+            }
+            ir.i18n = if messages.is_empty() { None } else { Some(messages) };
+        }
 
         // 2. Optimize & Transform IR
-        let mut optimizer = Optimizer::new();
+        let mut optimizer = Optimizer::with_tailwind_config(self.tailwind.clone());
 
         // Handle scope ID in optimizer/transformer
+        let path = options.file_path.clone().unwrap_or_else(|| name.to_string());
         let has_scoped_style = ir.styles.iter().any(|s| s.scoped);
         if has_scoped_style && options.scope_id.is_none() {
-            options.scope_id = Some(optimizer.generate_scope_id(name));
+            options.scope_id = Some(scope_id(&path, Some(source), &options.scope));
         }
 
-        optimizer.optimize(&mut ir, options.i18n_locale.as_deref(), options.is_prod);
+        optimizer.optimize(&mut ir, options.i18n_locale.as_deref(), options.is_prod)?;
+        optimizer.expand_style_directives(&mut ir)?;
 
         if let Some(scope_id) = &options.scope_id {
-            optimizer.apply_scope_id(&mut ir, scope_id);
+            optimizer.apply_scope_id(&mut ir, scope_id)?;
+        }
+
+        if ir.styles.iter().any(|s| s.module.is_some()) {
+            let module_options = ScopeIdOptions { prefix: String::new(), ..options.scope.clone() };
+            optimizer.apply_css_modules(&mut ir, &scope_id(&path, Some(source), &module_options))?;
         }
 
         // 3. Process styles (Tailwind/Utility CSS)
@@ -136,7 +226,26 @@ impl Compiler {
         };
 
         // 5. Get all generated CSS (includes both utility classes and <style> block contents)
-        self.last_css = optimizer.get_css();
+        let css = match &self.shared_styles {
+            Some(shared) => {
+                shared.merge(&optimizer.style_engine);
+                optimizer.style_engine.generate_raw_css()
+            }
+            None => optimizer.get_css(),
+        };
+
+        // 6. Post-process: inline `@import`s relative to the component, prefix, lower and minify
+        self.last_css = if options.minify || options.css_targets.is_some() || css.contains("@import") {
+            let mut processor =
+                StyleProcessor::new().with_targets(options.css_targets.clone().unwrap_or_default()).with_minify(options.minify);
+            if let Some(file_path) = &options.file_path {
+                processor = processor.with_filename(file_path).with_import_resolver(Arc::new(FsImportResolver::new(root)));
+            }
+            processor.process(&css)?
+        }
+        else {
+            css
+        };
 
         Ok(CompileResult { code, css: self.last_css.clone(), source_map })
     }
@@ -0,0 +1,544 @@
+//! Router build step behind `hxo router build`: a `RouterConfig`, read from a router file or scanned
+//! from a `pages/` directory, is validated, then emitted as a route table module that lazy-loads each
+//! component, and as a `.d.ts` that types route names and their params for the navigation helpers of
+//! that module.
+
+use crate::Compiler;
+use hxo_parser::Parser;
+use hxo_target_dts::DtsWriter;
+use hxo_target_js::js_string;
+use hxo_types::{CodeWriter, Error, HxoValue, Position, Result, Route, RouterConfig, Span};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs,
+    path::{Component, Path},
+};
+
+/// Something wrong with a route, found before any code is generated.
+#[derive(Debug, Clone)]
+pub struct RouteProblem {
+    /// Full path of the route, parents included.
+    pub route: String,
+    pub message: String,
+    pub span: Span,
+}
+
+/// A `:name`, `:name?` or `:name*` segment of a route path.
+#[derive(Clone)]
+struct RouteParam {
+    name: String,
+    optional: bool,
+    /// `:name*` matches the rest of the path, one or more segments.
+    rest: bool,
+}
+
+/// A route with its full path and every param up to it.
+struct FlatRoute<'a> {
+    route: &'a Route,
+    path: String,
+    params: Vec<RouteParam>,
+}
+
+/// Reads a router file: `.yaml`/`.yml` or `.json`.
+pub fn load_router(path: &Path) -> Result<RouterConfig> {
+    let source = fs::read_to_string(path)?;
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml" | "yml") => hxo_parser_yaml::parse_router(&source),
+        Some("json") => hxo_parser_json::parse(&source, Position { line: 1, column: 1, offset: 0 }),
+        extension => Err(Error::parse_error(
+            format!("unknown router format `{}`; use yaml or json", extension.unwrap_or_default()),
+            Span::unknown(),
+        )),
+    }
+}
+
+impl Compiler {
+    /// Routes from the `.hxo` files under `pages_dir`: `index.hxo` is its directory's path, `[id]` a
+    /// `:id` param and `[...slug]` a `:slug*` catch-all; a `_layout.hxo` makes its directory a parent
+    /// route of the pages below it, and `(group)` directories add no path segment. Each page's
+    /// `<metadata>` becomes its `meta`. Components are relative to the directory holding `pages_dir`,
+    /// as in a router file next to it.
+    pub fn scan_pages(&self, pages_dir: &Path) -> Result<RouterConfig> {
+        let module = format!("./{}", pages_dir.file_name().unwrap_or_default().to_string_lossy());
+        let layout = pages_dir.join("_layout.hxo");
+        let routes = if layout.is_file() {
+            let component = format!("{}/_layout.hxo", module);
+            let children = self.scan_dir(pages_dir, &module, &[], "", true)?;
+            let meta = self.page_meta(&layout, &component)?;
+            vec![Route {
+                path: "/".to_string(),
+                component,
+                name: None,
+                redirect: None,
+                children: Some(children),
+                meta,
+                span: Span::default(),
+            }]
+        }
+        else {
+            self.scan_dir(pages_dir, &module, &[], "", false)?
+        };
+        Ok(RouterConfig { routes, mode: "history".to_string(), base: None, span: Span::default() })
+    }
+
+    /// The routes of `dir`, whose pages are at `relative` under the closest layout route (`nested`), or
+    /// under the root; `names` are the segments naming its routes.
+    fn scan_dir(&self, dir: &Path, module: &str, names: &[String], relative: &str, nested: bool) -> Result<Vec<Route>> {
+        let mut entries: Vec<_> = fs::read_dir(dir)?.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+        entries.sort();
+        let route_path = |relative: &str| if nested { relative.to_string() } else { format!("/{}", relative) };
+        let mut routes = Vec::new();
+        for entry in entries {
+            let file_name = entry.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let module = format!("{}/{}", module, file_name);
+            if entry.is_dir() {
+                let (relative, names) = match page_segment(&file_name) {
+                    Some((segment, name)) => (join_segment(relative, &segment), [names, &[name]].concat()),
+                    None => (relative.to_string(), names.to_vec()),
+                };
+                if entry.join("_layout.hxo").is_file() {
+                    let layout = format!("{}/_layout.hxo", module);
+                    let children = self.scan_dir(&entry, &module, &names, "", true)?;
+                    let meta = self.page_meta(&entry.join("_layout.hxo"), &layout)?;
+                    routes.push(Route {
+                        path: route_path(&relative),
+                        component: layout,
+                        name: None,
+                        redirect: None,
+                        children: Some(children),
+                        meta,
+                        span: Span::default(),
+                    });
+                }
+                else {
+                    routes.extend(self.scan_dir(&entry, &module, &names, &relative, nested)?);
+                }
+                continue;
+            }
+            let Some(stem) = file_name.strip_suffix(".hxo")
+            else {
+                continue;
+            };
+            // `_layout.hxo` and other `_` files are not pages
+            if stem.starts_with('_') {
+                continue;
+            }
+            let (relative, names) = match page_segment(stem) {
+                Some(_) if stem == "index" => (relative.to_string(), names.to_vec()),
+                Some((segment, name)) => (join_segment(relative, &segment), [names, &[name]].concat()),
+                None => continue,
+            };
+            let name = if names.is_empty() { "index".to_string() } else { names.join("-") };
+            let meta = self.page_meta(&entry, &module)?;
+            routes.push(Route {
+                path: route_path(&relative),
+                component: module,
+                name: Some(name),
+                redirect: None,
+                children: None,
+                meta,
+                span: Span::default(),
+            });
+        }
+        routes.sort_by(|a, b| by_specificity(&a.path, &b.path));
+        Ok(routes)
+    }
+
+    fn page_meta(&self, path: &Path, module: &str) -> Result<Option<HxoValue>> {
+        let source = fs::read_to_string(path)?;
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let mut parser = Parser::new(name, &source, self.registry.clone());
+        if let Some(dir) = path.parent() {
+            parser = parser.with_base_dir(dir);
+        }
+        let ir = parser.parse_all().map_err(|e| Error::external_error(module.to_string(), e.to_string(), Span::unknown()))?;
+        Ok(if ir.metadata.is_empty() { None } else { Some(HxoValue::Object(ir.metadata)) })
+    }
+}
+
+/// The path segment and name part of a page file or directory: `[id]` is `:id`, `[...slug]` is
+/// `:slug*`, `(group)` has none.
+fn page_segment(name: &str) -> Option<(String, String)> {
+    if name.starts_with('(') && name.ends_with(')') {
+        return None;
+    }
+    match name.strip_prefix('[').and_then(|name| name.strip_suffix(']')) {
+        Some(param) => match param.strip_prefix("...") {
+            Some(rest) => Some((format!(":{}*", rest), rest.to_string())),
+            None => Some((format!(":{}", param), param.to_string())),
+        },
+        None => Some((name.to_string(), name.to_string())),
+    }
+}
+
+fn join_segment(relative: &str, segment: &str) -> String {
+    if relative.is_empty() { segment.to_string() } else { format!("{}/{}", relative, segment) }
+}
+
+/// Static segments before params, and params before catch-alls, so the first match is the most specific.
+fn by_specificity(a: &str, b: &str) -> Ordering {
+    let rank = |path: &str| -> Vec<u8> {
+        path.split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(param) if param.ends_with('*') => 3,
+                Some(param) if param.ends_with('?') => 2,
+                Some(_) => 1,
+                None => 0,
+            })
+            .collect()
+    };
+    rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+}
+
+/// Checks the mode, duplicate names, duplicate or ambiguous paths, params, and that every `component`
+/// resolves to a file relative to `base_dir`, the router file's directory.
+pub fn validate_routes(config: &RouterConfig, base_dir: &Path) -> Vec<RouteProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |route: &str, message: String, span: Span| {
+        problems.push(RouteProblem { route: route.to_string(), message, span });
+    };
+    if !matches!(config.mode.as_str(), "hash" | "history") {
+        problem("", format!("unknown mode `{}`; use hash or history", config.mode), config.span);
+    }
+
+    let mut routes = Vec::new();
+    flatten(&config.routes, "", &[], &mut routes);
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    // Paths by their shape, with every param written as `:`, so `/user/:id` and `/user/:name` collide
+    let mut shapes: HashMap<String, &str> = HashMap::new();
+    for flat in &routes {
+        let route = flat.route;
+        let path = flat.path.as_str();
+        if let Some(name) = &route.name {
+            match names.get(name.as_str()) {
+                Some(other) => problem(path, format!("route name `{}` is already used by `{}`", name, other), route.span),
+                None => {
+                    names.insert(name, path);
+                }
+            }
+        }
+        // A layout route shares its path with its index child
+        if route.children.is_none() {
+            match shapes.get(&shape(path)) {
+                Some(other) if *other == path => problem(path, "duplicate path".to_string(), route.span),
+                Some(other) => problem(path, format!("conflicts with `{}`; both match the same URLs", other), route.span),
+                None => {
+                    shapes.insert(shape(path), path);
+                }
+            }
+        }
+        for (index, param) in flat.params.iter().enumerate() {
+            if !is_identifier(&param.name) {
+                problem(path, format!("param `:{}` is not a valid name", param.name), route.span);
+            }
+            else if flat.params[..index].iter().any(|earlier| earlier.name == param.name) {
+                problem(path, format!("param `:{}` appears twice", param.name), route.span);
+            }
+        }
+        if route.component.is_empty() {
+            if route.redirect.is_none() && route.children.is_none() {
+                problem(path, "needs a `component`, a `redirect` or `children`".to_string(), route.span);
+            }
+        }
+        else if resolve_component(base_dir, &route.component).is_none() {
+            problem(path, format!("component `{}` does not exist", route.component), route.span);
+        }
+    }
+    problems
+}
+
+/// Rewrites the component paths of `config`, relative to `from` (the router file's directory), to be
+/// relative to `to`, the directory the route table is written to, so its `import()`s resolve from there.
+/// Both directories must exist.
+pub fn relocate_components(config: &mut RouterConfig, from: &Path, to: &Path) -> Result<()> {
+    fn relocate(routes: &mut [Route], from: &Path, to: &Path) {
+        for route in routes {
+            if !route.component.is_empty() {
+                route.component = relative_module(to, &from.join(&route.component));
+            }
+            if let Some(children) = &mut route.children {
+                relocate(children, from, to);
+            }
+        }
+    }
+
+    relocate(&mut config.routes, &fs::canonicalize(from)?, &fs::canonicalize(to)?);
+    Ok(())
+}
+
+/// `target` as a `./` or `../` specifier from `dir`, both absolute.
+fn relative_module(dir: &Path, target: &Path) -> String {
+    let mut target_parts: Vec<Component> = Vec::new();
+    for component in target.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                target_parts.pop();
+            }
+            component => target_parts.push(component),
+        }
+    }
+    let dir_parts: Vec<Component> = dir.components().collect();
+    let common = dir_parts.iter().zip(&target_parts).take_while(|(a, b)| a == b).count();
+    let mut parts = vec![".."; dir_parts.len() - common];
+    if parts.is_empty() {
+        parts.push(".");
+    }
+    let rest = target_parts[common..].iter().map(|part| part.as_os_str().to_string_lossy());
+    parts.into_iter().map(str::to_string).chain(rest.map(|part| part.into_owned())).collect::<Vec<_>>().join("/")
+}
+
+/// The route table module: `routes` with lazy `import()`s of the compiled components, `mode`, `base`,
+/// and the `routePath`, `routeHref`, `navigate` and `replace` helpers over the named routes.
+pub fn route_table(config: &RouterConfig, source_name: &str) -> String {
+    let mut writer = CodeWriter::new();
+    writer.write_line(&format!("// Generated by hxo from {}; do not edit.", source_name));
+    writer.write_line(&format!("export const mode = {};", js_string(&config.mode)));
+    writer.write_line(&format!("export const base = {};", js_string(config.base.as_deref().unwrap_or("/"))));
+    writer.newline();
+    writer.write("export const routes = ");
+    write_records(&mut writer, &config.routes);
+    writer.write_line(";");
+    writer.newline();
+
+    let mut routes = Vec::new();
+    flatten(&config.routes, "", &[], &mut routes);
+    writer.write_line("const paths = {");
+    writer.indent();
+    for flat in &routes {
+        if let Some(name) = &flat.route.name {
+            writer.write_line(&format!("{}: {},", js_string(name), js_string(&flat.path)));
+        }
+    }
+    writer.dedent();
+    writer.write_line("};");
+    writer.newline();
+    writer.write_line(HELPERS);
+    writer.finish().0
+}
+
+const HELPERS: &str = r##"export function routePath(name, params = {}) {
+  const pattern = paths[name];
+  if (pattern === undefined) throw new Error(`Unknown route "${name}"`);
+  const path = pattern.replace(/\/:(\w+)([?*])?/g, (_, key, modifier) => {
+    const value = params[key];
+    if (value === undefined || value === null) {
+      if (modifier === "?") return "";
+      throw new Error(`Route "${name}" needs the "${key}" param`);
+    }
+    if (modifier === "*") {
+      const segments = Array.isArray(value) ? value : String(value).split("/");
+      return "/" + segments.map((segment) => encodeURIComponent(String(segment))).join("/");
+    }
+    return "/" + encodeURIComponent(String(value));
+  });
+  return path || "/";
+}
+
+export function routeHref(name, params) {
+  const path = routePath(name, params);
+  return mode === "hash" ? "#" + path : base.replace(/\/$/, "") + path;
+}
+
+function go(name, params, replace) {
+  const href = routeHref(name, params);
+  if (mode === "hash") {
+    if (replace) location.replace(href);
+    else location.hash = href.slice(1);
+    return;
+  }
+  history[replace ? "replaceState" : "pushState"](null, "", href);
+  dispatchEvent(new PopStateEvent("popstate"));
+}
+
+export function navigate(name, params) {
+  go(name, params, false);
+}
+
+export function replace(name, params) {
+  go(name, params, true);
+}"##;
+
+/// Types for the route table module: `RouteName`, `RouteParams` per name, and typed helpers that
+/// require params exactly when the route has required ones.
+pub fn route_types(config: &RouterConfig, source_name: &str) -> String {
+    let mut routes = Vec::new();
+    flatten(&config.routes, "", &[], &mut routes);
+    let named: Vec<&FlatRoute> = routes.iter().filter(|flat| flat.route.name.is_some()).collect();
+
+    let mut writer = DtsWriter::new();
+    writer.write_line(&format!("// Generated by hxo from {}; do not edit.", source_name));
+    let names: Vec<String> = named.iter().filter_map(|flat| flat.route.name.as_deref()).map(js_string).collect();
+    writer.write_type_alias("RouteName", &if names.is_empty() { "never".to_string() } else { names.join(" | ") });
+    writer.newline();
+    writer.write_interface("RouteParams", |writer| {
+        for flat in &named {
+            let fields: Vec<String> = flat
+                .params
+                .iter()
+                .map(|param| {
+                    let value = if param.rest { "string | (string | number)[]" } else { "string | number" };
+                    format!("{}{}: {}", param.name, if param.optional { "?" } else { "" }, value)
+                })
+                .collect();
+            let params =
+                if fields.is_empty() { "Record<string, never>".to_string() } else { format!("{{ {} }}", fields.join("; ")) };
+            writer.write_line(&format!("{}: {};", js_string(flat.route.name.as_deref().unwrap_or_default()), params));
+        }
+    });
+    writer.newline();
+    writer.write_interface("RouteRecord", |writer| {
+        writer.write_line("path: string;");
+        writer.write_line("name?: RouteName;");
+        writer.write_line("component?: () => Promise<unknown>;");
+        writer.write_line("redirect?: string;");
+        writer.write_line("meta?: Record<string, unknown>;");
+        writer.write_line("children?: RouteRecord[];");
+    });
+    writer.newline();
+    writer.write_line("type ParamArgs<N extends RouteName> = {} extends RouteParams[N] ? [params?: RouteParams[N]] : [params: RouteParams[N]];");
+    writer.newline();
+    writer.write_line(&format!("export declare const mode: {};", js_string(&config.mode)));
+    writer.write_line("export declare const base: string;");
+    writer.write_line("export declare const routes: RouteRecord[];");
+    writer.write_line("export declare function routePath<N extends RouteName>(name: N, ...params: ParamArgs<N>): string;");
+    writer.write_line("export declare function routeHref<N extends RouteName>(name: N, ...params: ParamArgs<N>): string;");
+    writer.write_line("export declare function navigate<N extends RouteName>(name: N, ...params: ParamArgs<N>): void;");
+    writer.write_line("export declare function replace<N extends RouteName>(name: N, ...params: ParamArgs<N>): void;");
+    writer.finish()
+}
+
+fn write_records(writer: &mut CodeWriter, routes: &[Route]) {
+    writer.write("[");
+    writer.newline();
+    writer.indent();
+    for route in routes {
+        writer.write_line("{");
+        writer.indent();
+        writer.write_line(&format!("path: {},", js_string(&route.path)));
+        if let Some(name) = &route.name {
+            writer.write_line(&format!("name: {},", js_string(name)));
+        }
+        if !route.component.is_empty() {
+            writer.write_line(&format!("component: () => import({}),", js_string(&compiled_module(&route.component))));
+        }
+        if let Some(redirect) = &route.redirect {
+            writer.write_line(&format!("redirect: {},", js_string(redirect)));
+        }
+        if let Some(meta) = &route.meta {
+            writer.write_line(&format!("meta: {},", js_value(meta)));
+        }
+        if let Some(children) = &route.children {
+            writer.write("children: ");
+            write_records(writer, children);
+            writer.write_line(",");
+        }
+        writer.dedent();
+        writer.write_line("},");
+    }
+    writer.dedent();
+    writer.write("]");
+}
+
+fn flatten<'a>(routes: &'a [Route], parent: &str, inherited: &[RouteParam], out: &mut Vec<FlatRoute<'a>>) {
+    for route in routes {
+        let path = join_path(parent, &route.path);
+        let mut params = inherited.to_vec();
+        params.extend(params_of(&route.path));
+        out.push(FlatRoute { route, path: path.clone(), params: params.clone() });
+        if let Some(children) = &route.children {
+            flatten(children, &path, &params, out);
+        }
+    }
+}
+
+/// A child path is relative to its parent unless it starts with `/`.
+fn join_path(parent: &str, path: &str) -> String {
+    if path.starts_with('/') {
+        return path.to_string();
+    }
+    let parent = parent.trim_end_matches('/');
+    if path.is_empty() {
+        return if parent.is_empty() { "/".to_string() } else { parent.to_string() };
+    }
+    format!("{}/{}", parent, path)
+}
+
+fn params_of(path: &str) -> Vec<RouteParam> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix(':'))
+        .map(|param| match (param.strip_suffix('?'), param.strip_suffix('*')) {
+            (Some(name), _) => RouteParam { name: name.to_string(), optional: true, rest: false },
+            (_, Some(name)) => RouteParam { name: name.to_string(), optional: false, rest: true },
+            _ => RouteParam { name: param.to_string(), optional: false, rest: false },
+        })
+        .collect()
+}
+
+fn shape(path: &str) -> String {
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(param) if param.ends_with('?') => ":?",
+            Some(param) if param.ends_with('*') => ":*",
+            Some(_) => ":",
+            None => segment,
+        })
+        .collect();
+    format!("/{}", segments.join("/"))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The component file, with `.hxo` added when the path has no extension.
+fn resolve_component(base_dir: &Path, component: &str) -> Option<std::path::PathBuf> {
+    let path = base_dir.join(component);
+    if path.is_file() {
+        return Some(path);
+    }
+    let with_extension = path.with_extension("hxo");
+    (path.extension().is_none() && with_extension.is_file()).then_some(with_extension)
+}
+
+/// The import specifier of a component once compiled: `./views/Home.hxo` becomes `./views/Home.js`.
+fn compiled_module(component: &str) -> String {
+    let module = if component.starts_with("./") || component.starts_with("../") {
+        component.to_string()
+    }
+    else {
+        format!("./{}", component)
+    };
+    match module.strip_suffix(".hxo") {
+        Some(stem) => format!("{}.js", stem),
+        None if Path::new(&module).extension().is_none() => format!("{}.js", module),
+        None => module,
+    }
+}
+
+fn js_value(value: &HxoValue) -> String {
+    match value {
+        HxoValue::Null => "null".to_string(),
+        HxoValue::Bool(flag) => flag.to_string(),
+        HxoValue::Number(number) if number.is_finite() => number.to_string(),
+        HxoValue::Number(number) if number.is_nan() => "NaN".to_string(),
+        HxoValue::Number(number) => if *number > 0.0 { "Infinity" } else { "-Infinity" }.to_string(),
+        HxoValue::Array(items) => format!("[{}]", items.iter().map(js_value).collect::<Vec<_>>().join(", ")),
+        HxoValue::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let entries: Vec<String> =
+                keys.into_iter().map(|key| format!("{}: {}", js_string(key), js_value(&map[key]))).collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+        HxoValue::Binary(bytes) => {
+            format!("new Uint8Array([{}])", bytes.iter().map(|byte| byte.to_string()).collect::<Vec<_>>().join(", "))
+        }
+        HxoValue::String(text) | HxoValue::Signal(text) | HxoValue::Raw(text) | HxoValue::Ref(text) => js_string(text),
+    }
+}
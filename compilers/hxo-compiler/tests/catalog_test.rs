@@ -0,0 +1,47 @@
+mod common;
+
+use common::TempDir;
+use hxo_compiler::{Compiler, catalog::Extraction};
+
+#[test]
+fn test_i18n_extract_syncs_catalogs() {
+    let dir = TempDir::new("extract");
+    std::fs::write(dir.join("en.json"), r#"{ "nav": { "home": "Home" }, "old": "Old" }"#).unwrap();
+    std::fs::write(dir.join("de.ftl"), "# Deutsch\nlogin = Anmelden\n").unwrap();
+    let source = r#"<template><h1 :title="$t('nav.about')">{{ $t('nav.home') }}</h1></template>
+<script>
+const label = $t('login.title') + $t('footer');
+</script>
+<i18n locale="de">{ "footer": "Fußzeile" }</i18n>
+"#;
+    let compiler = Compiler::new();
+    let mut extraction = Extraction::default();
+    compiler.extract_i18n("Page", source, "src/Page.hxo", &dir, &mut extraction).unwrap();
+    assert_eq!(extraction.keys.keys().collect::<Vec<_>>(), ["footer", "login.title", "nav.about", "nav.home"]);
+    assert!(!extraction.needs("footer", "de") && extraction.needs("footer", "en"));
+
+    // Adding keys would rewrite the JSON catalog, so it is left alone unless asked for
+    let en = compiler.sync_catalog(&extraction, &dir.join("en.json"), "en", true, false).unwrap();
+    assert!(en.added.is_empty());
+    assert_eq!(en.skipped.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), ["footer", "login.title", "nav.about"]);
+    assert_eq!(std::fs::read_to_string(dir.join("en.json")).unwrap(), r#"{ "nav": { "home": "Home" }, "old": "Old" }"#);
+
+    let en = compiler.sync_catalog(&extraction, &dir.join("en.json"), "en", true, true).unwrap();
+    assert_eq!(en.added, ["footer", "login.title", "nav.about"]);
+    assert_eq!(en.unused, ["old"]);
+    let expected = "{\n  \"footer\": \"footer\",\n  \"login\": {\n    \"title\": \"login.title\"\n  },\n  \"nav\": {\n    \"about\": \"nav.about\",\n    \"home\": \"Home\"\n  },\n  \"old\": \"Old\"\n}\n";
+    assert_eq!(std::fs::read_to_string(dir.join("en.json")).unwrap(), expected);
+
+    // `login` already is a message, so its attribute cannot be appended
+    let de = compiler.sync_catalog(&extraction, &dir.join("de.ftl"), "de", true, false).unwrap();
+    assert_eq!(de.added, ["nav.about", "nav.home"]);
+    assert_eq!(de.skipped.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>(), ["login.title"]);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("de.ftl")).unwrap(),
+        "# Deutsch\nlogin = Anmelden\nnav =\n    .about = nav.about\n    .home = nav.home\n"
+    );
+
+    let ja = compiler.sync_catalog(&extraction, &dir.join("ja.properties"), "ja", false, false).unwrap();
+    assert_eq!(ja.untranslated.len(), 4);
+    assert!(!dir.join("ja.properties").exists());
+}
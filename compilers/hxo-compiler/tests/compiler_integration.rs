@@ -1,4 +1,8 @@
-use hxo_compiler::Compiler;
+mod common;
+
+use common::TempDir;
+use hxo_compiler::{CompileOptions, Compiler, SharedStyleEngine, Targets, catalog::Extraction};
+use hxo_types::HxoValue;
 
 #[test]
 fn test_compiler_pipeline() {
@@ -9,7 +13,7 @@ const [count, setCount] = createSignal(0);
 function inc() { setCount(count() + 1); }
 </script>
 <template>
-  <div class="p-4 text-blue">
+  <div class="p-4 text-blue-600">
     <span>Count: {{ count }}</span>
     <button @click="inc">+</button>
   </div>
@@ -20,7 +24,7 @@ function inc() { setCount(count() + 1); }
 
     // Check CSS
     assert!(css.contains(".p-4"));
-    assert!(css.contains(".text-blue"));
+    assert!(css.contains(".text-blue-600"));
 
     // Check JS Output (Pure JS as per whitebook.md)
     assert!(js.code.contains("export default {"));
@@ -55,7 +59,7 @@ function increment() {
     println!("Generated JS:\n{}", js.code);
 
     // Check imports and component structure
-    assert!(js.code.contains("import { Fragment, createComputed, createSignal } from '@hxo/core';"));
+    assert!(js.code.contains("import { createComputed, createSignal } from '@hxo/core';"));
     assert!(js.code.contains("import { createTextVNode, h } from '@hxo/dom';"));
     assert!(js.code.contains("name: 'App'"));
     assert!(js.code.contains("setup(props"));
@@ -69,7 +73,7 @@ function increment() {
     // Check render content
     assert!(js.code.contains("h('div', { 'class': 'container' }"));
     assert!(js.code.contains("h('h1', { 'onClick': ctx.increment }"));
-    assert!(js.code.contains("createTextVNode('Count:')"));
+    assert!(js.code.contains("createTextVNode('Count: ')"));
     // Check reactivity (signals should be called as functions)
     assert!(js.code.contains("createTextVNode(ctx.count)"));
     assert!(js.code.contains("h('button', { 'disabled': ctx.isMax }"));
@@ -83,7 +87,7 @@ fn test_compiler_tailwind() {
 addStyle("p-6 m-4");
 </script>
 <template>
-  <div class="p-4 m-2 flex items-center bg-blue text-white rounded-lg shadow-sm">
+  <div class="p-4 m-2 flex items-center bg-blue-500 text-white rounded-lg shadow-sm">
     <span :class="'font-bold text-2xl'">Tailwind Test</span>
     <p :class="['text-center', 'mx-4']">Dynamic array</p>
   </div>
@@ -101,8 +105,8 @@ addStyle("p-6 m-4");
     assert!(css.contains("display: flex;"));
     assert!(css.contains(".items-center"));
     assert!(css.contains("align-items: center;"));
-    assert!(css.contains(".bg-blue"));
-    assert!(css.contains("background-color: #0000ff;"));
+    assert!(css.contains(".bg-blue-500"));
+    assert!(css.contains("background-color: #3b82f6;"));
     assert!(css.contains(".text-white"));
     assert!(css.contains("color: #ffffff;"));
     assert!(css.contains(".rounded-lg"));
@@ -147,3 +151,294 @@ div.container
     assert!(res.code.contains("h('p', { 'id': 'desc', 'class': ctx.activeClass }"));
     assert!(res.code.contains("createTextVNode('This is a pug template')"));
 }
+
+#[test]
+fn test_scope_id_matches_between_ssr_and_client() {
+    let source = "<template>\n  <div class=\"card\">Hi</div>\n</template>\n<style scoped>\n.card { color: red; }\n</style>\n";
+    let options = CompileOptions { file_path: Some("src/pages/Card.hxo".to_string()), ..Default::default() };
+
+    let ssr = Compiler::new().compile_with_options("Card", source, CompileOptions { ssr: true, ..options.clone() }).unwrap();
+    let client = Compiler::new().compile_with_options("Card", source, options).unwrap();
+    let other_dir = Compiler::new()
+        .compile_with_options(
+            "Card",
+            source,
+            CompileOptions { file_path: Some("src/admin/Card.hxo".to_string()), ..Default::default() },
+        )
+        .unwrap();
+
+    let id = hxo_compiler::ScopeIdOptions::default().prefix + "ba93dc00";
+    assert!(ssr.code.contains(&id), "{}", ssr.code);
+    assert!(client.code.contains(&id), "{}", client.code);
+    assert!(!other_dir.code.contains(&id));
+}
+
+#[test]
+fn test_component_files_resolve_against_the_project_root() {
+    // The compiler runs in the crate directory, not in the project
+    let dir = TempDir::new("project-root");
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/messages.json"), r#"{ "en": { "title": "Welcome" } }"#).unwrap();
+    std::fs::write(dir.join("src/theme.css"), ".base { margin: 0; }\n").unwrap();
+    let source = r#"<template><h1 class="title">{{ $t('title') }}</h1></template>
+<style>
+@import "./theme.css";
+.title { color: red; }
+</style>
+<i18n src="./messages.json"></i18n>
+"#;
+    let options =
+        CompileOptions { file_path: Some("src/App.hxo".to_string()), root: Some(dir.to_path_buf()), ..Default::default() };
+    let res = Compiler::new().compile_with_options("App", source, options).unwrap();
+    assert!(res.code.contains("i18n: { 'en': { 'title': 'Welcome' } },"), "{}", res.code);
+    assert!(res.css.contains(".base {\n  margin: 0;\n}"), "{}", res.css);
+    assert!(!res.css.contains("@import"), "{}", res.css);
+
+    let mut extraction = Extraction::default();
+    Compiler::new().extract_i18n("App", source, "src/App.hxo", &dir, &mut extraction).unwrap();
+    assert_eq!(extraction.keys.keys().collect::<Vec<_>>(), ["title"]);
+}
+
+#[test]
+fn test_slotted_selector_matches_slot_content() {
+    let source = "<template>\n  <Card><p class=\"note\">Hi</p></Card>\n</template>\n<style scoped>\n:slotted(.note) { color: red; }\n</style>\n";
+    let options = CompileOptions { file_path: Some("src/Page.hxo".to_string()), ..Default::default() };
+    let res = Compiler::new().compile_with_options("Page", source, options).unwrap();
+
+    let id = hxo_compiler::ScopeIdOptions::default().prefix + &module_hash("src/Page.hxo");
+    assert!(res.css.contains(&format!(".note[{}] {{", id)), "{}", res.css);
+    assert!(res.code.contains(&format!("h('p', {{ 'class': 'note', '{}': true }}", id)), "{}", res.code);
+}
+
+#[test]
+fn test_css_modules() {
+    let source = r#"<template>
+  <div :class="$style.card">Hi</div>
+  <button :class="active" :disabled="busy">Go</button>
+</template>
+<style module>
+.card { color: red; }
+</style>
+"#;
+    let options = CompileOptions { file_path: Some("src/Card.hxo".to_string()), ..Default::default() };
+    let client = Compiler::new().compile_with_options("Card", source, options.clone()).unwrap();
+    let ssr = Compiler::new().compile_with_options("Card", source, CompileOptions { ssr: true, ..options }).unwrap();
+
+    let hash = module_hash("src/Card.hxo");
+    let class_map = format!("{{ 'card': 'card_{}' }}", hash);
+    assert!(client.code.contains(&format!("const $style = {};", class_map)), "{}", client.code);
+    assert!(client.code.contains("return { $style };"));
+    assert!(client.code.contains("'class': ctx.$style.card"));
+    assert!(client.css.contains(&format!(".card_{} {{", hash)), "{}", client.css);
+
+    assert!(ssr.code.contains(&format!("export const $style = {};", class_map)), "{}", ssr.code);
+    assert!(ssr.code.contains("html += '<div data-hxo-id=\"1\" class=\"' + ($style.card) + '\">';"), "{}", ssr.code);
+    // Other bindings are left to the client, as without CSS Modules
+    assert!(ssr.code.contains("html += '<button data-hxo-id=\"4\">';"), "{}", ssr.code);
+}
+
+#[test]
+fn test_tailwind_style_block_components() {
+    let source = r#"<template>
+  <button class="btn">Go</button>
+</template>
+<style lang="tailwind">
+.btn { @apply px-4 hover:bg-blue-600; cursor: pointer; }
+</style>
+"#;
+    let result = Compiler::new().compile("Button", source).unwrap();
+    assert!(
+        result.css.contains(".btn {\n  cursor: pointer;\n  padding-left: 1rem;\n  padding-right: 1rem;\n}"),
+        "{}",
+        result.css
+    );
+    assert!(result.css.contains(".btn:hover {\n  background-color: #2563eb;\n}"));
+}
+
+#[test]
+fn test_shared_utility_stylesheet() {
+    let shared = SharedStyleEngine::default();
+    let mut compiler = Compiler::new().with_shared_styles(shared.clone());
+    let header = compiler
+        .compile("Header", "<template><h1 class=\"p-4 font-bold p-4\">Hi</h1></template>\n<style>h1 { margin: 0; }</style>")
+        .unwrap();
+    let footer = compiler.compile("Footer", "<template><footer class=\"p-4 mt-2\">Bye</footer></template>").unwrap();
+
+    // Utilities move to the shared stylesheet; components keep their own <style> blocks
+    assert!(!header.css.contains(".p-4"));
+    assert!(header.css.contains("margin: 0"));
+    assert!(footer.css.is_empty());
+
+    let css = shared.generate_css();
+    assert_eq!(css.matches(".p-4 {").count(), 1);
+    assert!(css.contains(".font-bold"));
+    assert!(css.contains(".mt-2"));
+}
+
+#[test]
+fn test_tailwind_directives_in_style_blocks() {
+    let source = r#"<template>
+  <button class="btn p-4">Go</button>
+</template>
+<style lang="scss" scoped>
+$radius: 4px;
+@layer components {
+  .btn { @apply px-4 hover:bg-blue-600; border-radius: $radius; }
+}
+@screen md { .btn { color: theme('colors.blue.500 / 50%'); } }
+</style>
+"#;
+    let options = CompileOptions { scope_id: Some("data-v-x".to_string()), ..Default::default() };
+    let css = Compiler::new().compile_with_options("Button", source, options).unwrap().css;
+    assert!(
+        css.contains(".btn[data-v-x] {\n  padding-left: 1rem;\n  padding-right: 1rem;\n  border-radius: 4px;\n}"),
+        "{}",
+        css
+    );
+    assert!(css.contains(".btn[data-v-x]:hover {\n  background-color: #2563eb;\n}"));
+    assert!(css.contains("@media (min-width: 768px) {\n  .btn[data-v-x] {\n    color: rgb(59 130 246 / 50%);\n  }\n}"));
+    // The components layer comes before the utilities
+    assert!(css.find(".btn[data-v-x] {").unwrap() < css.find(".p-4 {").unwrap());
+    assert!(!css.contains("@layer") && !css.contains("@apply"));
+}
+
+#[test]
+fn test_style_errors_point_into_the_component() {
+    let mut compiler = Compiler::new();
+    let source = "<template><div></div></template>\n<style>\n.a {\n  color: theme('colors.nope');\n}\n</style>\n";
+    let Err(err) = compiler.compile("Broken", source)
+    else {
+        panic!("expected an error for the unknown theme path");
+    };
+    assert!(err.to_string().contains("colors.nope"), "{}", err);
+    assert_eq!(err.span().start.line, 4);
+}
+
+#[test]
+fn test_css_targets_and_minify() {
+    let source = r#"
+<template><div class="bg-blue-500/50"></div></template>
+<style>
+.card {
+  color: red;
+  user-select: none;
+  &:hover { color: blue; }
+}
+</style>
+"#;
+    let options = CompileOptions { css_targets: Some(Targets::parse("safari 11").unwrap()), ..Default::default() };
+    let css = Compiler::new().compile_with_options("Card", source, options.clone()).unwrap().css;
+    assert!(css.contains(".card:hover {\n  color: blue;\n}"), "{}", css);
+    assert!(css.contains("background-color: rgba(59, 130, 246, 0.5);"));
+    assert!(css.contains("-webkit-user-select: none;\n  user-select: none;"));
+
+    let minified =
+        Compiler::new().compile_with_options("Card", source, CompileOptions { minify: true, ..options }).unwrap().css;
+    assert!(minified.contains(".card:hover{color:blue;}"), "{}", minified);
+    assert!(!minified.contains('\n'));
+}
+
+#[test]
+fn test_scss_design_tokens_from_load_paths() {
+    let dir = TempDir::new("tokens");
+    std::fs::write(dir.join("_tokens.scss"), "$brand: #0044cc;\n@mixin focus-ring { outline: 2px solid $brand; }\n").unwrap();
+    let source = r#"<template><button class="btn">Go</button></template>
+<style lang="scss">
+@use "tokens";
+.btn { color: tokens.$brand; &:focus { @include tokens.focus-ring; } }
+</style>
+"#;
+    let css = Compiler::new().with_style_load_paths(vec![dir.to_path_buf()]).compile("Button", source).unwrap().css;
+    assert!(css.contains(".btn {\n  color: #0044cc;\n}"), "{}", css);
+    assert!(css.contains(".btn:focus {\n  outline: 2px solid #0044cc;\n}"), "{}", css);
+}
+
+#[test]
+fn test_less_theme_from_load_paths() {
+    let dir = TempDir::new("less-theme");
+    std::fs::write(dir.join("theme.less"), "@brand: #0044cc;\n.focus-ring(@width: 2px) { outline: @width solid @brand; }\n")
+        .unwrap();
+    let source = r#"<template><button class="btn">Go</button></template>
+<style lang="less">
+@import (reference) "theme";
+.btn { color: darken(@brand, 10%); &:focus { .focus-ring(); } }
+</style>
+"#;
+    let css = Compiler::new().with_style_load_paths(vec![dir.to_path_buf()]).compile("Button", source).unwrap().css;
+    assert!(css.contains(".btn {\n  color: #003399;\n}"), "{}", css);
+    assert!(css.contains(".btn:focus {\n  outline: 2px solid #0044cc;\n}"), "{}", css);
+}
+
+#[test]
+fn test_stylus_theme_from_load_paths() {
+    let dir = TempDir::new("stylus-theme");
+    std::fs::write(dir.join("theme.styl"), "brand = #0044cc\nfocus-ring(width = 2px)\n  outline width solid brand\n").unwrap();
+    let source = r#"<template><button class="btn">Go</button></template>
+<style lang="stylus">
+@import theme
+.btn
+  color brand
+  &:focus
+    focus-ring()
+</style>
+"#;
+    let css = Compiler::new().with_style_load_paths(vec![dir.to_path_buf()]).compile("Button", source).unwrap().css;
+    assert!(css.contains(".btn {\n  color: #0044cc;\n}"), "{}", css);
+    assert!(css.contains(".btn:focus {\n  outline: 2px solid #0044cc;\n}"), "{}", css);
+}
+
+fn module_hash(path: &str) -> String {
+    let options = hxo_compiler::ScopeIdOptions { prefix: String::new(), ..Default::default() };
+    hxo_optimizer::scope_id(path, None, &options)
+}
+
+#[test]
+fn test_compile_markdown_template() {
+    let source =
+        "<template lang=\"md\">\n# It's {{ title }}\n\n```js\nconst a = 'x';\n```\n\n<Badge type=\"tip\" />\n</template>\n";
+    let code = Compiler::new().compile("Doc", source).unwrap().code;
+    assert!(code.contains("h('h1', { 'id': 'its' }"), "{}", code);
+    assert!(code.contains("createTextVNode('It\\'s ')"), "{}", code);
+    assert!(code.contains("h('code', { 'class': 'language-js' }"), "{}", code);
+    assert!(code.contains("createTextVNode('const a = \\'x\\';')"), "{}", code);
+    assert!(code.contains("h('Badge', { 'type': 'tip' })"), "{}", code);
+}
+
+#[test]
+fn test_markdown_inline_text_keeps_its_spaces() {
+    let source = "<template lang=\"md\">\nHi {{ name }} there, <Badge /> and *you*\n</template>\n";
+    let code = Compiler::new().compile("Greeting", source).unwrap().code;
+    assert!(code.contains("createTextVNode('Hi ')"), "{}", code);
+    assert!(code.contains("createTextVNode(' there, ')"), "{}", code);
+    assert!(code.contains("createTextVNode(' and ')"), "{}", code);
+    assert!(!code.contains("createTextVNode('')"), "{}", code);
+
+    let options = CompileOptions { ssr: true, ..Default::default() };
+    let ssr = Compiler::new().compile_with_options("Greeting", source, options).unwrap().code;
+    assert!(ssr.contains("html += 'Hi ';") && ssr.contains("html += ' there, ';"), "{}", ssr);
+}
+
+#[test]
+fn test_markdown_front_matter_and_toc() {
+    let source = "<template lang=\"md\">\n---\ntitle: Guide\norder: 2\n---\n# Getting Started\n\n## It's `setup`\n\n### Hi {{ name }}\n</template>\n";
+    let code = Compiler::new().compile("Guide", source).unwrap().code;
+    assert!(code.contains("export const toc = [\n"), "{}", code);
+    assert!(code.contains("{ level: 1, text: 'Getting Started', slug: 'getting-started' },"), "{}", code);
+    assert!(code.contains("{ level: 2, text: 'It\\'s setup', slug: 'its-setup' },"), "{}", code);
+    assert!(code.contains("{ level: 3, text: 'Hi {{ name }}', slug: 'hi' },"), "{}", code);
+    assert!(!code.contains("title: Guide"), "{}", code);
+    for ssr in [true, false] {
+        let options = CompileOptions { ssr, hydrate: !ssr, ..Default::default() };
+        let code = Compiler::new().compile_with_options("Guide", source, options).unwrap().code;
+        assert!(code.contains("{ level: 1, text: 'Getting Started', slug: 'getting-started' },"), "{}", code);
+    }
+    let html = Compiler::new().compile("Page", "<template><h2 id=\"intro\">Intro</h2></template>").unwrap().code;
+    assert!(!html.contains("toc"), "{}", html);
+
+    let dir = TempDir::new("md-pages");
+    std::fs::write(dir.join("guide.hxo"), source).unwrap();
+    let config = Compiler::new().scan_pages(&dir).unwrap();
+    let meta = config.routes[0].meta.as_ref().unwrap();
+    assert_eq!(meta.get("title"), Some(&HxoValue::String("Guide".to_string())));
+    assert_eq!(meta.get("order"), Some(&HxoValue::Number(2.0)));
+}
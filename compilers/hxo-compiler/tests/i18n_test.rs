@@ -0,0 +1,141 @@
+mod common;
+
+use common::TempDir;
+use hxo_compiler::{CompileOptions, Compiler};
+
+#[test]
+fn test_i18n_blocks_and_locale_dirs() {
+    let dir = TempDir::new("i18n");
+    let locales = dir.join("locales");
+    std::fs::create_dir_all(&locales).unwrap();
+    std::fs::write(locales.join("en.json"), r#"{ "nav": { "home": "Home", "about": "About" }, "title": "Site" }"#).unwrap();
+    std::fs::write(locales.join("de.properties"), "nav.home=Startseite\n").unwrap();
+    std::fs::write(dir.join("messages.yaml"), "en:\n  greeting: Hi\n").unwrap();
+    let source = r#"<template><h1>{{ $t('title') }}</h1></template>
+<i18n src="messages.yaml"></i18n>
+<i18n lang="json">
+{ "en": { "title": "Welcome", "nav": { "about": "About us" } } }
+</i18n>
+<i18n lang="ftl" locale="de">
+title = Willkommen
+login = Anmelden
+    .title = Bei Ihrem Konto anmelden
+</i18n>
+"#;
+    let options = CompileOptions { file_path: Some(dir.join("Page.hxo").to_string_lossy().into_owned()), ..Default::default() };
+    let code = Compiler::new().with_locale_dirs(vec![locales]).compile_with_options("Page", source, options).unwrap().code;
+    let expected = concat!(
+        "i18n: { 'de': { 'login': 'Anmelden', 'login.title': 'Bei Ihrem Konto anmelden', 'nav.home': 'Startseite', ",
+        "'title': 'Willkommen' }, 'en': { 'greeting': 'Hi', 'nav.about': 'About us', 'nav.home': 'Home', 'title': 'Welcome' } },"
+    );
+    assert!(code.contains(expected), "{}", code);
+}
+
+#[test]
+fn test_i18n_block_without_locale_needs_locale_keys() {
+    let source = "<i18n lang=\"json\">{ \"title\": \"Welcome\" }</i18n>";
+    let err = Compiler::new().compile("Page", source).err().expect("messages outside a locale");
+    assert!(err.to_string().contains("locale"), "{}", err);
+}
+
+#[test]
+fn test_locale_build_inlines_messages() {
+    let source = r#"<template>
+  <div>
+    <h1>{{ $t('title') }}</h1>
+    <a :title="$t('nav.home')" :data-q="$t('q')" :aria-label="$t('hi', { name: user })">{{ $t('hi', { name: user }) }}</a>
+  </div>
+</template>
+<script>
+const user = 'Ada';
+const heading = $t('title');
+</script>
+<i18n lang="json">
+{ "de": { "title": "Es ist's", "nav": { "home": "Startseite" }, "hi": "Hallo {name}!", "q": "Es ist's \"x\"" }, "en": { "title": "Title" } }
+</i18n>
+"#;
+    let options = CompileOptions { i18n_locale: Some("de".to_string()), ..Default::default() };
+    let code = Compiler::new().compile_with_options("Page", source, options).unwrap().code;
+    assert!(code.contains("createTextVNode('Es ist\\'s')"), "{}", code);
+    assert!(code.contains("'title': 'Startseite'"), "{}", code);
+    assert!(code.contains("`Hallo ${ctx.user}!`"), "{}", code);
+    assert!(code.contains("'data-q': 'Es ist\\'s \"x\"'"), "{}", code);
+    assert!(code.contains("'aria-label': `Hallo ${ctx.user}!`"), "{}", code);
+    assert!(code.contains("const heading = 'Es ist\\'s';"), "{}", code);
+    assert!(!code.contains("$t") && !code.contains("useI18n") && !code.contains("i18n: "), "{}", code);
+}
+
+#[test]
+fn test_locale_build_keeps_runtime_for_computed_keys() {
+    let source = r#"<template><p>{{ $t(key) }}</p></template>
+<script>
+const key = 'title';
+</script>
+<i18n lang="json">{ "de": { "title": "Titel" }, "en": { "title": "Title" } }</i18n>
+"#;
+    let options = CompileOptions { i18n_locale: Some("de".to_string()), ..Default::default() };
+    let code = Compiler::new().compile_with_options("Page", source, options).unwrap().code;
+    assert!(code.contains("i18n: { 'de': { 'title': 'Titel' } },"), "{}", code);
+    assert!(code.contains("useI18n"), "{}", code);
+}
+
+#[test]
+fn test_locale_build_fails_on_missing_messages() {
+    let source = r#"<template><p>{{ $t('title') }} {{ $t('hi', { who: user }) }}</p></template>
+<i18n lang="json">{ "de": { "hi": "Hallo {name}" } }</i18n>
+"#;
+    let options = CompileOptions { i18n_locale: Some("de".to_string()), ..Default::default() };
+    let err = Compiler::new().compile_with_options("Page", source, options).err().expect("missing messages");
+    let message = err.to_string();
+    assert!(message.contains("`title` has no message"), "{}", message);
+    assert!(message.contains("`hi` needs the parameter `name`"), "{}", message);
+}
+
+#[test]
+fn test_fluent_plurals_compile_to_formatters() {
+    let source = r#"<template><p>{{ $t('emails', { count: total }) }}</p></template>
+<script>
+const total = 3;
+</script>
+<i18n lang="ftl" locale="en">
+emails = { $count ->
+    [one] One email
+   *[other] { $count } emails
+}
+</i18n>
+"#;
+    // The block's locale picks the same shipped plural rules as ICU messages
+    let formatter = "(args = {}, locale) => `${(($s, $c = typeof $s === 'number' ? ((x) => { const s = String(x).replace('-', ''), \
+                     n = +s, i = Math.trunc(n), F = s.split('.')[1] || '', v = F.length; return i === 1 && v === 0 ? 'one' : 'other'; })($s) \
+                     : $s) => $c === 'one' ? `One email` : `${args.count} emails`)(args.count)}`";
+    let code = Compiler::new().compile("Inbox", source).unwrap().code;
+    assert!(code.contains(&format!("i18n: {{ 'en': {{ 'emails': {} }} }},", formatter)), "{}", code);
+
+    let options = CompileOptions { i18n_locale: Some("en".to_string()), ..Default::default() };
+    let code = Compiler::new().compile_with_options("Inbox", source, options).unwrap().code;
+    assert!(code.contains(&format!("({})({{ count: ctx.total }}, 'en')", formatter)), "{}", code);
+    assert!(!code.contains("useI18n"), "{}", code);
+}
+
+#[test]
+fn test_icu_plurals_compile_with_shipped_rules() {
+    let source = r#"<template><p>{{ $t('cart', { count: items }) }}</p></template>
+<script>
+const items = 2;
+</script>
+<i18n locale="ja">
+{ "cart": "{count, plural, =0 {カートは空です} other {# 個の商品}}" }
+</i18n>
+"#;
+    let formatter = "(args = {}, locale) => `${(($n, $c = ((x) => 'other')($n)) => $n === 0 ? `カートは空です` : \
+                     `${new Intl.NumberFormat(locale).format($n)} 個の商品`)(args.count)}`";
+    let code = Compiler::new().compile("Cart", source).unwrap().code;
+    assert!(code.contains(&format!("i18n: {{ 'ja': {{ 'cart': {} }} }},", formatter)), "{}", code);
+
+    let options = CompileOptions { i18n_locale: Some("ja".to_string()), ..Default::default() };
+    let code = Compiler::new().compile_with_options("Cart", source, options).unwrap().code;
+    assert!(code.contains(&format!("({})({{ count: ctx.items }}, 'ja')", formatter)), "{}", code);
+
+    let source = r#"<template><p>{{ $t('n') }}</p></template><i18n locale="en">{ "n": "{n, plural, one {x}}" }</i18n>"#;
+    assert!(Compiler::new().compile("Broken", source).is_err());
+}
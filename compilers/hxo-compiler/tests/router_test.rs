@@ -0,0 +1,136 @@
+mod common;
+
+use common::TempDir;
+use hxo_compiler::{Compiler, router};
+use hxo_types::HxoValue;
+use std::collections::HashMap;
+
+#[test]
+fn test_router_build_generates_route_table_and_types() {
+    let dir = TempDir::new("router");
+    std::fs::create_dir_all(dir.join("views")).unwrap();
+    for view in ["Home", "Layout", "User", "Post"] {
+        std::fs::write(dir.join("views").join(format!("{}.hxo", view)), "<template><div /></template>").unwrap();
+    }
+    let yaml = r#"
+mode: history
+base: /app/
+auth: &auth { requiresAuth: true }
+routes:
+  - path: /
+    name: home
+    component: ./views/Home.hxo
+  - path: /users/:id
+    component: ./views/Layout.hxo
+    meta: *auth
+    children:
+      - path: ''
+        name: user
+        component: ./views/User
+      - path: posts/:post?
+        name: user-posts
+        component: ./views/Post.hxo
+  - path: /old
+    redirect: /
+"#;
+    std::fs::write(dir.join("router.yaml"), yaml).unwrap();
+    let config = router::load_router(&dir.join("router.yaml")).unwrap();
+    assert!(router::validate_routes(&config, &dir).is_empty());
+
+    let js = router::route_table(&config, "router.yaml");
+    assert!(js.contains("export const mode = 'history';"));
+    assert!(js.contains("component: () => import('./views/User.js'),"));
+    assert!(js.contains("meta: { 'requiresAuth': true },"));
+    assert!(js.contains("'user-posts': '/users/:id/posts/:post?',"));
+    assert!(js.contains("export function navigate(name, params)"));
+
+    let mut relocated = config.clone();
+    std::fs::create_dir_all(dir.join("dist/app")).unwrap();
+    router::relocate_components(&mut relocated, &dir, &dir.join("dist/app")).unwrap();
+    let js = router::route_table(&relocated, "router.yaml");
+    assert!(js.contains("component: () => import('../../views/User.js'),"), "{}", js);
+    assert!(js.contains("component: () => import('../../views/Home.js'),"), "{}", js);
+
+    let dts = router::route_types(&config, "router.yaml");
+    assert!(dts.contains("export type RouteName = 'home' | 'user' | 'user-posts';"));
+    assert!(dts.contains("'home': Record<string, never>;"));
+    assert!(dts.contains("'user-posts': { id: string | number; post?: string | number };"));
+    assert!(dts.contains("export declare const mode: 'history';"));
+}
+
+#[test]
+fn test_router_validation_reports_problems() {
+    let json = r#"{
+  "mode": "memory",
+  "routes": [
+    { "path": "/a/:id", "name": "a", "component": "./A.hxo" },
+    { "path": "/a/:slug", "name": "a", "component": "./Missing.hxo" },
+    { "path": "/b/:id/c/:id", "redirect": "/" },
+    { "path": "/d" }
+  ]
+}"#;
+    let dir = TempDir::new("router-problems");
+    std::fs::write(dir.join("A.hxo"), "<template><div /></template>").unwrap();
+    std::fs::write(dir.join("router.json"), json).unwrap();
+    let config = router::load_router(&dir.join("router.json")).unwrap();
+    let problems: Vec<String> = router::validate_routes(&config, &dir)
+        .into_iter()
+        .map(|problem| format!("{} {}", problem.route, problem.message))
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            " unknown mode `memory`; use hash or history",
+            "/a/:slug route name `a` is already used by `/a/:id`",
+            "/a/:slug conflicts with `/a/:id`; both match the same URLs",
+            "/a/:slug component `./Missing.hxo` does not exist",
+            "/b/:id/c/:id param `:id` appears twice",
+            "/d needs a `component`, a `redirect` or `children`",
+        ]
+    );
+}
+
+#[test]
+fn test_router_scans_pages_directory() {
+    let dir = TempDir::new("pages");
+    let pages = dir.join("pages");
+    let page = "<template><div /></template>";
+    for (path, source) in [
+        ("index.hxo", "<metadata lang=\"yaml\">\ntitle: Home\n</metadata>\n<template><div /></template>"),
+        ("(marketing)/pricing.hxo", page),
+        (
+            "users/[id]/_layout.hxo",
+            "<metadata lang=\"json\">{ \"requiresAuth\": true }</metadata>\n<template><slot /></template>",
+        ),
+        ("users/[id]/index.hxo", page),
+        ("users/[id]/posts.hxo", page),
+        ("docs/[...slug].hxo", page),
+        ("docs/index.hxo", page),
+        ("_helper.hxo", page),
+    ] {
+        let path = pages.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let config = Compiler::new().scan_pages(&pages).unwrap();
+    assert!(router::validate_routes(&config, &dir).is_empty());
+    let paths: Vec<&str> = config.routes.iter().map(|route| route.path.as_str()).collect();
+    assert_eq!(paths, ["/", "/docs", "/pricing", "/users/:id", "/docs/:slug*"]);
+    assert_eq!(
+        config.routes[0].meta,
+        Some(HxoValue::Object(HashMap::from([("title".to_string(), HxoValue::String("Home".to_string()))])))
+    );
+
+    let layout = &config.routes[3];
+    assert_eq!(layout.component, "./pages/users/[id]/_layout.hxo");
+    assert_eq!(layout.meta.as_ref().and_then(|meta| meta.get("requiresAuth")), Some(&HxoValue::Bool(true)));
+    let children: Vec<(&str, Option<&str>)> =
+        layout.children.iter().flatten().map(|route| (route.path.as_str(), route.name.as_deref())).collect();
+    assert_eq!(children, [("", Some("users-id")), ("posts", Some("users-id-posts"))]);
+
+    let js = router::route_table(&config, "pages");
+    assert!(js.contains("component: () => import('./pages/docs/[...slug].js'),"));
+    let dts = router::route_types(&config, "pages");
+    assert!(dts.contains("'docs-slug': { slug: string | (string | number)[] };"));
+}
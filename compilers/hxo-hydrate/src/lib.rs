@@ -1,5 +1,5 @@
 use hxo_ir::{IRModule, TemplateNodeIR};
-use hxo_target_js::JsWriter;
+use hxo_target_js::{JsWriter, write_toc};
 use hxo_types::Result;
 use std::collections::HashSet;
 
@@ -51,6 +51,7 @@ impl HydrateBackend {
                 }
             }
         });
+        write_toc(writer, ir.toc());
         Ok(())
     }
 
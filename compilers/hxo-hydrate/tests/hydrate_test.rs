@@ -26,6 +26,7 @@ fn test_hydrate_generation() {
                 is_static: false,
                 span: Span::default(),
             })],
+            toc: vec![],
             span: Span::default(),
         }),
         styles: vec![],
@@ -0,0 +1,275 @@
+use hxo_types::Span;
+use serde::{Deserialize, Serialize};
+
+/// A parsed stylesheet: top-level rules, at-rules and (for nested blocks) declarations.
+///
+/// Spans point into the component file for CSS written by hand and are unknown for CSS produced by
+/// a preprocessor or generated by a transform.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Stylesheet {
+    pub nodes: Vec<CssNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CssNode {
+    Rule(StyleRule),
+    AtRule(AtRule),
+    Declaration(Declaration),
+}
+
+/// `selector { ... }`. Children are declarations and, with CSS nesting, nested rules and at-rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyleRule {
+    pub selector: String,
+    pub children: Vec<CssNode>,
+    #[serde(default)]
+    pub selector_span: Span,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// `@name prelude;` or `@name prelude { ... }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AtRule {
+    pub name: String,
+    pub prelude: String,
+    pub block: Option<Vec<CssNode>>,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// `property: value` with `!important` split off the value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Declaration {
+    pub property: String,
+    pub value: String,
+    #[serde(default)]
+    pub important: bool,
+    #[serde(default)]
+    pub span: Span,
+}
+
+/// The prelude of the at-rules transforms care about, see [`AtRule::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AtRuleKind<'a> {
+    /// `@media <query>`.
+    Media(&'a str),
+    /// `@supports <condition>`.
+    Supports(&'a str),
+    /// `@container [name] <condition>`.
+    Container {
+        name: Option<&'a str>,
+        condition: &'a str,
+    },
+    /// `@layer a, b` statement or `@layer a { ... }` block, see [`AtRule::layer_names`].
+    Layer(&'a str),
+    /// `@font-face { ... }`.
+    FontFace,
+    /// `@import url [layer | layer(name)] [supports(...)] [media]`.
+    Import {
+        url: &'a str,
+        layer: Option<&'a str>,
+        supports: Option<&'a str>,
+        media: Option<&'a str>,
+    },
+    /// `@keyframes name`, with or without a vendor prefix.
+    Keyframes(&'a str),
+    Other,
+}
+
+impl Declaration {
+    /// A declaration without a source span; a trailing `!important` in `value` sets `important`.
+    pub fn new(property: impl Into<String>, value: impl Into<String>) -> Self {
+        let value = value.into();
+        let trimmed = value.trim_end();
+        match trimmed.strip_suffix("important").map(str::trim_end).and_then(|v| v.strip_suffix('!')) {
+            Some(value) => Declaration {
+                property: property.into(),
+                value: value.trim_end().to_string(),
+                important: true,
+                span: Span::unknown(),
+            },
+            None => {
+                Declaration { property: property.into(), value: trimmed.to_string(), important: false, span: Span::unknown() }
+            }
+        }
+    }
+}
+
+impl AtRule {
+    /// Name without a vendor prefix, so `-webkit-keyframes` reads as `keyframes`.
+    pub fn unprefixed_name(&self) -> &str {
+        let name = self.name.as_str();
+        if let Some(rest) = name.strip_prefix('-') {
+            if let Some(i) = rest.find('-') {
+                return &rest[i + 1..];
+            }
+        }
+        name
+    }
+
+    /// The prelude read according to the at-rule's name.
+    pub fn kind(&self) -> AtRuleKind<'_> {
+        let prelude = self.prelude.trim();
+        match self.unprefixed_name().to_ascii_lowercase().as_str() {
+            "media" => AtRuleKind::Media(prelude),
+            "supports" => AtRuleKind::Supports(prelude),
+            "container" => {
+                let first = prelude.split_whitespace().next().unwrap_or("");
+                if first.is_empty() || first.starts_with('(') || ["not", "style("].iter().any(|k| first.starts_with(k)) {
+                    AtRuleKind::Container { name: None, condition: prelude }
+                }
+                else {
+                    AtRuleKind::Container { name: Some(first), condition: prelude[first.len()..].trim_start() }
+                }
+            }
+            "layer" => AtRuleKind::Layer(prelude),
+            "font-face" => AtRuleKind::FontFace,
+            "import" => import_kind(prelude),
+            "keyframes" => AtRuleKind::Keyframes(prelude.trim_matches(|c| c == '"' || c == '\'')),
+            _ => AtRuleKind::Other,
+        }
+    }
+
+    /// Names of a `@layer a, b` statement or a `@layer a { }` block.
+    pub fn layer_names(&self) -> Vec<&str> {
+        self.prelude.split(',').map(str::trim).filter(|name| !name.is_empty()).collect()
+    }
+}
+
+fn import_kind(prelude: &str) -> AtRuleKind<'_> {
+    let (url, mut rest) = take_component(prelude);
+    let url = url
+        .strip_prefix("url(")
+        .and_then(|u| u.strip_suffix(')'))
+        .unwrap_or(url)
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'');
+
+    let mut layer = None;
+    let mut supports = None;
+    loop {
+        rest = rest.trim_start();
+        let (component, after) = take_component(rest);
+        if component == "layer" {
+            layer = Some("");
+        }
+        else if let Some(name) = component.strip_prefix("layer(").and_then(|c| c.strip_suffix(')')) {
+            layer = Some(name.trim());
+        }
+        else if let Some(condition) = component.strip_prefix("supports(").and_then(|c| c.strip_suffix(')')) {
+            supports = Some(condition.trim());
+        }
+        else {
+            break;
+        }
+        rest = after;
+    }
+    let media = if rest.is_empty() { None } else { Some(rest) };
+    AtRuleKind::Import { url, layer, supports, media }
+}
+
+/// Splits off the first whitespace-separated component, keeping strings and parentheses whole.
+fn take_component(s: &str) -> (&str, &str) {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, c) if c.is_whitespace() && depth == 0 => return (&s[..i], &s[i..]),
+            _ => {}
+        }
+    }
+    (s, "")
+}
+
+impl Stylesheet {
+    pub fn to_css(&self, minify: bool) -> String {
+        let mut out = String::new();
+        print_nodes(&self.nodes, minify, 0, &mut out);
+        out
+    }
+
+    /// Drops every source span, for a stylesheet parsed from generated CSS.
+    pub fn without_spans(mut self) -> Self {
+        fn clear(nodes: &mut [CssNode]) {
+            for node in nodes {
+                match node {
+                    CssNode::Rule(rule) => {
+                        rule.span = Span::unknown();
+                        rule.selector_span = Span::unknown();
+                        clear(&mut rule.children);
+                    }
+                    CssNode::AtRule(rule) => {
+                        rule.span = Span::unknown();
+                        if let Some(children) = &mut rule.block {
+                            clear(children);
+                        }
+                    }
+                    CssNode::Declaration(decl) => decl.span = Span::unknown(),
+                }
+            }
+        }
+        clear(&mut self.nodes);
+        self
+    }
+}
+
+fn print_nodes(nodes: &[CssNode], minify: bool, depth: usize, out: &mut String) {
+    let indent = "  ".repeat(depth);
+    for (i, node) in nodes.iter().enumerate() {
+        match node {
+            CssNode::Declaration(decl) => {
+                let important = if decl.important { " !important" } else { "" };
+                if minify {
+                    out.push_str(&format!("{}:{}{};", decl.property, decl.value, important.trim_start()));
+                }
+                else {
+                    out.push_str(&format!("{}{}: {}{};\n", indent, decl.property, decl.value, important));
+                }
+            }
+            CssNode::Rule(rule) => {
+                if !minify && i > 0 && depth == 0 {
+                    out.push('\n');
+                }
+                print_block(&indent, &rule.selector, &rule.children, minify, depth, out);
+            }
+            CssNode::AtRule(rule) => {
+                let head = if rule.prelude.is_empty() {
+                    format!("@{}", rule.name)
+                }
+                else {
+                    format!("@{} {}", rule.name, rule.prelude)
+                };
+                match &rule.block {
+                    Some(children) => {
+                        if !minify && i > 0 && depth == 0 {
+                            out.push('\n');
+                        }
+                        print_block(&indent, &head, children, minify, depth, out);
+                    }
+                    None if minify => out.push_str(&format!("{};", head)),
+                    None => out.push_str(&format!("{}{};\n", indent, head)),
+                }
+            }
+        }
+    }
+}
+
+fn print_block(indent: &str, head: &str, children: &[CssNode], minify: bool, depth: usize, out: &mut String) {
+    if minify {
+        out.push_str(head);
+        out.push('{');
+        print_nodes(children, minify, depth + 1, out);
+        out.push('}');
+    }
+    else {
+        out.push_str(&format!("{}{} {{\n", indent, head));
+        print_nodes(children, minify, depth + 1, out);
+        out.push_str(&format!("{}}}\n", indent));
+    }
+}
@@ -2,6 +2,10 @@ use hxo_types::{HxoValue, Span};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+mod css;
+
+pub use css::{AtRule, AtRuleKind, CssNode, Declaration, StyleRule, Stylesheet};
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum JsExpr {
     Identifier(String, #[serde(default)] Span),
@@ -139,6 +143,42 @@ pub struct JsProgram {
     pub span: Span,
 }
 
+/// A translated message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum I18nMessage {
+    /// Text with `{name}` placeholders.
+    Text(String),
+    /// Source of a JS function `(args, locale) => string`, for messages with plurals or selectors.
+    Formatter(String),
+}
+
+impl I18nMessage {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            I18nMessage::Text(text) => Some(text),
+            I18nMessage::Formatter(_) => None,
+        }
+    }
+}
+
+impl From<&str> for I18nMessage {
+    fn from(text: &str) -> Self {
+        I18nMessage::Text(text.to_string())
+    }
+}
+
+impl From<String> for I18nMessage {
+    fn from(text: String) -> Self {
+        I18nMessage::Text(text)
+    }
+}
+
+impl PartialEq<&str> for I18nMessage {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_text() == Some(*other)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IRModule {
     pub name: String,
@@ -147,15 +187,105 @@ pub struct IRModule {
     pub script_meta: Option<HxoValue>, // For script analysis results
     pub template: Option<TemplateIR>,
     pub styles: Vec<StyleIR>,
-    pub i18n: Option<HashMap<String, HashMap<String, String>>>,
+    /// Locale to message key to message.
+    pub i18n: Option<HashMap<String, HashMap<String, I18nMessage>>>,
     pub wasm: Vec<Vec<u8>>,
     pub custom_blocks: Vec<CustomBlockIR>,
     pub span: Span,
 }
 
+impl IRModule {
+    /// CSS Modules bindings with their class maps, merged per binding and sorted for stable output.
+    pub fn css_modules(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut bindings: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        for style in &self.styles {
+            let Some(binding) = &style.module
+            else {
+                continue;
+            };
+            let index = match bindings.iter().position(|(name, _)| name == binding) {
+                Some(index) => index,
+                None => {
+                    bindings.push((binding.clone(), Vec::new()));
+                    bindings.len() - 1
+                }
+            };
+            let classes = &mut bindings[index].1;
+            for (class, hashed) in &style.classes {
+                classes.retain(|(c, _)| c != class);
+                classes.push((class.clone(), hashed.clone()));
+            }
+        }
+        for (_, classes) in &mut bindings {
+            classes.sort();
+        }
+        bindings.sort_by(|a, b| a.0.cmp(&b.0));
+        bindings
+    }
+
+    /// Heading outline of a Markdown template, empty for other template languages.
+    pub fn toc(&self) -> &[TocEntry] {
+        self.template.as_ref().map_or(&[], |template| &template.toc)
+    }
+}
+
+/// A heading of the template outline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TocEntry {
+    pub level: u8,
+    pub text: String,
+    /// The heading's `id`, to link to it as `#slug`.
+    pub slug: String,
+}
+
+impl TocEntry {
+    /// The `h1`-`h6` elements of `nodes` that carry a static `id`, as every Markdown heading does, in
+    /// document order. Interpolations keep their `{{ }}` source in the text.
+    pub fn outline(nodes: &[TemplateNodeIR]) -> Vec<TocEntry> {
+        fn collect(nodes: &[TemplateNodeIR], toc: &mut Vec<TocEntry>) {
+            for node in nodes {
+                let TemplateNodeIR::Element(el) = node
+                else {
+                    continue;
+                };
+                let level = match el.tag.as_bytes() {
+                    [b'h', level @ b'1'..=b'6'] => level - b'0',
+                    _ => {
+                        collect(&el.children, toc);
+                        continue;
+                    }
+                };
+                let id = el.attributes.iter().find(|attr| attr.name == "id" && !attr.is_dynamic);
+                if let Some(slug) = id.and_then(|attr| attr.value.clone()) {
+                    toc.push(TocEntry { level, text: text_content(&el.children).trim().to_string(), slug });
+                }
+            }
+        }
+
+        let mut toc = Vec::new();
+        collect(nodes, &mut toc);
+        toc
+    }
+}
+
+fn text_content(nodes: &[TemplateNodeIR]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node {
+            TemplateNodeIR::Text(text, _) => text.clone(),
+            TemplateNodeIR::Element(el) => text_content(&el.children),
+            TemplateNodeIR::Interpolation(expr) => format!("{{{{ {} }}}}", expr.code.trim()),
+            TemplateNodeIR::Comment(_, _) => String::new(),
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemplateIR {
     pub nodes: Vec<TemplateNodeIR>,
+    /// Heading outline, recorded for Markdown templates only.
+    #[serde(default)]
+    pub toc: Vec<TocEntry>,
     #[serde(default)]
     pub span: Span,
 }
@@ -202,6 +332,16 @@ pub struct StyleIR {
     pub code: String,
     pub lang: String,
     pub scoped: bool,
+    /// Binding name of a `<style module>` block (`$style` unless `module="name"` is given).
+    #[serde(default)]
+    pub module: Option<String>,
+    /// CSS Modules class map: source class name to hashed class name(s).
+    #[serde(default)]
+    pub classes: HashMap<String, String>,
+    /// The block as a CSS AST. Transforms edit it and reprint `code`; `None` when the block was not
+    /// parsed (for instance IR built by hand).
+    #[serde(default)]
+    pub stylesheet: Option<Stylesheet>,
     #[serde(default)]
     pub span: Span,
 }
@@ -0,0 +1,566 @@
+use crate::text::utf16_len;
+use dashmap::DashMap;
+use hxo_ir::{JsExpr, JsProgram, JsStmt, TemplateNodeIR};
+use hxo_parser::{ParseState, ScriptParser, TemplateParser as _};
+use hxo_parser_expression::ExprParser;
+use hxo_parser_template::TemplateParser;
+use hxo_types::Span as HxoSpan;
+use tower_lsp::lsp_types::{Location, Position, Range, SymbolInformation, SymbolKind};
+
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Extensions picked up by the workspace scan.
+pub const INDEXED_EXTENSIONS: [&str; 3] = ["hxo", "ts", "js"];
+
+/// Directories never worth descending into.
+const IGNORED_DIRS: [&str; 5] = ["node_modules", "target", "dist", ".git", ".hxo"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReExport {
+    pub source: String,
+    /// `None` for `export * from '...'`.
+    pub specifiers: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComponentInfo {
+    pub name: String,
+    pub props: Vec<String>,
+    pub emits: Vec<String>,
+    pub slots: Vec<String>,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedFile {
+    pub uri: Url,
+    pub component: Option<ComponentInfo>,
+    pub exports: Vec<IndexedSymbol>,
+    pub re_exports: Vec<ReExport>,
+    /// Places where this file refers to other symbols: component tags and import specifiers.
+    pub usages: Vec<IndexedSymbol>,
+}
+
+impl IndexedFile {
+    pub fn find_export(&self, symbol: &str) -> Option<&IndexedSymbol> {
+        self.exports.iter().find(|s| s.name == symbol)
+    }
+
+    pub fn is_component(&self) -> bool {
+        self.component.is_some()
+    }
+}
+
+/// Background index of every `.hxo`/`.ts`/`.js` file in the open workspace folders.
+#[derive(Default)]
+pub struct WorkspaceIndex {
+    files: DashMap<String, IndexedFile>,
+}
+
+impl WorkspaceIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn get(&self, uri: &Url) -> Option<IndexedFile> {
+        self.files.get(uri.as_str()).map(|f| f.clone())
+    }
+
+    /// Returns the indexed entry, reading and indexing the file from disk if it is not known yet.
+    pub fn get_or_index(&self, uri: &Url) -> Option<IndexedFile> {
+        if let Some(file) = self.get(uri) {
+            return Some(file);
+        }
+        let path = uri.to_file_path().ok()?;
+        let content = std::fs::read_to_string(path).ok()?;
+        Some(self.update(uri.clone(), &content))
+    }
+
+    pub fn update(&self, uri: Url, content: &str) -> IndexedFile {
+        let file = analyze_file(uri, content);
+        self.files.insert(file.uri.to_string(), file.clone());
+        file
+    }
+
+    pub fn update_from_disk(&self, uri: &Url) {
+        match uri.to_file_path().ok().and_then(|p| std::fs::read_to_string(p).ok()) {
+            Some(content) => {
+                self.update(uri.clone(), &content);
+            }
+            None => self.remove(uri),
+        }
+    }
+
+    pub fn remove(&self, uri: &Url) {
+        self.files.remove(uri.as_str());
+    }
+
+    /// Drops every entry that lives under `folder`.
+    pub fn remove_folder(&self, folder: &Path) {
+        self.files.retain(|_, file| file.uri.to_file_path().map(|p| !p.starts_with(folder)).unwrap_or(true));
+    }
+
+    /// Walks `root` recursively and indexes every supported file. Returns the number of files indexed.
+    pub fn scan_folder(&self, root: &Path) -> usize {
+        let mut count = 0;
+        for path in collect_files(root) {
+            if let (Ok(content), Ok(uri)) = (std::fs::read_to_string(&path), Url::from_file_path(&path)) {
+                self.update(uri, &content);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub fn components(&self) -> Vec<(Url, ComponentInfo)> {
+        let mut components: Vec<_> =
+            self.files.iter().filter_map(|f| f.component.clone().map(|c| (f.uri.clone(), c))).collect();
+        components.sort_by(|a, b| a.1.name.cmp(&b.1.name).then_with(|| a.0.as_str().cmp(b.0.as_str())));
+        components
+    }
+
+    pub fn find_component(&self, name: &str) -> Option<(Url, ComponentInfo)> {
+        self.components().into_iter().find(|(_, c)| c.name == name)
+    }
+
+    /// Case-insensitive fuzzy search over components and exports, used by `workspace/symbol`.
+    #[allow(deprecated)]
+    pub fn search(&self, query: &str) -> Vec<SymbolInformation> {
+        let mut results = Vec::new();
+        for file in self.files.iter() {
+            let container = file.uri.path_segments().and_then(|mut s| s.next_back()).map(|s| s.to_string());
+            if let Some(component) = &file.component {
+                if fuzzy_match(query, &component.name) {
+                    results.push(SymbolInformation {
+                        name: component.name.clone(),
+                        kind: SymbolKind::CLASS,
+                        tags: None,
+                        deprecated: None,
+                        location: Location { uri: file.uri.clone(), range: component.range },
+                        container_name: container.clone(),
+                    });
+                }
+            }
+            for symbol in &file.exports {
+                if fuzzy_match(query, &symbol.name) {
+                    results.push(SymbolInformation {
+                        name: symbol.name.clone(),
+                        kind: symbol.kind,
+                        tags: None,
+                        deprecated: None,
+                        location: Location { uri: file.uri.clone(), range: symbol.range },
+                        container_name: container.clone(),
+                    });
+                }
+            }
+        }
+        results.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.location.uri.as_str().cmp(b.location.uri.as_str())));
+        results
+    }
+
+    /// Every usage of `name` across the workspace (component tags and import specifiers).
+    pub fn references(&self, name: &str) -> Vec<Location> {
+        let mut locations = Vec::new();
+        for file in self.files.iter() {
+            for usage in file.usages.iter().filter(|u| u.name == name) {
+                locations.push(Location { uri: file.uri.clone(), range: usage.range });
+            }
+        }
+        locations.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()).then_with(|| a.range.start.line.cmp(&b.range.start.line)));
+        locations
+    }
+
+    /// Declarations of `name`: components with that name and exports with that name.
+    pub fn declarations(&self, name: &str) -> Vec<Location> {
+        let mut locations = Vec::new();
+        for file in self.files.iter() {
+            if let Some(component) = file.component.as_ref().filter(|c| c.name == name) {
+                locations.push(Location { uri: file.uri.clone(), range: component.range });
+            }
+            if let Some(symbol) = file.find_export(name) {
+                locations.push(Location { uri: file.uri.clone(), range: symbol.range });
+            }
+        }
+        locations.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+        locations
+    }
+}
+
+pub fn is_indexed_path(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).map(|e| INDEXED_EXTENSIONS.contains(&e)).unwrap_or(false)
+        && !path.to_string_lossy().ends_with(".d.ts")
+}
+
+fn collect_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir)
+        else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if !IGNORED_DIRS.contains(&name.as_str()) && !name.starts_with('.') {
+                    stack.push(path);
+                }
+            }
+            else if is_indexed_path(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn fuzzy_match(query: &str, name: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+    let name = name.to_lowercase();
+    let mut chars = name.chars();
+    query.to_lowercase().chars().all(|q| chars.any(|c| c == q))
+}
+
+/// Converts a 1-based hxo span into an LSP range, shifting it down by `line_offset` lines.
+pub fn to_lsp_range(span: HxoSpan, line_offset: u32) -> Range {
+    Range {
+        start: Position {
+            line: (span.start.line + line_offset).saturating_sub(1),
+            character: span.start.column.saturating_sub(1),
+        },
+        end: Position { line: (span.end.line + line_offset).saturating_sub(1), character: span.end.column.saturating_sub(1) },
+    }
+}
+
+/// The component name of a `.hxo` file is its file stem.
+pub fn component_name(uri: &Url) -> Option<String> {
+    let file = uri.path_segments()?.next_back()?;
+    file.strip_suffix(".hxo").map(|s| s.to_string())
+}
+
+pub fn analyze_file(uri: Url, content: &str) -> IndexedFile {
+    if uri.path().ends_with(".hxo") { analyze_component(uri, content) } else { analyze_module(uri, content) }
+}
+
+fn analyze_component(uri: Url, content: &str) -> IndexedFile {
+    let mut file = IndexedFile { uri, component: None, exports: Vec::new(), re_exports: Vec::new(), usages: Vec::new() };
+
+    let mut state = ParseState::new(content);
+    let Ok(nodes) = TemplateParser.parse(&mut state, "html")
+    else {
+        // Keep the component discoverable even while it does not parse.
+        file.component = component_name(&file.uri).map(|name| ComponentInfo { name, ..Default::default() });
+        return file;
+    };
+
+    let mut component = ComponentInfo { name: component_name(&file.uri).unwrap_or_default(), ..Default::default() };
+
+    if let (Some(program), Some(script_span)) = (script_program(&nodes), script_element_span(&nodes)) {
+        let offset = script_span.start.line;
+        collect_exports(&program, offset, &mut file.exports, &mut file.re_exports);
+        collect_import_usages(&program, offset, &mut file.usages);
+        collect_component_api(&program, &mut component);
+    }
+
+    collect_template_info(&nodes, &mut component.slots, &mut file.usages);
+    component.slots.dedup();
+    file.component = Some(component);
+    file
+}
+
+fn analyze_module(uri: Url, content: &str) -> IndexedFile {
+    let mut file = IndexedFile { uri, component: None, exports: Vec::new(), re_exports: Vec::new(), usages: Vec::new() };
+    let mut state = ParseState::new(content);
+    match ExprParser.parse(&mut state, "ts") {
+        Ok(program) => {
+            collect_exports(&program, 0, &mut file.exports, &mut file.re_exports);
+            collect_import_usages(&program, 0, &mut file.usages);
+        }
+        // The expression parser does not understand full TypeScript, so fall back to a lexical scan.
+        Err(_) => scan_exports_lexically(content, &mut file.exports, &mut file.re_exports),
+    }
+    file
+}
+
+pub(crate) fn script_program(nodes: &[TemplateNodeIR]) -> Option<JsProgram> {
+    for node in nodes {
+        if let TemplateNodeIR::Element(el) = node {
+            if el.tag == "script" {
+                if let Some(TemplateNodeIR::Text(content, _)) = el.children.first() {
+                    let mut state = ParseState::new(content);
+                    return ExprParser.parse(&mut state, "ts").ok();
+                }
+            }
+            if let Some(p) = script_program(&el.children) {
+                return Some(p);
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn script_element_span(nodes: &[TemplateNodeIR]) -> Option<HxoSpan> {
+    for node in nodes {
+        if let TemplateNodeIR::Element(el) = node {
+            if el.tag == "script" {
+                return Some(el.span);
+            }
+            if let Some(s) = script_element_span(&el.children) {
+                return Some(s);
+            }
+        }
+    }
+    None
+}
+
+fn symbol_kind(stmt: &JsStmt) -> SymbolKind {
+    match stmt {
+        JsStmt::FunctionDecl { .. } => SymbolKind::FUNCTION,
+        JsStmt::VariableDecl { kind, .. } if kind == "const" => SymbolKind::CONSTANT,
+        _ => SymbolKind::VARIABLE,
+    }
+}
+
+fn collect_exports(program: &JsProgram, line_offset: u32, exports: &mut Vec<IndexedSymbol>, re_exports: &mut Vec<ReExport>) {
+    for stmt in &program.body {
+        match stmt {
+            JsStmt::Export { declaration, .. } => match &**declaration {
+                JsStmt::VariableDecl { id, span, .. } | JsStmt::FunctionDecl { id, span, .. } => {
+                    let name = if id.is_empty() { "default".to_string() } else { id.clone() };
+                    exports.push(IndexedSymbol {
+                        name,
+                        kind: symbol_kind(declaration),
+                        range: to_lsp_range(*span, line_offset),
+                    });
+                }
+                JsStmt::Expr(_, span) => {
+                    exports.push(IndexedSymbol {
+                        name: "default".to_string(),
+                        kind: SymbolKind::VARIABLE,
+                        range: to_lsp_range(*span, line_offset),
+                    });
+                }
+                _ => {}
+            },
+            JsStmt::ExportNamed { source: None, specifiers, span } => {
+                for name in specifiers {
+                    exports.push(IndexedSymbol {
+                        name: name.clone(),
+                        kind: SymbolKind::VARIABLE,
+                        range: to_lsp_range(*span, line_offset),
+                    });
+                }
+            }
+            JsStmt::ExportNamed { source: Some(source), specifiers, .. } => {
+                re_exports.push(ReExport { source: source.clone(), specifiers: Some(specifiers.clone()) });
+            }
+            JsStmt::ExportAll { source, .. } => {
+                re_exports.push(ReExport { source: source.clone(), specifiers: None });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_import_usages(program: &JsProgram, line_offset: u32, usages: &mut Vec<IndexedSymbol>) {
+    for stmt in &program.body {
+        if let JsStmt::Import { specifiers, span, .. } = stmt {
+            for name in specifiers {
+                usages.push(IndexedSymbol {
+                    name: name.clone(),
+                    kind: SymbolKind::MODULE,
+                    range: to_lsp_range(*span, line_offset),
+                });
+            }
+        }
+    }
+}
+
+/// Picks up `defineProps([...])`/`defineProps({ ... })` and `defineEmits([...])` declarations.
+fn collect_component_api(program: &JsProgram, component: &mut ComponentInfo) {
+    for stmt in &program.body {
+        let expr = match stmt {
+            JsStmt::Expr(expr, _) => Some(expr),
+            JsStmt::VariableDecl { init: Some(expr), .. } => Some(expr),
+            _ => None,
+        };
+        if let Some(JsExpr::Call { callee, args, .. }) = expr {
+            if let JsExpr::Identifier(name, _) = &**callee {
+                let target = match name.as_str() {
+                    "defineProps" => &mut component.props,
+                    "defineEmits" => &mut component.emits,
+                    _ => continue,
+                };
+                if let Some(arg) = args.first() {
+                    target.extend(declared_names(arg));
+                }
+            }
+        }
+    }
+}
+
+fn declared_names(arg: &JsExpr) -> Vec<String> {
+    match arg {
+        JsExpr::Array(items, _) => items
+            .iter()
+            .filter_map(|item| match item {
+                JsExpr::Literal(hxo_types::HxoValue::String(s), _) => Some(s.clone()),
+                _ => None,
+            })
+            .collect(),
+        JsExpr::Object(props, _) => {
+            let mut names: Vec<_> = props.keys().cloned().collect();
+            names.sort();
+            names
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn collect_template_info(nodes: &[TemplateNodeIR], slots: &mut Vec<String>, usages: &mut Vec<IndexedSymbol>) {
+    for node in nodes {
+        if let TemplateNodeIR::Element(el) = node {
+            if el.tag == "script" || el.tag == "style" {
+                continue;
+            }
+            if el.tag == "slot" {
+                let name = el
+                    .attributes
+                    .iter()
+                    .find(|a| a.name == "name")
+                    .and_then(|a| a.value.clone())
+                    .unwrap_or_else(|| "default".to_string());
+                if !slots.contains(&name) {
+                    slots.push(name);
+                }
+            }
+            else if el.tag.chars().next().map(|c| c.is_uppercase()).unwrap_or(false) {
+                let start = Position { line: el.span.start.line.saturating_sub(1), character: el.span.start.column };
+                let end = Position { line: start.line, character: start.character + el.tag.len() as u32 };
+                usages.push(IndexedSymbol { name: el.tag.clone(), kind: SymbolKind::CLASS, range: Range { start, end } });
+            }
+            collect_template_info(&el.children, slots, usages);
+        }
+    }
+}
+
+/// Line-based export discovery for TypeScript sources the expression parser rejects.
+fn scan_exports_lexically(content: &str, exports: &mut Vec<IndexedSymbol>, re_exports: &mut Vec<ReExport>) {
+    for (line_no, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let Some(rest) = trimmed.strip_prefix("export ")
+        else {
+            continue;
+        };
+        let indent = utf16_len(&line[..line.len() - trimmed.len()]);
+        let rest = rest.trim_start();
+
+        if let Some(source) = rest.strip_prefix('*').and_then(quoted_source) {
+            re_exports.push(ReExport { source, specifiers: None });
+            continue;
+        }
+        if rest.starts_with('{') {
+            if let Some(close) = rest.find('}') {
+                let specifiers: Vec<String> = rest[1..close]
+                    .split(',')
+                    .map(|s| s.split(" as ").last().unwrap_or("").trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                if let Some(source) = quoted_source(&rest[close + 1..]) {
+                    re_exports.push(ReExport { source, specifiers: Some(specifiers) });
+                }
+                else {
+                    let range = Range {
+                        start: Position { line: line_no as u32, character: indent },
+                        end: Position { line: line_no as u32, character: utf16_len(line) },
+                    };
+                    exports.extend(specifiers.into_iter().map(|name| IndexedSymbol {
+                        name,
+                        kind: SymbolKind::VARIABLE,
+                        range,
+                    }));
+                }
+            }
+            continue;
+        }
+
+        let rest = rest.strip_prefix("default ").unwrap_or(rest).trim_start();
+        let rest = rest.strip_prefix("declare ").unwrap_or(rest);
+        let rest = rest.strip_prefix("async ").unwrap_or(rest);
+        let (kind, rest) = [
+            ("function*", SymbolKind::FUNCTION),
+            ("function", SymbolKind::FUNCTION),
+            ("const", SymbolKind::CONSTANT),
+            ("let", SymbolKind::VARIABLE),
+            ("var", SymbolKind::VARIABLE),
+            ("class", SymbolKind::CLASS),
+            ("interface", SymbolKind::INTERFACE),
+            ("type", SymbolKind::TYPE_PARAMETER),
+            ("enum", SymbolKind::ENUM),
+        ]
+        .iter()
+        .find_map(|(kw, kind)| rest.strip_prefix(kw).filter(|r| r.starts_with(char::is_whitespace)).map(|r| (*kind, r)))
+        .unwrap_or((SymbolKind::VARIABLE, ""));
+
+        let rest = rest.trim_start();
+        let name: String = rest.chars().take_while(|c| hxo_types::is_alphanumeric(*c)).collect();
+        if name.is_empty() {
+            continue;
+        }
+        // `rest` ends the line, so the name starts where it does
+        let column = utf16_len(&line[..line.len() - rest.len()]);
+        exports.push(IndexedSymbol {
+            name: name.clone(),
+            kind,
+            range: Range {
+                start: Position { line: line_no as u32, character: column },
+                end: Position { line: line_no as u32, character: column + utf16_len(&name) },
+            },
+        });
+    }
+}
+
+fn quoted_source(rest: &str) -> Option<String> {
+    let rest = rest.trim_start().strip_prefix("from")?.trim_start();
+    let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let end = rest[1..].find(quote)?;
+    Some(rest[1..1 + end].to_string())
+}
+
+/// Builds the specifier used to import `to` from a file located at `from`, e.g. `./components/Button.hxo`.
+pub fn relative_import_path(from: &Path, to: &Path) -> Option<String> {
+    let from_dir = from.parent()?;
+    let from_parts: Vec<_> = from_dir.components().collect();
+    let to_parts: Vec<_> = to.components().collect();
+    let common = from_parts.iter().zip(to_parts.iter()).take_while(|(a, b)| a == b).count();
+
+    let mut segments = Vec::new();
+    for _ in common..from_parts.len() {
+        segments.push("..".to_string());
+    }
+    for part in &to_parts[common..] {
+        segments.push(part.as_os_str().to_string_lossy().to_string());
+    }
+    let joined = segments.join("/");
+    if joined.starts_with("..") { Some(joined) } else { Some(format!("./{}", joined)) }
+}
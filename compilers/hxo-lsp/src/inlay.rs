@@ -0,0 +1,234 @@
+use crate::text::position_at;
+use hxo_ir::{ElementIR, JsExpr, JsProgram, JsStmt, TemplateNodeIR};
+use hxo_script_analyzer::{ScriptAnalyzer, ScriptMetadata};
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintTooltip};
+
+/// Inlay hints that make the reactive transform visible: declarations the compiler treats as
+/// signals/computeds, and the implicit `()` unwrap it inserts wherever the template reads them.
+pub fn reactive_hints(content: &str, nodes: &[TemplateNodeIR]) -> Vec<InlayHint> {
+    let Some(script) = find_element(nodes, "script")
+    else {
+        return Vec::new();
+    };
+    let Some(program) = crate::index::script_program(nodes)
+    else {
+        return Vec::new();
+    };
+    let Ok(meta) = ScriptAnalyzer::new().analyze(&program)
+    else {
+        return Vec::new();
+    };
+    if meta.signals.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hints = Vec::new();
+    if let Some(script_start) = content_start(content, script.span.start.offset as usize) {
+        declaration_hints(content, script_start, &program, &meta, &mut hints);
+    }
+    template_hints(content, nodes, &meta, &mut hints);
+    hints.sort_by_key(|h| (h.position.line, h.position.character));
+    hints
+}
+
+fn find_element<'a>(nodes: &'a [TemplateNodeIR], tag: &str) -> Option<&'a ElementIR> {
+    for node in nodes {
+        if let TemplateNodeIR::Element(el) = node {
+            if el.tag == tag {
+                return Some(el);
+            }
+            if let Some(found) = find_element(&el.children, tag) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Byte offset right after the `>` of the tag opening at `tag_offset`.
+fn content_start(content: &str, tag_offset: usize) -> Option<usize> {
+    content.get(tag_offset..)?.find('>').map(|i| tag_offset + i + 1)
+}
+
+fn kind_of<'a>(meta: &ScriptMetadata, name: &str) -> Option<&'a str> {
+    if meta.computed.contains(name) {
+        Some("computed")
+    }
+    else if meta.signals.contains(name) {
+        Some("signal")
+    }
+    else {
+        None
+    }
+}
+
+fn declaration_hints(content: &str, base: usize, program: &JsProgram, meta: &ScriptMetadata, hints: &mut Vec<InlayHint>) {
+    for stmt in &program.body {
+        let stmt = match stmt {
+            JsStmt::Export { declaration, .. } => &**declaration,
+            other => other,
+        };
+        let JsStmt::VariableDecl { id, span, .. } = stmt
+        else {
+            continue;
+        };
+        let decl_start = base + span.start.offset as usize;
+        let decl_end = (base + span.end.offset as usize).min(content.len());
+        let Some(decl_text) = content.get(decl_start..decl_end)
+        else {
+            continue;
+        };
+
+        let names = id.trim_matches(|c| c == '[' || c == ']').split(',').map(|s| s.trim()).filter(|s| !s.is_empty());
+        for name in names {
+            let Some(kind) = kind_of(meta, name)
+            else {
+                continue;
+            };
+            if let Some(rel) = find_word(decl_text, name) {
+                hints.push(InlayHint {
+                    position: position_at(content, decl_start + rel + name.len()),
+                    label: InlayHintLabel::String(format!(": {}", kind)),
+                    kind: Some(InlayHintKind::TYPE),
+                    text_edits: None,
+                    tooltip: Some(InlayHintTooltip::String(format!(
+                        "`{}` is a {}: templates read it without calling it, the compiler unwraps it to `{}()`.",
+                        name, kind, name
+                    ))),
+                    padding_left: None,
+                    padding_right: Some(true),
+                    data: None,
+                });
+            }
+        }
+    }
+}
+
+fn find_word(haystack: &str, word: &str) -> Option<usize> {
+    let is_ident = |c: char| hxo_types::is_alphanumeric(c);
+    let mut from = 0;
+    while let Some(i) = haystack[from..].find(word) {
+        let start = from + i;
+        let end = start + word.len();
+        let before_ok = haystack[..start].chars().next_back().map(|c| !is_ident(c)).unwrap_or(true);
+        let after_ok = haystack[end..].chars().next().map(|c| !is_ident(c)).unwrap_or(true);
+        if before_ok && after_ok {
+            return Some(start);
+        }
+        from = end;
+    }
+    None
+}
+
+fn template_hints(content: &str, nodes: &[TemplateNodeIR], meta: &ScriptMetadata, hints: &mut Vec<InlayHint>) {
+    for node in nodes {
+        match node {
+            TemplateNodeIR::Element(el) => {
+                if el.tag == "script" || el.tag == "style" {
+                    continue;
+                }
+                for attr in &el.attributes {
+                    let (Some(ast), Some(value)) = (&attr.value_ast, &attr.value)
+                    else {
+                        continue;
+                    };
+                    let attr_start = attr.span.start.offset as usize;
+                    let attr_end = (attr.span.end.offset as usize).min(content.len());
+                    let Some(attr_text) = content.get(attr_start..attr_end)
+                    else {
+                        continue;
+                    };
+                    if let Some(rel) = attr_text.find(value.as_str()).filter(|_| !value.is_empty()) {
+                        unwrap_hints(content, attr_start + rel, ast, meta, &[], false, hints);
+                    }
+                }
+                template_hints(content, &el.children, meta, hints);
+            }
+            TemplateNodeIR::Interpolation(expr) => {
+                let Some(ast) = &expr.ast
+                else {
+                    continue;
+                };
+                let open = expr.span.start.offset as usize + 2;
+                let Some(inner) = content.get(open..)
+                else {
+                    continue;
+                };
+                let base = open + (inner.len() - inner.trim_start().len());
+                unwrap_hints(content, base, ast, meta, &[], false, hints);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn unwrap_hints(
+    content: &str,
+    base: usize,
+    expr: &JsExpr,
+    meta: &ScriptMetadata,
+    shadowed: &[String],
+    is_callee: bool,
+    hints: &mut Vec<InlayHint>,
+) {
+    match expr {
+        JsExpr::Identifier(name, span) => {
+            if is_callee || shadowed.contains(name) {
+                return;
+            }
+            if let Some(kind) = kind_of(meta, name) {
+                hints.push(InlayHint {
+                    position: position_at(content, base + span.end.offset as usize),
+                    label: InlayHintLabel::String("()".to_string()),
+                    kind: None,
+                    text_edits: None,
+                    tooltip: Some(InlayHintTooltip::String(format!(
+                        "`{}` is a {}; the compiler reads it as `ctx.{}()`.",
+                        name, kind, name
+                    ))),
+                    padding_left: None,
+                    padding_right: None,
+                    data: None,
+                });
+            }
+        }
+        JsExpr::Unary { argument, .. } => unwrap_hints(content, base, argument, meta, shadowed, false, hints),
+        JsExpr::Binary { left, right, .. } => {
+            unwrap_hints(content, base, left, meta, shadowed, false, hints);
+            unwrap_hints(content, base, right, meta, shadowed, false, hints);
+        }
+        JsExpr::Call { callee, args, .. } => {
+            unwrap_hints(content, base, callee, meta, shadowed, true, hints);
+            for arg in args {
+                unwrap_hints(content, base, arg, meta, shadowed, false, hints);
+            }
+        }
+        JsExpr::Member { object, .. } => unwrap_hints(content, base, object, meta, shadowed, false, hints),
+        JsExpr::Array(items, _) => {
+            for item in items {
+                unwrap_hints(content, base, item, meta, shadowed, false, hints);
+            }
+        }
+        JsExpr::Object(props, _) => {
+            for value in props.values() {
+                unwrap_hints(content, base, value, meta, shadowed, false, hints);
+            }
+        }
+        JsExpr::ArrowFunction { params, body, .. } => {
+            let mut inner = shadowed.to_vec();
+            inner.extend(params.iter().cloned());
+            unwrap_hints(content, base, body, meta, &inner, false, hints);
+        }
+        JsExpr::Conditional { test, consequent, alternate, .. } => {
+            unwrap_hints(content, base, test, meta, shadowed, false, hints);
+            unwrap_hints(content, base, consequent, meta, shadowed, false, hints);
+            unwrap_hints(content, base, alternate, meta, shadowed, false, hints);
+        }
+        JsExpr::TemplateLiteral { expressions, .. } => {
+            for e in expressions {
+                unwrap_hints(content, base, e, meta, shadowed, false, hints);
+            }
+        }
+        _ => {}
+    }
+}
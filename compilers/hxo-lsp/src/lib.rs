@@ -1,6 +1,6 @@
 use dashmap::{DashMap, DashSet};
 use hxo_ir::{ElementIR, ExpressionIR, JsExpr, JsProgram, JsStmt, TemplateNodeIR};
-use hxo_parser::{ParseState, ScriptParser, TemplateParser};
+use hxo_parser::{ParseState, ScriptParser, TemplateParser as _};
 use hxo_parser_expression::ExprParser;
 use hxo_parser_template::TemplateParser;
 use hxo_parser_toml::TomlParser;
@@ -8,9 +8,18 @@ use hxo_types::{Position as HxoPosition, Span as HxoSpan, is_pos_in_span};
 use serde::Deserialize;
 use tower_lsp::{Client, LanguageServer, LspService, Server, jsonrpc::Result, lsp_types::*};
 
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 use url::Url;
 
+mod index;
+mod inlay;
+mod signature;
+mod text;
+
+pub use index::{ComponentInfo, IndexedFile, IndexedSymbol, ReExport, WorkspaceIndex};
+pub use inlay::reactive_hints;
+pub use signature::{CallContext, CallSite, call_context_at, call_site_at, signature_help};
+
 #[derive(Debug, Deserialize)]
 struct HxoConfig {
     #[serde(default)]
@@ -35,6 +44,7 @@ pub struct Backend {
     documents: DashMap<String, String>,
     workspace_folders: DashMap<String, PathBuf>,
     auto_imports: DashSet<String>,
+    index: Arc<WorkspaceIndex>,
 }
 
 #[tower_lsp::async_trait]
@@ -77,6 +87,14 @@ impl LanguageServer for Backend {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
                 definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                inlay_hint_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 completion_provider: Some(CompletionOptions {
                     trigger_characters: Some(vec!["<".to_string(), "@".to_string(), ":".to_string()]),
@@ -97,6 +115,24 @@ impl LanguageServer for Backend {
 
     async fn initialized(&self, _: InitializedParams) {
         self.client.log_message(MessageType::INFO, "HXO Language Server initialized").await;
+
+        let watchers = index::INDEXED_EXTENSIONS
+            .iter()
+            .map(|ext| FileSystemWatcher { glob_pattern: GlobPattern::String(format!("**/*.{}", ext)), kind: None })
+            .collect();
+        let registration = Registration {
+            id: "hxo-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions { watchers }).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            self.client.log_message(MessageType::WARNING, format!("File watching unavailable: {}", e)).await;
+        }
+
+        let folders: Vec<PathBuf> = self.workspace_folders.iter().map(|f| f.value().clone()).collect();
+        for folder in folders {
+            self.spawn_scan(folder);
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -104,17 +140,129 @@ impl LanguageServer for Backend {
     }
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        self.reindex_document(&params.text_document.uri, &params.text_document.text);
         self.documents.insert(params.text_document.uri.to_string(), params.text_document.text);
         self.validate_document(params.text_document.uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         if let Some(change) = params.content_changes.into_iter().next() {
+            self.reindex_document(&params.text_document.uri, &change.text);
             self.documents.insert(params.text_document.uri.to_string(), change.text);
             self.validate_document(params.text_document.uri).await;
         }
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            // Open documents are indexed from the editor buffer, not from disk.
+            if self.documents.contains_key(change.uri.as_str()) && change.typ != FileChangeType::DELETED {
+                continue;
+            }
+            match change.typ {
+                FileChangeType::DELETED => self.index.remove(&change.uri),
+                _ => {
+                    if change.uri.to_file_path().map(|p| index::is_indexed_path(&p)).unwrap_or(false) {
+                        self.index.update_from_disk(&change.uri);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        for folder in params.event.removed {
+            if let Some((_, path)) = self.workspace_folders.remove(folder.uri.as_str()) {
+                self.index.remove_folder(&path);
+            }
+        }
+        for folder in params.event.added {
+            if let Ok(path) = folder.uri.to_file_path() {
+                self.workspace_folders.insert(folder.uri.to_string(), path.clone());
+                self.spawn_scan(path);
+            }
+        }
+    }
+
+    async fn signature_help(&self, params: SignatureHelpParams) -> Result<Option<SignatureHelp>> {
+        let uri = params.text_document_position_params.text_document.uri.to_string();
+        let position = params.text_document_position_params.position;
+
+        let Some(content) = self.documents.get(&uri).map(|c| c.clone())
+        else {
+            return Ok(None);
+        };
+        let offset = text::offset_at(&content, position);
+        let site = if uri.ends_with(".hxo") { call_site_at(&content, offset) } else { CallSite::Script };
+        let Some(context) = call_context_at(&content, offset, site)
+        else {
+            return Ok(None);
+        };
+
+        let program = if uri.ends_with(".hxo") {
+            let mut state = ParseState::new(&content);
+            TemplateParser.parse(&mut state, "html").ok().and_then(|nodes| index::script_program(&nodes))
+        }
+        else {
+            let mut state = ParseState::new(&content);
+            ExprParser.parse(&mut state, "ts").ok()
+        };
+        Ok(signature_help(&context, program.as_ref()))
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = params.text_document.uri.to_string();
+        if !uri.ends_with(".hxo") {
+            return Ok(None);
+        }
+        let Some(content) = self.documents.get(&uri).map(|c| c.clone())
+        else {
+            return Ok(None);
+        };
+        let mut state = ParseState::new(&content);
+        let Ok(nodes) = TemplateParser.parse(&mut state, "html")
+        else {
+            return Ok(None);
+        };
+
+        let range = params.range;
+        let hints = reactive_hints(&content, &nodes)
+            .into_iter()
+            .filter(|h| h.position >= range.start && h.position <= range.end)
+            .collect();
+        Ok(Some(hints))
+    }
+
+    async fn symbol(&self, params: WorkspaceSymbolParams) -> Result<Option<Vec<SymbolInformation>>> {
+        Ok(Some(self.index.search(&params.query)))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri.to_string();
+        let position = params.text_document_position.position;
+
+        let Some(content) = self.documents.get(&uri).map(|c| c.clone())
+        else {
+            return Ok(None);
+        };
+        let hxo_pos = HxoPosition { line: position.line + 1, column: position.character + 1, offset: 0 };
+        let mut state = ParseState::new(&content);
+        let Ok(nodes) = TemplateParser.parse(&mut state, "html")
+        else {
+            return Ok(None);
+        };
+
+        let Some(symbol) = Self::find_symbol_at_position(&nodes, hxo_pos)
+        else {
+            return Ok(None);
+        };
+        let mut locations = self.index.references(&symbol);
+        if params.context.include_declaration {
+            locations.extend(self.index.declarations(&symbol));
+        }
+        Ok(if locations.is_empty() { None } else { Some(locations) })
+    }
+
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
         let uri = params.text_document_position.text_document.uri.to_string();
         let position = params.text_document_position.position;
@@ -125,8 +273,7 @@ impl LanguageServer for Backend {
             let hxo_pos = HxoPosition { line: position.line + 1, column: position.character + 1, offset: 0 };
 
             let mut state = ParseState::new(&content);
-            let parser = TemplateParser;
-            if let Ok(nodes) = parser.parse(&mut state, "html") {
+            if let Ok(nodes) = TemplateParser.parse(&mut state, "html") {
                 if let Some(context) = Backend::get_completion_context(&nodes, hxo_pos) {
                     match context {
                         CompletionContext::Tag => {
@@ -140,8 +287,31 @@ impl LanguageServer for Backend {
                                     ..Default::default()
                                 });
                             }
+
+                            // Components from the workspace index
+                            items.extend(self.component_completions(&uri, &nodes));
                         }
                         CompletionContext::Attribute(tag_name) => {
+                            // Props and emits of workspace components
+                            if let Some((_, component)) = self.index.find_component(&tag_name) {
+                                for prop in &component.props {
+                                    items.push(CompletionItem {
+                                        label: format!(":{}", prop),
+                                        kind: Some(CompletionItemKind::PROPERTY),
+                                        detail: Some(format!("Prop of <{}>", component.name)),
+                                        ..Default::default()
+                                    });
+                                }
+                                for emit in &component.emits {
+                                    items.push(CompletionItem {
+                                        label: format!("@{}", emit),
+                                        kind: Some(CompletionItemKind::EVENT),
+                                        detail: Some(format!("Event of <{}>", component.name)),
+                                        ..Default::default()
+                                    });
+                                }
+                            }
+
                             // Directives
                             let directives = vec!["@click", "@input", "@change", ":class", ":style", ":value"];
                             for dir in directives {
@@ -174,7 +344,7 @@ impl LanguageServer for Backend {
                             }
 
                             // Script variables
-                            if let Some(program) = Self::get_script_program(&nodes) {
+                            if let Some(program) = index::script_program(&nodes) {
                                 for stmt in &program.body {
                                     if let JsStmt::VariableDecl { id, .. } = stmt {
                                         items.push(CompletionItem {
@@ -215,8 +385,7 @@ impl LanguageServer for Backend {
             let hxo_pos = HxoPosition { line: position.line + 1, column: position.character + 1, offset: 0 };
 
             let mut state = ParseState::new(&content);
-            let parser = TemplateParser;
-            if let Ok(nodes) = parser.parse(&mut state, "html") {
+            if let Ok(nodes) = TemplateParser.parse(&mut state, "html") {
                 return Ok(self.find_definition_in_nodes(&nodes, hxo_pos, &uri).await);
             }
         }
@@ -262,40 +431,36 @@ impl Backend {
     fn get_completion_context(nodes: &[TemplateNodeIR], pos: HxoPosition) -> Option<CompletionContext> {
         for node in nodes {
             match node {
-                TemplateNodeIR::Element(el) => {
-                    if is_pos_in_span(pos, el.span) {
-                        // Check if in tag name
-                        let tag_start = el.span.start;
-                        let tag_name_end = HxoPosition {
-                            line: tag_start.line,
-                            column: tag_start.column + (el.tag.len() as u32) + 1, // <tag
-                            offset: 0,
-                        };
-
-                        if pos.line == tag_start.line && pos.column <= tag_name_end.column {
-                            return Some(CompletionContext::Tag);
-                        }
-
-                        // Check attributes
-                        for attr in &el.attributes {
-                            if is_pos_in_span(pos, attr.span) {
-                                return Some(CompletionContext::Attribute(el.tag.clone()));
-                            }
-                        }
+                TemplateNodeIR::Element(el) if is_pos_in_span(pos, el.span) => {
+                    // Check if in tag name
+                    let tag_start = el.span.start;
+                    let tag_name_end = HxoPosition {
+                        line: tag_start.line,
+                        column: tag_start.column + (el.tag.len() as u32) + 1, // <tag
+                        offset: 0,
+                    };
+
+                    if pos.line == tag_start.line && pos.column <= tag_name_end.column {
+                        return Some(CompletionContext::Tag);
+                    }
 
-                        // Check children
-                        if let Some(ctx) = Self::get_completion_context(&el.children, pos) {
-                            return Some(ctx);
+                    // Check attributes
+                    for attr in &el.attributes {
+                        if is_pos_in_span(pos, attr.span) {
+                            return Some(CompletionContext::Attribute(el.tag.clone()));
                         }
+                    }
 
-                        // If in element but not in specific child/attr, might be in attribute area
-                        return Some(CompletionContext::Attribute(el.tag.clone()));
+                    // Check children
+                    if let Some(ctx) = Self::get_completion_context(&el.children, pos) {
+                        return Some(ctx);
                     }
+
+                    // If in element but not in specific child/attr, might be in attribute area
+                    return Some(CompletionContext::Attribute(el.tag.clone()));
                 }
-                TemplateNodeIR::Interpolation(expr) => {
-                    if is_pos_in_span(pos, expr.span) {
-                        return Some(CompletionContext::Expression);
-                    }
+                TemplateNodeIR::Interpolation(expr) if is_pos_in_span(pos, expr.span) => {
+                    return Some(CompletionContext::Expression);
                 }
                 _ => {}
             }
@@ -313,7 +478,127 @@ impl Backend {
         auto_imports.insert("onMount".to_string());
         auto_imports.insert("onCleanup".to_string());
 
-        Self { client, documents: DashMap::new(), workspace_folders: DashMap::new(), auto_imports }
+        Self {
+            client,
+            documents: DashMap::new(),
+            workspace_folders: DashMap::new(),
+            auto_imports,
+            index: Arc::new(WorkspaceIndex::new()),
+        }
+    }
+
+    pub fn index(&self) -> &WorkspaceIndex {
+        &self.index
+    }
+
+    fn spawn_scan(&self, folder: PathBuf) {
+        let index = self.index.clone();
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let scanned = folder.clone();
+            if let Ok(count) = tokio::task::spawn_blocking(move || index.scan_folder(&scanned)).await {
+                client.log_message(MessageType::INFO, format!("Indexed {} files in {}", count, folder.display())).await;
+            }
+        });
+    }
+
+    fn reindex_document(&self, uri: &Url, content: &str) {
+        if uri.to_file_path().map(|p| index::is_indexed_path(&p)).unwrap_or(false) {
+            self.index.update(uri.clone(), content);
+        }
+    }
+
+    fn component_completions(&self, uri: &str, nodes: &[TemplateNodeIR]) -> Vec<CompletionItem> {
+        let current_path = Url::parse(uri).ok().and_then(|u| u.to_file_path().ok());
+        let program = index::script_program(nodes);
+        let script_span = index::script_element_span(nodes);
+
+        let mut items = Vec::new();
+        for (component_uri, component) in self.index.components() {
+            if component_uri.as_str() == uri {
+                continue;
+            }
+            let target_path = component_uri.to_file_path().ok();
+            let import_path = match (&current_path, &target_path) {
+                (Some(from), Some(to)) => index::relative_import_path(from, to),
+                _ => None,
+            };
+
+            // Auto-import the component into the <script> block when it is not imported yet
+            let already_imported = program.as_ref().map(|p| Self::is_import(p, &component.name)).unwrap_or(false);
+            let additional_text_edits = match (&import_path, script_span) {
+                (Some(path), Some(span)) if !already_imported => Some(vec![TextEdit {
+                    range: Range {
+                        start: Position { line: span.start.line, character: 0 },
+                        end: Position { line: span.start.line, character: 0 },
+                    },
+                    new_text: format!("import {} from '{}';\n", component.name, path),
+                }]),
+                _ => None,
+            };
+
+            items.push(CompletionItem {
+                label: component.name.clone(),
+                kind: Some(CompletionItemKind::CLASS),
+                detail: Some(import_path.unwrap_or_else(|| component_uri.to_string())),
+                documentation: Self::component_documentation(&component),
+                additional_text_edits,
+                ..Default::default()
+            });
+        }
+        items
+    }
+
+    fn component_documentation(component: &ComponentInfo) -> Option<Documentation> {
+        let mut lines = Vec::new();
+        if !component.props.is_empty() {
+            lines.push(format!("**Props**: {}", component.props.join(", ")));
+        }
+        if !component.emits.is_empty() {
+            lines.push(format!("**Emits**: {}", component.emits.join(", ")));
+        }
+        if !component.slots.is_empty() {
+            lines.push(format!("**Slots**: {}", component.slots.join(", ")));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        Some(Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value: lines.join("\n\n") }))
+    }
+
+    /// Resolves the name under the cursor: a component tag, a template expression or a script identifier.
+    fn find_symbol_at_position(nodes: &[TemplateNodeIR], pos: HxoPosition) -> Option<String> {
+        for node in nodes {
+            match node {
+                TemplateNodeIR::Element(el) if is_pos_in_span(pos, el.span) => {
+                    if el.tag == "script" {
+                        let program = index::script_program(std::slice::from_ref(node))?;
+                        let relative_pos = HxoPosition {
+                            line: pos.line - el.span.start.line,
+                            column: if pos.line == el.span.start.line { pos.column - el.span.start.column } else { pos.column },
+                            offset: 0,
+                        };
+                        return Self::find_symbol_at(&program, relative_pos);
+                    }
+                    if let Some(symbol) = Self::find_symbol_at_position(&el.children, pos) {
+                        return Some(symbol);
+                    }
+                    let on_tag_name = pos.line == el.span.start.line
+                        && pos.column > el.span.start.column
+                        && pos.column <= el.span.start.column + el.tag.len() as u32 + 1;
+                    if on_tag_name {
+                        return Some(el.tag.clone());
+                    }
+                }
+                TemplateNodeIR::Interpolation(expr) => {
+                    if let Some(symbol) = Self::find_symbol_in_template_expr(expr, pos) {
+                        return Some(symbol);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
     }
 
     fn resolve_path(&self, current_uri: &str, relative_path: &str) -> Option<Url> {
@@ -374,18 +659,8 @@ impl Backend {
         None
     }
 
-    async fn get_document_content(&self, uri: &Url) -> Option<String> {
-        let uri_str = uri.to_string();
-        if let Some(content) = self.documents.get(&uri_str) {
-            return Some(content.clone());
-        }
-
-        // Read from disk if not in memory
-        if let Ok(path) = uri.to_file_path() {
-            return std::fs::read_to_string(path).ok();
-        }
-
-        None
+    fn get_open_document(&self, uri: &Url) -> Option<String> {
+        self.documents.get(uri.as_str()).map(|content| content.clone())
     }
 
     async fn find_definition_in_nodes(
@@ -394,62 +669,66 @@ impl Backend {
         pos: HxoPosition,
         uri: &str,
     ) -> Option<GotoDefinitionResponse> {
-        let script_program = Self::get_script_program(nodes);
+        let script_program = index::script_program(nodes);
 
         for node in nodes {
             match node {
-                TemplateNodeIR::Element(el) => {
-                    if is_pos_in_span(pos, el.span) {
-                        if el.tag == "script" {
-                            return self.find_definition_in_script(el, pos, uri).await;
-                        }
-                        else if el.tag == "style" {
-                            return Self::find_definition_in_style(el, pos, uri);
+                TemplateNodeIR::Element(el) if is_pos_in_span(pos, el.span) => {
+                    if el.tag == "script" {
+                        return self.find_definition_in_script(el, pos, uri).await;
+                    }
+                    else if el.tag == "style" {
+                        return Self::find_definition_in_style(el, pos, uri);
+                    }
+                    else {
+                        // 使用 Box::pin 处理异步递归
+                        let res = self.find_definition_in_nodes_recursive(&el.children, pos, uri).await;
+                        if res.is_some() {
+                            return res;
                         }
-                        else {
-                            // 使用 Box::pin 处理异步递归
-                            let res = self.find_definition_in_nodes_recursive(&el.children, pos, uri).await;
-                            if res.is_some() {
-                                return res;
-                            }
+
+                        // Component tags jump to the component file found in the workspace index
+                        if let Some((component_uri, component)) = self.index.find_component(&el.tag) {
+                            return Some(GotoDefinitionResponse::Scalar(Location {
+                                uri: component_uri,
+                                range: component.range,
+                            }));
                         }
                     }
                 }
-                TemplateNodeIR::Interpolation(expr) => {
-                    if !expr.span.is_unknown() && is_pos_in_span(pos, expr.span) {
-                        if let Some(symbol) = Self::find_symbol_in_template_expr(expr, pos) {
-                            // 1. 首先在 script 块中查找
-                            if let Some(program) = &script_program {
-                                if let Some(def_span) = Self::find_definition_of_symbol(program, &symbol) {
-                                    if Self::is_import(program, &symbol) {
-                                        if let Some(loc) = self.find_external_definition(program, &symbol, uri).await {
-                                            return Some(GotoDefinitionResponse::Scalar(loc));
-                                        }
+                TemplateNodeIR::Interpolation(expr) if !expr.span.is_unknown() && is_pos_in_span(pos, expr.span) => {
+                    if let Some(symbol) = Self::find_symbol_in_template_expr(expr, pos) {
+                        // 1. 首先在 script 块中查找
+                        if let Some(program) = &script_program {
+                            if let Some(def_span) = Self::find_definition_of_symbol(program, &symbol) {
+                                if Self::is_import(program, &symbol) {
+                                    if let Some(loc) = self.find_external_definition(program, &symbol, uri).await {
+                                        return Some(GotoDefinitionResponse::Scalar(loc));
                                     }
+                                }
 
-                                    if let Some(script_el_span) = Self::get_script_element_span(nodes) {
-                                        return Some(GotoDefinitionResponse::Scalar(Location {
-                                            uri: Url::parse(uri).unwrap(),
-                                            range: Range {
-                                                start: Position {
-                                                    line: script_el_span.start.line + def_span.start.line - 1,
-                                                    character: def_span.start.column - 1,
-                                                },
-                                                end: Position {
-                                                    line: script_el_span.start.line + def_span.end.line - 1,
-                                                    character: def_span.end.column - 1,
-                                                },
+                                if let Some(script_el_span) = index::script_element_span(nodes) {
+                                    return Some(GotoDefinitionResponse::Scalar(Location {
+                                        uri: Url::parse(uri).unwrap(),
+                                        range: Range {
+                                            start: Position {
+                                                line: script_el_span.start.line + def_span.start.line - 1,
+                                                character: def_span.start.column - 1,
                                             },
-                                        }));
-                                    }
+                                            end: Position {
+                                                line: script_el_span.start.line + def_span.end.line - 1,
+                                                character: def_span.end.column - 1,
+                                            },
+                                        },
+                                    }));
                                 }
                             }
+                        }
 
-                            // 2. 检查是否为自动导入
-                            if self.auto_imports.contains(&symbol) {
-                                if let Some(loc) = self.find_implicit_definition(&symbol, "@hxo/core", uri).await {
-                                    return Some(GotoDefinitionResponse::Scalar(loc));
-                                }
+                        // 2. 检查是否为自动导入
+                        if self.auto_imports.contains(&symbol) {
+                            if let Some(loc) = self.find_implicit_definition(&symbol, "@hxo/core", uri).await {
+                                return Some(GotoDefinitionResponse::Scalar(loc));
                             }
                         }
                     }
@@ -481,38 +760,6 @@ impl Backend {
         false
     }
 
-    fn get_script_program(nodes: &[TemplateNodeIR]) -> Option<JsProgram> {
-        for node in nodes {
-            if let TemplateNodeIR::Element(el) = node {
-                if el.tag == "script" {
-                    if let Some(TemplateNodeIR::Text(content, _)) = el.children.first() {
-                        let mut state = ParseState::new(content);
-                        let ts_parser = ExprParser;
-                        return ts_parser.parse(&mut state, "ts").ok();
-                    }
-                }
-                if let Some(p) = Self::get_script_program(&el.children) {
-                    return Some(p);
-                }
-            }
-        }
-        None
-    }
-
-    fn get_script_element_span(nodes: &[TemplateNodeIR]) -> Option<HxoSpan> {
-        for node in nodes {
-            if let TemplateNodeIR::Element(el) = node {
-                if el.tag == "script" {
-                    return Some(el.span);
-                }
-                if let Some(s) = Self::get_script_element_span(&el.children) {
-                    return Some(s);
-                }
-            }
-        }
-        None
-    }
-
     fn find_symbol_in_template_expr(expr: &ExpressionIR, pos: HxoPosition) -> Option<String> {
         // In hxo-ir, ExpressionIR has a 'code' field for the raw string
         if !expr.span.is_unknown() && is_pos_in_span(pos, expr.span) {
@@ -625,26 +872,20 @@ impl Backend {
     fn find_symbol_in_expr(expr: &JsExpr, pos: HxoPosition) -> Option<String> {
         use JsExpr::*;
         match expr {
-            Identifier(name, span) => {
-                if is_pos_in_span(pos, *span) {
-                    return Some(name.clone());
-                }
+            Identifier(name, span) if is_pos_in_span(pos, *span) => {
+                return Some(name.clone());
             }
-            Binary { left, right, span, .. } => {
-                if is_pos_in_span(pos, *span) {
-                    return Self::find_symbol_in_expr(left, pos).or_else(|| Self::find_symbol_in_expr(right, pos));
-                }
+            Binary { left, right, span, .. } if is_pos_in_span(pos, *span) => {
+                return Self::find_symbol_in_expr(left, pos).or_else(|| Self::find_symbol_in_expr(right, pos));
             }
-            Call { callee, args, span, .. } => {
-                if is_pos_in_span(pos, *span) {
-                    if let Some(symbol) = Self::find_symbol_in_expr(callee, pos) {
+            Call { callee, args, span, .. } if is_pos_in_span(pos, *span) => {
+                if let Some(symbol) = Self::find_symbol_in_expr(callee, pos) {
+                    return Some(symbol);
+                }
+                for arg in args {
+                    if let Some(symbol) = Self::find_symbol_in_expr(arg, pos) {
                         return Some(symbol);
                     }
-                    for arg in args {
-                        if let Some(symbol) = Self::find_symbol_in_expr(arg, pos) {
-                            return Some(symbol);
-                        }
-                    }
                 }
             }
             _ => {}
@@ -702,67 +943,24 @@ impl Backend {
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<Location>> + Send + 'a>> {
         let symbol_owned = symbol.to_string();
         Box::pin(async move {
-            let content = self.get_document_content(&uri).await?;
             let symbol = &symbol_owned;
-            if uri.path().ends_with(".hxo") {
-                let mut state = ParseState::new(&content);
-                let parser = TemplateParser;
-                if let Ok(nodes) = parser.parse(&mut state, "html") {
-                    if let Some(ext_program) = Self::get_script_program(&nodes) {
-                        if let Some(def_span) = Self::find_exported_definition(&ext_program, symbol) {
-                            if let Some(script_el_span) = Self::get_script_element_span(&nodes) {
-                                return Some(Location {
-                                    uri,
-                                    range: Range {
-                                        start: Position {
-                                            line: script_el_span.start.line + def_span.start.line - 1,
-                                            character: def_span.start.column - 1,
-                                        },
-                                        end: Position {
-                                            line: script_el_span.start.line + def_span.end.line - 1,
-                                            character: def_span.end.column - 1,
-                                        },
-                                    },
-                                });
-                            }
-                        }
-                    }
-                }
+            // Open documents are kept fresh by did_change; everything else comes from the workspace index.
+            let file = match self.get_open_document(&uri) {
+                Some(content) => self.index.update(uri.clone(), &content),
+                None => self.index.get_or_index(&uri)?,
+            };
+
+            if let Some(def) = file.find_export(symbol) {
+                return Some(Location { uri, range: def.range });
             }
-            else {
-                let mut state = ParseState::new(&content);
-                let ts_parser = ExprParser;
-                if let Ok(ext_program) = ts_parser.parse(&mut state, "ts") {
-                    if let Some(def_span) = Self::find_exported_definition(&ext_program, symbol) {
-                        return Some(Location {
-                            uri: uri.clone(),
-                            range: Range {
-                                start: Position { line: def_span.start.line - 1, character: def_span.start.column - 1 },
-                                end: Position { line: def_span.end.line - 1, character: def_span.end.column - 1 },
-                            },
-                        });
-                    }
 
-                    // Handle re-exports
-                    for stmt in &ext_program.body {
-                        match stmt {
-                            JsStmt::ExportAll { source, .. } => {
-                                if let Some(target_uri) = self.resolve_path(uri.as_str(), source) {
-                                    if let Some(loc) = self.find_external_definition_in_file(target_uri, symbol).await {
-                                        return Some(loc);
-                                    }
-                                }
-                            }
-                            JsStmt::ExportNamed { source: Some(source), specifiers, .. } => {
-                                if specifiers.contains(&symbol.to_string()) {
-                                    if let Some(target_uri) = self.resolve_path(uri.as_str(), source) {
-                                        if let Some(loc) = self.find_external_definition_in_file(target_uri, symbol).await {
-                                            return Some(loc);
-                                        }
-                                    }
-                                }
-                            }
-                            _ => {}
+            // Handle re-exports
+            for re_export in &file.re_exports {
+                let matches = re_export.specifiers.as_ref().map(|s| s.contains(symbol)).unwrap_or(true);
+                if matches {
+                    if let Some(target_uri) = self.resolve_path(uri.as_str(), &re_export.source) {
+                        if let Some(loc) = self.find_external_definition_in_file(target_uri, symbol).await {
+                            return Some(loc);
                         }
                     }
                 }
@@ -771,23 +969,6 @@ impl Backend {
         })
     }
 
-    fn find_exported_definition(program: &JsProgram, symbol: &str) -> Option<HxoSpan> {
-        for stmt in &program.body {
-            match stmt {
-                JsStmt::Export { declaration, .. } => match &**declaration {
-                    JsStmt::VariableDecl { id, span, .. } if id == symbol => return Some(*span),
-                    JsStmt::FunctionDecl { id, span, .. } if id == symbol => return Some(*span),
-                    _ => {}
-                },
-                JsStmt::ExportNamed { source: None, specifiers, span, .. } if specifiers.contains(&symbol.to_string()) => {
-                    return Some(*span);
-                }
-                _ => {}
-            }
-        }
-        None
-    }
-
     fn find_definition_in_style(_el: &ElementIR, _pos: HxoPosition, _uri: &str) -> Option<GotoDefinitionResponse> {
         // Similar to script, use hxo-parser-css
         None
@@ -0,0 +1,165 @@
+use hxo_ir::{JsExpr, JsProgram, JsStmt};
+use hxo_types::is_alphanumeric;
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp, SignatureInformation,
+};
+
+/// Look-back limit when searching for the enclosing call.
+const MAX_SCAN: usize = 4096;
+
+/// Signatures of the `@hxo/core` runtime APIs: (name, parameters, return type, documentation).
+const CORE_SIGNATURES: [(&str, &[&str], &str, &str); 10] = [
+    ("createSignal", &["value: T"], "[Getter<T>, Setter<T>]", "Creates a reactive signal."),
+    ("createEffect", &["fn: () => void"], "void", "Creates a reactive effect."),
+    (
+        "createComputed",
+        &["fn: () => T"],
+        "Getter<T>",
+        "Creates a computed signal that automatically updates when dependencies change.",
+    ),
+    ("nextTick", &["fn?: () => void"], "Promise<void>", "Push a task to the queue and schedule a flush."),
+    ("queueJob", &["job: Task"], "void", "Queue a job for the next tick."),
+    (
+        "h",
+        &["type: string | symbol", "props?: Record<string, any>", "children?: VNode[] | string"],
+        "VNode",
+        "Create a Virtual Node.",
+    ),
+    ("createTextVNode", &["text: string"], "VNode", "Create a text Virtual Node."),
+    ("setLocale", &["locale: string"], "void", "Switches the active i18n locale."),
+    ("useI18n", &["data?: I18nData"], "I18nInstance", "Creates an i18n instance bound to the component messages."),
+    ("$t", &["key: string", "params?: Record<string, any>"], "string", "Translates a message key for the active locale."),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallContext {
+    pub callee: String,
+    pub active_parameter: u32,
+}
+
+/// Where a position sits in a component, which decides where the search for the enclosing call stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallSite {
+    /// A `<script>` block, or a script file.
+    Script,
+    /// A template attribute value, delimited by `"`.
+    Attribute,
+    /// A `{{ }}` interpolation in template text.
+    Interpolation,
+}
+
+/// The [`CallSite`] of `offset` in a component file.
+pub fn call_site_at(content: &str, offset: usize) -> CallSite {
+    let before = &content[..offset.min(content.len())];
+    if before.rfind("<script") > before.rfind("</script>") {
+        CallSite::Script
+    }
+    else if before.rfind('<') > before.rfind('>') {
+        CallSite::Attribute
+    }
+    else {
+        CallSite::Interpolation
+    }
+}
+
+/// Finds the innermost unclosed call around `offset`, e.g. `select(item, |)` in an `@click` handler.
+pub fn call_context_at(content: &str, offset: usize, site: CallSite) -> Option<CallContext> {
+    let before = &content[..offset.min(content.len())];
+    let in_template = site != CallSite::Script;
+    let mut depth = 0i32;
+    let mut commas = 0u32;
+    let mut in_string: Option<char> = None;
+
+    for (i, c) in before.char_indices().rev().take(MAX_SCAN) {
+        if let Some(quote) = in_string {
+            if c == quote {
+                in_string = None;
+            }
+            continue;
+        }
+        match c {
+            // `"` delimits attribute values, so inside one it ends the search; `;` ends a statement
+            '"' | ';' if depth == 0 && (c == ';' || site == CallSite::Attribute) => return None,
+            '\'' | '`' | '"' => in_string = Some(c),
+            '}' if in_template && depth == 0 && before[i..].starts_with("}}") => return None,
+            '{' if in_template && depth == 0 && i > 0 && before[..i].ends_with('{') => return None,
+            ')' | ']' | '}' => depth += 1,
+            '(' if depth == 0 => {
+                let callee = callee_before(&before[..i])?;
+                return Some(CallContext { callee, active_parameter: commas });
+            }
+            // Inside an array/object literal argument: only commas after it belong to the call
+            '[' | '{' if depth == 0 => commas = 0,
+            '(' | '[' | '{' => depth -= 1,
+            ',' if depth == 0 => commas += 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn callee_before(text: &str) -> Option<String> {
+    let trimmed = text.trim_end();
+    let start = trimmed.char_indices().rev().take_while(|(_, c)| is_alphanumeric(*c)).last().map(|(i, _)| i)?;
+    let name = &trimmed[start..];
+    if matches!(name, "if" | "for" | "while" | "switch" | "catch" | "function" | "return") {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+/// Builds the signature for `context`, looking at local script functions first and the core APIs second.
+pub fn signature_help(context: &CallContext, program: Option<&JsProgram>) -> Option<SignatureHelp> {
+    let signature = program.and_then(|p| local_signature(p, &context.callee)).or_else(|| core_signature(&context.callee))?;
+
+    let param_count = signature.parameters.as_ref().map(|p| p.len()).unwrap_or(0) as u32;
+    let active_parameter = if param_count == 0 { None } else { Some(context.active_parameter.min(param_count - 1)) };
+    Some(SignatureHelp { signatures: vec![signature], active_signature: Some(0), active_parameter })
+}
+
+fn build_signature(name: &str, params: &[String], return_type: Option<&str>, doc: Option<&str>) -> SignatureInformation {
+    let mut label = format!("{}({})", name, params.join(", "));
+    if let Some(ret) = return_type {
+        label.push_str(": ");
+        label.push_str(ret);
+    }
+    SignatureInformation {
+        label,
+        documentation: doc
+            .map(|d| Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value: d.to_string() })),
+        parameters: Some(
+            params
+                .iter()
+                .map(|p| ParameterInformation { label: ParameterLabel::Simple(p.clone()), documentation: None })
+                .collect(),
+        ),
+        active_parameter: None,
+    }
+}
+
+pub fn core_signature(name: &str) -> Option<SignatureInformation> {
+    let (name, params, ret, doc) = CORE_SIGNATURES.iter().find(|(n, ..)| *n == name)?;
+    let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+    let display =
+        if params.iter().any(|p| p.contains(": T") || p.contains("=> T")) { format!("{}<T>", name) } else { name.to_string() };
+    Some(build_signature(&display, &params, Some(ret), Some(&format!("`@hxo/core` — {}", doc))))
+}
+
+fn local_signature(program: &JsProgram, name: &str) -> Option<SignatureInformation> {
+    for stmt in &program.body {
+        let stmt = match stmt {
+            JsStmt::Export { declaration, .. } => &**declaration,
+            other => other,
+        };
+        match stmt {
+            JsStmt::FunctionDecl { id, params, .. } if id == name => {
+                return Some(build_signature(name, params, None, Some("Local function")));
+            }
+            JsStmt::VariableDecl { id, init: Some(JsExpr::ArrowFunction { params, .. }), .. } if id == name => {
+                return Some(build_signature(name, params, None, Some("Local function")));
+            }
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,42 @@
+use tower_lsp::lsp_types::Position;
+
+/// Converts an LSP position (0-based line, UTF-16 column) into a byte offset into `content`.
+pub fn offset_at(content: &str, position: Position) -> usize {
+    let mut offset = 0;
+    for (line_no, line) in content.split_inclusive('\n').enumerate() {
+        if line_no as u32 == position.line {
+            let mut column = 0;
+            for (i, c) in line.char_indices() {
+                if column >= position.character || c == '\n' {
+                    return offset + i;
+                }
+                column += c.len_utf16() as u32;
+            }
+            return offset + line.len();
+        }
+        offset += line.len();
+    }
+    content.len()
+}
+
+/// Converts a byte offset into `content` back into an LSP position.
+pub fn position_at(content: &str, offset: usize) -> Position {
+    let offset = offset.min(content.len());
+    let mut line = 0;
+    let mut character = 0;
+    for c in content[..offset].chars() {
+        if c == '\n' {
+            line += 1;
+            character = 0;
+        }
+        else {
+            character += c.len_utf16() as u32;
+        }
+    }
+    Position { line, character }
+}
+
+/// Length of `text` in UTF-16 code units, the unit of LSP columns.
+pub fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
@@ -0,0 +1,72 @@
+use hxo_lsp::WorkspaceIndex;
+use url::Url;
+
+const BUTTON: &str = r#"<script>
+const props = defineProps(['label', 'disabled']);
+const emit = defineEmits(['press']);
+export const SIZES = ['sm', 'lg'];
+</script>
+<template>
+  <button><slot name="icon"></slot><slot></slot>{{ label }}</button>
+</template>
+"#;
+
+const APP: &str = r#"<script>
+import Button from './Button.hxo';
+</script>
+<template>
+  <div><Button label="Hi" /></div>
+</template>
+"#;
+
+#[test]
+fn test_index_component_api() {
+    let index = WorkspaceIndex::new();
+    let file = index.update(Url::parse("file:///project/src/Button.hxo").unwrap(), BUTTON);
+
+    let component = file.component.clone().expect("component info");
+    assert_eq!(component.name, "Button");
+    assert_eq!(component.props, vec!["label", "disabled"]);
+    assert_eq!(component.emits, vec!["press"]);
+    assert_eq!(component.slots, vec!["icon", "default"]);
+    assert!(file.find_export("SIZES").is_some());
+}
+
+#[test]
+fn test_index_module_lexical_fallback() {
+    let index = WorkspaceIndex::new();
+    let source = "export function createSignal<T>(value: T): [Getter<T>, Setter<T>] {\n  return [] as any;\n}\nexport * from './vdom';\n";
+    let file = index.update(Url::parse("file:///project/core/index.ts").unwrap(), source);
+
+    let symbol = file.find_export("createSignal").expect("export found");
+    assert_eq!(symbol.range.start.line, 0);
+    assert_eq!(file.re_exports.len(), 1);
+    assert_eq!(file.re_exports[0].source, "./vdom");
+
+    let source = "export const con = 1;\nexport function f<T>(value: T): T {}\n\u{3000}export let ünï = 2;\n";
+    let file = index.update(Url::parse("file:///project/core/names.ts").unwrap(), source);
+    let columns = |name: &str| file.find_export(name).map(|s| (s.range.start.character, s.range.end.character));
+    assert_eq!(columns("con"), Some((13, 16)));
+    assert_eq!(columns("f"), Some((16, 17)));
+    assert_eq!(columns("ünï"), Some((12, 15)));
+}
+
+#[test]
+fn test_workspace_symbol_and_references() {
+    let index = WorkspaceIndex::new();
+    index.update(Url::parse("file:///project/src/Button.hxo").unwrap(), BUTTON);
+    index.update(Url::parse("file:///project/src/App.hxo").unwrap(), APP);
+
+    let symbols = index.search("btn");
+    assert!(symbols.iter().any(|s| s.name == "Button"));
+    assert!(index.search("sizes").iter().any(|s| s.name == "SIZES"));
+
+    let references = index.references("Button");
+    assert_eq!(references.len(), 2);
+    assert!(references.iter().all(|l| l.uri.path().ends_with("App.hxo")));
+    let tag = references.iter().find(|l| l.range.start.line == 4).expect("tag usage");
+    assert_eq!(tag.range.start.character, 8);
+
+    index.remove(&Url::parse("file:///project/src/App.hxo").unwrap());
+    assert!(index.references("Button").is_empty());
+}
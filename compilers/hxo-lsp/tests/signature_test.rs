@@ -0,0 +1,81 @@
+use hxo_lsp::{CallSite, call_context_at, call_site_at, reactive_hints, signature_help};
+use hxo_parser::{ParseState, TemplateParser as _};
+use hxo_parser_template::TemplateParser;
+use tower_lsp::lsp_types::{InlayHintLabel, Position};
+
+const COUNTER: &str = r#"<script>
+const [count, setCount] = createSignal(0);
+const double = createComputed(() => count() * 2);
+function select(item, index) { setCount(index); }
+</script>
+<template>
+  <p :title="double">{{ count + 1 }}</p>
+</template>
+"#;
+
+#[test]
+fn test_call_context_in_event_handler() {
+    let source = r#"<button @click="select(item, "#;
+    let context = call_context_at(source, source.len(), CallSite::Attribute).unwrap();
+    assert_eq!(context.callee, "select");
+    assert_eq!(context.active_parameter, 1);
+
+    let nested = "{{ format(items[0], [1, 2], ";
+    let context = call_context_at(nested, nested.len(), CallSite::Interpolation).unwrap();
+    assert_eq!(context.callee, "format");
+    assert_eq!(context.active_parameter, 2);
+
+    assert!(call_context_at("{{ done() }} text", 17, CallSite::Interpolation).is_none());
+    assert!(call_context_at(r#"<p title="x" :a="b"#, 19, CallSite::Attribute).is_none());
+}
+
+#[test]
+fn test_call_context_in_script() {
+    let source = "<template><p :title=\"a\"></p></template>\n<script>\nconst s = useFoo(\"a\", { x: { y } }, ";
+    assert_eq!(call_site_at(source, source.len()), CallSite::Script);
+    let context = call_context_at(source, source.len(), CallSite::Script).unwrap();
+    assert_eq!((context.callee.as_str(), context.active_parameter), ("useFoo", 2));
+
+    let context = call_context_at(r#"t("key", "#, 9, CallSite::Script).unwrap();
+    assert_eq!((context.callee.as_str(), context.active_parameter), ("t", 1));
+
+    assert_eq!(call_site_at(COUNTER, COUNTER.find("double\">").unwrap()), CallSite::Attribute);
+    assert_eq!(call_site_at(COUNTER, COUNTER.find("count + 1").unwrap()), CallSite::Interpolation);
+}
+
+#[test]
+fn test_signature_help_local_and_core() {
+    let program = hxo_parser_expression::parse_program("function select(item, index) { }").unwrap();
+    let context = call_context_at("select(a, ", 10, CallSite::Script).unwrap();
+    let help = signature_help(&context, Some(&program)).unwrap();
+    assert_eq!(help.signatures[0].label, "select(item, index)");
+    assert_eq!(help.active_parameter, Some(1));
+
+    let context = call_context_at("createSignal(", 13, CallSite::Script).unwrap();
+    let help = signature_help(&context, None).unwrap();
+    assert_eq!(help.signatures[0].label, "createSignal<T>(value: T): [Getter<T>, Setter<T>]");
+}
+
+#[test]
+fn test_reactive_inlay_hints() {
+    let mut state = ParseState::new(COUNTER);
+    let nodes = TemplateParser.parse(&mut state, "html").unwrap();
+    let hints = reactive_hints(COUNTER, &nodes);
+
+    let labels: Vec<(u32, u32, String)> = hints
+        .iter()
+        .map(|h| match &h.label {
+            InlayHintLabel::String(s) => (h.position.line, h.position.character, s.clone()),
+            _ => unreachable!(),
+        })
+        .collect();
+
+    assert!(labels.contains(&(1, 12, ": signal".to_string())));
+    assert!(labels.contains(&(2, 12, ": computed".to_string())));
+    // `:title="double"` and `{{ count + 1 }}`
+    assert!(labels.contains(&(6, 19, "()".to_string())));
+    assert!(labels.contains(&(6, 29, "()".to_string())));
+    // `count()` inside the computed body is an explicit call, not an implicit unwrap
+    assert_eq!(hints.iter().filter(|h| h.position.line == 2).count(), 1);
+    assert!(hints.iter().all(|h| h.position != Position { line: 0, character: 0 }));
+}
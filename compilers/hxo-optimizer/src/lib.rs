@@ -1,7 +1,72 @@
-use hxo_ir::{AttributeIR, IRModule, JsExpr, JsStmt, TemplateNodeIR};
-use hxo_parser_tailwind::StyleEngine;
-use hxo_types::{HxoValue, Result};
-use std::collections::HashMap;
+use hxo_ir::{AttributeIR, I18nMessage, IRModule, JsExpr, JsStmt, StyleIR, Stylesheet, TemplateNodeIR};
+use hxo_parser_tailwind::{StyleEngine, TailwindConfig};
+use hxo_target_js::js_string;
+use hxo_types::{Error, HxoValue, Result, Span};
+use std::{collections::HashMap, sync::Arc};
+
+/// How scoped-style attribute names are derived.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeIdOptions {
+    /// Attribute prefix, e.g. `data-h-` gives `data-h-1a2b3c4d`.
+    pub prefix: String,
+    /// Number of hex digits kept from the hash (clamped to 4..=16).
+    pub length: usize,
+    /// Mix the component source into the hash, so edits produce a new id.
+    pub hash_content: bool,
+}
+
+impl Default for ScopeIdOptions {
+    fn default() -> Self {
+        Self { prefix: "data-h-".to_string(), length: 8, hash_content: false }
+    }
+}
+
+/// Derives the scope attribute for a component from its project-relative path (and, when
+/// `options.hash_content` is set, its source).
+///
+/// The hash is 64-bit FNV-1a over the path with `\` normalized to `/` and any leading `./` removed,
+/// followed by a NUL byte and the source. FNV-1a is fully specified, so the same file gets the same id
+/// on every machine, toolchain and build, which keeps SSR markup and the client bundle in agreement.
+pub fn scope_id(path: &str, source: Option<&str>, options: &ScopeIdOptions) -> String {
+    let normalized = path.replace('\\', "/");
+    let normalized = normalized.trim_start_matches("./");
+
+    let mut hash = fnv1a(FNV_OFFSET_BASIS, normalized.as_bytes());
+    if options.hash_content {
+        if let Some(source) = source {
+            hash = fnv1a(fnv1a(hash, &[0]), source.as_bytes());
+        }
+    }
+
+    let hex = format!("{:016x}", hash);
+    format!("{}{}", options.prefix, &hex[..options.length.clamp(4, 16)])
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// The style's AST, parsing `code` when the style parser did not produce one.
+fn stylesheet_mut(style: &mut StyleIR) -> Result<&mut Stylesheet> {
+    if style.stylesheet.is_none() {
+        style.stylesheet = Some(hxo_parser_css::parse_stylesheet(&style.code)?.without_spans());
+    }
+    Ok(style.stylesheet.as_mut().expect("parsed above"))
+}
+
+/// Keeps `code` in step with the transformed AST for consumers that read the text.
+fn reprint(style: &mut StyleIR) {
+    if let Some(sheet) = &style.stylesheet {
+        style.code = sheet.to_css(false);
+    }
+}
 
 pub struct Optimizer {
     pub style_engine: StyleEngine,
@@ -18,21 +83,33 @@ impl Optimizer {
         Self { style_engine: StyleEngine::new() }
     }
 
-    pub fn optimize(&mut self, ir: &mut IRModule, locale: Option<&str>, is_prod: bool) {
-        // 1. Static analysis
+    /// An optimizer whose utility classes resolve against a project theme.
+    pub fn with_tailwind_config(config: Arc<TailwindConfig>) -> Self {
+        Self { style_engine: StyleEngine::with_config(config) }
+    }
+
+    /// With a `locale`, `$t` calls are replaced by that locale's messages (see [`Self::optimize_i18n`]);
+    /// a call without a message fails the build. The message table is dropped when no call is left.
+    pub fn optimize(&mut self, ir: &mut IRModule, locale: Option<&str>, is_prod: bool) -> Result<()> {
+        // 1. i18n inlining, first so that inlined text counts as static
+        if let Some(locale) = locale {
+            let messages = ir.i18n.as_ref().and_then(|i18n| i18n.get(locale)).cloned().unwrap_or_default();
+            let problems = Self::optimize_i18n(ir, locale, &messages);
+            if let Some((_, span)) = problems.first() {
+                let list = problems.iter().map(|(problem, _)| problem.as_str()).collect::<Vec<_>>().join(", ");
+                return Err(Error::invalid_i18n(format!("messages for locale `{}`: {}", locale, list), *span));
+            }
+            // Calls with computed keys still translate at runtime, from this locale's messages only
+            ir.i18n = Self::uses_i18n_runtime(ir).then(|| HashMap::from([(locale.to_string(), messages)]));
+        }
+
+        // 2. Static analysis
         if let Some(template) = &mut ir.template {
             for node in &mut template.nodes {
                 Self::optimize_node(node);
             }
         }
 
-        // 2. i18n optimization
-        if let Some(_locale_val) = locale {
-            // Load messages for locale
-            let messages = HashMap::new(); // In real case, load from locale files
-            Self::optimize_i18n(ir, &messages);
-        }
-
         // 3. Call count tracking for inlining decisions
         let mut call_counts = HashMap::new();
         if let Some(script) = &ir.script {
@@ -47,6 +124,7 @@ impl Optimizer {
         if is_prod {
             // Additional production optimizations using call_counts etc.
         }
+        Ok(())
     }
 
     pub fn process_styles(&mut self, ir: &IRModule) -> Result<()> {
@@ -59,9 +137,25 @@ impl Optimizer {
         }
 
         for style in &ir.styles {
-            // All style parsers (css, scss, tailwind, etc.) return CSS code.
-            // We just need to collect it.
-            self.style_engine.add_raw_css(&style.code);
+            // All style parsers (css, scss, tailwind, etc.) return CSS code; `@layer` blocks are
+            // ordered together with the utilities.
+            match &style.stylesheet {
+                Some(sheet) => self.style_engine.add_stylesheet(sheet),
+                None => self.style_engine.add_css(&style.code)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Expands `@apply`, `theme()` and `@screen` in `<style>` blocks against the project theme. Runs
+    /// before scoping and CSS Modules so the rules it generates are transformed like handwritten ones.
+    pub fn expand_style_directives(&mut self, ir: &mut IRModule) -> Result<()> {
+        for style in &mut ir.styles {
+            if !["@apply", "@screen", "theme("].iter().any(|d| style.code.contains(d)) {
+                continue;
+            }
+            self.style_engine.expand_stylesheet(stylesheet_mut(style)?)?;
+            reprint(style);
         }
         Ok(())
     }
@@ -134,7 +228,7 @@ impl Optimizer {
         Ok(())
     }
 
-    pub fn apply_scope_id(&mut self, ir: &mut IRModule, scope_id: &str) {
+    pub fn apply_scope_id(&mut self, ir: &mut IRModule, scope_id: &str) -> Result<()> {
         if let Some(template) = &mut ir.template {
             Self::apply_scope_id_to_nodes(&mut template.nodes, scope_id);
         }
@@ -142,20 +236,28 @@ impl Optimizer {
         // Also transform scoped styles
         for style in &mut ir.styles {
             if style.scoped {
-                style.code = self.transform_scoped_css(&style.code, scope_id);
+                hxo_parser_css::scope_stylesheet(stylesheet_mut(style)?, scope_id)?;
+                reprint(style);
             }
         }
+        Ok(())
     }
 
-    pub fn generate_scope_id(&self, name: &str) -> String {
-        use std::{
-            collections::hash_map::DefaultHasher,
-            hash::{Hash, Hasher},
-        };
-        let mut hasher = DefaultHasher::new();
-        name.hash(&mut hasher);
-        let hash = hasher.finish();
-        format!("data-h-{:x}", hash)
+    /// Compiles `<style module>` blocks: class names get the `_{hash}` suffix and the class map is
+    /// stored on the style for the backends to expose as `$style` (or the named binding).
+    pub fn apply_css_modules(&mut self, ir: &mut IRModule, hash: &str) -> Result<()> {
+        for style in &mut ir.styles {
+            if style.module.is_some() {
+                style.classes = hxo_parser_css::localize_stylesheet(stylesheet_mut(style)?, hash)?;
+                reprint(style);
+            }
+        }
+        Ok(())
+    }
+
+    /// Scope id for a component with default [`ScopeIdOptions`]; see [`scope_id`].
+    pub fn generate_scope_id(&self, path: &str) -> String {
+        scope_id(path, None, &ScopeIdOptions::default())
     }
 
     pub fn get_css(&self) -> String {
@@ -199,33 +301,6 @@ impl Optimizer {
         }
     }
 
-    fn transform_scoped_css(&self, css: &str, scope_id: &str) -> String {
-        // Simple transformation: append [data-h-xxxx] to each selector
-        let mut result = String::new();
-        for line in css.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-            if line.contains('{') && !line.starts_with('@') {
-                let parts: Vec<&str> = line.split('{').collect();
-                let selectors: Vec<&str> = parts[0].split(',').collect();
-                let scoped_selectors: Vec<String> = selectors.iter().map(|s| format!("{}[{}]", s.trim(), scope_id)).collect();
-                result.push_str(&scoped_selectors.join(", "));
-                result.push_str(" {");
-                if parts.len() > 1 {
-                    result.push_str(parts[1]);
-                }
-                result.push('\n');
-            }
-            else {
-                result.push_str(line);
-                result.push('\n');
-            }
-        }
-        result
-    }
-
     fn track_script_calls(script: &hxo_ir::JsProgram, counts: &mut HashMap<String, usize>) {
         for stmt in &script.body {
             Self::track_stmt_calls(stmt, counts);
@@ -340,43 +415,127 @@ impl Optimizer {
         }
     }
 
-    pub fn optimize_i18n(ir: &mut IRModule, messages: &HashMap<String, String>) {
-        // Optimize template
+    /// Inlines `$t('key')` with its message from `messages`, and `$t('key', { name })` as a template
+    /// literal filling the message's `{name}` placeholders. Formatter messages become a call of the
+    /// formatter with the parameters and `locale`. Calls that can't be inlined are kept and reported:
+    /// keys without a message and placeholders the call does not pass.
+    pub fn optimize_i18n(ir: &mut IRModule, locale: &str, messages: &HashMap<String, I18nMessage>) -> Vec<(String, Span)> {
+        let mut problems = Vec::new();
         if let Some(template) = &mut ir.template {
             for node in &mut template.nodes {
-                Self::optimize_node_i18n(node, messages);
+                Self::optimize_node_i18n(node, locale, messages, &mut problems);
             }
         }
-        // Optimize script (simplified: only handle simple $t('key') calls)
         if let Some(script) = &mut ir.script {
             for stmt in &mut script.body {
-                Self::optimize_stmt_i18n(stmt, messages);
+                Self::optimize_stmt_i18n(stmt, locale, messages, &mut problems);
             }
         }
+        problems
     }
 
-    fn optimize_node_i18n(node: &mut TemplateNodeIR, messages: &HashMap<String, String>) {
+    /// Whether the module still calls `$t` at runtime, after inlining.
+    pub fn uses_i18n_runtime(ir: &IRModule) -> bool {
+        let template = ir.template.as_ref().is_some_and(|template| template.nodes.iter().any(Self::node_uses_t));
+        let script = ir.script.as_ref().is_some_and(|script| script.body.iter().any(Self::stmt_uses_t));
+        template || script
+    }
+
+    /// Message keys of the `$t` calls with a literal key, in source order with their call spans.
+    pub fn collect_i18n_keys(ir: &IRModule) -> Vec<(String, Span)> {
+        let mut keys = Vec::new();
+        if let Some(template) = &ir.template {
+            for node in &template.nodes {
+                Self::collect_node_keys(node, &mut keys);
+            }
+        }
+        if let Some(script) = &ir.script {
+            for stmt in &script.body {
+                Self::collect_stmt_keys(stmt, &mut keys);
+            }
+        }
+        keys
+    }
+
+    fn collect_node_keys(node: &TemplateNodeIR, keys: &mut Vec<(String, Span)>) {
+        match node {
+            TemplateNodeIR::Element(el) => {
+                for ast in el.attributes.iter().filter_map(|attr| attr.value_ast.as_ref()) {
+                    collect_expr_keys(ast, keys);
+                }
+                for child in &el.children {
+                    Self::collect_node_keys(child, keys);
+                }
+            }
+            TemplateNodeIR::Interpolation(expr) => {
+                if let Some(ast) = &expr.ast {
+                    collect_expr_keys(ast, keys);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_stmt_keys(stmt: &JsStmt, keys: &mut Vec<(String, Span)>) {
+        match stmt {
+            JsStmt::Expr(expr, _) | JsStmt::VariableDecl { init: Some(expr), .. } => collect_expr_keys(expr, keys),
+            JsStmt::FunctionDecl { body, .. } => {
+                for s in body {
+                    Self::collect_stmt_keys(s, keys);
+                }
+            }
+            JsStmt::Export { declaration, .. } => Self::collect_stmt_keys(declaration, keys),
+            _ => {}
+        }
+    }
+
+    fn optimize_node_i18n(
+        node: &mut TemplateNodeIR,
+        locale: &str,
+        messages: &HashMap<String, I18nMessage>,
+        problems: &mut Vec<(String, Span)>,
+    ) {
         match node {
             TemplateNodeIR::Element(el) => {
                 for attr in &mut el.attributes {
-                    if let Some(ast) = &mut attr.value_ast {
-                        Self::optimize_expr_i18n(ast, messages);
-                        // Update value if it's now a literal
-                        if let JsExpr::Literal(HxoValue::String(s), _) = ast {
-                            attr.value = Some(format!("'{}'", s));
+                    let Some(ast) = &attr.value_ast
+                    else {
+                        continue;
+                    };
+                    let Some(name) = attr.name.strip_prefix(':')
+                    else {
+                        continue;
+                    };
+                    // A message that became a plain string replaces the binding with a static attribute;
+                    // any other result stays bound, to the inlined expression.
+                    let mut inlined = ast.clone();
+                    Self::optimize_expr_i18n(&mut inlined, locale, messages, problems);
+                    if let JsExpr::Literal(HxoValue::String(s), _) = &inlined {
+                        attr.name = name.to_string();
+                        attr.value = Some(unescape_string(s));
+                        attr.value_ast = None;
+                        attr.is_dynamic = false;
+                        attr.is_directive = false;
+                    }
+                    else if inlined != *ast {
+                        if let Some(code) = expr_code(&inlined) {
+                            attr.value = Some(code);
+                            attr.value_ast = Some(inlined);
                         }
                     }
                 }
                 for child in &mut el.children {
-                    Self::optimize_node_i18n(child, messages);
+                    Self::optimize_node_i18n(child, locale, messages, problems);
                 }
             }
             TemplateNodeIR::Interpolation(expr) => {
-                if let Some(ast) = &mut expr.ast {
-                    Self::optimize_expr_i18n(ast, messages);
-                    // Simplified: update code string if it's now a literal
-                    if let JsExpr::Literal(HxoValue::String(s), _) = ast {
-                        expr.code = format!("'{}'", s);
+                // SSR and hydration print `code`, so the call stays unless the result can be printed
+                if let Some(ast) = &expr.ast {
+                    let mut inlined = ast.clone();
+                    Self::optimize_expr_i18n(&mut inlined, locale, messages, problems);
+                    if let Some(code) = expr_code(&inlined) {
+                        expr.code = code;
+                        expr.ast = Some(inlined);
                     }
                 }
             }
@@ -384,59 +543,114 @@ impl Optimizer {
         }
     }
 
-    fn optimize_stmt_i18n(stmt: &mut JsStmt, messages: &HashMap<String, String>) {
+    fn optimize_stmt_i18n(
+        stmt: &mut JsStmt,
+        locale: &str,
+        messages: &HashMap<String, I18nMessage>,
+        problems: &mut Vec<(String, Span)>,
+    ) {
         match stmt {
-            JsStmt::Expr(expr, _) => Self::optimize_expr_i18n(expr, messages),
+            JsStmt::Expr(expr, _) => Self::optimize_expr_i18n(expr, locale, messages, problems),
             JsStmt::VariableDecl { init: Some(expr), .. } => {
-                Self::optimize_expr_i18n(expr, messages);
+                Self::optimize_expr_i18n(expr, locale, messages, problems);
             }
             JsStmt::FunctionDecl { body, .. } => {
                 for s in body {
-                    Self::optimize_stmt_i18n(s, messages);
+                    Self::optimize_stmt_i18n(s, locale, messages, problems);
                 }
             }
             JsStmt::Export { declaration, .. } => {
-                Self::optimize_stmt_i18n(declaration, messages);
+                Self::optimize_stmt_i18n(declaration, locale, messages, problems);
             }
             _ => {}
         }
     }
 
-    fn optimize_expr_i18n(expr: &mut JsExpr, messages: &HashMap<String, String>) {
+    fn optimize_expr_i18n(
+        expr: &mut JsExpr,
+        locale: &str,
+        messages: &HashMap<String, I18nMessage>,
+        problems: &mut Vec<(String, Span)>,
+    ) {
         match expr {
-            JsExpr::Call { callee, args, .. } => {
-                if let JsExpr::Identifier(id, _) = &**callee {
-                    if id == "$t" && args.len() == 1 {
-                        if let JsExpr::Literal(HxoValue::String(key), _) = &args[0] {
-                            if let Some(translated) = messages.get(key) {
-                                *expr = JsExpr::Literal(HxoValue::String(translated.clone()), hxo_types::Span::unknown());
+            JsExpr::Call { callee, args, span } => {
+                let is_t = matches!(&**callee, JsExpr::Identifier(id, _) if id == "$t");
+                if let (true, Some(JsExpr::Literal(HxoValue::String(key), _))) = (is_t && args.len() <= 2, args.first()) {
+                    let span = *span;
+                    match messages.get(key) {
+                        Some(I18nMessage::Formatter(code)) => {
+                            *expr = formatter_call(code, args.get(1), locale, span);
+                            return;
+                        }
+                        Some(I18nMessage::Text(message)) => match message_expr(message, args.get(1), span) {
+                            Ok(inlined) => {
+                                *expr = inlined;
                                 return;
                             }
-                        }
+                            Err(placeholder) => {
+                                problems.push((format!("`{}` needs the parameter `{}`", key, placeholder), span));
+                            }
+                        },
+                        None => problems.push((format!("`{}` has no message", key), span)),
                     }
                 }
                 for arg in args {
-                    Self::optimize_expr_i18n(arg, messages);
+                    Self::optimize_expr_i18n(arg, locale, messages, problems);
                 }
             }
             JsExpr::Binary { left, right, .. } => {
-                Self::optimize_expr_i18n(left, messages);
-                Self::optimize_expr_i18n(right, messages);
-            }
-            JsExpr::Array(elements, _) => {
+                Self::optimize_expr_i18n(left, locale, messages, problems);
+                Self::optimize_expr_i18n(right, locale, messages, problems);
+            }
+            JsExpr::Unary { argument, .. } => Self::optimize_expr_i18n(argument, locale, messages, problems),
+            JsExpr::Member { object, .. } => Self::optimize_expr_i18n(object, locale, messages, problems),
+            JsExpr::Conditional { test, consequent, alternate, .. } => {
+                Self::optimize_expr_i18n(test, locale, messages, problems);
+                Self::optimize_expr_i18n(consequent, locale, messages, problems);
+                Self::optimize_expr_i18n(alternate, locale, messages, problems);
+            }
+            JsExpr::ArrowFunction { body, .. } => Self::optimize_expr_i18n(body, locale, messages, problems),
+            JsExpr::TemplateLiteral { expressions: elements, .. } | JsExpr::Array(elements, _) => {
                 for el in elements {
-                    Self::optimize_expr_i18n(el, messages);
+                    Self::optimize_expr_i18n(el, locale, messages, problems);
                 }
             }
             JsExpr::Object(props, _) => {
                 for val in props.values_mut() {
-                    Self::optimize_expr_i18n(val, messages);
+                    Self::optimize_expr_i18n(val, locale, messages, problems);
                 }
             }
             _ => {}
         }
     }
 
+    fn node_uses_t(node: &TemplateNodeIR) -> bool {
+        match node {
+            TemplateNodeIR::Element(el) => {
+                let attrs = el.attributes.iter().any(|attr| match &attr.value_ast {
+                    Some(ast) => expr_uses_t(ast),
+                    None => attr.is_dynamic && attr.value.as_deref().is_some_and(|value| value.contains("$t")),
+                });
+                attrs || el.children.iter().any(Self::node_uses_t)
+            }
+            TemplateNodeIR::Interpolation(expr) => match &expr.ast {
+                Some(ast) => expr_uses_t(ast),
+                None => expr.code.contains("$t"),
+            },
+            _ => false,
+        }
+    }
+
+    fn stmt_uses_t(stmt: &JsStmt) -> bool {
+        match stmt {
+            JsStmt::Expr(expr, _) | JsStmt::VariableDecl { init: Some(expr), .. } => expr_uses_t(expr),
+            JsStmt::FunctionDecl { body, .. } => body.iter().any(Self::stmt_uses_t),
+            JsStmt::Export { declaration, .. } => Self::stmt_uses_t(declaration),
+            JsStmt::Other(code, _) => code.contains("$t"),
+            _ => false,
+        }
+    }
+
     fn optimize_node(node: &mut TemplateNodeIR) {
         if let TemplateNodeIR::Element(el) = node {
             // Optimize children first
@@ -451,10 +665,199 @@ impl Optimizer {
                 TemplateNodeIR::Text(_, _) => true,
                 TemplateNodeIR::Element(child_el) => child_el.is_static,
                 TemplateNodeIR::Comment(_, _) => true,
-                TemplateNodeIR::Interpolation(_) => false,
+                TemplateNodeIR::Interpolation(expr) => matches!(expr.ast, Some(JsExpr::Literal(..))),
             });
 
             el.is_static = !has_dynamic_attr && all_children_static;
         }
     }
 }
+
+/// `message` as an expression: a string literal, or a template literal taking each `{name}` placeholder
+/// from `params`, either an object literal (`{ name }`), an array literal for `{0}`, or any expression
+/// as `params.name`. Errs with the first placeholder that `params` does not provide.
+fn message_expr(message: &str, params: Option<&JsExpr>, span: Span) -> std::result::Result<JsExpr, String> {
+    let mut quasis = Vec::new();
+    let mut expressions = Vec::new();
+    let mut quasi = String::new();
+    let mut rest = message;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close)
+        else {
+            break;
+        };
+        let name = rest[open + 1..close].trim();
+        let index = name.parse::<usize>().ok();
+        if index.is_none() && !is_identifier(name) {
+            quasi.push_str(&rest[..=close]);
+            rest = &rest[close + 1..];
+            continue;
+        }
+        let value = match (params, index) {
+            (Some(JsExpr::Object(props, _)), _) => props.get(name).cloned(),
+            (Some(JsExpr::Array(items, _)), Some(index)) => items.get(index).cloned(),
+            (Some(JsExpr::Array(..)), None) | (None, _) => None,
+            (Some(params), _) => Some(JsExpr::Member {
+                object: Box::new(params.clone()),
+                property: name.to_string(),
+                computed: index.is_some(),
+                span,
+            }),
+        };
+        quasi.push_str(&rest[..open]);
+        quasis.push(escape_template(&quasi));
+        quasi.clear();
+        expressions.push(value.ok_or_else(|| name.to_string())?);
+        rest = &rest[close + 1..];
+    }
+    if expressions.is_empty() {
+        return Ok(JsExpr::Literal(HxoValue::String(escape_string(message)), span));
+    }
+    quasi.push_str(rest);
+    quasis.push(escape_template(&quasi));
+    Ok(JsExpr::TemplateLiteral { quasis, expressions, span })
+}
+
+/// Calls a compiled formatter with the parameters of a `$t` call, `{}` without any.
+fn formatter_call(code: &str, params: Option<&JsExpr>, locale: &str, span: Span) -> JsExpr {
+    let params = params.cloned().unwrap_or_else(|| JsExpr::Object(HashMap::new(), span));
+    JsExpr::Call {
+        callee: Box::new(JsExpr::Other(format!("({})", code), span)),
+        args: vec![params, JsExpr::Literal(HxoValue::String(escape_string(locale)), span)],
+        span,
+    }
+}
+
+/// Source for the expressions an inlined message is made of, `None` for anything else.
+fn expr_code(expr: &JsExpr) -> Option<String> {
+    match expr {
+        JsExpr::Identifier(id, _) => Some(id.clone()),
+        JsExpr::Literal(HxoValue::String(s), _) => Some(format!("'{}'", s)),
+        JsExpr::Literal(HxoValue::Number(n), _) => Some(n.to_string()),
+        JsExpr::Literal(HxoValue::Bool(b), _) => Some(b.to_string()),
+        JsExpr::Member { object, property, computed: false, .. } => Some(format!("{}.{}", expr_code(object)?, property)),
+        JsExpr::Member { object, property, computed: true, .. } => Some(format!("{}[{}]", expr_code(object)?, property)),
+        JsExpr::Call { callee, args, .. } => {
+            let args = args.iter().map(expr_code).collect::<Option<Vec<_>>>()?;
+            Some(format!("{}({})", expr_code(callee)?, args.join(", ")))
+        }
+        JsExpr::Object(props, _) if props.is_empty() => Some("{}".to_string()),
+        JsExpr::Object(props, _) => {
+            let mut props =
+                props.iter().map(|(key, value)| Some(format!("{}: {}", key, expr_code(value)?))).collect::<Option<Vec<_>>>()?;
+            props.sort();
+            Some(format!("{{ {} }}", props.join(", ")))
+        }
+        JsExpr::Other(code, _) => Some(code.clone()),
+        JsExpr::TemplateLiteral { quasis, expressions, .. } => {
+            let mut code = String::from("`");
+            for (i, quasi) in quasis.iter().enumerate() {
+                code.push_str(quasi);
+                if let Some(expr) = expressions.get(i) {
+                    code.push_str(&format!("${{{}}}", expr_code(expr)?));
+                }
+            }
+            code.push('`');
+            Some(code)
+        }
+        _ => None,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let is_part = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    name.starts_with(|c: char| is_part(c) && !c.is_ascii_digit()) && name.chars().all(is_part)
+}
+
+/// String literals hold their source text, which the backends print between single quotes.
+fn escape_string(text: &str) -> String {
+    let quoted = js_string(text);
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// The text of a string literal's source, for static attributes.
+fn unescape_string(source: &str) -> String {
+    let mut text = String::with_capacity(source.len());
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('r') => text.push('\r'),
+            Some('t') => text.push('\t'),
+            Some(c) => text.push(c),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+fn escape_template(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('`', "\\`").replace("${", "\\${")
+}
+
+fn collect_expr_keys(expr: &JsExpr, keys: &mut Vec<(String, Span)>) {
+    match expr {
+        JsExpr::Call { callee, args, span } => {
+            if let (JsExpr::Identifier(id, _), Some(JsExpr::Literal(HxoValue::String(key), _))) = (&**callee, args.first()) {
+                if id == "$t" {
+                    keys.push((key.clone(), *span));
+                }
+            }
+            collect_expr_keys(callee, keys);
+            for arg in args {
+                collect_expr_keys(arg, keys);
+            }
+        }
+        JsExpr::Unary { argument, .. } => collect_expr_keys(argument, keys),
+        JsExpr::Binary { left, right, .. } => {
+            collect_expr_keys(left, keys);
+            collect_expr_keys(right, keys);
+        }
+        JsExpr::Member { object, .. } => collect_expr_keys(object, keys),
+        JsExpr::Array(items, _) | JsExpr::TemplateLiteral { expressions: items, .. } => {
+            for item in items {
+                collect_expr_keys(item, keys);
+            }
+        }
+        JsExpr::Object(props, _) => {
+            for value in props.values() {
+                collect_expr_keys(value, keys);
+            }
+        }
+        JsExpr::ArrowFunction { body, .. } => collect_expr_keys(body, keys),
+        JsExpr::TseElement { children, .. } => {
+            for child in children {
+                collect_expr_keys(child, keys);
+            }
+        }
+        JsExpr::Conditional { test, consequent, alternate, .. } => {
+            collect_expr_keys(test, keys);
+            collect_expr_keys(consequent, keys);
+            collect_expr_keys(alternate, keys);
+        }
+        _ => {}
+    }
+}
+
+fn expr_uses_t(expr: &JsExpr) -> bool {
+    match expr {
+        JsExpr::Identifier(id, _) => id == "$t",
+        JsExpr::Literal(..) => false,
+        JsExpr::Unary { argument, .. } => expr_uses_t(argument),
+        JsExpr::Binary { left, right, .. } => expr_uses_t(left) || expr_uses_t(right),
+        JsExpr::Call { callee, args, .. } => expr_uses_t(callee) || args.iter().any(expr_uses_t),
+        JsExpr::Member { object, .. } => expr_uses_t(object),
+        JsExpr::Array(items, _) | JsExpr::TemplateLiteral { expressions: items, .. } => items.iter().any(expr_uses_t),
+        JsExpr::Object(props, _) => props.values().any(expr_uses_t),
+        JsExpr::ArrowFunction { body, .. } => expr_uses_t(body),
+        JsExpr::TseElement { children, .. } => children.iter().any(expr_uses_t),
+        JsExpr::Conditional { test, consequent, alternate, .. } => {
+            expr_uses_t(test) || expr_uses_t(consequent) || expr_uses_t(alternate)
+        }
+        JsExpr::Other(code, _) => code.contains("$t"),
+    }
+}
@@ -1,12 +1,12 @@
 use hxo_ir::{ElementIR, ExpressionIR, IRModule, JsExpr, TemplateIR, TemplateNodeIR};
-use hxo_optimizer::Optimizer;
+use hxo_optimizer::{Optimizer, ScopeIdOptions, scope_id};
 use hxo_types::{HxoValue, Span};
 use std::collections::HashMap;
 
 #[test]
 fn test_optimize_i18n() {
     let mut messages = HashMap::new();
-    messages.insert("hello".to_string(), "你好".to_string());
+    messages.insert("hello".to_string(), "你好".into());
 
     let mut ir = IRModule {
         name: "Test".to_string(),
@@ -23,6 +23,7 @@ fn test_optimize_i18n() {
                 }),
                 span: Span::unknown(),
             })],
+            toc: vec![],
             span: Span::unknown(),
         }),
         styles: vec![],
@@ -33,7 +34,7 @@ fn test_optimize_i18n() {
     };
 
     let _optimizer = Optimizer::new();
-    Optimizer::optimize_i18n(&mut ir, &messages);
+    Optimizer::optimize_i18n(&mut ir, "zh", &messages);
 
     if let Some(template) = &ir.template {
         if let TemplateNodeIR::Interpolation(expr) = &template.nodes[0] {
@@ -69,6 +70,7 @@ fn test_optimize_static_element() {
                 is_static: false, // Initially false
                 span: Span::unknown(),
             })],
+            toc: vec![],
             span: Span::unknown(),
         }),
         styles: vec![],
@@ -79,7 +81,7 @@ fn test_optimize_static_element() {
     };
 
     let mut optimizer = Optimizer::new();
-    optimizer.optimize(&mut ir, None, false);
+    optimizer.optimize(&mut ir, None, false).unwrap();
 
     if let Some(template) = &ir.template {
         if let TemplateNodeIR::Element(el) = &template.nodes[0] {
@@ -93,3 +95,78 @@ fn test_optimize_static_element() {
         panic!("Expected template");
     }
 }
+
+#[test]
+fn test_scope_id_is_stable() {
+    let options = ScopeIdOptions::default();
+    // FNV-1a 64 of "src/components/Button.hxo"; must never change between builds or machines.
+    let id = scope_id("src/components/Button.hxo", None, &options);
+    assert_eq!(id, scope_id("./src\\components\\Button.hxo", None, &options));
+    assert_eq!(id, "data-h-c445e093");
+
+    assert_ne!(id, scope_id("src/admin/Button.hxo", None, &options));
+    assert_eq!(Optimizer::new().generate_scope_id("src/components/Button.hxo"), id);
+
+    let hashed = ScopeIdOptions { prefix: "data-v-".to_string(), length: 6, hash_content: true };
+    let a = scope_id("Button.hxo", Some("<template/>"), &hashed);
+    assert!(a.starts_with("data-v-") && a.len() == "data-v-".len() + 6);
+    assert_ne!(a, scope_id("Button.hxo", Some("<template></template>"), &hashed));
+}
+
+#[test]
+fn test_locale_inlining_makes_text_static() {
+    let call = |key: &str, params: Option<JsExpr>| JsExpr::Call {
+        callee: Box::new(JsExpr::Identifier("$t".to_string(), Span::unknown())),
+        args: std::iter::once(JsExpr::Literal(HxoValue::String(key.to_string()), Span::unknown())).chain(params).collect(),
+        span: Span::unknown(),
+    };
+    let interpolation = |ast: JsExpr| {
+        TemplateNodeIR::Interpolation(ExpressionIR { code: String::new(), ast: Some(ast), span: Span::unknown() })
+    };
+    let params = JsExpr::Object(
+        HashMap::from([("n".to_string(), JsExpr::Identifier("count".to_string(), Span::unknown()))]),
+        Span::unknown(),
+    );
+    let element = |child: TemplateNodeIR| {
+        TemplateNodeIR::Element(ElementIR {
+            tag: "p".to_string(),
+            attributes: vec![],
+            children: vec![child],
+            is_static: false,
+            span: Span::unknown(),
+        })
+    };
+    let messages = HashMap::from([("title".to_string(), "Titel".into()), ("items".to_string(), "{n} Einträge".into())]);
+    let mut ir = IRModule {
+        name: "Test".to_string(),
+        metadata: HashMap::new(),
+        script: None,
+        script_meta: None,
+        template: Some(TemplateIR {
+            nodes: vec![element(interpolation(call("title", None))), element(interpolation(call("items", Some(params))))],
+            toc: vec![],
+            span: Span::unknown(),
+        }),
+        styles: vec![],
+        i18n: Some(HashMap::from([("de".to_string(), messages)])),
+        wasm: vec![],
+        custom_blocks: vec![],
+        span: Span::unknown(),
+    };
+
+    Optimizer::new().optimize(&mut ir, Some("de"), false).unwrap();
+
+    let nodes = &ir.template.as_ref().unwrap().nodes;
+    let (TemplateNodeIR::Element(title), TemplateNodeIR::Element(items)) = (&nodes[0], &nodes[1])
+    else {
+        panic!("Expected elements");
+    };
+    assert!(title.is_static);
+    assert!(!items.is_static);
+    let TemplateNodeIR::Interpolation(expr) = &items.children[0]
+    else {
+        panic!("Expected interpolation");
+    };
+    assert_eq!(expr.code, "`${count} Einträge`");
+    assert!(ir.i18n.is_none(), "every call was inlined");
+}
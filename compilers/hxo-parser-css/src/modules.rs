@@ -0,0 +1,163 @@
+use crate::{
+    scoped::{find_pseudo, inner_selectors, rename_animation, rename_keyframes, splice},
+    selector::{ComplexSelector, CompoundSelector, PseudoArgument, SelectorList, SimpleSelector},
+    stylesheet::parse_stylesheet,
+};
+use hxo_ir::{CssNode, Stylesheet};
+use hxo_types::{Error, Result, Span};
+use std::collections::HashMap;
+
+/// A compiled `<style module>` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssModule {
+    pub code: String,
+    /// Source class name to the space-separated class list to apply, including `composes:` targets.
+    pub classes: HashMap<String, String>,
+}
+
+/// Compiles `css` as a CSS Module: every local class becomes `{class}_{hash}`, `:global(...)` is left
+/// untouched, `@keyframes` are renamed the same way and `composes:` is folded into the class map.
+pub fn css_module(css: &str, hash: &str) -> Result<CssModule> {
+    let mut sheet = parse_stylesheet(css)?;
+    let classes = localize_stylesheet(&mut sheet, hash)?;
+    Ok(CssModule { code: sheet.to_css(false), classes })
+}
+
+/// [`css_module`] on a parsed stylesheet: localizes it in place and returns the class map.
+pub fn localize_stylesheet(sheet: &mut Stylesheet, hash: &str) -> Result<HashMap<String, String>> {
+    let mut keyframes = HashMap::new();
+    rename_keyframes(&mut sheet.nodes, hash, &mut keyframes);
+
+    let mut classes = HashMap::new();
+    let mut composes = Vec::new();
+    localize_nodes(&mut sheet.nodes, hash, &keyframes, &mut classes, &mut composes)?;
+
+    for (class, extra) in composes {
+        let entry = classes.entry(class.clone()).or_insert_with(|| local_name(&class, hash));
+        entry.push(' ');
+        entry.push_str(&extra);
+    }
+    Ok(classes)
+}
+
+fn local_name(class: &str, hash: &str) -> String {
+    format!("{}_{}", class, hash)
+}
+
+fn localize_nodes(
+    nodes: &mut [CssNode],
+    hash: &str,
+    keyframes: &HashMap<String, String>,
+    classes: &mut HashMap<String, String>,
+    composes: &mut Vec<(String, String)>,
+) -> Result<()> {
+    for node in nodes.iter_mut() {
+        match node {
+            CssNode::Rule(rule) => {
+                let list = SelectorList::parse(&rule.selector)?;
+                let mut subjects = Vec::new();
+                for complex in &list.0 {
+                    if let [CompoundSelector(simple)] = complex.compounds.as_slice() {
+                        if let [SimpleSelector::Class(name)] = simple.as_slice() {
+                            subjects.push(name.clone());
+                        }
+                    }
+                }
+                rule.selector = localize_list(&list, hash, classes).to_string();
+
+                let mut composed = Vec::new();
+                rule.children.retain(|child| match child {
+                    CssNode::Declaration(decl) if decl.property.eq_ignore_ascii_case("composes") => {
+                        composed.push(decl.value.clone());
+                        false
+                    }
+                    _ => true,
+                });
+                for value in composed {
+                    if subjects.is_empty() || subjects.len() != list.0.len() {
+                        return Err(Error::parse_error(
+                            format!(
+                                "`composes` is only allowed in rules whose selectors are single classes: `{}`",
+                                rule.selector
+                            ),
+                            rule.span,
+                        ));
+                    }
+                    let extra = composed_classes(&value, hash, classes)?;
+                    for subject in &subjects {
+                        composes.push((subject.clone(), extra.clone()));
+                    }
+                }
+
+                localize_nodes(&mut rule.children, hash, keyframes, classes, composes)?;
+            }
+            CssNode::AtRule(rule) => {
+                if rule.unprefixed_name() != "keyframes" {
+                    if let Some(children) = &mut rule.block {
+                        localize_nodes(children, hash, keyframes, classes, composes)?;
+                    }
+                }
+            }
+            CssNode::Declaration(decl) => {
+                let property = decl.property.to_ascii_lowercase();
+                let property = property.trim_start_matches("-webkit-").trim_start_matches("-moz-");
+                if !keyframes.is_empty() && (property == "animation" || property == "animation-name") {
+                    decl.value = rename_animation(&decl.value, keyframes);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `composes: a b;`, `composes: a from global;`. Composing from another file is not supported.
+fn composed_classes(value: &str, hash: &str, classes: &mut HashMap<String, String>) -> Result<String> {
+    let (names, source) = match value.split_once(" from ") {
+        Some((names, source)) => (names, Some(source.trim())),
+        None => (value, None),
+    };
+    let names = names.split_whitespace();
+    match source {
+        None => Ok(names
+            .map(|name| classes.entry(name.to_string()).or_insert_with(|| local_name(name, hash)).clone())
+            .collect::<Vec<_>>()
+            .join(" ")),
+        Some("global") => Ok(names.collect::<Vec<_>>().join(" ")),
+        Some(file) => Err(Error::parse_error(
+            format!("`composes: ... from {}` is not supported, only local classes and `from global`", file),
+            Span::unknown(),
+        )),
+    }
+}
+
+fn localize_list(list: &SelectorList, hash: &str, classes: &mut HashMap<String, String>) -> SelectorList {
+    SelectorList(list.0.iter().flat_map(|complex| unwrap_global(localize_complex(complex, hash, classes))).collect())
+}
+
+fn localize_complex(complex: &ComplexSelector, hash: &str, classes: &mut HashMap<String, String>) -> ComplexSelector {
+    let mut out = complex.clone();
+    for compound in &mut out.compounds {
+        for simple in &mut compound.0 {
+            match simple {
+                SimpleSelector::Class(name) => {
+                    *name = classes.entry(name.clone()).or_insert_with(|| local_name(name, hash)).clone();
+                }
+                SimpleSelector::PseudoClass { name, argument: Some(PseudoArgument::Selector(list)) }
+                    if !name.eq_ignore_ascii_case("global") =>
+                {
+                    *list = localize_list(list, hash, classes);
+                }
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+fn unwrap_global(complex: ComplexSelector) -> Vec<ComplexSelector> {
+    let Some((ci, si)) = find_pseudo(&complex, &["global"])
+    else {
+        return vec![complex];
+    };
+    inner_selectors(&complex, ci, si).iter().flat_map(|inner| unwrap_global(splice(&complex, ci, si, inner))).collect()
+}
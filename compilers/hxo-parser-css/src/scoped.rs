@@ -0,0 +1,241 @@
+use crate::{
+    selector::{Combinator, ComplexSelector, CompoundSelector, PseudoArgument, SelectorList, SimpleSelector},
+    stylesheet::parse_stylesheet,
+};
+use hxo_ir::{CssNode, Stylesheet};
+use hxo_types::Result;
+use std::collections::HashMap;
+
+/// At-rules whose block holds ordinary style rules that still need scoping.
+const CONDITIONAL_AT_RULES: [&str; 6] = ["media", "supports", "container", "layer", "document", "starting-style"];
+
+/// Rewrites `css` so that every rule only matches elements carrying the `scope_id` attribute.
+///
+/// The attribute lands on the subject compound, before any pseudo-element (`.a::before` →
+/// `.a[data-h-x]::before`). `:deep(x)` scopes the compound before it and leaves `x` unscoped,
+/// `:slotted(x)` scopes `x` itself, since slot content in the template carries the attribute as well,
+/// and `:global(x)` opts the whole selector out. `@keyframes` names get the scope suffix and
+/// `animation`/`animation-name` follow.
+pub fn scope_css(css: &str, scope_id: &str) -> Result<String> {
+    let mut sheet = parse_stylesheet(css)?;
+    scope_stylesheet(&mut sheet, scope_id)?;
+    Ok(sheet.to_css(false))
+}
+
+/// [`scope_css`] on a parsed stylesheet, in place.
+pub fn scope_stylesheet(sheet: &mut Stylesheet, scope_id: &str) -> Result<()> {
+    let suffix = scope_id.strip_prefix("data-").unwrap_or(scope_id);
+
+    let mut keyframes = HashMap::new();
+    rename_keyframes(&mut sheet.nodes, suffix, &mut keyframes);
+    scope_nodes(&mut sheet.nodes, scope_id, &keyframes)
+}
+
+/// Scopes a single selector list, e.g. `.a, .b :deep(.c)`.
+pub fn scope_selector(selector: &str, scope_id: &str) -> Result<String> {
+    let list = SelectorList::parse(selector)?;
+    Ok(scope_list(&list, scope_id).to_string())
+}
+
+pub(crate) fn rename_keyframes(nodes: &mut [CssNode], suffix: &str, renamed: &mut HashMap<String, String>) {
+    for node in nodes {
+        match node {
+            CssNode::AtRule(rule) if rule.unprefixed_name() == "keyframes" => {
+                let name = rule.prelude.trim_matches(|c| c == '"' || c == '\'').to_string();
+                if name.is_empty() {
+                    continue;
+                }
+                let scoped = renamed.entry(name.clone()).or_insert_with(|| format!("{}-{}", name, suffix));
+                rule.prelude = scoped.clone();
+            }
+            CssNode::AtRule(rule) => {
+                if let Some(children) = &mut rule.block {
+                    rename_keyframes(children, suffix, renamed);
+                }
+            }
+            CssNode::Rule(rule) => rename_keyframes(&mut rule.children, suffix, renamed),
+            CssNode::Declaration(_) => {}
+        }
+    }
+}
+
+fn scope_nodes(nodes: &mut [CssNode], scope_id: &str, keyframes: &HashMap<String, String>) -> Result<()> {
+    for node in nodes {
+        match node {
+            CssNode::Rule(rule) => {
+                let list = SelectorList::parse(&rule.selector)?;
+                rule.selector = scope_list(&list, scope_id).to_string();
+                scope_nodes(&mut rule.children, scope_id, keyframes)?;
+            }
+            CssNode::AtRule(rule) => {
+                let name = rule.unprefixed_name().to_ascii_lowercase();
+                if let Some(children) = &mut rule.block {
+                    if CONDITIONAL_AT_RULES.contains(&name.as_str()) {
+                        scope_nodes(children, scope_id, keyframes)?;
+                    }
+                }
+            }
+            CssNode::Declaration(decl) => {
+                let property = decl.property.to_ascii_lowercase();
+                let property = property.trim_start_matches("-webkit-").trim_start_matches("-moz-");
+                if !keyframes.is_empty() && (property == "animation" || property == "animation-name") {
+                    decl.value = rename_animation(&decl.value, keyframes);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn rename_animation(value: &str, keyframes: &HashMap<String, String>) -> String {
+    if !value.split(|c: char| c == ',' || c.is_whitespace()).any(|t| keyframes.contains_key(t)) {
+        return value.to_string();
+    }
+    value
+        .split(',')
+        .map(|part| {
+            part.split_whitespace().map(|t| keyframes.get(t).map(String::as_str).unwrap_or(t)).collect::<Vec<_>>().join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn scope_list(list: &SelectorList, scope_id: &str) -> SelectorList {
+    SelectorList(list.0.iter().flat_map(|s| scope_complex(s, scope_id)).collect())
+}
+
+fn scope_complex(selector: &ComplexSelector, scope_id: &str) -> Vec<ComplexSelector> {
+    let attribute = SimpleSelector::Attribute(scope_id.to_string());
+
+    if let Some((ci, si)) = find_pseudo(selector, &["global"]) {
+        return inner_selectors(selector, ci, si).iter().map(|inner| splice(selector, ci, si, inner)).collect();
+    }
+
+    if let Some((ci, si)) = find_pseudo(selector, &["slotted"]) {
+        return inner_selectors(selector, ci, si)
+            .into_iter()
+            .map(|mut inner| {
+                if let Some(last) = inner.compounds.last_mut() {
+                    last.insert_before_pseudos(attribute.clone());
+                }
+                splice(selector, ci, si, &inner)
+            })
+            .collect();
+    }
+
+    if let Some((ci, si)) = find_pseudo(selector, &["deep", "v-deep"]) {
+        let mut rest = selector.compounds[ci].clone();
+        rest.0.remove(si);
+
+        // Everything up to the `:deep()` is the scoped part; its last compound gets the attribute.
+        let mut head = ComplexSelector {
+            leading: selector.leading,
+            compounds: selector.compounds[..ci].to_vec(),
+            combinators: selector.combinators[..ci.saturating_sub(1)].to_vec(),
+        };
+        let join = if rest.0.is_empty() {
+            if ci > 0 { selector.combinators[ci - 1] } else { Combinator::Descendant }
+        }
+        else {
+            if ci > 0 {
+                head.combinators.push(selector.combinators[ci - 1]);
+            }
+            head.compounds.push(rest);
+            Combinator::Descendant
+        };
+        match head.compounds.last_mut() {
+            Some(last) => last.insert_before_pseudos(attribute),
+            None => head.compounds.push(CompoundSelector(vec![attribute])),
+        }
+
+        return inner_selectors(selector, ci, si)
+            .into_iter()
+            .map(|inner| {
+                let mut out = head.clone();
+                if !inner.compounds.is_empty() {
+                    out.combinators.push(join);
+                }
+                out.compounds.extend(inner.compounds);
+                out.combinators.extend(inner.combinators);
+                append_tail(&mut out, selector, ci);
+                out
+            })
+            .collect();
+    }
+
+    let mut scoped = selector.clone();
+    if let Some(last) = scoped.compounds.last_mut() {
+        last.insert_before_pseudos(attribute);
+    }
+    vec![scoped]
+}
+
+pub(crate) fn find_pseudo(selector: &ComplexSelector, names: &[&str]) -> Option<(usize, usize)> {
+    selector.compounds.iter().enumerate().find_map(|(ci, compound)| {
+        compound
+            .0
+            .iter()
+            .position(|s| s.pseudo_class_name().map(|n| names.contains(&n.to_ascii_lowercase().as_str())).unwrap_or(false))
+            .map(|si| (ci, si))
+    })
+}
+
+/// The selectors inside the pseudo at `selector.compounds[ci].0[si]`; an argument-less pseudo yields nothing.
+pub(crate) fn inner_selectors(selector: &ComplexSelector, ci: usize, si: usize) -> Vec<ComplexSelector> {
+    match &selector.compounds[ci].0[si] {
+        SimpleSelector::PseudoClass { argument: Some(PseudoArgument::Selector(list)), .. } => list.0.clone(),
+        _ => vec![ComplexSelector::default()],
+    }
+}
+
+/// Replaces the pseudo at `compounds[ci].0[si]` with `inner`, merging the rest of that compound into
+/// inner's first compound.
+pub(crate) fn splice(selector: &ComplexSelector, ci: usize, si: usize, inner: &ComplexSelector) -> ComplexSelector {
+    let mut rest = selector.compounds[ci].clone();
+    rest.0.remove(si);
+
+    let mut out = ComplexSelector {
+        leading: selector.leading,
+        compounds: selector.compounds[..ci].to_vec(),
+        combinators: selector.combinators[..ci].to_vec(),
+    };
+    let mut inner_compounds = inner.compounds.clone();
+    match inner_compounds.first_mut() {
+        Some(first) => {
+            first.0.extend(rest.0);
+            first.0.sort_by_key(simple_rank);
+        }
+        None => inner_compounds.push(rest),
+    }
+    if inner_compounds.iter().all(|c| c.0.is_empty()) {
+        out.combinators.truncate(ci.saturating_sub(1));
+    }
+    else {
+        out.compounds.extend(inner_compounds);
+        out.combinators.extend(inner.combinators.iter().copied());
+    }
+    append_tail(&mut out, selector, ci);
+    out
+}
+
+fn append_tail(out: &mut ComplexSelector, selector: &ComplexSelector, ci: usize) {
+    if ci + 1 < selector.compounds.len() {
+        if out.compounds.is_empty() {
+            out.compounds.extend(selector.compounds[ci + 1..].iter().cloned());
+            out.combinators.extend(selector.combinators[ci + 1..].iter().copied());
+        }
+        else {
+            out.compounds.extend(selector.compounds[ci + 1..].iter().cloned());
+            out.combinators.extend(selector.combinators[ci..].iter().copied());
+        }
+    }
+}
+
+/// Orders merged compounds so they stay valid: type selectors first, pseudo-elements last.
+fn simple_rank(simple: &SimpleSelector) -> u8 {
+    match simple {
+        SimpleSelector::Type(_) | SimpleSelector::Universal | SimpleSelector::Nesting => 0,
+        SimpleSelector::PseudoElement { .. } => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,385 @@
+use hxo_types::{Error, Result, Span};
+use std::fmt;
+
+/// Pseudo-classes whose argument is itself a selector list.
+const SELECTOR_PSEUDOS: [&str; 12] =
+    ["is", "where", "not", "has", "matches", "any", "-webkit-any", "-moz-any", "deep", "v-deep", "slotted", "global"];
+
+/// `a, b, c`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorList(pub Vec<ComplexSelector>);
+
+/// Compound selectors joined by combinators, e.g. `.a > .b:hover`.
+///
+/// `leading` is set for relative selectors such as the argument of `:has(> img)`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ComplexSelector {
+    pub leading: Option<Combinator>,
+    pub compounds: Vec<CompoundSelector>,
+    /// `combinators[i]` joins `compounds[i]` and `compounds[i + 1]`.
+    pub combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CompoundSelector(pub Vec<SimpleSelector>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimpleSelector {
+    Type(String),
+    Universal,
+    /// `&`
+    Nesting,
+    Class(String),
+    Id(String),
+    /// Raw content between the brackets, e.g. `type="text" i`.
+    Attribute(String),
+    PseudoClass {
+        name: String,
+        argument: Option<PseudoArgument>,
+    },
+    PseudoElement {
+        name: String,
+        argument: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PseudoArgument {
+    Selector(SelectorList),
+    Raw(String),
+}
+
+impl SimpleSelector {
+    pub fn is_pseudo(&self) -> bool {
+        matches!(self, SimpleSelector::PseudoClass { .. } | SimpleSelector::PseudoElement { .. })
+    }
+
+    pub fn pseudo_class_name(&self) -> Option<&str> {
+        match self {
+            SimpleSelector::PseudoClass { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl SelectorList {
+    pub fn parse(source: &str) -> Result<Self> {
+        SelectorParser { chars: source.chars().collect(), pos: 0 }.parse_list()
+    }
+}
+
+impl CompoundSelector {
+    /// Appends `extra` after the last non-pseudo component, so `a::before` becomes `a[x]::before`.
+    pub fn insert_before_pseudos(&mut self, extra: SimpleSelector) {
+        let index = self.0.iter().rposition(|s| !s.is_pseudo()).map(|i| i + 1).unwrap_or(0);
+        self.0.insert(index, extra);
+    }
+}
+
+struct SelectorParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl SelectorParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let source: String = self.chars.iter().collect();
+        Error::parse_error(format!("{} in selector `{}`", message, source.trim()), Span::unknown())
+    }
+
+    fn skip_trivia(&mut self) -> bool {
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => self.pos += 1,
+                Some('/') if self.peek_at(1) == Some('*') => {
+                    self.pos += 2;
+                    while self.pos < self.chars.len() && !(self.peek() == Some('*') && self.peek_at(1) == Some('/')) {
+                        self.pos += 1;
+                    }
+                    self.pos = (self.pos + 2).min(self.chars.len());
+                }
+                _ => break,
+            }
+        }
+        self.pos > start
+    }
+
+    fn parse_list(&mut self) -> Result<SelectorList> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_trivia();
+            selectors.push(self.parse_complex()?);
+            self.skip_trivia();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                None => break,
+                Some(c) => return Err(self.error(&format!("Unexpected `{}`", c))),
+            }
+        }
+        Ok(SelectorList(selectors))
+    }
+
+    fn parse_combinator(&mut self) -> Option<Combinator> {
+        let combinator = match self.peek()? {
+            '>' => Combinator::Child,
+            '+' => Combinator::NextSibling,
+            '~' => Combinator::SubsequentSibling,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(combinator)
+    }
+
+    fn parse_complex(&mut self) -> Result<ComplexSelector> {
+        let mut complex = ComplexSelector { leading: self.parse_combinator(), ..Default::default() };
+        self.skip_trivia();
+
+        loop {
+            let compound = self.parse_compound()?;
+            if compound.0.is_empty() {
+                return Err(self.error("Expected selector"));
+            }
+            complex.compounds.push(compound);
+
+            let had_space = self.skip_trivia();
+            match self.peek() {
+                None | Some(',') | Some(')') => break,
+                _ => {}
+            }
+            let combinator = match self.parse_combinator() {
+                Some(c) => {
+                    self.skip_trivia();
+                    c
+                }
+                None if had_space => Combinator::Descendant,
+                None => return Err(self.error("Expected combinator")),
+            };
+            complex.combinators.push(combinator);
+        }
+        Ok(complex)
+    }
+
+    fn parse_compound(&mut self) -> Result<CompoundSelector> {
+        let mut parts = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '*' => {
+                    self.pos += 1;
+                    parts.push(SimpleSelector::Universal);
+                }
+                '&' => {
+                    self.pos += 1;
+                    parts.push(SimpleSelector::Nesting);
+                }
+                '.' => {
+                    self.pos += 1;
+                    parts.push(SimpleSelector::Class(self.parse_ident()?));
+                }
+                '#' => {
+                    self.pos += 1;
+                    parts.push(SimpleSelector::Id(self.parse_ident()?));
+                }
+                '[' => {
+                    self.pos += 1;
+                    let inner = self.consume_balanced(']')?;
+                    parts.push(SimpleSelector::Attribute(inner.trim().to_string()));
+                }
+                ':' => {
+                    self.pos += 1;
+                    let is_element = self.peek() == Some(':');
+                    if is_element {
+                        self.pos += 1;
+                    }
+                    let name = self.parse_ident()?;
+                    let raw = if self.peek() == Some('(') {
+                        self.pos += 1;
+                        Some(self.consume_balanced(')')?)
+                    }
+                    else {
+                        None
+                    };
+                    if is_element {
+                        parts.push(SimpleSelector::PseudoElement { name, argument: raw.map(|r| r.trim().to_string()) });
+                    }
+                    else {
+                        let lower = name.to_ascii_lowercase();
+                        let argument = match raw {
+                            Some(raw) if SELECTOR_PSEUDOS.contains(&lower.as_str()) => {
+                                let mut inner = SelectorParser { chars: raw.chars().collect(), pos: 0 };
+                                Some(PseudoArgument::Selector(inner.parse_list()?))
+                            }
+                            Some(raw) => Some(PseudoArgument::Raw(raw.trim().to_string())),
+                            None => None,
+                        };
+                        parts.push(SimpleSelector::PseudoClass { name, argument });
+                    }
+                }
+                c if is_ident_start(c) || c == '\\' => {
+                    parts.push(SimpleSelector::Type(self.parse_ident()?));
+                }
+                _ => break,
+            }
+        }
+        Ok(CompoundSelector(parts))
+    }
+
+    fn parse_ident(&mut self) -> Result<String> {
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                // Escapes keep the backslash so the selector prints back verbatim (`.md\:flex`).
+                ident.push(c);
+                self.pos += 1;
+                if let Some(next) = self.peek() {
+                    ident.push(next);
+                    self.pos += 1;
+                }
+            }
+            else if is_ident_char(c) {
+                ident.push(c);
+                self.pos += 1;
+            }
+            else {
+                break;
+            }
+        }
+        if ident.is_empty() {
+            return Err(self.error("Expected identifier"));
+        }
+        Ok(ident)
+    }
+
+    /// Consumes up to the matching `close`, honoring nesting and quotes. The closing char is consumed.
+    fn consume_balanced(&mut self, close: char) -> Result<String> {
+        let open = if close == ')' { '(' } else { '[' };
+        let mut depth = 0;
+        let mut quote: Option<char> = None;
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if let Some(q) = quote {
+                if c == '\\' {
+                    out.push(c);
+                    if let Some(next) = self.peek() {
+                        out.push(next);
+                        self.pos += 1;
+                    }
+                    continue;
+                }
+                if c == q {
+                    quote = None;
+                }
+                out.push(c);
+                continue;
+            }
+            match c {
+                '"' | '\'' => quote = Some(c),
+                c if c == open => depth += 1,
+                c if c == close => {
+                    if depth == 0 {
+                        return Ok(out);
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+            out.push(c);
+        }
+        Err(self.error(&format!("Missing `{}`", close)))
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+impl fmt::Display for SelectorList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, selector) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}", selector)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ComplexSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(leading) = self.leading {
+            write!(f, "{} ", leading)?;
+        }
+        for (i, compound) in self.compounds.iter().enumerate() {
+            if i > 0 {
+                match self.combinators.get(i - 1).copied().unwrap_or(Combinator::Descendant) {
+                    Combinator::Descendant => f.write_str(" ")?,
+                    other => write!(f, " {} ", other)?,
+                }
+            }
+            write!(f, "{}", compound)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Combinator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Combinator::Descendant => " ",
+            Combinator::Child => ">",
+            Combinator::NextSibling => "+",
+            Combinator::SubsequentSibling => "~",
+        })
+    }
+}
+
+impl fmt::Display for CompoundSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for simple in &self.0 {
+            write!(f, "{}", simple)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SimpleSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimpleSelector::Type(name) => f.write_str(name),
+            SimpleSelector::Universal => f.write_str("*"),
+            SimpleSelector::Nesting => f.write_str("&"),
+            SimpleSelector::Class(name) => write!(f, ".{}", name),
+            SimpleSelector::Id(name) => write!(f, "#{}", name),
+            SimpleSelector::Attribute(inner) => write!(f, "[{}]", inner),
+            SimpleSelector::PseudoClass { name, argument: None } => write!(f, ":{}", name),
+            SimpleSelector::PseudoClass { name, argument: Some(PseudoArgument::Selector(list)) } => {
+                write!(f, ":{}({})", name, list)
+            }
+            SimpleSelector::PseudoClass { name, argument: Some(PseudoArgument::Raw(raw)) } => write!(f, ":{}({})", name, raw),
+            SimpleSelector::PseudoElement { name, argument: None } => write!(f, "::{}", name),
+            SimpleSelector::PseudoElement { name, argument: Some(raw) } => write!(f, "::{}({})", name, raw),
+        }
+    }
+}
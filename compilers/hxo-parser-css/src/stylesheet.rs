@@ -0,0 +1,185 @@
+use hxo_ir::{AtRule, CssNode, Declaration, StyleRule, Stylesheet};
+use hxo_types::{Cursor, Error, Position, Result, Span};
+
+/// Parses `source` into a [`Stylesheet`] with spans relative to the start of `source`.
+pub fn parse_stylesheet(source: &str) -> Result<Stylesheet> {
+    parse_stylesheet_with(&mut Cursor::new(source))
+}
+
+/// Parses the rest of `cursor`; spans follow the cursor's position, so a cursor over a `<style>` block
+/// gives spans into the component file.
+pub fn parse_stylesheet_with(cursor: &mut Cursor) -> Result<Stylesheet> {
+    let mut parser = SheetParser { cursor };
+    let nodes = parser.parse_block(None)?;
+    Ok(Stylesheet { nodes })
+}
+
+struct SheetParser<'a, 'b> {
+    cursor: &'a mut Cursor<'b>,
+}
+
+impl SheetParser<'_, '_> {
+    fn skip_trivia(&mut self) {
+        loop {
+            self.cursor.skip_whitespace();
+            if self.cursor.peek_str("/*") {
+                self.skip_comment();
+            }
+            else {
+                break;
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        self.cursor.consume_n(2);
+        while !self.cursor.is_eof() && !self.cursor.peek_str("*/") {
+            self.cursor.consume();
+        }
+        self.cursor.consume_str("*/");
+    }
+
+    /// Reads a prelude or value up to a top-level `{`, `;` or `}`, skipping comments and keeping strings
+    /// intact. Returns the text and the position after its last non-whitespace character.
+    fn read_until_delimiter(&mut self) -> (String, Position) {
+        let mut out = String::new();
+        let mut end = self.cursor.position();
+        let mut parens = 0usize;
+        while !self.cursor.is_eof() {
+            let c = self.cursor.peek();
+            match c {
+                '"' | '\'' => {
+                    out.push(self.cursor.consume());
+                    while !self.cursor.is_eof() {
+                        let inner = self.cursor.consume();
+                        out.push(inner);
+                        if inner == '\\' {
+                            if !self.cursor.is_eof() {
+                                out.push(self.cursor.consume());
+                            }
+                        }
+                        else if inner == c {
+                            break;
+                        }
+                    }
+                    end = self.cursor.position();
+                    continue;
+                }
+                '/' if self.cursor.peek_str("/*") => {
+                    self.skip_comment();
+                    continue;
+                }
+                '(' => parens += 1,
+                ')' => parens = parens.saturating_sub(1),
+                '{' | ';' | '}' if parens == 0 => break,
+                _ => {}
+            }
+            out.push(self.cursor.consume());
+            if !c.is_whitespace() {
+                end = self.cursor.position();
+            }
+        }
+        (out, end)
+    }
+
+    /// Parses nodes up to the end of input (`open` is `None`) or the `}` matching the `{` at `open`.
+    fn parse_block(&mut self, open: Option<Span>) -> Result<Vec<CssNode>> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.cursor.is_eof() {
+                return match open {
+                    None => Ok(nodes),
+                    Some(span) => Err(Error::parse_error("Unclosed block, expected `}`".to_string(), span)),
+                };
+            }
+            let start = self.cursor.position();
+            match self.cursor.peek() {
+                '}' if open.is_none() => {
+                    return Err(Error::parse_error("Unexpected `}`".to_string(), self.cursor.span_at_current()));
+                }
+                '}' => {
+                    self.cursor.consume();
+                    return Ok(nodes);
+                }
+                ';' => {
+                    self.cursor.consume();
+                }
+                '@' => nodes.push(CssNode::AtRule(self.parse_at_rule()?)),
+                _ => {
+                    let (head, head_end) = self.read_until_delimiter();
+                    if self.cursor.peek() == '{' {
+                        let brace = self.cursor.span_at_current();
+                        self.cursor.consume();
+                        let children = self.parse_block(Some(brace))?;
+                        nodes.push(CssNode::Rule(StyleRule {
+                            selector: collapse_whitespace(&head),
+                            children,
+                            selector_span: Span { start, end: head_end },
+                            span: self.cursor.span_from(start),
+                        }));
+                    }
+                    else {
+                        self.cursor.consume_str(";");
+                        if let Some((property, value)) = head.split_once(':') {
+                            let (property, value) = (property.trim(), value.trim());
+                            if !property.is_empty() && !value.is_empty() {
+                                nodes.push(CssNode::Declaration(Declaration {
+                                    span: Span { start, end: head_end },
+                                    ..Declaration::new(property, value)
+                                }));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_at_rule(&mut self) -> Result<AtRule> {
+        let start = self.cursor.position();
+        self.cursor.consume(); // '@'
+        let name = self.cursor.consume_while(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        let (prelude, prelude_end) = self.read_until_delimiter();
+        let prelude = collapse_whitespace(&prelude);
+        let block = match self.cursor.peek() {
+            '{' => {
+                let brace = self.cursor.span_at_current();
+                self.cursor.consume();
+                Some(self.parse_block(Some(brace))?)
+            }
+            ';' => {
+                self.cursor.consume();
+                None
+            }
+            _ => None,
+        };
+        let end = if block.is_some() { self.cursor.position() } else { prelude_end };
+        Ok(AtRule { name, prelude, block, span: Span { start, end } })
+    }
+}
+
+/// Collapses runs of whitespace outside strings to one space and trims the ends.
+fn collapse_whitespace(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut quote = None;
+    let mut escaped = false;
+    for c in s.trim().chars() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c.is_whitespace() => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+                continue;
+            }
+            None => {}
+        }
+        out.push(c);
+    }
+    out
+}
@@ -1,4 +1,6 @@
-use hxo_parser_css::compile;
+use hxo_parser_css::{
+    AtRuleKind, Combinator, CssNode, SelectorList, compile, css_module, parse_stylesheet, scope_css, scope_selector,
+};
 
 #[test]
 fn test_parse_css() {
@@ -6,3 +8,155 @@ fn test_parse_css() {
     let res = compile(css).unwrap();
     assert!(res.contains("color: red"));
 }
+
+#[test]
+fn test_parse_selector_ast() {
+    let list = SelectorList::parse(".a > .b:hover,\n  :is(h1, h2)::before").unwrap();
+    assert_eq!(list.0.len(), 2);
+    assert_eq!(list.0[0].combinators, vec![Combinator::Child]);
+    assert_eq!(list.to_string(), ".a > .b:hover, :is(h1, h2)::before");
+
+    let escaped = SelectorList::parse(r".md\:flex").unwrap();
+    assert_eq!(escaped.to_string(), r".md\:flex");
+}
+
+#[test]
+fn test_scope_selectors() {
+    let scope = "data-h-1";
+    assert_eq!(scope_selector(".a .b", scope).unwrap(), ".a .b[data-h-1]");
+    assert_eq!(scope_selector(".a::before", scope).unwrap(), ".a[data-h-1]::before");
+    assert_eq!(scope_selector("li:hover::after", scope).unwrap(), "li[data-h-1]:hover::after");
+    assert_eq!(scope_selector(":where(.x, .y)", scope).unwrap(), "[data-h-1]:where(.x, .y)");
+    assert_eq!(scope_selector(".a :deep(.b .c)", scope).unwrap(), ".a[data-h-1] .b .c");
+    assert_eq!(scope_selector(".a:deep(.b)", scope).unwrap(), ".a[data-h-1] .b");
+    assert_eq!(scope_selector(":deep(.b)", scope).unwrap(), "[data-h-1] .b");
+    assert_eq!(scope_selector(".a > :deep(.b)", scope).unwrap(), ".a[data-h-1] > .b");
+    assert_eq!(scope_selector(":slotted(.s)", scope).unwrap(), ".s[data-h-1]");
+    assert_eq!(scope_selector(":global(.dark) .a", scope).unwrap(), ".dark .a");
+    assert_eq!(scope_selector("div:global(.x)", scope).unwrap(), "div.x");
+}
+
+#[test]
+fn test_scope_stylesheet() {
+    let css = r#"
+.card,
+.card-title { color: red; }
+@media (min-width: 640px) {
+  .card::before { content: "{"; }
+}
+@keyframes fade { from { opacity: 0; } to { opacity: 1; } }
+.box { animation: fade 1s ease; }
+"#;
+    let scoped = scope_css(css, "data-h-1").unwrap();
+    assert!(scoped.contains(".card[data-h-1], .card-title[data-h-1] {"));
+    assert!(scoped.contains("  .card[data-h-1]::before {\n    content: \"{\";"));
+    assert!(scoped.contains("@keyframes fade-h-1 {"));
+    assert!(scoped.contains("  from {\n"));
+    assert!(scoped.contains("animation: fade-h-1 1s ease;"));
+}
+
+#[test]
+fn test_css_module() {
+    let css = r#"
+.base { padding: 0; }
+.card { composes: base; composes: shadow from global; color: red; }
+.card:hover .title, :global(.dark) .card { color: blue; }
+@media (max-width: 600px) { .title { font-size: 12px; } }
+@keyframes spin { to { transform: rotate(1turn); } }
+.spinner { animation: spin 1s linear; }
+"#;
+    let module = css_module(css, "abc123").unwrap();
+    assert_eq!(module.classes["base"], "base_abc123");
+    assert_eq!(module.classes["card"], "card_abc123 base_abc123 shadow");
+    assert_eq!(module.classes["title"], "title_abc123");
+    assert!(!module.classes.contains_key("dark"));
+
+    assert!(module.code.contains(".card_abc123 {\n  color: red;\n}"));
+    assert!(!module.code.contains("composes"));
+    assert!(module.code.contains(".card_abc123:hover .title_abc123, .dark .card_abc123 {"));
+    assert!(module.code.contains("  .title_abc123 {"));
+    assert!(module.code.contains("@keyframes spin-abc123"));
+    assert!(module.code.contains("animation: spin-abc123 1s linear;"));
+
+    assert!(css_module(".a { composes: b from './other.css'; }", "x").is_err());
+}
+
+#[test]
+fn test_apply_directive_pass_through() {
+    let css =
+        compile("@layer components { .btn { @apply px-4 hover:bg-blue-500; color: theme('colors.blue.500'); } }").unwrap();
+    assert!(css.contains("@apply px-4 hover:bg-blue-500;"), "{}", css);
+    assert!(css.contains("color: theme('colors.blue.500');"));
+}
+
+#[test]
+fn test_stylesheet_ast() {
+    let css = r#"@import url("base.css") layer(base) supports(display: grid) screen;
+@layer reset, base;
+.card {
+  color: red !important;
+  &:hover { color: blue; }
+  @media (min-width: 640px) { padding: 1rem; }
+}
+@container sidebar (min-width: 400px) {
+  .a { display: none; }
+}
+@font-face {
+  font-family: "Inter";
+  src: url(inter.woff2);
+}
+"#;
+    let sheet = parse_stylesheet(css).unwrap();
+    assert_eq!(sheet.nodes.len(), 5);
+
+    let CssNode::AtRule(import) = &sheet.nodes[0]
+    else {
+        panic!("expected @import");
+    };
+    assert_eq!(
+        import.kind(),
+        AtRuleKind::Import { url: "base.css", layer: Some("base"), supports: Some("display: grid"), media: Some("screen") }
+    );
+    assert!(import.block.is_none());
+
+    let CssNode::AtRule(layer) = &sheet.nodes[1]
+    else {
+        panic!("expected @layer");
+    };
+    assert_eq!(layer.layer_names(), vec!["reset", "base"]);
+
+    let CssNode::Rule(card) = &sheet.nodes[2]
+    else {
+        panic!("expected .card");
+    };
+    assert_eq!(card.selector, ".card");
+    assert_eq!((card.selector_span.start.line, card.selector_span.start.column), (3, 1));
+    assert_eq!(card.span.end.line, 7);
+    let CssNode::Declaration(color) = &card.children[0]
+    else {
+        panic!("expected a declaration");
+    };
+    assert_eq!((color.property.as_str(), color.value.as_str(), color.important), ("color", "red", true));
+    assert_eq!(color.span.start.line, 4);
+    assert!(matches!(&card.children[1], CssNode::Rule(nested) if nested.selector == "&:hover"));
+    assert!(matches!(&card.children[2], CssNode::AtRule(media) if media.kind() == AtRuleKind::Media("(min-width: 640px)")));
+
+    let CssNode::AtRule(container) = &sheet.nodes[3]
+    else {
+        panic!("expected @container");
+    };
+    assert_eq!(container.kind(), AtRuleKind::Container { name: Some("sidebar"), condition: "(min-width: 400px)" });
+    assert!(matches!(&sheet.nodes[4], CssNode::AtRule(font) if font.kind() == AtRuleKind::FontFace));
+
+    let printed = sheet.to_css(false);
+    assert!(printed.contains("color: red !important;"));
+    assert_eq!(parse_stylesheet(&printed).unwrap().without_spans(), sheet.without_spans());
+}
+
+#[test]
+fn test_stylesheet_errors_have_spans() {
+    let err = parse_stylesheet(".a {\n  color: red;\n").unwrap_err();
+    assert_eq!(err.span().start.line, 1);
+    let err = parse_stylesheet(".a { color: red; }\n}").unwrap_err();
+    assert_eq!(err.span().start.line, 2);
+}
@@ -0,0 +1,144 @@
+use hxo_types::Span;
+
+/// A parsed `.ftl` file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Resource {
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    Message(Message),
+    Term(Term),
+    /// A `##` group or `###` resource comment, not attached to any message.
+    Comment(String, Span),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub id: String,
+    pub value: Option<Pattern>,
+    pub attributes: Vec<Attribute>,
+    /// The `#` comment right above the message, for translators.
+    pub comment: Option<String>,
+    pub span: Span,
+}
+
+/// A `-term`, only visible to references inside the resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub id: String,
+    pub value: Pattern,
+    pub attributes: Vec<Attribute>,
+    pub comment: Option<String>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    pub id: String,
+    pub value: Pattern,
+    pub span: Span,
+}
+
+/// Text and placeables, with the indentation of multiline patterns already removed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Pattern {
+    pub elements: Vec<PatternElement>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternElement {
+    Text(String),
+    Placeable(Expression),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    StringLiteral(String, Span),
+    /// The number as written, e.g. `-1.50`.
+    NumberLiteral(String, Span),
+    /// `$name`
+    Variable(String, Span),
+    /// `message` or `message.attribute`
+    MessageReference {
+        id: String,
+        attribute: Option<String>,
+        span: Span,
+    },
+    /// `-term`, `-term.attribute` or `-term(case: "genitive")`
+    TermReference {
+        id: String,
+        attribute: Option<String>,
+        arguments: Option<CallArguments>,
+        span: Span,
+    },
+    /// `NUMBER($count, minimumFractionDigits: 2)`
+    FunctionReference {
+        id: String,
+        arguments: CallArguments,
+        span: Span,
+    },
+    Select {
+        selector: Box<Expression>,
+        variants: Vec<Variant>,
+        span: Span,
+    },
+    /// `{ { ... } }`
+    Placeable(Box<Expression>, Span),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CallArguments {
+    pub positional: Vec<Expression>,
+    /// Named arguments take literals only.
+    pub named: Vec<(String, Expression)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub key: VariantKey,
+    pub value: Pattern,
+    /// The `*[other]` variant, used when no key matches.
+    pub default: bool,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariantKey {
+    /// A plural category such as `one`, or any other word to match a string selector.
+    Identifier(String),
+    Number(String),
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::StringLiteral(_, span)
+            | Expression::NumberLiteral(_, span)
+            | Expression::Variable(_, span)
+            | Expression::Placeable(_, span) => *span,
+            Expression::MessageReference { span, .. }
+            | Expression::TermReference { span, .. }
+            | Expression::FunctionReference { span, .. }
+            | Expression::Select { span, .. } => *span,
+        }
+    }
+}
+
+impl Resource {
+    pub fn messages(&self) -> impl Iterator<Item = &Message> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Message(message) => Some(message),
+            _ => None,
+        })
+    }
+
+    pub fn terms(&self) -> impl Iterator<Item = &Term> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Term(term) => Some(term),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,301 @@
+//! Compiles Fluent messages to i18n messages: plain text when a message has no logic, `{name}`
+//! placeholders for bare variables, and otherwise a JS formatter `(args = {}, locale) => string`.
+//!
+//! Message and term references are resolved here, so a formatter only needs `Intl`: selectors match
+//! plural categories with the same shipped rules as ICU messages (`Intl.PluralRules` when the locale
+//! is unknown), `NUMBER()` and `DATETIME()` format through `Intl.NumberFormat` and `Intl.DateTimeFormat`.
+
+use crate::ast::{CallArguments, Expression, Message, Pattern, PatternElement, Resource, Term, VariantKey};
+use hxo_parser_icu::plural_function;
+use hxo_target_js::js_string;
+use hxo_types::{Error, HxoValue, Result, Span};
+use std::collections::HashMap;
+
+const PLURAL_CATEGORIES: &[&str] = &["zero", "one", "two", "few", "many", "other"];
+
+/// Messages by id as the i18n tables expect them: a string (`HxoValue::String`) or a formatter
+/// (`HxoValue::Raw`), and `{ val, attribute... }` for messages with attributes. Terms are not exported.
+///
+/// Plural selectors use the rules of `locale`; without one they defer to `Intl.PluralRules` at runtime.
+pub fn compile_resource(resource: &Resource, locale: Option<&str>) -> Result<HashMap<String, HxoValue>> {
+    let mut compiler = FluentCompiler::new(resource, locale);
+    let mut messages = HashMap::new();
+    for message in resource.messages() {
+        let value = message.value.as_ref().map(|value| compiler.compile_message(value)).transpose()?;
+        if message.attributes.is_empty() {
+            if let Some(value) = value {
+                messages.insert(message.id.clone(), value);
+            }
+            continue;
+        }
+        let mut map = HashMap::new();
+        if let Some(value) = value {
+            map.insert("val".to_string(), value);
+        }
+        for attribute in &message.attributes {
+            map.insert(attribute.id.clone(), compiler.compile_message(&attribute.value)?);
+        }
+        messages.insert(message.id.clone(), HxoValue::Object(map));
+    }
+    Ok(messages)
+}
+
+/// Compiled pattern pieces.
+enum Part {
+    Text(String),
+    /// A variable of the message, printed as-is.
+    Variable(String),
+    /// A JS expression.
+    Code(String),
+}
+
+struct FluentCompiler<'r> {
+    locale: Option<&'r str>,
+    messages: HashMap<&'r str, &'r Message>,
+    terms: HashMap<&'r str, &'r Term>,
+    /// References being compiled, to report cycles.
+    stack: Vec<String>,
+    /// Arguments of the term being compiled; a term sees only what its reference passes.
+    term_scope: Option<HashMap<String, String>>,
+}
+
+impl<'r> FluentCompiler<'r> {
+    fn new(resource: &'r Resource, locale: Option<&'r str>) -> Self {
+        Self {
+            locale,
+            messages: resource.messages().map(|message| (message.id.as_str(), message)).collect(),
+            terms: resource.terms().map(|term| (term.id.as_str(), term)).collect(),
+            stack: Vec::new(),
+            term_scope: None,
+        }
+    }
+
+    fn compile_message(&mut self, pattern: &Pattern) -> Result<HxoValue> {
+        let parts = self.compile_pattern(pattern)?;
+        // Bare variables become `{name}` placeholders, which locale builds check and inline. Text with
+        // a literal `{` would read as one, so it needs a formatter too.
+        let placeholders = parts.iter().all(|part| match part {
+            Part::Text(text) => !text.contains('{'),
+            Part::Variable(name) => is_js_identifier(name),
+            Part::Code(_) => false,
+        });
+        if placeholders {
+            let text = parts
+                .iter()
+                .map(|part| match part {
+                    Part::Text(text) => text.clone(),
+                    Part::Variable(name) => format!("{{{}}}", name),
+                    Part::Code(_) => String::new(),
+                })
+                .collect();
+            return Ok(HxoValue::String(text));
+        }
+        Ok(HxoValue::Raw(format!("(args = {{}}, locale) => {}", template_literal(&parts))))
+    }
+
+    fn compile_pattern(&mut self, pattern: &Pattern) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        for element in &pattern.elements {
+            match element {
+                PatternElement::Text(text) => parts.push(Part::Text(text.clone())),
+                PatternElement::Placeable(expression) => parts.extend(self.compile_placeable(expression)?),
+            }
+        }
+        Ok(parts)
+    }
+
+    fn compile_placeable(&mut self, expression: &Expression) -> Result<Vec<Part>> {
+        match expression {
+            Expression::StringLiteral(text, _) => Ok(vec![Part::Text(text.clone())]),
+            Expression::NumberLiteral(number, _) => Ok(vec![Part::Text(number.clone())]),
+            Expression::Variable(name, _) if self.term_scope.is_none() => Ok(vec![Part::Variable(name.clone())]),
+            Expression::MessageReference { id, attribute, span } => {
+                let key = match attribute {
+                    Some(attribute) => format!("{}.{}", id, attribute),
+                    None => id.clone(),
+                };
+                let Some(message) = self.messages.get(id.as_str()).copied()
+                else {
+                    return Err(Error::parse_error(format!("unknown message `{}`", id), *span));
+                };
+                let pattern = match attribute {
+                    Some(attribute) => message.attributes.iter().find(|a| a.id == *attribute).map(|a| &a.value),
+                    None => message.value.as_ref(),
+                };
+                let Some(pattern) = pattern
+                else {
+                    return Err(Error::parse_error(format!("message `{}` has no value", key), *span));
+                };
+                // Referenced messages see the same arguments, outside of any term
+                let scope = self.term_scope.take();
+                let parts = self.compile_reference(key, pattern, *span);
+                self.term_scope = scope;
+                parts
+            }
+            Expression::TermReference { id, attribute, arguments, span } => {
+                let pattern = self.term_pattern(id, attribute.as_deref(), *span)?;
+                let mut scope = HashMap::new();
+                if let Some(arguments) = arguments {
+                    for (name, value) in &arguments.named {
+                        scope.insert(name.clone(), self.compile_expression(value)?);
+                    }
+                }
+                let outer = self.term_scope.replace(scope);
+                let parts = self.compile_reference(format!("-{}", id), pattern, *span);
+                self.term_scope = outer;
+                parts
+            }
+            Expression::Placeable(inner, _) => self.compile_placeable(inner),
+            _ => Ok(vec![Part::Code(self.compile_expression(expression)?)]),
+        }
+    }
+
+    fn compile_reference(&mut self, key: String, pattern: &Pattern, span: Span) -> Result<Vec<Part>> {
+        if self.stack.contains(&key) {
+            return Err(Error::parse_error(format!("`{}` references itself", key), span));
+        }
+        self.stack.push(key);
+        let parts = self.compile_pattern(pattern);
+        self.stack.pop();
+        parts
+    }
+
+    /// A JS expression for the value of `expression`.
+    fn compile_expression(&mut self, expression: &Expression) -> Result<String> {
+        match expression {
+            Expression::StringLiteral(text, _) => Ok(js_string(text)),
+            Expression::NumberLiteral(number, _) => Ok(number.clone()),
+            Expression::Variable(name, _) => Ok(match &self.term_scope {
+                Some(scope) => scope.get(name).cloned().unwrap_or_else(|| "undefined".to_string()),
+                None => variable(name),
+            }),
+            Expression::FunctionReference { id, arguments, span } => {
+                let formatter = match id.as_str() {
+                    "NUMBER" => "NumberFormat",
+                    "DATETIME" => "DateTimeFormat",
+                    _ => return Err(Error::parse_error(format!("unknown function `{}`; use NUMBER or DATETIME", id), *span)),
+                };
+                let Some(value) = arguments.positional.first()
+                else {
+                    return Err(Error::parse_error(format!("`{}` takes the value to format", id), *span));
+                };
+                let value = self.compile_expression(value)?;
+                Ok(format!("new Intl.{}(locale{}).format({})", formatter, options(arguments)?, value))
+            }
+            Expression::Select { selector, variants, .. } => {
+                // `NUMBER($n, type: "ordinal")` selects by ordinal category, on the unformatted number
+                let (selector, plural_options, ordinal) = match &**selector {
+                    Expression::FunctionReference { id, arguments, .. }
+                        if id == "NUMBER" && !arguments.positional.is_empty() =>
+                    {
+                        let ordinal = arguments.named.iter().any(|(name, value)| {
+                            name == "type" && matches!(value, Expression::StringLiteral(text, _) if text == "ordinal")
+                        });
+                        (self.compile_expression(&arguments.positional[0])?, options(arguments)?, ordinal)
+                    }
+                    selector => (self.compile_expression(selector)?, String::new(), false),
+                };
+                let plural = variants.iter().any(|variant| {
+                    !variant.default
+                        && matches!(&variant.key, VariantKey::Identifier(key) if PLURAL_CATEGORIES.contains(&key.as_str()))
+                });
+                // Exact numbers win over plural categories wherever they are written, so they are tested first
+                let mut branches = String::new();
+                let mut category_branches = String::new();
+                let mut fallback = String::new();
+                for variant in variants {
+                    let value = template_literal(&self.compile_pattern(&variant.value)?);
+                    if variant.default {
+                        fallback = value;
+                        continue;
+                    }
+                    match &variant.key {
+                        VariantKey::Number(number) => branches.push_str(&format!("$s === {} ? {} : ", number, value)),
+                        VariantKey::Identifier(key) if plural => {
+                            category_branches.push_str(&format!("$c === {} ? {} : ", js_string(key), value))
+                        }
+                        VariantKey::Identifier(key) => branches.push_str(&format!("$s === {} ? {} : ", js_string(key), value)),
+                    }
+                }
+                branches.push_str(&category_branches);
+                if plural {
+                    let category = match self.locale {
+                        Some(locale) => format!("typeof $s === 'number' ? ({})($s) : $s", plural_function(locale, ordinal)),
+                        None => {
+                            format!("typeof $s === 'number' ? new Intl.PluralRules(locale{}).select($s) : $s", plural_options)
+                        }
+                    };
+                    Ok(format!("(($s, $c = {}) => {}{})({})", category, branches, fallback, selector))
+                }
+                else {
+                    Ok(format!("(($s) => {}{})({})", branches, fallback, selector))
+                }
+            }
+            Expression::MessageReference { span, .. }
+            | Expression::TermReference { span, .. }
+            | Expression::Placeable(_, span) => {
+                let parts = self.compile_placeable(expression)?;
+                if parts.is_empty() {
+                    return Err(Error::parse_error("empty reference".to_string(), *span));
+                }
+                Ok(template_literal(&parts))
+            }
+        }
+    }
+
+    fn term_pattern(&self, id: &str, attribute: Option<&str>, span: Span) -> Result<&'r Pattern> {
+        let Some(term) = self.terms.get(id).copied()
+        else {
+            return Err(Error::parse_error(format!("unknown term `-{}`", id), span));
+        };
+        match attribute {
+            None => Ok(&term.value),
+            Some(attribute) => term
+                .attributes
+                .iter()
+                .find(|a| a.id == attribute)
+                .map(|a| &a.value)
+                .ok_or_else(|| Error::parse_error(format!("term `-{}` has no attribute `.{}`", id, attribute), span)),
+        }
+    }
+}
+
+/// `, { name: value }` for the named arguments of a function, empty without any.
+fn options(arguments: &CallArguments) -> Result<String> {
+    if arguments.named.is_empty() {
+        return Ok(String::new());
+    }
+    let mut entries = Vec::new();
+    for (name, value) in &arguments.named {
+        let value = match value {
+            Expression::StringLiteral(text, _) => js_string(text),
+            Expression::NumberLiteral(number, _) => number.clone(),
+            _ => return Err(Error::parse_error(format!("option `{}` takes a literal", name), value.span())),
+        };
+        let name = if is_js_identifier(name) { name.clone() } else { js_string(name) };
+        entries.push(format!("{}: {}", name, value));
+    }
+    Ok(format!(", {{ {} }}", entries.join(", ")))
+}
+
+fn template_literal(parts: &[Part]) -> String {
+    let mut code = String::from("`");
+    for part in parts {
+        match part {
+            Part::Text(text) => code.push_str(&text.replace('\\', "\\\\").replace('`', "\\`").replace("${", "\\${")),
+            Part::Variable(name) => code.push_str(&format!("${{{}}}", variable(name))),
+            Part::Code(expression) => code.push_str(&format!("${{{}}}", expression)),
+        }
+    }
+    code.push('`');
+    code
+}
+
+fn variable(name: &str) -> String {
+    if is_js_identifier(name) { format!("args.{}", name) } else { format!("args[{}]", js_string(name)) }
+}
+
+fn is_js_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,454 @@
+//! Fluent syntax 1.0: messages, terms, attributes and comments, with multiline patterns and placeables.
+
+use crate::ast::{
+    Attribute, CallArguments, Entry, Expression, Message, Pattern, PatternElement, Resource, Term, Variant, VariantKey,
+};
+use hxo_types::{Cursor, Error, Result};
+
+/// Parses a whole `.ftl` file.
+pub fn parse_resource(source: &str) -> Result<Resource> {
+    parse_resource_with(&mut Cursor::new(source))
+}
+
+/// Parses from `cursor` to the end of its source; spans follow the cursor's position.
+pub fn parse_resource_with(cursor: &mut Cursor) -> Result<Resource> {
+    FluentParserImpl { cursor }.parse_resource()
+}
+
+/// A pattern before its common indentation is removed.
+enum Piece {
+    Text(String),
+    /// The start of an indented line: line breaks before it and its indentation.
+    Indent(usize, usize),
+    Placeable(Expression),
+}
+
+/// The next non-blank line, looking ahead from a line end.
+struct NextLine {
+    /// Chars up to its first non-space char.
+    chars: usize,
+    line_breaks: usize,
+    indent: usize,
+    first: char,
+}
+
+struct FluentParserImpl<'a, 'b> {
+    cursor: &'a mut Cursor<'b>,
+}
+
+impl<'a, 'b> FluentParserImpl<'a, 'b> {
+    fn parse_resource(&mut self) -> Result<Resource> {
+        let mut entries = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.cursor.is_eof() {
+                break;
+            }
+            match self.cursor.peek() {
+                '#' => {
+                    let start = self.cursor.position();
+                    let (level, comment) = self.parse_comment()?;
+                    // A `#` comment right above a message or term belongs to it
+                    if level == 1 && (self.cursor.peek() == '-' || self.cursor.peek().is_ascii_alphabetic()) {
+                        entries.push(self.parse_entry(Some(comment))?);
+                    }
+                    else {
+                        entries.push(Entry::Comment(comment, self.cursor.span_from(start)));
+                    }
+                }
+                '-' => entries.push(self.parse_entry(None)?),
+                c if c.is_ascii_alphabetic() => entries.push(self.parse_entry(None)?),
+                c if c == ' ' || c == '\t' => return self.error("entries must start at the beginning of a line"),
+                _ => return self.error("expected a message, a term or a comment"),
+            }
+        }
+        Ok(Resource { entries })
+    }
+
+    /// Consecutive comment lines of the same level, joined by line breaks.
+    fn parse_comment(&mut self) -> Result<(usize, String)> {
+        let level = self.cursor.source[self.cursor.pos..].chars().take_while(|c| *c == '#').count();
+        if level > 3 {
+            return self.error("comments start with one to three `#`");
+        }
+        let mut lines = Vec::new();
+        loop {
+            let hashes = self.cursor.source[self.cursor.pos..].chars().take_while(|c| *c == '#').count();
+            if hashes != level {
+                break;
+            }
+            self.cursor.consume_n(level);
+            match self.cursor.peek() {
+                ' ' => {
+                    self.cursor.consume();
+                }
+                '\n' | '\r' | '\0' => {}
+                _ => return self.error("expected a space after the comment sigil"),
+            }
+            lines.push(self.cursor.consume_while(|c| c != '\n').trim_end_matches('\r').to_string());
+            self.cursor.consume_str("\n");
+        }
+        Ok((level, lines.join("\n")))
+    }
+
+    fn parse_entry(&mut self, comment: Option<String>) -> Result<Entry> {
+        let start = self.cursor.position();
+        let is_term = self.cursor.consume_str("-");
+        let id = self.parse_identifier()?;
+        self.skip_blank_inline();
+        self.cursor.expect('=')?;
+        let value = self.parse_pattern()?;
+        self.expect_line_end()?;
+        let attributes = self.parse_attributes()?;
+        let span = self.cursor.span_from(start);
+        if is_term {
+            let Some(value) = value
+            else {
+                return Err(Error::parse_error(format!("term `-{}` has no value", id), span));
+            };
+            return Ok(Entry::Term(Term { id, value, attributes, comment, span }));
+        }
+        if value.is_none() && attributes.is_empty() {
+            return Err(Error::parse_error(format!("message `{}` has neither a value nor attributes", id), span));
+        }
+        Ok(Entry::Message(Message { id, value, attributes, comment, span }))
+    }
+
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        while let Some(line) = self.next_line().filter(|line| line.indent > 0 && line.first == '.') {
+            self.cursor.consume_n(line.chars);
+            let start = self.cursor.position();
+            self.cursor.expect('.')?;
+            let id = self.parse_identifier()?;
+            self.skip_blank_inline();
+            self.cursor.expect('=')?;
+            let Some(value) = self.parse_pattern()?
+            else {
+                return Err(Error::parse_error(format!("attribute `.{}` has no value", id), self.cursor.span_from(start)));
+            };
+            self.expect_line_end()?;
+            attributes.push(Attribute { id, value, span: self.cursor.span_from(start) });
+        }
+        Ok(attributes)
+    }
+
+    fn parse_pattern(&mut self) -> Result<Option<Pattern>> {
+        self.skip_blank_inline();
+        let start = self.cursor.position();
+        let mut pieces = Vec::new();
+        loop {
+            match self.cursor.peek() {
+                '\0' | '}' => break,
+                '{' => pieces.push(Piece::Placeable(self.parse_placeable()?)),
+                '\n' | '\r' => match self.next_line() {
+                    // Lines starting with `[`, `*` or `.` are variants and attributes
+                    Some(line) if line.indent > 0 && !matches!(line.first, '[' | '*' | '.' | '}') => {
+                        self.cursor.consume_n(line.chars);
+                        pieces.push(Piece::Indent(line.line_breaks, line.indent));
+                    }
+                    _ => break,
+                },
+                _ => pieces.push(Piece::Text(self.cursor.consume_while(|c| !matches!(c, '{' | '}' | '\n' | '\r')))),
+            }
+        }
+        let span = self.cursor.span_from(start);
+        let elements = dedent(pieces);
+        Ok(if elements.is_empty() { None } else { Some(Pattern { elements, span }) })
+    }
+
+    fn parse_placeable(&mut self) -> Result<Expression> {
+        let start = self.cursor.position();
+        self.cursor.expect('{')?;
+        self.cursor.skip_whitespace();
+        let expression = self.parse_inline_expression()?;
+        self.cursor.skip_whitespace();
+        let expression = if self.cursor.consume_str("->") {
+            match &expression {
+                Expression::MessageReference { .. } => {
+                    return Err(Error::parse_error("messages cannot be used as selectors".to_string(), expression.span()));
+                }
+                Expression::TermReference { attribute: None, .. } => {
+                    return Err(Error::parse_error(
+                        "terms cannot be used as selectors, only their attributes".to_string(),
+                        expression.span(),
+                    ));
+                }
+                _ => {}
+            }
+            let variants = self.parse_variants()?;
+            self.cursor.skip_whitespace();
+            Expression::Select { selector: Box::new(expression), variants, span: self.cursor.span_from(start) }
+        }
+        else {
+            expression
+        };
+        self.cursor.expect('}')?;
+        Ok(expression)
+    }
+
+    fn parse_variants(&mut self) -> Result<Vec<Variant>> {
+        let start = self.cursor.position();
+        let mut variants = Vec::new();
+        loop {
+            self.cursor.skip_whitespace();
+            let variant_start = self.cursor.position();
+            let default = self.cursor.consume_str("*");
+            if self.cursor.peek() != '[' {
+                if default {
+                    return self.error("expected `[` after `*`");
+                }
+                break;
+            }
+            self.cursor.expect('[')?;
+            self.skip_blank_inline();
+            let key = if self.cursor.peek().is_ascii_digit() || self.cursor.peek() == '-' {
+                VariantKey::Number(self.parse_number())
+            }
+            else {
+                VariantKey::Identifier(self.parse_identifier()?)
+            };
+            self.skip_blank_inline();
+            self.cursor.expect(']')?;
+            let Some(value) = self.parse_pattern()?
+            else {
+                return Err(Error::parse_error("variant has no value".to_string(), self.cursor.span_from(variant_start)));
+            };
+            variants.push(Variant { key, value, default, span: self.cursor.span_from(variant_start) });
+        }
+        match variants.iter().filter(|variant| variant.default).count() {
+            1 => Ok(variants),
+            _ => Err(Error::parse_error(
+                "a select expression needs exactly one default `*[...]` variant".to_string(),
+                self.cursor.span_from(start),
+            )),
+        }
+    }
+
+    fn parse_inline_expression(&mut self) -> Result<Expression> {
+        let start = self.cursor.position();
+        match self.cursor.peek() {
+            '"' => {
+                let value = self.parse_string()?;
+                Ok(Expression::StringLiteral(value, self.cursor.span_from(start)))
+            }
+            c if c.is_ascii_digit() || (c == '-' && self.cursor.peek_n(1).is_ascii_digit()) => {
+                let number = self.parse_number();
+                Ok(Expression::NumberLiteral(number, self.cursor.span_from(start)))
+            }
+            '$' => {
+                self.cursor.consume();
+                let id = self.parse_identifier()?;
+                Ok(Expression::Variable(id, self.cursor.span_from(start)))
+            }
+            '-' => {
+                self.cursor.consume();
+                let id = self.parse_identifier()?;
+                let attribute = self.parse_attribute_accessor()?;
+                self.skip_blank_inline();
+                let arguments = if self.cursor.peek() == '(' { Some(self.parse_call_arguments()?) } else { None };
+                Ok(Expression::TermReference { id, attribute, arguments, span: self.cursor.span_from(start) })
+            }
+            '{' => {
+                let expression = self.parse_placeable()?;
+                Ok(Expression::Placeable(Box::new(expression), self.cursor.span_from(start)))
+            }
+            c if c.is_ascii_alphabetic() => {
+                let id = self.parse_identifier()?;
+                if self.cursor.peek() == '(' {
+                    if !id.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+                        return Err(Error::parse_error(
+                            format!("function names are upper case, found `{}`", id),
+                            self.cursor.span_from(start),
+                        ));
+                    }
+                    let arguments = self.parse_call_arguments()?;
+                    return Ok(Expression::FunctionReference { id, arguments, span: self.cursor.span_from(start) });
+                }
+                let attribute = self.parse_attribute_accessor()?;
+                Ok(Expression::MessageReference { id, attribute, span: self.cursor.span_from(start) })
+            }
+            _ => self.error("expected an expression"),
+        }
+    }
+
+    fn parse_attribute_accessor(&mut self) -> Result<Option<String>> {
+        if self.cursor.consume_str(".") { Ok(Some(self.parse_identifier()?)) } else { Ok(None) }
+    }
+
+    fn parse_call_arguments(&mut self) -> Result<CallArguments> {
+        self.cursor.expect('(')?;
+        let mut arguments = CallArguments::default();
+        loop {
+            self.cursor.skip_whitespace();
+            if self.cursor.consume_str(")") {
+                return Ok(arguments);
+            }
+            let argument = self.parse_inline_expression()?;
+            self.cursor.skip_whitespace();
+            if self.cursor.consume_str(":") {
+                let Expression::MessageReference { id, attribute: None, .. } = argument
+                else {
+                    return Err(Error::parse_error("argument names are plain identifiers".to_string(), argument.span()));
+                };
+                self.cursor.skip_whitespace();
+                let value = self.parse_inline_expression()?;
+                if !matches!(value, Expression::StringLiteral(..) | Expression::NumberLiteral(..)) {
+                    return Err(Error::parse_error(
+                        format!("named argument `{}` takes a string or number literal", id),
+                        value.span(),
+                    ));
+                }
+                if arguments.named.iter().any(|(name, _)| *name == id) {
+                    return Err(Error::parse_error(format!("named argument `{}` is given twice", id), value.span()));
+                }
+                arguments.named.push((id, value));
+            }
+            else if arguments.named.is_empty() {
+                arguments.positional.push(argument);
+            }
+            else {
+                return Err(Error::parse_error("positional arguments come before named ones".to_string(), argument.span()));
+            }
+            self.cursor.skip_whitespace();
+            if !self.cursor.consume_str(",") {
+                self.cursor.expect(')')?;
+                return Ok(arguments);
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        self.cursor.expect('"')?;
+        let mut value = String::new();
+        loop {
+            match self.cursor.peek() {
+                '"' => {
+                    self.cursor.consume();
+                    return Ok(value);
+                }
+                '\\' => {
+                    self.cursor.consume();
+                    match self.cursor.consume() {
+                        c @ ('"' | '\\') => value.push(c),
+                        c @ ('u' | 'U') => {
+                            let digits = if c == 'u' { 4 } else { 6 };
+                            let hex = self.cursor.source[self.cursor.pos..].chars().take(digits).collect::<String>();
+                            let Some(c) =
+                                u32::from_str_radix(&hex, 16).ok().filter(|_| hex.len() == digits).and_then(char::from_u32)
+                            else {
+                                return self.error(format!("invalid unicode escape `\\{}{}`", c, hex));
+                            };
+                            self.cursor.consume_n(digits);
+                            value.push(c);
+                        }
+                        c => return self.error(format!("unknown escape `\\{}`", c)),
+                    }
+                }
+                '\n' | '\r' | '\0' => return self.error("unterminated string literal"),
+                c => {
+                    self.cursor.consume();
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> String {
+        let start = self.cursor.pos;
+        self.cursor.consume_str("-");
+        self.cursor.consume_while(|c| c.is_ascii_digit());
+        if self.cursor.peek() == '.' && self.cursor.peek_n(1).is_ascii_digit() {
+            self.cursor.consume();
+            self.cursor.consume_while(|c| c.is_ascii_digit());
+        }
+        self.cursor.current_str(start).to_string()
+    }
+
+    fn parse_identifier(&mut self) -> Result<String> {
+        if !self.cursor.peek().is_ascii_alphabetic() {
+            return self.error("expected an identifier");
+        }
+        Ok(self.cursor.consume_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+    }
+
+    fn next_line(&self) -> Option<NextLine> {
+        let mut line = NextLine { chars: 0, line_breaks: 0, indent: 0, first: '\0' };
+        for c in self.cursor.source[self.cursor.pos..].chars() {
+            match c {
+                '\r' => {}
+                '\n' => {
+                    line.line_breaks += 1;
+                    line.indent = 0;
+                }
+                ' ' => line.indent += 1,
+                c => {
+                    line.first = c;
+                    return (line.line_breaks > 0).then_some(line);
+                }
+            }
+            line.chars += 1;
+        }
+        None
+    }
+
+    fn expect_line_end(&mut self) -> Result<()> {
+        self.skip_blank_inline();
+        match self.cursor.peek() {
+            '\n' | '\r' | '\0' => Ok(()),
+            '}' => self.error("unbalanced `}`; write `{\"}\"}` for a literal brace"),
+            _ => self.error("expected a line break"),
+        }
+    }
+
+    fn skip_blank_inline(&mut self) {
+        self.cursor.consume_while(|c| c == ' ');
+    }
+
+    fn skip_blank_lines(&mut self) {
+        loop {
+            let rest = &self.cursor.source[self.cursor.pos..];
+            let blank = rest.chars().take_while(|c| *c == ' ' || *c == '\r').count();
+            match rest[blank..].chars().next() {
+                Some('\n') => self.cursor.consume_n(blank + 1),
+                None => return self.cursor.consume_n(blank),
+                Some(_) => return,
+            }
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::parse_error(message.into(), self.cursor.span_at_current()))
+    }
+}
+
+/// Removes the indentation common to all indented lines, and the line break before a block pattern.
+/// Trailing spaces of the pattern are not part of it.
+fn dedent(pieces: Vec<Piece>) -> Vec<PatternElement> {
+    let common =
+        pieces.iter().filter_map(|piece| if let Piece::Indent(_, indent) = piece { Some(*indent) } else { None }).min();
+    let mut elements: Vec<PatternElement> = Vec::new();
+    for (index, piece) in pieces.into_iter().enumerate() {
+        let text = match piece {
+            Piece::Placeable(expression) => {
+                elements.push(PatternElement::Placeable(expression));
+                continue;
+            }
+            Piece::Text(text) => text,
+            Piece::Indent(line_breaks, indent) => {
+                let spaces = " ".repeat(indent - common.unwrap_or(0));
+                if index == 0 { spaces } else { "\n".repeat(line_breaks) + &spaces }
+            }
+        };
+        match elements.last_mut() {
+            Some(PatternElement::Text(last)) => last.push_str(&text),
+            _ => elements.push(PatternElement::Text(text)),
+        }
+    }
+    if let Some(PatternElement::Text(last)) = elements.last_mut() {
+        let trimmed = last.trim_end_matches(' ').len();
+        last.truncate(trimmed);
+    }
+    elements.retain(|element| !matches!(element, PatternElement::Text(text) if text.is_empty()));
+    elements
+}
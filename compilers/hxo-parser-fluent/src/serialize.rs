@@ -0,0 +1,98 @@
+//! Writes message tables as `.ftl`: `id = value` for text, `{ val, attribute... }` tables as a message
+//! with `.attribute` lines.
+
+use crate::FluentParser;
+use hxo_parser::MetadataSerializer;
+use hxo_types::{Error, HxoValue, Result, Span};
+use std::collections::HashMap;
+
+impl MetadataSerializer for FluentParser {
+    fn serialize(&self, value: &HxoValue, _lang: &str) -> Result<String> {
+        let HxoValue::Object(messages) = value
+        else {
+            return Err(error("expected a table of messages".to_string()));
+        };
+        let mut ids: Vec<&String> = messages.keys().collect();
+        ids.sort();
+        let mut out = String::new();
+        for id in ids {
+            check_identifier(id)?;
+            match &messages[id] {
+                HxoValue::Object(attributes) => write_message(id, attributes, &mut out)?,
+                value => out.push_str(&format!("{} = {}\n", id, pattern(id, value)?)),
+            }
+        }
+        Ok(out)
+    }
+
+    fn appends(&self) -> bool {
+        true
+    }
+}
+
+fn write_message(id: &str, attributes: &HashMap<String, HxoValue>, out: &mut String) -> Result<()> {
+    match attributes.get("val") {
+        Some(value) => out.push_str(&format!("{} = {}\n", id, pattern(id, value)?)),
+        None => out.push_str(&format!("{} =\n", id)),
+    }
+    let mut names: Vec<&String> = attributes.keys().filter(|name| *name != "val").collect();
+    names.sort();
+    for name in names {
+        check_identifier(name)?;
+        let key = format!("{}.{}", id, name);
+        out.push_str(&format!("    .{} = {}\n", name, pattern(&key, &attributes[name])?.replace("\n    ", "\n        ")));
+    }
+    Ok(())
+}
+
+/// Text as a pattern: braces become string literals, and continuation lines are indented, with a
+/// leading `[`, `*` or `.` quoted so that it does not read as a variant or an attribute.
+fn pattern(key: &str, value: &HxoValue) -> Result<String> {
+    let text = match value {
+        HxoValue::String(text) => text.clone(),
+        HxoValue::Number(number) => number.to_string(),
+        HxoValue::Bool(flag) => flag.to_string(),
+        _ => return Err(error(format!("`{}` is not text", key))),
+    };
+    if text.trim().is_empty() {
+        return Ok(format!("{{\"{}\"}}", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")));
+    }
+    let lines: Vec<String> = text
+        .split('\n')
+        .map(|line| {
+            let line: String =
+                line.chars().map(|c| if matches!(c, '{' | '}') { format!("{{\"{}\"}}", c) } else { c.to_string() }).collect();
+            match line.chars().next() {
+                Some(first @ ('[' | '*' | '.')) => format!("{{\"{}\"}}{}", first, &line[1..]),
+                _ => line,
+            }
+        })
+        .collect();
+    let first = &lines[0];
+    // Leading spaces of the value would be dropped
+    let first = match first.len() - first.trim_start().len() {
+        0 => first.clone(),
+        spaces => format!("{{\"{}\"}}{}", &first[..spaces], &first[spaces..]),
+    };
+    let mut out = first;
+    for line in &lines[1..] {
+        out.push_str("\n    ");
+        out.push_str(line);
+    }
+    Ok(out)
+}
+
+fn check_identifier(id: &str) -> Result<()> {
+    if id.starts_with(|c: char| c.is_ascii_alphabetic())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Ok(())
+    }
+    else {
+        Err(error(format!("`{}` is not a valid message identifier", id)))
+    }
+}
+
+fn error(reason: String) -> Error {
+    Error::invalid_i18n(format!("ftl: {}", reason), Span::unknown())
+}
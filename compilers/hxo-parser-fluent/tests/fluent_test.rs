@@ -1,6 +1,8 @@
-use hxo_parser::{MetadataParser, ParseState};
-use hxo_parser_fluent::FluentParser;
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
+use hxo_parser_fluent::{FluentParser, ast::Entry, compile_resource, parse_resource};
+use hxo_parser_icu::plural_function;
 use hxo_types::HxoValue;
+use std::collections::HashMap;
 
 #[test]
 fn test_parse_fluent_basic() {
@@ -68,3 +70,171 @@ world = World
         panic!("Expected object");
     }
 }
+
+fn parse(source: &str) -> HashMap<String, HxoValue> {
+    let mut state = ParseState::new(source);
+    match FluentParser.parse(&mut state, "ftl").unwrap() {
+        HxoValue::Object(map) => map,
+        _ => panic!("Expected object"),
+    }
+}
+
+#[test]
+fn test_multiline_patterns_are_dedented() {
+    let source =
+        "intro =\n    First line\n      indented\n\n    after a blank line\nnext = Inline start\n    continues here   \n";
+    let map = parse(source);
+    assert_eq!(map["intro"].as_str().unwrap(), "First line\n  indented\n\nafter a blank line");
+    assert_eq!(map["next"].as_str().unwrap(), "Inline start\ncontinues here");
+}
+
+#[test]
+fn test_variables_become_placeholders() {
+    let map = parse("hello = Hello, { $name }!\nbrace = Literal {\"{\"} brace\n");
+    assert_eq!(map["hello"], HxoValue::String("Hello, {name}!".to_string()));
+    // A literal brace would read as a placeholder
+    assert_eq!(map["brace"], HxoValue::Raw("(args = {}, locale) => `Literal { brace`".to_string()));
+}
+
+#[test]
+fn test_terms_and_message_references_are_resolved() {
+    let source = r#"
+-brand = { $case ->
+    [genitive] Firefoxes
+   *[nominative] Firefox
+}
+about = About { -brand }
+owner = { -brand(case: "genitive") } owner
+welcome = { about }, { $user }
+"#;
+    let map = parse(source);
+    assert!(!map.contains_key("-brand") && !map.contains_key("brand"));
+    assert_eq!(
+        map["about"].as_str().unwrap(),
+        "(args = {}, locale) => `About ${(($s) => $s === 'genitive' ? `Firefoxes` : `Firefox`)(undefined)}`"
+    );
+    assert!(map["owner"].as_str().unwrap().contains("$s === 'genitive' ? `Firefoxes` : `Firefox`)('genitive')} owner`"));
+    assert!(map["welcome"].as_str().unwrap().ends_with("(undefined)}, ${args.user}`"));
+}
+
+#[test]
+fn test_select_on_plural_categories() {
+    let source = r#"
+emails = { $count ->
+    [0] No emails
+    [one] One email
+   *[other] { NUMBER($count, minimumFractionDigits: 0) } emails
+}
+place = { NUMBER($pos, type: "ordinal") ->
+    [one] {$pos}st
+   *[other] {$pos}th
+}
+"#;
+    let map = parse(source);
+    assert_eq!(
+        map["emails"],
+        HxoValue::Raw(concat!(
+            "(args = {}, locale) => `${(($s, $c = typeof $s === 'number' ? new Intl.PluralRules(locale).select($s) : $s) => ",
+            "$s === 0 ? `No emails` : $c === 'one' ? `One email` : ",
+            "`${new Intl.NumberFormat(locale, { minimumFractionDigits: 0 }).format(args.count)} emails`)(args.count)}`"
+        )
+        .to_string())
+    );
+    assert!(map["place"].as_str().unwrap().contains("new Intl.PluralRules(locale, { type: 'ordinal' }).select($s)"));
+
+    let map = parse("items = { $n ->\n    [one] One item\n    [1] Exactly one\n   *[other] Items\n}\n");
+    assert!(map["items"].as_str().unwrap().contains("$s === 1 ? `Exactly one` : $c === 'one' ? `One item` : `Items`"));
+}
+
+#[test]
+fn test_select_uses_the_plural_rules_of_the_locale() {
+    let source = "items = { $n ->\n    [one] One item\n   *[other] Items\n}\nplace = { NUMBER($pos, type: \"ordinal\") ->\n    [two] {$pos}nd\n   *[other] {$pos}th\n}\n";
+    let resource = parse_resource(source).unwrap();
+
+    let ja = compile_resource(&resource, Some("ja")).unwrap();
+    assert!(ja["items"].as_str().unwrap().contains("$c = typeof $s === 'number' ? ((x) => 'other')($s) : $s"));
+
+    let en = compile_resource(&resource, Some("en")).unwrap();
+    let place = en["place"].as_str().unwrap();
+    assert!(place.contains(&format!("typeof $s === 'number' ? ({})($s) : $s", plural_function("en", true))), "{}", place);
+    assert!(!place.contains("Intl.PluralRules"));
+}
+
+#[test]
+fn test_attributes_have_spans_and_compile() {
+    let source = "login =\n    .title = Hi { $user }\n    .placeholder = { DATETIME($when, month: \"long\") }\n";
+    let map = parse(source);
+    let HxoValue::Object(login) = &map["login"]
+    else {
+        panic!("Expected object for login");
+    };
+    assert!(!login.contains_key("val"));
+    assert_eq!(login["title"].as_str().unwrap(), "Hi {user}");
+    assert_eq!(
+        login["placeholder"].as_str().unwrap(),
+        "(args = {}, locale) => `${new Intl.DateTimeFormat(locale, { month: 'long' }).format(args.when)}`"
+    );
+
+    let resource = parse_resource(source).unwrap();
+    let Entry::Message(message) = &resource.entries[0]
+    else {
+        panic!("Expected message");
+    };
+    let span = message.attributes[1].span;
+    assert_eq!((span.start.line, span.start.column), (3, 5));
+    assert_eq!(
+        &source[span.start.offset as usize..span.end.offset as usize],
+        ".placeholder = { DATETIME($when, month: \"long\") }"
+    );
+}
+
+#[test]
+fn test_comments_attach_to_messages() {
+    let resource = parse_resource("### Resource\n\n# Shown on the home page\nhome = Home\n").unwrap();
+    assert!(matches!(&resource.entries[0], Entry::Comment(text, _) if text == "Resource"));
+    let Entry::Message(message) = &resource.entries[1]
+    else {
+        panic!("Expected message");
+    };
+    assert_eq!(message.comment.as_deref(), Some("Shown on the home page"));
+}
+
+#[test]
+fn test_errors_point_at_the_problem() {
+    let err = parse_resource("a = { $n ->\n    [one] One\n}\n").unwrap_err();
+    assert!(err.to_string().contains("default"), "{}", err);
+    let mut state = ParseState::new("a = { missing }\n");
+    let err = FluentParser.parse(&mut state, "ftl").unwrap_err();
+    assert!(err.to_string().contains("unknown message `missing`"), "{}", err);
+    assert_eq!(err.span().start.column, 7);
+    let err = parse_resource("a = { FOO() }\n").map(|resource| compile_resource(&resource, None)).unwrap().unwrap_err();
+    assert!(err.to_string().contains("unknown function `FOO`"), "{}", err);
+}
+
+#[test]
+fn test_serialize_messages() {
+    let value = HxoValue::Object(HashMap::from([
+        ("braces".to_string(), HxoValue::String("Use {name}\n[not a variant]".to_string())),
+        (
+            "login".to_string(),
+            HxoValue::Object(HashMap::from([
+                ("val".to_string(), HxoValue::String("Login".to_string())),
+                ("title".to_string(), HxoValue::String("Please login".to_string())),
+            ])),
+        ),
+    ]));
+    let text = FluentParser.serialize(&value, "ftl").unwrap();
+    assert_eq!(text, "braces = Use {\"{\"}name{\"}\"}\n    {\"[\"}not a variant]\nlogin = Login\n    .title = Please login\n");
+    let HxoValue::Object(parsed) = FluentParser.parse(&mut ParseState::new(&text), "ftl").unwrap()
+    else {
+        panic!("Expected object");
+    };
+    assert_eq!(parsed["login"], value.as_object().unwrap()["login"]);
+    // A literal `{` reads back as a formatter, which keeps it from passing for a placeholder
+    assert_eq!(parsed["braces"], HxoValue::Raw("(args = {}, locale) => `Use {name}\n[not a variant]`".to_string()));
+    assert!(
+        FluentParser
+            .serialize(&HxoValue::Object(HashMap::from([("a.b".to_string(), HxoValue::String("x".to_string()))])), "ftl")
+            .is_err()
+    );
+}
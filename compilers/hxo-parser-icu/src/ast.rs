@@ -0,0 +1,65 @@
+use hxo_types::Span;
+
+/// A parsed message: text, arguments and `#` in plural branches.
+pub type Message = Vec<MessagePart>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessagePart {
+    /// Text with quoting (`''`, `'{'`) already resolved.
+    Text(String),
+    /// `#` inside a plural branch: the plural value minus its offset, formatted as a number.
+    Pound(Span),
+    Argument(Argument),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    /// A name or a position, `{0}`.
+    pub name: String,
+    pub kind: ArgumentKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentKind {
+    /// `{name}`
+    Simple,
+    /// `{n, number}`, `{n, number, percent}`, `{n, number, ::currency/EUR}`
+    Number(Option<FormatStyle>),
+    /// `{d, date, short}`, `{d, date, ::yyyyMMMd}`
+    Date(Option<FormatStyle>),
+    Time(Option<FormatStyle>),
+    /// `{n, plural, offset:1 =0 {...} one {...} other {...}}`, and `selectordinal` when `ordinal`.
+    Plural {
+        ordinal: bool,
+        offset: f64,
+        branches: Vec<Branch>,
+    },
+    /// `{gender, select, male {...} other {...}}`
+    Select {
+        branches: Vec<Branch>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatStyle {
+    /// `short`, `integer`, `percent`...
+    Named(String),
+    /// The skeleton after `::`.
+    Skeleton(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    pub selector: Selector,
+    pub message: Message,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selector {
+    /// `=0`, compared with the value before the offset.
+    Exact(f64),
+    /// A plural category, or any word for `select`.
+    Keyword(String),
+}
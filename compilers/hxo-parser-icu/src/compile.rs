@@ -0,0 +1,260 @@
+//! Compiles ICU messages to i18n messages: `{name}` placeholder text when a message has only simple
+//! arguments, otherwise a JS formatter `(args = {}, locale) => string` with the locale's plural
+//! rules built in and numbers and dates formatted through `Intl`.
+
+use crate::{
+    ast::{Argument, ArgumentKind, Branch, FormatStyle, Message, MessagePart, Selector},
+    parser::parse_message,
+    plural::plural_function,
+};
+use hxo_ir::I18nMessage;
+use hxo_types::{Error, Result, Span};
+
+/// Compiles the message `source` of `locale`.
+pub fn compile_message(source: &str, locale: &str) -> Result<I18nMessage> {
+    let message = parse_message(source)?;
+    // Quoting may have produced a literal `{`, which would read as a placeholder
+    let placeholders = message.iter().all(|part| match part {
+        MessagePart::Text(text) => !text.contains('{'),
+        MessagePart::Argument(Argument { kind: ArgumentKind::Simple, .. }) => true,
+        _ => false,
+    });
+    if placeholders {
+        let text = message
+            .iter()
+            .map(|part| match part {
+                MessagePart::Text(text) => text.clone(),
+                MessagePart::Argument(argument) => format!("{{{}}}", argument.name),
+                MessagePart::Pound(_) => String::new(),
+            })
+            .collect();
+        return Ok(I18nMessage::Text(text));
+    }
+    let body = IcuCompiler { locale, plural: Vec::new() }.compile(&message)?;
+    Ok(I18nMessage::Formatter(format!("(args = {{}}, locale) => {}", body)))
+}
+
+struct IcuCompiler<'l> {
+    locale: &'l str,
+    /// Offsets of the enclosing plurals; `#` refers to the innermost one.
+    plural: Vec<f64>,
+}
+
+impl<'l> IcuCompiler<'l> {
+    /// A template literal for `message`.
+    fn compile(&mut self, message: &Message) -> Result<String> {
+        let mut code = String::from("`");
+        for part in message {
+            match part {
+                MessagePart::Text(text) => code.push_str(&text.replace('\\', "\\\\").replace('`', "\\`").replace("${", "\\${")),
+                MessagePart::Pound(span) => {
+                    let Some(offset) = self.plural.last()
+                    else {
+                        return Err(Error::parse_error("`#` outside of a plural".to_string(), *span));
+                    };
+                    code.push_str(&format!("${{new Intl.NumberFormat(locale).format({})}}", minus("$n", *offset)));
+                }
+                MessagePart::Argument(argument) => code.push_str(&format!("${{{}}}", self.compile_argument(argument)?)),
+            }
+        }
+        code.push('`');
+        Ok(code)
+    }
+
+    fn compile_argument(&mut self, argument: &Argument) -> Result<String> {
+        let value = variable(&argument.name);
+        match &argument.kind {
+            ArgumentKind::Simple => Ok(value),
+            ArgumentKind::Number(style) => {
+                let options = number_options(style.as_ref(), argument.span)?;
+                Ok(format!("new Intl.NumberFormat(locale{}).format({})", options, value))
+            }
+            ArgumentKind::Date(style) | ArgumentKind::Time(style) => {
+                let is_date = matches!(argument.kind, ArgumentKind::Date(_));
+                let options = date_options(style.as_ref(), is_date, argument.span)?;
+                Ok(format!("new Intl.DateTimeFormat(locale{}).format({})", options, value))
+            }
+            ArgumentKind::Plural { ordinal, offset, branches } => {
+                self.plural.push(*offset);
+                let choices = self.compile_branches(branches, |selector| match selector {
+                    Selector::Exact(number) => format!("$n === {}", number),
+                    Selector::Keyword(category) => format!("$c === '{}'", category),
+                });
+                self.plural.pop();
+                let category = format!("({})({})", plural_function(self.locale, *ordinal), minus("$n", *offset));
+                Ok(format!("(($n, $c = {}) => {})({})", category, choices?, value))
+            }
+            ArgumentKind::Select { branches } => {
+                let choices = self.compile_branches(branches, |selector| match selector {
+                    Selector::Exact(number) => format!("$s === {}", number),
+                    Selector::Keyword(key) => format!("$s === '{}'", key.replace('\'', "\\'")),
+                })?;
+                Ok(format!("(($s) => {})({})", choices, value))
+            }
+        }
+    }
+
+    /// `test ? branch : ... : other`; `other` is the fallback whatever its position.
+    fn compile_branches(&mut self, branches: &[Branch], test: impl Fn(&Selector) -> String) -> Result<String> {
+        let mut code = String::new();
+        let mut other = String::new();
+        for branch in branches {
+            let message = self.compile(&branch.message)?;
+            match &branch.selector {
+                Selector::Keyword(keyword) if keyword == "other" => other = message,
+                selector => code.push_str(&format!("{} ? {} : ", test(selector), message)),
+            }
+        }
+        code.push_str(&other);
+        Ok(code)
+    }
+}
+
+/// `, { ... }` options of `Intl.NumberFormat` for a number style, empty for the default.
+fn number_options(style: Option<&FormatStyle>, span: Span) -> Result<String> {
+    let options: Vec<String> = match style {
+        None => return Ok(String::new()),
+        Some(FormatStyle::Named(name)) => match name.as_str() {
+            "integer" => vec!["maximumFractionDigits: 0".to_string()],
+            "percent" => vec!["style: 'percent'".to_string()],
+            "currency" => return Err(Error::parse_error("`currency` needs a code; use `::currency/EUR`".to_string(), span)),
+            _ => {
+                return Err(Error::parse_error(
+                    format!("number pattern `{}` is not supported; use a `::` skeleton", name),
+                    span,
+                ));
+            }
+        },
+        Some(FormatStyle::Skeleton(skeleton)) => {
+            let mut options = Vec::new();
+            let tokens: Vec<&str> = skeleton.split_whitespace().collect();
+            // `percent` alone appends `%` to the value; with `scale/100` the value is a fraction
+            let scaled = tokens.iter().any(|token| matches!(*token, "scale/100" | "%x100"));
+            for token in tokens {
+                let (stem, option) = token.split_once('/').unwrap_or((token, ""));
+                match stem {
+                    "percent" | "%" if scaled => options.push("style: 'percent'".to_string()),
+                    "percent" | "%" => options.push("style: 'unit', unit: 'percent'".to_string()),
+                    "%x100" => options.push("style: 'percent'".to_string()),
+                    "scale" if option == "100" => {}
+                    "currency" if !option.is_empty() => options.push(format!("style: 'currency', currency: '{}'", option)),
+                    "unit" | "measure-unit" if !option.is_empty() => {
+                        // `measure-unit/length-meter` names the type first
+                        let unit = if stem == "measure-unit" {
+                            option.split_once('-').map_or(option, |(_, unit)| unit)
+                        }
+                        else {
+                            option
+                        };
+                        options.push(format!("style: 'unit', unit: '{}'", unit));
+                    }
+                    "unit-width-full-name" => options.push("unitDisplay: 'long', currencyDisplay: 'name'".to_string()),
+                    "unit-width-narrow" => options.push("unitDisplay: 'narrow', currencyDisplay: 'narrowSymbol'".to_string()),
+                    "compact-short" | "K" => options.push("notation: 'compact', compactDisplay: 'short'".to_string()),
+                    "compact-long" | "KK" => options.push("notation: 'compact', compactDisplay: 'long'".to_string()),
+                    "scientific" => options.push("notation: 'scientific'".to_string()),
+                    "engineering" => options.push("notation: 'engineering'".to_string()),
+                    "group-off" | ",_" => options.push("useGrouping: false".to_string()),
+                    "sign-always" | "+!" => options.push("signDisplay: 'always'".to_string()),
+                    "sign-never" | "+_" => options.push("signDisplay: 'never'".to_string()),
+                    "sign-except-zero" | "+?" => options.push("signDisplay: 'exceptZero'".to_string()),
+                    "precision-integer" => options.push("maximumFractionDigits: 0".to_string()),
+                    "integer-width" if option.starts_with('*') && option[1..].chars().all(|c| c == '0') => {
+                        options.push(format!("minimumIntegerDigits: {}", option.len() - 1));
+                    }
+                    _ if stem.starts_with('.') => options.push(digits(&stem[1..], '0', "FractionDigits", token, span)?),
+                    _ if stem.starts_with('@') => options.push(digits(stem, '@', "SignificantDigits", token, span)?),
+                    _ if !stem.is_empty() && stem.chars().all(|c| c == '0') => {
+                        options.push(format!("minimumIntegerDigits: {}", stem.len()))
+                    }
+                    _ => return Err(Error::parse_error(format!("unsupported number skeleton `{}`", token), span)),
+                }
+            }
+            options
+        }
+    };
+    Ok(format!(", {{ {} }}", options.join(", ")))
+}
+
+/// Precision such as `00#` (fraction) or `@@#`: required digits, then optional `#`, or `+`/`*` for
+/// no maximum.
+fn digits(stem: &str, required: char, name: &str, token: &str, span: Span) -> Result<String> {
+    let min = stem.chars().take_while(|c| *c == required).count();
+    let rest = &stem[min..];
+    let max = match rest {
+        "+" | "*" => 20.max(min),
+        _ if rest.chars().all(|c| c == '#') => min + rest.len(),
+        _ => return Err(Error::parse_error(format!("unsupported number skeleton `{}`", token), span)),
+    };
+    Ok(format!("minimum{name}: {}, maximum{name}: {}", min, max, name = name))
+}
+
+/// `, { ... }` options of `Intl.DateTimeFormat` for a date or time style.
+fn date_options(style: Option<&FormatStyle>, is_date: bool, span: Span) -> Result<String> {
+    let key = if is_date { "dateStyle" } else { "timeStyle" };
+    let skeleton = match style {
+        None => return Ok(format!(", {{ {}: 'medium' }}", key)),
+        Some(FormatStyle::Named(name)) if matches!(name.as_str(), "short" | "medium" | "long" | "full") => {
+            return Ok(format!(", {{ {}: '{}' }}", key, name));
+        }
+        Some(FormatStyle::Named(name)) => {
+            return Err(Error::parse_error(format!("date pattern `{}` is not supported; use a `::` skeleton", name), span));
+        }
+        Some(FormatStyle::Skeleton(skeleton)) => skeleton,
+    };
+    let text = |count: usize| match count {
+        1..=3 => "'short'",
+        4 => "'long'",
+        _ => "'narrow'",
+    };
+    let number = |count: usize| if count == 2 { "'2-digit'" } else { "'numeric'" };
+    let mut options = Vec::new();
+    let chars: Vec<char> = skeleton.chars().collect();
+    let mut index = 0;
+    while index < chars.len() {
+        let letter = chars[index];
+        let count = chars[index..].iter().take_while(|c| **c == letter).count();
+        index += count;
+        match letter {
+            'G' => options.push(format!("era: {}", text(count))),
+            'y' => options.push(format!("year: {}", number(count))),
+            'M' | 'L' if count <= 2 => options.push(format!("month: {}", number(count))),
+            'M' | 'L' => options.push(format!("month: {}", text(count - 2))),
+            'd' => options.push(format!("day: {}", number(count))),
+            'E' | 'c' => options.push(format!("weekday: {}", text(count))),
+            'j' => options.push(format!("hour: {}", number(count))),
+            'h' => options.push(format!("hour: {}, hourCycle: 'h12'", number(count))),
+            'H' => options.push(format!("hour: {}, hourCycle: 'h23'", number(count))),
+            'K' => options.push(format!("hour: {}, hourCycle: 'h11'", number(count))),
+            'k' => options.push(format!("hour: {}, hourCycle: 'h24'", number(count))),
+            'm' => options.push(format!("minute: {}", number(count))),
+            's' => options.push(format!("second: {}", number(count))),
+            'S' => options.push(format!("fractionalSecondDigits: {}", count.min(3))),
+            'z' => options.push(format!("timeZoneName: {}", if count < 4 { "'short'" } else { "'long'" })),
+            'v' => options.push(format!("timeZoneName: {}", if count < 4 { "'shortGeneric'" } else { "'longGeneric'" })),
+            'O' => options.push(format!("timeZoneName: {}", if count < 4 { "'shortOffset'" } else { "'longOffset'" })),
+            // The day period follows from the hour cycle
+            'a' => {}
+            _ => return Err(Error::parse_error(format!("unsupported date skeleton field `{}`", letter), span)),
+        }
+    }
+    Ok(format!(", {{ {} }}", options.join(", ")))
+}
+
+fn minus(value: &str, offset: f64) -> String {
+    if offset == 0.0 { value.to_string() } else { format!("{} - {}", value, offset) }
+}
+
+fn variable(name: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("args[{}]", name)
+    }
+    else if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        format!("args.{}", name)
+    }
+    else {
+        format!("args['{}']", name)
+    }
+}
@@ -0,0 +1,8 @@
+pub mod ast;
+mod compile;
+pub mod parser;
+pub mod plural;
+
+pub use compile::compile_message;
+pub use parser::parse_message;
+pub use plural::{plural_function, plural_rules};
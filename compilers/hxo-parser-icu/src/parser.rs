@@ -0,0 +1,205 @@
+//! ICU MessageFormat syntax, with the apostrophe quoting of ICU 4.8 and later: `''` is a literal
+//! apostrophe and `'` only starts quoted text before `{`, `}`, `|`, or `#` in a plural branch.
+
+use crate::ast::{Argument, ArgumentKind, Branch, FormatStyle, Message, MessagePart, Selector};
+use hxo_types::{Cursor, Error, Result};
+
+pub const PLURAL_CATEGORIES: &[&str] = &["zero", "one", "two", "few", "many", "other"];
+
+/// Parses a whole message.
+pub fn parse_message(source: &str) -> Result<Message> {
+    let mut cursor = Cursor::new(source);
+    IcuParserImpl { cursor: &mut cursor }.parse_message(false, false)
+}
+
+struct IcuParserImpl<'a, 'b> {
+    cursor: &'a mut Cursor<'b>,
+}
+
+impl<'a, 'b> IcuParserImpl<'a, 'b> {
+    /// Parses up to the end of the source, or up to the `}` closing a branch when `nested`.
+    fn parse_message(&mut self, nested: bool, in_plural: bool) -> Result<Message> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        loop {
+            match self.cursor.peek() {
+                '\0' if self.cursor.is_eof() => {
+                    if nested {
+                        return self.error("unclosed branch, expected `}`");
+                    }
+                    break;
+                }
+                '}' if nested => break,
+                '}' => return self.error("unbalanced `}`; quote it as `'}'`"),
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(MessagePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(MessagePart::Argument(self.parse_argument(in_plural)?));
+                }
+                '#' if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(MessagePart::Text(std::mem::take(&mut text)));
+                    }
+                    let start = self.cursor.position();
+                    self.cursor.consume();
+                    parts.push(MessagePart::Pound(self.cursor.span_from(start)));
+                }
+                '\'' => self.parse_apostrophe(in_plural, &mut text),
+                c => {
+                    self.cursor.consume();
+                    text.push(c);
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(MessagePart::Text(text));
+        }
+        Ok(parts)
+    }
+
+    fn parse_apostrophe(&mut self, in_plural: bool, text: &mut String) {
+        self.cursor.consume();
+        match self.cursor.peek() {
+            '\'' => {
+                self.cursor.consume();
+                text.push('\'');
+            }
+            c if matches!(c, '{' | '}' | '|') || (c == '#' && in_plural) => {
+                // Quoted up to the next lone apostrophe, or to the end of the message
+                while !self.cursor.is_eof() {
+                    let c = self.cursor.consume();
+                    if c != '\'' {
+                        text.push(c);
+                    }
+                    else if self.cursor.peek() == '\'' {
+                        self.cursor.consume();
+                        text.push('\'');
+                    }
+                    else {
+                        break;
+                    }
+                }
+            }
+            _ => text.push('\''),
+        }
+    }
+
+    fn parse_argument(&mut self, in_plural: bool) -> Result<Argument> {
+        let start = self.cursor.position();
+        self.cursor.expect('{')?;
+        self.cursor.skip_whitespace();
+        let name = self.cursor.consume_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+        if name.is_empty() {
+            return self.error("expected an argument name");
+        }
+        self.cursor.skip_whitespace();
+        if self.cursor.consume_str("}") {
+            return Ok(Argument { name, kind: ArgumentKind::Simple, span: self.cursor.span_from(start) });
+        }
+        self.cursor.expect(',')?;
+        self.cursor.skip_whitespace();
+        let kind_start = self.cursor.position();
+        let kind = self.cursor.consume_while(|c| c.is_ascii_alphabetic());
+        self.cursor.skip_whitespace();
+        let kind = match kind.as_str() {
+            "number" => ArgumentKind::Number(self.parse_style()?),
+            "date" => ArgumentKind::Date(self.parse_style()?),
+            "time" => ArgumentKind::Time(self.parse_style()?),
+            "plural" | "selectordinal" => {
+                self.cursor.expect(',')?;
+                self.cursor.skip_whitespace();
+                let offset = if self.cursor.consume_str("offset:") {
+                    self.cursor.skip_whitespace();
+                    let number = self.cursor.consume_while(|c| c.is_ascii_digit() || c == '.');
+                    self.cursor.skip_whitespace();
+                    number.parse().map_err(|_| {
+                        Error::parse_error("expected a number after `offset:`".to_string(), self.cursor.span_at_current())
+                    })?
+                }
+                else {
+                    0.0
+                };
+                let branches = self.parse_branches(true, true)?;
+                ArgumentKind::Plural { ordinal: kind == "selectordinal", offset, branches }
+            }
+            "select" => {
+                self.cursor.expect(',')?;
+                ArgumentKind::Select { branches: self.parse_branches(false, in_plural)? }
+            }
+            "spellout" | "ordinal" | "duration" => {
+                return Err(Error::parse_error(
+                    format!("`{}` arguments are not supported", kind),
+                    self.cursor.span_from(kind_start),
+                ));
+            }
+            "" => return self.error("expected an argument type"),
+            _ => {
+                return Err(Error::parse_error(format!("unknown argument type `{}`", kind), self.cursor.span_from(kind_start)));
+            }
+        };
+        self.cursor.expect('}')?;
+        Ok(Argument { name, kind, span: self.cursor.span_from(start) })
+    }
+
+    /// The style after `number`, `date` or `time`, up to the closing `}`.
+    fn parse_style(&mut self) -> Result<Option<FormatStyle>> {
+        if !self.cursor.consume_str(",") {
+            return Ok(None);
+        }
+        self.cursor.skip_whitespace();
+        let is_skeleton = self.cursor.consume_str("::");
+        let style = self.cursor.consume_while(|c| c != '}').trim().to_string();
+        if style.is_empty() {
+            return self.error("expected a style");
+        }
+        Ok(Some(if is_skeleton { FormatStyle::Skeleton(style) } else { FormatStyle::Named(style) }))
+    }
+
+    /// Branches of a `plural` or `select`; `#` stays special in a `select` nested in a plural branch.
+    fn parse_branches(&mut self, plural: bool, in_plural: bool) -> Result<Vec<Branch>> {
+        let start = self.cursor.position();
+        let mut branches = Vec::new();
+        loop {
+            self.cursor.skip_whitespace();
+            if self.cursor.peek() == '}' || self.cursor.is_eof() {
+                break;
+            }
+            let branch_start = self.cursor.position();
+            let selector = if self.cursor.consume_str("=") {
+                let number = self.cursor.consume_while(|c| c.is_ascii_digit() || c == '.' || c == '-');
+                let Ok(number) = number.parse()
+                else {
+                    return self.error("expected a number after `=`");
+                };
+                Selector::Exact(number)
+            }
+            else {
+                let keyword = self.cursor.consume_while(|c| c.is_alphanumeric() || c == '_' || c == '-');
+                if keyword.is_empty() {
+                    return self.error("expected a branch selector");
+                }
+                if plural && !PLURAL_CATEGORIES.contains(&keyword.as_str()) {
+                    return Err(Error::parse_error(
+                        format!("`{}` is not a plural category; use one of {} or `=N`", keyword, PLURAL_CATEGORIES.join(", ")),
+                        self.cursor.span_from(branch_start),
+                    ));
+                }
+                Selector::Keyword(keyword)
+            };
+            self.cursor.skip_whitespace();
+            self.cursor.expect('{')?;
+            let message = self.parse_message(true, in_plural)?;
+            self.cursor.expect('}')?;
+            branches.push(Branch { selector, message, span: self.cursor.span_from(branch_start) });
+        }
+        if !branches.iter().any(|branch| branch.selector == Selector::Keyword("other".to_string())) {
+            return Err(Error::parse_error("an `other` branch is required".to_string(), self.cursor.span_from(start)));
+        }
+        Ok(branches)
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::parse_error(message.into(), self.cursor.span_at_current()))
+    }
+}
@@ -0,0 +1,200 @@
+//! CLDR plural rules, compiled to JS so that formatters select plural categories without a runtime
+//! library. Locales without shipped rules fall back to `Intl.PluralRules`.
+
+/// `category: condition` pairs, tried in order; `other` is implied.
+type Rules = &'static [(&'static str, &'static str)];
+
+const MANY_MILLIONS: &str = "e = 0 and i != 0 and i % 1000000 = 0 and v = 0 or e != 0..5";
+const EAST_SLAVIC: Rules = &[
+    ("one", "v = 0 and i % 10 = 1 and i % 100 != 11"),
+    ("few", "v = 0 and i % 10 = 2..4 and i % 100 != 12..14"),
+    ("many", "v = 0 and i % 10 = 0 or v = 0 and i % 10 = 5..9 or v = 0 and i % 100 = 11..14"),
+];
+const SOUTH_SLAVIC: Rules = &[
+    ("one", "v = 0 and i % 10 = 1 and i % 100 != 11 or f % 10 = 1 and f % 100 != 11"),
+    ("few", "v = 0 and i % 10 = 2..4 and i % 100 != 12..14 or f % 10 = 2..4 and f % 100 != 12..14"),
+];
+const ONE_INTEGER: Rules = &[("one", "i = 1 and v = 0")];
+const ONE: Rules = &[("one", "n = 1")];
+const ONE_OR_ZERO: Rules = &[("one", "i = 0 or n = 1")];
+const NONE: Rules = &[];
+
+/// Language (or `language-region`) to cardinal and ordinal rules, from CLDR 44.
+const LOCALES: &[(&str, Rules, Rules)] = &[
+    ("af", ONE, NONE),
+    (
+        "ar",
+        &[("zero", "n = 0"), ("one", "n = 1"), ("two", "n = 2"), ("few", "n % 100 = 3..10"), ("many", "n % 100 = 11..99")],
+        NONE,
+    ),
+    ("bg", ONE, NONE),
+    ("bn", ONE_OR_ZERO, &[("one", "n = 1,5,7,8,9,10"), ("two", "n = 2,3"), ("few", "n = 4"), ("many", "n = 6")]),
+    ("bs", SOUTH_SLAVIC, NONE),
+    ("ca", &[("one", "i = 1 and v = 0"), ("many", MANY_MILLIONS)], &[("one", "n = 1,3"), ("two", "n = 2"), ("few", "n = 4")]),
+    ("cs", &[("one", "i = 1 and v = 0"), ("few", "i = 2..4 and v = 0"), ("many", "v != 0")], NONE),
+    (
+        "cy",
+        &[("zero", "n = 0"), ("one", "n = 1"), ("two", "n = 2"), ("few", "n = 3"), ("many", "n = 6")],
+        &[("zero", "n = 0,7,8,9"), ("one", "n = 1"), ("two", "n = 2"), ("few", "n = 3,4"), ("many", "n = 5,6")],
+    ),
+    ("da", &[("one", "n = 1 or t != 0 and i = 0,1")], NONE),
+    ("de", ONE_INTEGER, NONE),
+    ("el", ONE, NONE),
+    (
+        "en",
+        ONE_INTEGER,
+        &[
+            ("one", "n % 10 = 1 and n % 100 != 11"),
+            ("two", "n % 10 = 2 and n % 100 != 12"),
+            ("few", "n % 10 = 3 and n % 100 != 13"),
+        ],
+    ),
+    ("es", &[("one", "n = 1"), ("many", MANY_MILLIONS)], NONE),
+    ("et", ONE_INTEGER, NONE),
+    ("fa", ONE_OR_ZERO, NONE),
+    ("fi", ONE_INTEGER, NONE),
+    ("fil", &[("one", "v = 0 and i = 1,2,3 or v = 0 and i % 10 != 4,6,9 or v != 0 and f % 10 != 4,6,9")], ONE),
+    ("fr", &[("one", "i = 0,1"), ("many", MANY_MILLIONS)], ONE),
+    ("ga", &[("one", "n = 1"), ("two", "n = 2"), ("few", "n = 3..6"), ("many", "n = 7..10")], ONE),
+    ("gl", ONE_INTEGER, NONE),
+    ("he", &[("one", "i = 1 and v = 0 or i = 0 and v != 0"), ("two", "i = 2 and v = 0")], NONE),
+    ("hi", ONE_OR_ZERO, &[("one", "n = 1"), ("two", "n = 2,3"), ("few", "n = 4"), ("many", "n = 6")]),
+    ("hr", SOUTH_SLAVIC, NONE),
+    ("hu", ONE, &[("one", "n = 1,5")]),
+    ("id", NONE, NONE),
+    ("it", &[("one", "i = 1 and v = 0"), ("many", MANY_MILLIONS)], &[("many", "n = 11,8,80,800")]),
+    ("ja", NONE, NONE),
+    ("km", NONE, NONE),
+    ("ko", NONE, NONE),
+    ("lo", NONE, ONE),
+    (
+        "lt",
+        &[("one", "n % 10 = 1 and n % 100 != 11..19"), ("few", "n % 10 = 2..9 and n % 100 != 11..19"), ("many", "f != 0")],
+        NONE,
+    ),
+    (
+        "lv",
+        &[
+            ("zero", "n % 10 = 0 or n % 100 = 11..19 or v = 2 and f % 100 = 11..19"),
+            ("one", "n % 10 = 1 and n % 100 != 11 or v = 2 and f % 10 = 1 and f % 100 != 11 or v != 2 and f % 10 = 1"),
+        ],
+        NONE,
+    ),
+    ("ms", NONE, ONE),
+    ("my", NONE, NONE),
+    ("nb", ONE, NONE),
+    ("nl", ONE_INTEGER, NONE),
+    ("no", ONE, NONE),
+    (
+        "pl",
+        &[
+            ("one", "i = 1 and v = 0"),
+            ("few", "v = 0 and i % 10 = 2..4 and i % 100 != 12..14"),
+            ("many", "v = 0 and i != 1 and i % 10 = 0..1 or v = 0 and i % 10 = 5..9 or v = 0 and i % 100 = 12..14"),
+        ],
+        NONE,
+    ),
+    ("pt", &[("one", "i = 0..1"), ("many", MANY_MILLIONS)], NONE),
+    ("pt-pt", &[("one", "i = 1 and v = 0"), ("many", MANY_MILLIONS)], NONE),
+    ("ro", &[("one", "i = 1 and v = 0"), ("few", "v != 0 or n = 0 or n != 1 and n % 100 = 1..19")], ONE),
+    ("ru", EAST_SLAVIC, NONE),
+    ("sk", &[("one", "i = 1 and v = 0"), ("few", "i = 2..4 and v = 0"), ("many", "v != 0")], NONE),
+    (
+        "sl",
+        &[("one", "v = 0 and i % 100 = 1"), ("two", "v = 0 and i % 100 = 2"), ("few", "v = 0 and i % 100 = 3..4 or v != 0")],
+        NONE,
+    ),
+    ("sr", SOUTH_SLAVIC, NONE),
+    ("sv", ONE_INTEGER, &[("one", "n % 10 = 1,2 and n % 100 != 11,12")]),
+    ("th", NONE, NONE),
+    ("tr", ONE, NONE),
+    ("uk", EAST_SLAVIC, &[("few", "n % 10 = 3 and n % 100 != 13")]),
+    ("vi", NONE, ONE),
+    ("yue", NONE, NONE),
+    ("zh", NONE, NONE),
+];
+
+/// The shipped rules for `locale` (`pt-PT`, `de_AT`, `zh-Hans`), trying the full tag before its language.
+pub fn plural_rules(locale: &str, ordinal: bool) -> Option<Rules> {
+    let tag = locale.to_ascii_lowercase().replace('_', "-");
+    let language = tag.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|(name, ..)| *name == tag)
+        .or_else(|| LOCALES.iter().find(|(name, ..)| *name == language))
+        .map(|(_, cardinal, ordinals)| if ordinal { *ordinals } else { *cardinal })
+}
+
+/// A JS function from a number to its plural category in `locale`.
+pub fn plural_function(locale: &str, ordinal: bool) -> String {
+    let Some(rules) = plural_rules(locale, ordinal)
+    else {
+        let options = if ordinal { ", { type: 'ordinal' }" } else { "" };
+        return format!("(x) => new Intl.PluralRules(locale{}).select(x)", options);
+    };
+    if rules.is_empty() {
+        return "(x) => 'other'".to_string();
+    }
+    let mut body = String::new();
+    for (category, condition) in rules {
+        body.push_str(&format!("{} ? '{}' : ", compile_condition(condition), category));
+    }
+    body.push_str("'other'");
+    // Operands of the rules: n absolute value, i integer digits, v/w visible fraction digit counts
+    // with and without trailing zeros, f/t those digits, e/c exponent (always 0 for JS numbers)
+    let uses =
+        |operand: char| rules.iter().any(|(_, condition)| condition.split_whitespace().any(|word| word == operand.to_string()));
+    let mut operands = vec!["s = String(x).replace('-', '')".to_string(), "n = +s".to_string()];
+    if uses('i') {
+        operands.push("i = Math.trunc(n)".to_string());
+    }
+    if ['v', 'f', 't', 'w'].into_iter().any(uses) {
+        operands.push("F = s.split('.')[1] || ''".to_string());
+    }
+    for (operand, value) in [
+        ('v', "F.length"),
+        ('f', "+F"),
+        ('t', "+F.replace(/0+$/, '')"),
+        ('w', "F.replace(/0+$/, '').length"),
+        ('e', "0"),
+        ('c', "0"),
+    ] {
+        if uses(operand) {
+            operands.push(format!("{} = {}", operand, value));
+        }
+    }
+    format!("(x) => {{ const {}; return {}; }}", operands.join(", "), body)
+}
+
+/// `i % 10 = 2..4 and i % 100 != 12..14 or v != 0` as a JS condition.
+fn compile_condition(condition: &str) -> String {
+    let or: Vec<String> = condition
+        .split(" or ")
+        .map(|and| {
+            let and: Vec<String> = and.split(" and ").map(compile_relation).collect();
+            and.join(" && ")
+        })
+        .collect();
+    if or.len() > 1 { format!("({})", or.join(" || ")) } else { or.join("") }
+}
+
+fn compile_relation(relation: &str) -> String {
+    let (left, right, negated) = match relation.split_once(" != ") {
+        Some((left, right)) => (left, right, true),
+        None => {
+            let (left, right) = relation.split_once(" = ").unwrap_or((relation, ""));
+            (left, right, false)
+        }
+    };
+    // Only `n` can have a fraction, and a fraction is never within a range
+    let integer_check = if left.starts_with('n') { format!(" && {} % 1 === 0", left) } else { String::new() };
+    let tests: Vec<String> = right
+        .split(',')
+        .map(|item| match item.split_once("..") {
+            Some((from, to)) => format!("{} >= {} && {} <= {}{}", left, from, left, to, integer_check),
+            None => format!("{} === {}", left, item),
+        })
+        .collect();
+    let test = if tests.len() > 1 || tests[0].contains("&&") { format!("({})", tests.join(" || ")) } else { tests.join("") };
+    if negated { format!("!{}", if test.starts_with('(') { test } else { format!("({})", test) }) } else { test }
+}
@@ -0,0 +1,109 @@
+use hxo_ir::I18nMessage;
+use hxo_parser_icu::{
+    ast::{ArgumentKind, MessagePart, Selector},
+    compile_message, parse_message, plural_function, plural_rules,
+};
+
+fn formatter(source: &str, locale: &str) -> String {
+    match compile_message(source, locale).unwrap() {
+        I18nMessage::Formatter(code) => code,
+        I18nMessage::Text(text) => panic!("expected a formatter, got text {:?}", text),
+    }
+}
+
+#[test]
+fn test_parse_plural_with_offset_and_exact_branches() {
+    let message = parse_message("{count, plural, offset:1 =0 {none} one {# item} other {# items}}").unwrap();
+    let MessagePart::Argument(argument) = &message[0]
+    else {
+        panic!("expected an argument");
+    };
+    assert_eq!(argument.name, "count");
+    let ArgumentKind::Plural { ordinal, offset, branches } = &argument.kind
+    else {
+        panic!("expected a plural");
+    };
+    assert!(!ordinal);
+    assert_eq!(*offset, 1.0);
+    assert_eq!(branches[0].selector, Selector::Exact(0.0));
+    assert!(matches!(branches[1].message[0], MessagePart::Pound(_)));
+}
+
+#[test]
+fn test_parse_quoting() {
+    let message = parse_message("It''s '{literal}' and don't").unwrap();
+    assert_eq!(message, vec![MessagePart::Text("It's {literal} and don't".to_string())]);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(parse_message("{n, plural, one {x}}").is_err());
+    assert!(parse_message("{n, plural, single {x} other {y}}").is_err());
+    assert!(parse_message("{n, spellout}").is_err());
+    assert!(parse_message("{n, plural, other {x}").is_err());
+    assert!(parse_message("a } b").is_err());
+    // `#` is plain text outside plurals and selects keep free keywords
+    assert!(parse_message("#1 {g, select, he {x} other {y}}").is_ok());
+}
+
+#[test]
+fn test_simple_arguments_stay_text() {
+    assert_eq!(
+        compile_message("Hello, {name}! You are #{0}", "en").unwrap(),
+        I18nMessage::Text("Hello, {name}! You are #{0}".to_string())
+    );
+    assert_eq!(compile_message("It''s fine", "en").unwrap(), I18nMessage::Text("It's fine".to_string()));
+    // A quoted brace would read as a placeholder
+    assert!(matches!(compile_message("'{'name'}'", "en").unwrap(), I18nMessage::Formatter(_)));
+}
+
+#[test]
+fn test_compile_plural_with_shipped_rules() {
+    let code = formatter("{count, plural, =0 {No items} one {# item} other {# items}}", "en");
+    assert_eq!(
+        code,
+        "(args = {}, locale) => `${(($n, $c = ((x) => { const s = String(x).replace('-', ''), n = +s, i = Math.trunc(n), \
+         F = s.split('.')[1] || '', v = F.length; return i === 1 && v === 0 ? 'one' : 'other'; })($n)) => $n === 0 ? `No items` : \
+         $c === 'one' ? `${new Intl.NumberFormat(locale).format($n)} item` : `${new Intl.NumberFormat(locale).format($n)} items`)(args.count)}`"
+    );
+    assert!(!formatter("{n, plural, one {a} few {b} many {c} other {d}}", "ru").contains("Intl.PluralRules"));
+}
+
+#[test]
+fn test_compile_select_and_nested_arguments() {
+    let code = formatter(
+        "{gender, select, female {{n, plural, one {She has # cat} other {She has # cats}}} other {They have {n, number} cats}}",
+        "en",
+    );
+    assert!(code.contains("(($s) => $s === 'female' ? `${(($n, $c ="), "{}", code);
+    assert!(code.contains("`They have ${new Intl.NumberFormat(locale).format(args.n)} cats`)(args.gender)"), "{}", code);
+}
+
+#[test]
+fn test_compile_number_and_date_styles() {
+    assert!(
+        formatter("{p, number, percent}", "en").contains("new Intl.NumberFormat(locale, { style: 'percent' }).format(args.p)")
+    );
+    assert!(
+        formatter("{price, number, ::currency/EUR .00}", "de")
+            .contains("{ style: 'currency', currency: 'EUR', minimumFractionDigits: 2, maximumFractionDigits: 2 }")
+    );
+    assert!(formatter("{n, number, ::compact-short}", "en").contains("{ notation: 'compact', compactDisplay: 'short' }"));
+    assert!(
+        formatter("{d, date, short}", "en").contains("new Intl.DateTimeFormat(locale, { dateStyle: 'short' }).format(args.d)")
+    );
+    assert!(formatter("{d, date, ::yyyyMMMd}", "en").contains("{ year: 'numeric', month: 'short', day: 'numeric' }"));
+    assert!(formatter("{t, time}", "en").contains("{ timeStyle: 'medium' }"));
+    assert!(compile_message("{n, number, ::frobnicate}", "en").is_err());
+    assert!(compile_message("{n, number, currency}", "en").is_err());
+}
+
+#[test]
+fn test_plural_rules() {
+    assert_eq!(plural_rules("pt-PT", false).unwrap()[0].1, "i = 1 and v = 0");
+    assert_eq!(plural_rules("pt_BR", false).unwrap()[0].1, "i = 0..1");
+    assert!(plural_rules("zh-Hans", false).unwrap().is_empty());
+    assert_eq!(plural_function("ja", false), "(x) => 'other'");
+    assert_eq!(plural_function("tlh", true), "(x) => new Intl.PluralRules(locale, { type: 'ordinal' }).select(x)");
+    assert!(plural_function("en", true).contains("n % 10 === 2 && !(n % 100 === 12) ? 'two'"));
+}
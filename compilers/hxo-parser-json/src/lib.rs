@@ -1,4 +1,4 @@
-use hxo_parser::{Cursor, MetadataParser, ParseState};
+use hxo_parser::{Cursor, MetadataParser, MetadataSerializer, ParseState};
 use hxo_types::{Error, HxoValue, Position, Result, Route, RouterConfig, Span};
 use std::collections::HashMap;
 
@@ -11,6 +11,72 @@ impl MetadataParser for JsonParser {
     }
 }
 
+impl MetadataSerializer for JsonParser {
+    /// Two-space indented, with object keys sorted so that rewritten files diff cleanly.
+    fn serialize(&self, value: &HxoValue, _lang: &str) -> Result<String> {
+        let mut out = String::new();
+        write_value(value, 0, &mut out)?;
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+fn write_value(value: &HxoValue, depth: usize, out: &mut String) -> Result<()> {
+    let indent = "  ".repeat(depth + 1);
+    match value {
+        HxoValue::Null => out.push_str("null"),
+        HxoValue::Bool(flag) => out.push_str(&flag.to_string()),
+        HxoValue::Number(number) => out.push_str(&number.to_string()),
+        HxoValue::String(text) => write_string(text, out),
+        HxoValue::Array(items) if items.is_empty() => out.push_str("[]"),
+        HxoValue::Array(items) => {
+            out.push_str("[\n");
+            for (index, item) in items.iter().enumerate() {
+                out.push_str(&indent);
+                write_value(item, depth + 1, out)?;
+                out.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&format!("{}]", "  ".repeat(depth)));
+        }
+        HxoValue::Object(map) if map.is_empty() => out.push_str("{}"),
+        HxoValue::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push_str("{\n");
+            for (index, key) in keys.iter().enumerate() {
+                out.push_str(&indent);
+                write_string(key, out);
+                out.push_str(": ");
+                write_value(&map[*key], depth + 1, out)?;
+                out.push_str(if index + 1 < keys.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&format!("{}}}", "  ".repeat(depth)));
+        }
+        _ => {
+            return Err(Error::invalid_i18n(
+                "json: only text, numbers, lists and tables can be written".to_string(),
+                Span::unknown(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn write_string(text: &str, out: &mut String) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
 struct JsonParserImpl<'a, 'b> {
     state: &'a mut ParseState<'b>,
 }
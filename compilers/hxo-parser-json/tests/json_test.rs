@@ -1,5 +1,7 @@
-use hxo_parser_json::parse;
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
+use hxo_parser_json::{JsonParser, parse};
 use hxo_types::{HxoValue, Position};
+use std::collections::HashMap;
 
 fn default_pos() -> Position {
     Position { line: 1, column: 1, offset: 0 }
@@ -86,3 +88,14 @@ fn test_parse_with_meta() {
         panic!("Expected meta object");
     }
 }
+
+#[test]
+fn test_serialize_round_trip() {
+    let value = HxoValue::Object(HashMap::from([
+        ("title".to_string(), HxoValue::String("Say \"hi\"\\now".to_string())),
+        ("nav".to_string(), HxoValue::Object(HashMap::from([("home".to_string(), HxoValue::String("Home".to_string()))]))),
+    ]));
+    let text = JsonParser.serialize(&value, "json").unwrap();
+    assert_eq!(text, "{\n  \"nav\": {\n    \"home\": \"Home\"\n  },\n  \"title\": \"Say \\\"hi\\\"\\\\now\"\n}\n");
+    assert_eq!(JsonParser.parse(&mut ParseState::new(&text), "json").unwrap(), value);
+}
@@ -0,0 +1,148 @@
+//! Syntax tree of a Less stylesheet, evaluated by [`Evaluator`](crate::Evaluator).
+
+use hxo_parser_scss::{Color, ast::ListSeparator};
+use hxo_types::Span;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssExpr {
+    Number(f64, String),
+    Color(Color),
+    /// `@name`
+    Variable(String),
+    /// `@@name`: the variable named by the value of `@name`.
+    VariableVariable(String),
+    Binary(Box<CssExpr>, String, Box<CssExpr>),
+    Negate(Box<CssExpr>),
+    /// `"text"` with `@{var}` interpolation; `escaped` for `~"text"`, which prints without quotes.
+    Quoted {
+        text: String,
+        quote: char,
+        escaped: bool,
+    },
+    Call {
+        name: String,
+        args: Vec<CssExpr>,
+    },
+    List(Vec<CssExpr>, ListSeparator),
+    /// `(expr)`: `/` only divides inside parentheses.
+    Paren(Box<CssExpr>),
+    /// Keywords and text kept as written, such as `url(...)` and `calc(...)`; `@{var}` and `@var`
+    /// inside are substituted.
+    Raw(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// `@name: value;`
+    Variable {
+        name: String,
+        value: CssExpr,
+        span: Span,
+    },
+    Declaration {
+        property: String,
+        value: CssExpr,
+        important: bool,
+        span: Span,
+    },
+    /// `selector [when guard] { ... }`; rules with a plain class or id selector are also mixins.
+    Rule {
+        selector: String,
+        guard: Option<Guard>,
+        body: Rc<Vec<Node>>,
+        span: Span,
+    },
+    /// `.name(params) [when guard] { ... }`, which prints nothing until called.
+    Mixin(Rc<MixinDefinition>),
+    /// `.name(args) [!important];` or `#namespace > .name();`
+    MixinCall {
+        path: Vec<String>,
+        args: Vec<MixinArg>,
+        important: bool,
+        span: Span,
+    },
+    /// `&:extend(.a, .b all);`
+    Extend {
+        targets: Vec<ExtendTarget>,
+        span: Span,
+    },
+    /// `@media`, `@font-face`, `@apply`... with `@var` and `@{var}` substituted in the prelude.
+    AtRule {
+        name: String,
+        prelude: String,
+        body: Option<Vec<Node>>,
+        span: Span,
+    },
+    /// `@import (options) "url";`
+    Import {
+        url: CssExpr,
+        options: Vec<String>,
+        prelude: String,
+        span: Span,
+    },
+}
+
+impl Node {
+    pub fn span(&self) -> Span {
+        match self {
+            Node::Mixin(mixin) => mixin.span,
+            Node::Variable { span, .. }
+            | Node::Declaration { span, .. }
+            | Node::Rule { span, .. }
+            | Node::MixinCall { span, .. }
+            | Node::Extend { span, .. }
+            | Node::AtRule { span, .. }
+            | Node::Import { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixinDefinition {
+    /// `.name` or `#name`
+    pub name: String,
+    pub params: Vec<MixinParam>,
+    /// `@rest...`, or an empty name for a bare `...`.
+    pub variadic: Option<String>,
+    pub guard: Option<Guard>,
+    pub body: Rc<Vec<Node>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MixinParam {
+    Variable {
+        name: String,
+        default: Option<CssExpr>,
+    },
+    /// A value the argument must equal for the mixin to match: `.m(dark; @color)`.
+    Pattern(CssExpr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixinArg {
+    pub name: Option<String>,
+    pub value: CssExpr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendTarget {
+    pub selector: String,
+    /// `all` also extends selectors that merely contain the target.
+    pub all: bool,
+}
+
+/// `when (a) and (b), (c)`: alternatives of conditions that must all hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Guard {
+    pub alternatives: Vec<Vec<Condition>>,
+}
+
+/// `[not] (left [op right])`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub negated: bool,
+    pub left: CssExpr,
+    pub comparison: Option<(String, CssExpr)>,
+}
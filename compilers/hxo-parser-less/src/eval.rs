@@ -0,0 +1,985 @@
+//! Evaluates the syntax tree into a CSS [`Stylesheet`]: lazy variables, mixins with pattern matching
+//! and guards, operations, `:extend` and `@import` through the load paths.
+
+use crate::{
+    ast::{CssExpr, ExtendTarget, Guard, MixinArg, MixinDefinition, MixinParam, Node},
+    functions,
+    parser::{collapse_whitespace, is_name_char, parse_extend_targets, parse_less},
+};
+use hxo_ir::{AtRule, CssNode, Declaration, StyleRule, Stylesheet};
+use hxo_parser_scss::{
+    Color, Value,
+    ast::ListSeparator,
+    selector::{extend_selector, resolve_selectors, split_selector_list},
+};
+use hxo_types::{Error, Result, Span};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+const MAX_CALL_DEPTH: usize = 256;
+
+pub struct Evaluator {
+    load_paths: Vec<PathBuf>,
+    compressed: bool,
+    /// Innermost last; the first is the stylesheet's.
+    scopes: Vec<Rc<RefCell<Scope>>>,
+    frames: Vec<Frame>,
+    /// The selector list of the innermost style rule, empty outside rules.
+    selectors: Vec<String>,
+    extensions: Vec<Extension>,
+    /// Imported files by path, parsed once.
+    parsed: HashMap<PathBuf, Rc<Vec<Node>>>,
+    /// Files whose CSS was output, which a second `@import` skips unless it is `(multiple)`.
+    imported: HashSet<PathBuf>,
+    /// Files being evaluated, to report import loops.
+    loading: Vec<PathBuf>,
+    file: Option<PathBuf>,
+    /// Above zero while evaluating another file, whose spans don't point into the entry source.
+    imported_depth: usize,
+    /// Set while a mixin called with `!important` runs.
+    important: bool,
+    /// What `default()` returns in the guard being checked.
+    default_guard: bool,
+    /// Variables being evaluated, to report `@a: @a`.
+    evaluating: Vec<String>,
+    call_depth: usize,
+    /// The node being evaluated, for error spans.
+    span: Span,
+}
+
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<String, Variable>,
+    mixins: Vec<Mixin>,
+}
+
+/// Less variables are lazy: the last definition in a scope wins and is evaluated where it is used.
+#[derive(Clone)]
+enum Variable {
+    Lazy(CssExpr),
+    /// A bound mixin argument.
+    Value(Value),
+}
+
+#[derive(Clone)]
+struct Mixin {
+    definition: Rc<MixinDefinition>,
+    /// The scopes it was defined in, innermost last.
+    scopes: Vec<Rc<RefCell<Scope>>>,
+    file: Option<PathBuf>,
+    imported_depth: usize,
+}
+
+/// CSS being built: the nodes of the stylesheet or an at-rule block, and the style rule among them
+/// that receives declarations.
+#[derive(Default)]
+struct Frame {
+    nodes: Vec<CssNode>,
+    rule: Option<usize>,
+}
+
+struct Extension {
+    target: ExtendTarget,
+    extenders: Vec<String>,
+}
+
+/// Where an `@import` leads.
+enum ImportTarget {
+    /// Plain CSS, printed as an `@import` rule.
+    Css,
+    File(PathBuf),
+    /// A missing `(optional)` import.
+    Skip,
+}
+
+impl Evaluator {
+    /// An evaluator resolving `@import` against `load_paths` after the importing file's directory.
+    pub fn new(load_paths: Vec<PathBuf>) -> Self {
+        Self {
+            load_paths,
+            compressed: false,
+            scopes: Vec::new(),
+            frames: Vec::new(),
+            selectors: Vec::new(),
+            extensions: Vec::new(),
+            parsed: HashMap::new(),
+            imported: HashSet::new(),
+            loading: Vec::new(),
+            file: None,
+            imported_depth: 0,
+            important: false,
+            default_guard: false,
+            evaluating: Vec::new(),
+            call_depth: 0,
+            span: Span::unknown(),
+        }
+    }
+
+    /// Prints comma-separated values without the space after the comma.
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    /// Evaluates the entry stylesheet; `file` is where it was read from, for relative imports.
+    pub fn evaluate(&mut self, nodes: &[Node], file: Option<&Path>) -> Result<Stylesheet> {
+        self.file = file.map(Path::to_path_buf);
+        if let Some(file) = file {
+            self.loading.push(std::fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf()));
+        }
+        self.frames = vec![Frame::default()];
+        let result = self.exec_scoped(nodes);
+        let root = self.frames.pop().unwrap_or_default();
+        self.loading.clear();
+        result?;
+
+        let mut nodes = root.nodes;
+        extend_nodes(&mut nodes, &self.extensions);
+        remove_empty(&mut nodes);
+        Ok(Stylesheet { nodes })
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        let message = message.into();
+        match (&self.file, self.imported_depth) {
+            (Some(file), depth) if depth > 0 => {
+                Err(Error::parse_error(format!("{} (in {})", message, file.display()), Span::unknown()))
+            }
+            _ => Err(Error::parse_error(message, self.span)),
+        }
+    }
+
+    /// The span for output nodes: nodes of imported files have none.
+    fn output_span(&self, span: Span) -> Span {
+        if self.imported_depth > 0 { Span::unknown() } else { span }
+    }
+
+    fn innermost(&self) -> Rc<RefCell<Scope>> {
+        self.scopes.last().cloned().unwrap_or_default()
+    }
+
+    /// Runs `nodes` in a new scope. Mixins hold the scope they were defined in, which holds them;
+    /// clearing them on the way out frees the cycle.
+    fn exec_scoped(&mut self, nodes: &[Node]) -> Result<()> {
+        let scope = Rc::new(RefCell::new(Scope::default()));
+        self.scopes.push(scope.clone());
+        let result = self.declare(nodes).and_then(|_| self.exec_block(nodes));
+        self.scopes.pop();
+        scope.borrow_mut().mixins.clear();
+        result
+    }
+
+    /// Registers the variables and mixins of a block, including those of the files it imports, before
+    /// any of it runs: Less variables and mixins can be used above their definition.
+    fn declare(&mut self, nodes: &[Node]) -> Result<()> {
+        let scope = self.innermost();
+        for node in nodes {
+            match node {
+                Node::Variable { name, value, .. } => {
+                    scope.borrow_mut().variables.insert(name.clone(), Variable::Lazy(value.clone()));
+                }
+                Node::Mixin(definition) => self.register_mixin(definition.clone()),
+                Node::Rule { selector, guard, body, span } => {
+                    // `.a { ... }` and `#ns { ... }` can also be called as mixins
+                    for part in split_selector_list(selector) {
+                        let (name, _) = strip_extend(&part);
+                        let simple = name.len() > 1 && name.starts_with(['.', '#']) && name[1..].chars().all(is_name_char);
+                        if simple {
+                            self.register_mixin(Rc::new(MixinDefinition {
+                                name,
+                                params: Vec::new(),
+                                variadic: None,
+                                guard: guard.clone(),
+                                body: body.clone(),
+                                span: *span,
+                            }));
+                        }
+                    }
+                }
+                Node::Import { url, options, span, .. } => {
+                    self.span = *span;
+                    let ImportTarget::File(path) = self.import_target(url, options)?
+                    else {
+                        continue;
+                    };
+                    if self.loading.contains(&path) {
+                        continue;
+                    }
+                    let nodes = self.parse_file(&path)?;
+                    let saved_file = self.file.replace(path.clone());
+                    self.loading.push(path);
+                    self.imported_depth += 1;
+                    let result = self.declare(&nodes);
+                    self.imported_depth -= 1;
+                    self.loading.pop();
+                    self.file = saved_file;
+                    result?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn register_mixin(&mut self, definition: Rc<MixinDefinition>) {
+        let mixin =
+            Mixin { definition, scopes: self.scopes.clone(), file: self.file.clone(), imported_depth: self.imported_depth };
+        self.innermost().borrow_mut().mixins.push(mixin);
+    }
+
+    fn exec_block(&mut self, nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            self.exec(node)?;
+        }
+        Ok(())
+    }
+
+    fn exec(&mut self, node: &Node) -> Result<()> {
+        self.span = node.span();
+        match node {
+            Node::Variable { .. } | Node::Mixin(_) => {}
+            Node::Declaration { property, value, important, span } => {
+                let property = self.interpolate(property)?;
+                let value = self.eval(value)?;
+                if value.is_blank() {
+                    return Ok(());
+                }
+                let text = match value.serialize(self.compressed) {
+                    Ok(text) => text,
+                    Err(message) => return self.error(message),
+                };
+                if self.frames.len() == 1 && self.frames[0].rule.is_none() {
+                    return self.error("Properties must be inside selector blocks.");
+                }
+                let span = self.output_span(*span);
+                let important = *important || self.important;
+                self.push_child(CssNode::Declaration(Declaration { property, value: text, important, span }));
+            }
+            Node::Rule { selector, guard, body, span } => {
+                if let Some(guard) = guard {
+                    if !self.guard_passes(guard)? {
+                        return Ok(());
+                    }
+                }
+                let text = collapse_whitespace(&self.interpolate(selector)?);
+                let mut parts = Vec::new();
+                let mut extends = Vec::new();
+                for (i, part) in split_selector_list(&text).into_iter().enumerate() {
+                    let (part, targets) = strip_extend(&part);
+                    if !targets.is_empty() {
+                        extends.push((i, targets));
+                    }
+                    parts.push(part);
+                }
+                let selectors = resolve_selectors(&self.selectors, &parts.join(", "));
+                for (i, targets) in extends {
+                    let extenders = selectors.iter().skip(i).step_by(parts.len()).cloned().collect::<Vec<_>>();
+                    for target in targets {
+                        self.extensions.push(Extension { target, extenders: extenders.clone() });
+                    }
+                }
+                self.exec_rule(selectors, body, *span)?;
+            }
+            Node::MixinCall { path, args, important, .. } => self.call_mixin(path, args, *important)?,
+            Node::Extend { targets, .. } => {
+                if self.selectors.is_empty() {
+                    return self.error("&:extend must be used inside a ruleset.");
+                }
+                for target in targets {
+                    self.extensions.push(Extension { target: target.clone(), extenders: self.selectors.clone() });
+                }
+            }
+            Node::AtRule { name, prelude, body, span } => {
+                let prelude = collapse_whitespace(&self.substitute(prelude)?);
+                let span = self.output_span(*span);
+                match body {
+                    None => self.push_child(CssNode::AtRule(AtRule { name: name.clone(), prelude, block: None, span })),
+                    Some(body) => {
+                        let keyframes = name.ends_with("keyframes");
+                        let mut frame = Frame::default();
+                        if !keyframes && !self.selectors.is_empty() {
+                            // Declarations inside `@media` in a rule go to a copy of the rule inside it
+                            frame.nodes.push(CssNode::Rule(StyleRule {
+                                selector: self.selectors.join(", "),
+                                children: Vec::new(),
+                                selector_span: span,
+                                span,
+                            }));
+                            frame.rule = Some(0);
+                        }
+                        let saved_selectors =
+                            if keyframes { std::mem::take(&mut self.selectors) } else { self.selectors.clone() };
+                        self.frames.push(frame);
+                        let result = self.exec_scoped(body);
+                        let frame = self.frames.pop().unwrap_or_default();
+                        self.selectors = saved_selectors;
+                        result?;
+                        let node = CssNode::AtRule(AtRule { name: name.clone(), prelude, block: Some(frame.nodes), span });
+                        if let Some(parent) = self.frames.last_mut() {
+                            parent.nodes.push(node);
+                        }
+                    }
+                }
+            }
+            Node::Import { url, options, prelude, span } => match self.import_target(url, options)? {
+                ImportTarget::Css => {
+                    let prelude = collapse_whitespace(&self.substitute(prelude)?);
+                    let span = self.output_span(*span);
+                    self.push_child(CssNode::AtRule(AtRule { name: "import".to_string(), prelude, block: None, span }));
+                }
+                ImportTarget::File(path) => {
+                    let has = |option: &str| options.iter().any(|o| o == option);
+                    if has("reference") || (self.imported.contains(&path) && !has("multiple")) {
+                        return Ok(());
+                    }
+                    if self.loading.contains(&path) {
+                        return self.error(format!("Recursive import of {}.", path.display()));
+                    }
+                    self.imported.insert(path.clone());
+                    let nodes = self.parse_file(&path)?;
+                    let saved_file = self.file.replace(path.clone());
+                    self.loading.push(path);
+                    self.imported_depth += 1;
+                    let result = self.exec_block(&nodes);
+                    self.imported_depth -= 1;
+                    self.loading.pop();
+                    self.file = saved_file;
+                    result?;
+                }
+                ImportTarget::Skip => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// Opens a style rule: its declarations go into a new rule node and nested rules follow it.
+    fn exec_rule(&mut self, selectors: Vec<String>, body: &[Node], span: Span) -> Result<()> {
+        let span = self.output_span(span);
+        let Some(frame) = self.frames.last_mut()
+        else {
+            return Ok(());
+        };
+        frame.nodes.push(CssNode::Rule(StyleRule {
+            selector: selectors.join(", "),
+            children: Vec::new(),
+            selector_span: span,
+            span,
+        }));
+        let saved_rule = frame.rule.replace(frame.nodes.len() - 1);
+        let saved_selectors = std::mem::replace(&mut self.selectors, selectors);
+        let frames = self.frames.len();
+        let result = self.exec_scoped(body);
+        self.selectors = saved_selectors;
+        self.frames[frames - 1].rule = saved_rule;
+        result
+    }
+
+    /// Adds a declaration or childless at-rule to the current style rule, or to the current block.
+    fn push_child(&mut self, node: CssNode) {
+        let Some(frame) = self.frames.last_mut()
+        else {
+            return;
+        };
+        match frame.rule.and_then(|index| frame.nodes.get_mut(index)) {
+            Some(CssNode::Rule(rule)) => rule.children.push(node),
+            _ => frame.nodes.push(node),
+        }
+    }
+
+    fn variable(&mut self, name: &str) -> Result<Value> {
+        for i in (0..self.scopes.len()).rev() {
+            let found = self.scopes[i].borrow().variables.get(name).cloned();
+            match found {
+                Some(Variable::Value(value)) => return Ok(value),
+                Some(Variable::Lazy(expr)) => {
+                    if self.evaluating.iter().any(|evaluating| evaluating == name) {
+                        return self.error(format!("Recursive variable definition for @{}", name));
+                    }
+                    // The value sees the scope it was defined in, not the ones above it
+                    let inner = self.scopes.split_off(i + 1);
+                    self.evaluating.push(name.to_string());
+                    let result = self.eval(&expr);
+                    self.evaluating.pop();
+                    self.scopes.extend(inner);
+                    return result;
+                }
+                None => {}
+            }
+        }
+        self.error(format!("variable @{} is undefined", name))
+    }
+
+    fn eval(&mut self, expr: &CssExpr) -> Result<Value> {
+        Ok(match expr {
+            CssExpr::Number(value, unit) => Value::number(*value, unit.clone()),
+            CssExpr::Color(color) => Value::Color(color.clone()),
+            CssExpr::Variable(name) => self.variable(name)?,
+            CssExpr::VariableVariable(name) => {
+                let inner = self.variable(name)?;
+                match inner.interpolated() {
+                    Ok(inner) => self.variable(&inner)?,
+                    Err(message) => return self.error(message),
+                }
+            }
+            CssExpr::Binary(left, op, right) => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                self.operate(op, left, right)?
+            }
+            CssExpr::Negate(inner) => match self.eval(inner)? {
+                Value::Number(number) => Value::number(-number.value, number.unit),
+                other => Value::ident(format!("-{}", self.css(&other)?)),
+            },
+            CssExpr::Quoted { text, quote, escaped } => {
+                let text = self.interpolate(text)?;
+                if *escaped { Value::ident(text) } else { Value::String(text, Some(*quote)) }
+            }
+            CssExpr::Call { name, args } => {
+                if name == "default" && args.is_empty() {
+                    return Ok(Value::Bool(self.default_guard));
+                }
+                let args = args.iter().map(|arg| self.eval(arg)).collect::<Result<Vec<_>>>()?;
+                match functions::call(name, &args) {
+                    Some(Ok(value)) => value,
+                    Some(Err(message)) => return self.error(format!("error evaluating function `{}`: {}", name, message)),
+                    None => {
+                        let args = args.iter().map(|arg| self.css(arg)).collect::<Result<Vec<_>>>()?;
+                        let separator = if self.compressed { "," } else { ", " };
+                        Value::ident(format!("{}({})", name, args.join(separator)))
+                    }
+                }
+            }
+            CssExpr::List(items, separator) => {
+                let items = items.iter().map(|item| self.eval(item)).collect::<Result<Vec<_>>>()?;
+                Value::List(items, *separator, false)
+            }
+            CssExpr::Paren(inner) => self.eval(inner)?,
+            CssExpr::Raw(text) => Value::ident(self.substitute(text)?),
+        })
+    }
+
+    fn css(&self, value: &Value) -> Result<String> {
+        match value.serialize(self.compressed) {
+            Ok(text) => Ok(text),
+            Err(message) => self.error(message),
+        }
+    }
+
+    /// Numbers take the unit of the left operand, converting the right one when they are compatible;
+    /// colors combine channel by channel. Other values print as written.
+    fn operate(&self, op: &str, left: Value, right: Value) -> Result<Value> {
+        let apply = |a: f64, b: f64| match op {
+            "+" => a + b,
+            "-" => a - b,
+            "*" => a * b,
+            _ => a / b,
+        };
+        let channels = |color: &Color| [color.red, color.green, color.blue];
+        let color = |[red, green, blue]: [f64; 3], alpha: f64| {
+            Value::Color(Color::rgba(red.clamp(0.0, 255.0), green.clamp(0.0, 255.0), blue.clamp(0.0, 255.0), alpha))
+        };
+        Ok(match (&left, &right) {
+            (Value::Number(a), Value::Number(b)) => {
+                if op == "/" && b.value == 0.0 {
+                    return self.error("Division by zero.");
+                }
+                let (unit, b) = if a.unit.is_empty() {
+                    (b.unit.clone(), b.value)
+                }
+                else if b.unit.is_empty() {
+                    (a.unit.clone(), b.value)
+                }
+                else {
+                    (a.unit.clone(), b.convert_to(&a.unit).unwrap_or(b.value))
+                };
+                Value::number(apply(a.value, b), unit)
+            }
+            (Value::Color(a), Value::Color(b)) => {
+                let (a_channels, b_channels) = (channels(a), channels(b));
+                color(std::array::from_fn(|i| apply(a_channels[i], b_channels[i])), a.alpha)
+            }
+            (Value::Color(a), Value::Number(b)) => color(channels(a).map(|channel| apply(channel, b.value)), a.alpha),
+            (Value::Number(a), Value::Color(b)) => color(channels(b).map(|channel| apply(a.value, channel)), b.alpha),
+            _ => Value::ident(format!("{} {} {}", self.css(&left)?, op, self.css(&right)?)),
+        })
+    }
+
+    /// Replaces `@{name}` with the variable's text, strings unquoted.
+    fn interpolate(&mut self, text: &str) -> Result<String> {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("@{") {
+            let Some(end) = rest[start..].find('}')
+            else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            let value = self.variable(&rest[start + 2..start + end])?;
+            match value.interpolated() {
+                Ok(text) => out.push_str(&text),
+                Err(message) => return self.error(message),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Substitutes `@{name}` and `@name` in raw text such as `calc(100% - @gutter)` or a media query.
+    fn substitute(&mut self, text: &str) -> Result<String> {
+        let text = self.interpolate(text)?;
+        if !text.contains('@') {
+            return Ok(text);
+        }
+        let mut out = String::new();
+        let mut rest = text.as_str();
+        while let Some(at) = rest.find('@') {
+            out.push_str(&rest[..at]);
+            let name_len = rest[at + 1..].find(|c: char| !is_name_char(c)).unwrap_or(rest.len() - at - 1);
+            if name_len == 0 {
+                out.push('@');
+            }
+            else {
+                let value = self.variable(&rest[at + 1..at + 1 + name_len])?;
+                out.push_str(&self.css(&value)?);
+            }
+            rest = &rest[at + 1 + name_len..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    fn guard_passes(&mut self, guard: &Guard) -> Result<bool> {
+        for conditions in &guard.alternatives {
+            let mut passes = true;
+            for condition in conditions {
+                let left = self.eval(&condition.left)?;
+                let result = match &condition.comparison {
+                    None => is_true(&left),
+                    Some((op, right)) => {
+                        let right = self.eval(right)?;
+                        compare(&left, op, &right)
+                    }
+                };
+                if result == condition.negated {
+                    passes = false;
+                    break;
+                }
+            }
+            if passes {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Calls every definition of the mixin whose parameters and guard accept the arguments.
+    fn call_mixin(&mut self, path: &[String], args: &[MixinArg], important: bool) -> Result<()> {
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        for arg in args {
+            let value = self.eval(&arg.value)?;
+            match &arg.name {
+                Some(name) => named.push((name.clone(), value)),
+                None => positional.push(value),
+            }
+        }
+        let mut namespaces = Vec::new();
+        let result = self
+            .find_mixins(path, &mut namespaces)
+            .and_then(|candidates| self.call_candidates(&path.concat(), candidates, &positional, &named, important));
+        for scope in namespaces {
+            scope.borrow_mut().mixins.clear();
+        }
+        result
+    }
+
+    /// The definitions named by `path` in the innermost scope that has any. Namespaces along the path
+    /// are declared into the scopes collected in `namespaces`, which the caller clears.
+    fn find_mixins(&mut self, path: &[String], namespaces: &mut Vec<Rc<RefCell<Scope>>>) -> Result<Vec<Mixin>> {
+        let Some((first, members)) = path.split_first()
+        else {
+            return self.error("expected a mixin name");
+        };
+        let mut found = Vec::new();
+        for scope in self.scopes.iter().rev() {
+            found = scope.borrow().mixins.iter().filter(|mixin| mixin.definition.name == *first).cloned().collect();
+            if !found.is_empty() {
+                break;
+            }
+        }
+        for member in members {
+            let mut next = Vec::new();
+            for namespace in &found {
+                let scope = Rc::new(RefCell::new(Scope::default()));
+                namespaces.push(scope.clone());
+                let mut scopes = namespace.scopes.clone();
+                scopes.push(scope.clone());
+                let saved_scopes = std::mem::replace(&mut self.scopes, scopes);
+                let saved_location = self.enter(namespace);
+                let result = self.declare(&namespace.definition.body);
+                (self.file, self.imported_depth) = saved_location;
+                self.scopes = saved_scopes;
+                result?;
+                next.extend(scope.borrow().mixins.iter().filter(|mixin| mixin.definition.name == *member).cloned());
+            }
+            found = next;
+        }
+        Ok(found)
+    }
+
+    fn call_candidates(
+        &mut self,
+        name: &str,
+        candidates: Vec<Mixin>,
+        positional: &[Value],
+        named: &[(String, Value)],
+        important: bool,
+    ) -> Result<()> {
+        if candidates.is_empty() {
+            return self.error(format!("{} is undefined", name));
+        }
+        let mut matching = Vec::new();
+        for mixin in candidates {
+            if self.accepts(&mixin.definition, positional, named)? {
+                matching.push(mixin);
+            }
+        }
+        if matching.is_empty() {
+            return self.error(format!("No matching definition was found for `{}()`", name));
+        }
+        // `default()` is true only when no other definition matched
+        let mut selected = Vec::new();
+        let mut defaults = Vec::new();
+        for mixin in matching {
+            match mixin.definition.guard.clone() {
+                Some(guard) if !self.mixin_guard(&mixin, &guard, positional, named, false)? => defaults.push((mixin, guard)),
+                _ => selected.push(mixin),
+            }
+        }
+        if selected.is_empty() {
+            for (mixin, guard) in defaults {
+                if self.mixin_guard(&mixin, &guard, positional, named, true)? {
+                    selected.push(mixin);
+                }
+            }
+        }
+        for mixin in &selected {
+            self.run_mixin(mixin, positional, named, important)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the arguments fit the parameters: arity, names and patterns.
+    fn accepts(&mut self, definition: &MixinDefinition, positional: &[Value], named: &[(String, Value)]) -> Result<bool> {
+        let is_param = |name: &str| {
+            definition.params.iter().any(|param| matches!(param, MixinParam::Variable { name: param, .. } if param == name))
+        };
+        if named.iter().any(|(name, _)| !is_param(name)) {
+            return Ok(false);
+        }
+        let mut remaining = positional.iter();
+        for param in &definition.params {
+            match param {
+                MixinParam::Pattern(pattern) => {
+                    let Some(value) = remaining.next()
+                    else {
+                        return Ok(false);
+                    };
+                    let pattern = self.eval(pattern)?;
+                    if !compare(value, "=", &pattern) {
+                        return Ok(false);
+                    }
+                }
+                MixinParam::Variable { name, default } => {
+                    if named.iter().any(|(n, _)| n == name) {
+                        continue;
+                    }
+                    if remaining.next().is_none() && default.is_none() {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+        Ok(remaining.next().is_none() || definition.variadic.is_some())
+    }
+
+    fn mixin_guard(
+        &mut self,
+        mixin: &Mixin,
+        guard: &Guard,
+        positional: &[Value],
+        named: &[(String, Value)],
+        default: bool,
+    ) -> Result<bool> {
+        self.with_mixin_scope(mixin, positional, named, |this| {
+            let saved = std::mem::replace(&mut this.default_guard, default);
+            let result = this.guard_passes(guard);
+            this.default_guard = saved;
+            result
+        })
+    }
+
+    fn run_mixin(&mut self, mixin: &Mixin, positional: &[Value], named: &[(String, Value)], important: bool) -> Result<()> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return self.error(format!("{} recursed too deeply", mixin.definition.name));
+        }
+        let body = mixin.definition.body.clone();
+        let returned = self.with_mixin_scope(mixin, positional, named, |this| {
+            let saved_important = this.important;
+            this.important |= important;
+            this.call_depth += 1;
+            let result = this.declare(&body).and_then(|_| this.exec_block(&body));
+            this.call_depth -= 1;
+            this.important = saved_important;
+            result?;
+            // Variables set in the mixin become visible to the caller, unless it has its own
+            let names = this
+                .innermost()
+                .borrow()
+                .variables
+                .iter()
+                .filter(|(_, variable)| matches!(variable, Variable::Lazy(_)))
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            Ok(names.into_iter().filter_map(|name| Some((name.clone(), this.variable(&name).ok()?))).collect::<Vec<_>>())
+        })?;
+        let scope = self.innermost();
+        let mut scope = scope.borrow_mut();
+        for (name, value) in returned {
+            scope.variables.entry(name).or_insert(Variable::Value(value));
+        }
+        Ok(())
+    }
+
+    /// Runs `f` with the arguments bound in a scope above the mixin's defining scopes, which are above
+    /// the caller's, and with the file the mixin was written in.
+    fn with_mixin_scope<T>(
+        &mut self,
+        mixin: &Mixin,
+        positional: &[Value],
+        named: &[(String, Value)],
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let params = Rc::new(RefCell::new(Scope::default()));
+        let mut scopes = self.scopes.clone();
+        scopes.extend(mixin.scopes.iter().cloned());
+        scopes.push(params.clone());
+        let saved_scopes = std::mem::replace(&mut self.scopes, scopes);
+        let saved_location = self.enter(mixin);
+        let result = self.bind(&mixin.definition, positional, named).and_then(|_| f(self));
+        (self.file, self.imported_depth) = saved_location;
+        self.scopes = saved_scopes;
+        params.borrow_mut().mixins.clear();
+        result
+    }
+
+    fn enter(&mut self, mixin: &Mixin) -> (Option<PathBuf>, usize) {
+        let file = std::mem::replace(&mut self.file, mixin.file.clone());
+        (file, std::mem::replace(&mut self.imported_depth, mixin.imported_depth))
+    }
+
+    /// Binds the arguments in the innermost scope, with `@arguments` holding all of them.
+    fn bind(&mut self, definition: &MixinDefinition, positional: &[Value], named: &[(String, Value)]) -> Result<()> {
+        let scope = self.innermost();
+        let mut remaining = positional.iter();
+        let mut arguments = Vec::new();
+        for param in &definition.params {
+            match param {
+                MixinParam::Pattern(_) => arguments.extend(remaining.next().cloned()),
+                MixinParam::Variable { name, default } => {
+                    let value = if let Some((_, value)) = named.iter().find(|(n, _)| n == name) {
+                        value.clone()
+                    }
+                    else if let Some(value) = remaining.next() {
+                        value.clone()
+                    }
+                    else if let Some(default) = default {
+                        self.eval(default)?
+                    }
+                    else {
+                        return self.error(format!("wrong number of arguments for {}", definition.name));
+                    };
+                    arguments.push(value.clone());
+                    scope.borrow_mut().variables.insert(name.clone(), Variable::Value(value));
+                }
+            }
+        }
+        let rest = remaining.cloned().collect::<Vec<_>>();
+        arguments.extend(rest.iter().cloned());
+        let mut scope = scope.borrow_mut();
+        if let Some(name) = definition.variadic.as_ref().filter(|name| !name.is_empty()) {
+            scope.variables.insert(name.clone(), Variable::Value(Value::List(rest, ListSeparator::Space, false)));
+        }
+        scope.variables.insert("arguments".to_string(), Variable::Value(Value::List(arguments, ListSeparator::Space, false)));
+        Ok(())
+    }
+
+    /// Plain CSS imports (`.css` files, `url()`, `(css)`, remote URLs) stay `@import` rules; anything
+    /// else is a Less file found next to the current file or in a load path, `.less` added when the
+    /// name has no extension.
+    fn import_target(&mut self, url: &CssExpr, options: &[String]) -> Result<ImportTarget> {
+        let has = |option: &str| options.iter().any(|o| o == option);
+        if matches!(url, CssExpr::Raw(text) if text.starts_with("url(")) || has("css") {
+            return Ok(ImportTarget::Css);
+        }
+        let url = match self.eval(url)? {
+            Value::String(text, _) => text,
+            _ => return Ok(ImportTarget::Css),
+        };
+        let remote = url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//");
+        if remote || (url.ends_with(".css") && !has("less")) {
+            return Ok(ImportTarget::Css);
+        }
+        let base = match &self.file {
+            Some(file) => file.parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => PathBuf::from("."),
+        };
+        let name = if Path::new(&url).extension().is_some() { url.clone() } else { format!("{}.less", url) };
+        let found =
+            std::iter::once(base).chain(self.load_paths.iter().cloned()).map(|dir| dir.join(&name)).find(|path| path.is_file());
+        match found {
+            Some(path) => Ok(ImportTarget::File(std::fs::canonicalize(&path).unwrap_or(path))),
+            None if has("optional") => Ok(ImportTarget::Skip),
+            None => self.error(format!("'{}' wasn't found.", url)),
+        }
+    }
+
+    fn parse_file(&mut self, path: &Path) -> Result<Rc<Vec<Node>>> {
+        if let Some(nodes) = self.parsed.get(path) {
+            return Ok(nodes.clone());
+        }
+        let source = std::fs::read_to_string(path)?;
+        let nodes = parse_less(&source)
+            .map_err(|err| Error::parse_error(format!("{} (in {})", err, path.display()), Span::unknown()))?;
+        let nodes = Rc::new(nodes);
+        self.parsed.insert(path.to_path_buf(), nodes.clone());
+        Ok(nodes)
+    }
+}
+
+/// `true` and `(true)`; anything else fails a guard.
+fn is_true(value: &Value) -> bool {
+    match value {
+        Value::Bool(value) => *value,
+        Value::String(text, None) => text == "true",
+        _ => false,
+    }
+}
+
+/// Numbers compare after unit conversion; other values are only equal when they print the same,
+/// quotes aside.
+fn compare(left: &Value, op: &str, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::Number(_), Value::Number(_)) => left.compare(right).ok(),
+        _ => None,
+    };
+    match ordering {
+        Some(ordering) => match op {
+            ">" => ordering == Ordering::Greater,
+            ">=" => ordering != Ordering::Less,
+            "<" => ordering == Ordering::Less,
+            "=<" => ordering != Ordering::Greater,
+            _ => ordering == Ordering::Equal,
+        },
+        None => op == "=" && comparable_text(left) == comparable_text(right),
+    }
+}
+
+fn comparable_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Color(color) => Some(color.clone().computed().to_css()),
+        other => other.interpolated().ok(),
+    }
+}
+
+/// Splits `.b:extend(.a all)` into `.b` and its targets.
+fn strip_extend(selector: &str) -> (String, Vec<ExtendTarget>) {
+    let mut selector = selector.to_string();
+    let mut targets = Vec::new();
+    while let Some(start) = selector.find(":extend(") {
+        let inner = start + ":extend(".len();
+        let mut depth = 1usize;
+        let mut end = selector.len();
+        for (i, c) in selector[inner..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        end = inner + i;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        targets.extend(parse_extend_targets(&selector[inner..end]));
+        selector.replace_range(start..(end + 1).min(selector.len()), "");
+    }
+    (selector.trim().to_string(), targets)
+}
+
+/// Adds the extending selectors to the rules matching each target: the whole selector, or with `all`
+/// any selector containing it.
+fn extend_nodes(nodes: &mut [CssNode], extensions: &[Extension]) {
+    if extensions.is_empty() {
+        return;
+    }
+    for node in nodes.iter_mut() {
+        match node {
+            CssNode::Rule(rule) => {
+                let mut selectors = split_selector_list(&rule.selector);
+                let mut i = 0;
+                while i < selectors.len() && selectors.len() < 1000 {
+                    for extension in extensions {
+                        let target = &extension.target.selector;
+                        for extender in &extension.extenders {
+                            let extended = if extension.target.all {
+                                extend_selector(&selectors[i], target, extender)
+                            }
+                            else {
+                                (selectors[i] == *target).then(|| extender.clone())
+                            };
+                            if let Some(extended) = extended.filter(|extended| !selectors.contains(extended)) {
+                                selectors.push(extended);
+                            }
+                        }
+                    }
+                    i += 1;
+                }
+                rule.selector = selectors.join(", ");
+            }
+            CssNode::AtRule(rule) => {
+                if let Some(block) = &mut rule.block {
+                    extend_nodes(block, extensions);
+                }
+            }
+            CssNode::Declaration(_) => {}
+        }
+    }
+}
+
+/// Drops rules without declarations and conditional blocks left empty.
+fn remove_empty(nodes: &mut Vec<CssNode>) {
+    for node in nodes.iter_mut() {
+        if let CssNode::AtRule(rule) = node {
+            if let Some(block) = &mut rule.block {
+                remove_empty(block);
+            }
+        }
+    }
+    nodes.retain(|node| match node {
+        CssNode::Rule(rule) => !rule.children.is_empty(),
+        CssNode::AtRule(rule) => {
+            !(rule.block.as_ref().is_some_and(Vec::is_empty)
+                && matches!(rule.name.as_str(), "media" | "supports" | "container" | "layer" | "screen"))
+        }
+        CssNode::Declaration(_) => true,
+    });
+}
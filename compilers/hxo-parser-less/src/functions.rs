@@ -0,0 +1,234 @@
+//! Built-in functions. Those Less shares with Sass (`darken`, `mix`, `rgba`, `percentage`...) go through
+//! [`hxo_parser_scss::builtins`]; the ones only Less has (`fade`, `spin`, `tint`, type checks...) are here.
+
+use hxo_parser_scss::{
+    Color, Value,
+    builtins::{self, BuiltinArgs},
+};
+use std::f64::consts::PI;
+
+/// Functions whose Less and Sass versions agree.
+const SHARED: &[&str] = &[
+    "rgb",
+    "rgba",
+    "hsl",
+    "hsla",
+    "red",
+    "green",
+    "blue",
+    "alpha",
+    "saturation",
+    "lightness",
+    "mix",
+    "lighten",
+    "darken",
+    "saturate",
+    "desaturate",
+    "percentage",
+    "ceil",
+    "floor",
+    "abs",
+    "min",
+    "max",
+];
+
+/// Calls a built-in function; `None` when there is no such function, which then prints as plain CSS.
+pub fn call(name: &str, args: &[Value]) -> Option<Result<Value, String>> {
+    let name = name.to_ascii_lowercase();
+    Some(match name.as_str() {
+        "fade" => color(args, 0).and_then(|mut color| {
+            color.alpha = fraction(args, 1)?.clamp(0.0, 1.0);
+            Ok(Value::Color(color))
+        }),
+        "fadein" | "fadeout" => color(args, 0).and_then(|mut color| {
+            let amount = fraction(args, 1)?;
+            let amount = if name == "fadein" { amount } else { -amount };
+            color.alpha = (color.alpha + amount).clamp(0.0, 1.0);
+            Ok(Value::Color(color))
+        }),
+        "spin" => color(args, 0).and_then(|color| {
+            let (hue, saturation, lightness) = color.to_hsl();
+            let degrees = number(args, 1)?.0;
+            Ok(Value::Color(Color::hsla(hue + degrees, saturation, lightness, color.alpha)))
+        }),
+        "tint" | "shade" => {
+            let base = if name == "tint" { Color::rgba(255.0, 255.0, 255.0, 1.0) } else { Color::rgba(0.0, 0.0, 0.0, 1.0) };
+            let weight = args.get(2).cloned().unwrap_or(Value::number(50.0, "%"));
+            let args = [Value::Color(base), args.first().cloned().unwrap_or(Value::Null), weight];
+            return shared("mix", &args);
+        }
+        "greyscale" => return shared("grayscale", args),
+        "argb" => return shared("ie-hex-str", args),
+        "hue" => color(args, 0).map(|color| Value::number(color.to_hsl().0.round(), "")),
+        "luma" => color(args, 0).map(|color| Value::number((luma(&color) * 100.0).round(), "%")),
+        "contrast" => contrast(args),
+
+        "round" => number(args, 0).and_then(|(value, unit)| {
+            let places = match args.get(1) {
+                Some(_) => number(args, 1)?.0,
+                None => 0.0,
+            };
+            let factor = 10f64.powi(places as i32);
+            Ok(Value::number((value * factor).round() / factor, unit))
+        }),
+        "sqrt" => number(args, 0).map(|(value, unit)| Value::number(value.sqrt(), unit)),
+        "pow" => number(args, 0).and_then(|(base, unit)| Ok(Value::number(base.powf(number(args, 1)?.0), unit))),
+        "mod" => number(args, 0).and_then(|(a, unit)| Ok(Value::number(a % number(args, 1)?.0, unit))),
+        "pi" => Ok(Value::number(PI, "")),
+        "unit" => number(args, 0).map(|(value, _)| {
+            let unit = args.get(1).and_then(|unit| unit.interpolated().ok()).unwrap_or_default();
+            Value::number(value, unit)
+        }),
+        "get-unit" => match args.first() {
+            Some(Value::Number(number)) => Ok(Value::ident(number.unit.clone())),
+            _ => Ok(Value::ident("")),
+        },
+        "convert" => match (args.first(), args.get(1).and_then(|unit| unit.interpolated().ok())) {
+            (Some(Value::Number(number)), Some(unit)) => {
+                Ok(number.convert_to(&unit).map_or_else(|| Value::Number(number.clone()), |value| Value::number(value, unit)))
+            }
+            _ => Err("convert() takes a number and a unit.".to_string()),
+        },
+
+        "iscolor" => Ok(Value::Bool(matches!(args.first(), Some(Value::Color(_))))),
+        "isnumber" => Ok(Value::Bool(matches!(args.first(), Some(Value::Number(_))))),
+        "isstring" => Ok(Value::Bool(matches!(args.first(), Some(Value::String(_, Some(_)))))),
+        "iskeyword" => Ok(Value::Bool(matches!(args.first(), Some(Value::String(text, None)) if !text.starts_with("url(")))),
+        "isurl" => Ok(Value::Bool(matches!(args.first(), Some(Value::String(text, None)) if text.starts_with("url(")))),
+        "ispixel" => Ok(is_unit(args.first(), "px")),
+        "isem" => Ok(is_unit(args.first(), "em")),
+        "ispercentage" => Ok(is_unit(args.first(), "%")),
+        "isunit" => Ok(is_unit(args.first(), &args.get(1).and_then(|unit| unit.interpolated().ok()).unwrap_or_default())),
+
+        "e" => string(args, 0).map(Value::ident),
+        "escape" => string(args, 0).map(|text| Value::ident(escape(&text))),
+        "%" => format(args),
+        "length" => Ok(Value::number(args.first().map_or(0, |list| list.as_list().len()) as f64, "")),
+        "extract" => number(args, 1).and_then(|(index, _)| {
+            let items = args.first().map(Value::as_list).unwrap_or_default();
+            let index = index as usize;
+            if index == 0 || index > items.len() {
+                return Err(format!("extract(): index {} is out of bounds.", index));
+            }
+            Ok(items[index - 1].clone())
+        }),
+        name if SHARED.contains(&name) => return shared(name, args),
+        _ => return None,
+    })
+}
+
+fn shared(name: &str, args: &[Value]) -> Option<Result<Value, String>> {
+    builtins::call(None, name, &BuiltinArgs { positional: args.to_vec(), named: Vec::new() })
+}
+
+fn color(args: &[Value], index: usize) -> Result<Color, String> {
+    match args.get(index) {
+        Some(Value::Color(color)) => Ok(color.clone().computed()),
+        Some(other) => Err(format!("{} is not a color.", other.inspect())),
+        None => Err("Missing color argument.".to_string()),
+    }
+}
+
+fn number(args: &[Value], index: usize) -> Result<(f64, String), String> {
+    match args.get(index) {
+        Some(Value::Number(number)) => Ok((number.value, number.unit.clone())),
+        Some(other) => Err(format!("{} is not a number.", other.inspect())),
+        None => Err("Missing number argument.".to_string()),
+    }
+}
+
+/// `50%` or `0.5` as `0.5`.
+fn fraction(args: &[Value], index: usize) -> Result<f64, String> {
+    let (value, unit) = number(args, index)?;
+    Ok(if unit == "%" { value / 100.0 } else { value })
+}
+
+fn string(args: &[Value], index: usize) -> Result<String, String> {
+    match args.get(index) {
+        Some(Value::String(text, _)) => Ok(text.clone()),
+        Some(other) => other.to_css(),
+        None => Err("Missing string argument.".to_string()),
+    }
+}
+
+fn is_unit(value: Option<&Value>, unit: &str) -> Value {
+    Value::Bool(matches!(value, Some(Value::Number(number)) if number.unit.eq_ignore_ascii_case(unit)))
+}
+
+/// Relative luminance, `0..=1`.
+fn luma(color: &Color) -> f64 {
+    let linear = |channel: f64| {
+        let channel = channel / 255.0;
+        if channel <= 0.03928 { channel / 12.92 } else { ((channel + 0.055) / 1.055).powf(2.4) }
+    };
+    0.2126 * linear(color.red) + 0.7152 * linear(color.green) + 0.0722 * linear(color.blue)
+}
+
+/// `contrast(@background, @dark: black, @light: white, @threshold: 43%)`
+fn contrast(args: &[Value]) -> Result<Value, String> {
+    let Value::Color(background) = args.first().cloned().unwrap_or(Value::Null)
+    else {
+        // Not a color: the CSS filter function
+        return Err("contrast() takes a color.".to_string());
+    };
+    let mut dark = match args.get(1) {
+        Some(_) => color(args, 1)?,
+        None => Color::rgba(0.0, 0.0, 0.0, 1.0),
+    };
+    let mut light = match args.get(2) {
+        Some(_) => color(args, 2)?,
+        None => Color::rgba(255.0, 255.0, 255.0, 1.0),
+    };
+    let threshold = match args.get(3) {
+        Some(_) => fraction(args, 3)?,
+        None => 0.43,
+    };
+    if luma(&dark) > luma(&light) {
+        std::mem::swap(&mut dark, &mut light);
+    }
+    Ok(Value::Color(if luma(&background) < threshold { light } else { dark }))
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, ' ' | '#' | '^' | '(' | ')' | '{' | '}' | '|' | ':' | '>' | '<' | ';' | ']' | '[' | '=') {
+            out.push_str(&format!("%{:02X}", c as u32));
+        }
+        else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// `%("%d items in %s", 3, "cart")`: `%s` inserts a string's text, `%d` and `%a` any value as CSS;
+/// upper-case placeholders are also URL-escaped.
+fn format(args: &[Value]) -> Result<Value, String> {
+    let Some(Value::String(pattern, quote)) = args.first()
+    else {
+        return Err("%() takes a format string.".to_string());
+    };
+    let mut values = args[1..].iter();
+    let mut out = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek().copied()) {
+            ('%', Some(kind @ ('s' | 'S' | 'd' | 'D' | 'a' | 'A'))) => {
+                chars.next();
+                let value = values.next().cloned().unwrap_or(Value::Null);
+                let text = match (&value, kind.to_ascii_lowercase()) {
+                    (Value::String(text, _), 's') => text.clone(),
+                    (value, _) => value.to_css()?,
+                };
+                out.push_str(&if kind.is_ascii_uppercase() { escape(&text) } else { text });
+            }
+            ('%', Some('%')) => {
+                chars.next();
+                out.push('%');
+            }
+            (c, _) => out.push(c),
+        }
+    }
+    Ok(Value::String(out, *quote))
+}
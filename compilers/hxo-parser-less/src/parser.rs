@@ -0,0 +1,904 @@
+//! Less syntax: turns source text into [`Node`]s, with a Pratt parser for [`CssExpr`] values.
+
+use crate::ast::{Condition, CssExpr, ExtendTarget, Guard, MixinArg, MixinDefinition, MixinParam, Node};
+use hxo_parser_scss::{Color, ast::ListSeparator};
+use hxo_types::{Cursor, Error, Position, Result};
+use std::rc::Rc;
+
+/// Parses a whole Less stylesheet.
+pub fn parse_less(source: &str) -> Result<Vec<Node>> {
+    parse_less_with(&mut Cursor::new(source))
+}
+
+/// Parses from `cursor` to the end of its source; spans follow the cursor's position.
+pub fn parse_less_with(cursor: &mut Cursor) -> Result<Vec<Node>> {
+    let mut parser = LessParserImpl::new(cursor);
+    let nodes = parser.parse_nodes()?;
+    if !parser.cursor.is_eof() {
+        return Err(Error::parse_error("unexpected `}`".to_string(), parser.cursor.span_at_current()));
+    }
+    Ok(nodes)
+}
+
+pub fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || (!c.is_ascii() && c != '\0')
+}
+
+/// Functions whose arguments are plain CSS, kept as text with `@var` and `@{var}` substituted.
+const RAW_FUNCTIONS: &[&str] = &["url", "calc", "-webkit-calc", "-moz-calc", "var", "env", "expression"];
+
+pub struct LessParserImpl<'a, 'b> {
+    pub cursor: &'a mut Cursor<'b>,
+    /// Inside `( )`, where `/` divides.
+    paren_depth: usize,
+    /// Inside a `when` guard, where `=`, `<` and `>` compare.
+    in_guard: bool,
+}
+
+impl<'a, 'b> LessParserImpl<'a, 'b> {
+    pub fn new(cursor: &'a mut Cursor<'b>) -> Self {
+        Self { cursor, paren_depth: 0, in_guard: false }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::parse_error(message.into(), self.cursor.span_at_current()))
+    }
+
+    fn save(&self) -> (usize, usize, usize) {
+        (self.cursor.pos, self.cursor.line, self.cursor.column)
+    }
+
+    fn restore(&mut self, (pos, line, column): (usize, usize, usize)) {
+        self.cursor.pos = pos;
+        self.cursor.line = line;
+        self.cursor.column = column;
+    }
+
+    fn rest(&self) -> &'b str {
+        &self.cursor.source[self.cursor.pos..]
+    }
+
+    /// Skips whitespace and comments; returns whether anything was skipped.
+    pub fn skip_comments_and_whitespace(&mut self) -> bool {
+        let start = self.cursor.pos;
+        loop {
+            self.cursor.skip_whitespace();
+            if self.cursor.peek_str("//") {
+                self.consume_until_newline();
+            }
+            else if self.cursor.peek_str("/*") {
+                self.cursor.consume_n(2);
+                self.consume_until_str("*/");
+                self.cursor.consume_n(2);
+            }
+            else {
+                break;
+            }
+        }
+        self.cursor.pos != start
+    }
+
+    fn consume_until_newline(&mut self) {
+        while !self.cursor.is_eof() && self.cursor.peek() != '\n' {
+            self.cursor.consume();
+        }
+    }
+
+    fn consume_until_str(&mut self, target: &str) {
+        while !self.cursor.is_eof() && !self.cursor.peek_str(target) {
+            self.cursor.consume();
+        }
+    }
+
+    /// Whether the cursor is at `word` followed by a non-name character.
+    fn at_keyword(&self, word: &str) -> bool {
+        self.cursor.peek_str(word) && !is_name_char(self.rest()[word.len()..].chars().next().unwrap_or(' '))
+    }
+
+    fn consume_keyword(&mut self, word: &str) -> bool {
+        if self.at_keyword(word) {
+            self.cursor.consume_n(word.len());
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn consume_ident(&mut self) -> String {
+        self.cursor.consume_while(is_name_char)
+    }
+
+    /// Reads a name that may contain `@{var}` interpolations, such as `@{prefix}-button`.
+    fn consume_word(&mut self) -> String {
+        let start = self.cursor.pos;
+        loop {
+            if self.cursor.peek_str("@{") {
+                while !self.cursor.is_eof() && self.cursor.peek() != '}' {
+                    self.cursor.consume();
+                }
+                self.cursor.consume();
+            }
+            else if is_name_char(self.cursor.peek()) {
+                self.cursor.consume();
+            }
+            else {
+                break;
+            }
+        }
+        self.cursor.current_str(start).to_string()
+    }
+
+    /// Reads a quoted string and returns its text, escapes included.
+    fn consume_quoted(&mut self) -> Result<String> {
+        let quote = self.cursor.consume();
+        let start = self.cursor.pos;
+        while !self.cursor.is_eof() && self.cursor.peek() != quote {
+            if self.cursor.consume() == '\\' {
+                self.cursor.consume();
+            }
+        }
+        let text = self.cursor.current_str(start).to_string();
+        if !self.cursor.consume_str(&quote.to_string()) {
+            return self.error("unterminated string");
+        }
+        Ok(text)
+    }
+
+    /// Reads `( ... )` as written, nested parentheses and strings included.
+    fn consume_parens_raw(&mut self) -> Result<String> {
+        let start = self.cursor.pos;
+        let mut depth = 0usize;
+        while !self.cursor.is_eof() {
+            match self.cursor.peek() {
+                '"' | '\'' => {
+                    self.consume_quoted()?;
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.cursor.consume();
+                        return Ok(self.cursor.current_str(start).to_string());
+                    }
+                }
+                _ => {}
+            }
+            self.cursor.consume();
+        }
+        self.error("expected `)`")
+    }
+
+    /// Reads raw text up to a top-level `;`, `}`, `{` or `!`, for values the expression grammar doesn't
+    /// cover. Comments are dropped.
+    fn consume_raw(&mut self) -> Result<String> {
+        let mut text = String::new();
+        let mut depth = 0usize;
+        while !self.cursor.is_eof() {
+            let c = self.cursor.peek();
+            match c {
+                '"' | '\'' => {
+                    let start = self.cursor.pos;
+                    self.consume_quoted()?;
+                    text.push_str(self.cursor.current_str(start));
+                    continue;
+                }
+                '/' if depth == 0 && (self.cursor.peek_str("//") || self.cursor.peek_str("/*")) => {
+                    self.skip_comments_and_whitespace();
+                    text.push(' ');
+                    continue;
+                }
+                '@' if self.cursor.peek_n(1) == '{' => {
+                    text.push_str(&self.consume_word());
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                ';' | '}' | '{' | '!' if depth == 0 => break,
+                _ => {}
+            }
+            text.push(c);
+            self.cursor.consume();
+        }
+        Ok(collapse_whitespace(&text))
+    }
+
+    /// Ends a statement at `;`, or before the `}` closing its block.
+    fn end_statement(&mut self) -> Result<()> {
+        self.skip_comments_and_whitespace();
+        match self.cursor.peek() {
+            ';' => {
+                self.cursor.consume();
+                Ok(())
+            }
+            '}' | '\0' => Ok(()),
+            c => self.error(format!("expected `;`, found `{}`", c)),
+        }
+    }
+
+    pub fn parse_nodes(&mut self) -> Result<Vec<Node>> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_comments_and_whitespace();
+            match self.cursor.peek() {
+                '\0' | '}' => break,
+                ';' => {
+                    self.cursor.consume();
+                }
+                _ => nodes.push(self.parse_node()?),
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn parse_block(&mut self) -> Result<Vec<Node>> {
+        self.skip_comments_and_whitespace();
+        self.cursor.expect('{')?;
+        let nodes = self.parse_nodes()?;
+        self.cursor.expect('}')?;
+        Ok(nodes)
+    }
+
+    fn parse_node(&mut self) -> Result<Node> {
+        let start = self.cursor.position();
+        if self.cursor.peek() == '@' && self.cursor.peek_n(1) != '{' {
+            if self.is_variable() {
+                return self.parse_variable(start);
+            }
+            return self.parse_at_rule(start);
+        }
+        let opens_block = self.statement_end() == '{';
+        if opens_block {
+            if self.at_mixin_definition() { self.parse_mixin_definition(start) } else { self.parse_rule(start) }
+        }
+        else if matches!(self.cursor.peek(), '.' | '#') {
+            self.parse_mixin_call(start)
+        }
+        else if self.cursor.peek_str("&:extend(") {
+            self.parse_extend(start)
+        }
+        else {
+            self.parse_declaration(start)
+        }
+    }
+
+    /// The character that ends the statement at the cursor: a top-level `{`, `;` or `}`, or `\0` at the
+    /// end of input.
+    fn statement_end(&self) -> char {
+        let rest = self.rest();
+        let bytes = rest.as_bytes();
+        let mut depth = 0usize;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                quote @ (b'"' | b'\'') => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                b'@' if bytes.get(i + 1) == Some(&b'{') => {
+                    i += rest[i..].find('}').unwrap_or(bytes.len());
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i += rest[i..].find("*/").map_or(bytes.len(), |end| end + 1);
+                }
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => depth = depth.saturating_sub(1),
+                c @ (b'{' | b';' | b'}') if depth == 0 => return c as char,
+                _ => {}
+            }
+            i += 1;
+        }
+        '\0'
+    }
+
+    /// `@name:` starts a variable; any other `@name` is an at-rule such as `@media` or `@apply`.
+    fn is_variable(&self) -> bool {
+        let Some(rest) = self.rest().strip_prefix('@')
+        else {
+            return false;
+        };
+        let rest = rest.trim_start_matches(is_name_char);
+        rest.trim_start().starts_with(':')
+    }
+
+    /// `.name(` or `#name(` followed by a block defines a parametric mixin.
+    fn at_mixin_definition(&self) -> bool {
+        let rest = self.rest();
+        let Some(name) = rest.strip_prefix('.').or_else(|| rest.strip_prefix('#'))
+        else {
+            return false;
+        };
+        let after = name.trim_start_matches(is_name_char);
+        after.len() < name.len() && after.starts_with('(')
+    }
+
+    fn parse_variable(&mut self, start: Position) -> Result<Node> {
+        self.cursor.consume(); // @
+        let name = self.consume_ident();
+        self.skip_comments_and_whitespace();
+        self.cursor.expect(':')?;
+        self.skip_comments_and_whitespace();
+        if self.cursor.peek() == '{' {
+            return self.error("detached rulesets are not supported");
+        }
+        let value = self.parse_value()?;
+        self.end_statement()?;
+        Ok(Node::Variable { name, value, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_declaration(&mut self, start: Position) -> Result<Node> {
+        let mut property = String::new();
+        while !self.cursor.is_eof() && !matches!(self.cursor.peek(), ':' | ';' | '}') {
+            if self.cursor.peek_str("@{") {
+                property.push_str(&self.consume_word());
+            }
+            else {
+                property.push(self.cursor.consume());
+            }
+        }
+        let property = property.trim().to_string();
+        if self.cursor.peek() != ':' {
+            return self.error(format!("expected `:` after `{}`", property));
+        }
+        self.cursor.consume();
+        self.skip_comments_and_whitespace();
+
+        let value = if property.starts_with("--") { CssExpr::Raw(self.consume_raw()?) } else { self.parse_value()? };
+        let important = self.parse_important()?;
+        self.end_statement()?;
+        Ok(Node::Declaration { property, value, important, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_important(&mut self) -> Result<bool> {
+        self.skip_comments_and_whitespace();
+        if self.cursor.peek() != '!' {
+            return Ok(false);
+        }
+        self.cursor.consume();
+        self.cursor.skip_whitespace();
+        if self.consume_keyword("important") { Ok(true) } else { self.error("expected `!important`") }
+    }
+
+    fn parse_rule(&mut self, start: Position) -> Result<Node> {
+        let (selector, guard) = self.parse_selector()?;
+        let body = self.parse_block()?;
+        Ok(Node::Rule { selector, guard, body: Rc::new(body), span: self.cursor.span_from(start) })
+    }
+
+    /// Reads a selector up to its block, and the `when` guard after it.
+    fn parse_selector(&mut self) -> Result<(String, Option<Guard>)> {
+        let mut text = String::new();
+        let mut depth = 0usize;
+        let mut guard = None;
+        loop {
+            let c = self.cursor.peek();
+            match c {
+                '\0' => return self.error("expected `{`"),
+                '{' if depth == 0 => break,
+                '@' if self.cursor.peek_n(1) == '{' => {
+                    text.push_str(&self.consume_word());
+                    continue;
+                }
+                '"' | '\'' => {
+                    let start = self.cursor.pos;
+                    self.consume_quoted()?;
+                    text.push_str(self.cursor.current_str(start));
+                    continue;
+                }
+                '/' if self.cursor.peek_n(1) == '*' || self.cursor.peek_n(1) == '/' => {
+                    self.skip_comments_and_whitespace();
+                    text.push(' ');
+                    continue;
+                }
+                'w' if depth == 0 && text.ends_with(char::is_whitespace) && self.at_keyword("when") => {
+                    guard = Some(self.parse_guard()?);
+                    self.skip_comments_and_whitespace();
+                    break;
+                }
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            text.push(c);
+            self.cursor.consume();
+        }
+        Ok((collapse_whitespace(&text), guard))
+    }
+
+    /// `when [not] (condition) [and ...][, ...]`
+    fn parse_guard(&mut self) -> Result<Guard> {
+        self.consume_keyword("when");
+        let mut alternatives = Vec::new();
+        let mut conditions = Vec::new();
+        loop {
+            self.skip_comments_and_whitespace();
+            let negated = self.consume_keyword("not");
+            self.skip_comments_and_whitespace();
+            self.cursor.expect('(')?;
+            self.in_guard = true;
+            self.paren_depth += 1;
+            let condition = self.parse_condition(negated);
+            self.in_guard = false;
+            self.paren_depth -= 1;
+            conditions.push(condition?);
+            self.skip_comments_and_whitespace();
+            self.cursor.expect(')')?;
+            self.skip_comments_and_whitespace();
+            if self.consume_keyword("and") {
+                continue;
+            }
+            if self.cursor.peek() == ',' || self.at_keyword("or") {
+                if !self.consume_keyword("or") {
+                    self.cursor.consume();
+                }
+                alternatives.push(std::mem::take(&mut conditions));
+                continue;
+            }
+            break;
+        }
+        alternatives.push(conditions);
+        Ok(Guard { alternatives })
+    }
+
+    fn parse_condition(&mut self, negated: bool) -> Result<Condition> {
+        let left = self.parse_space_list()?;
+        self.skip_comments_and_whitespace();
+        let op = [">=", "=<", "<=", "=>", "=", "<", ">"].into_iter().find(|op| self.cursor.peek_str(op));
+        let comparison = match op {
+            Some(op) => {
+                self.cursor.consume_n(op.len());
+                let op = match op {
+                    "<=" => "=<",
+                    "=>" => ">=",
+                    op => op,
+                };
+                Some((op.to_string(), self.parse_space_list()?))
+            }
+            None => None,
+        };
+        Ok(Condition { negated, left, comparison })
+    }
+
+    fn parse_mixin_definition(&mut self, start: Position) -> Result<Node> {
+        let prefix = self.cursor.consume();
+        let name = format!("{}{}", prefix, self.consume_ident());
+        self.cursor.expect('(')?;
+        let (params, variadic) = self.parse_params()?;
+        self.cursor.expect(')')?;
+        self.skip_comments_and_whitespace();
+        let guard = if self.at_keyword("when") { Some(self.parse_guard()?) } else { None };
+        let body = self.parse_block()?;
+        let span = self.cursor.span_from(start);
+        Ok(Node::Mixin(Rc::new(MixinDefinition { name, params, variadic, guard, body: Rc::new(body), span })))
+    }
+
+    /// Arguments and parameters are separated by `;` when there is one, so that `,` can build lists.
+    fn argument_separator(&self) -> char {
+        let bytes = self.rest().as_bytes();
+        let mut depth = 0usize;
+        let mut quote = None;
+        for &b in bytes {
+            match (quote, b) {
+                (Some(q), b) if b == q => quote = None,
+                (Some(_), _) => {}
+                (None, b'"' | b'\'') => quote = Some(b),
+                (None, b'(') => depth += 1,
+                (None, b')') if depth == 0 => break,
+                (None, b')') => depth -= 1,
+                (None, b';') if depth == 0 => return ';',
+                (None, b'{' | b'}') => break,
+                _ => {}
+            }
+        }
+        ','
+    }
+
+    fn parse_argument_value(&mut self, separator: char) -> Result<CssExpr> {
+        if separator == ';' { self.parse_comma_list() } else { self.parse_space_list() }
+    }
+
+    fn parse_params(&mut self) -> Result<(Vec<MixinParam>, Option<String>)> {
+        let separator = self.argument_separator();
+        let mut params = Vec::new();
+        let mut variadic = None;
+        loop {
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() == ')' {
+                break;
+            }
+            if self.cursor.consume_str("...") {
+                variadic = Some(String::new());
+            }
+            else if self.cursor.peek() == '@' && self.cursor.peek_n(1) != '{' {
+                self.cursor.consume();
+                let name = self.consume_ident();
+                if self.cursor.consume_str("...") {
+                    variadic = Some(name);
+                }
+                else {
+                    self.skip_comments_and_whitespace();
+                    let default = if self.cursor.peek() == ':' {
+                        self.cursor.consume();
+                        Some(self.parse_argument_value(separator)?)
+                    }
+                    else {
+                        None
+                    };
+                    params.push(MixinParam::Variable { name, default });
+                }
+            }
+            else {
+                params.push(MixinParam::Pattern(self.parse_argument_value(separator)?));
+            }
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() != separator {
+                break;
+            }
+            self.cursor.consume();
+        }
+        Ok((params, variadic))
+    }
+
+    fn parse_mixin_call(&mut self, start: Position) -> Result<Node> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() == '>' {
+                self.cursor.consume();
+                self.skip_comments_and_whitespace();
+            }
+            let prefix = self.cursor.peek();
+            if prefix != '.' && prefix != '#' {
+                break;
+            }
+            self.cursor.consume();
+            path.push(format!("{}{}", prefix, self.consume_ident()));
+        }
+        self.skip_comments_and_whitespace();
+        let mut args = Vec::new();
+        if self.cursor.peek() == '(' {
+            self.cursor.consume();
+            args = self.parse_args()?;
+            self.skip_comments_and_whitespace();
+            self.cursor.expect(')')?;
+        }
+        let important = self.parse_important()?;
+        self.end_statement()?;
+        Ok(Node::MixinCall { path, args, important, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_args(&mut self) -> Result<Vec<MixinArg>> {
+        let separator = self.argument_separator();
+        let mut args = Vec::new();
+        loop {
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() == ')' {
+                break;
+            }
+            let name = if self.cursor.peek() == '@' && self.is_variable() {
+                self.cursor.consume();
+                let name = self.consume_ident();
+                self.skip_comments_and_whitespace();
+                self.cursor.expect(':')?;
+                Some(name)
+            }
+            else {
+                None
+            };
+            let value = self.parse_argument_value(separator)?;
+            args.push(MixinArg { name, value });
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() != separator {
+                break;
+            }
+            self.cursor.consume();
+        }
+        Ok(args)
+    }
+
+    /// `&:extend(.a, .b all);`
+    fn parse_extend(&mut self, start: Position) -> Result<Node> {
+        self.cursor.consume_str("&:extend");
+        let raw = self.consume_parens_raw()?;
+        let targets = parse_extend_targets(&raw[1..raw.len() - 1]);
+        self.end_statement()?;
+        Ok(Node::Extend { targets, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_at_rule(&mut self, start: Position) -> Result<Node> {
+        self.cursor.consume(); // @
+        let name = self.consume_ident();
+        if name == "import" {
+            return self.parse_import(start);
+        }
+        self.skip_comments_and_whitespace();
+        let prelude = self.consume_raw()?;
+        let body = if self.cursor.peek() == '{' {
+            Some(self.parse_block()?)
+        }
+        else {
+            self.end_statement()?;
+            None
+        };
+        Ok(Node::AtRule { name, prelude, body, span: self.cursor.span_from(start) })
+    }
+
+    /// `@import (reference, optional) "file";`
+    fn parse_import(&mut self, start: Position) -> Result<Node> {
+        self.skip_comments_and_whitespace();
+        let mut options = Vec::new();
+        if self.cursor.peek() == '(' {
+            let raw = self.consume_parens_raw()?;
+            options = raw[1..raw.len() - 1].split(',').map(|option| option.trim().to_ascii_lowercase()).collect();
+            self.skip_comments_and_whitespace();
+        }
+        let prelude_start = self.cursor.pos;
+        let url = self.parse_nud()?;
+        self.consume_raw()?;
+        let prelude = collapse_whitespace(self.cursor.current_str(prelude_start));
+        self.end_statement()?;
+        Ok(Node::Import { url, options, prelude, span: self.cursor.span_from(start) })
+    }
+
+    /// A declaration or variable value: a comma-separated list of space-separated lists. A value the
+    /// expression grammar can't read is kept as raw text.
+    fn parse_value(&mut self) -> Result<CssExpr> {
+        let saved = self.save();
+        if let Ok(value) = self.parse_comma_list() {
+            self.skip_comments_and_whitespace();
+            if matches!(self.cursor.peek(), ';' | '}' | '!' | '\0') {
+                return Ok(value);
+            }
+        }
+        self.restore(saved);
+        Ok(CssExpr::Raw(self.consume_raw()?))
+    }
+
+    fn parse_comma_list(&mut self) -> Result<CssExpr> {
+        let mut items = vec![self.parse_space_list()?];
+        loop {
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() != ',' || self.in_guard {
+                break;
+            }
+            self.cursor.consume();
+            items.push(self.parse_space_list()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { CssExpr::List(items, ListSeparator::Comma) })
+    }
+
+    fn at_expression_end(&self) -> bool {
+        match self.cursor.peek() {
+            ';' | '}' | '{' | ')' | ',' | '!' | '\0' => true,
+            '=' | '<' | '>' => self.in_guard,
+            _ => false,
+        }
+    }
+
+    fn parse_space_list(&mut self) -> Result<CssExpr> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_comments_and_whitespace();
+            if self.at_expression_end() || (self.in_guard && (self.at_keyword("and") || self.at_keyword("or"))) {
+                break;
+            }
+            items.push(self.parse_slash_list()?);
+        }
+        match items.len() {
+            0 => self.error("expected a value"),
+            1 => Ok(items.remove(0)),
+            _ => Ok(CssExpr::List(items, ListSeparator::Space)),
+        }
+    }
+
+    /// `12px/1.5`: outside parentheses `/` separates rather than divides.
+    fn parse_slash_list(&mut self) -> Result<CssExpr> {
+        let mut items = vec![self.parse_expression()?];
+        loop {
+            let saved = self.save();
+            self.skip_comments_and_whitespace();
+            if self.paren_depth > 0 || self.cursor.peek() != '/' {
+                self.restore(saved);
+                break;
+            }
+            self.cursor.consume();
+            self.skip_comments_and_whitespace();
+            items.push(self.parse_expression()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { CssExpr::List(items, ListSeparator::Slash) })
+    }
+
+    pub fn parse_expression(&mut self) -> Result<CssExpr> {
+        self.parse_pratt_expr(0)
+    }
+
+    fn parse_pratt_expr(&mut self, min_precedence: i32) -> Result<CssExpr> {
+        self.skip_comments_and_whitespace();
+        let mut left = self.parse_nud()?;
+
+        loop {
+            let saved = self.save();
+            let spaced = self.skip_comments_and_whitespace();
+            let op = self.peek_operator(spaced);
+            if op.is_empty() {
+                self.restore(saved);
+                break;
+            }
+
+            let precedence = self.get_precedence(&op);
+            if precedence <= min_precedence {
+                self.restore(saved);
+                break;
+            }
+
+            self.cursor.consume_n(op.len());
+            let right = self.parse_pratt_expr(precedence)?;
+            let op = if op == "./" { "/".to_string() } else { op };
+            left = CssExpr::Binary(Box::new(left), op, Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn get_precedence(&self, op: &str) -> i32 {
+        match op {
+            "+" | "-" => 1,
+            "*" | "/" | "./" => 2,
+            _ => 0,
+        }
+    }
+
+    /// The operator at the cursor. `1px -1px` is a list: a sign right after whitespace belongs to the
+    /// next value. `/` only divides inside parentheses, `./` anywhere.
+    fn peek_operator(&self, spaced: bool) -> String {
+        let next = self.cursor.peek_n(1);
+        match self.cursor.peek() {
+            '*' => "*",
+            '+' | '-' if spaced && !next.is_whitespace() => "",
+            '+' => "+",
+            '-' => "-",
+            '/' if next == '/' || next == '*' => "",
+            '/' if self.paren_depth > 0 => "/",
+            '.' if next == '/' => "./",
+            _ => "",
+        }
+        .to_string()
+    }
+
+    fn parse_nud(&mut self) -> Result<CssExpr> {
+        let c = self.cursor.peek();
+        let next = self.cursor.peek_n(1);
+        match c {
+            '@' if next == '@' => {
+                self.cursor.consume_n(2);
+                Ok(CssExpr::VariableVariable(self.consume_ident()))
+            }
+            '@' if next == '{' => Ok(CssExpr::Raw(self.consume_word())),
+            '@' => {
+                self.cursor.consume();
+                Ok(CssExpr::Variable(self.consume_ident()))
+            }
+            '0'..='9' => self.parse_number(),
+            '.' if next.is_ascii_digit() => self.parse_number(),
+            '-' | '+' if next.is_ascii_digit() || (next == '.' && self.cursor.peek_n(2).is_ascii_digit()) => {
+                self.parse_number()
+            }
+            '-' if next == '@' || next == '(' => {
+                self.cursor.consume();
+                Ok(CssExpr::Negate(Box::new(self.parse_nud()?)))
+            }
+            '#' => {
+                self.cursor.consume();
+                let digits = self.consume_ident();
+                let text = format!("#{}", digits);
+                Ok(match Color::from_hex(&digits) {
+                    Some(mut color) => {
+                        color.original = Some(text);
+                        CssExpr::Color(color)
+                    }
+                    None => CssExpr::Raw(text),
+                })
+            }
+            '"' | '\'' => Ok(CssExpr::Quoted { text: self.consume_quoted()?, quote: c, escaped: false }),
+            '~' if next == '"' || next == '\'' => {
+                self.cursor.consume();
+                Ok(CssExpr::Quoted { text: self.consume_quoted()?, quote: next, escaped: true })
+            }
+            '(' => {
+                self.cursor.consume();
+                self.paren_depth += 1;
+                let inner = self.parse_comma_list();
+                self.paren_depth -= 1;
+                let inner = inner?;
+                self.skip_comments_and_whitespace();
+                self.cursor.expect(')')?;
+                Ok(CssExpr::Paren(Box::new(inner)))
+            }
+            '%' if next == '(' => {
+                self.cursor.consume();
+                self.parse_call("%".to_string())
+            }
+            c if is_name_char(c) && !c.is_ascii_digit() => {
+                let name = self.consume_word();
+                if self.cursor.peek() == '(' {
+                    return self.parse_call(name);
+                }
+                if !name.contains("@{") {
+                    if let Some(mut color) = Color::named(&name.to_ascii_lowercase()) {
+                        color.original = Some(name);
+                        return Ok(CssExpr::Color(color));
+                    }
+                }
+                Ok(CssExpr::Raw(name))
+            }
+            _ => {
+                self.cursor.consume();
+                Ok(CssExpr::Raw(c.to_string()))
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<CssExpr> {
+        let start = self.cursor.pos;
+        if matches!(self.cursor.peek(), '-' | '+') {
+            self.cursor.consume();
+        }
+        self.cursor.consume_while(|c| c.is_ascii_digit() || c == '.');
+        let value = self.cursor.current_str(start).parse::<f64>();
+        let Ok(value) = value
+        else {
+            return self.error(format!("invalid number `{}`", self.cursor.current_str(start)));
+        };
+        let unit = if self.cursor.peek() == '%' {
+            self.cursor.consume();
+            "%".to_string()
+        }
+        else {
+            self.cursor.consume_while(|c| c.is_ascii_alphabetic())
+        };
+        Ok(CssExpr::Number(value, unit))
+    }
+
+    /// `name(args)` with the cursor at `(`.
+    fn parse_call(&mut self, name: String) -> Result<CssExpr> {
+        if RAW_FUNCTIONS.contains(&name.to_ascii_lowercase().as_str()) {
+            return Ok(CssExpr::Raw(format!("{}{}", name, self.consume_parens_raw()?)));
+        }
+        self.cursor.expect('(')?;
+        let separator = self.argument_separator();
+        let mut args = Vec::new();
+        loop {
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() == ')' {
+                break;
+            }
+            args.push(self.parse_argument_value(separator)?);
+            self.skip_comments_and_whitespace();
+            if self.cursor.peek() != separator {
+                break;
+            }
+            self.cursor.consume();
+        }
+        self.cursor.expect(')')?;
+        Ok(CssExpr::Call { name, args })
+    }
+}
+
+/// `.a, .b all` inside `:extend(...)`.
+pub fn parse_extend_targets(text: &str) -> Vec<ExtendTarget> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|target| !target.is_empty())
+        .map(|target| match target.strip_suffix(" all") {
+            Some(selector) => ExtendTarget { selector: collapse_whitespace(selector), all: true },
+            None => ExtendTarget { selector: collapse_whitespace(target), all: false },
+        })
+        .collect()
+}
+
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -1,4 +1,4 @@
-use hxo_parser_less::compile;
+use hxo_parser_less::{LessParserOptions, compile, compile_with_options};
 
 #[test]
 fn test_less_basic() {
@@ -39,3 +39,217 @@ fn test_parse_nested_less() {
     assert!(css.contains(".parent .child {"));
     assert!(css.contains("color: blue;"));
 }
+
+#[test]
+fn test_tailwind_directives_pass_through() {
+    let less = "@gap: 4px;\n@import \"base.css\";\n@layer components {\n  .btn { @apply px-4 hover:bg-blue-500; color: theme('colors.blue.500'); margin: @gap; }\n}\n@screen md { .a { color: red; } }";
+    let css = compile(less).unwrap();
+    assert!(css.contains("@import \"base.css\";"));
+    assert!(css.contains("@layer components {\n  .btn {\n    @apply px-4 hover:bg-blue-500;\n"), "{}", css);
+    assert!(css.contains("color: theme('colors.blue.500');"));
+    assert!(css.contains("margin: 4px;"));
+    assert!(css.contains("@screen md {\n  .a {\n    color: red;\n  }\n}"));
+}
+
+#[test]
+fn test_parametric_mixins_with_guards() {
+    let less = r#"
+.bordered(@width: 2px; @style: solid) {
+  border: @width @style black;
+}
+.text(@color) when (lightness(@color) >= 50%) {
+  color: black;
+}
+.text(@color) when (lightness(@color) < 50%) {
+  color: white;
+}
+.text(@color) {
+  background: @color;
+}
+.size(small) { padding: 2px; }
+.size(large) { padding: 8px; }
+.fallback(@a) when (@a > 10) { width: @a; }
+.fallback(@a) when (default()) { width: auto; }
+.shadow(@rest...) { box-shadow: @arguments; }
+.reset { margin: 0; }
+.card {
+  .bordered(4px);
+  .text(#222);
+  .size(large);
+  .fallback(3);
+  .shadow(1px; 2px; 3px);
+  .reset() !important;
+}
+.panel { .bordered(@style: dashed); .text(#eee); }
+"#;
+    let css = compile(less).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".card {\n  border: 4px solid black;\n  color: white;\n  background: #222;\n  padding: 8px;\n  width: auto;\n  box-shadow: 1px 2px 3px;\n  margin: 0 !important;\n}"), "{}", css);
+    assert!(css.contains(".panel {\n  border: 2px dashed black;\n  color: black;\n  background: #eee;\n}"), "{}", css);
+    assert!(!css.contains(".bordered"), "{}", css);
+    assert!(css.contains(".reset {\n  margin: 0;\n}"), "{}", css);
+
+    let err = compile(".m(@a) { width: @a; }\n.x { .m(1; 2); }").unwrap_err();
+    assert!(err.to_string().contains("No matching definition"), "{}", err);
+}
+
+#[test]
+fn test_namespaces_and_recursive_mixins() {
+    let less = r#"
+#theme {
+  .button() { color: red; }
+}
+.loop(@i) when (@i > 0) {
+  .col-@{i} { width: (@i * 25%); }
+  .loop(@i - 1);
+}
+.grid { .loop(2); }
+.b { #theme > .button(); }
+.c { #theme.button(); }
+"#;
+    let css = compile(less).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".grid .col-2 {\n  width: 50%;\n}"), "{}", css);
+    assert!(css.contains(".grid .col-1 {\n  width: 25%;\n}"), "{}", css);
+    assert!(css.contains(".b {\n  color: red;\n}"), "{}", css);
+    assert!(css.contains(".c {\n  color: red;\n}"), "{}", css);
+}
+
+#[test]
+fn test_extend() {
+    let less = r#"
+.btn { padding: 4px; }
+.btn:hover { color: red; }
+.primary:extend(.btn) { background: blue; }
+.link {
+  &:extend(.btn all);
+  text-decoration: none;
+}
+"#;
+    let css = compile(less).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".btn, .primary, .link {\n  padding: 4px;\n}"), "{}", css);
+    assert!(css.contains(".btn:hover, .link:hover {"), "{}", css);
+    assert!(css.contains(".primary {\n  background: blue;\n}"), "{}", css);
+}
+
+#[test]
+fn test_color_functions_and_arithmetic() {
+    let less = r#"
+@base: #336699;
+@gutter: 10px;
+.a {
+  color: darken(@base, 10%);
+  background: fade(@base, 50%);
+  border-color: mix(#ff0000, #0000ff, 50%);
+  outline-color: (@base + #111);
+  width: (@gutter * 2 + 1em);
+  height: @gutter + 5;
+  margin: -@gutter (@gutter / 2);
+  font: 12px/1.5 sans-serif;
+  left: calc(100% - @gutter);
+  top: percentage(0.25);
+  right: round(3.14159, 2);
+}
+"#;
+    let css = compile(less).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains("color: #264c73;"), "{}", css);
+    assert!(css.contains("background: rgba(51, 102, 153, 0.5);"), "{}", css);
+    assert!(css.contains("border-color: #800080;"), "{}", css);
+    assert!(css.contains("outline-color: #4477aa;"), "{}", css);
+    assert!(css.contains("width: 21px;"), "{}", css);
+    assert!(css.contains("height: 15px;"), "{}", css);
+    assert!(css.contains("margin: -10px 5px;"), "{}", css);
+    assert!(css.contains("font: 12px/1.5 sans-serif;"), "{}", css);
+    assert!(css.contains("left: calc(100% - 10px);"), "{}", css);
+    assert!(css.contains("top: 25%;"), "{}", css);
+    assert!(css.contains("right: 3.14;"), "{}", css);
+}
+
+#[test]
+fn test_interpolation_and_escaping() {
+    let less = r#"
+@name: banner;
+@prop: color;
+@images: "../img";
+@phone: ~"(max-width: 768px)";
+@var: name;
+.@{name}-title {
+  @{prop}: red;
+  background-@{prop}: blue;
+  background-image: url("@{images}/bg.png");
+  filter: ~"ms:alwaysHasItsOwnSyntax.For.Stuff()";
+  content: "@{name}";
+  label: @@var;
+  @media @phone { display: none; }
+}
+"#;
+    let css = compile(less).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".banner-title {\n  color: red;\n  background-color: blue;\n"), "{}", css);
+    assert!(css.contains("background-image: url(\"../img/bg.png\");"), "{}", css);
+    assert!(css.contains("filter: ms:alwaysHasItsOwnSyntax.For.Stuff();"), "{}", css);
+    assert!(css.contains("content: \"banner\";"), "{}", css);
+    assert!(css.contains("label: banner;"), "{}", css);
+    assert!(css.contains("@media (max-width: 768px) {\n  .banner-title {\n    display: none;\n  }\n}"), "{}", css);
+}
+
+#[test]
+fn test_lazy_variables_and_scope() {
+    let less = r#"
+.a {
+  width: @size;
+  @size: @base * 2;
+}
+@base: 5px;
+.b {
+  @base: 1px;
+  width: @size;
+  @size: @base;
+}
+"#;
+    let css = compile(less).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".a {\n  width: 10px;\n}"), "{}", css);
+    assert!(css.contains(".b {\n  width: 1px;\n}"), "{}", css);
+
+    let err = compile("@a: @a;\n.x { width: @a; }").unwrap_err();
+    assert!(err.to_string().contains("Recursive variable"), "{}", err);
+    let err = compile(".x { width: @missing; }").unwrap_err();
+    assert!(err.to_string().contains("@missing"), "{}", err);
+}
+
+#[test]
+fn test_imports_resolved_through_load_paths() {
+    let dir = std::env::temp_dir().join(format!("hxo-less-imports-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("theme")).unwrap();
+    std::fs::write(dir.join("theme/variables.less"), "@primary: #0044cc;\n@radius: 4px;\n").unwrap();
+    std::fs::write(
+        dir.join("theme/mixins.less"),
+        "@import \"variables\";\n.rounded(@r: @radius) { border-radius: @r; }\n.unused { color: red; }\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("broken.less"), ".broken() { width: @nope; }\n").unwrap();
+
+    let options = LessParserOptions { load_paths: vec![dir.clone()], ..LessParserOptions::default() };
+    let less = r#"
+@import (reference) "theme/mixins";
+@import "theme/variables";
+@import "theme/variables";
+@import (optional) "not-there";
+@import (css) url("print.css") print;
+.card { color: @primary; .rounded(); }
+"#;
+    let css = compile_with_options(less, &options).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains("@import url(\"print.css\") print;"), "{}", css);
+    assert!(css.contains(".card {\n  color: #0044cc;\n  border-radius: 4px;\n}"), "{}", css);
+    assert!(!css.contains(".unused"), "{}", css);
+
+    let err = compile_with_options("@import \"missing\";", &options).unwrap_err();
+    assert!(err.to_string().contains("missing"), "{}", err);
+    let err = compile_with_options("@import \"broken\";\n.a { .broken(); }", &options).unwrap_err();
+    assert!(err.to_string().contains("broken.less"), "{}", err);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_compressed_output() {
+    let options = LessParserOptions { is_compressed: true, ..LessParserOptions::default() };
+    let css = compile_with_options(".a { .b { margin: 0 auto; font-family: a, b; } }", &options).unwrap();
+    assert_eq!(css, ".a .b{margin:0 auto;font-family:a,b;}");
+}
@@ -0,0 +1,668 @@
+use crate::inline::{Link, link_destination, link_title, normalize_label, unescape};
+use std::collections::HashMap;
+
+/// Byte range of one source line, with container prefixes (`>`, list indentation) already stripped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Line {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Block {
+    pub kind: BlockKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug)]
+pub(crate) enum BlockKind {
+    Heading {
+        level: usize,
+        content: Vec<Line>,
+        id: Option<String>,
+    },
+    Paragraph(Vec<Line>),
+    ThematicBreak,
+    Code {
+        info: String,
+        lines: Vec<Line>,
+    },
+    Quote(Vec<Block>),
+    List {
+        ordered: bool,
+        start: u64,
+        tight: bool,
+        items: Vec<ListItem>,
+    },
+    Table {
+        aligns: Vec<Option<&'static str>>,
+        head: Vec<Line>,
+        rows: Vec<Vec<Line>>,
+    },
+    /// Raw HTML, handed to the template parser as is.
+    Html(Vec<Line>),
+    /// An element whose start and end tags sit on their own lines around Markdown content.
+    Container {
+        open: Line,
+        children: Vec<Block>,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct ListItem {
+    /// Checkbox state of a GFM task list item.
+    pub task: Option<bool>,
+    pub children: Vec<Block>,
+    pub start: usize,
+    pub end: usize,
+}
+
+struct Marker {
+    ordered: bool,
+    /// Bullet character, or the `.`/`)` delimiter of an ordered marker.
+    ch: char,
+    number: u64,
+    width: usize,
+}
+
+/// Tags whose HTML blocks may interrupt a paragraph.
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+    "script",
+    "style",
+    "textarea",
+];
+
+/// Tags whose HTML blocks run to their end tag instead of the next blank line.
+const RAW_TAGS: &[&str] = &["pre", "script", "style", "textarea"];
+
+pub(crate) struct BlockParser<'a> {
+    src: &'a str,
+    pub refs: HashMap<String, Link>,
+}
+
+impl<'a> BlockParser<'a> {
+    pub fn new(src: &'a str) -> Self {
+        Self { src, refs: HashMap::new() }
+    }
+
+    pub fn parse(&mut self) -> Vec<Block> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for line in self.src.split_inclusive('\n') {
+            let end = start + line.trim_end_matches(['\n', '\r']).len();
+            lines.push(Line { start, end });
+            start += line.len();
+        }
+        self.blocks(&lines).0
+    }
+
+    fn text(&self, line: Line) -> &'a str {
+        &self.src[line.start..line.end]
+    }
+
+    fn is_blank(&self, line: Line) -> bool {
+        self.text(line).trim().is_empty()
+    }
+
+    /// Width of the leading whitespace in columns, and the line without it.
+    fn indentation(&self, line: Line) -> (usize, Line) {
+        let mut columns = 0;
+        let mut start = line.start;
+        for c in self.text(line).chars() {
+            match c {
+                ' ' => columns += 1,
+                '\t' => columns += 4 - columns % 4,
+                _ => break,
+            }
+            start += 1;
+        }
+        (columns, Line { start, end: line.end })
+    }
+
+    /// Removes up to `columns` columns of leading whitespace.
+    fn strip_columns(&self, line: Line, columns: usize) -> Line {
+        let mut width = 0;
+        let mut start = line.start;
+        for c in self.text(line).chars() {
+            if width >= columns {
+                break;
+            }
+            match c {
+                ' ' => width += 1,
+                '\t' => width += 4 - width % 4,
+                _ => break,
+            }
+            start += 1;
+        }
+        Line { start, end: line.end }
+    }
+
+    /// Parses the blocks of a container, also reporting whether blank lines separate any of them.
+    fn blocks(&mut self, lines: &[Line]) -> (Vec<Block>, bool) {
+        let mut blocks = Vec::new();
+        let mut separated = false;
+        let mut blank = false;
+        let mut i = 0;
+        while i < lines.len() {
+            if self.is_blank(lines[i]) {
+                blank = !blocks.is_empty();
+                i += 1;
+                continue;
+            }
+            separated |= blank;
+            blank = false;
+            let (block, next) = self.block(lines, i);
+            blocks.extend(block);
+            i = next;
+        }
+        (blocks, separated)
+    }
+
+    fn block(&mut self, lines: &[Line], i: usize) -> (Option<Block>, usize) {
+        let (indent, body) = self.indentation(lines[i]);
+        let rest = self.text(body);
+        if indent >= 4 {
+            return self.indented_code(lines, i);
+        }
+        if let Some((level, content, id)) = self.atx_heading(body) {
+            let kind = BlockKind::Heading { level, content: vec![content], id };
+            return (Some(Block { kind, start: body.start, end: body.end }), i + 1);
+        }
+        if let Some((ch, len, info)) = fence(rest) {
+            return self.fenced_code(lines, i, indent, ch, len, info);
+        }
+        if rest.starts_with('>') {
+            return self.quote(lines, i);
+        }
+        if is_thematic_break(rest) {
+            return (Some(Block { kind: BlockKind::ThematicBreak, start: body.start, end: body.end }), i + 1);
+        }
+        if let Some(marker) = list_marker(rest) {
+            return self.list(lines, i, marker);
+        }
+        if let Some(tag) = html_start(rest) {
+            return self.html(lines, i, tag);
+        }
+        if let Some(table) = self.table(lines, i) {
+            return table;
+        }
+        self.paragraph(lines, i)
+    }
+
+    /// Whether a line starts a block that interrupts a paragraph.
+    fn interrupts(&self, line: Line) -> bool {
+        let (indent, body) = self.indentation(line);
+        let rest = self.text(body);
+        if indent >= 4 {
+            return false;
+        }
+        self.atx_heading(body).is_some()
+            || fence(rest).is_some()
+            || rest.starts_with('>')
+            || is_thematic_break(rest)
+            || list_marker(rest).is_some_and(|marker| {
+                let content = &rest[marker.width..];
+                (!marker.ordered || marker.number == 1) && !content.trim().is_empty()
+            })
+            || html_start(rest).is_some_and(|tag| tag == "!--" || BLOCK_TAGS.contains(&tag.to_lowercase().as_str()))
+    }
+
+    fn atx_heading(&self, body: Line) -> Option<(usize, Line, Option<String>)> {
+        let rest = self.text(body);
+        let level = rest.chars().take_while(|&c| c == '#').count();
+        if level == 0 || level > 6 || !rest[level..].chars().next().is_none_or(|c| c == ' ' || c == '\t') {
+            return None;
+        }
+        let content = rest[level..].trim();
+        // A closing sequence of `#`s must be preceded by whitespace
+        let stripped = content.trim_end_matches('#');
+        let mut content = if stripped.is_empty() || stripped.ends_with([' ', '\t']) { stripped.trim_end() } else { content };
+        let mut id = None;
+        if let Some(open) = content.rfind("{#") {
+            let custom = &content[open + 2..];
+            if let Some(custom) =
+                custom.strip_suffix('}').filter(|custom| !custom.is_empty() && !custom.contains(char::is_whitespace))
+            {
+                id = Some(custom.to_string());
+                content = content[..open].trim_end();
+            }
+        }
+        let start =
+            if content.is_empty() { body.end } else { body.start + (content.as_ptr() as usize - rest.as_ptr() as usize) };
+        Some((level, Line { start, end: start + content.len() }, id))
+    }
+
+    fn indented_code(&mut self, lines: &[Line], i: usize) -> (Option<Block>, usize) {
+        let mut end = i + 1;
+        let mut j = i + 1;
+        while j < lines.len() {
+            if self.is_blank(lines[j]) {
+                j += 1;
+            }
+            else if self.indentation(lines[j]).0 >= 4 {
+                j += 1;
+                end = j;
+            }
+            else {
+                break;
+            }
+        }
+        let code = lines[i..end].iter().map(|&line| self.strip_columns(line, 4)).collect();
+        let kind = BlockKind::Code { info: String::new(), lines: code };
+        (Some(Block { kind, start: lines[i].start, end: lines[end - 1].end }), end)
+    }
+
+    fn fenced_code(
+        &mut self,
+        lines: &[Line],
+        i: usize,
+        indent: usize,
+        ch: char,
+        len: usize,
+        info: &str,
+    ) -> (Option<Block>, usize) {
+        let mut code = Vec::new();
+        let mut j = i + 1;
+        let mut end = lines[i].end;
+        while j < lines.len() {
+            let (columns, body) = self.indentation(lines[j]);
+            let rest = self.text(body);
+            j += 1;
+            if columns < 4
+                && rest.chars().take_while(|&c| c == ch).count() >= len
+                && rest.trim_start_matches(ch).trim().is_empty()
+            {
+                end = lines[j - 1].end;
+                break;
+            }
+            code.push(self.strip_columns(lines[j - 1], indent));
+            end = lines[j - 1].end;
+        }
+        let kind = BlockKind::Code { info: unescape(info.split_whitespace().next().unwrap_or("")), lines: code };
+        (Some(Block { kind, start: lines[i].start, end }), j)
+    }
+
+    fn quote(&mut self, lines: &[Line], i: usize) -> (Option<Block>, usize) {
+        let mut inner: Vec<Line> = Vec::new();
+        let mut j = i;
+        while j < lines.len() {
+            let (indent, body) = self.indentation(lines[j]);
+            if indent < 4 && self.text(body).starts_with('>') {
+                let mut start = body.start + 1;
+                if self.src[start..body.end].starts_with([' ', '\t']) {
+                    start += 1;
+                }
+                inner.push(Line { start, end: body.end });
+            }
+            // Lazy continuation of a paragraph inside the quote
+            else if !self.is_blank(lines[j])
+                && inner.last().is_some_and(|&last| !self.is_blank(last))
+                && !self.interrupts(lines[j])
+            {
+                inner.push(body);
+            }
+            else {
+                break;
+            }
+            j += 1;
+        }
+        let (children, _) = self.blocks(&inner);
+        (Some(Block { kind: BlockKind::Quote(children), start: lines[i].start, end: lines[j - 1].end }), j)
+    }
+
+    fn list(&mut self, lines: &[Line], i: usize, first: Marker) -> (Option<Block>, usize) {
+        let mut items = Vec::new();
+        let mut tight = true;
+        let mut j = i;
+        let mut next;
+        loop {
+            let (indent, body) = self.indentation(lines[j]);
+            let after = body.start + list_marker(self.text(body)).map_or(0, |marker| marker.width);
+            let (spaces, content) = self.indentation(Line { start: after, end: body.end });
+            let (content_indent, mut content) = if self.is_blank(content) {
+                (indent + after - body.start + 1, Line { start: body.end, end: body.end })
+            }
+            else if spaces > 4 {
+                // The content is an indented code block; only one space belongs to the marker
+                (indent + after - body.start + 1, self.strip_columns(Line { start: after, end: body.end }, 1))
+            }
+            else {
+                (indent + after - body.start + spaces, content)
+            };
+
+            let mut task = None;
+            let text = self.text(content);
+            if text.len() >= 3
+                && matches!(&text[..3], "[ ]" | "[x]" | "[X]")
+                && text[3..].chars().next().is_none_or(|c| c == ' ' || c == '\t')
+            {
+                task = Some(&text[1..2] != " ");
+                content.start += 3;
+            }
+
+            let mut item = vec![content];
+            let mut k = j + 1;
+            while k < lines.len() {
+                let line = lines[k];
+                if self.is_blank(line) {
+                    // A list item can begin with at most one blank line
+                    if item.len() == 1 && self.is_blank(item[0]) {
+                        break;
+                    }
+                    item.push(Line { start: line.end, end: line.end });
+                }
+                else if self.indentation(line).0 >= content_indent {
+                    item.push(self.strip_columns(line, content_indent));
+                }
+                else if item.last().is_some_and(|&last| !self.is_blank(last))
+                    && !self.interrupts(line)
+                    && list_marker(self.text(self.indentation(line).1)).is_none()
+                {
+                    item.push(self.indentation(line).1);
+                }
+                else {
+                    break;
+                }
+                k += 1;
+            }
+            // Trailing blank lines belong to whatever follows the item
+            next = k;
+            while next > j + 1 && self.is_blank(lines[next - 1]) {
+                next -= 1;
+                item.pop();
+            }
+
+            let (children, separated) = self.blocks(&item);
+            tight &= !separated;
+            items.push(ListItem { task, children, start: lines[j].start, end: lines[next - 1].end });
+
+            let continues = k < lines.len() && {
+                let (indent, body) = self.indentation(lines[k]);
+                let rest = self.text(body);
+                indent < 4
+                    && !is_thematic_break(rest)
+                    && list_marker(rest).is_some_and(|marker| marker.ordered == first.ordered && marker.ch == first.ch)
+            };
+            if !continues {
+                break;
+            }
+            tight &= next == k;
+            j = k;
+        }
+        let end = items.last().map_or(lines[i].end, |item| item.end);
+        let kind = BlockKind::List { ordered: first.ordered, start: first.number, tight, items };
+        (Some(Block { kind, start: lines[i].start, end }), next)
+    }
+
+    fn html(&mut self, lines: &[Line], i: usize, tag: &str) -> (Option<Block>, usize) {
+        let body = self.indentation(lines[i]).1;
+        let rest = self.text(body);
+        let raw = |end: usize| {
+            let mut html = vec![body];
+            html.extend_from_slice(&lines[i + 1..end]);
+            (Some(Block { kind: BlockKind::Html(html), start: body.start, end: lines[end - 1].end }), end)
+        };
+
+        if tag == "!--" {
+            let j = (i..lines.len()).find(|&j| self.text(lines[j]).contains("-->")).map_or(lines.len(), |j| j + 1);
+            return raw(j);
+        }
+        let lower = tag.to_lowercase();
+        if RAW_TAGS.contains(&lower.as_str()) {
+            let close = format!("</{}>", lower);
+            let j =
+                (i..lines.len()).find(|&j| self.text(lines[j]).to_lowercase().contains(&close)).map_or(lines.len(), |j| j + 1);
+            return raw(j);
+        }
+
+        // A start tag alone on its line whose element spans blank lines wraps Markdown content
+        let trimmed = rest.trim_end();
+        let close = format!("</{}>", tag);
+        if trimmed.ends_with('>')
+            && !trimmed.ends_with("/>")
+            && !trimmed.contains(&close)
+            && !hxo_types::is_void_element(&lower)
+        {
+            let mut depth = 1;
+            let mut j = i + 1;
+            while j < lines.len() {
+                let text = self.text(lines[j]).trim();
+                if text.starts_with(&close) {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                else if html_start(text) == Some(tag) && !text.ends_with("/>") && !text.contains(&close) {
+                    depth += 1;
+                }
+                j += 1;
+            }
+            if j < lines.len() && lines[i + 1..j].iter().any(|&line| self.is_blank(line)) {
+                let (children, _) = self.blocks(&lines[i + 1..j]);
+                let kind = BlockKind::Container { open: Line { start: body.start, end: body.start + trimmed.len() }, children };
+                return (Some(Block { kind, start: body.start, end: lines[j].end }), j + 1);
+            }
+        }
+
+        let j = (i + 1..lines.len()).find(|&j| self.is_blank(lines[j])).unwrap_or(lines.len());
+        raw(j)
+    }
+
+    fn table(&mut self, lines: &[Line], i: usize) -> Option<(Option<Block>, usize)> {
+        let head = self.indentation(lines[i]).1;
+        let (indent, delimiter) = self.indentation(*lines.get(i + 1)?);
+        if indent >= 4 || !self.text(head).contains('|') {
+            return None;
+        }
+        let aligns = self
+            .cells(delimiter)
+            .into_iter()
+            .map(|cell| {
+                let text = self.text(cell);
+                let dashes = text.trim_start_matches(':').trim_end_matches(':');
+                if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+                    return None;
+                }
+                Some(match (text.starts_with(':'), text.ends_with(':')) {
+                    (true, true) => Some("center"),
+                    (true, false) => Some("left"),
+                    (false, true) => Some("right"),
+                    (false, false) => None,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let head = self.cells(head);
+        if head.len() != aligns.len() {
+            return None;
+        }
+
+        let mut rows = Vec::new();
+        let mut j = i + 2;
+        while j < lines.len() && !self.is_blank(lines[j]) && !self.interrupts(lines[j]) {
+            rows.push(self.cells(self.indentation(lines[j]).1));
+            j += 1;
+        }
+        let kind = BlockKind::Table { aligns, head, rows };
+        Some((Some(Block { kind, start: lines[i].start, end: lines[j - 1].end }), j))
+    }
+
+    /// Splits a table row on unescaped pipes, dropping the optional outer ones.
+    fn cells(&self, line: Line) -> Vec<Line> {
+        let text = self.text(line);
+        let mut bounds = vec![line.start];
+        let mut escaped = false;
+        for (index, c) in text.char_indices() {
+            if c == '|' && !escaped {
+                bounds.push(line.start + index);
+            }
+            escaped = c == '\\' && !escaped;
+        }
+        bounds.push(line.end);
+
+        let mut cells: Vec<Line> = bounds
+            .windows(2)
+            .enumerate()
+            .map(|(index, pair)| {
+                let start = if index == 0 { pair[0] } else { pair[0] + 1 };
+                let cell = &self.src[start..pair[1]];
+                let trimmed = cell.trim_start();
+                let start = start + cell.len() - trimmed.len();
+                Line { start, end: start + trimmed.trim_end().len() }
+            })
+            .collect();
+        if text.trim_start().starts_with('|') && cells.len() > 1 {
+            cells.remove(0);
+        }
+        if text.trim_end().ends_with('|') && !text.trim_end().ends_with("\\|") && cells.len() > 1 {
+            cells.pop();
+        }
+        cells
+    }
+
+    fn paragraph(&mut self, lines: &[Line], i: usize) -> (Option<Block>, usize) {
+        let mut content = vec![self.indentation(lines[i]).1];
+        let mut j = i + 1;
+        while j < lines.len() && !self.is_blank(lines[j]) {
+            let (indent, body) = self.indentation(lines[j]);
+            let rest = self.text(body).trim_end();
+            if indent < 4 && !rest.is_empty() && (rest.chars().all(|c| c == '=') || rest.chars().all(|c| c == '-')) {
+                let level = if rest.starts_with('=') { 1 } else { 2 };
+                let kind = BlockKind::Heading { level, content: self.trim_content(content), id: None };
+                return (Some(Block { kind, start: lines[i].start, end: lines[j].end }), j + 1);
+            }
+            if self.interrupts(lines[j]) {
+                break;
+            }
+            content.push(body);
+            j += 1;
+        }
+
+        while let Some(&first) = content.first() {
+            let Some((label, link, len)) = self.definition(first)
+            else {
+                break;
+            };
+            if first.start + len < first.end && !self.src[first.start + len..first.end].trim().is_empty() {
+                break;
+            }
+            self.refs.entry(label).or_insert(link);
+            content.remove(0);
+        }
+        let content = self.trim_content(content);
+        if content.is_empty() {
+            return (None, j);
+        }
+        (Some(Block { kind: BlockKind::Paragraph(content), start: lines[i].start, end: lines[j - 1].end }), j)
+    }
+
+    fn trim_content(&self, mut content: Vec<Line>) -> Vec<Line> {
+        if let Some(last) = content.last_mut() {
+            last.end = last.start + self.text(*last).trim_end().len();
+        }
+        content
+    }
+
+    /// Parses a link reference definition such as `[docs]: https://hxo.dev "Docs"`.
+    fn definition(&self, line: Line) -> Option<(String, Link, usize)> {
+        let text = self.text(line);
+        let rest = text.strip_prefix('[')?;
+        let close = rest.find(']')?;
+        let label = normalize_label(&rest[..close]);
+        if label.is_empty() || rest[..close].contains('[') {
+            return None;
+        }
+        let mut pos = 1 + close + 1;
+        pos += text[pos..].strip_prefix(':').map(|_| 1)?;
+        pos += text[pos..].len() - text[pos..].trim_start().len();
+        let (href, len) = link_destination(&text[pos..])?;
+        pos += len;
+        let spaced = text[pos..].starts_with([' ', '\t']);
+        let after = text[pos..].trim_start();
+        let title = if spaced && !after.is_empty() {
+            let (title, len) = link_title(after)?;
+            pos = text.len() - after.len() + len;
+            Some(title)
+        }
+        else {
+            None
+        };
+        Some((label, Link { href, title }, pos))
+    }
+}
+
+fn fence(rest: &str) -> Option<(char, usize, &str)> {
+    let ch = rest.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = rest.chars().take_while(|&c| c == ch).count();
+    let info = rest[len..].trim();
+    (len >= 3 && !(ch == '`' && info.contains('`'))).then_some((ch, len, info))
+}
+
+fn is_thematic_break(rest: &str) -> bool {
+    let Some(ch) = rest.chars().next().filter(|&c| c == '*' || c == '-' || c == '_')
+    else {
+        return false;
+    };
+    rest.chars().filter(|&c| c == ch).count() >= 3 && rest.chars().all(|c| c == ch || c == ' ' || c == '\t')
+}
+
+fn list_marker(rest: &str) -> Option<Marker> {
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+    let (ordered, ch, number, width) = if digits == 0 {
+        let ch = rest.chars().next().filter(|&c| c == '-' || c == '*' || c == '+')?;
+        (false, ch, 1, 1)
+    }
+    else {
+        let ch = rest[digits..].chars().next().filter(|&c| c == '.' || c == ')')?;
+        if digits > 9 {
+            return None;
+        }
+        (true, ch, rest[..digits].parse().ok()?, digits + 1)
+    };
+    rest[width..].chars().next().is_none_or(|c| c == ' ' || c == '\t').then_some(Marker { ordered, ch, number, width })
+}
+
+/// The tag name of an HTML block start (`!--` for comments).
+fn html_start(rest: &str) -> Option<&str> {
+    if rest.starts_with("<!--") {
+        return Some("!--");
+    }
+    let name = rest.strip_prefix('<')?;
+    let len = name.chars().take_while(|&c| c.is_ascii_alphanumeric() || c == '-').count();
+    if len == 0 || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    name[len..].chars().next().is_none_or(|c| c.is_whitespace() || c == '>' || c == '/').then_some(&name[..len])
+}
@@ -0,0 +1,628 @@
+use crate::{Locator, block::Line, element};
+use hxo_ir::{ExpressionIR, TemplateNodeIR};
+use hxo_parser::ParseState;
+use hxo_types::{Cursor, Position, Span};
+use std::collections::HashMap;
+
+/// Target of a link reference definition.
+#[derive(Debug, Clone)]
+pub(crate) struct Link {
+    pub href: String,
+    pub title: Option<String>,
+}
+
+/// The inline content of a block, joined into one string that remembers where each line came from.
+pub(crate) struct Inline {
+    text: String,
+    /// `(text offset, source offset)` of the start of each line.
+    map: Vec<(usize, usize)>,
+    /// Table cells keep `\|` escaped even inside code spans.
+    cell: bool,
+}
+
+impl Inline {
+    pub fn new(src: &str, lines: &[Line]) -> Self {
+        let mut text = String::new();
+        let mut map = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            if index > 0 {
+                text.push('\n');
+            }
+            map.push((text.len(), line.start));
+            text.push_str(&src[line.start..line.end]);
+        }
+        Self { text, map, cell: false }
+    }
+
+    pub fn cell(src: &str, line: Line) -> Self {
+        Self { cell: true, ..Self::new(src, &[line]) }
+    }
+
+    fn source_offset(&self, offset: usize) -> usize {
+        let index = self.map.partition_point(|&(start, _)| start <= offset).saturating_sub(1);
+        self.map.get(index).map_or(0, |&(start, source)| source + offset - start)
+    }
+}
+
+struct Delimiter {
+    ch: char,
+    /// Characters left in the run.
+    count: usize,
+    /// Length of the whole run, for the rule of three.
+    len: usize,
+    start: usize,
+    can_open: bool,
+    can_close: bool,
+}
+
+enum Item {
+    Text(String, usize, usize),
+    Node(TemplateNodeIR),
+    Delimiter(Delimiter),
+    Bracket { image: bool, start: usize, active: bool },
+}
+
+pub(crate) struct InlineParser<'a> {
+    inline: &'a Inline,
+    text: &'a str,
+    locator: &'a Locator<'a>,
+    refs: &'a HashMap<String, Link>,
+    items: Vec<Item>,
+    buffer: String,
+    buffer_start: usize,
+    pos: usize,
+}
+
+impl<'a> InlineParser<'a> {
+    pub fn new(inline: &'a Inline, locator: &'a Locator<'a>, refs: &'a HashMap<String, Link>) -> Self {
+        Self { inline, text: &inline.text, locator, refs, items: Vec::new(), buffer: String::new(), buffer_start: 0, pos: 0 }
+    }
+
+    pub fn parse(mut self) -> Vec<TemplateNodeIR> {
+        while self.pos < self.text.len() {
+            let rest = &self.text[self.pos..];
+            let c = rest.chars().next().unwrap_or('\0');
+            match c {
+                '\\' => self.escape(),
+                '`' => self.code_span(),
+                '*' | '_' | '~' => self.delimiter(c),
+                '[' => self.bracket(false),
+                '!' if rest.starts_with("![") => self.bracket(true),
+                ']' => self.close_bracket(),
+                '<' => self.angle(),
+                '{' if rest.starts_with("{{") => self.interpolation(),
+                '&' => self.entity(),
+                '\n' => self.line_break(),
+                'h' | 'w' if self.at_word_start() => self.bare_autolink(),
+                _ => self.push(c),
+            }
+        }
+        self.flush();
+        let items = std::mem::take(&mut self.items);
+        self.emphasis(items)
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        self.locator.position(self.inline.source_offset(offset))
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span { start: self.position(start), end: self.position(end) }
+    }
+
+    fn push(&mut self, c: char) {
+        self.push_str(&c.to_string(), c.len_utf8());
+    }
+
+    /// Appends literal text standing for the next `len` bytes of the source.
+    fn push_str(&mut self, text: &str, len: usize) {
+        if self.buffer.is_empty() {
+            self.buffer_start = self.pos;
+        }
+        self.buffer.push_str(text);
+        self.pos += len;
+    }
+
+    fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            let text = std::mem::take(&mut self.buffer);
+            self.items.push(Item::Text(text, self.buffer_start, self.pos));
+        }
+    }
+
+    fn push_node(&mut self, node: TemplateNodeIR, len: usize) {
+        self.flush();
+        self.items.push(Item::Node(node));
+        self.pos += len;
+    }
+
+    fn escape(&mut self) {
+        match self.text[self.pos + 1..].chars().next() {
+            Some('\n') => {
+                let span = self.span(self.pos, self.pos + 2);
+                self.push_node(element("br", Vec::new(), Vec::new(), span), 2);
+            }
+            Some(c) if c.is_ascii_punctuation() => self.push_str(&c.to_string(), 2),
+            _ => self.push('\\'),
+        }
+    }
+
+    fn code_span(&mut self) {
+        let rest = &self.text[self.pos..];
+        let len = rest.chars().take_while(|&c| c == '`').count();
+        let mut search = len;
+        while let Some(found) = rest[search..].find('`') {
+            let open = search + found;
+            let run = rest[open..].chars().take_while(|&c| c == '`').count();
+            if run == len {
+                let mut code = rest[len..open].replace('\n', " ");
+                if self.inline.cell {
+                    code = code.replace("\\|", "|");
+                }
+                if code.len() > 1 && code.starts_with(' ') && code.ends_with(' ') && !code.trim().is_empty() {
+                    code = code[1..code.len() - 1].to_string();
+                }
+                let text_span = self.span(self.pos + len, self.pos + open);
+                let span = self.span(self.pos, self.pos + open + len);
+                let node = element("code", Vec::new(), vec![TemplateNodeIR::Text(code, text_span)], span);
+                self.push_node(node, open + len);
+                return;
+            }
+            search = open + run;
+        }
+        self.push_str(&"`".repeat(len), len);
+    }
+
+    fn delimiter(&mut self, ch: char) {
+        let len = self.text[self.pos..].chars().take_while(|&c| c == ch).count();
+        if ch == '~' && len > 2 {
+            self.push_str(&"~".repeat(len), len);
+            return;
+        }
+        let before = self.text[..self.pos].chars().next_back().unwrap_or(' ');
+        let after = self.text[self.pos + len..].chars().next().unwrap_or(' ');
+        let left = !after.is_whitespace() && (!is_punctuation(after) || before.is_whitespace() || is_punctuation(before));
+        let right = !before.is_whitespace() && (!is_punctuation(before) || after.is_whitespace() || is_punctuation(after));
+        let (can_open, can_close) = if ch == '_' {
+            (left && (!right || is_punctuation(before)), right && (!left || is_punctuation(after)))
+        }
+        else {
+            (left, right)
+        };
+        self.flush();
+        self.items.push(Item::Delimiter(Delimiter { ch, count: len, len, start: self.pos, can_open, can_close }));
+        self.pos += len;
+    }
+
+    fn bracket(&mut self, image: bool) {
+        self.flush();
+        self.items.push(Item::Bracket { image, start: self.pos, active: true });
+        self.pos += if image { 2 } else { 1 };
+    }
+
+    fn close_bracket(&mut self) {
+        self.flush();
+        let Some(index) = self.items.iter().rposition(|item| matches!(item, Item::Bracket { .. }))
+        else {
+            self.push(']');
+            return;
+        };
+        let Item::Bracket { image, start, active } = self.items[index]
+        else {
+            unreachable!()
+        };
+        let opener = if image { "![" } else { "[" };
+        let link = if active { self.link_target(start + opener.len()) } else { None };
+        let Some((link, end)) = link
+        else {
+            self.items[index] = Item::Text(opener.to_string(), start, start + opener.len());
+            self.push(']');
+            return;
+        };
+
+        let inner: Vec<Item> = self.items.drain(index + 1..).collect();
+        self.items.pop();
+        let children = self.emphasis(inner);
+        let span = self.span(start, end);
+        let mut attributes = Vec::new();
+        let node = if image {
+            attributes.push(crate::attribute("src", &link.href, span));
+            attributes.push(crate::attribute("alt", &crate::plain_text(&children), span));
+            if let Some(title) = &link.title {
+                attributes.push(crate::attribute("title", title, span));
+            }
+            element("img", attributes, Vec::new(), span)
+        }
+        else {
+            attributes.push(crate::attribute("href", &link.href, span));
+            if let Some(title) = &link.title {
+                attributes.push(crate::attribute("title", title, span));
+            }
+            // Links may not contain other links
+            for item in &mut self.items {
+                if let Item::Bracket { image: false, active, .. } = item {
+                    *active = false;
+                }
+            }
+            element("a", attributes, children, span)
+        };
+        self.items.push(Item::Node(node));
+        self.pos = end;
+    }
+
+    /// Resolves the target of a link whose text starts at `label_start` and ends at the current `]`.
+    fn link_target(&self, label_start: usize) -> Option<(Link, usize)> {
+        let after = self.pos + 1;
+        let rest = &self.text[after..];
+        if let Some(inner) = rest.strip_prefix('(') {
+            let mut len = 1 + whitespace(inner);
+            let (href, dest_len) = link_destination(&rest[len..]).unwrap_or_default();
+            len += dest_len;
+            let mut title = None;
+            let spaced = whitespace(&rest[len..]);
+            if let Some((text, title_len)) = link_title(&rest[len + spaced..]).filter(|_| spaced > 0) {
+                title = Some(text);
+                len += spaced + title_len;
+            }
+            len += whitespace(&rest[len..]);
+            if rest[len..].starts_with(')') {
+                return Some((Link { href, title }, after + len + 1));
+            }
+        }
+        if let Some((inner, close)) = rest.strip_prefix('[').and_then(|inner| Some((inner, inner.find(']')?))) {
+            let label = if close == 0 { &self.text[label_start..self.pos] } else { &inner[..close] };
+            return self.refs.get(&normalize_label(label)).map(|link| (link.clone(), after + close + 2));
+        }
+        self.refs.get(&normalize_label(&self.text[label_start..self.pos])).map(|link| (link.clone(), after))
+    }
+
+    fn angle(&mut self) {
+        let rest = &self.text[self.pos..];
+        if let Some(end) = rest.find('>') {
+            let target = &rest[1..end];
+            let scheme = target.split(':').next().unwrap_or("");
+            let is_uri = target.contains(':')
+                && (2..=32).contains(&scheme.len())
+                && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+                && !target.contains(|c: char| c.is_whitespace() || c == '<');
+            let is_email = !is_uri
+                && target.split_once('@').is_some_and(|(user, domain)| {
+                    !user.is_empty()
+                        && domain.contains('.')
+                        && !target.contains(|c: char| c.is_whitespace() || c == '<' || c == '\\')
+                });
+            if is_uri || is_email {
+                let href = if is_email { format!("mailto:{}", target) } else { target.to_string() };
+                let span = self.span(self.pos, self.pos + end + 1);
+                let text = TemplateNodeIR::Text(target.to_string(), self.span(self.pos + 1, self.pos + end));
+                let node = element("a", vec![crate::attribute("href", &href, span)], vec![text], span);
+                self.push_node(node, end + 1);
+                return;
+            }
+        }
+        if let Some((comment, end)) = rest.strip_prefix("<!--").and_then(|comment| Some((comment, comment.find("-->")?))) {
+            let span = self.span(self.pos, self.pos + end + 7);
+            self.push_node(TemplateNodeIR::Comment(comment[..end].to_string(), span), end + 7);
+            return;
+        }
+        if rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let mut state = ParseState::with_cursor(Cursor::with_sliced_source(rest, self.position(self.pos)));
+            if let Ok(node) = hxo_parser_template::parse_element(&mut state) {
+                let len = state.cursor.pos;
+                self.push_node(node, len);
+                return;
+            }
+        }
+        self.push('<');
+    }
+
+    fn interpolation(&mut self) {
+        let Some(end) = self.text[self.pos + 2..].find("}}")
+        else {
+            self.push_str("{{", 2);
+            return;
+        };
+        let code = self.text[self.pos + 2..self.pos + 2 + end].trim().to_string();
+        let ast = hxo_parser_expression::parse_expression(&code).ok();
+        let span = self.span(self.pos, self.pos + end + 4);
+        self.push_node(TemplateNodeIR::Interpolation(ExpressionIR { code, ast, span }), end + 4);
+    }
+
+    fn entity(&mut self) {
+        match decode_entity(&self.text[self.pos..]) {
+            Some((decoded, len)) => self.push_str(&decoded, len),
+            None => self.push('&'),
+        }
+    }
+
+    fn line_break(&mut self) {
+        let trimmed = self.buffer.trim_end_matches(' ').len();
+        let hard = self.buffer.len() - trimmed >= 2;
+        self.buffer.truncate(trimmed);
+        if hard {
+            let span = self.span(self.pos, self.pos + 1);
+            self.push_node(element("br", Vec::new(), Vec::new(), span), 1);
+        }
+        else {
+            self.push('\n');
+        }
+    }
+
+    fn at_word_start(&self) -> bool {
+        self.text[..self.pos].chars().next_back().is_none_or(|c| c.is_whitespace() || matches!(c, '*' | '_' | '~' | '('))
+    }
+
+    /// GFM extended autolinks: bare `https://`, `http://` and `www.` addresses.
+    fn bare_autolink(&mut self) {
+        let rest = &self.text[self.pos..];
+        let prefix = ["https://", "http://", "www."].into_iter().find(|prefix| rest.starts_with(prefix));
+        let Some(prefix) = prefix
+        else {
+            self.push(rest.chars().next().unwrap_or('\0'));
+            return;
+        };
+        let mut link = &rest[..rest.find(|c: char| c.is_whitespace() || c == '<').unwrap_or(rest.len())];
+        loop {
+            let trimmed = link.trim_end_matches(['?', '!', '.', ',', ':', '*', '_', '~', '\'', '"']);
+            let trimmed = if trimmed.ends_with(')') && trimmed.matches(')').count() > trimmed.matches('(').count() {
+                &trimmed[..trimmed.len() - 1]
+            }
+            else {
+                trimmed
+            };
+            if trimmed.len() == link.len() {
+                break;
+            }
+            link = trimmed;
+        }
+        if link.len() <= prefix.len() || !link[prefix.len()..].contains(|c: char| c.is_alphanumeric()) {
+            self.push_str(prefix, prefix.len());
+            return;
+        }
+        let href = if prefix == "www." { format!("http://{}", link) } else { link.to_string() };
+        let span = self.span(self.pos, self.pos + link.len());
+        let node =
+            element("a", vec![crate::attribute("href", &href, span)], vec![TemplateNodeIR::Text(link.to_string(), span)], span);
+        self.push_node(node, link.len());
+    }
+
+    /// Matches emphasis delimiters as described by the CommonMark "process emphasis" procedure.
+    fn emphasis(&self, mut items: Vec<Item>) -> Vec<TemplateNodeIR> {
+        let mut closer = 0;
+        while closer < items.len() {
+            let Item::Delimiter(Delimiter { ch, count, len, can_open, can_close: true, .. }) = items[closer]
+            else {
+                closer += 1;
+                continue;
+            };
+            let opener = (0..closer).rev().find(|&index| match &items[index] {
+                Item::Delimiter(opener) => {
+                    // Rule of three: a run that can both open and close needs lengths that don't sum to a multiple of 3
+                    let both = opener.can_close || can_open;
+                    let lengths = (opener.len + len) % 3 != 0 || (opener.len % 3 == 0 && len % 3 == 0);
+                    opener.ch == ch && opener.can_open && (ch != '~' || opener.count == count) && (!both || lengths)
+                }
+                _ => false,
+            });
+            let Some(opener) = opener
+            else {
+                if let Item::Delimiter(delimiter) = &mut items[closer] {
+                    delimiter.can_close = false;
+                }
+                closer += 1;
+                continue;
+            };
+
+            let Item::Delimiter(open) = &items[opener]
+            else {
+                unreachable!()
+            };
+            let used = if ch == '~' {
+                count
+            }
+            else if open.count >= 2 && count >= 2 {
+                2
+            }
+            else {
+                1
+            };
+            let tag = match (ch, used) {
+                ('~', _) => "del",
+                (_, 2) => "strong",
+                _ => "em",
+            };
+            let start = open.start + open.count - used;
+            let Item::Delimiter(close) = &items[closer]
+            else {
+                unreachable!()
+            };
+            let span = self.span(start, close.start + used);
+
+            let inner: Vec<Item> = items.drain(opener + 1..closer).collect();
+            let node = element(tag, Vec::new(), self.nodes(inner), span);
+            items.insert(opener + 1, Item::Node(node));
+            closer = opener + 2;
+            if let Item::Delimiter(open) = &mut items[opener] {
+                open.count -= used;
+            }
+            if let Item::Delimiter(close) = &mut items[closer] {
+                close.count -= used;
+                close.start += used;
+            }
+            if matches!(&items[closer], Item::Delimiter(close) if close.count == 0) {
+                items.remove(closer);
+            }
+            if matches!(&items[opener], Item::Delimiter(open) if open.count == 0) {
+                items.remove(opener);
+                closer -= 1;
+            }
+        }
+        self.nodes(items)
+    }
+
+    /// Turns items into nodes, leaving unmatched delimiters and brackets as text.
+    fn nodes(&self, items: Vec<Item>) -> Vec<TemplateNodeIR> {
+        let mut nodes = Vec::new();
+        let mut text: Option<(String, usize, usize)> = None;
+        for item in items {
+            let (content, start, end) = match item {
+                Item::Text(content, start, end) => (content, start, end),
+                Item::Delimiter(delimiter) => {
+                    (delimiter.ch.to_string().repeat(delimiter.count), delimiter.start, delimiter.start + delimiter.count)
+                }
+                Item::Bracket { image, start, .. } => {
+                    let opener = if image { "![" } else { "[" };
+                    (opener.to_string(), start, start + opener.len())
+                }
+                Item::Node(node) => {
+                    if let Some((content, start, end)) = text.take() {
+                        nodes.push(TemplateNodeIR::Text(content, self.span(start, end)));
+                    }
+                    nodes.push(node);
+                    continue;
+                }
+            };
+            match &mut text {
+                Some((buffer, _, buffer_end)) => {
+                    buffer.push_str(&content);
+                    *buffer_end = end;
+                }
+                None => text = Some((content, start, end)),
+            }
+        }
+        if let Some((content, start, end)) = text {
+            nodes.push(TemplateNodeIR::Text(content, self.span(start, end)));
+        }
+        nodes
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation() || (!c.is_alphanumeric() && !c.is_whitespace() && !c.is_control() && !c.is_ascii())
+}
+
+fn whitespace(text: &str) -> usize {
+    text.len() - text.trim_start().len()
+}
+
+/// Parses a link destination, either `<...>` or a run without spaces and with balanced parentheses.
+pub(crate) fn link_destination(text: &str) -> Option<(String, usize)> {
+    if let Some(inner) = text.strip_prefix('<') {
+        let end = inner.find(['>', '\n', '<'])?;
+        return inner[end..].starts_with('>').then(|| (unescape(&inner[..end]), end + 2));
+    }
+    let mut depth = 0;
+    let mut escaped = false;
+    let mut end = text.len();
+    for (index, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '(' => depth += 1,
+            ')' if depth == 0 => {
+                end = index;
+                break;
+            }
+            ')' => depth -= 1,
+            c if c.is_whitespace() || c.is_control() => {
+                end = index;
+                break;
+            }
+            _ => {}
+        }
+    }
+    (end > 0 && depth == 0).then(|| (unescape(&text[..end]), end))
+}
+
+/// Parses a link title in `"..."`, `'...'` or `(...)`.
+pub(crate) fn link_title(text: &str) -> Option<(String, usize)> {
+    let open = text.chars().next()?;
+    let close = match open {
+        '"' | '\'' => open,
+        '(' => ')',
+        _ => return None,
+    };
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        }
+        else if c == '\\' {
+            escaped = true;
+        }
+        else if c == close {
+            return Some((unescape(&text[1..index]), index + 1));
+        }
+    }
+    None
+}
+
+/// Case-folds a link label and collapses its whitespace.
+pub(crate) fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Resolves backslash escapes and character references.
+pub(crate) fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut index = 0;
+    while let Some(c) = text[index..].chars().next() {
+        let next = text[index + 1..].chars().next();
+        match (c, next) {
+            ('\\', Some(escaped)) if escaped.is_ascii_punctuation() => {
+                out.push(escaped);
+                index += 2;
+            }
+            ('&', _) if decode_entity(&text[index..]).is_some() => {
+                let (decoded, len) = decode_entity(&text[index..]).unwrap_or_default();
+                out.push_str(&decoded);
+                index += len;
+            }
+            _ => {
+                out.push(c);
+                index += c.len_utf8();
+            }
+        }
+    }
+    out
+}
+
+/// Decodes a character reference such as `&amp;`, `&#35;` or `&#x23;` at the start of `text`.
+fn decode_entity(text: &str) -> Option<(String, usize)> {
+    let end = text.find(';').filter(|&end| end <= 33)?;
+    let name = &text[1..end];
+    let decoded = if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) if (1..=6).contains(&hex.len()) => u32::from_str_radix(hex, 16).ok()?,
+            None if (1..=7).contains(&number.len()) => number.parse().ok()?,
+            _ => return None,
+        };
+        char::from_u32(code).filter(|&c| c != '\0').unwrap_or('\u{FFFD}').to_string()
+    }
+    else {
+        match name {
+            "amp" => "&",
+            "lt" => "<",
+            "gt" => ">",
+            "quot" => "\"",
+            "apos" => "'",
+            "nbsp" => "\u{A0}",
+            "copy" => "\u{A9}",
+            "reg" => "\u{AE}",
+            "trade" => "\u{2122}",
+            "hellip" => "\u{2026}",
+            "mdash" => "\u{2014}",
+            "ndash" => "\u{2013}",
+            "larr" => "\u{2190}",
+            "rarr" => "\u{2192}",
+            "times" => "\u{D7}",
+            _ => return None,
+        }
+        .to_string()
+    };
+    Some((decoded, end + 1))
+}
@@ -1,177 +1,253 @@
-use hxo_ir::TemplateNodeIR;
+use block::{Block, BlockKind, BlockParser, Line};
+use hxo_ir::{AttributeIR, ElementIR, TemplateNodeIR};
 use hxo_parser::{ParseState, TemplateParser};
-use hxo_types::Result;
+use hxo_types::{Cursor, Error, Position, Result, Span};
+use inline::{Inline, InlineParser, Link};
+use std::collections::HashMap;
+
+mod block;
+mod inline;
 
 pub struct MarkdownParser;
 
 impl TemplateParser for MarkdownParser {
     fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<Vec<TemplateNodeIR>> {
-        let mut impl_parser = MarkdownParserImpl::with_state(state);
-        let html = impl_parser.parse_to_html()?;
-        hxo_parser_template::parse(html.trim())
+        let start = state.cursor.position();
+        let source = &state.cursor.source[state.cursor.pos..];
+        let nodes = MarkdownParserImpl::new(source, start).parse()?;
+        while !state.cursor.is_eof() {
+            state.cursor.consume();
+        }
+        Ok(nodes)
     }
 }
 
-struct MarkdownParserImpl<'a, 'b> {
-    state: &'a mut ParseState<'b>,
+/// Parses CommonMark with the GFM extensions into template nodes.
+pub fn parse(source: &str) -> Result<Vec<TemplateNodeIR>> {
+    MarkdownParserImpl::new(source, Position { line: 1, column: 1, offset: 0 }).parse()
+}
+
+/// Turns heading text into an anchor id the way GitHub does: lowercase, punctuation dropped, spaces as `-`.
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Maps byte offsets of the Markdown source to positions in the component file.
+pub(crate) struct Locator<'a> {
+    source: &'a str,
+    start: Position,
+    line_starts: Vec<usize>,
 }
 
-impl<'a, 'b> MarkdownParserImpl<'a, 'b> {
-    pub fn with_state(state: &'a mut ParseState<'b>) -> Self {
-        Self { state }
+impl<'a> Locator<'a> {
+    fn new(source: &'a str, start: Position) -> Self {
+        let line_starts = std::iter::once(0).chain(source.match_indices('\n').map(|(index, _)| index + 1)).collect();
+        Self { source, start, line_starts }
     }
 
-    pub fn parse_to_html(&mut self) -> Result<String> {
-        let mut html = String::new();
-        while !self.state.cursor.is_eof() {
-            if self.state.cursor.peek() == '#' {
-                html.push_str(&self.parse_heading()?);
-            }
-            else if self.state.cursor.peek() == '-' || self.state.cursor.peek() == '*' {
-                if self.state.cursor.peek_n(1).is_whitespace() {
-                    html.push_str(&self.parse_list()?);
-                }
-                else {
-                    html.push_str(&self.parse_paragraph()?);
-                }
-            }
-            else if self.state.cursor.peek() == '\n' {
-                self.state.cursor.consume();
-            }
-            else {
-                html.push_str(&self.parse_paragraph()?);
-            }
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let width = self.source[self.line_starts[line]..offset].encode_utf16().count() as u32;
+        Position {
+            line: self.start.line + line as u32,
+            column: if line == 0 { self.start.column + width } else { 1 + width },
+            offset: self.start.offset + offset as u32,
         }
-        Ok(html)
     }
 
-    fn parse_heading(&mut self) -> Result<String> {
-        let mut level = 0;
-        while self.state.cursor.peek() == '#' && level < 6 {
-            self.state.cursor.consume();
-            level += 1;
-        }
-        self.state.cursor.skip_whitespace();
-        let content = self.parse_inline()?;
-        Ok(format!("<h{}>{}</h{}>\n", level, content, level))
+    fn span(&self, start: usize, end: usize) -> Span {
+        Span { start: self.position(start), end: self.position(end) }
     }
+}
 
-    fn parse_list(&mut self) -> Result<String> {
-        let mut html = String::from("<ul>\n");
-        while !self.state.cursor.is_eof() && (self.state.cursor.peek() == '-' || self.state.cursor.peek() == '*') {
-            self.state.cursor.consume(); // - or *
-            self.state.cursor.skip_whitespace();
-            let content = self.parse_inline()?;
-            html.push_str(&format!("  <li>{}</li>\n", content));
-            self.state.cursor.skip_whitespace();
-        }
-        html.push_str("</ul>\n");
-        Ok(html)
+struct MarkdownParserImpl<'a> {
+    source: &'a str,
+    locator: Locator<'a>,
+    refs: HashMap<String, Link>,
+    /// How often each heading slug was used, to keep anchors unique.
+    slugs: HashMap<String, usize>,
+}
+
+impl<'a> MarkdownParserImpl<'a> {
+    fn new(source: &'a str, start: Position) -> Self {
+        Self { source, locator: Locator::new(source, start), refs: HashMap::new(), slugs: HashMap::new() }
     }
 
-    fn parse_paragraph(&mut self) -> Result<String> {
-        let content = self.parse_inline()?;
-        if content.is_empty() { Ok(String::new()) } else { Ok(format!("<p>{}</p>\n", content)) }
+    fn parse(mut self) -> Result<Vec<TemplateNodeIR>> {
+        let mut parser = BlockParser::new(self.source);
+        let blocks = parser.parse();
+        self.refs = parser.refs;
+        self.render(&blocks, false)
     }
 
-    fn parse_inline(&mut self) -> Result<String> {
-        let mut inline = String::new();
-        while !self.state.cursor.is_eof() && self.state.cursor.peek() != '\n' {
-            let c = self.state.cursor.peek();
-            match c {
-                '*' => {
-                    if self.state.cursor.peek_str("**") {
-                        self.state.cursor.consume_n(2);
-                        let content = self.consume_until("**")?;
-                        self.state.cursor.consume_n(2);
-                        inline.push_str(&format!("<strong>{}</strong>", content));
-                    }
-                    else {
-                        self.state.cursor.consume();
-                        let content = self.consume_until("*")?;
-                        self.state.cursor.consume();
-                        inline.push_str(&format!("<em>{}</em>", content));
-                    }
+    fn inline(&self, lines: &[Line]) -> Vec<TemplateNodeIR> {
+        self.parse_inline(&Inline::new(self.source, lines))
+    }
+
+    fn parse_inline(&self, inline: &Inline) -> Vec<TemplateNodeIR> {
+        InlineParser::new(inline, &self.locator, &self.refs).parse()
+    }
+
+    /// Renders blocks; paragraphs of tight list items are not wrapped in `<p>`.
+    fn render(&mut self, blocks: &[Block], tight: bool) -> Result<Vec<TemplateNodeIR>> {
+        let mut nodes = Vec::new();
+        for block in blocks {
+            let span = self.locator.span(block.start, block.end);
+            match &block.kind {
+                BlockKind::Heading { level, content, id } => {
+                    let children = self.inline(content);
+                    let id = id.clone().unwrap_or_else(|| self.unique_slug(&plain_text(&children)));
+                    nodes.push(element(&format!("h{}", level), vec![attribute("id", &id, span)], children, span));
                 }
-                '_' => {
-                    if self.state.cursor.peek_str("__") {
-                        self.state.cursor.consume_n(2);
-                        let content = self.consume_until("__")?;
-                        self.state.cursor.consume_n(2);
-                        inline.push_str(&format!("<strong>{}</strong>", content));
+                BlockKind::Paragraph(content) if tight => nodes.extend(self.inline(content)),
+                BlockKind::Paragraph(content) => nodes.push(element("p", Vec::new(), self.inline(content), span)),
+                BlockKind::ThematicBreak => nodes.push(element("hr", Vec::new(), Vec::new(), span)),
+                BlockKind::Code { info, lines } => {
+                    let mut children = Vec::new();
+                    if let (Some(first), Some(last)) = (lines.first(), lines.last()) {
+                        let text: Vec<&str> = lines.iter().map(|line| &self.source[line.start..line.end]).collect();
+                        let text_span = self.locator.span(first.start, last.end);
+                        children.push(TemplateNodeIR::Text(format!("{}\n", text.join("\n")), text_span));
                     }
-                    else {
-                        self.state.cursor.consume();
-                        let content = self.consume_until("_")?;
-                        self.state.cursor.consume();
-                        inline.push_str(&format!("<em>{}</em>", content));
+                    let mut attributes = Vec::new();
+                    if !info.is_empty() {
+                        attributes.push(attribute("class", &format!("language-{}", info), span));
                     }
+                    let code = element("code", attributes, children, span);
+                    nodes.push(element("pre", Vec::new(), vec![code], span));
                 }
-                '[' => {
-                    self.state.cursor.consume();
-                    let text = self.consume_until("]")?;
-                    self.state.cursor.consume();
-                    if self.state.cursor.peek() == '(' {
-                        self.state.cursor.consume();
-                        let url = self.consume_until(")")?;
-                        self.state.cursor.consume();
-                        inline.push_str(&format!("<a href=\"{}\">{}</a>", url, text));
-                    }
-                    else {
-                        inline.push('[');
-                        inline.push_str(&text);
-                        inline.push(']');
-                    }
+                BlockKind::Quote(children) => {
+                    let children = self.render(children, false)?;
+                    nodes.push(element("blockquote", Vec::new(), children, span));
                 }
-                '!' => {
-                    if self.state.cursor.peek_n(1) == '[' {
-                        self.state.cursor.consume_n(2);
-                        let alt = self.consume_until("]")?;
-                        self.state.cursor.consume();
-                        if self.state.cursor.peek() == '(' {
-                            self.state.cursor.consume();
-                            let src = self.consume_until(")")?;
-                            self.state.cursor.consume();
-                            inline.push_str(&format!("<img src=\"{}\" alt=\"{}\">", src, alt));
-                        }
-                        else {
-                            inline.push_str("![");
-                            inline.push_str(&alt);
-                            inline.push(']');
+                BlockKind::List { ordered, start, tight, items } => {
+                    let mut attributes = Vec::new();
+                    if *ordered && *start != 1 {
+                        attributes.push(attribute("start", &start.to_string(), span));
+                    }
+                    let mut children = Vec::new();
+                    for item in items {
+                        let item_span = self.locator.span(item.start, item.end);
+                        let mut content = self.render(&item.children, *tight)?;
+                        let mut item_attributes = Vec::new();
+                        if let Some(checked) = item.task {
+                            item_attributes.push(attribute("class", "task-list-item", item_span));
+                            let mut checkbox = vec![attribute("type", "checkbox", item_span), flag("disabled", item_span)];
+                            if checked {
+                                checkbox.push(flag("checked", item_span));
+                            }
+                            let checkbox = element("input", checkbox, Vec::new(), item_span);
+                            match content.first_mut() {
+                                Some(TemplateNodeIR::Element(p)) if p.tag == "p" => p.children.insert(0, checkbox),
+                                _ => content.insert(0, checkbox),
+                            }
                         }
+                        children.push(element("li", item_attributes, content, item_span));
                     }
-                    else {
-                        inline.push(self.state.cursor.consume());
+                    nodes.push(element(if *ordered { "ol" } else { "ul" }, attributes, children, span));
+                }
+                BlockKind::Table { aligns, head, rows } => {
+                    let row = |this: &Self, tag: &str, cells: &[Line]| {
+                        let children = aligns
+                            .iter()
+                            .enumerate()
+                            .map(|(index, align)| {
+                                let (content, cell_span) = match cells.get(index) {
+                                    Some(&cell) => (
+                                        this.parse_inline(&Inline::cell(this.source, cell)),
+                                        this.locator.span(cell.start, cell.end),
+                                    ),
+                                    None => (Vec::new(), span),
+                                };
+                                let attributes =
+                                    align.map(|align| vec![attribute("align", align, cell_span)]).unwrap_or_default();
+                                element(tag, attributes, content, cell_span)
+                            })
+                            .collect();
+                        let start = cells.first().map_or(block.start, |cell| cell.start);
+                        let end = cells.last().map_or(block.end, |cell| cell.end);
+                        element("tr", Vec::new(), children, this.locator.span(start, end))
+                    };
+                    let mut children = vec![element("thead", Vec::new(), vec![row(self, "th", head)], span)];
+                    if !rows.is_empty() {
+                        let body = rows.iter().map(|cells| row(self, "td", cells)).collect();
+                        children.push(element("tbody", Vec::new(), body, span));
                     }
+                    nodes.push(element("table", Vec::new(), children, span));
                 }
-                '`' => {
-                    self.state.cursor.consume();
-                    let content = self.consume_until("`")?;
-                    self.state.cursor.consume();
-                    inline.push_str(&format!("<code>{}</code>", content));
+                BlockKind::Html(lines) => {
+                    let html: Vec<&str> = lines.iter().map(|line| &self.source[line.start..line.end]).collect();
+                    let html = html.join("\n");
+                    let mut state =
+                        ParseState::with_cursor(Cursor::with_sliced_source(&html, self.locator.position(block.start)));
+                    let parsed = hxo_parser_template::TemplateParser.parse(&mut state, "html")?;
+                    if !state.cursor.is_eof() {
+                        let message = "unexpected closing tag in markdown html block".to_string();
+                        return Err(Error::parse_error(message, state.cursor.span_at_current()));
+                    }
+                    nodes.extend(
+                        parsed
+                            .into_iter()
+                            .filter(|node| !matches!(node, TemplateNodeIR::Text(text, _) if text.trim().is_empty())),
+                    );
                 }
-                _ => {
-                    inline.push(self.state.cursor.consume());
+                BlockKind::Container { open, children } => {
+                    let tag = &self.source[open.start..open.end];
+                    let mut state = ParseState::with_cursor(Cursor::with_sliced_source(tag, self.locator.position(open.start)));
+                    let (tag, attributes, _) = hxo_parser_template::parse_start_tag(&mut state)?;
+                    let children = self.render(children, false)?;
+                    nodes.push(TemplateNodeIR::Element(ElementIR { tag, attributes, children, is_static: false, span }));
                 }
             }
         }
-        if !self.state.cursor.is_eof() {
-            self.state.cursor.consume(); // \n
-        }
-        Ok(inline.trim().to_string())
+        Ok(nodes)
     }
 
-    fn consume_until(&mut self, s: &str) -> Result<String> {
-        let start = self.state.cursor.pos;
-        while !self.state.cursor.is_eof() && !self.state.cursor.peek_str(s) {
-            self.state.cursor.consume();
-        }
-        Ok(self.state.cursor.current_str(start).to_string())
+    fn unique_slug(&mut self, text: &str) -> String {
+        let slug = slugify(text);
+        let count = self.slugs.entry(slug.clone()).or_insert(0);
+        *count += 1;
+        if *count == 1 { slug } else { format!("{}-{}", slug, *count - 1) }
     }
 }
 
-pub fn parse(source: &str) -> Result<String> {
-    let mut state = ParseState::new(source);
-    let mut parser = MarkdownParserImpl::with_state(&mut state);
-    parser.parse_to_html()
+pub(crate) fn element(tag: &str, attributes: Vec<AttributeIR>, children: Vec<TemplateNodeIR>, span: Span) -> TemplateNodeIR {
+    TemplateNodeIR::Element(ElementIR { tag: tag.to_string(), attributes, children, is_static: false, span })
+}
+
+/// A static attribute generated from Markdown syntax.
+pub(crate) fn attribute(name: &str, value: &str, span: Span) -> AttributeIR {
+    AttributeIR {
+        name: name.to_string(),
+        value: Some(value.to_string()),
+        value_ast: None,
+        is_directive: false,
+        is_dynamic: false,
+        span,
+    }
+}
+
+fn flag(name: &str, span: Span) -> AttributeIR {
+    AttributeIR { value: None, ..attribute(name, "", span) }
+}
+
+/// The text content of nodes, as used for image `alt` text and heading anchors.
+pub(crate) fn plain_text(nodes: &[TemplateNodeIR]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node {
+            TemplateNodeIR::Text(text, _) => text.clone(),
+            TemplateNodeIR::Element(el) => plain_text(&el.children),
+            TemplateNodeIR::Interpolation(_) | TemplateNodeIR::Comment(_, _) => String::new(),
+        })
+        .collect()
 }
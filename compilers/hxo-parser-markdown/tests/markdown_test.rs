@@ -1,23 +1,108 @@
+use hxo_ir::TemplateNodeIR;
 use hxo_parser::{Parser, ParserRegistry};
-use hxo_parser_markdown::{MarkdownParser, parse};
+use hxo_parser_markdown::{MarkdownParser, parse, slugify};
 use std::sync::Arc;
 
+/// Serializes nodes back to compact HTML so the expected trees stay readable.
+fn html(nodes: &[TemplateNodeIR]) -> String {
+    nodes
+        .iter()
+        .map(|node| match node {
+            TemplateNodeIR::Text(text, _) => text.clone(),
+            TemplateNodeIR::Interpolation(expr) => format!("{{{{ {} }}}}", expr.code),
+            TemplateNodeIR::Comment(comment, _) => format!("<!--{}-->", comment),
+            TemplateNodeIR::Element(el) => {
+                let attributes: String = el
+                    .attributes
+                    .iter()
+                    .map(|attr| match &attr.value {
+                        Some(value) => format!(" {}=\"{}\"", attr.name, value),
+                        None => format!(" {}", attr.name),
+                    })
+                    .collect();
+                format!("<{}{}>{}</{}>", el.tag, attributes, html(&el.children), el.tag)
+            }
+        })
+        .collect()
+}
+
+fn markdown(source: &str) -> String {
+    html(&parse(source).expect("Should parse markdown"))
+}
+
 #[test]
 fn test_parse_markdown() {
     let md = "# Hello\n\nThis is **bold** and *italic* and `code`";
-    let res = parse(md).unwrap();
-    assert!(res.contains("<h1>Hello</h1>"));
-    assert!(res.contains("<strong>bold</strong>"));
-    assert!(res.contains("<em>italic</em>"));
-    assert!(res.contains("<code>code</code>"));
+    assert_eq!(
+        markdown(md),
+        "<h1 id=\"hello\">Hello</h1><p>This is <strong>bold</strong> and <em>italic</em> and <code>code</code></p>"
+    );
 }
 
 #[test]
 fn test_parse_markdown_links_images() {
-    let md = "[Link](https://hxo.dev) and ![Image](src.png)";
-    let html = parse(md).expect("Should parse MD links/images");
-    assert!(html.contains("<a href=\"https://hxo.dev\">Link</a>"));
-    assert!(html.contains("<img src=\"src.png\" alt=\"Image\">"));
+    let md = "[Link](https://hxo.dev) and ![Image](src.png \"Logo\")";
+    assert_eq!(
+        markdown(md),
+        "<p><a href=\"https://hxo.dev\">Link</a> and <img src=\"src.png\" alt=\"Image\" title=\"Logo\"></img></p>"
+    );
+
+    let md = "See [the docs][docs], <https://hxo.dev> or www.hxo.dev.\n\n[docs]: /guide \"Guide\"";
+    assert_eq!(
+        markdown(md),
+        "<p>See <a href=\"/guide\" title=\"Guide\">the docs</a>, <a href=\"https://hxo.dev\">https://hxo.dev</a> or <a href=\"http://www.hxo.dev\">www.hxo.dev</a>.</p>"
+    );
+}
+
+#[test]
+fn test_emphasis_follows_commonmark_delimiter_rules() {
+    assert_eq!(markdown("*foo**bar**baz*"), "<p><em>foo<strong>bar</strong>baz</em></p>");
+    assert_eq!(markdown("***both*** and **foo*"), "<p><em><strong>both</strong></em> and *<em>foo</em></p>");
+    assert_eq!(markdown("snake_case_name and _a_ ~~gone~~"), "<p>snake_case_name and <em>a</em> <del>gone</del></p>");
+    assert_eq!(markdown("\\*literal\\* &amp; &#35;"), "<p>*literal* & #</p>");
+    assert_eq!(markdown("line  \nbreak\\\nagain"), "<p>line<br></br>break<br></br>again</p>");
+}
+
+#[test]
+fn test_block_structure() {
+    let md = "Title\n=====\n\n> quote\nlazy\n> > nested\n\n- a\n- b\n  1. one\n  2. two\n\n3) three\n\n***\n\n    indented\n";
+    assert_eq!(
+        markdown(md),
+        "<h1 id=\"title\">Title</h1><blockquote><p>quote\nlazy</p><blockquote><p>nested</p></blockquote></blockquote><ul><li>a</li><li>b<ol><li>one</li><li>two</li></ol></li></ul><ol start=\"3\"><li>three</li></ol><hr></hr><pre><code>indented\n</code></pre>"
+    );
+
+    let md = "- loose\n\n- list\n";
+    assert_eq!(markdown(md), "<ul><li><p>loose</p></li><li><p>list</p></li></ul>");
+}
+
+#[test]
+fn test_gfm_tables_task_lists_and_fences() {
+    let md = "| Name | Size |\n|:-----|-----:|\n| `a\\|b` | **2** |\n\n- [ ] todo\n- [x] done\n\n```rust title\nlet s = \"{{ raw }}\";\n```\n";
+    assert_eq!(
+        markdown(md),
+        "<table><thead><tr><th align=\"left\">Name</th><th align=\"right\">Size</th></tr></thead><tbody><tr><td align=\"left\"><code>a|b</code></td><td align=\"right\"><strong>2</strong></td></tr></tbody></table>\
+<ul><li class=\"task-list-item\"><input type=\"checkbox\" disabled></input>todo</li><li class=\"task-list-item\"><input type=\"checkbox\" disabled checked></input>done</li></ul>\
+<pre><code class=\"language-rust\">let s = \"{{ raw }}\";\n</code></pre>"
+    );
+}
+
+#[test]
+fn test_heading_anchors() {
+    let md = "## Getting *Started*\n## Getting Started\n## Custom {#setup}";
+    assert_eq!(
+        markdown(md),
+        "<h2 id=\"getting-started\">Getting <em>Started</em></h2><h2 id=\"getting-started-1\">Getting Started</h2><h2 id=\"setup\">Custom</h2>"
+    );
+    assert_eq!(slugify("What's new in v2.0?"), "whats-new-in-v20");
+}
+
+#[test]
+fn test_html_components_and_interpolation() {
+    let md = "<Badge type=\"tip\" />\n\nHello {{ user.name }}, see <Tip>this</Tip>.\n\n<Callout title=\"Note\">\n\nSome **markdown**.\n\n</Callout>\n\n<!-- hidden -->";
+    assert_eq!(
+        markdown(md),
+        "<Badge type=\"tip\"></Badge><p>Hello {{ user.name }}, see <Tip>this</Tip>.</p><Callout title=\"Note\"><p>Some <strong>markdown</strong>.</p></Callout><!-- hidden -->"
+    );
 }
 
 #[test]
@@ -25,6 +110,8 @@ fn test_markdown_integration() {
     let source = r#"
 <template lang="markdown">
 # Hello Markdown
+
+Hi {{ name }} <Badge type="tip" />
 </template>
 "#;
     let mut registry = ParserRegistry::new();
@@ -35,18 +122,38 @@ fn test_markdown_integration() {
     let parsed = parser.parse_all().expect("Failed to parse");
 
     let template = parsed.template.expect("Should have template");
-    assert_eq!(template.nodes.len(), 1);
-
-    if let hxo_ir::TemplateNodeIR::Element(el) = &template.nodes[0] {
-        assert_eq!(el.tag, "h1");
-        if let hxo_ir::TemplateNodeIR::Text(text, _) = &el.children[0] {
-            assert_eq!(text, "Hello Markdown");
-        }
-        else {
-            panic!("Expected text child");
-        }
-    }
+    assert_eq!(template.nodes.len(), 2);
+
+    let TemplateNodeIR::Element(h1) = &template.nodes[0]
     else {
         panic!("Expected h1 element");
-    }
+    };
+    assert_eq!(h1.tag, "h1");
+    assert_eq!((h1.span.start.line, h1.span.start.column), (3, 1));
+    let TemplateNodeIR::Text(text, _) = &h1.children[0]
+    else {
+        panic!("Expected text child");
+    };
+    assert_eq!(text, "Hello Markdown");
+
+    let TemplateNodeIR::Element(p) = &template.nodes[1]
+    else {
+        panic!("Expected paragraph");
+    };
+    let TemplateNodeIR::Interpolation(expr) = &p.children[1]
+    else {
+        panic!("Expected interpolation");
+    };
+    assert_eq!(expr.code, "name");
+    assert_eq!((expr.span.start.line, expr.span.start.column, expr.span.end.column), (5, 4, 14));
+    assert_eq!(&source[expr.span.start.offset as usize..expr.span.end.offset as usize], "{{ name }}");
+    let TemplateNodeIR::Element(badge) = &p.children[3]
+    else {
+        panic!("Expected component");
+    };
+    assert_eq!(badge.tag, "Badge");
+    assert_eq!(
+        &source[badge.attributes[0].span.start.offset as usize..badge.attributes[0].span.end.offset as usize],
+        "type=\"tip\""
+    );
 }
@@ -1,5 +1,5 @@
-use hxo_parser::{MetadataParser, ParseState};
-use hxo_types::{HxoValue, Result};
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
+use hxo_types::{Error, HxoValue, Result, Span};
 use std::collections::HashMap;
 
 pub struct PropertiesParser;
@@ -11,6 +11,46 @@ impl MetadataParser for PropertiesParser {
     }
 }
 
+impl MetadataSerializer for PropertiesParser {
+    /// One `key = value` line per entry, sorted; nested tables become dotted keys.
+    fn serialize(&self, value: &HxoValue, _lang: &str) -> Result<String> {
+        let mut entries = Vec::new();
+        flatten(value, "", &mut entries)?;
+        entries.sort();
+        let mut out = String::new();
+        for (key, value) in entries {
+            if key.is_empty() || key.contains(|c: char| c.is_whitespace() || c == '=' || c == ':') {
+                return Err(Error::invalid_i18n(format!("properties: `{}` is not a valid key", key), Span::unknown()));
+            }
+            if value.contains('\n') {
+                return Err(Error::invalid_i18n(format!("properties: `{}` spans several lines", key), Span::unknown()));
+            }
+            out.push_str(&format!("{} = {}\n", key, value));
+        }
+        Ok(out)
+    }
+
+    fn appends(&self) -> bool {
+        true
+    }
+}
+
+fn flatten(value: &HxoValue, prefix: &str, out: &mut Vec<(String, String)>) -> Result<()> {
+    match value {
+        HxoValue::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(value, &key, out)?;
+            }
+        }
+        HxoValue::String(text) => out.push((prefix.to_string(), text.clone())),
+        HxoValue::Number(number) => out.push((prefix.to_string(), number.to_string())),
+        HxoValue::Bool(flag) => out.push((prefix.to_string(), flag.to_string())),
+        _ => return Err(Error::invalid_i18n(format!("properties: `{}` is not text", prefix), Span::unknown())),
+    }
+    Ok(())
+}
+
 struct PropertiesParserImpl<'a, 'b> {
     state: &'a mut ParseState<'b>,
 }
@@ -1,6 +1,7 @@
-use hxo_parser::{MetadataParser, ParseState};
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
 use hxo_parser_properties::PropertiesParser;
 use hxo_types::HxoValue;
+use std::collections::HashMap;
 
 #[test]
 fn test_parse_properties_basic() {
@@ -42,3 +43,15 @@ fn test_parse_properties_comments() {
         panic!("Expected object");
     }
 }
+
+#[test]
+fn test_serialize_flattens_tables() {
+    let value = HxoValue::Object(HashMap::from([
+        ("title".to_string(), HxoValue::String("Welcome".to_string())),
+        ("nav".to_string(), HxoValue::Object(HashMap::from([("home".to_string(), HxoValue::String("Home".to_string()))]))),
+    ]));
+    assert_eq!(PropertiesParser.serialize(&value, "properties").unwrap(), "nav.home = Home\ntitle = Welcome\n");
+    assert!(PropertiesParser.appends());
+    let multiline = HxoValue::Object(HashMap::from([("a".to_string(), HxoValue::String("x\ny".to_string()))]));
+    assert!(PropertiesParser.serialize(&multiline, "properties").is_err());
+}
@@ -1,126 +1,64 @@
-use hxo_parser::{ParseState, StyleParser};
-use hxo_types::Result;
-use std::collections::HashMap;
-
-pub struct SassParser;
+pub mod parser;
 
-#[derive(Default)]
-pub struct SassParserOptions {}
+pub use parser::{parse_sass, parse_sass_with, to_scss};
 
-pub fn compile(source: &str, _options: &SassParserOptions) -> Result<String> {
-    let mut state = ParseState::new(source);
-    let parser = SassParser;
-    parser.parse(&mut state, "sass")
+use hxo_ir::Stylesheet;
+use hxo_parser::{ParseState, StyleParser};
+use hxo_parser_scss::Evaluator;
+use hxo_types::{Cursor, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SassParserOptions {
+    pub is_compressed: bool,
+    /// Directories searched by `@use`, `@forward` and `@import` after the importing file's own.
+    pub load_paths: Vec<PathBuf>,
 }
 
-impl StyleParser for SassParser {
-    fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<String> {
-        let mut parser = SassParserImpl { state, variables: HashMap::new() };
-        parser.parse()
-    }
+pub fn compile(source: &str, options: &SassParserOptions) -> Result<String> {
+    let sheet = evaluate(&mut Cursor::new(source), None, options)?;
+    Ok(sheet.to_css(options.is_compressed).trim().to_string())
 }
 
-pub struct SassParserImpl<'a, 'b> {
-    state: &'a mut ParseState<'b>,
-    variables: HashMap<String, String>,
+/// Compiles a file, resolving its relative imports from its directory.
+pub fn compile_file(path: &Path, options: &SassParserOptions) -> Result<String> {
+    let source = std::fs::read_to_string(path)?;
+    let sheet = evaluate(&mut Cursor::new(&source), Some(path), options)?;
+    Ok(sheet.to_css(options.is_compressed).trim().to_string())
 }
 
-impl<'a, 'b> SassParserImpl<'a, 'b> {
-    pub fn parse(&mut self) -> Result<String> {
-        let mut css = String::new();
-        let mut selector_stack: Vec<(usize, String)> = Vec::new();
-
-        while !self.state.cursor.is_eof() {
-            let (indent, line) = self.consume_line_with_indent()?;
-            if line.is_empty() || line.starts_with("//") {
-                continue;
-            }
-
-            if line.starts_with('$') {
-                self.parse_variable_line(line)?;
-            }
-            else if line.contains(':') && !self.is_selector(&line) {
-                // Property
-                let parts: Vec<&str> = line.splitn(2, ':').collect();
-                let prop = parts[0].trim().to_string();
-                let mut val = parts[1].trim().to_string();
-
-                // Replace variables
-                for (name, var_val) in &self.variables {
-                    val = val.replace(&format!("${}", name), var_val);
-                }
-
-                // Find the current rule in css and append property
-                let current_selector = self.get_current_selector(&selector_stack);
-                let rule_header = format!("{} {{\n", current_selector);
-                let prop_line = format!("  {}: {};\n", prop, val);
-
-                if let Some(pos) = css.rfind(&rule_header) {
-                    let end_pos = css[pos..].find('}').map(|i| i + pos).unwrap_or(css.len());
-                    css.insert_str(end_pos, &prop_line);
-                }
-                else {
-                    css.push_str(&rule_header);
-                    css.push_str(&prop_line);
-                    css.push_str("}\n");
-                }
-            }
-            else {
-                // Selector
-                while !selector_stack.is_empty() && indent <= selector_stack.last().unwrap().0 {
-                    selector_stack.pop();
-                }
-                selector_stack.push((indent, line));
-                let current_selector = self.get_current_selector(&selector_stack);
-                css.push_str(&format!("{} {{\n}}\n", current_selector));
-            }
-        }
-
-        Ok(css.replace("{\n}\n", "").trim().to_string())
-    }
+/// Imported `.sass` files are read as indented Sass, `.scss` files as SCSS.
+fn evaluate(cursor: &mut Cursor, file: Option<&Path>, options: &SassParserOptions) -> Result<Stylesheet> {
+    let stmts = parse_sass_with(cursor)?;
+    Evaluator::new(options.load_paths.clone())
+        .with_syntax("sass", parse_sass)
+        .with_compressed(options.is_compressed)
+        .evaluate(&stmts, file)
+}
 
-    fn consume_line_with_indent(&mut self) -> Result<(usize, String)> {
-        let mut indent = 0;
-        while !self.state.cursor.is_eof() && (self.state.cursor.peek() == ' ' || self.state.cursor.peek() == '\t') {
-            if self.state.cursor.peek() == ' ' {
-                indent += 1;
-            }
-            else {
-                indent += 2;
-            } // Tab as 2 spaces
-            self.state.cursor.consume();
-        }
-        let line = self.consume_until_newline()?;
-        Ok((indent, line))
-    }
+#[derive(Debug, Clone, Default)]
+pub struct SassParser {
+    options: SassParserOptions,
+}
 
-    fn parse_variable_line(&mut self, line: String) -> Result<()> {
-        let parts: Vec<&str> = line[1..].splitn(2, ':').collect();
-        if parts.len() == 2 {
-            let name = parts[0].trim().to_string();
-            let value = parts[1].trim().to_string();
-            self.variables.insert(name, value);
-        }
-        Ok(())
+impl SassParser {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn is_selector(&self, line: &str) -> bool {
-        line.contains('.') || line.contains('#') || (!line.contains(':') && !line.starts_with('$'))
+    pub fn with_options(options: SassParserOptions) -> Self {
+        Self { options }
     }
+}
 
-    fn get_current_selector(&self, stack: &[(usize, String)]) -> String {
-        stack.iter().map(|(_, s)| s.as_str()).collect::<Vec<&str>>().join(" ")
+impl StyleParser for SassParser {
+    fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<String> {
+        let sheet = evaluate(&mut state.cursor, None, &self.options)?;
+        Ok(sheet.to_css(self.options.is_compressed).trim().to_string())
     }
 
-    fn consume_until_newline(&mut self) -> Result<String> {
-        let start = self.state.cursor.pos;
-        while !self.state.cursor.is_eof() && self.state.cursor.peek() != '\n' {
-            self.state.cursor.consume();
-        }
-        let s = self.state.cursor.current_str(start).to_string();
-        if self.state.cursor.peek() == '\n' {
-            self.state.cursor.consume();
-        }
-        Ok(s.trim().to_string())
+    fn parse_stylesheet(&self, state: &mut ParseState, _lang: &str) -> Result<Option<Stylesheet>> {
+        evaluate(&mut state.cursor, None, &self.options).map(Some)
     }
 }
@@ -0,0 +1,83 @@
+//! Indented Sass: each line is translated to SCSS and parsed by `hxo-parser-scss`, which then
+//! evaluates it like any SCSS stylesheet.
+
+use hxo_parser_scss::{
+    ast::Stmt,
+    indented::{self, quote_imports},
+    parse_scss_with,
+    parser::{is_name_char, is_name_start},
+};
+use hxo_types::{Cursor, Result};
+
+/// Parses a whole indented Sass stylesheet.
+pub fn parse_sass(source: &str) -> Result<Vec<Stmt>> {
+    parse_sass_with(&mut Cursor::new(source))
+}
+
+/// Parses from `cursor` to the end of its source; spans keep the lines of the Sass source.
+pub fn parse_sass_with(cursor: &mut Cursor) -> Result<Vec<Stmt>> {
+    indented::parse_translated(cursor, to_scss, parse_scss_with)
+}
+
+/// The SCSS equivalent of `source`, line for line.
+pub fn to_scss(source: &str) -> Result<String> {
+    let lines = indented::lines(source);
+    indented::to_scss(source, &lines, |i, opens| Ok(translate(lines[i].text.trim_end_matches(';').trim_end(), opens)))
+}
+
+fn translate(text: &str, opens: bool) -> String {
+    if let Some(rest) = text.strip_prefix('=') {
+        return format!("@mixin {}", rest.trim_start());
+    }
+    // `+ .sibling` is a selector, `+name` an include
+    if let Some(rest) = text.strip_prefix('+').filter(|rest| rest.starts_with(is_name_start)) {
+        return format!("@include {}", rest);
+    }
+    if let Some(urls) = text.strip_prefix("@import ") {
+        return format!("@import {}", quote_imports(urls));
+    }
+    if text.starts_with(['@', '$']) {
+        return text.to_string();
+    }
+    if let Some(property) = property(text) {
+        return property;
+    }
+    if opens {
+        return text.to_string();
+    }
+    // Without a block below it the line can't be a selector, so `b:c` is `b: c`
+    match property_colon(text) {
+        Some(colon) => format!("{}: {}", &text[..colon], &text[colon + 1..]),
+        None => format!("{} {{}}", text),
+    }
+}
+
+/// `name: value`, `name:` opening nested properties, or the older `:name value`. A colon with no
+/// space after it belongs to a selector such as `a:hover` when a block follows.
+fn property(text: &str) -> Option<String> {
+    if let Some(rest) = text.strip_prefix(':') {
+        let (name, value) = rest.split_once(char::is_whitespace)?;
+        let valid = !name.is_empty() && name.chars().all(is_name_char);
+        return valid.then(|| format!("{}: {}", name, value.trim()));
+    }
+    let colon = property_colon(text)?;
+    let after = &text[colon + 1..];
+    (after.is_empty() || after.starts_with(char::is_whitespace)).then(|| text.to_string())
+}
+
+/// The colon ending a property name made of name characters and `#{...}` interpolations.
+fn property_colon(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b':' if i > 0 => return Some(i),
+            b'#' if bytes.get(i + 1) == Some(&b'{') => i += text[i..].find('}')?,
+            b'*' if i == 0 => {}
+            _ if is_name_char(text[i..].chars().next()?) => {}
+            _ => return None,
+        }
+        i += text[i..].chars().next().map_or(1, char::len_utf8);
+    }
+    None
+}
@@ -19,3 +19,87 @@ fn test_compile_nested_sass() {
     assert!(result.contains(".parent .child {"));
     assert!(result.contains("color: red;"));
 }
+
+#[test]
+fn test_mixins_functions_and_control_flow() {
+    let sass = r#"
+=button($color, $radius: 4px)
+  color: $color
+  border-radius: $radius
+  &:hover
+    color: darken($color, 10%)
+  @content
+
+@function double($n)
+  @return $n * 2
+
+.btn,
+.link
+  +button(#336699)
+    cursor: pointer
+  width: double(5px)
+  @if $theme == dark
+    background: black
+  @else
+    background: white
+  @each $size in 1, 2
+    .m-#{$size}
+      margin: $size * 4px
+"#;
+    let css =
+        compile(&format!("$theme: light\n{}", sass), &SassParserOptions::default()).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".btn, .link {\n  color: #336699;\n  border-radius: 4px;\n  cursor: pointer;\n  width: 10px;\n  background: white;\n}"), "{}", css);
+    assert!(css.contains(".btn:hover, .link:hover {\n  color: #264c73;\n}"), "{}", css);
+    assert!(css.contains(".btn .m-2, .link .m-2 {\n  margin: 8px;\n}"), "{}", css);
+}
+
+#[test]
+fn test_selectors_versus_properties() {
+    let sass = r#"
+// a comment
+  that continues
+ul li
+  a:hover
+    color: red
+  font:
+    family: serif
+    size: 12px
+  :margin 0
+  %placeholder-free
+    top: 0
+.base
+  padding: 1px
+.child
+  @extend .base
+"#;
+    let css = compile(sass, &SassParserOptions::default()).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains("ul li {\n  font-family: serif;\n  font-size: 12px;\n  margin: 0;\n}"), "{}", css);
+    assert!(css.contains("ul li a:hover {\n  color: red;\n}"), "{}", css);
+    assert!(css.contains(".base, .child {\n  padding: 1px;\n}"), "{}", css);
+    assert!(!css.contains("placeholder"), "{}", css);
+
+    let css = compile("a\n  b:c\n  a:hover\n    color:red\n", &SassParserOptions::default()).unwrap();
+    assert!(css.contains("a {\n  b: c;\n}"), "{}", css);
+    assert!(css.contains("a a:hover {\n  color: red;\n}"), "{}", css);
+}
+
+#[test]
+fn test_errors_keep_sass_lines() {
+    let err = compile(".a\n  color: red\n.b\n  width: $missing\n", &SassParserOptions::default()).unwrap_err();
+    assert!(err.to_string().contains("$missing"), "{}", err);
+    assert!(format!("{:?}", err).contains("line: 4"), "{:?}", err);
+}
+
+#[test]
+fn test_imports_through_load_paths() {
+    let dir = std::env::temp_dir().join(format!("hxo-sass-imports-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("_theme.sass"), "$brand: #0044cc\n=rounded\n  border-radius: 4px\n").unwrap();
+    std::fs::write(dir.join("_tokens.scss"), "$gap: 8px;\n").unwrap();
+
+    let options = SassParserOptions { load_paths: vec![dir.clone()], ..SassParserOptions::default() };
+    let css = compile("@import theme, tokens\n.card\n  color: $brand\n  padding: $gap\n  +rounded\n", &options)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".card {\n  color: #0044cc;\n  padding: 8px;\n  border-radius: 4px;\n}"), "{}", css);
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,310 @@
+//! Syntax tree shared by the Sass-family front ends (SCSS here, indented Sass and Stylus build the same
+//! tree) and evaluated by [`Evaluator`](crate::Evaluator).
+
+use crate::value::Color;
+use hxo_types::Span;
+use std::rc::Rc;
+
+/// Text with `#{...}` interpolations: selectors, property names, at-rule preludes and strings.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Interpolation {
+    pub parts: Vec<InterpolationPart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterpolationPart {
+    Text(String),
+    Expr(Expr),
+}
+
+impl Interpolation {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self { parts: vec![InterpolationPart::Text(text.into())] }
+    }
+
+    /// The text when there is nothing to interpolate.
+    pub fn as_plain(&self) -> Option<String> {
+        let mut text = String::new();
+        for part in &self.parts {
+            match part {
+                InterpolationPart::Text(t) => text.push_str(t),
+                InterpolationPart::Expr(_) => return None,
+            }
+        }
+        Some(text)
+    }
+
+    pub fn push_text(&mut self, text: &str) {
+        match self.parts.last_mut() {
+            Some(InterpolationPart::Text(last)) => last.push_str(text),
+            _ => self.parts.push(InterpolationPart::Text(text.to_string())),
+        }
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        match self.parts.last_mut() {
+            Some(InterpolationPart::Text(last)) => last.push(c),
+            _ => self.parts.push(InterpolationPart::Text(c.to_string())),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.iter().all(|part| matches!(part, InterpolationPart::Text(t) if t.is_empty()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stmt {
+    /// `$name: value [!default] [!global];`
+    Variable(VariableDecl),
+    /// `selector { ... }`
+    Rule {
+        selector: Interpolation,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+    /// `name: value [!important];`, or `name: [value] { nested: properties }`.
+    Declaration {
+        name: Interpolation,
+        value: Option<Expr>,
+        important: bool,
+        children: Vec<Stmt>,
+        span: Span,
+    },
+    /// Any other at-rule, passed through with its prelude interpolated: `@media`, `@font-face`, `@apply`...
+    AtRule {
+        name: String,
+        prelude: Interpolation,
+        body: Option<Vec<Stmt>>,
+        span: Span,
+    },
+    Mixin(Rc<Callable>),
+    Function(Rc<Callable>),
+    /// `@include [namespace.]name[(args)] [using ($params)] [{ content }]`
+    Include {
+        namespace: Option<String>,
+        name: String,
+        args: Arguments,
+        content: Option<Rc<ContentBlock>>,
+        span: Span,
+    },
+    /// `@content[(args)]`
+    Content {
+        args: Arguments,
+        span: Span,
+    },
+    Return {
+        value: Expr,
+        span: Span,
+    },
+    /// `@if` / `@else if` / `@else`
+    If {
+        clauses: Vec<(Expr, Vec<Stmt>)>,
+        otherwise: Option<Vec<Stmt>>,
+        span: Span,
+    },
+    /// `@each $a, $b in list-or-map`
+    Each {
+        variables: Vec<String>,
+        list: Expr,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+    /// `@for $i from a through|to b`
+    For {
+        variable: String,
+        from: Expr,
+        to: Expr,
+        inclusive: bool,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+    While {
+        condition: Expr,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+    /// `@extend selector [!optional]`
+    Extend {
+        selector: Interpolation,
+        optional: bool,
+        span: Span,
+    },
+    /// `@use "url" [as namespace|*] [with (...)]`; `namespace` is `None` for the default (last url segment).
+    Use {
+        url: String,
+        namespace: Option<String>,
+        config: Vec<(String, Expr)>,
+        span: Span,
+    },
+    /// `@forward "url" [as prefix-*] [show ...|hide ...] [with (...)]`
+    Forward {
+        url: String,
+        prefix: Option<String>,
+        show: Option<Vec<String>>,
+        hide: Option<Vec<String>>,
+        config: Vec<(String, Expr)>,
+        span: Span,
+    },
+    /// `@import "a", "b";` of Sass files; plain CSS imports are [`Stmt::AtRule`]s.
+    Import {
+        urls: Vec<String>,
+        span: Span,
+    },
+    /// `@debug`, `@warn` and `@error`
+    Message {
+        kind: MessageKind,
+        value: Expr,
+        span: Span,
+    },
+    /// `@at-root [selector] { ... }`
+    AtRoot {
+        selector: Option<Interpolation>,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Variable(decl) => decl.span,
+            Stmt::Mixin(callable) | Stmt::Function(callable) => callable.span,
+            Stmt::Rule { span, .. }
+            | Stmt::Declaration { span, .. }
+            | Stmt::AtRule { span, .. }
+            | Stmt::Include { span, .. }
+            | Stmt::Content { span, .. }
+            | Stmt::Return { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::Each { span, .. }
+            | Stmt::For { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::Extend { span, .. }
+            | Stmt::Use { span, .. }
+            | Stmt::Forward { span, .. }
+            | Stmt::Import { span, .. }
+            | Stmt::Message { span, .. }
+            | Stmt::AtRoot { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    Debug,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDecl {
+    pub namespace: Option<String>,
+    pub name: String,
+    pub value: Expr,
+    pub default: bool,
+    pub global: bool,
+    pub span: Span,
+}
+
+/// A `@mixin` or `@function`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Callable {
+    pub name: String,
+    pub params: Parameters,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+/// The block passed to a mixin, with its `using ($params)`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ContentBlock {
+    pub params: Parameters,
+    pub body: Vec<Stmt>,
+}
+
+/// `($a, $b: default, $rest...)`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Parameters {
+    pub params: Vec<(String, Option<Expr>)>,
+    pub rest: Option<String>,
+}
+
+/// `(positional, $named: value, $list...)`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Arguments {
+    pub positional: Vec<Expr>,
+    pub named: Vec<(String, Expr)>,
+    pub rest: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListSeparator {
+    Space,
+    Comma,
+    Slash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Plus,
+    Not,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64, String),
+    Color(Color),
+    /// Quoted (`quote` is the quote character) or unquoted string, identifiers included.
+    String {
+        value: Interpolation,
+        quote: Option<char>,
+    },
+    Bool(bool),
+    Null,
+    Variable {
+        namespace: Option<String>,
+        name: String,
+    },
+    List {
+        items: Vec<Expr>,
+        separator: ListSeparator,
+        bracketed: bool,
+    },
+    Map(Vec<(Expr, Expr)>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Call {
+        namespace: Option<String>,
+        name: String,
+        args: Arguments,
+    },
+    /// `(expr)`: a `/` inside parentheses always divides.
+    Paren(Box<Expr>),
+    /// `&`
+    Parent,
+}
+
+impl Expr {
+    pub fn ident(text: impl Into<String>) -> Self {
+        Expr::String { value: Interpolation::plain(text), quote: None }
+    }
+}
@@ -0,0 +1,727 @@
+//! Built-in functions: the global ones (`darken()`, `map-get()`, `percentage()`) and those of the
+//! `sass:math`, `sass:color`, `sass:list`, `sass:map`, `sass:string` and `sass:meta` modules.
+
+use crate::{
+    ast::ListSeparator,
+    value::{Color, Number, Value},
+};
+use std::f64::consts::{E, PI};
+
+/// The `sass:` modules `@use` can load.
+pub const MODULES: &[&str] = &["math", "color", "list", "map", "string", "meta"];
+
+/// Evaluated arguments of a built-in call.
+#[derive(Debug, Clone, Default)]
+pub struct BuiltinArgs {
+    pub positional: Vec<Value>,
+    pub named: Vec<(String, Value)>,
+}
+
+impl BuiltinArgs {
+    pub fn get(&self, index: usize, name: &str) -> Option<&Value> {
+        self.named.iter().find(|(n, _)| n == name).map(|(_, value)| value).or_else(|| self.positional.get(index))
+    }
+
+    fn value(&self, index: usize, name: &str) -> Result<&Value, String> {
+        self.get(index, name).ok_or_else(|| format!("Missing argument ${}.", name))
+    }
+
+    fn number(&self, index: usize, name: &str) -> Result<&Number, String> {
+        let value = self.value(index, name)?;
+        value.as_number().ok_or_else(|| format!("${}: {} is not a number.", name, value.inspect()))
+    }
+
+    fn optional_number(&self, index: usize, name: &str) -> Result<Option<&Number>, String> {
+        match self.get(index, name) {
+            None | Some(Value::Null) => Ok(None),
+            Some(_) => self.number(index, name).map(Some),
+        }
+    }
+
+    fn color(&self, index: usize, name: &str) -> Result<Color, String> {
+        match self.value(index, name)? {
+            Value::Color(color) => Ok(color.clone().computed()),
+            other => Err(format!("${}: {} is not a color.", name, other.inspect())),
+        }
+    }
+
+    fn string(&self, index: usize, name: &str) -> Result<(&str, Option<char>), String> {
+        match self.value(index, name)? {
+            Value::String(text, quote) => Ok((text, *quote)),
+            other => Err(format!("${}: {} is not a string.", name, other.inspect())),
+        }
+    }
+
+    fn map(&self, index: usize, name: &str) -> Result<Vec<(Value, Value)>, String> {
+        let value = self.value(index, name)?;
+        value.as_map().ok_or_else(|| format!("${}: {} is not a map.", name, value.inspect()))
+    }
+
+    /// Everything from `index` on, for functions taking `$args...`.
+    fn rest(&self, index: usize) -> &[Value] {
+        self.positional.get(index..).unwrap_or_default()
+    }
+}
+
+/// The module variables of `sass:math`.
+pub fn module_variable(module: &str, name: &str) -> Option<Value> {
+    match (module, name) {
+        ("math", "pi") => Some(Value::number(PI, "")),
+        ("math", "e") => Some(Value::number(E, "")),
+        _ => None,
+    }
+}
+
+/// Whether `name` is a global built-in function, or a function of the built-in `module`.
+pub fn exists(module: Option<&str>, name: &str) -> bool {
+    canonical(module, name).is_some()
+}
+
+/// Calls a built-in function; `None` when there is no such function.
+pub fn call(module: Option<&str>, name: &str, args: &BuiltinArgs) -> Option<Result<Value, String>> {
+    let canonical = canonical(module, name)?;
+    Some(dispatch(canonical, name, args))
+}
+
+fn canonical(module: Option<&str>, name: &str) -> Option<&'static str> {
+    Some(match (module, name) {
+        (None, "rgb" | "rgba") => "rgb",
+        (None, "hsl" | "hsla") => "hsl",
+        (None | Some("color"), "red") => "red",
+        (None | Some("color"), "green") => "green",
+        (None | Some("color"), "blue") => "blue",
+        (None | Some("color"), "alpha" | "opacity") => "alpha",
+        (None | Some("color"), "hue") => "hue",
+        (None | Some("color"), "saturation") => "saturation",
+        (None | Some("color"), "lightness") => "lightness",
+        (None | Some("color"), "mix") => "mix",
+        (None | Some("color"), "complement") => "complement",
+        (None | Some("color"), "grayscale") => "grayscale",
+        (None | Some("color"), "invert") => "invert",
+        (None | Some("color"), "ie-hex-str") => "ie-hex-str",
+        (None, "lighten") => "lighten",
+        (None, "darken") => "darken",
+        (None, "saturate") => "saturate",
+        (None, "desaturate") => "desaturate",
+        (None, "adjust-hue") => "adjust-hue",
+        (None, "opacify" | "fade-in") => "opacify",
+        (None, "transparentize" | "fade-out") => "transparentize",
+        (None, "adjust-color") | (Some("color"), "adjust") => "adjust",
+        (None, "scale-color") | (Some("color"), "scale") => "scale",
+        (None, "change-color") | (Some("color"), "change") => "change",
+
+        (None | Some("math"), "percentage") => "percentage",
+        (None | Some("math"), "round") => "round",
+        (None | Some("math"), "ceil") => "ceil",
+        (None | Some("math"), "floor") => "floor",
+        (None | Some("math"), "abs") => "abs",
+        (None | Some("math"), "min") => "min",
+        (None | Some("math"), "max") => "max",
+        (None | Some("math"), "unit") => "unit",
+        (None, "unitless") | (Some("math"), "is-unitless") => "unitless",
+        (None, "comparable") | (Some("math"), "compatible") => "comparable",
+        (Some("math"), "div") => "div",
+        (Some("math"), "sqrt") => "sqrt",
+        (Some("math"), "pow") => "pow",
+        (Some("math"), "log") => "log",
+        (Some("math"), "hypot") => "hypot",
+        (Some("math"), "clamp") => "clamp",
+        (Some("math"), "sin") => "sin",
+        (Some("math"), "cos") => "cos",
+        (Some("math"), "tan") => "tan",
+
+        (None | Some("list"), "length") => "length",
+        (None | Some("list"), "nth") => "nth",
+        (None | Some("list"), "set-nth") => "set-nth",
+        (None | Some("list"), "join") => "join",
+        (None | Some("list"), "append") => "append",
+        (None | Some("list"), "zip") => "zip",
+        (None | Some("list"), "index") => "index",
+        (None | Some("list"), "is-bracketed") => "is-bracketed",
+        (None, "list-separator") | (Some("list"), "separator") => "separator",
+        (Some("list"), "slash") => "slash",
+
+        (None, "map-get") | (Some("map"), "get") => "map-get",
+        (None, "map-merge") | (Some("map"), "merge") => "map-merge",
+        (None, "map-remove") | (Some("map"), "remove") => "map-remove",
+        (None, "map-keys") | (Some("map"), "keys") => "map-keys",
+        (None, "map-values") | (Some("map"), "values") => "map-values",
+        (None, "map-has-key") | (Some("map"), "has-key") => "map-has-key",
+        (Some("map"), "deep-merge") => "deep-merge",
+        (Some("map"), "set") => "map-set",
+
+        (None | Some("string"), "quote") => "quote",
+        (None | Some("string"), "unquote") => "unquote",
+        (None, "str-length") | (Some("string"), "length") => "str-length",
+        (None, "str-index") | (Some("string"), "index") => "str-index",
+        (None, "str-insert") | (Some("string"), "insert") => "str-insert",
+        (None, "str-slice") | (Some("string"), "slice") => "str-slice",
+        (None | Some("string"), "to-upper-case") => "to-upper-case",
+        (None | Some("string"), "to-lower-case") => "to-lower-case",
+
+        (None | Some("meta"), "type-of") => "type-of",
+        (None | Some("meta"), "inspect") => "inspect",
+        _ => return None,
+    })
+}
+
+fn dispatch(function: &str, name: &str, args: &BuiltinArgs) -> Result<Value, String> {
+    match function {
+        "rgb" => rgb(name, args),
+        "hsl" => hsl(name, args),
+        "red" => Ok(Value::number(args.color(0, "color")?.red.round(), "")),
+        "green" => Ok(Value::number(args.color(0, "color")?.green.round(), "")),
+        "blue" => Ok(Value::number(args.color(0, "color")?.blue.round(), "")),
+        "alpha" => match args.value(0, "color")? {
+            // `opacity(50%)` is the CSS filter function
+            Value::Color(color) => Ok(Value::number(color.alpha, "")),
+            other => Ok(plain_css(name, std::slice::from_ref(other))),
+        },
+        "hue" => Ok(Value::number(args.color(0, "color")?.to_hsl().0, "deg")),
+        "saturation" => Ok(Value::number(args.color(0, "color")?.to_hsl().1, "%")),
+        "lightness" => Ok(Value::number(args.color(0, "color")?.to_hsl().2, "%")),
+        "mix" => {
+            let weight = args.optional_number(2, "weight")?.map_or(50.0, |weight| weight.value);
+            Ok(Value::Color(mix(&args.color(0, "color1")?, &args.color(1, "color2")?, weight)))
+        }
+        "complement" => adjust_hsl(args, |h, s, l| (h + 180.0, s, l)),
+        "grayscale" => match args.value(0, "color")? {
+            Value::Color(_) => adjust_hsl(args, |h, _, l| (h, 0.0, l)),
+            other => Ok(plain_css(name, std::slice::from_ref(other))),
+        },
+        "invert" => match args.value(0, "color")? {
+            Value::Color(color) => {
+                let weight = args.optional_number(1, "weight")?.map_or(100.0, |weight| weight.value);
+                let inverted = Color::rgba(255.0 - color.red, 255.0 - color.green, 255.0 - color.blue, color.alpha);
+                Ok(Value::Color(mix(&inverted, &color.clone().computed(), weight)))
+            }
+            other => Ok(plain_css(name, std::slice::from_ref(other))),
+        },
+        "ie-hex-str" => {
+            let color = args.color(0, "color")?;
+            Ok(Value::ident(format!(
+                "#{:02X}{:02X}{:02X}{:02X}",
+                (color.alpha * 255.0).round() as u8,
+                color.red.round() as u8,
+                color.green.round() as u8,
+                color.blue.round() as u8
+            )))
+        }
+        "lighten" => {
+            let amount = args.number(1, "amount")?.value;
+            adjust_hsl(args, |h, s, l| (h, s, l + amount))
+        }
+        "darken" => {
+            let amount = args.number(1, "amount")?.value;
+            adjust_hsl(args, |h, s, l| (h, s, l - amount))
+        }
+        "saturate" => match args.get(1, "amount") {
+            // `saturate(50%)` with one argument is the CSS filter function
+            None => Ok(plain_css(name, &args.positional)),
+            Some(_) => {
+                let amount = args.number(1, "amount")?.value;
+                adjust_hsl(args, |h, s, l| (h, s + amount, l))
+            }
+        },
+        "desaturate" => {
+            let amount = args.number(1, "amount")?.value;
+            adjust_hsl(args, |h, s, l| (h, s - amount, l))
+        }
+        "adjust-hue" => {
+            let degrees = args.number(1, "degrees")?.value;
+            adjust_hsl(args, |h, s, l| (h + degrees, s, l))
+        }
+        "opacify" | "transparentize" => {
+            let mut color = args.color(0, "color")?;
+            let amount = args.number(1, "amount")?.value;
+            let amount = if function == "opacify" { amount } else { -amount };
+            color.alpha = (color.alpha + amount).clamp(0.0, 1.0);
+            Ok(Value::Color(color))
+        }
+        "adjust" | "scale" | "change" => adjust_color(function, args),
+
+        "percentage" => {
+            let number = args.number(0, "number")?;
+            if !number.unit.is_empty() {
+                return Err(format!("$number: Expected {} to have no units.", number));
+            }
+            Ok(Value::number(number.value * 100.0, "%"))
+        }
+        "round" => map_number(args, f64::round),
+        "ceil" => map_number(args, f64::ceil),
+        "floor" => map_number(args, f64::floor),
+        "abs" => map_number(args, f64::abs),
+        "min" | "max" => min_max(function, args),
+        "unit" => Ok(Value::quoted(args.number(0, "number")?.unit.clone())),
+        "unitless" => Ok(Value::Bool(args.number(0, "number")?.unit.is_empty())),
+        "comparable" => Ok(Value::Bool(args.number(0, "number1")?.is_comparable(args.number(1, "number2")?))),
+        "div" => args.value(0, "number1")?.div(args.value(1, "number2")?),
+        "sqrt" => Ok(Value::number(unitless(args.number(0, "number")?)?.sqrt(), "")),
+        "pow" => Ok(Value::number(unitless(args.number(0, "base")?)?.powf(unitless(args.number(1, "exponent")?)?), "")),
+        "log" => {
+            let number = unitless(args.number(0, "number")?)?;
+            match args.optional_number(1, "base")? {
+                Some(base) => Ok(Value::number(number.ln() / unitless(base)?.ln(), "")),
+                None => Ok(Value::number(number.ln(), "")),
+            }
+        }
+        "hypot" => {
+            let first = args.number(0, "number")?;
+            let mut sum = 0.0;
+            for value in &args.positional {
+                let number = value.as_number().ok_or_else(|| format!("{} is not a number.", value.inspect()))?;
+                let converted = number
+                    .convert_to(&first.unit)
+                    .ok_or_else(|| format!("Incompatible units {} and {}.", first.unit, number.unit))?;
+                sum += converted * converted;
+            }
+            Ok(Value::number(sum.sqrt(), first.unit.clone()))
+        }
+        "clamp" => {
+            let (min, number, max) = (args.value(0, "min")?, args.value(1, "number")?, args.value(2, "max")?);
+            if number.compare(min)?.is_lt() {
+                Ok(min.clone())
+            }
+            else if number.compare(max)?.is_gt() {
+                Ok(max.clone())
+            }
+            else {
+                Ok(number.clone())
+            }
+        }
+        "sin" | "cos" | "tan" => {
+            let number = args.number(0, "number")?;
+            let radians =
+                if number.unit.is_empty() { number.value } else { number.convert_to("rad").ok_or("Expected an angle.")? };
+            let result = match function {
+                "sin" => radians.sin(),
+                "cos" => radians.cos(),
+                _ => radians.tan(),
+            };
+            Ok(Value::number(result, ""))
+        }
+
+        "length" => Ok(Value::number(args.value(0, "list")?.as_list().len() as f64, "")),
+        "nth" => {
+            let list = args.value(0, "list")?.as_list();
+            let index = list_index(args.number(1, "n")?, list.len())?;
+            Ok(list[index].clone())
+        }
+        "set-nth" => {
+            let value = args.value(0, "list")?;
+            let mut list = value.as_list();
+            let index = list_index(args.number(1, "n")?, list.len())?;
+            list[index] = args.value(2, "value")?.clone();
+            Ok(Value::List(list, value.separator(), is_bracketed(value)))
+        }
+        "join" => {
+            let (first, second) = (args.value(0, "list1")?, args.value(1, "list2")?);
+            let separator = separator_arg(args.get(2, "separator"))?.unwrap_or_else(|| {
+                if first.as_list().len() > 1 || matches!(first, Value::List(..) | Value::Map(_)) {
+                    first.separator()
+                }
+                else {
+                    second.separator()
+                }
+            });
+            let bracketed = match args.get(3, "bracketed") {
+                None => is_bracketed(first),
+                Some(Value::String(auto, None)) if auto == "auto" => is_bracketed(first),
+                Some(value) => value.is_truthy(),
+            };
+            let mut items = first.as_list();
+            items.extend(second.as_list());
+            Ok(Value::List(items, separator, bracketed))
+        }
+        "append" => {
+            let list = args.value(0, "list")?;
+            let separator = separator_arg(args.get(2, "separator"))?.unwrap_or_else(|| list.separator());
+            let mut items = list.as_list();
+            items.push(args.value(1, "val")?.clone());
+            Ok(Value::List(items, separator, is_bracketed(list)))
+        }
+        "zip" => {
+            let lists = args.positional.iter().map(Value::as_list).collect::<Vec<_>>();
+            let len = lists.iter().map(Vec::len).min().unwrap_or(0);
+            let zipped = (0..len)
+                .map(|i| Value::List(lists.iter().map(|list| list[i].clone()).collect(), ListSeparator::Space, false))
+                .collect();
+            Ok(Value::List(zipped, ListSeparator::Comma, false))
+        }
+        "index" => {
+            let list = args.value(0, "list")?.as_list();
+            let value = args.value(1, "value")?;
+            Ok(list.iter().position(|item| item.equals(value)).map_or(Value::Null, |i| Value::number((i + 1) as f64, "")))
+        }
+        "is-bracketed" => Ok(Value::Bool(is_bracketed(args.value(0, "list")?))),
+        "separator" => Ok(Value::ident(match args.value(0, "list")?.separator() {
+            ListSeparator::Space => "space",
+            ListSeparator::Comma => "comma",
+            ListSeparator::Slash => "slash",
+        })),
+        "slash" => Ok(Value::List(args.positional.clone(), ListSeparator::Slash, false)),
+
+        "map-get" => {
+            let mut current = Value::Map(args.map(0, "map")?);
+            for key in std::iter::once(args.value(1, "key")?).chain(args.rest(2)) {
+                let Some(entries) = current.as_map()
+                else {
+                    return Ok(Value::Null);
+                };
+                match entries.into_iter().find(|(k, _)| k.equals(key)) {
+                    Some((_, value)) => current = value,
+                    None => return Ok(Value::Null),
+                }
+            }
+            Ok(current)
+        }
+        "map-has-key" => {
+            let mut current = Value::Map(args.map(0, "map")?);
+            for key in std::iter::once(args.value(1, "key")?).chain(args.rest(2)) {
+                let Some(entries) = current.as_map()
+                else {
+                    return Ok(Value::Bool(false));
+                };
+                match entries.into_iter().find(|(k, _)| k.equals(key)) {
+                    Some((_, value)) => current = value,
+                    None => return Ok(Value::Bool(false)),
+                }
+            }
+            Ok(Value::Bool(true))
+        }
+        "map-merge" => {
+            let map = args.map(0, "map1")?;
+            // `map.merge($map, $keys..., $map2)` merges into a nested map
+            let (keys, merged) = match args.positional.len() {
+                0..=2 => (&[][..], args.map(1, "map2")?),
+                n => (&args.positional[1..n - 1], args.map(n - 1, "map2")?),
+            };
+            Ok(Value::Map(update_nested(map, keys, &|inner| {
+                let mut inner = inner.and_then(|inner| inner.as_map()).unwrap_or_default();
+                merge_entries(&mut inner, merged.clone(), false);
+                Value::Map(inner)
+            })))
+        }
+        "deep-merge" => {
+            let mut map = args.map(0, "map1")?;
+            merge_entries(&mut map, args.map(1, "map2")?, true);
+            Ok(Value::Map(map))
+        }
+        "map-set" => {
+            let map = args.map(0, "map")?;
+            let n = args.positional.len();
+            if n < 3 {
+                return Err("Expected $args to contain a key and a value.".to_string());
+            }
+            let value = args.positional[n - 1].clone();
+            Ok(Value::Map(update_nested(map, &args.positional[1..n - 1], &|_| value.clone())))
+        }
+        "map-remove" => {
+            let mut map = args.map(0, "map")?;
+            let keys = args.rest(1);
+            map.retain(|(key, _)| !keys.iter().any(|removed| removed.equals(key)));
+            Ok(Value::Map(map))
+        }
+        "map-keys" => {
+            Ok(Value::List(args.map(0, "map")?.into_iter().map(|(key, _)| key).collect(), ListSeparator::Comma, false))
+        }
+        "map-values" => {
+            Ok(Value::List(args.map(0, "map")?.into_iter().map(|(_, value)| value).collect(), ListSeparator::Comma, false))
+        }
+
+        "quote" => Ok(Value::String(args.string(0, "string")?.0.to_string(), Some('"'))),
+        "unquote" => Ok(Value::ident(args.string(0, "string")?.0)),
+        "str-length" => Ok(Value::number(args.string(0, "string")?.0.chars().count() as f64, "")),
+        "str-index" => {
+            let (text, _) = args.string(0, "string")?;
+            let (needle, _) = args.string(1, "substring")?;
+            Ok(text.find(needle).map_or(Value::Null, |i| Value::number((text[..i].chars().count() + 1) as f64, "")))
+        }
+        "str-insert" => {
+            let (text, quote) = args.string(0, "string")?;
+            let (insert, _) = args.string(1, "insert")?;
+            let chars = text.chars().collect::<Vec<_>>();
+            let index = args.number(2, "index")?.value as i64;
+            let at = if index > 0 {
+                (index as usize - 1).min(chars.len())
+            }
+            else {
+                (chars.len() as i64 + index + 1).max(0) as usize
+            };
+            let mut result = chars[..at].iter().collect::<String>();
+            result.push_str(insert);
+            result.extend(&chars[at..]);
+            Ok(Value::String(result, quote))
+        }
+        "str-slice" => {
+            let (text, quote) = args.string(0, "string")?;
+            let chars = text.chars().collect::<Vec<_>>();
+            let len = chars.len() as i64;
+            let position = |n: f64| {
+                let n = n as i64;
+                if n < 0 { (len + n + 1).max(0) } else { n.min(len) }
+            };
+            let start = position(args.number(1, "start-at")?.value).max(1);
+            let end = position(args.optional_number(2, "end-at")?.map_or(-1.0, |end| end.value));
+            let slice = if end < start { String::new() } else { chars[(start - 1) as usize..end as usize].iter().collect() };
+            Ok(Value::String(slice, quote))
+        }
+        "to-upper-case" => {
+            let (text, quote) = args.string(0, "string")?;
+            Ok(Value::String(text.to_ascii_uppercase(), quote))
+        }
+        "to-lower-case" => {
+            let (text, quote) = args.string(0, "string")?;
+            Ok(Value::String(text.to_ascii_lowercase(), quote))
+        }
+
+        "type-of" => Ok(Value::ident(args.value(0, "value")?.type_name())),
+        "inspect" => Ok(Value::ident(args.value(0, "value")?.inspect())),
+        _ => Err(format!("Undefined function {}.", name)),
+    }
+}
+
+/// Prints the call as CSS, for `rgb(var(--c))` or the filter `grayscale(50%)`.
+fn plain_css(name: &str, args: &[Value]) -> Value {
+    let args = args.iter().map(|arg| arg.to_css().unwrap_or_else(|_| arg.inspect())).collect::<Vec<_>>();
+    Value::ident(format!("{}({})", name, args.join(", ")))
+}
+
+/// Spreads `rgb(1 2 3 / 50%)` and `rgb(1 2 3)` into channel arguments.
+fn channels(args: &BuiltinArgs) -> Vec<Value> {
+    if let [Value::List(items, ListSeparator::Space, false)] = args.positional.as_slice() {
+        let mut channels = items.clone();
+        if let Some(Value::List(last, ListSeparator::Slash, false)) = channels.last().cloned() {
+            channels.pop();
+            channels.extend(last);
+        }
+        return channels;
+    }
+    if let [Value::List(items, ListSeparator::Slash, false)] = args.positional.as_slice() {
+        if let [Value::List(rgb, ListSeparator::Space, false), alpha] = items.as_slice() {
+            let mut channels = rgb.clone();
+            channels.push(alpha.clone());
+            return channels;
+        }
+    }
+    args.positional.clone()
+}
+
+fn alpha_channel(value: Option<&Value>) -> Result<f64, String> {
+    match value {
+        None => Ok(1.0),
+        Some(Value::Number(alpha)) if alpha.unit == "%" => Ok(alpha.value / 100.0),
+        Some(Value::Number(alpha)) => Ok(alpha.value),
+        Some(other) => Err(format!("$alpha: {} is not a number.", other.inspect())),
+    }
+}
+
+fn rgb(name: &str, args: &BuiltinArgs) -> Result<Value, String> {
+    let channels = channels(args);
+    if channels.iter().any(|channel| matches!(channel, Value::String(..))) {
+        return Ok(plain_css(name, &args.positional));
+    }
+    // `rgba($color, 0.5)`
+    if let Some(Value::Color(color)) = channels.first() {
+        let mut color = color.clone().computed();
+        color.alpha = alpha_channel(channels.get(1).or_else(|| args.get(1, "alpha")))?.clamp(0.0, 1.0);
+        return Ok(Value::Color(color));
+    }
+    let channel = |index: usize, name: &str| -> Result<f64, String> {
+        let value =
+            channels.get(index).or_else(|| args.get(usize::MAX, name)).ok_or_else(|| format!("Missing argument ${}.", name))?;
+        match value {
+            Value::Number(number) if number.unit == "%" => Ok(number.value * 255.0 / 100.0),
+            Value::Number(number) => Ok(number.value),
+            other => Err(format!("${}: {} is not a number.", name, other.inspect())),
+        }
+    };
+    let alpha = alpha_channel(channels.get(3).or_else(|| args.get(usize::MAX, "alpha")))?;
+    Ok(Value::Color(Color::rgba(channel(0, "red")?, channel(1, "green")?, channel(2, "blue")?, alpha)))
+}
+
+fn hsl(name: &str, args: &BuiltinArgs) -> Result<Value, String> {
+    let channels = channels(args);
+    if channels.iter().any(|channel| matches!(channel, Value::String(..))) {
+        return Ok(plain_css(name, &args.positional));
+    }
+    let channel = |index: usize, name: &str| -> Result<f64, String> {
+        match channels.get(index).or_else(|| args.get(usize::MAX, name)) {
+            Some(Value::Number(number)) if index == 0 => Ok(number.convert_to("deg").unwrap_or(number.value)),
+            Some(Value::Number(number)) => Ok(number.value),
+            Some(other) => Err(format!("${}: {} is not a number.", name, other.inspect())),
+            None => Err(format!("Missing argument ${}.", name)),
+        }
+    };
+    let alpha = alpha_channel(channels.get(3).or_else(|| args.get(usize::MAX, "alpha")))?;
+    Ok(Value::Color(Color::hsla(channel(0, "hue")?, channel(1, "saturation")?, channel(2, "lightness")?, alpha)))
+}
+
+fn adjust_hsl(args: &BuiltinArgs, f: impl Fn(f64, f64, f64) -> (f64, f64, f64)) -> Result<Value, String> {
+    let color = args.color(0, "color")?;
+    let (h, s, l) = color.to_hsl();
+    let (h, s, l) = f(h, s, l);
+    Ok(Value::Color(Color::hsla(h, s.clamp(0.0, 100.0), l.clamp(0.0, 100.0), color.alpha)))
+}
+
+/// Sass's `mix()`: weights the channels by `weight` percent of the first color, taking the alpha of
+/// both colors into account.
+fn mix(first: &Color, second: &Color, weight: f64) -> Color {
+    let p = weight / 100.0;
+    let w = 2.0 * p - 1.0;
+    let a = first.alpha - second.alpha;
+    let w1 = (if w * a == -1.0 { w } else { (w + a) / (1.0 + w * a) } + 1.0) / 2.0;
+    let w2 = 1.0 - w1;
+    Color::rgba(
+        first.red * w1 + second.red * w2,
+        first.green * w1 + second.green * w2,
+        first.blue * w1 + second.blue * w2,
+        first.alpha * p + second.alpha * (1.0 - p),
+    )
+}
+
+/// `color.adjust()`, `color.scale()` and `color.change()` with keyword arguments per channel.
+fn adjust_color(function: &str, args: &BuiltinArgs) -> Result<Value, String> {
+    let color = args.color(0, "color")?;
+    let get = |name: &str| -> Result<Option<f64>, String> {
+        match args.named.iter().find(|(n, _)| n == name) {
+            None => Ok(None),
+            Some((_, Value::Number(number))) => Ok(Some(number.value)),
+            Some((_, other)) => Err(format!("${}: {} is not a number.", name, other.inspect())),
+        }
+    };
+    let (h, s, l) = color.to_hsl();
+    let rgb = [("red", color.red), ("green", color.green), ("blue", color.blue)];
+    let hsl_given = get("hue")?.is_some() || get("saturation")?.is_some() || get("lightness")?.is_some();
+    let apply = |name: &str, current: f64, max: f64| -> Result<f64, String> {
+        let Some(amount) = get(name)?
+        else {
+            return Ok(current);
+        };
+        Ok(match function {
+            "adjust" => current + amount,
+            "change" => amount,
+            _ if amount > 0.0 => current + (max - current) * amount / 100.0,
+            _ => current + current * amount / 100.0,
+        })
+    };
+    let alpha = apply("alpha", color.alpha, 1.0)?.clamp(0.0, 1.0);
+    let result = if hsl_given {
+        if function == "scale" && get("hue")?.is_some() {
+            return Err("$hue: color.scale() doesn't support hue.".to_string());
+        }
+        Color::hsla(
+            apply("hue", h, 360.0)?,
+            apply("saturation", s, 100.0)?.clamp(0.0, 100.0),
+            apply("lightness", l, 100.0)?.clamp(0.0, 100.0),
+            alpha,
+        )
+    }
+    else {
+        let [r, g, b] = rgb.map(|(name, current)| apply(name, current, 255.0));
+        Color::rgba(r?, g?, b?, alpha)
+    };
+    Ok(Value::Color(result))
+}
+
+fn map_number(args: &BuiltinArgs, f: impl Fn(f64) -> f64) -> Result<Value, String> {
+    let number = args.number(0, "number")?;
+    Ok(Value::number(f(number.value), number.unit.clone()))
+}
+
+fn unitless(number: &Number) -> Result<f64, String> {
+    if number.unit.is_empty() { Ok(number.value) } else { Err(format!("Expected {} to have no units.", number)) }
+}
+
+/// `min()`/`max()` of comparable numbers, or the CSS function when units can't be compared (`min(100%, 20rem)`).
+fn min_max(function: &str, args: &BuiltinArgs) -> Result<Value, String> {
+    let Some(first) = args.positional.first()
+    else {
+        return Err("At least one argument must be passed.".to_string());
+    };
+    let mut best = first.clone();
+    for value in &args.positional[1..] {
+        let ordering = match (best.as_number(), value.as_number()) {
+            (Some(_), Some(_)) => match value.compare(&best) {
+                Ok(ordering) => ordering,
+                Err(_) => return Ok(plain_css(function, &args.positional)),
+            },
+            _ => return Ok(plain_css(function, &args.positional)),
+        };
+        if (function == "min" && ordering.is_lt()) || (function == "max" && ordering.is_gt()) {
+            best = value.clone();
+        }
+    }
+    if best.as_number().is_none() {
+        return Ok(plain_css(function, &args.positional));
+    }
+    Ok(best)
+}
+
+/// A 1-based (negative from the end) Sass list index.
+fn list_index(n: &Number, len: usize) -> Result<usize, String> {
+    let n = n.value as i64;
+    let index = if n < 0 { len as i64 + n } else { n - 1 };
+    if n == 0 || index < 0 || index >= len as i64 {
+        return Err(format!("$n: Invalid index {} for a list with {} elements.", n, len));
+    }
+    Ok(index as usize)
+}
+
+fn is_bracketed(value: &Value) -> bool {
+    matches!(value, Value::List(_, _, true))
+}
+
+fn separator_arg(value: Option<&Value>) -> Result<Option<ListSeparator>, String> {
+    match value.and_then(Value::as_str) {
+        None | Some("auto") => Ok(None),
+        Some("space") => Ok(Some(ListSeparator::Space)),
+        Some("comma") => Ok(Some(ListSeparator::Comma)),
+        Some("slash") => Ok(Some(ListSeparator::Slash)),
+        Some(other) => Err(format!("$separator: Must be \"space\", \"comma\", \"slash\", or \"auto\", was {}.", other)),
+    }
+}
+
+/// Replaces the value at the path `keys` in `map` (creating nested maps on the way) with `update`.
+fn update_nested(
+    mut map: Vec<(Value, Value)>,
+    keys: &[Value],
+    update: &dyn Fn(Option<&Value>) -> Value,
+) -> Vec<(Value, Value)> {
+    let Some((key, rest)) = keys.split_first()
+    else {
+        return match update(Some(&Value::Map(map))) {
+            Value::Map(entries) => entries,
+            _ => Vec::new(),
+        };
+    };
+    let position = map.iter().position(|(k, _)| k.equals(key));
+    let current = position.map(|i| map[i].1.clone());
+    let value = if rest.is_empty() {
+        update(current.as_ref())
+    }
+    else {
+        let inner = current.and_then(|value| value.as_map()).unwrap_or_default();
+        Value::Map(update_nested(inner, rest, update))
+    };
+    match position {
+        Some(i) => map[i].1 = value,
+        None => map.push((key.clone(), value)),
+    }
+    map
+}
+
+fn merge_entries(map: &mut Vec<(Value, Value)>, other: Vec<(Value, Value)>, deep: bool) {
+    for (key, value) in other {
+        match map.iter_mut().find(|(k, _)| k.equals(&key)) {
+            Some((_, existing)) => match (deep, existing.as_map(), value.as_map()) {
+                (true, Some(mut inner), Some(other)) if matches!(existing, Value::Map(_)) => {
+                    merge_entries(&mut inner, other, true);
+                    *existing = Value::Map(inner);
+                }
+                _ => *existing = value,
+            },
+            None => map.push((key, value)),
+        }
+    }
+}
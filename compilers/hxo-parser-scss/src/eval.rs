@@ -0,0 +1,1231 @@
+//! Evaluates the syntax tree into a CSS [`Stylesheet`]: scopes, mixins and functions, control flow,
+//! `@extend` and the module system (`@use`, `@forward` and `@import` through the load paths).
+
+use crate::{
+    ast::{
+        Arguments, BinaryOp, Callable, ContentBlock, Expr, Interpolation, InterpolationPart, ListSeparator, MessageKind,
+        Parameters, Stmt, UnaryOp,
+    },
+    builtins::{self, BuiltinArgs},
+    parser::{normalize_name, parse_scss},
+    selector::{extend_selector, resolve_selectors, split_selector_list},
+    value::Value,
+};
+use hxo_ir::{AtRule, CssNode, Declaration, StyleRule, Stylesheet};
+use hxo_types::{Error, Result, Span};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// Parses the source of a loaded file; registered per file extension with [`Evaluator::with_syntax`].
+pub type SyntaxParser = fn(&str) -> Result<Vec<Stmt>>;
+
+/// Positional and named argument values.
+type EvaluatedArgs = (Vec<Value>, Vec<(String, Value)>);
+
+const MAX_CALL_DEPTH: usize = 256;
+const MAX_ITERATIONS: usize = 100_000;
+
+pub struct Evaluator {
+    load_paths: Vec<PathBuf>,
+    syntaxes: Vec<(&'static str, SyntaxParser)>,
+    compressed: bool,
+    /// Stylus: an unquoted identifier naming a variable stands for its value.
+    bare_variables: bool,
+    modules: HashMap<PathBuf, Rc<Module>>,
+    builtin_modules: HashMap<&'static str, Rc<Module>>,
+    /// Files being evaluated, to report import loops.
+    loading: Vec<PathBuf>,
+    env: Env,
+    frames: Vec<Frame>,
+    /// The selector list of the innermost style rule, empty outside rules.
+    selectors: Vec<String>,
+    /// `font` while evaluating `font: { family: ... }`.
+    property_prefix: String,
+    contents: Vec<Option<Rc<ContentClosure>>>,
+    extensions: Vec<Extension>,
+    file: Option<PathBuf>,
+    /// Above zero while evaluating another file, whose spans don't point into the entry source.
+    imported_depth: usize,
+    in_function: bool,
+    call_depth: usize,
+    /// The statement being evaluated, for error spans.
+    span: Span,
+    messages: Vec<String>,
+}
+
+#[derive(Clone)]
+struct Env {
+    module: Rc<Module>,
+    /// Innermost last; the first is the module's global scope.
+    scopes: Vec<Rc<RefCell<Scope>>>,
+}
+
+#[derive(Default)]
+struct Scope {
+    variables: HashMap<String, Value>,
+    mixins: HashMap<String, Closure>,
+    functions: HashMap<String, Closure>,
+    /// Set for `@if`/`@each`/`@for`/`@while` blocks at the top level, which assign global variables.
+    semi_global: bool,
+}
+
+#[derive(Clone)]
+struct Closure {
+    callable: Rc<Callable>,
+    env: Env,
+    location: Location,
+}
+
+struct ContentClosure {
+    block: Rc<ContentBlock>,
+    env: Env,
+    location: Location,
+}
+
+/// The file a mixin, function or content block was written in, for its spans and errors.
+#[derive(Clone)]
+struct Location {
+    file: Option<PathBuf>,
+    imported_depth: usize,
+}
+
+#[derive(Default)]
+struct Module {
+    scope: Rc<RefCell<Scope>>,
+    uses: RefCell<HashMap<String, Rc<Module>>>,
+    star_uses: RefCell<Vec<Rc<Module>>>,
+    forwards: RefCell<Vec<Forwarded>>,
+    /// `math` for `sass:math`.
+    builtin: Option<&'static str>,
+    /// Values from `@use ... with (...)` that replace the module's `!default`s.
+    config: HashMap<String, Value>,
+}
+
+struct Forwarded {
+    module: Rc<Module>,
+    prefix: Option<String>,
+    show: Option<Vec<String>>,
+    hide: Option<Vec<String>>,
+}
+
+/// CSS being built: the nodes of the stylesheet or an at-rule block, and the style rule among them
+/// that receives declarations.
+#[derive(Default)]
+struct Frame {
+    nodes: Vec<CssNode>,
+    rule: Option<usize>,
+}
+
+struct Extension {
+    target: String,
+    extenders: Vec<String>,
+    optional: bool,
+    span: Span,
+}
+
+impl Env {
+    fn new(module: Rc<Module>) -> Self {
+        let scope = module.scope.clone();
+        Self { module, scopes: vec![scope] }
+    }
+}
+
+impl Module {
+    fn with_config(config: HashMap<String, Value>) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    /// A member of the module or of a module it forwards.
+    fn lookup<T>(&self, name: &str, variable: bool, get: &dyn Fn(&Scope, &str) -> Option<T>) -> Option<T> {
+        if let Some(found) = get(&self.scope.borrow(), name) {
+            return Some(found);
+        }
+        for forward in self.forwards.borrow().iter() {
+            let inner = match &forward.prefix {
+                Some(prefix) => match name.strip_prefix(prefix.as_str()) {
+                    Some(inner) => inner,
+                    None => continue,
+                },
+                None => name,
+            };
+            let member = if variable { format!("${}", inner) } else { inner.to_string() };
+            if forward.show.as_ref().is_some_and(|show| !show.contains(&member))
+                || forward.hide.as_ref().is_some_and(|hide| hide.contains(&member))
+            {
+                continue;
+            }
+            if let Some(found) = forward.module.lookup(inner, variable, get) {
+                return Some(found);
+            }
+        }
+        None
+    }
+}
+
+impl Evaluator {
+    /// An evaluator resolving `@use`, `@forward` and `@import` against `load_paths` after the importing
+    /// file's directory.
+    pub fn new(load_paths: Vec<PathBuf>) -> Self {
+        Self {
+            load_paths,
+            syntaxes: vec![("scss", parse_scss), ("css", parse_scss)],
+            compressed: false,
+            bare_variables: false,
+            modules: HashMap::new(),
+            builtin_modules: HashMap::new(),
+            loading: Vec::new(),
+            env: Env::new(Rc::new(Module::default())),
+            frames: Vec::new(),
+            selectors: Vec::new(),
+            property_prefix: String::new(),
+            contents: Vec::new(),
+            extensions: Vec::new(),
+            file: None,
+            imported_depth: 0,
+            in_function: false,
+            call_depth: 0,
+            span: Span::unknown(),
+            messages: Vec::new(),
+        }
+    }
+
+    /// Loads files with `extension` through `parser`, ahead of the built-in `scss` and `css`.
+    pub fn with_syntax(mut self, extension: &'static str, parser: SyntaxParser) -> Self {
+        self.syntaxes.insert(0, (extension, parser));
+        self
+    }
+
+    /// Prints comma-separated values without the space after the comma.
+    pub fn with_compressed(mut self, compressed: bool) -> Self {
+        self.compressed = compressed;
+        self
+    }
+
+    /// Resolves unquoted identifiers that name a variable, as Stylus references variables without `$`.
+    pub fn with_bare_variables(mut self, bare_variables: bool) -> Self {
+        self.bare_variables = bare_variables;
+        self
+    }
+
+    /// `@debug` and `@warn` output of the last evaluation.
+    pub fn messages(&self) -> &[String] {
+        &self.messages
+    }
+
+    /// Evaluates the entry stylesheet; `file` is where it was read from, for relative imports.
+    pub fn evaluate(&mut self, stmts: &[Stmt], file: Option<&Path>) -> Result<Stylesheet> {
+        self.file = file.map(Path::to_path_buf);
+        self.frames = vec![Frame::default()];
+        let result = self.exec_block(stmts);
+        let root = self.frames.pop().unwrap_or_default();
+        self.release();
+        result?;
+
+        let mut nodes = root.nodes;
+        self.apply_extensions(&mut nodes)?;
+        remove_empty(&mut nodes);
+        Ok(Stylesheet { nodes })
+    }
+
+    /// Mixins and functions hold their defining scope, which holds them; clearing the module scopes
+    /// frees those cycles.
+    fn release(&mut self) {
+        for module in self.modules.values().chain(std::iter::once(&self.env.module)) {
+            let mut scope = module.scope.borrow_mut();
+            scope.mixins.clear();
+            scope.functions.clear();
+        }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        let message = message.into();
+        match (&self.file, self.imported_depth) {
+            (Some(file), depth) if depth > 0 => {
+                Err(Error::parse_error(format!("{} (in {})", message, file.display()), Span::unknown()))
+            }
+            _ => Err(Error::parse_error(message, self.span)),
+        }
+    }
+
+    /// The span for output nodes: statements of imported files have none.
+    fn output_span(&self, span: Span) -> Span {
+        if self.imported_depth > 0 { Span::unknown() } else { span }
+    }
+
+    fn exec_block(&mut self, stmts: &[Stmt]) -> Result<Option<Value>> {
+        for stmt in stmts {
+            if let Some(value) = self.exec(stmt)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Runs `stmts` in a new scope; `flow` for control-flow blocks.
+    fn exec_scoped(&mut self, stmts: &[Stmt], flow: bool) -> Result<Option<Value>> {
+        self.push_scope(flow);
+        let result = self.exec_block(stmts);
+        self.env.scopes.pop();
+        result
+    }
+
+    fn exec(&mut self, stmt: &Stmt) -> Result<Option<Value>> {
+        self.span = stmt.span();
+        match stmt {
+            Stmt::Variable(decl) => {
+                if let Some(namespace) = &decl.namespace {
+                    let module = self.namespace(namespace)?;
+                    if !module.scope.borrow().variables.contains_key(&decl.name) {
+                        return self.error(format!("Undefined variable ${}.{}.", namespace, decl.name));
+                    }
+                    let value = self.eval(&decl.value)?;
+                    module.scope.borrow_mut().variables.insert(decl.name.clone(), value);
+                    return Ok(None);
+                }
+                if decl.default {
+                    let configured =
+                        if self.env.scopes.len() == 1 { self.env.module.config.get(&decl.name).cloned() } else { None };
+                    if let Some(value) = configured {
+                        self.set_variable(&decl.name, value, decl.global);
+                        return Ok(None);
+                    }
+                    if self.lookup_variable(&decl.name).is_some_and(|value| value != Value::Null) {
+                        return Ok(None);
+                    }
+                }
+                let value = self.eval(&decl.value)?;
+                self.set_variable(&decl.name, value, decl.global);
+            }
+            Stmt::Rule { selector, body, span } => {
+                let text = collapse_whitespace(&self.interpolate(selector)?);
+                let selectors = resolve_selectors(&self.selectors, &text);
+                self.exec_rule(selectors, body, *span)?;
+            }
+            Stmt::Declaration { name, value, important, children, span } => {
+                let name = self.interpolate(name)?;
+                let property =
+                    if self.property_prefix.is_empty() { name } else { format!("{}-{}", self.property_prefix, name) };
+                if let Some(value) = value {
+                    let value = self.eval_css_value(value)?;
+                    if !value.is_blank() {
+                        let text = match value.serialize(self.compressed) {
+                            Ok(text) => text,
+                            Err(message) => return self.error(message),
+                        };
+                        if self.frames.len() == 1 && self.frames[0].rule.is_none() {
+                            return self.error("Declarations may only be used within style rules.");
+                        }
+                        let span = self.output_span(*span);
+                        self.push_child(CssNode::Declaration(Declaration {
+                            property: property.clone(),
+                            value: text,
+                            important: *important,
+                            span,
+                        }));
+                    }
+                }
+                if !children.is_empty() {
+                    let saved = std::mem::replace(&mut self.property_prefix, property);
+                    let result = self.exec_block(children);
+                    self.property_prefix = saved;
+                    result?;
+                }
+            }
+            Stmt::AtRule { name, prelude, body, span } => {
+                let prelude = collapse_whitespace(&self.interpolate(prelude)?);
+                let span = self.output_span(*span);
+                match body {
+                    None => self.push_child(CssNode::AtRule(AtRule { name: name.clone(), prelude, block: None, span })),
+                    Some(body) => {
+                        let keyframes = name.ends_with("keyframes");
+                        let mut frame = Frame::default();
+                        if !keyframes && !self.selectors.is_empty() {
+                            // Declarations inside `@media` in a rule go to a copy of the rule inside it
+                            frame.nodes.push(CssNode::Rule(StyleRule {
+                                selector: self.selectors.join(", "),
+                                children: Vec::new(),
+                                selector_span: span,
+                                span,
+                            }));
+                            frame.rule = Some(0);
+                        }
+                        let saved_selectors =
+                            if keyframes { std::mem::take(&mut self.selectors) } else { self.selectors.clone() };
+                        self.frames.push(frame);
+                        let result = self.exec_scoped(body, false);
+                        let frame = self.frames.pop().unwrap_or_default();
+                        self.selectors = saved_selectors;
+                        result?;
+                        let node = CssNode::AtRule(AtRule { name: name.clone(), prelude, block: Some(frame.nodes), span });
+                        if let Some(parent) = self.frames.last_mut() {
+                            parent.nodes.push(node);
+                        }
+                    }
+                }
+            }
+            Stmt::Mixin(callable) => {
+                let closure = Closure { callable: callable.clone(), env: self.env.clone(), location: self.location() };
+                self.innermost().borrow_mut().mixins.insert(callable.name.clone(), closure);
+            }
+            Stmt::Function(callable) => {
+                let closure = Closure { callable: callable.clone(), env: self.env.clone(), location: self.location() };
+                self.innermost().borrow_mut().functions.insert(callable.name.clone(), closure);
+            }
+            Stmt::Include { namespace, name, args, content, .. } => {
+                let Some(mixin) =
+                    self.lookup_member(namespace.as_deref(), name, |scope, name| scope.mixins.get(name).cloned())?
+                else {
+                    return self.error(format!("Undefined mixin {}.", name));
+                };
+                let content = content.as_ref().map(|block| {
+                    Rc::new(ContentClosure { block: block.clone(), env: self.env.clone(), location: self.location() })
+                });
+                self.contents.push(content);
+                let result = self.invoke(&mixin, args, false);
+                self.contents.pop();
+                result?;
+            }
+            Stmt::Content { args, .. } => {
+                let Some(content) = self.contents.pop().flatten()
+                else {
+                    self.contents.push(None);
+                    return Ok(None);
+                };
+                // The content block sees the `@content` of the mixin it was written in, not its own
+                let result = self.bind(&content.block.params, args, content.env.clone()).and_then(|env| {
+                    let saved = std::mem::replace(&mut self.env, env);
+                    let location = self.enter(content.location.clone());
+                    let result = self.exec_block(&content.block.body);
+                    self.enter(location);
+                    self.env = saved;
+                    result
+                });
+                self.contents.push(Some(content));
+                result?;
+            }
+            Stmt::Return { value, .. } => {
+                if !self.in_function {
+                    return self.error("@return may only be used within a function.");
+                }
+                return self.eval(value).map(Some);
+            }
+            Stmt::If { clauses, otherwise, .. } => {
+                for (condition, body) in clauses {
+                    if self.eval(condition)?.is_truthy() {
+                        return self.exec_scoped(body, true);
+                    }
+                }
+                if let Some(body) = otherwise {
+                    return self.exec_scoped(body, true);
+                }
+            }
+            Stmt::Each { variables, list, body, .. } => {
+                let list = self.eval(list)?;
+                for item in list.as_list() {
+                    self.push_scope(true);
+                    if let [variable] = variables.as_slice() {
+                        self.declare_local(variable, item);
+                    }
+                    else {
+                        let values = item.as_list();
+                        for (i, variable) in variables.iter().enumerate() {
+                            self.declare_local(variable, values.get(i).cloned().unwrap_or(Value::Null));
+                        }
+                    }
+                    let result = self.exec_block(body);
+                    self.env.scopes.pop();
+                    if let Some(value) = result? {
+                        return Ok(Some(value));
+                    }
+                }
+            }
+            Stmt::For { variable, from, to, inclusive, body, .. } => {
+                let from = self.eval(from)?;
+                let to = self.eval(to)?;
+                let (Some(from), Some(to)) = (from.as_number(), to.as_number())
+                else {
+                    return self.error("@for bounds must be numbers.");
+                };
+                let Some(end) = to.convert_to(&from.unit)
+                else {
+                    return self.error(format!("Incompatible units {} and {}.", from.unit, to.unit));
+                };
+                let unit = if from.unit.is_empty() { to.unit.clone() } else { from.unit.clone() };
+                let (start, end) = (from.value.round() as i64, end.round() as i64);
+                let step = if start <= end { 1 } else { -1 };
+                let end = if *inclusive { end + step } else { end };
+                if (end - start).unsigned_abs() as usize > MAX_ITERATIONS {
+                    return self.error("@for loop has too many iterations.");
+                }
+                let mut i = start;
+                while i != end {
+                    self.push_scope(true);
+                    self.declare_local(variable, Value::number(i as f64, unit.clone()));
+                    let result = self.exec_block(body);
+                    self.env.scopes.pop();
+                    if let Some(value) = result? {
+                        return Ok(Some(value));
+                    }
+                    i += step;
+                }
+            }
+            Stmt::While { condition, body, .. } => {
+                let mut iterations = 0;
+                while self.eval(condition)?.is_truthy() {
+                    iterations += 1;
+                    if iterations > MAX_ITERATIONS {
+                        return self.error("@while loop has too many iterations.");
+                    }
+                    if let Some(value) = self.exec_scoped(body, true)? {
+                        return Ok(Some(value));
+                    }
+                }
+            }
+            Stmt::Extend { selector, optional, span } => {
+                if self.selectors.is_empty() {
+                    return self.error("@extend may only be used within style rules.");
+                }
+                let text = collapse_whitespace(&self.interpolate(selector)?);
+                for target in split_selector_list(&text) {
+                    self.extensions.push(Extension {
+                        target,
+                        extenders: self.selectors.clone(),
+                        optional: *optional,
+                        span: *span,
+                    });
+                }
+            }
+            Stmt::Use { url, namespace, config, .. } => {
+                let config = self.eval_configuration(config)?;
+                let module = self.load_module(url, config, true)?;
+                match namespace.as_deref() {
+                    Some("*") => self.env.module.star_uses.borrow_mut().push(module),
+                    Some(namespace) => {
+                        self.env.module.uses.borrow_mut().insert(namespace.to_string(), module);
+                    }
+                    None => {
+                        self.env.module.uses.borrow_mut().insert(default_namespace(url), module);
+                    }
+                }
+            }
+            Stmt::Forward { url, prefix, show, hide, config, .. } => {
+                let mut forwarded = self.eval_configuration(config)?;
+                // Configuration given to this module flows on to the modules it forwards
+                for (name, value) in &self.env.module.config {
+                    let name = match prefix {
+                        Some(prefix) => match name.strip_prefix(prefix.as_str()) {
+                            Some(name) => name,
+                            None => continue,
+                        },
+                        None => name,
+                    };
+                    forwarded.entry(name.to_string()).or_insert_with(|| value.clone());
+                }
+                let module = self.load_module(url, forwarded, !config.is_empty())?;
+                self.env.module.forwards.borrow_mut().push(Forwarded {
+                    module,
+                    prefix: prefix.clone(),
+                    show: show.clone(),
+                    hide: hide.clone(),
+                });
+            }
+            Stmt::Import { urls, .. } => {
+                for url in urls {
+                    let Some(path) = self.resolve(url)
+                    else {
+                        return self.error(format!("Can't find stylesheet to import: \"{}\".", url));
+                    };
+                    if self.loading.contains(&path) {
+                        return self.error(format!("This file is already being loaded: \"{}\".", url));
+                    }
+                    let stmts = self.parse_file(&path)?;
+                    let saved_file = self.file.replace(path.clone());
+                    self.loading.push(path);
+                    self.imported_depth += 1;
+                    let result = self.exec_block(&stmts);
+                    self.imported_depth -= 1;
+                    self.loading.pop();
+                    self.file = saved_file;
+                    result?;
+                }
+            }
+            Stmt::Message { kind, value, .. } => {
+                let value = self.eval(value)?;
+                let text = match &value {
+                    Value::String(text, _) => text.clone(),
+                    other => other.inspect(),
+                };
+                match kind {
+                    MessageKind::Debug => self.messages.push(format!("DEBUG: {}", text)),
+                    MessageKind::Warn => self.messages.push(format!("WARNING: {}", text)),
+                    MessageKind::Error => return self.error(text),
+                }
+            }
+            Stmt::AtRoot { selector, body, span } => {
+                let parents = std::mem::take(&mut self.selectors);
+                self.frames.push(Frame::default());
+                let result = match selector {
+                    Some(selector) => self.interpolate(selector).and_then(|text| {
+                        let text = collapse_whitespace(&text);
+                        let parents: &[String] = if text.contains('&') { &parents } else { &[] };
+                        let selectors = resolve_selectors(parents, &text);
+                        self.exec_rule(selectors, body, *span)
+                    }),
+                    None => self.exec_scoped(body, false).map(|_| ()),
+                };
+                let frame = self.frames.pop().unwrap_or_default();
+                self.selectors = parents;
+                result?;
+                self.frames[0].nodes.extend(frame.nodes);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Opens a style rule: its declarations go into a new rule node and nested rules follow it.
+    fn exec_rule(&mut self, selectors: Vec<String>, body: &[Stmt], span: Span) -> Result<()> {
+        let span = self.output_span(span);
+        let Some(frame) = self.frames.last_mut()
+        else {
+            return Ok(());
+        };
+        frame.nodes.push(CssNode::Rule(StyleRule {
+            selector: selectors.join(", "),
+            children: Vec::new(),
+            selector_span: span,
+            span,
+        }));
+        let saved_rule = frame.rule.replace(frame.nodes.len() - 1);
+        let saved_selectors = std::mem::replace(&mut self.selectors, selectors);
+        let frames = self.frames.len();
+        let result = self.exec_scoped(body, false);
+        self.selectors = saved_selectors;
+        self.frames[frames - 1].rule = saved_rule;
+        result.map(|_| ())
+    }
+
+    /// Adds a declaration or childless at-rule to the current style rule, or to the current block.
+    fn push_child(&mut self, node: CssNode) {
+        let Some(frame) = self.frames.last_mut()
+        else {
+            return;
+        };
+        match frame.rule.and_then(|index| frame.nodes.get_mut(index)) {
+            Some(CssNode::Rule(rule)) => rule.children.push(node),
+            _ => frame.nodes.push(node),
+        }
+    }
+
+    fn innermost(&self) -> Rc<RefCell<Scope>> {
+        self.env.scopes.last().cloned().unwrap_or_else(|| self.env.module.scope.clone())
+    }
+
+    fn push_scope(&mut self, flow: bool) {
+        let semi_global = flow && (self.env.scopes.len() == 1 || self.innermost().borrow().semi_global);
+        self.env.scopes.push(Rc::new(RefCell::new(Scope { semi_global, ..Scope::default() })));
+    }
+
+    fn declare_local(&mut self, name: &str, value: Value) {
+        self.innermost().borrow_mut().variables.insert(name.to_string(), value);
+    }
+
+    /// Assigns a variable: an existing local one, a global one with `!global` or from a top-level
+    /// control-flow block, and otherwise a new local.
+    fn set_variable(&mut self, name: &str, value: Value, global: bool) {
+        if global || self.env.scopes.len() == 1 {
+            self.env.module.scope.borrow_mut().variables.insert(name.to_string(), value);
+            return;
+        }
+        let semi_global = self.innermost().borrow().semi_global;
+        for (i, scope) in self.env.scopes.iter().enumerate().rev() {
+            if scope.borrow().variables.contains_key(name) {
+                if i == 0 && !semi_global {
+                    break;
+                }
+                scope.borrow_mut().variables.insert(name.to_string(), value);
+                return;
+            }
+        }
+        self.declare_local(name, value);
+    }
+
+    fn lookup_variable(&self, name: &str) -> Option<Value> {
+        for scope in self.env.scopes.iter().rev() {
+            if let Some(value) = scope.borrow().variables.get(name) {
+                return Some(value.clone());
+            }
+        }
+        self.env
+            .module
+            .star_uses
+            .borrow()
+            .iter()
+            .find_map(|module| module.lookup(name, true, &|scope, name| scope.variables.get(name).cloned()))
+    }
+
+    fn get_variable(&self, namespace: Option<&str>, name: &str) -> Result<Value> {
+        let found = match namespace {
+            Some(namespace) => {
+                let module = self.namespace(namespace)?;
+                match module.builtin {
+                    Some(builtin) => builtins::module_variable(builtin, name),
+                    None => module.lookup(name, true, &|scope, name| scope.variables.get(name).cloned()),
+                }
+            }
+            None => self.lookup_variable(name),
+        };
+        match found {
+            Some(value) => Ok(value),
+            None => self.error(format!("Undefined variable ${}.", name)),
+        }
+    }
+
+    fn namespace(&self, namespace: &str) -> Result<Rc<Module>> {
+        match self.env.module.uses.borrow().get(namespace) {
+            Some(module) => Ok(module.clone()),
+            None => self.error(format!("There is no module with the namespace \"{}\".", namespace)),
+        }
+    }
+
+    /// A mixin or function, from the scopes and `@use ... as *` modules or from a namespace.
+    fn lookup_member(
+        &self,
+        namespace: Option<&str>,
+        name: &str,
+        get: fn(&Scope, &str) -> Option<Closure>,
+    ) -> Result<Option<Closure>> {
+        if let Some(namespace) = namespace {
+            return Ok(self.namespace(namespace)?.lookup(name, false, &get));
+        }
+        for scope in self.env.scopes.iter().rev() {
+            if let Some(found) = get(&scope.borrow(), name) {
+                return Ok(Some(found));
+            }
+        }
+        Ok(self.env.module.star_uses.borrow().iter().find_map(|module| module.lookup(name, false, &get)))
+    }
+
+    /// Evaluates `args` here and binds them to `params` in a new scope on top of `env`.
+    fn bind(&mut self, params: &Parameters, args: &Arguments, env: Env) -> Result<Env> {
+        let (positional, named) = self.eval_arguments(args)?;
+        let mut env = env;
+        env.scopes.push(Rc::new(RefCell::new(Scope::default())));
+        let saved = std::mem::replace(&mut self.env, env);
+        let result = self.bind_parameters(params, positional, named);
+        let env = std::mem::replace(&mut self.env, saved);
+        result.map(|_| env)
+    }
+
+    fn bind_parameters(&mut self, params: &Parameters, positional: Vec<Value>, mut named: Vec<(String, Value)>) -> Result<()> {
+        let passed = positional.len();
+        let mut positional = positional.into_iter();
+        for (name, default) in &params.params {
+            let value = if let Some(value) = positional.next() {
+                value
+            }
+            else if let Some(index) = named.iter().position(|(n, _)| n == name) {
+                named.remove(index).1
+            }
+            else if let Some(default) = default {
+                self.eval(default)?
+            }
+            else {
+                return self.error(format!("Missing argument ${}.", name));
+            };
+            self.declare_local(name, value);
+        }
+        let extra = positional.collect::<Vec<_>>();
+        match &params.rest {
+            Some(rest) => self.declare_local(rest, Value::List(extra, ListSeparator::Comma, false)),
+            None if !extra.is_empty() => {
+                return self.error(format!("Only {} arguments allowed, but {} were passed.", params.params.len(), passed));
+            }
+            None => {
+                if let Some((name, _)) = named.first() {
+                    return self.error(format!("No argument named ${}.", name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn eval_arguments(&mut self, args: &Arguments) -> Result<EvaluatedArgs> {
+        let mut positional = Vec::with_capacity(args.positional.len());
+        for arg in &args.positional {
+            positional.push(self.eval(arg)?);
+        }
+        let mut named = Vec::with_capacity(args.named.len());
+        for (name, arg) in &args.named {
+            named.push((name.clone(), self.eval(arg)?));
+        }
+        if let Some(rest) = &args.rest {
+            match self.eval(rest)? {
+                Value::Map(entries) => {
+                    for (key, value) in entries {
+                        named.push((normalize_name(&key.interpolated().unwrap_or_default()), value));
+                    }
+                }
+                Value::List(items, ..) => positional.extend(items),
+                other => positional.push(other),
+            }
+        }
+        Ok((positional, named))
+    }
+
+    /// Runs a mixin (`function` false) or function body with its arguments bound.
+    fn invoke(&mut self, closure: &Closure, args: &Arguments, function: bool) -> Result<Option<Value>> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return self.error(format!("Stack depth exceeded calling {}.", closure.callable.name));
+        }
+        let span = self.span;
+        let env = self.bind(&closure.callable.params, args, closure.env.clone())?;
+        let saved_env = std::mem::replace(&mut self.env, env);
+        let saved_function = std::mem::replace(&mut self.in_function, function);
+        let location = self.enter(closure.location.clone());
+        self.call_depth += 1;
+        let result = self.exec_block(&closure.callable.body);
+        self.call_depth -= 1;
+        self.enter(location);
+        self.in_function = saved_function;
+        self.env = saved_env;
+        self.span = span;
+        result
+    }
+
+    fn location(&self) -> Location {
+        Location { file: self.file.clone(), imported_depth: self.imported_depth }
+    }
+
+    /// Switches to evaluating code from `location`, returning the previous one.
+    fn enter(&mut self, location: Location) -> Location {
+        let file = std::mem::replace(&mut self.file, location.file);
+        let imported_depth = std::mem::replace(&mut self.imported_depth, location.imported_depth);
+        Location { file, imported_depth }
+    }
+
+    fn eval_configuration(&mut self, config: &[(String, Expr)]) -> Result<HashMap<String, Value>> {
+        let mut values = HashMap::new();
+        for (name, expr) in config {
+            values.insert(name.clone(), self.eval(expr)?);
+        }
+        Ok(values)
+    }
+
+    /// Loads a module once; `strict` refuses to configure a module that is already loaded.
+    fn load_module(&mut self, url: &str, config: HashMap<String, Value>, strict: bool) -> Result<Rc<Module>> {
+        if let Some(name) = url.strip_prefix("sass:") {
+            let Some(name) = builtins::MODULES.iter().copied().find(|module| *module == name)
+            else {
+                return self.error(format!("Can't find stylesheet to import: \"{}\".", url));
+            };
+            let module = self
+                .builtin_modules
+                .entry(name)
+                .or_insert_with(|| Rc::new(Module { builtin: Some(name), ..Module::default() }));
+            return Ok(module.clone());
+        }
+        let Some(path) = self.resolve(url)
+        else {
+            return self.error(format!("Can't find stylesheet to import: \"{}\".", url));
+        };
+        if let Some(module) = self.modules.get(&path) {
+            if strict && !config.is_empty() {
+                return self.error(format!("\"{}\" was already loaded, so it can't be configured using \"with\".", url));
+            }
+            return Ok(module.clone());
+        }
+        if self.loading.contains(&path) {
+            return self.error(format!("Module loop: \"{}\" is already being loaded.", url));
+        }
+
+        let stmts = self.parse_file(&path)?;
+        let module = Rc::new(Module::with_config(config));
+        let saved_env = std::mem::replace(&mut self.env, Env::new(module.clone()));
+        let saved_selectors = std::mem::take(&mut self.selectors);
+        let saved_file = self.file.replace(path.clone());
+        self.loading.push(path.clone());
+        self.imported_depth += 1;
+        self.frames.push(Frame::default());
+        let result = self.exec_block(&stmts);
+        let frame = self.frames.pop().unwrap_or_default();
+        self.imported_depth -= 1;
+        self.loading.pop();
+        self.file = saved_file;
+        self.selectors = saved_selectors;
+        self.env = saved_env;
+        result?;
+
+        // A module's CSS is emitted once, where it is first loaded
+        if let Some(parent) = self.frames.last_mut() {
+            parent.nodes.extend(frame.nodes);
+        }
+        self.modules.insert(path, module.clone());
+        Ok(module)
+    }
+
+    fn parse_file(&self, path: &Path) -> Result<Vec<Stmt>> {
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("scss");
+        let parser =
+            self.syntaxes.iter().find(|(ext, _)| *ext == extension).map_or(parse_scss as SyntaxParser, |(_, parser)| *parser);
+        let source = std::fs::read_to_string(path)?;
+        parser(&source).map_err(|err| Error::parse_error(format!("{} (in {})", err, path.display()), Span::unknown()))
+    }
+
+    /// Finds the file `url` refers to next to the current file (the working directory for the entry
+    /// stylesheet) or in a load path, trying partials (`_name.scss`) and `_index` files.
+    fn resolve(&self, url: &str) -> Option<PathBuf> {
+        let base = match &self.file {
+            Some(file) => file.parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => PathBuf::from("."),
+        };
+        std::iter::once(base).chain(self.load_paths.iter().cloned()).find_map(|base| self.find_file(&base.join(url)))
+    }
+
+    fn find_file(&self, path: &Path) -> Option<PathBuf> {
+        let found = |candidate: PathBuf| candidate.is_file().then(|| std::fs::canonicalize(&candidate).unwrap_or(candidate));
+        let extensions = self.syntaxes.iter().map(|(extension, _)| *extension).collect::<Vec<_>>();
+        if path.extension().and_then(|extension| extension.to_str()).is_some_and(|extension| extensions.contains(&extension)) {
+            return found(path.to_path_buf());
+        }
+        let name = path.file_name()?.to_string_lossy().into_owned();
+        let dir = path.parent()?;
+        for extension in &extensions {
+            for candidate in [dir.join(format!("{}.{}", name, extension)), dir.join(format!("_{}.{}", name, extension))] {
+                if let Some(found) = found(candidate) {
+                    return Some(found);
+                }
+            }
+        }
+        extensions.iter().find_map(|extension| {
+            found(path.join(format!("_index.{}", extension))).or_else(|| found(path.join(format!("index.{}", extension))))
+        })
+    }
+
+    fn interpolate(&mut self, text: &Interpolation) -> Result<String> {
+        let mut out = String::new();
+        for part in &text.parts {
+            match part {
+                InterpolationPart::Text(text) => out.push_str(text),
+                InterpolationPart::Expr(expr) => {
+                    let value = self.eval(expr)?;
+                    match value.interpolated() {
+                        Ok(text) => out.push_str(&text),
+                        Err(message) => return self.error(message),
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Evaluates a declaration value, where `/` between two number literals is a separator
+    /// (`font: 12px/1.5`) rather than a division.
+    fn eval_css_value(&mut self, expr: &Expr) -> Result<Value> {
+        match expr {
+            Expr::List { items, separator, bracketed } => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(self.eval_css_value(item)?);
+                }
+                Ok(Value::List(values, *separator, *bracketed))
+            }
+            Expr::Binary(left, BinaryOp::Div, right) if is_slash_literal(left) && is_slash_literal(right) => {
+                Ok(Value::List(vec![self.eval_css_value(left)?, self.eval_css_value(right)?], ListSeparator::Slash, false))
+            }
+            _ => self.eval(expr),
+        }
+    }
+
+    pub(crate) fn eval(&mut self, expr: &Expr) -> Result<Value> {
+        match expr {
+            Expr::Number(value, unit) => Ok(Value::number(*value, unit.clone())),
+            Expr::Color(color) => Ok(Value::Color(color.clone())),
+            Expr::String { value, quote } => {
+                if self.bare_variables && quote.is_none() {
+                    if let Some(value) = value.as_plain().and_then(|name| self.lookup_variable(&normalize_name(&name))) {
+                        return Ok(value);
+                    }
+                }
+                Ok(Value::String(self.interpolate(value)?, *quote))
+            }
+            Expr::Bool(value) => Ok(Value::Bool(*value)),
+            Expr::Null => Ok(Value::Null),
+            Expr::Variable { namespace, name } => self.get_variable(namespace.as_deref(), name),
+            Expr::List { items, separator, bracketed } => {
+                let mut values = Vec::with_capacity(items.len());
+                for item in items {
+                    values.push(self.eval(item)?);
+                }
+                Ok(Value::List(values, *separator, *bracketed))
+            }
+            Expr::Map(entries) => {
+                let mut map: Vec<(Value, Value)> = Vec::with_capacity(entries.len());
+                for (key, value) in entries {
+                    let key = self.eval(key)?;
+                    if map.iter().any(|(existing, _)| existing.equals(&key)) {
+                        return self.error(format!("Duplicate key {} in map.", key.inspect()));
+                    }
+                    let value = self.eval(value)?;
+                    map.push((key, value));
+                }
+                Ok(Value::Map(map))
+            }
+            Expr::Binary(left, op, right) => self.eval_binary(left, *op, right),
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand)?;
+                Ok(match (op, value) {
+                    (UnaryOp::Not, value) => Value::Bool(!value.is_truthy()),
+                    (UnaryOp::Neg, Value::Number(number)) => Value::number(-number.value, number.unit),
+                    (UnaryOp::Plus, Value::Number(number)) => Value::Number(number),
+                    (op, value) => {
+                        let sign = if *op == UnaryOp::Neg { "-" } else { "+" };
+                        match value.interpolated() {
+                            Ok(text) => Value::ident(format!("{}{}", sign, text)),
+                            Err(message) => return self.error(message),
+                        }
+                    }
+                })
+            }
+            Expr::Call { namespace, name, args } => self.call_function(namespace.as_deref(), name, args),
+            Expr::Paren(inner) => self.eval(inner),
+            Expr::Parent => Ok(match self.selectors.as_slice() {
+                [] => Value::Null,
+                selectors => Value::List(
+                    selectors.iter().map(|selector| Value::ident(selector.clone())).collect(),
+                    ListSeparator::Comma,
+                    false,
+                ),
+            }),
+        }
+    }
+
+    fn eval_binary(&mut self, left: &Expr, op: BinaryOp, right: &Expr) -> Result<Value> {
+        let a = self.eval(left)?;
+        match op {
+            BinaryOp::And if !a.is_truthy() => return Ok(a),
+            BinaryOp::Or if a.is_truthy() => return Ok(a),
+            BinaryOp::And | BinaryOp::Or => return self.eval(right),
+            _ => {}
+        }
+        let b = self.eval(right)?;
+        let result = match op {
+            BinaryOp::Add => a.add(&b),
+            BinaryOp::Sub => a.sub(&b),
+            BinaryOp::Mul => a.mul(&b),
+            BinaryOp::Div => match a.div(&b) {
+                Err(_) if is_slash_literal(left) && is_slash_literal(right) => {
+                    Ok(Value::List(vec![a, b], ListSeparator::Slash, false))
+                }
+                result => result,
+            },
+            BinaryOp::Mod => a.rem(&b),
+            BinaryOp::Eq => Ok(Value::Bool(a.equals(&b))),
+            BinaryOp::Ne => Ok(Value::Bool(!a.equals(&b))),
+            BinaryOp::Lt => a.compare(&b).map(|ordering| Value::Bool(ordering.is_lt())),
+            BinaryOp::Le => a.compare(&b).map(|ordering| Value::Bool(ordering.is_le())),
+            BinaryOp::Gt => a.compare(&b).map(|ordering| Value::Bool(ordering.is_gt())),
+            BinaryOp::Ge => a.compare(&b).map(|ordering| Value::Bool(ordering.is_ge())),
+            BinaryOp::And | BinaryOp::Or => unreachable!("handled above"),
+        };
+        result.or_else(|message| self.error(message))
+    }
+
+    fn call_function(&mut self, namespace: Option<&str>, name: &str, args: &Arguments) -> Result<Value> {
+        let normalized = normalize_name(name);
+        let builtin_module = match namespace {
+            Some(namespace) => self.namespace(namespace)?.builtin,
+            None => None,
+        };
+        if namespace.is_none() && normalized == "if" {
+            return self.call_if(args);
+        }
+        if namespace.is_none() || builtin_module == Some("meta") {
+            if let Some(value) = self.call_meta(&normalized, args)? {
+                return Ok(value);
+            }
+        }
+        if builtin_module.is_none() {
+            if let Some(function) =
+                self.lookup_member(namespace, &normalized, |scope, name| scope.functions.get(name).cloned())?
+            {
+                return match self.invoke(&function, args, true)? {
+                    Some(value) => Ok(value),
+                    None => self.error(format!("Function {} finished without @return.", name)),
+                };
+            }
+            if let Some(namespace) = namespace {
+                return self.error(format!("Undefined function {}.{}.", namespace, name));
+            }
+        }
+
+        if builtins::exists(builtin_module, &normalized) {
+            let (positional, named) = self.eval_arguments(args)?;
+            let args = BuiltinArgs { positional, named };
+            return match builtins::call(builtin_module, &normalized, &args) {
+                Some(Ok(value)) => Ok(value),
+                Some(Err(message)) => self.error(message),
+                None => self.error(format!("Undefined function {}.", name)),
+            };
+        }
+        if let Some(namespace) = namespace {
+            return self.error(format!("Undefined function {}.{}.", namespace, name));
+        }
+
+        // Anything else is a plain CSS function such as `theme()` or `translate()`
+        if !args.named.is_empty() {
+            return self.error(format!("Plain CSS function {}() doesn't support keyword arguments.", name));
+        }
+        let mut printed = Vec::with_capacity(args.positional.len());
+        for arg in &args.positional {
+            let value = self.eval_css_value(arg)?;
+            match value.serialize(self.compressed) {
+                Ok(text) => printed.push(text),
+                Err(message) => return self.error(message),
+            }
+        }
+        if let Some(rest) = &args.rest {
+            for value in self.eval(rest)?.as_list() {
+                printed.push(value.to_css().or_else(|message| self.error(message))?);
+            }
+        }
+        Ok(Value::ident(format!("{}({})", name, printed.join(if self.compressed { "," } else { ", " }))))
+    }
+
+    /// `if($condition, $if-true, $if-false)` only evaluates the branch it returns.
+    fn call_if(&mut self, args: &Arguments) -> Result<Value> {
+        let arg = |index: usize, name: &str| {
+            args.named.iter().find(|(n, _)| n == name).map(|(_, expr)| expr).or_else(|| args.positional.get(index))
+        };
+        let (Some(condition), Some(if_true), Some(if_false)) = (arg(0, "condition"), arg(1, "if-true"), arg(2, "if-false"))
+        else {
+            return self.error("if() takes $condition, $if-true and $if-false.");
+        };
+        let branch = if self.eval(condition)?.is_truthy() { if_true } else { if_false };
+        self.eval(branch)
+    }
+
+    /// `sass:meta` functions that look at the environment.
+    fn call_meta(&mut self, name: &str, args: &Arguments) -> Result<Option<Value>> {
+        if !matches!(name, "variable-exists" | "global-variable-exists" | "function-exists" | "mixin-exists") {
+            return Ok(None);
+        }
+        let (positional, named) = self.eval_arguments(args)?;
+        let Some(Value::String(member, _)) =
+            named.iter().find(|(n, _)| n == "name").map(|(_, value)| value).or(positional.first())
+        else {
+            return self.error(format!("{}() takes a $name string.", name));
+        };
+        let member = normalize_name(member);
+        let exists = match name {
+            "variable-exists" => self.lookup_variable(&member).is_some(),
+            "global-variable-exists" => self.env.module.scope.borrow().variables.contains_key(&member),
+            "function-exists" => {
+                self.lookup_member(None, &member, |scope, name| scope.functions.get(name).cloned())?.is_some()
+                    || builtins::exists(None, &member)
+            }
+            _ => self.lookup_member(None, &member, |scope, name| scope.mixins.get(name).cloned())?.is_some(),
+        };
+        Ok(Some(Value::Bool(exists)))
+    }
+
+    /// Adds the selectors of `@extend`ing rules to every rule matching their targets and drops
+    /// selectors that still contain `%placeholders`.
+    fn apply_extensions(&self, nodes: &mut Vec<CssNode>) -> Result<()> {
+        let mut matched = vec![false; self.extensions.len()];
+        extend_nodes(nodes, &self.extensions, &mut matched);
+        for (extension, matched) in self.extensions.iter().zip(matched) {
+            if !matched && !extension.optional {
+                return Err(Error::parse_error(
+                    format!(
+                        "The target selector was not found. Use \"@extend {} !optional\" to avoid this error.",
+                        extension.target
+                    ),
+                    extension.span,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_slash_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(..) => true,
+        Expr::Binary(left, BinaryOp::Div, right) => is_slash_literal(left) && is_slash_literal(right),
+        _ => false,
+    }
+}
+
+/// `sass:math` is `math` and `src/_tokens.scss` is `tokens`.
+fn default_namespace(url: &str) -> String {
+    let name = url.strip_prefix("sass:").unwrap_or(url);
+    let name = name.rsplit('/').next().unwrap_or(name);
+    let name = name.split('.').next().unwrap_or(name);
+    name.strip_prefix('_').unwrap_or(name).to_string()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn has_placeholder(selector: &str) -> bool {
+    selector
+        .match_indices('%')
+        .any(|(i, _)| selector[i + 1..].chars().next().is_some_and(|c| c.is_alphabetic() || c == '_' || c == '-'))
+}
+
+fn extend_nodes(nodes: &mut Vec<CssNode>, extensions: &[Extension], matched: &mut [bool]) {
+    for node in nodes.iter_mut() {
+        match node {
+            CssNode::Rule(rule) if !extensions.is_empty() || has_placeholder(&rule.selector) => {
+                let mut selectors = split_selector_list(&rule.selector);
+                let mut i = 0;
+                while i < selectors.len() && selectors.len() < 1000 {
+                    for (k, extension) in extensions.iter().enumerate() {
+                        for extender in &extension.extenders {
+                            if let Some(extended) = extend_selector(&selectors[i], &extension.target, extender) {
+                                matched[k] = true;
+                                if !selectors.contains(&extended) {
+                                    selectors.push(extended);
+                                }
+                            }
+                        }
+                    }
+                    i += 1;
+                }
+                selectors.retain(|selector| !has_placeholder(selector));
+                rule.selector = selectors.join(", ");
+            }
+            CssNode::AtRule(rule) => {
+                if let Some(block) = &mut rule.block {
+                    extend_nodes(block, extensions, matched);
+                }
+            }
+            _ => {}
+        }
+    }
+    nodes.retain(|node| !matches!(node, CssNode::Rule(rule) if rule.selector.is_empty()));
+}
+
+/// Drops rules without declarations and conditional blocks left empty.
+fn remove_empty(nodes: &mut Vec<CssNode>) {
+    for node in nodes.iter_mut() {
+        if let CssNode::AtRule(rule) = node {
+            if let Some(block) = &mut rule.block {
+                remove_empty(block);
+            }
+        }
+    }
+    nodes.retain(|node| match node {
+        CssNode::Rule(rule) => !rule.children.is_empty(),
+        CssNode::AtRule(rule) => {
+            !(rule.block.as_ref().is_some_and(Vec::is_empty)
+                && matches!(rule.name.as_str(), "media" | "supports" | "container" | "layer" | "screen"))
+        }
+        CssNode::Declaration(_) => true,
+    });
+}
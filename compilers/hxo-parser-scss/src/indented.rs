@@ -0,0 +1,145 @@
+//! Line structure of the indentation-based syntaxes. Indented Sass and Stylus are translated to SCSS
+//! one source line to one output line, so the SCSS parser reports their errors on the right line.
+
+use hxo_types::{Cursor, Error, Position, Result, Span};
+
+/// A logical source line: a statement, a selector or the head of a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    /// Zero-based number of the source line it starts on.
+    pub number: usize,
+    /// Width of the leading whitespace, a tab counting as two spaces.
+    pub indent: usize,
+    /// The trimmed text without `//` comments. A line ending in `,` is joined with the next one.
+    pub text: String,
+}
+
+impl Line {
+    pub fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        let start = Position { line: self.number as u32 + 1, column: self.indent as u32 + 1, offset: 0 };
+        let end = Position { column: start.column + self.text.len() as u32, ..start };
+        Err(Error::parse_error(message.into(), Span { start, end }))
+    }
+}
+
+/// Splits `source` into logical lines. Blank lines are skipped, and so are comments: a `//` or
+/// `/*` line with the lines indented below it, or a `/* ... */` spanning several lines.
+pub fn lines(source: &str) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+    let mut comment_indent = None;
+    let mut in_block_comment = false;
+    let mut continued = false;
+    for (number, raw) in source.split('\n').enumerate() {
+        let trimmed = raw.trim();
+        if in_block_comment {
+            in_block_comment = !trimmed.contains("*/");
+            continue;
+        }
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent = raw.chars().take_while(|c| c.is_whitespace()).map(|c| if c == '\t' { 2 } else { 1 }).sum();
+        if let Some(comment) = comment_indent {
+            if indent > comment {
+                continue;
+            }
+            comment_indent = None;
+        }
+        if trimmed.starts_with("//") || trimmed.starts_with("/*") {
+            if trimmed.starts_with("/*") && !trimmed.contains("*/") && !continued {
+                in_block_comment = true;
+            }
+            comment_indent = Some(indent);
+            continue;
+        }
+        let text = strip_comment(trimmed).trim_end().to_string();
+        match lines.last_mut() {
+            Some(last) if continued => {
+                last.text.push(' ');
+                last.text.push_str(&text);
+            }
+            _ => lines.push(Line { number, indent, text }),
+        }
+        continued = lines.last().is_some_and(|line| line.text.ends_with(','));
+    }
+    lines
+}
+
+/// Cuts a trailing `//` comment, leaving `//` inside strings and parentheses (`url(//cdn/a.png)`).
+pub fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let mut quote = None;
+    let mut depth = 0usize;
+    for (i, &b) in bytes.iter().enumerate() {
+        match (quote, b) {
+            (Some(q), b) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(b),
+            (None, b'(') => depth += 1,
+            (None, b')') => depth = depth.saturating_sub(1),
+            (None, b'/') if depth == 0 && bytes.get(i + 1) == Some(&b'/') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Builds SCSS from `lines`, each translated by `translate(index, opens_block)` on its own source line.
+/// A line opens a block when the next one is indented deeper, and blocks close before the next line
+/// at the same or a lower indentation. Statements that don't open a block get a `;` unless they end in
+/// `}` or `;`.
+pub fn to_scss(source: &str, lines: &[Line], mut translate: impl FnMut(usize, bool) -> Result<String>) -> Result<String> {
+    let mut out = vec![String::new(); source.split('\n').count()];
+    let mut open: Vec<usize> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let mut text = String::new();
+        while open.last().is_some_and(|&indent| indent >= line.indent) {
+            open.pop();
+            text.push_str("} ");
+        }
+        let opens = lines.get(i + 1).is_some_and(|next| next.indent > line.indent);
+        let head = translate(i, opens)?;
+        text.push_str(&head);
+        if opens {
+            text.push_str(" {");
+            open.push(line.indent);
+        }
+        else if !head.ends_with(['}', ';']) {
+            text.push(';');
+        }
+        out[line.number] = text;
+    }
+    if let Some(last) = out.last_mut() {
+        last.push_str(&" }".repeat(open.len()));
+    }
+    Ok(out.join("\n"))
+}
+
+/// Translates the rest of `cursor` with `translate` and hands the SCSS to `parse` on a cursor starting
+/// where `cursor` is, so spans keep their lines. `cursor` ends up at the end of its source.
+pub fn parse_translated<T>(
+    cursor: &mut Cursor,
+    translate: impl FnOnce(&str) -> Result<String>,
+    parse: impl FnOnce(&mut Cursor) -> Result<T>,
+) -> Result<T> {
+    let scss = translate(&cursor.source[cursor.pos..])?;
+    let mut translated = Cursor::with_sliced_source(&scss, cursor.position());
+    let result = parse(&mut translated)?;
+    while !cursor.is_eof() {
+        cursor.consume();
+    }
+    Ok(result)
+}
+
+/// Quotes the bare URLs of an indented `@import foo, bar`.
+pub fn quote_imports(urls: &str) -> String {
+    urls.split(',')
+        .map(str::trim)
+        .map(
+            |url| {
+                if url.starts_with(['"', '\'']) || url.starts_with("url(") { url.to_string() } else { format!("\"{}\"", url) }
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(", ")
+}
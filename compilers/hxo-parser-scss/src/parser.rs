@@ -0,0 +1,1231 @@
+//! SCSS syntax: turns source text into [`Stmt`]s with spans from the cursor.
+
+use crate::{
+    ast::{
+        Arguments, BinaryOp, Callable, ContentBlock, Expr, Interpolation, InterpolationPart, ListSeparator, MessageKind,
+        Parameters, Stmt, UnaryOp, VariableDecl,
+    },
+    value::Color,
+};
+use hxo_types::{Cursor, Error, Position, Result};
+use std::rc::Rc;
+
+/// Parses a whole SCSS stylesheet.
+pub fn parse_scss(source: &str) -> Result<Vec<Stmt>> {
+    parse_scss_with(&mut Cursor::new(source))
+}
+
+/// Parses from `cursor` to the end of its source; spans follow the cursor's position, so a cursor over
+/// a `<style>` block gives spans into the component file.
+pub fn parse_scss_with(cursor: &mut Cursor) -> Result<Vec<Stmt>> {
+    let mut parser = ScssSyntax::new(cursor);
+    let stmts = parser.parse_statements()?;
+    if !parser.cursor.is_eof() {
+        return Err(Error::parse_error("unexpected `}`".to_string(), parser.cursor.span_at_current()));
+    }
+    Ok(stmts)
+}
+
+/// Parses a single SassScript expression, such as a value passed in from configuration.
+pub fn parse_expression(source: &str) -> Result<Expr> {
+    let mut cursor = Cursor::new(source);
+    let mut parser = ScssSyntax::new(&mut cursor);
+    let expr = parser.parse_expression()?;
+    parser.skip_trivia();
+    if !parser.cursor.is_eof() {
+        return Err(Error::parse_error(format!("unexpected `{}`", parser.cursor.peek()), parser.cursor.span_at_current()));
+    }
+    Ok(expr)
+}
+
+/// Sass treats `-` and `_` in names as the same character.
+pub fn normalize_name(name: &str) -> String {
+    name.replace('_', "-")
+}
+
+pub fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || (!c.is_ascii() && c != '\0')
+}
+
+pub fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+/// Functions whose arguments are plain CSS, kept as text with `#{}` and `$variables` substituted.
+const SPECIAL_FUNCTIONS: &[&str] = &["calc", "var", "env", "url", "element", "expression", "-webkit-calc", "-moz-calc"];
+
+pub struct ScssSyntax<'a, 'b> {
+    pub cursor: &'a mut Cursor<'b>,
+    /// Keywords that end an expression, such as `through` in `@for $i from 1 through 3`.
+    stop_words: &'static [&'static str],
+}
+
+impl<'a, 'b> ScssSyntax<'a, 'b> {
+    pub fn new(cursor: &'a mut Cursor<'b>) -> Self {
+        Self { cursor, stop_words: &[] }
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::parse_error(message.into(), self.cursor.span_at_current()))
+    }
+
+    fn save(&self) -> (usize, usize, usize) {
+        (self.cursor.pos, self.cursor.line, self.cursor.column)
+    }
+
+    fn restore(&mut self, (pos, line, column): (usize, usize, usize)) {
+        self.cursor.pos = pos;
+        self.cursor.line = line;
+        self.cursor.column = column;
+    }
+
+    /// Skips whitespace and comments; returns whether anything was skipped.
+    pub fn skip_trivia(&mut self) -> bool {
+        let start = self.cursor.pos;
+        loop {
+            self.cursor.skip_whitespace();
+            if self.cursor.peek_str("//") {
+                while !self.cursor.is_eof() && self.cursor.peek() != '\n' {
+                    self.cursor.consume();
+                }
+            }
+            else if self.cursor.peek_str("/*") {
+                self.cursor.consume_n(2);
+                while !self.cursor.is_eof() && !self.cursor.peek_str("*/") {
+                    self.cursor.consume();
+                }
+                self.cursor.consume_n(2);
+            }
+            else {
+                break;
+            }
+        }
+        self.cursor.pos != start
+    }
+
+    /// Whether the cursor is at `word` followed by a non-name character.
+    fn at_keyword(&self, word: &str) -> bool {
+        self.cursor.peek_str(word)
+            && !is_name_char(self.cursor.source[self.cursor.pos + word.len()..].chars().next().unwrap_or(' '))
+    }
+
+    fn consume_keyword(&mut self, word: &str) -> bool {
+        if self.at_keyword(word) {
+            self.cursor.consume_n(word.len());
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<()> {
+        self.skip_trivia();
+        if self.consume_keyword(word) { Ok(()) } else { self.error(format!("expected `{}`", word)) }
+    }
+
+    /// Reads a plain identifier.
+    pub fn parse_name(&mut self) -> Result<String> {
+        let c = self.cursor.peek();
+        if !is_name_start(c) && !(c == '-' && (is_name_start(self.cursor.peek_n(1)) || self.cursor.peek_n(1) == '-')) {
+            return self.error("expected identifier");
+        }
+        Ok(self.cursor.consume_while(is_name_char))
+    }
+
+    fn parse_variable_name(&mut self) -> Result<String> {
+        self.cursor.expect('$')?;
+        Ok(normalize_name(&self.parse_name()?))
+    }
+
+    /// Ends a statement at `;`, or before the `}` closing its block.
+    fn end_statement(&mut self) -> Result<()> {
+        self.skip_trivia();
+        match self.cursor.peek() {
+            ';' => {
+                self.cursor.consume();
+                Ok(())
+            }
+            '}' | '\0' => Ok(()),
+            c => self.error(format!("expected `;`, found `{}`", c)),
+        }
+    }
+
+    pub fn parse_statements(&mut self) -> Result<Vec<Stmt>> {
+        let mut stmts = Vec::new();
+        loop {
+            self.skip_trivia();
+            match self.cursor.peek() {
+                '\0' | '}' => break,
+                ';' => {
+                    self.cursor.consume();
+                }
+                _ => stmts.push(self.parse_statement()?),
+            }
+        }
+        Ok(stmts)
+    }
+
+    pub fn parse_block(&mut self) -> Result<Vec<Stmt>> {
+        self.skip_trivia();
+        self.cursor.expect('{')?;
+        let body = self.parse_statements()?;
+        self.cursor.expect('}')?;
+        Ok(body)
+    }
+
+    fn parse_statement(&mut self) -> Result<Stmt> {
+        let start = self.cursor.position();
+        match self.cursor.peek() {
+            '$' => self.parse_variable_decl(None, start),
+            '@' => self.parse_at_rule(start),
+            _ => {
+                if let Some(namespace) = self.namespaced_variable() {
+                    return self.parse_variable_decl(Some(namespace), start);
+                }
+                if self.looks_like_declaration() { self.parse_declaration(start) } else { self.parse_rule(start) }
+            }
+        }
+    }
+
+    /// `namespace.$name: value`
+    fn namespaced_variable(&mut self) -> Option<String> {
+        let saved = self.save();
+        if let Ok(namespace) = self.parse_name() {
+            if self.cursor.peek() == '.' && self.cursor.peek_n(1) == '$' {
+                self.cursor.consume();
+                return Some(namespace);
+            }
+        }
+        self.restore(saved);
+        None
+    }
+
+    fn parse_variable_decl(&mut self, namespace: Option<String>, start: Position) -> Result<Stmt> {
+        let name = self.parse_variable_name()?;
+        self.skip_trivia();
+        self.cursor.expect(':')?;
+        let value = self.parse_expression()?;
+        let (mut default, mut global) = (false, false);
+        loop {
+            self.skip_trivia();
+            if self.cursor.consume_str("!default") {
+                default = true;
+            }
+            else if self.cursor.consume_str("!global") {
+                global = true;
+            }
+            else {
+                break;
+            }
+        }
+        self.end_statement()?;
+        Ok(Stmt::Variable(VariableDecl { namespace, name, value, default, global, span: self.cursor.span_from(start) }))
+    }
+
+    /// Decides between `name: value` and `selector {`: a statement is a declaration unless a `{` comes
+    /// before the end of the statement, and `a:hover {` is a rule while `font: {` starts nested properties.
+    fn looks_like_declaration(&self) -> bool {
+        let rest = &self.cursor.source[self.cursor.pos..];
+        let bytes = rest.as_bytes();
+        let mut depth = 0usize;
+        let mut colon = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                quote @ (b'"' | b'\'') => {
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != quote {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                b'#' if bytes.get(i + 1) == Some(&b'{') => {
+                    let mut braces = 0;
+                    while i < bytes.len() {
+                        match bytes[i] {
+                            b'{' => braces += 1,
+                            b'}' => {
+                                braces -= 1;
+                                if braces == 0 {
+                                    break;
+                                }
+                            }
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i += rest[i..].find("*/").map_or(bytes.len(), |end| end + 1);
+                }
+                b'(' | b'[' => depth += 1,
+                b')' | b']' => depth = depth.saturating_sub(1),
+                b':' if depth == 0 && colon.is_none() => colon = Some(i),
+                b'{' if depth == 0 => {
+                    return colon.is_some_and(|colon| {
+                        rest[colon + 1..i].trim().is_empty() && rest[..colon].trim().chars().all(is_name_char)
+                    });
+                }
+                b';' | b'}' if depth == 0 => return true,
+                _ => {}
+            }
+            i += 1;
+        }
+        true
+    }
+
+    fn parse_declaration(&mut self, start: Position) -> Result<Stmt> {
+        let name = self.parse_interpolated_text(&[':', ';', '{', '}'], false)?;
+        self.cursor.expect(':')?;
+        let custom_property = matches!(name.parts.first(), Some(InterpolationPart::Text(text)) if text.starts_with("--"));
+        if custom_property {
+            let value = self.parse_interpolated_text(&[';', '}'], false)?;
+            self.end_statement()?;
+            let value = Some(Expr::String { value, quote: None });
+            return Ok(Stmt::Declaration {
+                name,
+                value,
+                important: false,
+                children: Vec::new(),
+                span: self.cursor.span_from(start),
+            });
+        }
+
+        self.skip_trivia();
+        let mut value = None;
+        let mut important = false;
+        if self.cursor.peek() != '{' {
+            value = Some(self.parse_expression()?);
+            self.skip_trivia();
+            if self.cursor.peek() == '!' {
+                let saved = self.save();
+                self.cursor.consume();
+                self.cursor.skip_whitespace();
+                if self.consume_keyword("important") {
+                    important = true;
+                }
+                else {
+                    self.restore(saved);
+                }
+            }
+        }
+        self.skip_trivia();
+        let children = if self.cursor.peek() == '{' {
+            self.parse_block()?
+        }
+        else {
+            self.end_statement()?;
+            Vec::new()
+        };
+        Ok(Stmt::Declaration { name, value, important, children, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_rule(&mut self, start: Position) -> Result<Stmt> {
+        let selector = self.parse_interpolated_text(&['{', ';', '}'], false)?;
+        if self.cursor.peek() != '{' {
+            return self.error(format!("expected `{{` after `{}`", selector.as_plain().unwrap_or_default()));
+        }
+        let body = self.parse_block()?;
+        Ok(Stmt::Rule { selector, body, span: self.cursor.span_from(start) })
+    }
+
+    /// Reads text up to a top-level character of `stops`, turning `#{...}` (and `$variables` when
+    /// `variables` is set, for media queries) into expressions. Comments are dropped and the result is
+    /// trimmed.
+    pub fn parse_interpolated_text(&mut self, stops: &[char], variables: bool) -> Result<Interpolation> {
+        let mut text = Interpolation::default();
+        let mut depth = 0usize;
+        let mut quote = None;
+        while !self.cursor.is_eof() {
+            let c = self.cursor.peek();
+            if self.cursor.peek_str("#{") {
+                text.parts.push(InterpolationPart::Expr(self.parse_interpolation()?));
+                continue;
+            }
+            match quote {
+                Some(q) => {
+                    if c == '\\' {
+                        text.push_char(self.cursor.consume());
+                    }
+                    else if c == q {
+                        quote = None;
+                    }
+                }
+                None => match c {
+                    '"' | '\'' => quote = Some(c),
+                    '(' | '[' => depth += 1,
+                    ')' | ']' => depth = depth.saturating_sub(1),
+                    '/' if self.cursor.peek_n(1) == '*' => {
+                        self.skip_trivia();
+                        text.push_char(' ');
+                        continue;
+                    }
+                    '/' if self.cursor.peek_n(1) == '/' && depth == 0 => {
+                        self.skip_trivia();
+                        text.push_char(' ');
+                        continue;
+                    }
+                    '$' if variables && is_name_start(self.cursor.peek_n(1)) => {
+                        text.parts.push(InterpolationPart::Expr(self.parse_space_list()?));
+                        continue;
+                    }
+                    '\\' => {
+                        text.push_char(self.cursor.consume());
+                    }
+                    c if depth == 0 && stops.contains(&c) => break,
+                    _ => {}
+                },
+            }
+            text.push_char(self.cursor.consume());
+        }
+        Ok(trim_interpolation(text))
+    }
+
+    /// `#{expr}`
+    fn parse_interpolation(&mut self) -> Result<Expr> {
+        self.cursor.expect_str("#{")?;
+        let saved_stops = std::mem::take(&mut self.stop_words);
+        let expr = self.parse_expression();
+        self.stop_words = saved_stops;
+        let expr = expr?;
+        self.skip_trivia();
+        self.cursor.expect('}')?;
+        Ok(expr)
+    }
+
+    fn parse_at_rule(&mut self, start: Position) -> Result<Stmt> {
+        self.cursor.expect('@')?;
+        let name = self.cursor.consume_while(is_name_char);
+        self.skip_trivia();
+        match name.as_str() {
+            "mixin" | "function" => {
+                let callable_name = normalize_name(&self.parse_name()?);
+                self.skip_trivia();
+                let params = if self.cursor.peek() == '(' { self.parse_parameters()? } else { Parameters::default() };
+                let body = self.parse_block()?;
+                let callable = Rc::new(Callable { name: callable_name, params, body, span: self.cursor.span_from(start) });
+                Ok(if name == "mixin" { Stmt::Mixin(callable) } else { Stmt::Function(callable) })
+            }
+            "include" => self.parse_include(start),
+            "content" => {
+                let args = if self.cursor.peek() == '(' { self.parse_arguments()? } else { Arguments::default() };
+                self.end_statement()?;
+                Ok(Stmt::Content { args, span: self.cursor.span_from(start) })
+            }
+            "return" => {
+                let value = self.parse_expression()?;
+                self.end_statement()?;
+                Ok(Stmt::Return { value, span: self.cursor.span_from(start) })
+            }
+            "if" => self.parse_if(start),
+            "else" => self.error("`@else` must come right after an `@if` block"),
+            "each" => {
+                let mut variables = vec![self.parse_variable_name()?];
+                loop {
+                    self.skip_trivia();
+                    if !self.cursor.consume_str(",") {
+                        break;
+                    }
+                    self.skip_trivia();
+                    variables.push(self.parse_variable_name()?);
+                }
+                self.expect_keyword("in")?;
+                let list = self.parse_expression()?;
+                let body = self.parse_block()?;
+                Ok(Stmt::Each { variables, list, body, span: self.cursor.span_from(start) })
+            }
+            "for" => {
+                let variable = self.parse_variable_name()?;
+                self.expect_keyword("from")?;
+                self.stop_words = &["through", "to"];
+                let from = self.parse_expression();
+                self.stop_words = &[];
+                let from = from?;
+                self.skip_trivia();
+                let inclusive = if self.consume_keyword("through") {
+                    true
+                }
+                else if self.consume_keyword("to") {
+                    false
+                }
+                else {
+                    return self.error("expected `through` or `to`");
+                };
+                let to = self.parse_expression()?;
+                let body = self.parse_block()?;
+                Ok(Stmt::For { variable, from, to, inclusive, body, span: self.cursor.span_from(start) })
+            }
+            "while" => {
+                let condition = self.parse_expression()?;
+                let body = self.parse_block()?;
+                Ok(Stmt::While { condition, body, span: self.cursor.span_from(start) })
+            }
+            "extend" => {
+                let selector = self.parse_interpolated_text(&[';', '}', '!'], false)?;
+                let optional = self.cursor.consume_str("!optional");
+                self.end_statement()?;
+                Ok(Stmt::Extend { selector, optional, span: self.cursor.span_from(start) })
+            }
+            "use" => self.parse_use(start),
+            "forward" => self.parse_forward(start),
+            "import" => self.parse_import(start),
+            "debug" | "warn" | "error" => {
+                let kind = match name.as_str() {
+                    "debug" => MessageKind::Debug,
+                    "warn" => MessageKind::Warn,
+                    _ => MessageKind::Error,
+                };
+                let value = self.parse_expression()?;
+                self.end_statement()?;
+                Ok(Stmt::Message { kind, value, span: self.cursor.span_from(start) })
+            }
+            "at-root" => {
+                let selector =
+                    if self.cursor.peek() == '{' { None } else { Some(self.parse_interpolated_text(&['{', ';', '}'], false)?) };
+                let body = self.parse_block()?;
+                Ok(Stmt::AtRoot { selector, body, span: self.cursor.span_from(start) })
+            }
+            _ => {
+                let variables = matches!(name.as_str(), "media" | "supports" | "container");
+                let prelude = self.parse_interpolated_text(&['{', ';', '}'], variables)?;
+                let body = if self.cursor.peek() == '{' {
+                    Some(self.parse_block()?)
+                }
+                else {
+                    self.end_statement()?;
+                    None
+                };
+                Ok(Stmt::AtRule { name, prelude, body, span: self.cursor.span_from(start) })
+            }
+        }
+    }
+
+    fn parse_include(&mut self, start: Position) -> Result<Stmt> {
+        let mut namespace = None;
+        let mut name = self.parse_name()?;
+        if self.cursor.peek() == '.' && is_name_start(self.cursor.peek_n(1)) {
+            self.cursor.consume();
+            namespace = Some(name);
+            name = self.parse_name()?;
+        }
+        self.skip_trivia();
+        let args = if self.cursor.peek() == '(' { self.parse_arguments()? } else { Arguments::default() };
+        self.skip_trivia();
+        let params = if self.consume_keyword("using") {
+            self.skip_trivia();
+            self.parse_parameters()?
+        }
+        else {
+            Parameters::default()
+        };
+        self.skip_trivia();
+        let content = if self.cursor.peek() == '{' {
+            Some(Rc::new(ContentBlock { params, body: self.parse_block()? }))
+        }
+        else {
+            self.end_statement()?;
+            None
+        };
+        Ok(Stmt::Include { namespace, name: normalize_name(&name), args, content, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_if(&mut self, start: Position) -> Result<Stmt> {
+        let condition = self.parse_expression()?;
+        let mut clauses = vec![(condition, self.parse_block()?)];
+        let mut otherwise = None;
+        loop {
+            let saved = self.save();
+            self.skip_trivia();
+            if !self.cursor.consume_str("@else") {
+                self.restore(saved);
+                break;
+            }
+            self.skip_trivia();
+            if self.consume_keyword("if") {
+                let condition = self.parse_expression()?;
+                clauses.push((condition, self.parse_block()?));
+            }
+            else {
+                otherwise = Some(self.parse_block()?);
+                break;
+            }
+        }
+        Ok(Stmt::If { clauses, otherwise, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_use(&mut self, start: Position) -> Result<Stmt> {
+        let url = self.cursor.consume_string()?;
+        let mut namespace = None;
+        let mut config = Vec::new();
+        self.skip_trivia();
+        if self.consume_keyword("as") {
+            self.skip_trivia();
+            namespace = Some(if self.cursor.consume_str("*") { "*".to_string() } else { self.parse_name()? });
+            self.skip_trivia();
+        }
+        if self.consume_keyword("with") {
+            config = self.parse_configuration()?;
+        }
+        self.end_statement()?;
+        Ok(Stmt::Use { url, namespace, config, span: self.cursor.span_from(start) })
+    }
+
+    fn parse_forward(&mut self, start: Position) -> Result<Stmt> {
+        let url = self.cursor.consume_string()?;
+        let (mut prefix, mut show, mut hide, mut config) = (None, None, None, Vec::new());
+        loop {
+            self.skip_trivia();
+            if self.consume_keyword("as") {
+                self.skip_trivia();
+                let name = self.cursor.consume_while(is_name_char);
+                if !self.cursor.consume_str("*") {
+                    return self.error("expected `*` after the `@forward` prefix");
+                }
+                prefix = Some(name);
+            }
+            else if self.at_keyword("show") || self.at_keyword("hide") {
+                let is_show = self.consume_keyword("show") || !self.consume_keyword("hide");
+                let mut members = Vec::new();
+                loop {
+                    self.skip_trivia();
+                    let variable = self.cursor.consume_str("$");
+                    let name = normalize_name(&self.parse_name()?);
+                    members.push(if variable { format!("${}", name) } else { name });
+                    self.skip_trivia();
+                    if !self.cursor.consume_str(",") {
+                        break;
+                    }
+                }
+                if is_show {
+                    show = Some(members);
+                }
+                else {
+                    hide = Some(members);
+                }
+            }
+            else if self.consume_keyword("with") {
+                config = self.parse_configuration()?;
+            }
+            else {
+                break;
+            }
+        }
+        self.end_statement()?;
+        Ok(Stmt::Forward { url, prefix, show, hide, config, span: self.cursor.span_from(start) })
+    }
+
+    /// `($name: value [!default], ...)` after `with`.
+    fn parse_configuration(&mut self) -> Result<Vec<(String, Expr)>> {
+        self.skip_trivia();
+        self.cursor.expect('(')?;
+        let mut config = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.cursor.peek() == ')' {
+                break;
+            }
+            let name = self.parse_variable_name()?;
+            self.skip_trivia();
+            self.cursor.expect(':')?;
+            let value = self.parse_space_list()?;
+            self.skip_trivia();
+            self.cursor.consume_str("!default");
+            config.push((name, value));
+            self.skip_trivia();
+            if !self.cursor.consume_str(",") {
+                break;
+            }
+        }
+        self.skip_trivia();
+        self.cursor.expect(')')?;
+        Ok(config)
+    }
+
+    /// Sass `@import`s become [`Stmt::Import`]; imports of CSS files, urls or with media queries stay
+    /// plain CSS at-rules.
+    fn parse_import(&mut self, start: Position) -> Result<Stmt> {
+        let prelude_start = self.save();
+        let mut urls = Vec::new();
+        let mut plain_css = false;
+        loop {
+            self.skip_trivia();
+            if !matches!(self.cursor.peek(), '"' | '\'') {
+                plain_css = true;
+                break;
+            }
+            let url = self.cursor.consume_string()?;
+            plain_css |=
+                url.ends_with(".css") || url.starts_with("http://") || url.starts_with("https://") || url.starts_with("//");
+            urls.push(url);
+            self.skip_trivia();
+            match self.cursor.peek() {
+                ',' => {
+                    self.cursor.consume();
+                }
+                ';' | '}' | '\0' => break,
+                _ => {
+                    plain_css = true;
+                    break;
+                }
+            }
+        }
+        if plain_css {
+            self.restore(prelude_start);
+            let prelude = self.parse_interpolated_text(&[';', '}'], false)?;
+            self.end_statement()?;
+            return Ok(Stmt::AtRule { name: "import".to_string(), prelude, body: None, span: self.cursor.span_from(start) });
+        }
+        self.end_statement()?;
+        Ok(Stmt::Import { urls, span: self.cursor.span_from(start) })
+    }
+
+    /// `($a, $b: default, $rest...)`
+    pub fn parse_parameters(&mut self) -> Result<Parameters> {
+        self.cursor.expect('(')?;
+        let mut params = Parameters::default();
+        loop {
+            self.skip_trivia();
+            if self.cursor.peek() == ')' {
+                break;
+            }
+            let name = self.parse_variable_name()?;
+            self.skip_trivia();
+            if self.cursor.consume_str("...") {
+                params.rest = Some(name);
+            }
+            else if self.cursor.consume_str(":") {
+                params.params.push((name, Some(self.parse_space_list()?)));
+            }
+            else {
+                params.params.push((name, None));
+            }
+            self.skip_trivia();
+            if !self.cursor.consume_str(",") {
+                break;
+            }
+        }
+        self.skip_trivia();
+        self.cursor.expect(')')?;
+        Ok(params)
+    }
+
+    /// `(positional, $named: value, $list...)`
+    pub fn parse_arguments(&mut self) -> Result<Arguments> {
+        self.cursor.expect('(')?;
+        let saved_stops = std::mem::take(&mut self.stop_words);
+        let args = self.parse_argument_list();
+        self.stop_words = saved_stops;
+        args
+    }
+
+    fn parse_argument_list(&mut self) -> Result<Arguments> {
+        let mut args = Arguments::default();
+        loop {
+            self.skip_trivia();
+            if self.cursor.peek() == ')' {
+                break;
+            }
+            if self.cursor.peek() == '$' && self.is_named_argument() {
+                let name = self.parse_variable_name()?;
+                self.skip_trivia();
+                self.cursor.expect(':')?;
+                args.named.push((name, self.parse_space_list()?));
+            }
+            else {
+                let expr = self.parse_space_list()?;
+                self.skip_trivia();
+                if self.cursor.consume_str("...") {
+                    args.rest = Some(Box::new(expr));
+                }
+                else {
+                    args.positional.push(expr);
+                }
+            }
+            self.skip_trivia();
+            if !self.cursor.consume_str(",") {
+                break;
+            }
+        }
+        self.skip_trivia();
+        self.cursor.expect(')')?;
+        Ok(args)
+    }
+
+    fn is_named_argument(&self) -> bool {
+        let rest = &self.cursor.source[self.cursor.pos + 1..];
+        let after_name = rest.trim_start_matches(is_name_char);
+        after_name.trim_start().starts_with(':')
+    }
+
+    fn at_expression_end(&self) -> bool {
+        match self.cursor.peek() {
+            '\0' | ';' | '}' | '{' | ')' | ']' | ',' | ':' => true,
+            '!' => self.cursor.peek_n(1) != '=',
+            '.' => self.cursor.peek_str("..."),
+            _ => self.stop_words.iter().any(|word| self.at_keyword(word)),
+        }
+    }
+
+    /// A full expression: a comma-separated list or a single space-separated one.
+    pub fn parse_expression(&mut self) -> Result<Expr> {
+        self.skip_trivia();
+        let first = self.parse_space_list()?;
+        self.skip_trivia();
+        if self.cursor.peek() != ',' {
+            return Ok(first);
+        }
+        let mut items = vec![first];
+        while self.cursor.consume_str(",") {
+            self.skip_trivia();
+            if self.at_expression_end() {
+                break;
+            }
+            items.push(self.parse_space_list()?);
+            self.skip_trivia();
+        }
+        Ok(Expr::List { items, separator: ListSeparator::Comma, bracketed: false })
+    }
+
+    pub fn parse_space_list(&mut self) -> Result<Expr> {
+        self.skip_trivia();
+        let mut items = vec![self.parse_or()?];
+        loop {
+            self.skip_trivia();
+            if self.at_expression_end() {
+                break;
+            }
+            items.push(self.parse_or()?);
+        }
+        Ok(if items.len() == 1 {
+            items.pop().unwrap_or(Expr::Null)
+        }
+        else {
+            Expr::List { items, separator: ListSeparator::Space, bracketed: false }
+        })
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut left = self.parse_and()?;
+        loop {
+            let saved = self.save();
+            self.skip_trivia();
+            if !self.consume_keyword("or") {
+                self.restore(saved);
+                return Ok(left);
+            }
+            left = Expr::Binary(Box::new(left), BinaryOp::Or, Box::new(self.parse_and()?));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut left = self.parse_equality()?;
+        loop {
+            let saved = self.save();
+            self.skip_trivia();
+            if !self.consume_keyword("and") {
+                self.restore(saved);
+                return Ok(left);
+            }
+            left = Expr::Binary(Box::new(left), BinaryOp::And, Box::new(self.parse_equality()?));
+        }
+    }
+
+    fn parse_equality(&mut self) -> Result<Expr> {
+        let mut left = self.parse_relational()?;
+        loop {
+            let saved = self.save();
+            self.skip_trivia();
+            let op = if self.cursor.consume_str("==") {
+                BinaryOp::Eq
+            }
+            else if self.cursor.consume_str("!=") {
+                BinaryOp::Ne
+            }
+            else {
+                self.restore(saved);
+                return Ok(left);
+            };
+            left = Expr::Binary(Box::new(left), op, Box::new(self.parse_relational()?));
+        }
+    }
+
+    fn parse_relational(&mut self) -> Result<Expr> {
+        let mut left = self.parse_additive()?;
+        loop {
+            let saved = self.save();
+            self.skip_trivia();
+            let op = if self.cursor.consume_str("<=") {
+                BinaryOp::Le
+            }
+            else if self.cursor.consume_str(">=") {
+                BinaryOp::Ge
+            }
+            else if self.cursor.consume_str("<") {
+                BinaryOp::Lt
+            }
+            else if self.cursor.consume_str(">") {
+                BinaryOp::Gt
+            }
+            else {
+                self.restore(saved);
+                return Ok(left);
+            };
+            left = Expr::Binary(Box::new(left), op, Box::new(self.parse_additive()?));
+        }
+    }
+
+    /// `a + b` and `a - b`. With a space before the operator but not after it, `a -b` is a list of `a`
+    /// and `-b`, as in `margin: 0 -1px`.
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let saved = self.save();
+            let space_before = self.skip_trivia();
+            let op = match self.cursor.peek() {
+                '+' => BinaryOp::Add,
+                '-' => BinaryOp::Sub,
+                _ => {
+                    self.restore(saved);
+                    return Ok(left);
+                }
+            };
+            let space_after = self.cursor.peek_n(1).is_whitespace();
+            if space_before && !space_after {
+                self.restore(saved);
+                return Ok(left);
+            }
+            self.cursor.consume();
+            left = Expr::Binary(Box::new(left), op, Box::new(self.parse_multiplicative()?));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let saved = self.save();
+            let space_before = self.skip_trivia();
+            let op = match self.cursor.peek() {
+                '*' => BinaryOp::Mul,
+                '/' if !matches!(self.cursor.peek_n(1), '/' | '*') => BinaryOp::Div,
+                '%' if space_before && self.cursor.peek_n(1).is_whitespace() => BinaryOp::Mod,
+                _ => {
+                    self.restore(saved);
+                    return Ok(left);
+                }
+            };
+            self.cursor.consume();
+            left = Expr::Binary(Box::new(left), op, Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        self.skip_trivia();
+        let next = self.cursor.peek_n(1);
+        match self.cursor.peek() {
+            '-' if matches!(next, '$' | '(') => {
+                self.cursor.consume();
+                Ok(Expr::Unary(UnaryOp::Neg, Box::new(self.parse_unary()?)))
+            }
+            '+' if !next.is_ascii_digit() && next != '.' => {
+                self.cursor.consume();
+                Ok(Expr::Unary(UnaryOp::Plus, Box::new(self.parse_unary()?)))
+            }
+            _ if self.at_keyword("not") => {
+                self.cursor.consume_n(3);
+                Ok(Expr::Unary(UnaryOp::Not, Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let c = self.cursor.peek();
+        let next = self.cursor.peek_n(1);
+        match c {
+            '(' => self.parse_parenthesized(),
+            '[' => {
+                self.cursor.consume();
+                self.skip_trivia();
+                let inner = if self.cursor.peek() == ']' { None } else { Some(self.parse_expression()?) };
+                self.skip_trivia();
+                self.cursor.expect(']')?;
+                let items = match inner {
+                    None => Vec::new(),
+                    Some(Expr::List { items, separator, bracketed: false }) => {
+                        return Ok(Expr::List { items, separator, bracketed: true });
+                    }
+                    Some(expr) => vec![expr],
+                };
+                Ok(Expr::List { items, separator: ListSeparator::Space, bracketed: true })
+            }
+            '$' => Ok(Expr::Variable { namespace: None, name: self.parse_variable_name()? }),
+            '"' | '\'' => self.parse_quoted_string(),
+            '&' => {
+                self.cursor.consume();
+                Ok(Expr::Parent)
+            }
+            '#' if next == '{' => self.parse_identifier_expression(),
+            '#' => {
+                self.cursor.consume();
+                let digits = self.cursor.consume_while(is_name_char);
+                let text = format!("#{}", digits);
+                Ok(match Color::from_hex(&digits) {
+                    Some(color) => Expr::Color(Color { original: Some(text), ..color }),
+                    None => Expr::ident(text),
+                })
+            }
+            c if c.is_ascii_digit() || (c == '.' && next.is_ascii_digit()) => self.parse_number(),
+            '-' | '+' if next.is_ascii_digit() || (next == '.' && self.cursor.peek_n(2).is_ascii_digit()) => {
+                self.parse_number()
+            }
+            c if is_name_start(c) || c == '-' || c == '\\' => self.parse_identifier_expression(),
+            '%' | '/' | '*' | '=' | '<' | '>' | '~' | '|' | '^' | '@' => {
+                // Loose punctuation in plain CSS values, such as `1 / span 2` after interpolation
+                self.cursor.consume();
+                Ok(Expr::ident(c.to_string()))
+            }
+            _ => self.error(format!("expected expression, found `{}`", c)),
+        }
+    }
+
+    fn parse_parenthesized(&mut self) -> Result<Expr> {
+        self.cursor.expect('(')?;
+        let saved_stops = std::mem::take(&mut self.stop_words);
+        let expr = self.parse_parenthesized_inner();
+        self.stop_words = saved_stops;
+        expr
+    }
+
+    fn parse_parenthesized_inner(&mut self) -> Result<Expr> {
+        self.skip_trivia();
+        if self.cursor.consume_str(")") {
+            return Ok(Expr::List { items: Vec::new(), separator: ListSeparator::Space, bracketed: false });
+        }
+        let first = self.parse_space_list()?;
+        self.skip_trivia();
+        match self.cursor.peek() {
+            ':' => {
+                self.cursor.consume();
+                let mut entries = vec![(first, self.parse_space_list()?)];
+                self.skip_trivia();
+                while self.cursor.consume_str(",") {
+                    self.skip_trivia();
+                    if self.cursor.peek() == ')' {
+                        break;
+                    }
+                    let key = self.parse_space_list()?;
+                    self.skip_trivia();
+                    self.cursor.expect(':')?;
+                    entries.push((key, self.parse_space_list()?));
+                    self.skip_trivia();
+                }
+                self.cursor.expect(')')?;
+                Ok(Expr::Map(entries))
+            }
+            ',' => {
+                let mut items = vec![first];
+                while self.cursor.consume_str(",") {
+                    self.skip_trivia();
+                    if self.cursor.peek() == ')' {
+                        break;
+                    }
+                    items.push(self.parse_space_list()?);
+                    self.skip_trivia();
+                }
+                self.cursor.expect(')')?;
+                Ok(Expr::Paren(Box::new(Expr::List { items, separator: ListSeparator::Comma, bracketed: false })))
+            }
+            _ => {
+                self.cursor.expect(')')?;
+                Ok(Expr::Paren(Box::new(first)))
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Expr> {
+        let start = self.cursor.pos;
+        if matches!(self.cursor.peek(), '-' | '+') {
+            self.cursor.consume();
+        }
+        self.cursor.consume_while(|c| c.is_ascii_digit());
+        if self.cursor.peek() == '.' && self.cursor.peek_n(1).is_ascii_digit() {
+            self.cursor.consume();
+            self.cursor.consume_while(|c| c.is_ascii_digit());
+        }
+        let exponent = self.cursor.peek_n(1);
+        if matches!(self.cursor.peek(), 'e' | 'E')
+            && (exponent.is_ascii_digit() || (matches!(exponent, '-' | '+') && self.cursor.peek_n(2).is_ascii_digit()))
+        {
+            self.cursor.consume_n(2);
+            self.cursor.consume_while(|c| c.is_ascii_digit());
+        }
+        let text = self.cursor.current_str(start);
+        let value = text.parse::<f64>().or_else(|_| self.error(format!("invalid number `{}`", text)))?;
+
+        let unit = if self.cursor.consume_str("%") {
+            "%".to_string()
+        }
+        else if is_name_start(self.cursor.peek()) {
+            let mut unit = String::new();
+            while is_name_start(self.cursor.peek())
+                || self.cursor.peek().is_ascii_digit()
+                || (self.cursor.peek() == '-' && is_name_start(self.cursor.peek_n(1)))
+            {
+                unit.push(self.cursor.consume());
+            }
+            unit
+        }
+        else {
+            String::new()
+        };
+        Ok(Expr::Number(value, unit))
+    }
+
+    fn parse_quoted_string(&mut self) -> Result<Expr> {
+        let quote = self.cursor.consume();
+        let mut value = Interpolation::default();
+        loop {
+            if self.cursor.is_eof() {
+                return self.error("unterminated string");
+            }
+            if self.cursor.peek_str("#{") {
+                value.parts.push(InterpolationPart::Expr(self.parse_interpolation()?));
+                continue;
+            }
+            let c = self.cursor.consume();
+            if c == quote {
+                break;
+            }
+            value.push_char(c);
+            if c == '\\' && !self.cursor.is_eof() {
+                value.push_char(self.cursor.consume());
+            }
+        }
+        Ok(Expr::String { value, quote: Some(quote) })
+    }
+
+    /// Reads an identifier with `#{}` parts, then decides whether it is a keyword, a color, a function
+    /// call or a plain string.
+    fn parse_identifier_expression(&mut self) -> Result<Expr> {
+        let ident = self.parse_interpolated_identifier()?;
+        let Some(name) = ident.as_plain()
+        else {
+            return Ok(Expr::String { value: ident, quote: None });
+        };
+
+        if self.cursor.peek() == '(' {
+            let lower = name.to_ascii_lowercase();
+            if SPECIAL_FUNCTIONS.contains(&lower.as_str()) && !(lower == "url" && self.url_is_expression()) {
+                return self.parse_special_function(&name);
+            }
+            return Ok(Expr::Call { namespace: None, name, args: self.parse_arguments()? });
+        }
+        if self.cursor.peek() == '.' && !name.starts_with('-') {
+            let next = self.cursor.peek_n(1);
+            if next == '$' {
+                self.cursor.consume();
+                return Ok(Expr::Variable { namespace: Some(name), name: self.parse_variable_name()? });
+            }
+            if is_name_start(next) {
+                self.cursor.consume();
+                let function = self.parse_name()?;
+                if self.cursor.peek() != '(' {
+                    return self.error(format!("expected `(` after `{}.{}`", name, function));
+                }
+                return Ok(Expr::Call { namespace: Some(name), name: function, args: self.parse_arguments()? });
+            }
+        }
+        Ok(match name.as_str() {
+            "true" => Expr::Bool(true),
+            "false" => Expr::Bool(false),
+            "null" => Expr::Null,
+            _ => match Color::named(&name) {
+                Some(color) => Expr::Color(Color { original: Some(name), ..color }),
+                None => Expr::String { value: Interpolation::plain(name), quote: None },
+            },
+        })
+    }
+
+    fn parse_interpolated_identifier(&mut self) -> Result<Interpolation> {
+        let mut ident = Interpolation::default();
+        loop {
+            let c = self.cursor.peek();
+            if self.cursor.peek_str("#{") {
+                ident.parts.push(InterpolationPart::Expr(self.parse_interpolation()?));
+            }
+            else if c == '\\' {
+                ident.push_char(self.cursor.consume());
+                ident.push_char(self.cursor.consume());
+            }
+            else if is_name_char(c) {
+                ident.push_char(self.cursor.consume());
+            }
+            else {
+                break;
+            }
+        }
+        if ident.is_empty() {
+            return self.error("expected identifier");
+        }
+        Ok(ident)
+    }
+
+    /// `url($path)` and `url("a" + $b)` are function calls; `url(a.png)` is plain text.
+    fn url_is_expression(&self) -> bool {
+        let inner = self.cursor.source[self.cursor.pos + 1..].trim_start();
+        inner.starts_with('$') || inner.starts_with('"') || inner.starts_with('\'')
+    }
+
+    /// Keeps the arguments of `calc()`, `var()` and friends as written, substituting `#{}` and
+    /// `$variables`.
+    fn parse_special_function(&mut self, name: &str) -> Result<Expr> {
+        let mut text = Interpolation::plain(name);
+        let mut depth = 0usize;
+        let mut quote = None;
+        loop {
+            if self.cursor.is_eof() {
+                return self.error(format!("unclosed `{}(`", name));
+            }
+            if self.cursor.peek_str("#{") {
+                text.parts.push(InterpolationPart::Expr(self.parse_interpolation()?));
+                continue;
+            }
+            let c = self.cursor.peek();
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None => match c {
+                    '"' | '\'' => quote = Some(c),
+                    '$' if is_name_start(self.cursor.peek_n(1)) => {
+                        let name = self.parse_variable_name()?;
+                        text.parts.push(InterpolationPart::Expr(Expr::Variable { namespace: None, name }));
+                        continue;
+                    }
+                    '(' => depth += 1,
+                    ')' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            text.push_char(self.cursor.consume());
+                            break;
+                        }
+                    }
+                    _ => {}
+                },
+            }
+            text.push_char(self.cursor.consume());
+        }
+        Ok(Expr::String { value: text, quote: None })
+    }
+}
+
+/// Trims leading whitespace of the first text part and trailing whitespace of the last one.
+fn trim_interpolation(mut text: Interpolation) -> Interpolation {
+    if let Some(InterpolationPart::Text(first)) = text.parts.first_mut() {
+        *first = first.trim_start().to_string();
+    }
+    if let Some(InterpolationPart::Text(last)) = text.parts.last_mut() {
+        *last = last.trim_end().to_string();
+    }
+    text.parts.retain(|part| !matches!(part, InterpolationPart::Text(t) if t.is_empty()));
+    text
+}
@@ -0,0 +1,78 @@
+//! Selector lists: splitting, nesting with `&` and rewriting for `@extend`.
+
+use crate::parser::is_name_char;
+
+/// Splits a selector list at top-level commas.
+pub fn split_selector_list(text: &str) -> Vec<String> {
+    let mut selectors = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                selectors.push(text[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    selectors.push(text[start..].trim().to_string());
+    selectors.retain(|selector| !selector.is_empty());
+    selectors
+}
+
+/// Combines each parent selector with each child: `&` stands for the parent and a child without one
+/// is a descendant.
+pub fn resolve_selectors(parents: &[String], text: &str) -> Vec<String> {
+    let children = split_selector_list(text);
+    if parents.is_empty() {
+        return children.into_iter().map(|child| child.replace('&', "").trim().to_string()).collect();
+    }
+    let mut resolved = Vec::with_capacity(parents.len() * children.len());
+    for parent in parents {
+        for child in &children {
+            resolved.push(if child.contains('&') { child.replace('&', parent) } else { format!("{} {}", parent, child) });
+        }
+    }
+    resolved
+}
+
+/// Replaces the simple selector `target` in `selector` with `extender`. The ancestors of a complex
+/// extender go before the compound selector holding the target: `.a .btn:hover` extended by
+/// `.nav .link` is `.a .nav .link:hover`.
+pub fn extend_selector(selector: &str, target: &str, extender: &str) -> Option<String> {
+    let mut start = 0;
+    while let Some(found) = selector[start..].find(target) {
+        let at = start + found;
+        let end = at + target.len();
+        start = end;
+        let before_ok = !target.starts_with(is_name_char)
+            || selector[..at].chars().last().is_none_or(|c| !is_name_char(c) && !matches!(c, '.' | '#' | '%'));
+        let after_ok = selector[end..].chars().next().is_none_or(|c| !is_name_char(c));
+        if !before_ok || !after_ok {
+            continue;
+        }
+        let (ancestors, last) = match extender.rfind(' ') {
+            Some(i) => (Some(&extender[..i]), &extender[i + 1..]),
+            None => (None, extender),
+        };
+        let compound_start =
+            selector[..at].rfind(|c: char| c.is_whitespace() || matches!(c, '>' | '+' | '~')).map_or(0, |i| i + 1);
+        let mut extended = selector[..compound_start].to_string();
+        if let Some(ancestors) = ancestors {
+            extended.push_str(ancestors);
+            extended.push(' ');
+        }
+        extended.push_str(&selector[compound_start..at]);
+        extended.push_str(last);
+        extended.push_str(&selector[end..]);
+        return Some(extended);
+    }
+    None
+}
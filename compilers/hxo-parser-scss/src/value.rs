@@ -0,0 +1,667 @@
+//! Runtime values: unit-aware numbers, colors, strings, lists and maps, with Sass's operators.
+
+use crate::ast::ListSeparator;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(Number),
+    Color(Color),
+    /// Text and the quote it was written with; `None` for identifiers and other unquoted strings.
+    String(String, Option<char>),
+    List(Vec<Value>, ListSeparator, bool),
+    Map(Vec<(Value, Value)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number {
+    pub value: f64,
+    pub unit: String,
+}
+
+/// An sRGB color: channels in `0..=255`, alpha in `0..=1`. `original` keeps how a literal was written
+/// (`red`, `#FFF`) so colors that are only passed around print unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Color {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+    pub alpha: f64,
+    pub original: Option<String>,
+}
+
+impl Value {
+    pub fn ident(text: impl Into<String>) -> Self {
+        Value::String(text.into(), None)
+    }
+
+    pub fn quoted(text: impl Into<String>) -> Self {
+        Value::String(text.into(), Some('"'))
+    }
+
+    pub fn number(value: f64, unit: impl Into<String>) -> Self {
+        Value::Number(Number::new(value, unit))
+    }
+
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Null | Value::Bool(false))
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Number(_) => "number",
+            Value::Color(_) => "color",
+            Value::String(..) => "string",
+            Value::List(..) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+
+    /// Whether a declaration with this value is left out of the output.
+    pub fn is_blank(&self) -> bool {
+        match self {
+            Value::Null => true,
+            Value::List(items, _, false) => items.iter().all(Value::is_blank),
+            _ => false,
+        }
+    }
+
+    /// The value as a list: maps are lists of `key value` pairs and any other value a list of one.
+    pub fn as_list(&self) -> Vec<Value> {
+        match self {
+            Value::List(items, ..) => items.clone(),
+            Value::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| Value::List(vec![key.clone(), value.clone()], ListSeparator::Space, false))
+                .collect(),
+            other => vec![other.clone()],
+        }
+    }
+
+    pub fn separator(&self) -> ListSeparator {
+        match self {
+            Value::List(_, separator, _) => *separator,
+            Value::Map(_) => ListSeparator::Comma,
+            _ => ListSeparator::Space,
+        }
+    }
+
+    /// The value as a map; an empty list is the empty map.
+    pub fn as_map(&self) -> Option<Vec<(Value, Value)>> {
+        match self {
+            Value::Map(entries) => Some(entries.clone()),
+            Value::List(items, ..) if items.is_empty() => Some(Vec::new()),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Value::Number(number) => Some(number),
+            _ => None,
+        }
+    }
+
+    /// Text of a string or identifier, without quotes.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(text, _) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The CSS text of the value, or why it has none (maps, empty lists).
+    pub fn to_css(&self) -> Result<String, String> {
+        self.serialize(false)
+    }
+
+    pub fn serialize(&self, compressed: bool) -> Result<String, String> {
+        match self {
+            Value::Null => Ok(String::new()),
+            Value::Bool(value) => Ok(value.to_string()),
+            Value::Number(number) => Ok(number.to_string()),
+            Value::Color(color) => Ok(color.to_css()),
+            Value::String(text, None) => Ok(text.clone()),
+            Value::String(text, Some(quote)) => Ok(quote_string(text, *quote)),
+            Value::List(items, separator, bracketed) => {
+                if items.is_empty() && !bracketed {
+                    return Err("() isn't a valid CSS value.".to_string());
+                }
+                let separator = match (separator, compressed) {
+                    (ListSeparator::Space, _) => " ",
+                    (ListSeparator::Comma, false) => ", ",
+                    (ListSeparator::Comma, true) => ",",
+                    (ListSeparator::Slash, _) => "/",
+                };
+                let mut parts = Vec::with_capacity(items.len());
+                for item in items.iter().filter(|item| !item.is_blank()) {
+                    parts.push(item.serialize(compressed)?);
+                }
+                let joined = parts.join(separator);
+                Ok(if *bracketed { format!("[{}]", joined) } else { joined })
+            }
+            Value::Map(_) => Err(format!("{} isn't a valid CSS value.", self.inspect())),
+        }
+    }
+
+    /// How `#{...}` prints the value: strings lose their quotes.
+    pub fn interpolated(&self) -> Result<String, String> {
+        match self {
+            Value::String(text, _) => Ok(text.clone()),
+            Value::List(items, ..) if items.is_empty() => Ok(String::new()),
+            Value::Map(_) => Ok(self.inspect()),
+            other => other.to_css(),
+        }
+    }
+
+    /// A representation of any value, maps and empty lists included, for `@debug` and `inspect()`.
+    pub fn inspect(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::List(items, separator, bracketed) => {
+                let separator = match separator {
+                    ListSeparator::Space => " ",
+                    ListSeparator::Comma => ", ",
+                    ListSeparator::Slash => " / ",
+                };
+                let inner = items.iter().map(Value::inspect).collect::<Vec<_>>().join(separator);
+                if *bracketed {
+                    format!("[{}]", inner)
+                }
+                else if items.is_empty() {
+                    "()".to_string()
+                }
+                else {
+                    inner
+                }
+            }
+            Value::Map(entries) => format!(
+                "({})",
+                entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.inspect(), value.inspect()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            other => other.to_css().unwrap_or_default(),
+        }
+    }
+
+    /// Sass equality: numbers compare across compatible units and quoted strings equal unquoted ones.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => {
+                a.unit.is_empty() == b.unit.is_empty() && b.convert_to(&a.unit).is_some_and(|b| fuzzy_eq(a.value, b))
+            }
+            (Value::Color(a), Value::Color(b)) => {
+                fuzzy_eq(a.red, b.red) && fuzzy_eq(a.green, b.green) && fuzzy_eq(a.blue, b.blue) && fuzzy_eq(a.alpha, b.alpha)
+            }
+            (Value::String(a, _), Value::String(b, _)) => a == b,
+            (Value::List(a, sa, ba), Value::List(b, sb, bb)) => {
+                a.len() == b.len() && (sa == sb || a.len() < 2) && ba == bb && a.iter().zip(b).all(|(a, b)| a.equals(b))
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                a.len() == b.len() && a.iter().all(|(key, value)| b.iter().any(|(k, v)| k.equals(key) && v.equals(value)))
+            }
+            (Value::List(items, ..), Value::Map(entries)) | (Value::Map(entries), Value::List(items, ..)) => {
+                items.is_empty() && entries.is_empty()
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    pub fn add(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.combine(b, "+", |a, b| a + b).map(Value::Number),
+            (Value::String(a, quote), _) => Ok(Value::String(format!("{}{}", a, other.interpolated()?), *quote)),
+            (_, Value::String(b, _)) => Ok(Value::String(format!("{}{}", self.interpolated()?, b), None)),
+            (Value::Color(_), _) | (_, Value::Color(_)) => {
+                Err(format!("Undefined operation \"{} + {}\".", self.inspect(), other.inspect()))
+            }
+            _ => Ok(Value::ident(format!("{}{}", self.interpolated()?, other.interpolated()?))),
+        }
+    }
+
+    pub fn sub(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.combine(b, "-", |a, b| a - b).map(Value::Number),
+            (Value::Color(_), _) | (_, Value::Color(_)) => {
+                Err(format!("Undefined operation \"{} - {}\".", self.inspect(), other.inspect()))
+            }
+            _ => Ok(Value::ident(format!("{}-{}", self.interpolated()?, other.interpolated()?))),
+        }
+    }
+
+    pub fn mul(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => match (a.unit.is_empty(), b.unit.is_empty()) {
+                (true, _) => Ok(Value::number(a.value * b.value, b.unit.clone())),
+                (_, true) => Ok(Value::number(a.value * b.value, a.unit.clone())),
+                _ => Err(format!("{}*{} isn't a valid CSS value.", a, b)),
+            },
+            _ => Err(format!("Undefined operation \"{} * {}\".", self.inspect(), other.inspect())),
+        }
+    }
+
+    pub fn div(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => {
+                if b.unit.is_empty() {
+                    return Ok(Value::number(a.value / b.value, a.unit.clone()));
+                }
+                match a.convert_from(b) {
+                    Some(a_value) => Ok(Value::number(a_value / b.value, "")),
+                    None => Err(format!("{}/{} isn't a valid CSS value.", a, b)),
+                }
+            }
+            _ => Ok(Value::ident(format!("{}/{}", self.interpolated()?, other.interpolated()?))),
+        }
+    }
+
+    pub fn rem(&self, other: &Value) -> Result<Value, String> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a
+                .combine(b, "%", |a, b| {
+                    let r = a % b;
+                    if r != 0.0 && (r < 0.0) != (b < 0.0) { r + b } else { r }
+                })
+                .map(Value::Number),
+            _ => Err(format!("Undefined operation \"{} % {}\".", self.inspect(), other.inspect())),
+        }
+    }
+
+    /// `<`, `<=`, `>` and `>=` on numbers of compatible units.
+    pub fn compare(&self, other: &Value) -> Result<std::cmp::Ordering, String> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => {
+                let b_value = b.convert_to(&a.unit).ok_or_else(|| format!("Incompatible units {} and {}.", a.unit, b.unit))?;
+                if fuzzy_eq(a.value, b_value) {
+                    return Ok(std::cmp::Ordering::Equal);
+                }
+                Ok(a.value.partial_cmp(&b_value).unwrap_or(std::cmp::Ordering::Equal))
+            }
+            _ => Err(format!("Undefined operation \"{} < {}\".", self.inspect(), other.inspect())),
+        }
+    }
+}
+
+fn fuzzy_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-10
+}
+
+/// Quotes `text` with `quote`. The text keeps the escapes it was written with, so only a bare quote
+/// character gets one.
+fn quote_string(text: &str, quote: char) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push(quote);
+    let mut escaped = false;
+    for c in text.chars() {
+        if c == quote && !escaped {
+            out.push('\\');
+        }
+        escaped = c == '\\' && !escaped;
+        out.push(c);
+    }
+    out.push(quote);
+    out
+}
+
+impl Number {
+    pub fn new(value: f64, unit: impl Into<String>) -> Self {
+        Self { value, unit: unit.into() }
+    }
+
+    /// The value in `unit`: unitless numbers fit any unit.
+    pub fn convert_to(&self, unit: &str) -> Option<f64> {
+        if self.unit.is_empty() || unit.is_empty() || self.unit.eq_ignore_ascii_case(unit) {
+            return Some(self.value);
+        }
+        let (from_dimension, from_factor) = unit_factor(&self.unit)?;
+        let (to_dimension, to_factor) = unit_factor(unit)?;
+        (from_dimension == to_dimension).then(|| self.value * from_factor / to_factor)
+    }
+
+    /// This number in `other`'s unit.
+    fn convert_from(&self, other: &Number) -> Option<f64> {
+        if self.unit.is_empty() {
+            return None;
+        }
+        self.convert_to(&other.unit)
+    }
+
+    pub fn is_comparable(&self, other: &Number) -> bool {
+        other.convert_to(&self.unit).is_some()
+    }
+
+    /// Applies `op` in the unit of the left operand, or the right one's when the left has none.
+    fn combine(&self, other: &Number, op: &str, f: impl Fn(f64, f64) -> f64) -> Result<Number, String> {
+        let unit = if self.unit.is_empty() { &other.unit } else { &self.unit };
+        let right =
+            other.convert_to(unit).ok_or_else(|| format!("{} and {} have incompatible units for \"{}\".", self, other, op))?;
+        Ok(Number::new(f(self.value, right), unit.clone()))
+    }
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", format_number(self.value), self.unit)
+    }
+}
+
+/// Prints a number the way Sass does: at most ten decimals, no trailing zeros and no `-0`.
+pub fn format_number(value: f64) -> String {
+    if !value.is_finite() {
+        return if value.is_nan() {
+            "NaN".to_string()
+        }
+        else if value > 0.0 {
+            "Infinity".to_string()
+        }
+        else {
+            "-Infinity".to_string()
+        };
+    }
+    let rounded = (value * 1e10).round() / 1e10;
+    if rounded == 0.0 {
+        return "0".to_string();
+    }
+    if rounded.fract() == 0.0 && rounded.abs() < 1e15 {
+        return format!("{}", rounded as i64);
+    }
+    let text = format!("{:.10}", rounded);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// The dimension a unit measures and its size in that dimension's canonical unit.
+fn unit_factor(unit: &str) -> Option<(&'static str, f64)> {
+    let unit = unit.to_ascii_lowercase();
+    Some(match unit.as_str() {
+        "px" => ("length", 1.0),
+        "in" => ("length", 96.0),
+        "cm" => ("length", 96.0 / 2.54),
+        "mm" => ("length", 96.0 / 25.4),
+        "q" => ("length", 96.0 / 101.6),
+        "pt" => ("length", 4.0 / 3.0),
+        "pc" => ("length", 16.0),
+        "deg" => ("angle", 1.0),
+        "grad" => ("angle", 0.9),
+        "rad" => ("angle", 180.0 / std::f64::consts::PI),
+        "turn" => ("angle", 360.0),
+        "ms" => ("time", 1.0),
+        "s" => ("time", 1000.0),
+        "hz" => ("frequency", 1.0),
+        "khz" => ("frequency", 1000.0),
+        "dppx" | "x" => ("resolution", 96.0),
+        "dpi" => ("resolution", 1.0),
+        "dpcm" => ("resolution", 2.54),
+        _ => return None,
+    })
+}
+
+impl Color {
+    pub fn rgba(red: f64, green: f64, blue: f64, alpha: f64) -> Self {
+        Self {
+            red: red.clamp(0.0, 255.0),
+            green: green.clamp(0.0, 255.0),
+            blue: blue.clamp(0.0, 255.0),
+            alpha: alpha.clamp(0.0, 1.0),
+            original: None,
+        }
+    }
+
+    /// Parses the digits of a 3, 4, 6 or 8 digit hex color.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).ok().map(f64::from);
+        let doubled = |i: usize| channel(&hex[i..i + 1].repeat(2));
+        let (r, g, b, a) = match hex.len() {
+            3 => (doubled(0)?, doubled(1)?, doubled(2)?, 255.0),
+            4 => (doubled(0)?, doubled(1)?, doubled(2)?, doubled(3)?),
+            6 => (channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?, 255.0),
+            8 => (channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?, channel(&hex[6..8])?),
+            _ => return None,
+        };
+        Some(Self::rgba(r, g, b, a / 255.0))
+    }
+
+    /// A CSS named color (`red`, `rebeccapurple`, `transparent`).
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Self::rgba(0.0, 0.0, 0.0, 0.0));
+        }
+        let index = NAMED_COLORS.binary_search_by(|(candidate, _)| candidate.cmp(&name.as_str())).ok()?;
+        let rgb = NAMED_COLORS[index].1;
+        Some(Self::rgba(f64::from((rgb >> 16) & 0xff), f64::from((rgb >> 8) & 0xff), f64::from(rgb & 0xff), 1.0))
+    }
+
+    /// `hue` in degrees, `saturation` and `lightness` in percent.
+    pub fn hsla(hue: f64, saturation: f64, lightness: f64, alpha: f64) -> Self {
+        let h = hue.rem_euclid(360.0) / 360.0;
+        let s = (saturation / 100.0).clamp(0.0, 1.0);
+        let l = (lightness / 100.0).clamp(0.0, 1.0);
+        let m2 = if l <= 0.5 { l * (s + 1.0) } else { l + s - l * s };
+        let m1 = l * 2.0 - m2;
+        let channel = |mut h: f64| {
+            if h < 0.0 {
+                h += 1.0;
+            }
+            if h > 1.0 {
+                h -= 1.0;
+            }
+            let v = if h * 6.0 < 1.0 {
+                m1 + (m2 - m1) * h * 6.0
+            }
+            else if h * 2.0 < 1.0 {
+                m2
+            }
+            else if h * 3.0 < 2.0 {
+                m1 + (m2 - m1) * (2.0 / 3.0 - h) * 6.0
+            }
+            else {
+                m1
+            };
+            v * 255.0
+        };
+        Self::rgba(channel(h + 1.0 / 3.0), channel(h), channel(h - 1.0 / 3.0), alpha)
+    }
+
+    /// Hue in degrees, saturation and lightness in percent.
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let (r, g, b) = (self.red / 255.0, self.green / 255.0, self.blue / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+        let lightness = (max + min) / 2.0;
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness * 100.0);
+        }
+        let saturation = if lightness < 0.5 { delta / (max + min) } else { delta / (2.0 - max - min) };
+        let hue = if max == r {
+            (g - b) / delta + if g < b { 6.0 } else { 0.0 }
+        }
+        else if max == g {
+            (b - r) / delta + 2.0
+        }
+        else {
+            (r - g) / delta + 4.0
+        };
+        (hue * 60.0, saturation * 100.0, lightness * 100.0)
+    }
+
+    pub fn to_css(&self) -> String {
+        if let Some(original) = &self.original {
+            return original.clone();
+        }
+        let (r, g, b) = (self.red.round() as u8, self.green.round() as u8, self.blue.round() as u8);
+        if self.alpha >= 1.0 {
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+        else {
+            format!("rgba({}, {}, {}, {})", r, g, b, format_number(self.alpha))
+        }
+    }
+
+    /// The same color without the literal text it was written with.
+    pub fn computed(mut self) -> Self {
+        self.original = None;
+        self
+    }
+}
+
+/// CSS named colors, sorted for binary search.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
@@ -33,3 +33,164 @@ fn test_compile_comments_scss() {
     assert!(result.contains("color: red"));
     assert!(result.contains(".test"));
 }
+
+#[test]
+fn test_tailwind_directives_pass_through() {
+    let scss = "$gap: 4px;\n@layer components {\n  .btn { @apply px-4 hover:bg-blue-500; font-family: sans-serif; color: theme('colors.blue.500'); margin: $gap; }\n}\n@screen md { .a { color: red; } }";
+    let result = compile(scss, &ScssParserOptions::default()).unwrap();
+    assert!(result.contains("@layer components {\n  .btn {\n    @apply px-4 hover:bg-blue-500;\n"), "{}", result);
+    assert!(result.contains("font-family: sans-serif;"));
+    assert!(result.contains("color: theme('colors.blue.500');"));
+    assert!(result.contains("margin: 4px;"));
+    assert!(result.contains("@screen md {\n  .a {\n    color: red;\n  }\n}"));
+}
+
+fn compile_default(scss: &str) -> String {
+    compile(scss, &ScssParserOptions::default()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+#[test]
+fn test_mixins_with_arguments_and_content() {
+    let scss = r#"
+@mixin button($bg, $fg: white, $args...) {
+  background: $bg;
+  color: $fg;
+  margin: $args;
+  &:hover { @content; }
+}
+@mixin breakpoint($width) {
+  @media (min-width: $width) { @content($width); }
+}
+.btn {
+  @include button(blue, white, 1px, 2px) { opacity: 0.8; }
+  @include breakpoint(768px) using ($w) { width: $w; }
+}
+"#;
+    let css = compile_default(scss);
+    assert!(css.contains(".btn {\n  background: blue;\n  color: white;\n  margin: 1px, 2px;\n}"), "{}", css);
+    assert!(css.contains(".btn:hover {\n  opacity: 0.8;\n}"), "{}", css);
+    assert!(css.contains("@media (min-width: 768px) {\n  .btn {\n    width: 768px;\n  }\n}"), "{}", css);
+}
+
+#[test]
+fn test_functions_and_control_flow() {
+    let scss = r#"
+$sizes: (sm: 4px, md: 8px, lg: 16px);
+@function double($n) { @return $n * 2; }
+@function sum($numbers...) {
+  $total: 0;
+  @each $n in $numbers { $total: $total + $n; }
+  @return $total;
+}
+@each $name, $size in $sizes {
+  .p-#{$name} { padding: double($size); }
+}
+@for $i from 1 through 3 {
+  .w-#{$i} { width: percentage($i / 4); }
+}
+$i: 2;
+@while $i > 0 {
+  .z-#{$i} { z-index: $i; }
+  $i: $i - 1;
+}
+.t {
+  @if sum(1, 2, 3) == 6 { total: ok; } @else { total: bad; }
+  font: 12px/1.5 sans-serif;
+  height: (10px / 2);
+  content: if(false, "a", "b");
+}
+"#;
+    let css = compile_default(scss);
+    assert!(css.contains(".p-sm {\n  padding: 8px;\n}"), "{}", css);
+    assert!(css.contains(".p-lg {\n  padding: 32px;\n}"), "{}", css);
+    assert!(css.contains(".w-3 {\n  width: 75%;\n}"), "{}", css);
+    assert!(css.contains(".z-2 {\n  z-index: 2;\n}\n\n.z-1"), "{}", css);
+    assert!(!css.contains(".z-0"), "{}", css);
+    assert!(css.contains("total: ok;"), "{}", css);
+    assert!(css.contains("font: 12px/1.5 sans-serif;"), "{}", css);
+    assert!(css.contains("height: 5px;"), "{}", css);
+    assert!(css.contains("content: \"b\";"), "{}", css);
+}
+
+#[test]
+fn test_extend_and_placeholders() {
+    let scss = r#"
+%message { border: 1px solid gray; }
+.alert { color: red; }
+.success { @extend %message; color: green; }
+.error { @extend %message; @extend .alert; }
+"#;
+    let css = compile_default(scss);
+    assert!(css.contains(".success, .error {\n  border: 1px solid gray;\n}"), "{}", css);
+    assert!(css.contains(".alert, .error {\n  color: red;\n}"), "{}", css);
+    assert!(!css.contains('%'), "{}", css);
+    assert!(compile(".a { @extend .missing; }", &ScssParserOptions::default()).is_err());
+    assert!(compile(".a { @extend .missing !optional; color: red; }", &ScssParserOptions::default()).is_ok());
+}
+
+#[test]
+fn test_builtin_modules() {
+    let scss = r#"
+@use "sass:math";
+@use "sass:color";
+@use "sass:map";
+$theme: (primary: #3366ff);
+.a {
+  color: lighten(#333, 20%);
+  background: color.adjust(map.get($theme, primary), $alpha: -0.5);
+  border-color: mix(#000, #fff);
+  width: math.div(100%, 3);
+  height: math.round(2.6px);
+  top: math.$pi * 0;
+  left: max(1px, 3px);
+  right: calc(100% - #{10px * 2});
+}
+"#;
+    let css = compile_default(scss);
+    assert!(css.contains("color: #666666;"), "{}", css);
+    assert!(css.contains("background: rgba(51, 102, 255, 0.5);"), "{}", css);
+    assert!(css.contains("border-color: #808080;") || css.contains("border-color: gray;"), "{}", css);
+    assert!(css.contains("width: 33.3333333333%;"), "{}", css);
+    assert!(css.contains("height: 3px;"), "{}", css);
+    assert!(css.contains("top: 0;"), "{}", css);
+    assert!(css.contains("left: 3px;"), "{}", css);
+    assert!(css.contains("right: calc(100% - 20px);"), "{}", css);
+}
+
+#[test]
+fn test_modules_resolved_through_load_paths() {
+    let dir = std::env::temp_dir().join(format!("hxo-scss-modules-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("tokens")).unwrap();
+    std::fs::write(dir.join("tokens/_colors.scss"), "$primary: #0044cc !default;\n$radius: 4px !default;\n").unwrap();
+    std::fs::write(
+        dir.join("tokens/_index.scss"),
+        "@forward \"colors\";\n@use \"colors\" as *;\n@mixin rounded { border-radius: $radius; }\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("_legacy.scss"), ".legacy { color: $legacy-color; }\n").unwrap();
+
+    let options = ScssParserOptions { load_paths: vec![dir.clone()], ..ScssParserOptions::default() };
+    let scss = r#"
+@use "tokens" with ($radius: 8px);
+$legacy-color: tokens.$primary;
+@import "legacy";
+.card { color: tokens.$primary; @include tokens.rounded; }
+"#;
+    let css = compile(scss, &options).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".legacy {\n  color: #0044cc;\n}"), "{}", css);
+    assert!(css.contains(".card {\n  color: #0044cc;\n  border-radius: 8px;\n}"), "{}", css);
+
+    let err = compile("@use \"missing\";", &options).unwrap_err();
+    assert!(err.to_string().contains("missing"), "{}", err);
+    std::fs::write(dir.join("_broken.scss"), "@mixin broken { width: $nope; }\n").unwrap();
+    let err = compile("@use \"broken\";\n.a { @include broken.broken; }", &options).unwrap_err();
+    assert!(err.to_string().contains("_broken.scss"), "{}", err);
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_compressed_output() {
+    let options = ScssParserOptions { is_compressed: true, ..ScssParserOptions::default() };
+    let css = compile(".a { .b { margin: 0 auto; font-family: a, b; } }", &options).unwrap();
+    assert_eq!(css, ".a .b{margin:0 auto;font-family:a,b;}");
+}
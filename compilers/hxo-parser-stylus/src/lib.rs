@@ -1,139 +1,82 @@
-use hxo_parser::{ParseState, StyleParser};
-use hxo_types::Result;
-use std::collections::HashMap;
+pub mod parser;
 
-pub struct StylusParser;
+pub use parser::{parse_stylus, parse_stylus_with, to_scss};
 
-pub fn compile(source: &str) -> Result<String> {
-    let mut state = ParseState::new(source);
-    let parser = StylusParser;
-    parser.parse(&mut state, "stylus")
+use hxo_ir::Stylesheet;
+use hxo_parser::{ParseState, StyleParser};
+use hxo_parser_scss::Evaluator;
+use hxo_types::{Cursor, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StylusParserOptions {
+    pub is_compressed: bool,
+    /// Directories searched by `@import` and `@require` after the importing file's own.
+    pub load_paths: Vec<PathBuf>,
 }
 
-impl StyleParser for StylusParser {
-    fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<String> {
-        let mut parser = StylusParserImpl { state, variables: HashMap::new() };
-        parser.parse()
-    }
+pub fn compile(source: &str) -> Result<String> {
+    compile_with_options(source, &StylusParserOptions::default())
 }
 
-struct StylusParserImpl<'a, 'b> {
-    state: &'a mut ParseState<'b>,
-    variables: HashMap<String, String>,
+pub fn compile_with_options(source: &str, options: &StylusParserOptions) -> Result<String> {
+    let sheet = evaluate(&mut Cursor::new(source), None, options)?;
+    Ok(to_css(&sheet, options.is_compressed))
 }
 
-impl<'a, 'b> StylusParserImpl<'a, 'b> {
-    pub fn parse(&mut self) -> Result<String> {
-        let mut css = String::new();
-        let mut selector_stack: Vec<(usize, String)> = Vec::new();
-
-        while !self.state.cursor.is_eof() {
-            let (indent, line) = self.consume_line_with_indent()?;
-            if line.is_empty() || line.starts_with("//") {
-                continue;
-            }
-
-            // Handle variables: var = value
-            if line.contains('=') && !line.contains('{') && !line.contains(':') {
-                self.parse_variable_line(&line)?;
-                continue;
-            }
-
-            // Determine if it's a property or selector
-            // In Stylus, if it's indented and contains multiple words or a colon, it's likely a property
-            let is_property = indent > 0 && (line.contains(':') || line.contains(' '));
-
-            if is_property {
-                let (prop, val) = if line.contains(':') {
-                    let parts: Vec<&str> = line.splitn(2, ':').collect();
-                    (parts[0].trim().to_string(), parts[1].trim().to_string())
-                }
-                else {
-                    let parts: Vec<&str> = line.splitn(2, ' ').collect();
-                    if parts.len() == 2 {
-                        (parts[0].trim().to_string(), parts[1].trim().to_string())
-                    }
-                    else {
-                        (line.clone(), "".to_string())
-                    }
-                };
-
-                let mut final_val = val;
-                for (name, var_val) in &self.variables {
-                    final_val = final_val.replace(name, var_val);
-                }
+/// Compiles a file, resolving its relative imports from its directory.
+pub fn compile_file(path: &Path, options: &StylusParserOptions) -> Result<String> {
+    let source = std::fs::read_to_string(path)?;
+    let sheet = evaluate(&mut Cursor::new(&source), Some(path), options)?;
+    Ok(to_css(&sheet, options.is_compressed))
+}
 
-                let current_selector = self.get_current_selector(&selector_stack);
-                let rule_header = format!("{} {{\n", current_selector);
-                let prop_line = format!("  {}: {};\n", prop, final_val);
+/// Like Stylus itself, top-level rules follow each other without a blank line.
+fn to_css(sheet: &Stylesheet, compressed: bool) -> String {
+    if compressed {
+        return sheet.to_css(true);
+    }
+    sheet
+        .nodes
+        .iter()
+        .map(|node| Stylesheet { nodes: vec![node.clone()] }.to_css(false).trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
-                if let Some(pos) = css.rfind(&rule_header) {
-                    let end_pos = css[pos..].find('}').map(|i| i + pos).unwrap_or(css.len());
-                    css.insert_str(end_pos, &prop_line);
-                }
-                else {
-                    css.push_str(&rule_header);
-                    css.push_str(&prop_line);
-                    css.push_str("}\n");
-                }
-            }
-            else {
-                // Selector
-                while !selector_stack.is_empty() && indent <= selector_stack.last().unwrap().0 {
-                    selector_stack.pop();
-                }
-                selector_stack.push((indent, line));
-                let current_selector = self.get_current_selector(&selector_stack);
-                // Pre-create the rule if it doesn't exist
-                let rule_header = format!("{} {{\n", current_selector);
-                if !css.contains(&rule_header) {
-                    css.push_str(&rule_header);
-                    css.push_str("}\n");
-                }
-            }
-        }
+/// Imported `.styl` files are read as Stylus, `.scss` files as SCSS.
+fn evaluate(cursor: &mut Cursor, file: Option<&Path>, options: &StylusParserOptions) -> Result<Stylesheet> {
+    let stmts = parse_stylus_with(cursor)?;
+    Evaluator::new(options.load_paths.clone())
+        .with_syntax("styl", parse_stylus)
+        .with_bare_variables(true)
+        .with_compressed(options.is_compressed)
+        .evaluate(&stmts, file)
+}
 
-        Ok(css.replace("{\n}\n", "").trim().to_string())
-    }
+#[derive(Debug, Clone, Default)]
+pub struct StylusParser {
+    options: StylusParserOptions,
+}
 
-    fn consume_line_with_indent(&mut self) -> Result<(usize, String)> {
-        let mut indent = 0;
-        while !self.state.cursor.is_eof() && (self.state.cursor.peek() == ' ' || self.state.cursor.peek() == '\t') {
-            if self.state.cursor.peek() == ' ' {
-                indent += 1;
-            }
-            else {
-                indent += 2;
-            }
-            self.state.cursor.consume();
-        }
-        let line = self.consume_until_newline()?;
-        Ok((indent, line))
+impl StylusParser {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn consume_until_newline(&mut self) -> Result<String> {
-        let start = self.state.cursor.pos;
-        while !self.state.cursor.is_eof() && self.state.cursor.peek() != '\n' {
-            self.state.cursor.consume();
-        }
-        let s = self.state.cursor.current_str(start).to_string();
-        if !self.state.cursor.is_eof() && self.state.cursor.peek() == '\n' {
-            self.state.cursor.consume();
-        }
-        Ok(s.trim().to_string())
+    pub fn with_options(options: StylusParserOptions) -> Self {
+        Self { options }
     }
+}
 
-    fn parse_variable_line(&mut self, line: &str) -> Result<()> {
-        let parts: Vec<&str> = line.splitn(2, '=').collect();
-        if parts.len() == 2 {
-            let name = parts[0].trim().to_string();
-            let value = parts[1].trim().to_string();
-            self.variables.insert(name, value);
-        }
-        Ok(())
+impl StyleParser for StylusParser {
+    fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<String> {
+        let sheet = evaluate(&mut state.cursor, None, &self.options)?;
+        Ok(to_css(&sheet, self.options.is_compressed))
     }
 
-    fn get_current_selector(&self, stack: &[(usize, String)]) -> String {
-        stack.iter().map(|(_, s)| s.as_str()).collect::<Vec<&str>>().join(" ")
+    fn parse_stylesheet(&self, state: &mut ParseState, _lang: &str) -> Result<Option<Stylesheet>> {
+        evaluate(&mut state.cursor, None, &self.options).map(Some)
     }
 }
@@ -0,0 +1,312 @@
+//! Stylus: each line is translated to SCSS and parsed by `hxo-parser-scss`. Variables keep their bare
+//! names in values and are resolved by the evaluator (see [`Evaluator::with_bare_variables`]).
+//!
+//! A line is a selector when the next line is indented below it, or when it looks like one on its
+//! own (`.a`, `&:hover`, a single word); otherwise `name value` and `name: value` are properties.
+//! `name(params)` heading a block defines a function when it returns a value and a mixin otherwise;
+//! a property named after a mixin calls it, as Stylus "transparent" mixins do.
+//!
+//! [`Evaluator::with_bare_variables`]: hxo_parser_scss::Evaluator::with_bare_variables
+
+use hxo_parser_scss::{
+    ast::Stmt,
+    indented::{self, Line, quote_imports},
+    parse_scss_with,
+    parser::{is_name_char, is_name_start},
+};
+use hxo_types::{Cursor, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Parses a whole Stylus stylesheet.
+pub fn parse_stylus(source: &str) -> Result<Vec<Stmt>> {
+    parse_stylus_with(&mut Cursor::new(source))
+}
+
+/// Parses from `cursor` to the end of its source; spans keep the lines of the Stylus source.
+pub fn parse_stylus_with(cursor: &mut Cursor) -> Result<Vec<Stmt>> {
+    indented::parse_translated(cursor, to_scss, parse_scss_with)
+}
+
+/// The SCSS equivalent of `source`, line for line.
+pub fn to_scss(source: &str) -> Result<String> {
+    let mut lines = indented::lines(source);
+    merge_selector_lines(&mut lines);
+    let scan = Scan::new(&lines);
+    let mut mixin_bodies: Vec<(usize, &str)> = Vec::new();
+    indented::to_scss(source, &lines, |i, opens| {
+        let line = &lines[i];
+        while mixin_bodies.last().is_some_and(|(indent, _)| *indent >= line.indent) {
+            mixin_bodies.pop();
+        }
+        if let Some(definition) = scan.definitions.get(&i) {
+            if !definition.function {
+                mixin_bodies.push((line.indent, &definition.name));
+            }
+            return Ok(definition.head.clone());
+        }
+        let text = line.text.trim_end_matches(';').trim_end();
+        if scan.returns.contains(&i) {
+            return Ok(format!("@return {}", text));
+        }
+        translate(line, text, opens, &scan.mixins, mixin_bodies.last().map(|(_, name)| *name))
+    })
+}
+
+struct Definition {
+    name: String,
+    function: bool,
+    /// `@mixin name($a, $b: 1)` or `@function ...`
+    head: String,
+}
+
+/// Mixin and function definitions, found before translating so calls above them are recognized.
+struct Scan {
+    /// By line index.
+    definitions: HashMap<usize, Definition>,
+    mixins: HashSet<String>,
+    /// The last lines of functions without `return`, whose value they return.
+    returns: HashSet<usize>,
+}
+
+impl Scan {
+    fn new(lines: &[Line]) -> Self {
+        let mut scan = Scan { definitions: HashMap::new(), mixins: HashSet::new(), returns: HashSet::new() };
+        for (i, line) in lines.iter().enumerate() {
+            let Some((name, params)) = call(&line.text)
+            else {
+                continue;
+            };
+            let body = lines[i + 1..].iter().take_while(|body| body.indent > line.indent).count();
+            if body == 0 {
+                continue;
+            }
+            let body_indent = lines[i + 1].indent;
+            let top = (i + 1..=i + body).filter(|&k| lines[k].indent == body_indent).collect::<Vec<_>>();
+            let names = params.split(',').map(|param| param_name(param).to_string()).collect::<Vec<_>>();
+            let returns = top.iter().any(|&k| lines[k].text.starts_with("return "));
+            let last = top.last().copied().filter(|&k| k == i + body && is_expression(&lines[k].text, &names));
+            let function = returns || last.is_some();
+            if let (false, Some(last)) = (returns, last) {
+                scan.returns.insert(last);
+            }
+            let head = if function {
+                format!("@function {}({})", name, scss_params(params, false))
+            }
+            else {
+                scan.mixins.insert(name.to_string());
+                format!("@mixin {}({})", name, scss_params(params, true))
+            };
+            scan.definitions.insert(i, Definition { name: name.to_string(), function, head });
+        }
+        scan
+    }
+}
+
+fn translate(line: &Line, text: &str, opens: bool, mixins: &HashSet<String>, in_mixin: Option<&str>) -> Result<String> {
+    let (word, rest) = match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    };
+    match word {
+        "@import" | "@require" => return Ok(format!("@import {}", quote_imports(rest))),
+        "@extend" | "@extends" => return Ok(format!("@extend {}", rest)),
+        "if" => return Ok(format!("@if {}", condition(rest))),
+        "unless" => return Ok(format!("@if not ({})", condition(rest))),
+        "else" if rest.is_empty() => return Ok("@else".to_string()),
+        "else" => match rest.strip_prefix("if ") {
+            Some(condition_text) => return Ok(format!("@else if {}", condition(condition_text))),
+            None => return line.error(format!("expected `if` after `else`, found `{}`", rest)),
+        },
+        "for" => return for_loop(line, rest),
+        "return" => return Ok(format!("@return {}", rest)),
+        _ => {}
+    }
+    if text == "{block}" {
+        return Ok("@content".to_string());
+    }
+    if text.starts_with('@') {
+        return Ok(interpolate(text));
+    }
+    if let Some(assignment) = assignment(text) {
+        return Ok(assignment);
+    }
+    if let Some(call) = text.strip_prefix('+').filter(|call| call.starts_with(is_name_start)) {
+        return Ok(format!("@include {}", call));
+    }
+    if call(text).is_some() || mixins.contains(text) {
+        return Ok(format!("@include {}", text));
+    }
+    if opens {
+        return Ok(interpolate(text));
+    }
+    if is_selector(text) {
+        return Ok(format!("{} {{}}", interpolate(text)));
+    }
+    let (name, value) = match property_colon(text) {
+        Some(colon) => (&text[..colon], text[colon + 1..].trim()),
+        None => (word, rest),
+    };
+    if value.is_empty() {
+        return Ok(format!("{} {{}}", interpolate(text)));
+    }
+    if mixins.contains(name) && in_mixin != Some(name) {
+        return Ok(format!("@include {}({})", name, value));
+    }
+    Ok(format!("{}: {}", interpolate(name), value))
+}
+
+/// `name(args)` with nothing after the closing parenthesis.
+fn call(text: &str) -> Option<(&str, &str)> {
+    let open = text.find('(')?;
+    let name = &text[..open];
+    let valid = name.starts_with(|c: char| is_name_start(c) || c == '-') && name.chars().all(is_name_char);
+    (valid && text.ends_with(')')).then(|| (name, &text[open + 1..text.len() - 1]))
+}
+
+/// Selectors that can't be properties: they start with a combinator or selector character, or are a
+/// single word.
+fn is_selector(text: &str) -> bool {
+    match text.chars().next() {
+        Some('.' | '#' | '&' | '>' | '~' | '[' | '*' | ':') => true,
+        Some('+') => !text[1..].starts_with(is_name_start),
+        _ => !text.contains(char::is_whitespace) && !text.contains(['(', '=']) && !text.starts_with('@') && text != "else",
+    }
+}
+
+/// `textarea` followed by `input` at the same indentation, heading a block, is `textarea, input`.
+/// Top-level lines can't be properties, so there `ul li` joins the group too; nested lines have to look
+/// like selectors on their own.
+fn merge_selector_lines(lines: &mut Vec<Line>) {
+    let top = lines.iter().map(|line| line.indent).min().unwrap_or(0);
+    for i in (0..lines.len().saturating_sub(1)).rev() {
+        let next_opens = lines.get(i + 2).is_some_and(|after| after.indent > lines[i + 1].indent);
+        let same_indent = lines[i].indent == lines[i + 1].indent;
+        let selector = if lines[i].indent == top { may_be_selector(&lines[i].text) } else { is_selector(&lines[i].text) };
+        if same_indent && next_opens && selector && may_be_selector(&lines[i + 1].text) {
+            let line = lines.remove(i);
+            lines[i].text = format!("{}, {}", line.text, lines[i].text);
+            lines[i].number = line.number;
+        }
+    }
+}
+
+/// Lines that aren't something else heading a block: a directive, assignment, call, control keyword or
+/// `name: value` property.
+fn may_be_selector(text: &str) -> bool {
+    let word = text.split_whitespace().next().unwrap_or_default();
+    let keyword = matches!(word, "if" | "unless" | "else" | "for" | "return");
+    !keyword
+        && !text.starts_with(['@', '$', '+'])
+        && assignment(text).is_none()
+        && call(text).is_none()
+        && property_colon(text).is_none()
+}
+
+/// The colon of `name: value`.
+fn property_colon(text: &str) -> Option<usize> {
+    let colon = text.find(':')?;
+    let name = &text[..colon];
+    let after = &text[colon + 1..];
+    let valid = !name.is_empty() && name.chars().all(|c| is_name_char(c) || matches!(c, '{' | '}' | '*'));
+    (valid && (after.is_empty() || after.starts_with(char::is_whitespace))).then_some(colon)
+}
+
+/// `name = value`, `name ?= value` and `name := value` (the last two only set an undefined variable).
+fn assignment(text: &str) -> Option<String> {
+    let name_end = text.find(|c: char| !is_name_char(c) && c != '$')?;
+    let name = text[..name_end].trim_start_matches('$');
+    if name.is_empty() || !name.starts_with(|c: char| is_name_start(c) || c == '-') {
+        return None;
+    }
+    let rest = text[name_end..].trim_start();
+    let (value, default) = if let Some(value) = rest.strip_prefix("?=").or_else(|| rest.strip_prefix(":=")) {
+        (value, true)
+    }
+    else if rest.starts_with('=') && !rest.starts_with("==") {
+        (&rest[1..], false)
+    }
+    else {
+        return None;
+    };
+    Some(format!("${}: {}{}", name, value.trim(), if default { " !default" } else { "" }))
+}
+
+/// `{name}` interpolation in selectors and property names.
+fn interpolate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut previous = ' ';
+    for c in text.chars() {
+        if c == '{' && previous != '#' {
+            out.push('#');
+        }
+        out.push(c);
+        previous = c;
+    }
+    out
+}
+
+fn condition(text: &str) -> String {
+    let text = format!(" {} ", text.trim());
+    let text = text.replace(" is not ", " != ").replace(" isnt ", " != ").replace(" is ", " == ");
+    let text = text.replace(" && ", " and ").replace(" || ", " or ");
+    let text = text.trim();
+    match text.strip_prefix('!') {
+        Some(negated) => format!("not {}", negated),
+        None => text.to_string(),
+    }
+}
+
+/// `for i in 1..3` (inclusive), `for i in 0...3` (exclusive) and `for item in list`.
+fn for_loop(line: &Line, text: &str) -> Result<String> {
+    let Some((variable, list)) = text.split_once(" in ")
+    else {
+        return line.error("expected `for name in values`");
+    };
+    let variable = variable.trim();
+    if variable.contains(',') {
+        return line.error("`for value, index in` is not supported");
+    }
+    let list = list.trim();
+    if let Some((from, to)) = list.split_once("...") {
+        return Ok(format!("@for ${} from {} to {}", variable, from.trim(), to.trim()));
+    }
+    if let Some((from, to)) = list.split_once("..") {
+        return Ok(format!("@for ${} from {} through {}", variable, from.trim(), to.trim()));
+    }
+    Ok(format!("@each ${} in {}", variable, list))
+}
+
+fn param_name(param: &str) -> &str {
+    let param = param.trim();
+    let name = param.split('=').next().unwrap_or(param).trim();
+    name.trim_end_matches("...").trim_start_matches('$')
+}
+
+/// `a, b = 1, rest...` as `$a, $b: 1, $rest...`. A mixin without parameters takes any arguments as
+/// `arguments`.
+fn scss_params(params: &str, mixin: bool) -> String {
+    if params.trim().is_empty() {
+        return if mixin { "$arguments...".to_string() } else { String::new() };
+    }
+    params
+        .split(',')
+        .map(|param| {
+            let param = param.trim();
+            match param.split_once('=') {
+                Some((name, default)) => format!("${}: {}", param_name(name), default.trim()),
+                None if param.ends_with("...") => format!("${}...", param_name(param)),
+                None => format!("${}", param_name(param)),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether the last line of a definition is a value to return: it starts with a literal or one of the
+/// parameters, as in `a + b`.
+fn is_expression(text: &str, params: &[String]) -> bool {
+    let first = text.split(|c: char| c.is_whitespace() || matches!(c, '+' | '*' | '/' | '(' | ')' | ',')).next().unwrap_or("");
+    text.starts_with(|c: char| c.is_ascii_digit() || matches!(c, '(' | '"' | '\''))
+        || (text.starts_with('-') && text[1..].starts_with(|c: char| c.is_ascii_digit()))
+        || params.iter().any(|param| !param.is_empty() && param == first)
+}
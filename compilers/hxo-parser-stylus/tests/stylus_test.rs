@@ -1,4 +1,4 @@
-use hxo_parser_stylus::compile;
+use hxo_parser_stylus::{StylusParserOptions, compile, compile_with_options};
 
 #[test]
 fn test_stylus_basic() {
@@ -36,3 +36,72 @@ body
     let result = compile(stylus).unwrap();
     assert_eq!(result, expected);
 }
+
+#[test]
+fn test_mixins_functions_and_conditionals() {
+    let stylus = r#"
+border-radius(n)
+  -webkit-border-radius n
+  border-radius n
+
+add(a, b)
+  a + b
+
+theme = dark
+textarea
+input
+  border-radius 4px
+  width add(10px, 5px)
+  if theme is dark
+    color white
+  else
+    color black
+  unless theme is light
+    background black
+  for i in 1..2
+    &.m-{i}
+      margin i * 4px
+"#;
+    let css = compile(stylus).unwrap_or_else(|err| panic!("{}", err));
+    let expected = "textarea, input {\n  -webkit-border-radius: 4px;\n  border-radius: 4px;\n  width: 15px;\n  color: white;\n  background: black;\n}";
+    assert!(css.contains(expected), "{}", css);
+    assert!(css.contains("textarea.m-2, input.m-2 {\n  margin: 8px;\n}"), "{}", css);
+}
+
+#[test]
+fn test_selectors_versus_properties() {
+    let stylus = r#"
+ul li
+  a:hover
+    color: red;
+  padding 0 4px
+.base
+  display block
+.child
+  @extend .base
+  &:hover
+    opacity .5
+"#;
+    let css = compile(stylus).unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains("ul li {\n  padding: 0 4px;\n}"), "{}", css);
+    assert!(css.contains("ul li a:hover {\n  color: red;\n}"), "{}", css);
+    assert!(css.contains(".base, .child {\n  display: block;\n}"), "{}", css);
+    assert!(css.contains(".child:hover {\n  opacity: 0.5;\n}"), "{}", css);
+
+    let css = compile("ul li\nol li\n  margin 0\n  a\n  b\n    color red\n").unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains("ul li, ol li {\n  margin: 0;\n}"), "{}", css);
+    assert!(css.contains("ul li a, ul li b, ol li a, ol li b {\n  color: red;\n}"), "{}", css);
+}
+
+#[test]
+fn test_imports_through_load_paths() {
+    let dir = std::env::temp_dir().join(format!("hxo-stylus-imports-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("theme.styl"), "brand ?= #0044cc\nrounded()\n  border-radius 4px\n").unwrap();
+
+    let options = StylusParserOptions { load_paths: vec![dir.clone()], ..StylusParserOptions::default() };
+    let css = compile_with_options("brand = #333\n@import theme\n.card\n  color brand\n  rounded()\n", &options)
+        .unwrap_or_else(|err| panic!("{}", err));
+    assert!(css.contains(".card {\n  color: #333;\n  border-radius: 4px;\n}"), "{}", css);
+    std::fs::remove_dir_all(&dir).ok();
+}
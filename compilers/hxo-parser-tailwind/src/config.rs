@@ -0,0 +1,388 @@
+use crate::{theme, values::with_alpha};
+use hxo_parser_toml::TomlParser;
+use hxo_types::Result;
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path};
+
+/// How the `dark:` variant is matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DarkMode {
+    /// `@media (prefers-color-scheme: dark)`.
+    #[default]
+    Media,
+    /// A `.dark` class on an ancestor, usually `<html>`.
+    Class,
+}
+
+/// The resolved Tailwind theme: the default scales with `hxo.config.toml` overrides applied.
+///
+/// Nested palettes are flattened, so `blue.500` is stored as `blue-500` and a `DEFAULT` shade as
+/// the bare palette name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TailwindConfig {
+    pub dark_mode: DarkMode,
+    pub colors: HashMap<String, String>,
+    pub spacing: HashMap<String, String>,
+    /// Font size and its default line height.
+    pub font_size: HashMap<String, (String, Option<String>)>,
+    pub font_weight: HashMap<String, String>,
+    pub font_family: HashMap<String, String>,
+    pub line_height: HashMap<String, String>,
+    pub letter_spacing: HashMap<String, String>,
+    pub border_radius: HashMap<String, String>,
+    pub border_width: HashMap<String, String>,
+    pub box_shadow: HashMap<String, String>,
+    pub opacity: HashMap<String, String>,
+    pub z_index: HashMap<String, String>,
+    pub max_width: HashMap<String, String>,
+    /// Responsive breakpoints in ascending order: (variant name, min-width).
+    pub screens: Vec<(String, String)>,
+}
+
+impl Default for TailwindConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn scale(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+impl TailwindConfig {
+    /// The stock Tailwind theme.
+    pub fn new() -> Self {
+        let mut colors = scale(&theme::BASE_COLORS);
+        for (name, shades) in theme::PALETTE {
+            for (shade, hex) in theme::SHADES.iter().zip(shades.split_whitespace()) {
+                colors.insert(format!("{}-{}", name, shade), hex.to_string());
+            }
+        }
+
+        let font_size = theme::FONT_SIZE
+            .iter()
+            .map(|(name, size, line_height)| (name.to_string(), (size.to_string(), Some(line_height.to_string()))))
+            .collect();
+        let opacity = (0..=100).step_by(5).map(|n| (n.to_string(), format_number(n as f64 / 100.0))).collect();
+
+        Self {
+            dark_mode: DarkMode::Media,
+            colors,
+            spacing: scale(&theme::SPACING),
+            font_size,
+            font_weight: scale(&theme::FONT_WEIGHT),
+            font_family: scale(&theme::FONT_FAMILY),
+            line_height: scale(&theme::LINE_HEIGHT),
+            letter_spacing: scale(&theme::LETTER_SPACING),
+            border_radius: scale(&theme::BORDER_RADIUS),
+            border_width: scale(&theme::BORDER_WIDTH),
+            box_shadow: scale(&theme::BOX_SHADOW),
+            opacity,
+            z_index: scale(&theme::Z_INDEX),
+            max_width: scale(&theme::MAX_WIDTH),
+            screens: theme::SCREENS.iter().map(|(name, width)| (name.to_string(), width.to_string())).collect(),
+        }
+    }
+
+    /// Reads `hxo.config.toml` from `root`, falling back to the defaults when the file does not exist.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join("hxo.config.toml");
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let content = fs::read_to_string(&path)?;
+        Self::from_toml(&content)
+    }
+
+    /// Builds the theme from the `[tailwind]` table of an `hxo.config.toml`:
+    ///
+    /// ```toml
+    /// [tailwind]
+    /// darkMode = "class"
+    ///
+    /// [tailwind.theme.screens]      # replaces the default breakpoints
+    /// tablet = "640px"
+    ///
+    /// [tailwind.theme.extend.colors] # merged into the default palette
+    /// brand = { 500 = "#0f766e", DEFAULT = "#115e59" }
+    /// ```
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let file: ConfigFile = TomlParser::new().parse_to_type(content)?;
+        let mut config = Self::new();
+        config.apply(file.tailwind);
+        Ok(config)
+    }
+
+    /// Looks up a dotted theme path as written in `theme()`: `colors.blue.500`, `spacing[2.5]` or
+    /// `fontSize.lg`. Colors accept an opacity after a slash, `colors.blue.500 / 50%`.
+    pub fn lookup(&self, path: &str) -> Option<String> {
+        let (path, alpha) = match path.split_once('/') {
+            Some((path, alpha)) => (path.trim(), Some(alpha.trim())),
+            None => (path.trim(), None),
+        };
+        let split = path.find(['.', '['])?;
+        let (scale, mut rest) = (&path[..split], &path[split..]);
+        let mut keys = Vec::new();
+        while !rest.is_empty() {
+            if let Some(bracketed) = rest.strip_prefix('[') {
+                let end = bracketed.find(']')?;
+                keys.push(&bracketed[..end]);
+                rest = &bracketed[end + 1..];
+            }
+            else {
+                let segment = rest.strip_prefix('.')?;
+                let end = segment.find(['.', '[']).unwrap_or(segment.len());
+                keys.push(&segment[..end]);
+                rest = &segment[end..];
+            }
+        }
+        if alpha.is_some() && scale != "colors" {
+            return None;
+        }
+
+        let key = keys.join(".");
+        let value = match scale {
+            "colors" => {
+                if keys.last() == Some(&"DEFAULT") {
+                    keys.pop();
+                }
+                let color = self.colors.get(&keys.join("-"))?;
+                return Some(match alpha {
+                    Some(alpha) => with_alpha(color, alpha),
+                    None => color.clone(),
+                });
+            }
+            "spacing" => self.spacing.get(&key),
+            "fontSize" | "font_size" => self.font_size.get(&key).map(|(size, _)| size),
+            "fontWeight" | "font_weight" => self.font_weight.get(&key),
+            "fontFamily" | "font_family" => self.font_family.get(&key),
+            "lineHeight" | "line_height" => self.line_height.get(&key),
+            "letterSpacing" | "letter_spacing" => self.letter_spacing.get(&key),
+            "borderRadius" | "border_radius" => self.border_radius.get(&key),
+            "borderWidth" | "border_width" => self.border_width.get(&key),
+            "boxShadow" | "box_shadow" => self.box_shadow.get(&key),
+            "opacity" => self.opacity.get(&key),
+            "zIndex" | "z_index" => self.z_index.get(&key),
+            "maxWidth" | "max_width" => self.max_width.get(&key),
+            "screens" => self.screens.iter().find(|(name, _)| *name == key).map(|(_, width)| width),
+            _ => None,
+        };
+        value.cloned()
+    }
+
+    fn apply(&mut self, section: TailwindSection) {
+        if let Some(mode) = section.dark_mode {
+            self.dark_mode = if mode.eq_ignore_ascii_case("class") || mode.eq_ignore_ascii_case("selector") {
+                DarkMode::Class
+            }
+            else {
+                DarkMode::Media
+            };
+        }
+        self.merge(section.theme.scales, true);
+        self.merge(section.theme.extend, false);
+    }
+
+    /// Applies `scales` on top of the current theme; `replace` drops the existing entries of every
+    /// scale that is present first (`theme.*`), otherwise they are merged (`theme.extend.*`).
+    fn merge(&mut self, scales: ThemeScales, replace: bool) {
+        fn merge_into<V>(target: &mut HashMap<String, V>, entries: Option<HashMap<String, V>>, replace: bool) {
+            if let Some(entries) = entries {
+                if replace {
+                    target.clear();
+                }
+                target.extend(entries);
+            }
+        }
+        fn tokens(entries: Option<HashMap<String, Token>>) -> Option<HashMap<String, String>> {
+            entries.map(|e| e.into_iter().map(|(k, v)| (k, v.into_string())).collect())
+        }
+
+        merge_into(&mut self.colors, scales.colors.map(flatten_colors), replace);
+        merge_into(&mut self.spacing, tokens(scales.spacing), replace);
+        merge_into(
+            &mut self.font_size,
+            scales.font_size.map(|e| e.into_iter().map(|(k, v)| (k, v.into_pair())).collect()),
+            replace,
+        );
+        merge_into(&mut self.font_weight, tokens(scales.font_weight), replace);
+        merge_into(
+            &mut self.font_family,
+            scales.font_family.map(|e| e.into_iter().map(|(k, v)| (k, v.into_string())).collect()),
+            replace,
+        );
+        merge_into(&mut self.line_height, tokens(scales.line_height), replace);
+        merge_into(&mut self.letter_spacing, tokens(scales.letter_spacing), replace);
+        merge_into(&mut self.border_radius, tokens(scales.border_radius), replace);
+        merge_into(&mut self.border_width, tokens(scales.border_width), replace);
+        merge_into(&mut self.box_shadow, tokens(scales.box_shadow), replace);
+        merge_into(&mut self.opacity, tokens(scales.opacity), replace);
+        merge_into(&mut self.z_index, tokens(scales.z_index), replace);
+        merge_into(&mut self.max_width, tokens(scales.max_width), replace);
+
+        if let Some(screens) = scales.screens {
+            if replace {
+                self.screens.clear();
+            }
+            for (name, width) in screens {
+                let width = width.into_string();
+                match self.screens.iter_mut().find(|(n, _)| *n == name) {
+                    Some(existing) => existing.1 = width,
+                    None => self.screens.push((name, width)),
+                }
+            }
+            self.screens.sort_by(|a, b| screen_width(&a.1).total_cmp(&screen_width(&b.1)));
+        }
+    }
+}
+
+/// `640px` → 640, `40rem`/`40em` → 640, so breakpoints in mixed units still sort correctly.
+fn screen_width(width: &str) -> f64 {
+    let width = width.trim();
+    let (number, factor) = if let Some(n) = width.strip_suffix("rem").or_else(|| width.strip_suffix("em")) {
+        (n, 16.0)
+    }
+    else {
+        (width.trim_end_matches("px"), 1.0)
+    };
+    number.trim().parse::<f64>().map(|n| n * factor).unwrap_or(f64::MAX)
+}
+
+/// Formats a number without a trailing `.0`: 0.5 → `0.5`, 1.0 → `1`.
+pub(crate) fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 { format!("{}", n as i64) } else { format!("{}", n) }
+}
+
+fn flatten_colors(colors: HashMap<String, ColorValue>) -> HashMap<String, String> {
+    let mut flat = HashMap::new();
+    for (name, value) in colors {
+        match value {
+            ColorValue::Color(color) => {
+                flat.insert(name, color);
+            }
+            ColorValue::Palette(shades) => {
+                for (shade, color) in shades {
+                    let key = if shade == "DEFAULT" { name.clone() } else { format!("{}-{}", name, shade) };
+                    flat.insert(key, color);
+                }
+            }
+        }
+    }
+    flat
+}
+
+#[derive(Deserialize, Default)]
+struct ConfigFile {
+    #[serde(default)]
+    tailwind: TailwindSection,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct TailwindSection {
+    #[serde(alias = "darkMode")]
+    dark_mode: Option<String>,
+    theme: ThemeSection,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ThemeSection {
+    extend: ThemeScales,
+    #[serde(flatten)]
+    scales: ThemeScales,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ThemeScales {
+    colors: Option<HashMap<String, ColorValue>>,
+    spacing: Option<HashMap<String, Token>>,
+    #[serde(alias = "fontSize")]
+    font_size: Option<HashMap<String, FontSizeValue>>,
+    #[serde(alias = "fontWeight")]
+    font_weight: Option<HashMap<String, Token>>,
+    #[serde(alias = "fontFamily")]
+    font_family: Option<HashMap<String, FontFamilyValue>>,
+    #[serde(alias = "lineHeight")]
+    line_height: Option<HashMap<String, Token>>,
+    #[serde(alias = "letterSpacing")]
+    letter_spacing: Option<HashMap<String, Token>>,
+    #[serde(alias = "borderRadius")]
+    border_radius: Option<HashMap<String, Token>>,
+    #[serde(alias = "borderWidth")]
+    border_width: Option<HashMap<String, Token>>,
+    #[serde(alias = "boxShadow")]
+    box_shadow: Option<HashMap<String, Token>>,
+    opacity: Option<HashMap<String, Token>>,
+    #[serde(alias = "zIndex")]
+    z_index: Option<HashMap<String, Token>>,
+    #[serde(alias = "maxWidth")]
+    max_width: Option<HashMap<String, Token>>,
+    screens: Option<HashMap<String, Token>>,
+}
+
+/// A theme value written as a string or a bare number (`semibold = 600`).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Token {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Token {
+    fn into_string(self) -> String {
+        match self {
+            Token::Integer(n) => n.to_string(),
+            Token::Float(n) => format_number(n),
+            Token::Text(s) => s,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorValue {
+    Color(String),
+    Palette(HashMap<String, String>),
+}
+
+/// `"1rem"` or `["1rem", "1.5rem"]` (size, line height).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FontSizeValue {
+    Size(String),
+    WithLineHeight(Vec<String>),
+}
+
+impl FontSizeValue {
+    fn into_pair(self) -> (String, Option<String>) {
+        match self {
+            FontSizeValue::Size(size) => (size, None),
+            FontSizeValue::WithLineHeight(mut values) => {
+                let line_height = if values.len() > 1 { Some(values.remove(1)) } else { None };
+                (values.into_iter().next().unwrap_or_default(), line_height)
+            }
+        }
+    }
+}
+
+/// `"Inter, sans-serif"` or `["Inter", "sans-serif"]`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FontFamilyValue {
+    Family(String),
+    Stack(Vec<String>),
+}
+
+impl FontFamilyValue {
+    fn into_string(self) -> String {
+        match self {
+            FontFamilyValue::Family(family) => family,
+            FontFamilyValue::Stack(stack) => {
+                stack.into_iter().map(|f| if f.contains(' ') { format!("\"{}\"", f) } else { f }).collect::<Vec<_>>().join(", ")
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{Layer, StyleEngine, Variant, parse_variant};
+use hxo_parser_css::{AtRule, CssNode, Declaration, StyleRule as CssRule, Stylesheet, parse_stylesheet};
+use hxo_types::{Error, Result, Span};
+
+impl StyleEngine {
+    /// Expands Tailwind directives in the CSS of a plain `<style>` block: `@apply` inside rules,
+    /// `theme('colors.blue.500')` in values and preludes, and `@screen md { ... }`. `@layer` blocks are
+    /// left in place for [`add_css`](Self::add_css) to sort.
+    pub fn expand_directives(&self, css: &str) -> Result<String> {
+        if !["@apply", "@screen", "theme("].iter().any(|d| css.contains(d)) {
+            return Ok(css.to_string());
+        }
+        let mut sheet = parse_stylesheet(css)?;
+        self.expand_stylesheet(&mut sheet)?;
+        Ok(sheet.to_css(false))
+    }
+
+    /// [`expand_directives`](Self::expand_directives) on a parsed stylesheet, in place. Errors point at
+    /// the offending rule or declaration.
+    pub fn expand_stylesheet(&self, sheet: &mut Stylesheet) -> Result<()> {
+        let (nodes, hoisted) = self.expand_nodes(std::mem::take(&mut sheet.nodes), None)?;
+        debug_assert!(hoisted.is_empty(), "`@apply` outside a rule is rejected");
+        sheet.nodes = nodes;
+        Ok(())
+    }
+
+    /// Adds the CSS of a `<style>` block, moving top-level `@layer base|components|utilities` blocks into
+    /// their layer so they are ordered with the generated rules.
+    pub fn add_css(&mut self, css: &str) -> Result<()> {
+        if !css.contains("@layer") {
+            self.add_raw_css(css);
+            return Ok(());
+        }
+        self.add_stylesheet(&parse_stylesheet(css)?);
+        Ok(())
+    }
+
+    /// [`add_css`](Self::add_css) for a parsed stylesheet.
+    pub fn add_stylesheet(&mut self, sheet: &Stylesheet) {
+        let mut unlayered = Vec::new();
+        for node in sheet.nodes.iter().cloned() {
+            let layer = match &node {
+                CssNode::AtRule(rule) if rule.name == "layer" && rule.block.is_some() => Layer::from_name(&rule.prelude),
+                _ => None,
+            };
+            match (layer, node) {
+                (Some(layer), CssNode::AtRule(AtRule { block: Some(nodes), .. })) => {
+                    self.layer_css.push((layer, Stylesheet { nodes }.to_css(false).trim_end().to_string()));
+                }
+                (_, node) => unlayered.push(node),
+            }
+        }
+        if !unlayered.is_empty() {
+            self.add_raw_css(Stylesheet { nodes: unlayered }.to_css(false).trim_end());
+        }
+    }
+
+    /// Expands `nodes`, the children of the rule `selector` (or of the stylesheet). Returns the new
+    /// children and the variant rules `@apply` produced, which belong after the rule itself.
+    fn expand_nodes(&self, nodes: Vec<CssNode>, selector: Option<&str>) -> Result<(Vec<CssNode>, Vec<CssNode>)> {
+        let mut expanded = Vec::new();
+        let mut hoisted = Vec::new();
+        for node in nodes {
+            match node {
+                CssNode::AtRule(rule) if rule.name == "apply" => {
+                    let Some(selector) = selector
+                    else {
+                        return Err(Error::parse_error(format!("`@apply {}` must be inside a rule", rule.prelude), rule.span));
+                    };
+                    let span = rule.span;
+                    let rules = self.apply_rules(Layer::Components, selector, &rule.prelude, Vec::new(), span)?;
+                    for (i, rule) in rules.into_iter().enumerate() {
+                        let declarations = rule.declarations.into_iter().map(|(property, value)| {
+                            CssNode::Declaration(Declaration { span, ..Declaration::new(property, value) })
+                        });
+                        // The first rule is `selector` itself; its declarations are inlined where `@apply` was.
+                        if i == 0 && rule.at_rules.is_empty() {
+                            expanded.extend(declarations);
+                            continue;
+                        }
+                        let mut node = CssNode::Rule(CssRule {
+                            selector: rule.selector,
+                            children: declarations.collect(),
+                            selector_span: Span::unknown(),
+                            span,
+                        });
+                        for at_rule in rule.at_rules.iter().rev() {
+                            let at_rule = at_rule.trim_start_matches('@');
+                            let (name, prelude) = at_rule.split_once(' ').unwrap_or((at_rule, ""));
+                            node = CssNode::AtRule(AtRule {
+                                name: name.to_string(),
+                                prelude: prelude.to_string(),
+                                block: Some(vec![node]),
+                                span,
+                            });
+                        }
+                        hoisted.push(node);
+                    }
+                }
+                CssNode::AtRule(mut rule) => {
+                    if rule.name == "screen" {
+                        rule = self.expand_screen(rule)?;
+                    }
+                    rule.prelude = self.expand_theme(&rule.prelude, rule.span)?;
+                    if let Some(block) = rule.block.take() {
+                        let (mut children, nested) = self.expand_nodes(block, selector)?;
+                        children.extend(nested);
+                        rule.block = Some(children);
+                    }
+                    expanded.push(CssNode::AtRule(rule));
+                }
+                CssNode::Rule(mut rule) => {
+                    let (children, nested) = self.expand_nodes(std::mem::take(&mut rule.children), Some(&rule.selector))?;
+                    expanded.push(CssNode::Rule(CssRule { children, ..rule }));
+                    expanded.extend(nested);
+                }
+                CssNode::Declaration(declaration) => {
+                    let value = self.expand_theme(&declaration.value, declaration.span)?;
+                    expanded.push(CssNode::Declaration(Declaration { value, ..declaration }));
+                }
+            }
+        }
+        Ok((expanded, hoisted))
+    }
+
+    /// `@screen md` → `@media (min-width: 768px)`, using the configured breakpoints.
+    fn expand_screen(&self, rule: AtRule) -> Result<AtRule> {
+        match parse_variant(&rule.prelude, &self.config) {
+            Some(Variant::AtRule(media)) if media.starts_with("@media ") => {
+                Ok(AtRule { name: "media".to_string(), prelude: media["@media ".len()..].to_string(), ..rule })
+            }
+            _ => Err(Error::parse_error(format!("`@screen` cannot find the screen `{}`", rule.prelude), rule.span)),
+        }
+    }
+
+    /// Replaces every `theme(path)` call in `value` with the theme value.
+    fn expand_theme(&self, value: &str, span: Span) -> Result<String> {
+        let mut out = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("theme(") {
+            // `--theme(` or `my-theme(` are other functions
+            if rest[..start].ends_with(|c: char| c.is_alphanumeric() || c == '-' || c == '_') {
+                out.push_str(&rest[..start + 6]);
+                rest = &rest[start + 6..];
+                continue;
+            }
+            let args = &rest[start + 6..];
+            let end = args.find(')').ok_or_else(|| Error::parse_error(format!("unclosed `theme(` in `{}`", value), span))?;
+            let path = args[..end].trim().trim_matches(|c| c == '"' || c == '\'');
+            let resolved = self
+                .config
+                .lookup(path)
+                .ok_or_else(|| Error::parse_error(format!("`theme()` cannot find `{}`", path), span))?;
+            out.push_str(&rest[..start]);
+            out.push_str(&resolved);
+            rest = &args[end + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
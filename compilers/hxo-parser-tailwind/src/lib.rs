@@ -1,68 +1,108 @@
 use hxo_parser::{ParseState, StyleParser};
-use hxo_types::{Position, Result, Span};
-use std::collections::HashMap;
+use hxo_types::{Error, Position, Result, Span};
+use std::sync::Arc;
 
-pub struct TailwindParser;
+mod config;
+mod directives;
+mod registry;
+mod shared;
+mod source;
+mod theme;
+mod values;
+mod variant;
+
+pub use config::{DarkMode, TailwindConfig};
+pub use registry::{Declarations, ResolvedUtility, UtilityFn, UtilityRegistry};
+pub use shared::SharedStyleEngine;
+pub use values::{arbitrary, theme_color, theme_value};
+pub use variant::{Variant, apply_variants, escape_class, parse_variant, split_variants};
+
+#[derive(Default)]
+pub struct TailwindParser {
+    config: Arc<TailwindConfig>,
+}
+
+impl TailwindParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: Arc<TailwindConfig>) -> Self {
+        Self { config }
+    }
+}
 
 impl StyleParser for TailwindParser {
     fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<String> {
-        let mut engine = StyleEngine::new();
+        let mut engine = StyleEngine::with_config(self.config.clone());
         let content = state.cursor.source[state.cursor.pos..].to_string();
-        engine.parse_classes(&content, state.cursor.span_from(state.cursor.position()))?;
+        engine.parse_source(&content, state.cursor.span_from(state.cursor.position()))?;
 
         Ok(engine.generate_css())
     }
 }
 
+/// Cascade layers in output order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// Element defaults, `@layer base { h1 { ... } }`.
+    Base,
+    /// Component classes built with `@apply`.
+    Components,
+    /// Utility classes.
+    Utilities,
+}
+
+impl Layer {
+    /// The layer named in `@layer base`, `@layer components` or `@layer utilities`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            "base" => Some(Layer::Base),
+            "components" => Some(Layer::Components),
+            "utilities" => Some(Layer::Utilities),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct StyleRule {
     pub selector: String,
     pub declarations: Vec<(String, String)>,
+    /// Enclosing at-rules from variants, outermost first (e.g. `@media (min-width: 768px)`).
+    pub at_rules: Vec<String>,
+    pub layer: Layer,
+    /// Output position among utilities with the same variants: the utility's registration order, 0 in
+    /// the other layers.
+    pub order: usize,
     pub span: Span,
 }
 
-struct TailwindConfig {
-    spacing: HashMap<String, String>,
-    colors: HashMap<String, String>,
-}
-
 #[derive(Default)]
 pub struct StyleEngine {
     pub rules: Vec<StyleRule>,
     pub raw_css: Vec<String>,
-    config: TailwindConfig,
+    /// Contents of `@layer base|components|utilities` blocks from `<style>` blocks.
+    pub layer_css: Vec<(Layer, String)>,
+    config: Arc<TailwindConfig>,
+    registry: Arc<UtilityRegistry>,
 }
 
 impl StyleEngine {
     pub fn new() -> Self {
         Self::default()
     }
-}
 
-impl TailwindConfig {
-    pub fn new() -> Self {
-        let mut spacing = HashMap::new();
-        spacing.insert("0".to_string(), "0".to_string());
-        spacing.insert("1".to_string(), "0.25rem".to_string());
-        spacing.insert("2".to_string(), "0.5rem".to_string());
-        spacing.insert("4".to_string(), "1rem".to_string());
-        spacing.insert("6".to_string(), "1.5rem".to_string());
-        spacing.insert("8".to_string(), "2rem".to_string());
-
-        let mut colors = HashMap::new();
-        colors.insert("red".to_string(), "#ff0000".to_string());
-        colors.insert("blue".to_string(), "#0000ff".to_string());
-        colors.insert("white".to_string(), "#ffffff".to_string());
-        colors.insert("black".to_string(), "#000000".to_string());
-        colors.insert("gray-100".to_string(), "#f3f4f6".to_string());
-        colors.insert("gray-800".to_string(), "#1f2937".to_string());
-
-        Self { spacing, colors }
+    pub fn with_config(config: Arc<TailwindConfig>) -> Self {
+        Self { rules: Vec::new(), raw_css: Vec::new(), layer_css: Vec::new(), config, registry: Arc::default() }
     }
-}
 
-impl Default for TailwindConfig {
-    fn default() -> Self {
-        Self::new()
+    pub fn config(&self) -> &TailwindConfig {
+        &self.config
+    }
+
+    pub fn registry(&self) -> &UtilityRegistry {
+        &self.registry
     }
 }
 
@@ -74,10 +114,33 @@ impl StyleEngine {
     /// Add a single Tailwind class
     pub fn add_style(&mut self, class: &str) {
         if let Some(rule) = self.resolve_class(class, Span::unknown()) {
-            // Avoid duplicates
-            if !self.rules.iter().any(|r| r.selector == rule.selector) {
-                self.rules.push(rule);
+            self.push_rule(rule);
+        }
+    }
+
+    /// Adds a rule unless one with the same selector and at-rules exists; the first occurrence keeps its
+    /// span. Declarations missing from an existing rule (two `@apply` blocks for `.btn`) are appended.
+    pub(crate) fn push_rule(&mut self, rule: StyleRule) {
+        match self
+            .rules
+            .iter_mut()
+            .find(|r| r.layer == rule.layer && r.selector == rule.selector && r.at_rules == rule.at_rules)
+        {
+            Some(existing) => {
+                for declaration in rule.declarations {
+                    if !existing.declarations.contains(&declaration) {
+                        existing.declarations.push(declaration);
+                    }
+                }
             }
+            None => self.rules.push(rule),
+        }
+    }
+
+    /// Adds every rule of `other` (but not its raw CSS), skipping the ones already present.
+    pub fn merge(&mut self, other: &StyleEngine) {
+        for rule in &other.rules {
+            self.push_rule(rule.clone());
         }
     }
 
@@ -102,7 +165,7 @@ impl StyleEngine {
             };
 
             if let Some(rule) = self.resolve_class(class, span) {
-                self.rules.push(rule);
+                self.push_rule(rule);
             }
 
             current_column += class_len + 1;
@@ -111,163 +174,220 @@ impl StyleEngine {
         Ok(())
     }
 
+    /// Resolves a class such as `md:hover:!bg-blue-500`: the utility gives the declarations, each
+    /// variant prefix adds a pseudo-class, ancestor selector or `@media` wrapper and a `!` before or
+    /// after the utility marks every declaration `!important`.
     fn resolve_class(&self, class: &str, span: Span) -> Option<StyleRule> {
-        let mut declarations = Vec::new();
+        self.resolve_for(class, &format!(".{}", escape_class(class)), span)
+    }
 
-        if let Some(val) = class.strip_prefix("m-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("margin".to_string(), spacing.clone()));
-            }
-        }
-        else if let Some(val) = class.strip_prefix("mx-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("margin-left".to_string(), spacing.clone()));
-                declarations.push(("margin-right".to_string(), spacing.clone()));
-            }
-        }
-        else if let Some(val) = class.strip_prefix("my-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("margin-top".to_string(), spacing.clone()));
-                declarations.push(("margin-bottom".to_string(), spacing.clone()));
-            }
-        }
-        else if let Some(val) = class.strip_prefix("p-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("padding".to_string(), spacing.clone()));
-            }
-        }
-        else if let Some(val) = class.strip_prefix("px-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("padding-left".to_string(), spacing.clone()));
-                declarations.push(("padding-right".to_string(), spacing.clone()));
-            }
-        }
-        else if let Some(val) = class.strip_prefix("py-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("padding-top".to_string(), spacing.clone()));
-                declarations.push(("padding-bottom".to_string(), spacing.clone()));
+    /// Resolves `class` with its variants applied to `selector` instead of the class's own selector.
+    fn resolve_for(&self, class: &str, selector: &str, span: Span) -> Option<StyleRule> {
+        let (variant_names, utility) = split_variants(class);
+        let variants = variant_names.iter().map(|name| parse_variant(name, &self.config)).collect::<Option<Vec<_>>>()?;
+
+        let (utility, important) = match utility.strip_prefix('!').or_else(|| utility.strip_suffix('!')) {
+            Some(utility) => (utility, true),
+            None => (utility, false),
+        };
+        let ResolvedUtility { mut declarations, order } = self.registry.resolve(utility, &self.config)?;
+        if important {
+            for (_, value) in &mut declarations {
+                value.push_str(" !important");
             }
         }
-        else if let Some(val) = class.strip_prefix("w-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("width".to_string(), spacing.clone()));
-            }
-            else if val == "full" {
-                declarations.push(("width".to_string(), "100%".to_string()));
-            }
-            else if val == "screen" {
-                declarations.push(("width".to_string(), "100vw".to_string()));
-            }
+        let (selector, at_rules) = apply_variants(selector, &variants);
+        Some(StyleRule { selector, declarations, at_rules, layer: Layer::Utilities, order, span })
+    }
+
+    /// Registers a utility with fixed declarations, usable with variants like any built-in one.
+    pub fn add_utility(&mut self, name: &str, declarations: Declarations) {
+        Arc::make_mut(&mut self.registry).add_static(name, declarations);
+    }
+
+    /// Registers a functional utility `{prefix}-{value}`; see [`UtilityRegistry::add_functional`].
+    pub fn add_functional_utility<F>(&mut self, prefix: &str, handler: F)
+    where
+        F: Fn(&str, &TailwindConfig) -> Option<Declarations> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.registry).add_functional(prefix, handler);
+    }
+
+    /// Emits a component class built from utilities, like `.btn { @apply px-4 py-2 hover:bg-blue-500; }`.
+    ///
+    /// Variants apply to `selector` (`.btn:hover`), a trailing `!important` marks everything important
+    /// and unknown utilities are an error. Component rules precede all utilities in the output.
+    pub fn add_component(&mut self, selector: &str, utilities: &str) -> Result<()> {
+        self.add_layer_rule(Layer::Components, selector, utilities, Vec::new(), Span::unknown())
+    }
+
+    /// Like [`add_component`](Self::add_component) for element defaults in the base layer, e.g. `h1`.
+    pub fn add_base(&mut self, selector: &str, utilities: &str) -> Result<()> {
+        self.add_layer_rule(Layer::Base, selector, utilities, Vec::new(), Span::unknown())
+    }
+
+    fn add_layer_rule(
+        &mut self,
+        layer: Layer,
+        selector: &str,
+        utilities: &str,
+        declarations: Declarations,
+        span: Span,
+    ) -> Result<()> {
+        for rule in self.apply_rules(layer, selector, utilities, declarations, span)? {
+            self.push_rule(rule);
         }
-        else if let Some(val) = class.strip_prefix("h-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("height".to_string(), spacing.clone()));
-            }
-            else if val == "full" {
-                declarations.push(("height".to_string(), "100%".to_string()));
-            }
-            else if val == "screen" {
-                declarations.push(("height".to_string(), "100vh".to_string()));
-            }
+        Ok(())
+    }
+
+    /// Builds the rules for `selector { @apply utilities; declarations }`: the plain rule first, then one
+    /// per variant. Empty rules are dropped.
+    fn apply_rules(
+        &self,
+        layer: Layer,
+        selector: &str,
+        utilities: &str,
+        declarations: Declarations,
+        span: Span,
+    ) -> Result<Vec<StyleRule>> {
+        let mut classes: Vec<&str> = utilities.split_whitespace().collect();
+        let important = classes.last() == Some(&"!important");
+        if important {
+            classes.pop();
         }
-        else if let Some(val) = class.strip_prefix("text-") {
-            if let Some(color) = self.config.colors.get(val) {
-                declarations.push(("color".to_string(), color.clone()));
-            }
-            else if val == "center" {
-                declarations.push(("text-align".to_string(), "center".to_string()));
-            }
-            else if val == "xl" {
-                declarations.push(("font-size".to_string(), "1.25rem".to_string()));
-            }
-            else if val == "2xl" {
-                declarations.push(("font-size".to_string(), "1.5rem".to_string()));
+
+        let selector = split_selectors(selector).join(", ");
+        let mut rules =
+            vec![StyleRule { selector: selector.clone(), declarations, at_rules: Vec::new(), layer, order: 0, span }];
+        for class in classes {
+            let mut selectors = Vec::new();
+            let mut resolved = None;
+            for part in split_selectors(&selector) {
+                let rule = self
+                    .resolve_for(class, part, span)
+                    .ok_or_else(|| Error::parse_error(format!("`@apply` cannot find the utility `{}`", class), span))?;
+                selectors.push(rule.selector.clone());
+                resolved = Some(rule);
             }
-        }
-        else if let Some(val) = class.strip_prefix("bg-") {
-            if let Some(color) = self.config.colors.get(val) {
-                declarations.push(("background-color".to_string(), color.clone()));
+            let Some(mut rule) = resolved
+            else {
+                continue;
+            };
+            rule.selector = selectors.join(", ");
+            if important {
+                for (_, value) in &mut rule.declarations {
+                    if !value.ends_with("!important") {
+                        value.push_str(" !important");
+                    }
+                }
             }
-        }
-        else if class == "flex" {
-            declarations.push(("display".to_string(), "flex".to_string()));
-        }
-        else if class == "grid" {
-            declarations.push(("display".to_string(), "grid".to_string()));
-        }
-        else if let Some(val) = class.strip_prefix("grid-cols-") {
-            if let Ok(n) = val.parse::<u32>() {
-                declarations.push(("grid-template-columns".to_string(), format!("repeat({}, minmax(0, 1fr))", n)));
+            match rules.iter_mut().find(|r| r.selector == rule.selector && r.at_rules == rule.at_rules) {
+                Some(existing) => existing.declarations.extend(rule.declarations),
+                None => rules.push(StyleRule { layer, order: 0, ..rule }),
             }
         }
-        else if class == "block" {
-            declarations.push(("display".to_string(), "block".to_string()));
-        }
-        else if class == "inline-block" {
-            declarations.push(("display".to_string(), "inline-block".to_string()));
-        }
-        else if class == "hidden" {
-            declarations.push(("display".to_string(), "none".to_string()));
-        }
-        else if class == "items-center" {
-            declarations.push(("align-items".to_string(), "center".to_string()));
+        rules.retain(|r| !r.declarations.is_empty());
+        Ok(rules)
+    }
+
+    /// Position of a rule in the output: plain utilities first, then other at-rule variants, then
+    /// breakpoints from smallest to largest so wider screens win the cascade.
+    fn variant_rank(&self, rule: &StyleRule) -> usize {
+        if rule.at_rules.is_empty() {
+            return 0;
+        }
+        rule.at_rules
+            .iter()
+            .filter_map(|at_rule| {
+                self.config.screens.iter().position(|(_, width)| at_rule == &format!("@media (min-width: {})", width))
+            })
+            .max()
+            .map(|i| i + 2)
+            .unwrap_or(1)
+    }
+
+    /// The whole stylesheet: each layer's `@layer` CSS followed by its rules, then the unlayered CSS of
+    /// `<style>` blocks, which wins over every layer as in native cascade layers.
+    pub fn generate_css(&self) -> String {
+        let mut css = String::new();
+        for layer in [Layer::Base, Layer::Components, Layer::Utilities] {
+            self.push_layer_css(layer, &mut css);
+            self.push_rules(self.rules.iter().filter(|r| r.layer == layer), &mut css);
         }
-        else if class == "justify-center" {
-            declarations.push(("justify-content".to_string(), "center".to_string()));
+        self.push_unlayered_css(&mut css);
+        css
+    }
+
+    /// The rules in layer order (base, components, utilities); within a layer plain rules come
+    /// before variants and utilities follow their registration order.
+    pub fn generate_rules_css(&self) -> String {
+        let mut css = String::new();
+        self.push_rules(self.rules.iter(), &mut css);
+        css
+    }
+
+    /// The CSS of `<style>` blocks: `@layer` contents in layer order, then the rest as it was added.
+    pub fn generate_raw_css(&self) -> String {
+        let mut css = String::new();
+        for layer in [Layer::Base, Layer::Components, Layer::Utilities] {
+            self.push_layer_css(layer, &mut css);
         }
-        else if let Some(val) = class.strip_prefix("gap-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("gap".to_string(), spacing.clone()));
+        self.push_unlayered_css(&mut css);
+        css
+    }
+
+    fn push_rules<'a>(&self, rules: impl Iterator<Item = &'a StyleRule>, css: &mut String) {
+        let mut ordered: Vec<&StyleRule> = rules.collect();
+        ordered.sort_by_key(|rule| (rule.layer, self.variant_rank(rule), rule.order));
+
+        for rule in ordered {
+            let depth = rule.at_rules.len();
+            for (i, at_rule) in rule.at_rules.iter().enumerate() {
+                css.push_str(&format!("{}{} {{\n", "  ".repeat(i), at_rule));
             }
-        }
-        else if class == "font-bold" {
-            declarations.push(("font-weight".to_string(), "700".to_string()));
-        }
-        else if class == "rounded-lg" {
-            declarations.push(("border-radius".to_string(), "0.5rem".to_string()));
-        }
-        else if class == "rounded-xl" {
-            declarations.push(("border-radius".to_string(), "0.75rem".to_string()));
-        }
-        else if class == "shadow-sm" {
-            declarations.push(("box-shadow".to_string(), "0 1px 2px 0 rgba(0, 0, 0, 0.05)".to_string()));
-        }
-        else if class == "absolute" {
-            declarations.push(("position".to_string(), "absolute".to_string()));
-        }
-        else if class == "relative" {
-            declarations.push(("position".to_string(), "relative".to_string()));
-        }
-        else if class == "fixed" {
-            declarations.push(("position".to_string(), "fixed".to_string()));
-        }
-        else if let Some(val) = class.strip_prefix("top-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("top".to_string(), spacing.clone()));
+            let indent = "  ".repeat(depth);
+            css.push_str(&format!("{}{} {{\n", indent, rule.selector));
+            for (prop, val) in &rule.declarations {
+                css.push_str(&format!("{}  {}: {};\n", indent, prop, val));
             }
-        }
-        else if let Some(val) = class.strip_prefix("left-") {
-            if let Some(spacing) = self.config.spacing.get(val) {
-                declarations.push(("left".to_string(), spacing.clone()));
+            css.push_str(&format!("{}}}\n", indent));
+            for i in (0..depth).rev() {
+                css.push_str(&format!("{}}}\n", "  ".repeat(i)));
             }
         }
-
-        if !declarations.is_empty() { Some(StyleRule { selector: format!(".{}", class), declarations, span }) } else { None }
     }
 
-    pub fn generate_css(&self) -> String {
-        let mut css = String::new();
-        for rule in &self.rules {
-            css.push_str(&format!("{} {{\n", rule.selector));
-            for (prop, val) in &rule.declarations {
-                css.push_str(&format!("  {}: {};\n", prop, val));
-            }
-            css.push_str("}\n");
+    fn push_layer_css(&self, layer: Layer, css: &mut String) {
+        for (_, raw) in self.layer_css.iter().filter(|(l, _)| *l == layer) {
+            css.push_str(raw);
+            css.push('\n');
         }
+    }
+
+    fn push_unlayered_css(&self, css: &mut String) {
         for raw in &self.raw_css {
             css.push_str(raw);
             css.push('\n');
         }
-        css
     }
 }
+
+/// Splits a selector list at top-level commas: `.a, :is(.b, .c)` → `.a`, `:is(.b, .c)`.
+fn split_selectors(selector: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in selector.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(selector[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(selector[start..].trim());
+    parts
+}
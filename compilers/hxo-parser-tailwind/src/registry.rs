@@ -0,0 +1,434 @@
+use crate::{
+    TailwindConfig,
+    values::{
+        arbitrary, height, looks_like_color, negate, spacing, spacing_or_auto, theme_color, theme_value, type_hint, width,
+    },
+};
+use std::{collections::HashMap, sync::Arc};
+
+pub type Declarations = Vec<(String, String)>;
+
+/// Resolves the value of a functional utility (`4` in `p-4`, `DEFAULT` for a bare `rounded`) against
+/// the theme, or returns `None` when the value does not belong to it.
+pub type UtilityFn = Arc<dyn Fn(&str, &TailwindConfig) -> Option<Declarations> + Send + Sync>;
+
+/// Theme lookup used by single-value utilities such as `mt-4`.
+type ValueFn = fn(&TailwindConfig, &str) -> Option<String>;
+
+/// A row of the built-in utility table.
+enum Core {
+    /// `flex` → `display: flex`.
+    Static(&'static str, &'static [(&'static str, &'static str)]),
+    /// `mx-4` → every listed property set to the resolved value; the flag allows `-mx-4`.
+    Property(&'static str, &'static [&'static str], ValueFn, bool),
+    /// Anything that needs more than one property or value lookup.
+    Functional(&'static str, fn(&str, &TailwindConfig) -> Option<Declarations>),
+}
+
+/// Built-in utilities in output order: a utility registered later wins over an earlier one, so
+/// shorthands (`p-4`) precede their longhands (`pt-2`) and `text-*` sizes precede `leading-*`.
+const CORE: &[Core] = &[
+    // Layout
+    Core::Static("block", &[("display", "block")]),
+    Core::Static("inline-block", &[("display", "inline-block")]),
+    Core::Static("inline", &[("display", "inline")]),
+    Core::Static("flex", &[("display", "flex")]),
+    Core::Static("inline-flex", &[("display", "inline-flex")]),
+    Core::Static("grid", &[("display", "grid")]),
+    Core::Static("inline-grid", &[("display", "inline-grid")]),
+    Core::Static("contents", &[("display", "contents")]),
+    Core::Static("hidden", &[("display", "none")]),
+    Core::Static("overflow-auto", &[("overflow", "auto")]),
+    Core::Static("overflow-hidden", &[("overflow", "hidden")]),
+    Core::Static("overflow-clip", &[("overflow", "clip")]),
+    Core::Static("overflow-visible", &[("overflow", "visible")]),
+    Core::Static("overflow-scroll", &[("overflow", "scroll")]),
+    Core::Static("overflow-x-auto", &[("overflow-x", "auto")]),
+    Core::Static("overflow-x-hidden", &[("overflow-x", "hidden")]),
+    Core::Static("overflow-x-clip", &[("overflow-x", "clip")]),
+    Core::Static("overflow-x-visible", &[("overflow-x", "visible")]),
+    Core::Static("overflow-x-scroll", &[("overflow-x", "scroll")]),
+    Core::Static("overflow-y-auto", &[("overflow-y", "auto")]),
+    Core::Static("overflow-y-hidden", &[("overflow-y", "hidden")]),
+    Core::Static("overflow-y-clip", &[("overflow-y", "clip")]),
+    Core::Static("overflow-y-visible", &[("overflow-y", "visible")]),
+    Core::Static("overflow-y-scroll", &[("overflow-y", "scroll")]),
+    Core::Static("static", &[("position", "static")]),
+    Core::Static("fixed", &[("position", "fixed")]),
+    Core::Static("absolute", &[("position", "absolute")]),
+    Core::Static("relative", &[("position", "relative")]),
+    Core::Static("sticky", &[("position", "sticky")]),
+    Core::Property("inset", &["inset"], width, true),
+    Core::Property("inset-x", &["left", "right"], width, true),
+    Core::Property("inset-y", &["top", "bottom"], height, true),
+    Core::Property("top", &["top"], height, true),
+    Core::Property("right", &["right"], width, true),
+    Core::Property("bottom", &["bottom"], height, true),
+    Core::Property("left", &["left"], width, true),
+    Core::Property("z", &["z-index"], |c, v| theme_value(&c.z_index, v), true),
+    // Spacing
+    Core::Property("m", &["margin"], spacing_or_auto, true),
+    Core::Property("mx", &["margin-left", "margin-right"], spacing_or_auto, true),
+    Core::Property("my", &["margin-top", "margin-bottom"], spacing_or_auto, true),
+    Core::Property("mt", &["margin-top"], spacing_or_auto, true),
+    Core::Property("mr", &["margin-right"], spacing_or_auto, true),
+    Core::Property("mb", &["margin-bottom"], spacing_or_auto, true),
+    Core::Property("ml", &["margin-left"], spacing_or_auto, true),
+    // Sizing
+    Core::Property("w", &["width"], width, false),
+    Core::Property("h", &["height"], height, false),
+    Core::Property("min-w", &["min-width"], width, false),
+    Core::Property("max-w", &["max-width"], max_width, false),
+    Core::Property("min-h", &["min-height"], height, false),
+    Core::Property("max-h", &["max-height"], |c, v| if v == "none" { Some("none".to_string()) } else { height(c, v) }, false),
+    // Grid & flexbox
+    Core::Static("flex-row", &[("flex-direction", "row")]),
+    Core::Static("flex-row-reverse", &[("flex-direction", "row-reverse")]),
+    Core::Static("flex-col", &[("flex-direction", "column")]),
+    Core::Static("flex-col-reverse", &[("flex-direction", "column-reverse")]),
+    Core::Static("flex-wrap", &[("flex-wrap", "wrap")]),
+    Core::Static("flex-wrap-reverse", &[("flex-wrap", "wrap-reverse")]),
+    Core::Static("flex-nowrap", &[("flex-wrap", "nowrap")]),
+    Core::Static("flex-1", &[("flex", "1 1 0%")]),
+    Core::Static("flex-auto", &[("flex", "1 1 auto")]),
+    Core::Static("flex-initial", &[("flex", "0 1 auto")]),
+    Core::Static("flex-none", &[("flex", "none")]),
+    Core::Static("grow", &[("flex-grow", "1")]),
+    Core::Static("grow-0", &[("flex-grow", "0")]),
+    Core::Static("shrink", &[("flex-shrink", "1")]),
+    Core::Static("shrink-0", &[("flex-shrink", "0")]),
+    Core::Functional("grid-cols", grid_cols),
+    Core::Static("items-start", &[("align-items", "flex-start")]),
+    Core::Static("items-end", &[("align-items", "flex-end")]),
+    Core::Static("items-center", &[("align-items", "center")]),
+    Core::Static("items-baseline", &[("align-items", "baseline")]),
+    Core::Static("items-stretch", &[("align-items", "stretch")]),
+    Core::Static("justify-start", &[("justify-content", "flex-start")]),
+    Core::Static("justify-end", &[("justify-content", "flex-end")]),
+    Core::Static("justify-center", &[("justify-content", "center")]),
+    Core::Static("justify-between", &[("justify-content", "space-between")]),
+    Core::Static("justify-around", &[("justify-content", "space-around")]),
+    Core::Static("justify-evenly", &[("justify-content", "space-evenly")]),
+    Core::Static("self-auto", &[("align-self", "auto")]),
+    Core::Static("self-start", &[("align-self", "flex-start")]),
+    Core::Static("self-end", &[("align-self", "flex-end")]),
+    Core::Static("self-center", &[("align-self", "center")]),
+    Core::Static("self-stretch", &[("align-self", "stretch")]),
+    Core::Property("gap", &["gap"], spacing, false),
+    Core::Property("gap-x", &["column-gap"], spacing, false),
+    Core::Property("gap-y", &["row-gap"], spacing, false),
+    // Borders
+    Core::Property("rounded", &["border-radius"], |c, v| theme_value(&c.border_radius, v), false),
+    Core::Functional("border", |v, c| border_width(&["border"], v, c)),
+    Core::Functional("border-x", |v, c| border_width(&["border-left", "border-right"], v, c)),
+    Core::Functional("border-y", |v, c| border_width(&["border-top", "border-bottom"], v, c)),
+    Core::Functional("border-t", |v, c| border_width(&["border-top"], v, c)),
+    Core::Functional("border-r", |v, c| border_width(&["border-right"], v, c)),
+    Core::Functional("border-b", |v, c| border_width(&["border-bottom"], v, c)),
+    Core::Functional("border-l", |v, c| border_width(&["border-left"], v, c)),
+    Core::Property("border", &["border-color"], theme_color, false),
+    Core::Property("border-x", &["border-left-color", "border-right-color"], theme_color, false),
+    Core::Property("border-y", &["border-top-color", "border-bottom-color"], theme_color, false),
+    Core::Property("border-t", &["border-top-color"], theme_color, false),
+    Core::Property("border-r", &["border-right-color"], theme_color, false),
+    Core::Property("border-b", &["border-bottom-color"], theme_color, false),
+    Core::Property("border-l", &["border-left-color"], theme_color, false),
+    Core::Static("border-solid", &[("--tw-border-style", "solid"), ("border-style", "solid")]),
+    Core::Static("border-dashed", &[("--tw-border-style", "dashed"), ("border-style", "dashed")]),
+    Core::Static("border-dotted", &[("--tw-border-style", "dotted"), ("border-style", "dotted")]),
+    Core::Static("border-double", &[("--tw-border-style", "double"), ("border-style", "double")]),
+    Core::Static("border-none", &[("--tw-border-style", "none"), ("border-style", "none")]),
+    // Backgrounds
+    Core::Functional("bg", background),
+    // Padding
+    Core::Property("p", &["padding"], spacing, false),
+    Core::Property("px", &["padding-left", "padding-right"], spacing, false),
+    Core::Property("py", &["padding-top", "padding-bottom"], spacing, false),
+    Core::Property("pt", &["padding-top"], spacing, false),
+    Core::Property("pr", &["padding-right"], spacing, false),
+    Core::Property("pb", &["padding-bottom"], spacing, false),
+    Core::Property("pl", &["padding-left"], spacing, false),
+    // Typography
+    Core::Static("text-left", &[("text-align", "left")]),
+    Core::Static("text-center", &[("text-align", "center")]),
+    Core::Static("text-right", &[("text-align", "right")]),
+    Core::Static("text-justify", &[("text-align", "justify")]),
+    Core::Static("text-start", &[("text-align", "start")]),
+    Core::Static("text-end", &[("text-align", "end")]),
+    Core::Static("italic", &[("font-style", "italic")]),
+    Core::Static("not-italic", &[("font-style", "normal")]),
+    Core::Static("underline", &[("text-decoration-line", "underline")]),
+    Core::Static("line-through", &[("text-decoration-line", "line-through")]),
+    Core::Static("no-underline", &[("text-decoration-line", "none")]),
+    Core::Static("uppercase", &[("text-transform", "uppercase")]),
+    Core::Static("lowercase", &[("text-transform", "lowercase")]),
+    Core::Static("capitalize", &[("text-transform", "capitalize")]),
+    Core::Static("normal-case", &[("text-transform", "none")]),
+    Core::Static("truncate", &[("overflow", "hidden"), ("text-overflow", "ellipsis"), ("white-space", "nowrap")]),
+    Core::Static("whitespace-normal", &[("white-space", "normal")]),
+    Core::Static("whitespace-nowrap", &[("white-space", "nowrap")]),
+    Core::Static("whitespace-pre", &[("white-space", "pre")]),
+    Core::Static("whitespace-pre-line", &[("white-space", "pre-line")]),
+    Core::Static("whitespace-pre-wrap", &[("white-space", "pre-wrap")]),
+    Core::Static("break-words", &[("overflow-wrap", "break-word")]),
+    Core::Property("font", &["font-family"], |c, v| c.font_family.get(v).cloned(), false),
+    Core::Functional("text", font_size),
+    Core::Property("font", &["font-weight"], |c, v| theme_value(&c.font_weight, v), false),
+    Core::Property("leading", &["line-height"], |c, v| theme_value(&c.line_height, v), false),
+    Core::Property("tracking", &["letter-spacing"], |c, v| theme_value(&c.letter_spacing, v), true),
+    Core::Property("text", &["color"], theme_color, false),
+    // Effects
+    Core::Property("opacity", &["opacity"], |c, v| theme_value(&c.opacity, v), false),
+    Core::Property("shadow", &["box-shadow"], |c, v| theme_value(&c.box_shadow, v), false),
+    // Interactivity
+    Core::Static("cursor-auto", &[("cursor", "auto")]),
+    Core::Static("cursor-default", &[("cursor", "default")]),
+    Core::Static("cursor-pointer", &[("cursor", "pointer")]),
+    Core::Static("cursor-not-allowed", &[("cursor", "not-allowed")]),
+    Core::Static("pointer-events-none", &[("pointer-events", "none")]),
+    Core::Static("pointer-events-auto", &[("pointer-events", "auto")]),
+    Core::Static("select-none", &[("user-select", "none")]),
+    Core::Static("select-text", &[("user-select", "text")]),
+    Core::Static("select-all", &[("user-select", "all")]),
+];
+
+fn grid_cols(value: &str, _config: &TailwindConfig) -> Option<Declarations> {
+    let columns = match value.parse::<u32>() {
+        Ok(n) => format!("repeat({}, minmax(0, 1fr))", n),
+        Err(_) if value == "none" => "none".to_string(),
+        Err(_) => arbitrary(value)?,
+    };
+    Some(vec![("grid-template-columns".to_string(), columns)])
+}
+
+/// `max-w-prose`, `max-w-screen-md` (a breakpoint) or `max-w-[50ch]`.
+fn max_width(config: &TailwindConfig, key: &str) -> Option<String> {
+    match key.strip_prefix("screen-") {
+        Some(screen) => config.screens.iter().find(|(name, _)| name == screen).map(|(_, width)| width.clone()),
+        None => theme_value(&config.max_width, key),
+    }
+}
+
+/// `border-2` on `sides` (`border`, `border-top`, ...). There is no preflight resetting borders, so
+/// each side also gets a style, `solid` unless `border-dashed` and friends set `--tw-border-style`.
+fn border_width(sides: &[&str], value: &str, config: &TailwindConfig) -> Option<Declarations> {
+    let width = match arbitrary(value) {
+        Some(value) => match type_hint(&value) {
+            (Some("length"), width) => width.to_string(),
+            (None, width) if !looks_like_color(width) => width.to_string(),
+            _ => return None,
+        },
+        None => config.border_width.get(value)?.clone(),
+    };
+    let mut declarations = Vec::new();
+    for side in sides {
+        declarations.push((format!("{}-style", side), "var(--tw-border-style, solid)".to_string()));
+        declarations.push((format!("{}-width", side), width.clone()));
+    }
+    Some(declarations)
+}
+
+/// `bg-blue-500/50`, `bg-[#123456]`, `bg-[url(/hero.png)]`.
+fn background(value: &str, config: &TailwindConfig) -> Option<Declarations> {
+    if let Some(color) = theme_color(config, value) {
+        return Some(vec![("background-color".to_string(), color)]);
+    }
+    let value = arbitrary(value)?;
+    let (hint, image) = type_hint(&value);
+    if matches!(hint, Some("url" | "image")) || image.starts_with("url(") || image.contains("gradient(") {
+        return Some(vec![("background-image".to_string(), image.to_string())]);
+    }
+    None
+}
+
+/// `text-sm` sets the size with its line height; `text-[13px]` only the size.
+fn font_size(value: &str, config: &TailwindConfig) -> Option<Declarations> {
+    if let Some((size, line_height)) = config.font_size.get(value) {
+        let mut declarations = vec![("font-size".to_string(), size.clone())];
+        if let Some(line_height) = line_height {
+            declarations.push(("line-height".to_string(), line_height.clone()));
+        }
+        return Some(declarations);
+    }
+    let value = arbitrary(value)?;
+    match type_hint(&value) {
+        (Some("length"), size) => Some(vec![("font-size".to_string(), size.to_string())]),
+        (None, size) if !looks_like_color(size) => Some(vec![("font-size".to_string(), size.to_string())]),
+        _ => None,
+    }
+}
+
+#[derive(Clone)]
+struct Utility {
+    handler: Handler,
+    order: usize,
+    negatable: bool,
+}
+
+#[derive(Clone)]
+enum Handler {
+    Static(Declarations),
+    Functional(UtilityFn),
+}
+
+/// A utility resolved by the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedUtility {
+    pub declarations: Declarations,
+    /// Registration order of the utility; rules are emitted in this order so later utilities win.
+    pub order: usize,
+}
+
+/// Static (`flex`) and functional (`p-*`) utilities keyed by name or prefix.
+///
+/// A class is looked up as a static utility first, then split at each `-` from the right so that
+/// the longest registered prefix is tried first (`inset-x-4` before `inset-x…`); every handler of a
+/// prefix is tried in registration order until one accepts the value. A bare functional name such as
+/// `rounded` is resolved with the value `DEFAULT`.
+#[derive(Clone)]
+pub struct UtilityRegistry {
+    statics: HashMap<String, Utility>,
+    functionals: HashMap<String, Vec<Utility>>,
+    /// Orders start at 1; 0 is reserved for component classes, which precede all utilities.
+    next_order: usize,
+}
+
+impl Default for UtilityRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UtilityRegistry {
+    /// A registry with the built-in utilities.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        for core in CORE {
+            match core {
+                Core::Static(name, declarations) => {
+                    registry.add_static(name, declarations.iter().map(|(p, v)| (p.to_string(), v.to_string())).collect())
+                }
+                Core::Property(prefix, properties, value, negatable) => {
+                    let (properties, value) = (*properties, *value);
+                    registry.insert_functional(
+                        prefix,
+                        Arc::new(move |v, config| {
+                            let value = value(config, v)?;
+                            Some(properties.iter().map(|p| (p.to_string(), value.clone())).collect())
+                        }),
+                        *negatable,
+                    );
+                }
+                Core::Functional(prefix, handler) => registry.insert_functional(prefix, Arc::new(*handler), false),
+            }
+        }
+        registry
+    }
+
+    /// A registry without any utilities.
+    pub fn empty() -> Self {
+        Self { statics: HashMap::new(), functionals: HashMap::new(), next_order: 1 }
+    }
+
+    fn next_order(&mut self) -> usize {
+        self.next_order += 1;
+        self.next_order - 1
+    }
+
+    /// Registers (or replaces) a utility with fixed declarations, e.g. `content-auto`.
+    pub fn add_static(&mut self, name: &str, declarations: Declarations) {
+        let order = self.next_order();
+        self.statics.insert(name.to_string(), Utility { handler: Handler::Static(declarations), order, negatable: false });
+    }
+
+    /// Registers a functional utility for `{prefix}-{value}`; it is tried after the handlers already
+    /// registered for the same prefix.
+    pub fn add_functional<F>(&mut self, prefix: &str, handler: F)
+    where
+        F: Fn(&str, &TailwindConfig) -> Option<Declarations> + Send + Sync + 'static,
+    {
+        self.insert_functional(prefix, Arc::new(handler), false);
+    }
+
+    /// Like [`add_functional`](Self::add_functional), but `-{prefix}-{value}` negates the values.
+    pub fn add_negatable_functional<F>(&mut self, prefix: &str, handler: F)
+    where
+        F: Fn(&str, &TailwindConfig) -> Option<Declarations> + Send + Sync + 'static,
+    {
+        self.insert_functional(prefix, Arc::new(handler), true);
+    }
+
+    fn insert_functional(&mut self, prefix: &str, handler: UtilityFn, negatable: bool) {
+        let order = self.next_order();
+        self.functionals.entry(prefix.to_string()).or_default().push(Utility {
+            handler: Handler::Functional(handler),
+            order,
+            negatable,
+        });
+    }
+
+    /// Whether `name` is a registered static utility or functional prefix.
+    pub fn contains(&self, name: &str) -> bool {
+        self.statics.contains_key(name) || self.functionals.contains_key(name)
+    }
+
+    /// Resolves a utility without variants, e.g. `bg-blue-500/50` or `-mt-4`.
+    pub fn resolve(&self, utility: &str, config: &TailwindConfig) -> Option<ResolvedUtility> {
+        let (negative, name) = match utility.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, utility),
+        };
+
+        if !negative {
+            if let Some(Utility { handler: Handler::Static(declarations), order, .. }) = self.statics.get(name) {
+                return Some(ResolvedUtility { declarations: declarations.clone(), order: *order });
+            }
+        }
+
+        let mut splits = dash_positions(name);
+        splits.reverse();
+        let candidates = splits.into_iter().map(|i| (&name[..i], &name[i + 1..])).chain([(name, "DEFAULT")]);
+        for (prefix, value) in candidates {
+            let Some(utilities) = self.functionals.get(prefix)
+            else {
+                continue;
+            };
+            for utility in utilities {
+                if negative && !utility.negatable {
+                    continue;
+                }
+                let Handler::Functional(handler) = &utility.handler
+                else {
+                    continue;
+                };
+                let Some(declarations) = handler(value, config)
+                else {
+                    continue;
+                };
+                let declarations = if negative {
+                    declarations.into_iter().map(|(p, v)| negate(&v).map(|v| (p, v))).collect::<Option<Vec<_>>>()?
+                }
+                else {
+                    declarations
+                };
+                return Some(ResolvedUtility { declarations, order: utility.order });
+            }
+        }
+        None
+    }
+}
+
+/// Byte offsets of the `-` separators outside of `[...]`/`(...)`.
+fn dash_positions(name: &str) -> Vec<usize> {
+    let mut depth = 0i32;
+    let mut positions = Vec::new();
+    for (i, c) in name.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            '-' if depth == 0 && i > 0 => positions.push(i),
+            _ => {}
+        }
+    }
+    positions
+}
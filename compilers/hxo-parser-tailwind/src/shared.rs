@@ -0,0 +1,30 @@
+use crate::{StyleEngine, TailwindConfig};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A project-wide [`StyleEngine`] that every component compile merges its rules into, so a utility
+/// used by several components is emitted once in a single stylesheet.
+#[derive(Clone, Default)]
+pub struct SharedStyleEngine {
+    engine: Arc<Mutex<StyleEngine>>,
+}
+
+impl SharedStyleEngine {
+    pub fn new(config: Arc<TailwindConfig>) -> Self {
+        Self { engine: Arc::new(Mutex::new(StyleEngine::with_config(config))) }
+    }
+
+    /// Adds the rules of a component's engine; its raw CSS stays with the component.
+    pub fn merge(&self, engine: &StyleEngine) {
+        self.lock().merge(engine);
+    }
+
+    /// The utility stylesheet for every component merged so far.
+    pub fn generate_css(&self) -> String {
+        self.lock().generate_rules_css()
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, StyleEngine> {
+        // A panic while holding the lock leaves the rule list intact, so keep using it.
+        self.engine.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
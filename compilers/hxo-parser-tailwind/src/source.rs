@@ -0,0 +1,175 @@
+use crate::{Declarations, Layer, StyleEngine};
+use hxo_types::{Error, Position, Result, Span};
+
+impl StyleEngine {
+    /// Parses the body of a `<style lang="tailwind">` block: whitespace-separated classes, component
+    /// rules such as `.btn { @apply px-4 py-2; cursor: pointer; }`, custom utilities declared with
+    /// `@utility content-auto { content-visibility: auto; }` and `@layer base { h1 { ... } }` blocks.
+    pub fn parse_source(&mut self, source: &str, base_span: Span) -> Result<()> {
+        self.parse_rules(source, base_span.start, None)
+    }
+
+    /// `layer` is set inside `@layer`, where every prelude is a selector rather than only `.class`.
+    fn parse_rules(&mut self, source: &str, start: Position, layer: Option<Layer>) -> Result<()> {
+        let mut position = start;
+        let mut rest = source;
+
+        loop {
+            let trimmed = rest.trim_start();
+            position = advance(position, &rest[..rest.len() - trimmed.len()]);
+            rest = trimmed;
+            if rest.is_empty() {
+                break;
+            }
+
+            if rest.starts_with("/*") {
+                let end = rest.find("*/").map(|i| i + 2).unwrap_or(rest.len());
+                position = advance(position, &rest[..end]);
+                rest = &rest[end..];
+            }
+            else if layer.is_some() || rest.starts_with('.') || rest.starts_with('@') {
+                let start = position;
+                let open = rest
+                    .find('{')
+                    .ok_or_else(|| Error::parse_error(format!("expected `{{` after `{}`", rest.trim()), span_at(start)))?;
+                let close =
+                    matching_brace(rest, open).ok_or_else(|| Error::parse_error("unclosed `{`".to_string(), span_at(start)))?;
+                let prelude = rest[..open].trim();
+                let body_start = advance(position, &rest[..=open]);
+                position = advance(position, &rest[..=close]);
+                let span = Span { start, end: position };
+                if let Some(name) = prelude.strip_prefix("@layer") {
+                    let layer = Layer::from_name(name)
+                        .ok_or_else(|| Error::parse_error(format!("unknown layer `{}`", name.trim()), span))?;
+                    self.parse_rules(&rest[open + 1..close], body_start, Some(layer))?;
+                }
+                else {
+                    self.parse_block(prelude, &rest[open + 1..close], layer.unwrap_or(Layer::Components), span)?;
+                }
+                rest = &rest[close + 1..];
+            }
+            else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let class = &rest[..end];
+                let start = position;
+                position = advance(position, class);
+                if let Some(rule) = self.resolve_class(class, Span { start, end: position }) {
+                    self.push_rule(rule);
+                }
+                rest = &rest[end..];
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_block(&mut self, prelude: &str, body: &str, layer: Layer, span: Span) -> Result<()> {
+        let mut applied = Vec::new();
+        let mut declarations = Declarations::new();
+        for statement in split_statements(body) {
+            if let Some(utilities) = statement.strip_prefix("@apply") {
+                applied.push(utilities.trim());
+            }
+            else if let Some((property, value)) = statement.split_once(':') {
+                declarations.push((property.trim().to_string(), value.trim().to_string()));
+            }
+            else {
+                return Err(Error::parse_error(format!("expected a declaration or `@apply`, found `{}`", statement), span));
+            }
+        }
+
+        // `@layer utilities { .content-auto { ... } }` is the older spelling of `@utility`.
+        let utility_name = match layer {
+            Layer::Utilities => prelude.strip_prefix('.'),
+            _ => None,
+        };
+        let Some(name) = prelude.strip_prefix("@utility").or(utility_name)
+        else {
+            if prelude.starts_with('@') {
+                return Err(Error::parse_error(format!("unsupported at-rule `{}`", prelude), span));
+            }
+            return self.add_layer_rule(layer, prelude, &applied.join(" "), declarations, span);
+        };
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(Error::parse_error(format!("invalid `@utility` name `{}`", name), span));
+        }
+
+        // `@apply` inside a utility inlines the plain declarations; variants need a selector to attach to.
+        let mut utility = Declarations::new();
+        for class in applied.iter().flat_map(|a| a.split_whitespace()) {
+            let resolved = self.registry.resolve(class, &self.config).ok_or_else(|| {
+                Error::parse_error(format!("`@apply` cannot find the utility `{}` in `@utility {}`", class, name), span)
+            })?;
+            utility.extend(resolved.declarations);
+        }
+        utility.extend(declarations);
+        self.add_utility(name, utility);
+        Ok(())
+    }
+}
+
+fn span_at(position: Position) -> Span {
+    Span { start: position, end: position }
+}
+
+/// Moves `position` past `text`, counting columns in UTF-16 units like `SourceCursor`.
+fn advance(mut position: Position, text: &str) -> Position {
+    for c in text.chars() {
+        if c == '\n' {
+            position.line += 1;
+            position.column = 1;
+        }
+        else {
+            position.column += c.len_utf16() as u32;
+        }
+        position.offset += c.len_utf8() as u32;
+    }
+    position
+}
+
+/// Byte index of the `}` closing the `{` at `open`, skipping strings.
+fn matching_brace(source: &str, open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, c) in source[open..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits a block body at `;` outside of strings and parentheses, dropping empty statements.
+fn split_statements(body: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in body.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ';') if depth == 0 => {
+                statements.push(body[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    statements.push(body[start..].trim());
+    statements.retain(|s| !s.is_empty());
+    statements
+}
@@ -0,0 +1,158 @@
+use crate::{TailwindConfig, config::format_number};
+use std::collections::HashMap;
+
+/// `[37px]` → `37px`. Underscores stand for spaces (`[1fr_2fr]`) unless escaped as `\_`.
+pub fn arbitrary(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'_') => out.push(chars.next().unwrap()),
+            '_' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+/// Splits an optional `color:`/`length:` type hint off an arbitrary value.
+pub(crate) fn type_hint(value: &str) -> (Option<&str>, &str) {
+    for hint in ["color", "length", "url", "image"] {
+        if let Some(rest) = value.strip_prefix(hint).and_then(|r| r.strip_prefix(':')) {
+            return (Some(hint), rest);
+        }
+    }
+    (None, value)
+}
+
+pub(crate) fn looks_like_color(value: &str) -> bool {
+    value.starts_with('#')
+        || ["rgb(", "rgba(", "hsl(", "hsla(", "hwb(", "lab(", "lch(", "oklab(", "oklch(", "color(", "color-mix("]
+            .iter()
+            .any(|f| value.starts_with(f))
+}
+
+/// Splits `blue-500/50` into `blue-500` and `50`, ignoring slashes inside brackets.
+pub(crate) fn split_modifier(value: &str) -> (&str, Option<&str>) {
+    let mut depth = 0i32;
+    let mut split = None;
+    for (i, c) in value.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            '/' if depth == 0 => split = Some(i),
+            _ => {}
+        }
+    }
+    match split {
+        Some(i) => (&value[..i], Some(&value[i + 1..])),
+        None => (value, None),
+    }
+}
+
+/// Applies an alpha channel: hex colors become `rgb(r g b / a)`, anything else goes through `color-mix()`.
+pub(crate) fn with_alpha(color: &str, alpha: &str) -> String {
+    if let Some(hex) = color.strip_prefix('#') {
+        let expanded: String = match hex.len() {
+            3 => hex.chars().flat_map(|c| [c, c]).collect(),
+            6 => hex.to_string(),
+            _ => String::new(),
+        };
+        if let Ok(rgb) = u32::from_str_radix(&expanded, 16) {
+            return format!("rgb({} {} {} / {})", rgb >> 16, (rgb >> 8) & 0xff, rgb & 0xff, alpha);
+        }
+    }
+    let percentage = match alpha.parse::<f64>() {
+        Ok(n) => format!("{}%", format_number(n * 100.0)),
+        Err(_) => format!("calc({} * 100%)", alpha),
+    };
+    format!("color-mix(in srgb, {} {}, transparent)", color, percentage)
+}
+
+/// `-` in front of a length; `auto` and keywords cannot be negated.
+pub(crate) fn negate(value: &str) -> Option<String> {
+    if let Some(positive) = value.strip_prefix('-') {
+        return Some(positive.to_string());
+    }
+    if value.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return Some(if value.trim_start_matches(['0', '.']).starts_with(|c: char| c.is_ascii_digit()) {
+            format!("-{}", value)
+        }
+        else {
+            value.to_string()
+        });
+    }
+    if value.starts_with("calc(") || value.starts_with("var(") {
+        return Some(format!("calc({} * -1)", value));
+    }
+    None
+}
+
+/// A theme scale entry or an arbitrary `[value]`.
+pub fn theme_value(scale: &HashMap<String, String>, key: &str) -> Option<String> {
+    arbitrary(key).or_else(|| scale.get(key).cloned())
+}
+
+pub(crate) fn spacing(config: &TailwindConfig, key: &str) -> Option<String> {
+    theme_value(&config.spacing, key)
+}
+
+/// Margins also accept `auto`.
+pub(crate) fn spacing_or_auto(config: &TailwindConfig, key: &str) -> Option<String> {
+    if key == "auto" { Some("auto".to_string()) } else { spacing(config, key) }
+}
+
+/// Widths, heights and insets: the spacing scale plus fractions (`1/2` → `50%`) and keywords.
+fn size(config: &TailwindConfig, key: &str, viewport: &str) -> Option<String> {
+    if let Some((numerator, denominator)) = key.split_once('/') {
+        let (Ok(n), Ok(d)) = (numerator.parse::<f64>(), denominator.parse::<f64>())
+        else {
+            return None;
+        };
+        if d == 0.0 {
+            return None;
+        }
+        let percent = (n / d * 100.0 * 1_000_000.0).round() / 1_000_000.0;
+        return Some(format!("{}%", format_number(percent)));
+    }
+    match key {
+        "full" => Some("100%".to_string()),
+        "screen" => Some(viewport.to_string()),
+        "min" | "max" | "fit" => Some(format!("{}-content", key)),
+        _ => spacing_or_auto(config, key),
+    }
+}
+
+pub(crate) fn width(config: &TailwindConfig, key: &str) -> Option<String> {
+    size(config, key, "100vw")
+}
+
+pub(crate) fn height(config: &TailwindConfig, key: &str) -> Option<String> {
+    size(config, key, "100vh")
+}
+
+/// A palette color with an optional `/NN` or `/[0.35]` opacity modifier.
+pub fn theme_color(config: &TailwindConfig, key: &str) -> Option<String> {
+    let (base, alpha) = split_modifier(key);
+    let color = match arbitrary(base) {
+        Some(value) => match type_hint(&value) {
+            (Some("color"), color) => color.to_string(),
+            (None, color) if looks_like_color(color) => color.to_string(),
+            _ => return None,
+        },
+        None => config.colors.get(base)?.clone(),
+    };
+    let Some(alpha) = alpha
+    else {
+        return Some(color);
+    };
+    let alpha = match arbitrary(alpha) {
+        Some(value) => value,
+        None => match config.opacity.get(alpha) {
+            Some(value) => value.clone(),
+            None => format_number(alpha.parse::<f64>().ok()? / 100.0),
+        },
+    };
+    Some(with_alpha(&color, &alpha))
+}
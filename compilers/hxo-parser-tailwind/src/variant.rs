@@ -0,0 +1,168 @@
+use crate::{DarkMode, TailwindConfig};
+
+/// A single `prefix:` of a utility class such as `md:hover:bg-blue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Variant {
+    /// Appended to the class selector, e.g. `:hover`.
+    PseudoClass(String),
+    /// Appended after all pseudo-classes, e.g. `::placeholder`.
+    PseudoElement(String),
+    /// Wraps the rule in an at-rule, e.g. `@media (min-width: 768px)`.
+    AtRule(String),
+    /// Prefixes the selector with an ancestor/sibling, e.g. `.group:hover ` or `.peer:focus ~ `.
+    Ancestor(String),
+    /// Arbitrary selector variant (`[&>li]`), `&` stands for the selector built so far.
+    Selector(String),
+}
+
+const PSEUDO_CLASSES: [(&str, &str); 25] = [
+    ("hover", ":hover"),
+    ("focus", ":focus"),
+    ("focus-visible", ":focus-visible"),
+    ("focus-within", ":focus-within"),
+    ("active", ":active"),
+    ("visited", ":visited"),
+    ("target", ":target"),
+    ("disabled", ":disabled"),
+    ("enabled", ":enabled"),
+    ("checked", ":checked"),
+    ("indeterminate", ":indeterminate"),
+    ("required", ":required"),
+    ("optional", ":optional"),
+    ("valid", ":valid"),
+    ("invalid", ":invalid"),
+    ("read-only", ":read-only"),
+    ("placeholder-shown", ":placeholder-shown"),
+    ("empty", ":empty"),
+    ("first", ":first-child"),
+    ("last", ":last-child"),
+    ("only", ":only-child"),
+    ("odd", ":nth-child(odd)"),
+    ("even", ":nth-child(even)"),
+    ("first-of-type", ":first-of-type"),
+    ("last-of-type", ":last-of-type"),
+];
+
+const PSEUDO_ELEMENTS: [(&str, &str); 8] = [
+    ("before", "::before"),
+    ("after", "::after"),
+    ("placeholder", "::placeholder"),
+    ("selection", "::selection"),
+    ("marker", "::marker"),
+    ("first-line", "::first-line"),
+    ("first-letter", "::first-letter"),
+    ("file", "::file-selector-button"),
+];
+
+const MEDIA: [(&str, &str); 6] = [
+    ("dark", "(prefers-color-scheme: dark)"),
+    ("print", "print"),
+    ("motion-safe", "(prefers-reduced-motion: no-preference)"),
+    ("motion-reduce", "(prefers-reduced-motion: reduce)"),
+    ("portrait", "(orientation: portrait)"),
+    ("landscape", "(orientation: landscape)"),
+];
+
+fn pseudo_class(name: &str) -> Option<&'static str> {
+    if name == "open" {
+        return Some("[open]");
+    }
+    PSEUDO_CLASSES.iter().find(|(n, _)| *n == name).map(|(_, p)| *p)
+}
+
+/// Resolves a variant name against the theme's breakpoints and dark mode strategy.
+pub fn parse_variant(name: &str, config: &TailwindConfig) -> Option<Variant> {
+    let screens = &config.screens;
+    if name == "dark" && config.dark_mode == DarkMode::Class {
+        return Some(Variant::Ancestor(".dark ".to_string()));
+    }
+    if let Some(pseudo) = pseudo_class(name) {
+        return Some(Variant::PseudoClass(pseudo.to_string()));
+    }
+    if let Some((_, pseudo)) = PSEUDO_ELEMENTS.iter().find(|(n, _)| *n == name) {
+        return Some(Variant::PseudoElement(pseudo.to_string()));
+    }
+    if let Some((_, query)) = MEDIA.iter().find(|(n, _)| *n == name) {
+        return Some(Variant::AtRule(format!("@media {}", query)));
+    }
+    if let Some((_, width)) = screens.iter().find(|(n, _)| n == name) {
+        return Some(Variant::AtRule(format!("@media (min-width: {})", width)));
+    }
+    if let Some(screen) = name.strip_prefix("max-") {
+        if let Some((_, width)) = screens.iter().find(|(n, _)| n == screen) {
+            return Some(Variant::AtRule(format!("@media not all and (min-width: {})", width)));
+        }
+    }
+    if let Some(state) = name.strip_prefix("group-") {
+        return pseudo_class(state).map(|p| Variant::Ancestor(format!(".group{} ", p)));
+    }
+    if let Some(state) = name.strip_prefix("peer-") {
+        return pseudo_class(state).map(|p| Variant::Ancestor(format!(".peer{} ~ ", p)));
+    }
+    if let Some(inner) = name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+        let inner = inner.replace('_', " ");
+        if inner.starts_with('@') {
+            return Some(Variant::AtRule(inner));
+        }
+        if inner.contains('&') {
+            return Some(Variant::Selector(inner));
+        }
+    }
+    None
+}
+
+/// Splits `md:hover:bg-[#fff]` into `["md", "hover"]` and `bg-[#fff]`, ignoring `:` inside brackets.
+pub fn split_variants(class: &str) -> (Vec<&str>, &str) {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in class.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ':' if depth == 0 => {
+                parts.push(&class[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    (parts, &class[start..])
+}
+
+/// Escapes a class name for use in a selector, like `CSS.escape`: `md:w-1/2` becomes `md\:w-1\/2`.
+pub fn escape_class(class: &str) -> String {
+    let mut escaped = String::with_capacity(class.len());
+    for (i, c) in class.chars().enumerate() {
+        if i == 0 && c.is_ascii_digit() {
+            escaped.push_str(&format!("\\3{} ", c));
+        }
+        else if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+            escaped.push(c);
+        }
+        else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+/// Builds the final selector and at-rule wrappers for `.{escaped}` with `variants` applied left to right.
+pub fn apply_variants(class_selector: &str, variants: &[Variant]) -> (String, Vec<String>) {
+    let mut selector = class_selector.to_string();
+    let mut pseudo_element = String::new();
+    let mut at_rules = Vec::new();
+
+    for variant in variants {
+        match variant {
+            Variant::PseudoClass(pseudo) => selector.push_str(pseudo),
+            Variant::PseudoElement(pseudo) => pseudo_element = pseudo.clone(),
+            Variant::AtRule(rule) => at_rules.push(rule.clone()),
+            Variant::Ancestor(prefix) => selector = format!("{}{}", prefix, selector),
+            Variant::Selector(pattern) => selector = pattern.replace('&', &selector),
+        }
+    }
+    selector.push_str(&pseudo_element);
+    (selector, at_rules)
+}
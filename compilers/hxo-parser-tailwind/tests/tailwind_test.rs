@@ -1,11 +1,14 @@
-use hxo_parser_tailwind::StyleEngine;
+use hxo_parser_tailwind::{
+    DarkMode, Layer, SharedStyleEngine, StyleEngine, TailwindConfig, UtilityRegistry, escape_class, theme_value,
+};
 use hxo_types::{Position, Span};
+use std::sync::Arc;
 
 #[test]
 fn test_parse_classes() {
     let mut engine = StyleEngine::new();
     let span = Span { start: Position { line: 1, column: 1, offset: 0 }, end: Position { line: 1, column: 1, offset: 0 } };
-    engine.parse_classes("m-4 p-2 rounded-lg bg-blue text-white", span).unwrap();
+    engine.parse_classes("m-4 p-2 rounded-lg bg-blue-500 text-white", span).unwrap();
 
     let css = engine.generate_css();
     assert!(css.contains(".m-4"));
@@ -14,8 +17,8 @@ fn test_parse_classes() {
     assert!(css.contains("padding: 0.5rem;"));
     assert!(css.contains(".rounded-lg"));
     assert!(css.contains("border-radius: 0.5rem;"));
-    assert!(css.contains(".bg-blue"));
-    assert!(css.contains("background-color: #0000ff;"));
+    assert!(css.contains(".bg-blue-500"));
+    assert!(css.contains("background-color: #3b82f6;"));
     assert!(css.contains(".text-white"));
     assert!(css.contains("color: #ffffff;"));
 }
@@ -24,7 +27,7 @@ fn test_parse_classes() {
 fn test_position_tracking() {
     let mut engine = StyleEngine::new();
     let span = Span { start: Position { line: 1, column: 10, offset: 10 }, end: Position { line: 1, column: 20, offset: 20 } };
-    engine.parse_classes("m-4 text-red", span).unwrap();
+    engine.parse_classes("m-4 text-red-500", span).unwrap();
 
     assert_eq!(engine.rules.len(), 2);
 
@@ -32,8 +35,347 @@ fn test_position_tracking() {
     assert_eq!(engine.rules[0].span.start.column, 10);
     assert_eq!(engine.rules[0].span.end.column, 13);
 
-    // text-red
+    // text-red-500
     // current_column += class_len + 1 = 10 + 3 + 1 = 14
     assert_eq!(engine.rules[1].span.start.column, 14);
-    assert_eq!(engine.rules[1].span.end.column, 22);
+    assert_eq!(engine.rules[1].span.end.column, 26);
+}
+
+#[test]
+fn test_variants() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles(
+        "lg:p-4 md:flex hover:bg-blue-500 dark:text-white focus-visible:m-2 group-hover:text-red-500 disabled:hidden",
+    );
+    engine.add_styles("md:hover:bg-[#fff] peer-checked:block placeholder:text-gray-800 [&>li]:m-1 flex bogus:flex");
+
+    let css = engine.generate_css();
+    assert!(css.contains(".hover\\:bg-blue-500:hover {\n  background-color: #3b82f6;\n}"));
+    assert!(css.contains("@media (prefers-color-scheme: dark) {\n  .dark\\:text-white {\n    color: #ffffff;\n  }\n}"));
+    assert!(css.contains(".focus-visible\\:m-2:focus-visible {"));
+    assert!(css.contains(".group:hover .group-hover\\:text-red-500 {"));
+    assert!(css.contains(".disabled\\:hidden:disabled {"));
+    assert!(css.contains(".peer:checked ~ .peer-checked\\:block {"));
+    assert!(css.contains(".placeholder\\:text-gray-800::placeholder {"));
+    assert!(css.contains(".\\[\\&\\>li\\]\\:m-1>li {"));
+    assert!(!css.contains("bogus"));
+    assert!(css.contains("@media (min-width: 768px) {\n  .md\\:hover\\:bg-\\[\\#fff\\]:hover {\n    background-color: #fff;"));
+
+    // Base utilities come first, then breakpoints in ascending order
+    let flex = css.find(".flex {").unwrap();
+    let md = css.find("@media (min-width: 768px) {\n  .md\\:flex {").unwrap();
+    let lg = css.find("@media (min-width: 1024px) {\n  .lg\\:p-4 {").unwrap();
+    assert!(flex < md && md < lg);
+}
+
+#[test]
+fn test_stacked_variants_and_escaping() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles("md:hover:p-4 sm:dark:m-1");
+    let css = engine.generate_css();
+    assert!(css.contains("@media (min-width: 768px) {\n  .md\\:hover\\:p-4:hover {\n    padding: 1rem;\n  }\n}"));
+    assert!(css.contains(
+        "@media (min-width: 640px) {\n  @media (prefers-color-scheme: dark) {\n    .sm\\:dark\\:m-1 {\n      margin: 0.25rem;\n    }\n  }\n}"
+    ));
+    assert_eq!(escape_class("w-1/2"), "w-1\\/2");
+    assert_eq!(escape_class("2xl:p-4"), "\\32 xl\\:p-4");
+}
+
+#[test]
+fn test_default_theme_scales() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles(
+        "text-sm font-semibold font-mono rounded shadow-md leading-tight tracking-wide opacity-75 z-50 bg-slate-950",
+    );
+    let css = engine.generate_css();
+    assert!(css.contains(".text-sm {\n  font-size: 0.875rem;\n  line-height: 1.25rem;\n}"));
+    assert!(css.contains("font-weight: 600;"));
+    assert!(css.contains("font-family: ui-monospace, SFMono-Regular"));
+    assert!(css.contains(".rounded {\n  border-radius: 0.25rem;\n}"));
+    assert!(css.contains("box-shadow: 0 4px 6px -1px rgb(0 0 0 / 0.1), 0 2px 4px -2px rgb(0 0 0 / 0.1);"));
+    assert!(css.contains("line-height: 1.25;"));
+    assert!(css.contains("letter-spacing: 0.025em;"));
+    assert!(css.contains("opacity: 0.75;"));
+    assert!(css.contains("z-index: 50;"));
+    assert!(css.contains("background-color: #020617;"));
+}
+
+#[test]
+fn test_layout_border_and_typography_utilities() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles("flex flex-col flex-wrap flex-1 shrink-0 self-center overflow-hidden overflow-y-auto");
+    engine.add_styles("max-w-md max-w-screen-lg min-h-screen max-h-none border border-t-2 border-x-[3px] border-red-500");
+    engine.add_styles("border-dashed truncate uppercase italic underline cursor-pointer select-none");
+    let css = engine.generate_css();
+    assert!(css.contains(".flex-col {\n  flex-direction: column;\n}"));
+    assert!(css.contains("flex-wrap: wrap;"));
+    assert!(css.contains(".flex-1 {\n  flex: 1 1 0%;\n}"));
+    assert!(css.contains("flex-shrink: 0;"));
+    assert!(css.contains("align-self: center;"));
+    assert!(css.contains(".overflow-hidden {\n  overflow: hidden;\n}"));
+    assert!(css.contains("overflow-y: auto;"));
+    assert!(css.contains(".max-w-md {\n  max-width: 28rem;\n}"));
+    assert!(css.contains(".max-w-screen-lg {\n  max-width: 1024px;\n}"));
+    assert!(css.contains("min-height: 100vh;"));
+    assert!(css.contains("max-height: none;"));
+    assert!(css.contains(".border {\n  border-style: var(--tw-border-style, solid);\n  border-width: 1px;\n}"));
+    assert!(css.contains(".border-t-2 {\n  border-top-style: var(--tw-border-style, solid);\n  border-top-width: 2px;\n}"));
+    assert!(css.contains("border-left-width: 3px;") && css.contains("border-right-width: 3px;"));
+    assert!(css.contains(".border-red-500 {\n  border-color: #ef4444;\n}"));
+    assert!(css.contains(".border-dashed {\n  --tw-border-style: dashed;\n  border-style: dashed;\n}"));
+    assert!(css.contains(".truncate {\n  overflow: hidden;\n  text-overflow: ellipsis;\n  white-space: nowrap;\n}"));
+    assert!(css.contains("text-transform: uppercase;"));
+    assert!(css.contains("font-style: italic;"));
+    assert!(css.contains("text-decoration-line: underline;"));
+    assert!(css.contains("cursor: pointer;"));
+    assert!(css.contains("user-select: none;"));
+
+    // The border style follows the widths, so `border-dashed` wins over `border`
+    assert!(css.find(".border {").unwrap() < css.find(".border-dashed {").unwrap());
+}
+
+#[test]
+fn test_arbitrary_opacity_and_negative_values() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles("w-[37px] bg-[#123456] grid-cols-[1fr_2fr] text-[color:var(--brand)] text-[13px] w-1/3");
+    engine.add_styles("bg-blue-500/50 text-black/[.35] bg-current/25 -mt-4 -z-10 -inset-x-px -p-4 -m-auto");
+    let css = engine.generate_css();
+    assert!(css.contains(".w-\\[37px\\] {\n  width: 37px;\n}"));
+    assert!(css.contains("background-color: #123456;"));
+    assert!(css.contains("grid-template-columns: 1fr 2fr;"));
+    assert!(css.contains("color: var(--brand);"));
+    assert!(css.contains("font-size: 13px;"));
+    assert!(css.contains("width: 33.333333%;"));
+    assert!(css.contains(".bg-blue-500\\/50 {\n  background-color: rgb(59 130 246 / 0.5);\n}"));
+    assert!(css.contains("color: rgb(0 0 0 / .35);"));
+    assert!(css.contains("background-color: color-mix(in srgb, currentColor 25%, transparent);"));
+    assert!(css.contains(".-mt-4 {\n  margin-top: -1rem;\n}"));
+    assert!(css.contains("z-index: -10;"));
+    assert!(css.contains("left: -1px;\n  right: -1px;"));
+    assert!(!css.contains("-p-4"));
+    assert!(!css.contains("-m-auto"));
+}
+
+#[test]
+fn test_theme_from_config() {
+    let config = TailwindConfig::from_toml(
+        r##"
+[compiler]
+auto_imports = false
+
+[tailwind]
+darkMode = "class"
+
+[tailwind.theme.screens]
+tablet = "640px"
+desktop = "80rem"
+
+[tailwind.theme.extend.colors]
+brand = { DEFAULT = "#0f766e", 700 = "#115e59" }
+blue = { 500 = "#1d4ed8" }
+
+[tailwind.theme.extend.spacing]
+18 = "4.5rem"
+
+[tailwind.theme.extend.fontWeight]
+heavy = 850
+"##,
+    )
+    .unwrap();
+    assert_eq!(config.dark_mode, DarkMode::Class);
+    assert_eq!(config.screens, vec![("tablet".to_string(), "640px".to_string()), ("desktop".to_string(), "80rem".to_string())]);
+    assert_eq!(config.colors["red-500"], "#ef4444");
+
+    let mut engine = StyleEngine::with_config(Arc::new(config));
+    engine.add_styles("bg-brand text-brand-700 bg-blue-500 p-18 font-heavy desktop:flex md:flex dark:text-white");
+    let css = engine.generate_css();
+    assert!(css.contains(".bg-brand {\n  background-color: #0f766e;\n}"));
+    assert!(css.contains("color: #115e59;"));
+    assert!(css.contains("background-color: #1d4ed8;"));
+    assert!(css.contains("padding: 4.5rem;"));
+    assert!(css.contains("font-weight: 850;"));
+    assert!(css.contains("@media (min-width: 80rem) {\n  .desktop\\:flex {"));
+    assert!(!css.contains("md\\:flex"));
+    assert!(css.contains(".dark .dark\\:text-white {\n  color: #ffffff;\n}"));
+
+    assert!(TailwindConfig::from_toml("[tailwind.theme.colors]\nbrand = 5").is_err());
+    assert_eq!(TailwindConfig::from_toml("").unwrap(), TailwindConfig::new());
+}
+
+#[test]
+fn test_registry_lookup_and_order() {
+    let registry = UtilityRegistry::new();
+    let config = TailwindConfig::new();
+    let inset = registry.resolve("inset-x-4", &config).unwrap();
+    assert_eq!(inset.declarations, vec![("left".to_string(), "1rem".to_string()), ("right".to_string(), "1rem".to_string())]);
+    assert_eq!(registry.resolve("rounded", &config).unwrap().declarations[0].1, "0.25rem");
+    assert!(registry.resolve("-p-4", &config).is_none());
+    assert!(registry.resolve("bogus-4", &config).is_none());
+
+    let p = registry.resolve("p-4", &config).unwrap().order;
+    let pt = registry.resolve("pt-2", &config).unwrap().order;
+    assert!(p < pt);
+
+    // Output follows registration order, not class order, so the longhand wins the cascade
+    let mut engine = StyleEngine::new();
+    engine.add_styles("pt-2 leading-none p-4 text-lg !mt-2 md:m-1!");
+    let css = engine.generate_css();
+    assert!(css.find(".p-4 {").unwrap() < css.find(".pt-2 {").unwrap());
+    assert!(css.find(".text-lg {").unwrap() < css.find(".leading-none {").unwrap());
+    assert!(css.contains(".\\!mt-2 {\n  margin-top: 0.5rem !important;\n}"));
+    assert!(css.contains(".md\\:m-1\\! {\n    margin: 0.25rem !important;\n  }"));
+}
+
+#[test]
+fn test_custom_utilities_and_components() {
+    let mut engine = StyleEngine::new();
+    engine.add_utility("content-auto", vec![("content-visibility".to_string(), "auto".to_string())]);
+    engine.add_functional_utility("tab", |value, config| {
+        theme_value(&config.spacing, value).map(|size| vec![("tab-size".to_string(), size)])
+    });
+    engine.add_styles("hover:content-auto tab-4 tab-[3]");
+    engine.add_component(".btn, .button", "px-4 hover:bg-blue-500 font-bold !important").unwrap();
+    assert!(engine.add_component(".card", "p-4 nope-1").is_err());
+
+    let css = engine.generate_css();
+    assert!(css.contains(".hover\\:content-auto:hover {\n  content-visibility: auto;\n}"));
+    assert!(css.contains(".tab-4 {\n  tab-size: 1rem;\n}"));
+    assert!(css.contains("tab-size: 3;"));
+    assert!(css.contains(
+        ".btn, .button {\n  padding-left: 1rem !important;\n  padding-right: 1rem !important;\n  font-weight: 700 !important;\n}"
+    ));
+    assert!(css.contains(".btn:hover, .button:hover {\n  background-color: #3b82f6 !important;\n}"));
+    // Components come before utilities
+    assert!(css.find(".btn, .button {").unwrap() < css.find(".tab-4 {").unwrap());
+    assert!(!css.contains(".card"));
+}
+
+#[test]
+fn test_parse_source_blocks() {
+    let mut engine = StyleEngine::new();
+    let span = Span { start: Position { line: 3, column: 5, offset: 40 }, end: Position { line: 3, column: 5, offset: 40 } };
+    let source = "/* layout */ flex\n@utility stack { @apply flex gap-2; flex-direction: column; }\n.card {\n  @apply p-4 md:p-6;\n  border: 1px solid var(--line);\n}\nmd:stack";
+    engine.parse_source(source, span).unwrap();
+
+    let flex = engine.rules.iter().find(|r| r.selector == ".flex").unwrap();
+    assert_eq!((flex.span.start.line, flex.span.start.column, flex.span.start.offset), (3, 18, 53));
+    let stack = engine.rules.iter().find(|r| r.selector == ".md\\:stack").unwrap();
+    assert_eq!(stack.span.start.line, 9);
+
+    let css = engine.generate_css();
+    assert!(css.contains(".card {\n  border: 1px solid var(--line);\n  padding: 1rem;\n}"));
+    assert!(css.contains("@media (min-width: 768px) {\n  .card {\n    padding: 1.5rem;\n  }\n}"));
+    assert!(css.contains(".md\\:stack {\n    display: flex;\n    gap: 0.5rem;\n    flex-direction: column;\n  }"));
+
+    assert!(StyleEngine::new().parse_source(".card { @apply bogus; }", Span::unknown()).is_err());
+    assert!(StyleEngine::new().parse_source(".card { color: red;", Span::unknown()).is_err());
+}
+
+#[test]
+fn test_rules_are_deduplicated() {
+    let mut engine = StyleEngine::new();
+    let span = Span { start: Position { line: 1, column: 1, offset: 0 }, end: Position { line: 1, column: 1, offset: 0 } };
+    engine.parse_classes("p-4 flex p-4", span).unwrap();
+    engine.add_styles("p-4 hover:p-4");
+    engine.parse_source("p-4 .card { @apply flex; } .card { @apply p-4; }", Span::unknown()).unwrap();
+
+    let css = engine.generate_css();
+    assert_eq!(css.matches(".p-4 {").count(), 1);
+    assert_eq!(css.matches(".hover\\:p-4:hover {").count(), 1);
+    assert!(css.contains(".card {\n  display: flex;\n  padding: 1rem;\n}"), "{}", css);
+    // The first occurrence keeps its span
+    assert_eq!(engine.rules.iter().find(|r| r.selector == ".p-4").unwrap().span.start.column, 1);
+}
+
+#[test]
+fn test_layer_order() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles("p-4 md:flex");
+    engine.add_component(".btn", "px-4").unwrap();
+    engine.add_base("h1", "text-2xl").unwrap();
+    let source = "@layer base { a { @apply font-bold; color: inherit; } }\n@layer utilities { .content-auto { content-visibility: auto; } }\ncontent-auto";
+    engine.parse_source(source, Span::unknown()).unwrap();
+    engine.add_raw_css(".raw { color: red; }");
+
+    let css = engine.generate_css();
+    let positions: Vec<usize> = ["h1 {", "a {", ".btn {", ".p-4 {", ".content-auto {", "@media (min-width: 768px)", ".raw"]
+        .iter()
+        .map(|s| css.find(s).unwrap())
+        .collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", css);
+    assert_eq!(engine.rules.iter().find(|r| r.selector == "a").unwrap().layer, Layer::Base);
+    assert!(engine.generate_rules_css().find(".raw").is_none());
+    assert!(StyleEngine::new().parse_source("@layer nope { a { color: red; } }", Span::unknown()).is_err());
+}
+
+#[test]
+fn test_shared_engine_merges_components() {
+    let shared = SharedStyleEngine::new(Arc::new(TailwindConfig::new()));
+    let mut header = StyleEngine::new();
+    header.add_styles("p-4 flex");
+    header.add_raw_css(".header { color: red; }");
+    let mut footer = StyleEngine::new();
+    footer.add_styles("flex p-4 mt-2");
+    footer.add_component(".link", "font-bold").unwrap();
+
+    shared.merge(&header);
+    shared.clone().merge(&footer);
+
+    let css = shared.generate_css();
+    assert_eq!(css.matches(".p-4 {").count(), 1);
+    assert_eq!(css.matches(".flex {").count(), 1);
+    assert!(css.contains(".mt-2"));
+    assert!(css.find(".link {").unwrap() < css.find(".flex {").unwrap());
+    assert!(!css.contains(".header"));
+    assert_eq!(shared.lock().rules.len(), 4);
+}
+
+#[test]
+fn test_theme_lookup() {
+    let config = TailwindConfig::new();
+    assert_eq!(config.lookup("colors.blue.500").as_deref(), Some("#3b82f6"));
+    assert_eq!(config.lookup("colors.blue.500 / 50%").as_deref(), Some("rgb(59 130 246 / 50%)"));
+    assert_eq!(config.lookup("spacing[2.5]").as_deref(), Some("0.625rem"));
+    assert_eq!(config.lookup("spacing.4").as_deref(), Some("1rem"));
+    assert_eq!(config.lookup("fontSize.lg").as_deref(), Some("1.125rem"));
+    assert_eq!(config.lookup("screens.md").as_deref(), Some("768px"));
+    assert_eq!(config.lookup("colors.nope"), None);
+    assert_eq!(config.lookup("spacing.4 / 50%"), None);
+}
+
+#[test]
+fn test_expand_directives() {
+    let engine = StyleEngine::new();
+    let css = ".btn { color: theme('colors.blue.500'); @apply px-4 hover:bg-blue-600 md:p-6; }\n@screen md { .a { margin: theme(spacing.4); } }";
+    let expanded = engine.expand_directives(css).unwrap();
+    assert!(expanded.contains(".btn {\n  color: #3b82f6;\n  padding-left: 1rem;\n  padding-right: 1rem;\n}"), "{}", expanded);
+    assert!(expanded.contains(".btn:hover {\n  background-color: #2563eb;\n}"));
+    assert!(expanded.contains("@media (min-width: 768px) {\n  .btn {\n    padding: 1.5rem;\n  }\n}"));
+    assert!(expanded.contains("@media (min-width: 768px) {\n  .a {\n    margin: 1rem;\n  }\n}"));
+
+    // Plain CSS is returned untouched
+    assert_eq!(engine.expand_directives(".a{color:red}").unwrap(), ".a{color:red}");
+    assert!(engine.expand_directives(".a { color: theme('colors.nope'); }").is_err());
+    assert!(engine.expand_directives("@apply p-4;").is_err());
+    assert!(engine.expand_directives("@screen huge { .a { color: red; } }").is_err());
+    assert!(engine.expand_directives(".a { @apply bogus; }").is_err());
+}
+
+#[test]
+fn test_layered_css_order() {
+    let mut engine = StyleEngine::new();
+    engine.add_styles("p-4");
+    engine
+        .add_css(".plain { color: red; }\n@layer components { .card { padding: 2rem; } }\n@layer base { h1 { margin: 0; } }")
+        .unwrap();
+    engine.add_css("@layer custom { .x { color: blue; } }").unwrap();
+
+    let css = engine.generate_css();
+    let positions: Vec<usize> =
+        ["h1 {", ".card {", ".p-4 {", ".plain {", "@layer custom"].iter().map(|s| css.find(s).unwrap()).collect();
+    assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", css);
+
+    let raw = engine.generate_raw_css();
+    assert!(!raw.contains(".p-4"));
+    assert!(raw.find("h1 {").unwrap() < raw.find(".card {").unwrap());
 }
@@ -1,5 +1,5 @@
 use hxo_ir::{AttributeIR, ElementIR, ExpressionIR, TemplateNodeIR};
-use hxo_parser::{ParseState, TemplateParser};
+use hxo_parser::{ParseState, TemplateParser as TemplateParserTrait};
 use hxo_types::{Result, Span, is_void_element};
 
 pub struct TemplateParser;
@@ -10,7 +10,17 @@ pub fn parse(source: &str) -> Result<Vec<TemplateNodeIR>> {
     parser.parse(&mut state, "html")
 }
 
-impl TemplateParser for TemplateParser {
+/// Parses the element at the cursor, leaving the cursor after its end tag.
+pub fn parse_element(state: &mut ParseState) -> Result<TemplateNodeIR> {
+    TemplateParserImpl { state }.parse_element()
+}
+
+/// Parses a start tag such as `<Card title="Hi">`, returning its name, its attributes and whether it was self-closing.
+pub fn parse_start_tag(state: &mut ParseState) -> Result<(String, Vec<AttributeIR>, bool)> {
+    TemplateParserImpl { state }.parse_start_tag()
+}
+
+impl TemplateParserTrait for TemplateParser {
     fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<Vec<TemplateNodeIR>> {
         let mut parser = TemplateParserImpl { state };
         parser.parse()
@@ -48,6 +58,41 @@ impl<'a, 'b> TemplateParserImpl<'a, 'b> {
 
     fn parse_element(&mut self) -> Result<TemplateNodeIR> {
         let start_pos = self.state.cursor.position();
+        let (tag, attributes, is_self_closing) = self.parse_start_tag()?;
+
+        let mut children = Vec::new();
+        if !is_self_closing && !is_void_element(&tag) {
+            if tag == "script" || tag == "style" {
+                let start = self.state.cursor.pos;
+                let end_tag = format!("</{}>", tag);
+                while !self.state.cursor.is_eof() && !self.state.cursor.peek_str(&end_tag) {
+                    self.state.cursor.consume();
+                }
+                let content = self.state.cursor.current_str(start).to_string();
+                if !content.is_empty() {
+                    let text_span = self.state.cursor.span_from(start_pos);
+                    children.push(TemplateNodeIR::Text(content, text_span));
+                }
+                self.state.cursor.expect_str(&end_tag)?;
+            }
+            else {
+                children = self.parse()?;
+                self.state.cursor.expect_str(&format!("</{}>", tag))?;
+            }
+        }
+
+        let end_pos = self.state.cursor.position();
+
+        Ok(TemplateNodeIR::Element(ElementIR {
+            tag,
+            attributes,
+            children,
+            is_static: false, // Default to false, optimizer will handle it
+            span: Span { start: start_pos, end: end_pos },
+        }))
+    }
+
+    fn parse_start_tag(&mut self) -> Result<(String, Vec<AttributeIR>, bool)> {
         self.state.cursor.expect('<')?;
         let tag = self.state.cursor.consume_while(|c| c.is_alphanumeric() || c == '-');
         let mut attributes = Vec::new();
@@ -103,37 +148,7 @@ impl<'a, 'b> TemplateParserImpl<'a, 'b> {
             self.state.cursor.expect('>')?;
             false
         };
-
-        let mut children = Vec::new();
-        if !is_self_closing && !is_void_element(&tag) {
-            if tag == "script" || tag == "style" {
-                let start = self.state.cursor.pos;
-                let end_tag = format!("</{}>", tag);
-                while !self.state.cursor.is_eof() && !self.state.cursor.peek_str(&end_tag) {
-                    self.state.cursor.consume();
-                }
-                let content = self.state.cursor.current_str(start).to_string();
-                if !content.is_empty() {
-                    let text_span = self.state.cursor.span_from(start_pos);
-                    children.push(TemplateNodeIR::Text(content, text_span));
-                }
-                self.state.cursor.expect_str(&end_tag)?;
-            }
-            else {
-                children = self.parse()?;
-                self.state.cursor.expect_str(&format!("</{}>", tag))?;
-            }
-        }
-
-        let end_pos = self.state.cursor.position();
-
-        Ok(TemplateNodeIR::Element(ElementIR {
-            tag,
-            attributes,
-            children,
-            is_static: false, // Default to false, optimizer will handle it
-            span: Span { start: start_pos, end: end_pos },
-        }))
+        Ok((tag, attributes, is_self_closing))
     }
 
     fn parse_interpolation(&mut self) -> Result<TemplateNodeIR> {
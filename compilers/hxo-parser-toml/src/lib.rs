@@ -1,4 +1,4 @@
-use hxo_parser::{MetadataParser, ParseState};
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
 use hxo_types::{HxoValue, Result, Span};
 
 #[derive(Default)]
@@ -28,6 +28,33 @@ impl MetadataParser for TomlParser {
     }
 }
 
+impl MetadataSerializer for TomlParser {
+    fn serialize(&self, value: &HxoValue, _lang: &str) -> Result<String> {
+        toml::to_string(&convert_hxo_to_json(value)?)
+            .map_err(|e| hxo_types::Error::external_error("TOML".to_string(), e.to_string(), Span::unknown()))
+    }
+}
+
+fn convert_hxo_to_json(value: &HxoValue) -> Result<serde_json::Value> {
+    Ok(match value {
+        HxoValue::Bool(b) => serde_json::Value::Bool(*b),
+        HxoValue::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => serde_json::Value::from(*n as i64),
+        HxoValue::Number(n) => serde_json::Value::from(*n),
+        HxoValue::String(s) => serde_json::Value::String(s.clone()),
+        HxoValue::Array(a) => serde_json::Value::Array(a.iter().map(convert_hxo_to_json).collect::<Result<_>>()?),
+        HxoValue::Object(o) => {
+            serde_json::Value::Object(o.iter().map(|(k, v)| Ok((k.clone(), convert_hxo_to_json(v)?))).collect::<Result<_>>()?)
+        }
+        // TOML has no null
+        _ => {
+            return Err(hxo_types::Error::invalid_i18n(
+                "toml: only text, numbers, lists and tables can be written".to_string(),
+                Span::unknown(),
+            ));
+        }
+    })
+}
+
 fn convert_json_to_hxo(value: serde_json::Value) -> HxoValue {
     match value {
         serde_json::Value::Null => HxoValue::Null,
@@ -1,7 +1,8 @@
-use hxo_parser::{MetadataParser, ParseState};
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
 use hxo_parser_toml::TomlParser;
 use hxo_types::HxoValue;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Deserialize, Debug, PartialEq)]
 struct Config {
@@ -54,3 +55,14 @@ fn test_parse_toml_as_metadata() {
         panic!("Expected object");
     }
 }
+
+#[test]
+fn test_serialize_round_trip() {
+    let value = HxoValue::Object(HashMap::from([
+        ("title".to_string(), HxoValue::String("Say \"hi\"\\now".to_string())),
+        ("nav".to_string(), HxoValue::Object(HashMap::from([("home".to_string(), HxoValue::String("Home".to_string()))]))),
+    ]));
+    let text = TomlParser.serialize(&value, "toml").unwrap();
+    assert_eq!(TomlParser.parse(&mut ParseState::new(&text), "toml").unwrap(), value);
+    assert!(TomlParser.serialize(&HxoValue::Object(HashMap::from([("x".to_string(), HxoValue::Null)])), "toml").is_err());
+}
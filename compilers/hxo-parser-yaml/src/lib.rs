@@ -1,14 +1,91 @@
-use hxo_parser::{MetadataParser, ParseState};
-use hxo_types::{HxoValue, Result, Route, RouterConfig, Span};
+use hxo_parser::{MetadataParser, MetadataSerializer, ParseState};
+use hxo_types::{Cursor, Error, HxoValue, Result, Route, RouterConfig, Span};
 use std::collections::HashMap;
 
+pub mod parser;
+
 pub struct YamlParser;
 
 impl MetadataParser for YamlParser {
     fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<HxoValue> {
-        let mut parser = YamlParserImpl { state };
-        parser.parse_yaml()
+        Ok(single(parser::parse_stream(&mut state.cursor)?))
+    }
+}
+
+impl MetadataSerializer for YamlParser {
+    /// Block style with sorted keys; every string is double-quoted so that no text reads as another type.
+    fn serialize(&self, value: &HxoValue, _lang: &str) -> Result<String> {
+        let mut out = String::new();
+        match value {
+            HxoValue::Object(map) if !map.is_empty() => write_map(map, 0, &mut out)?,
+            HxoValue::Array(items) if !items.is_empty() => write_list(items, 0, &mut out)?,
+            _ => {
+                out.push_str(&scalar(value)?);
+                out.push('\n');
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn write_map(map: &HashMap<String, HxoValue>, indent: usize, out: &mut String) -> Result<()> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    for key in keys {
+        let key_text = if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+            key.clone()
+        }
+        else {
+            quote(key)
+        };
+        out.push_str(&format!("{}{}:", " ".repeat(indent), key_text));
+        write_nested(&map[key], indent, out)?;
+    }
+    Ok(())
+}
+
+fn write_list(items: &[HxoValue], indent: usize, out: &mut String) -> Result<()> {
+    for item in items {
+        out.push_str(&format!("{}-", " ".repeat(indent)));
+        write_nested(item, indent, out)?;
     }
+    Ok(())
+}
+
+/// The value after `key:` or `-`: a scalar on the same line, or a block indented under it.
+fn write_nested(value: &HxoValue, indent: usize, out: &mut String) -> Result<()> {
+    match value {
+        HxoValue::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_map(map, indent + 2, out)
+        }
+        HxoValue::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_list(items, indent + 2, out)
+        }
+        _ => {
+            out.push_str(&format!(" {}\n", scalar(value)?));
+            Ok(())
+        }
+    }
+}
+
+fn scalar(value: &HxoValue) -> Result<String> {
+    match value {
+        HxoValue::Null => Ok("null".to_string()),
+        HxoValue::Bool(flag) => Ok(flag.to_string()),
+        HxoValue::Number(number) => Ok(number.to_string()),
+        HxoValue::String(text) => Ok(quote(text)),
+        HxoValue::Object(_) => Ok("{}".to_string()),
+        HxoValue::Array(_) => Ok("[]".to_string()),
+        _ => Err(Error::invalid_i18n("yaml: only text, numbers, lists and tables can be written".to_string(), Span::unknown())),
+    }
+}
+
+fn quote(text: &str) -> String {
+    let escaped =
+        text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t").replace('\r', "\\r");
+    format!("\"{}\"", escaped)
 }
 
 pub fn parse_router(source: &str) -> Result<RouterConfig> {
@@ -41,7 +118,9 @@ fn parse_routes(value: &HxoValue) -> Result<Vec<Route>> {
                     let children =
                         if let Some(children_val) = map.get("children") { Some(parse_routes(children_val)?) } else { None };
 
-                    routes.push(Route { path, component, name, redirect, children, meta: None, span: Span::default() });
+                    let meta = map.get("meta").cloned();
+
+                    routes.push(Route { path, component, name, redirect, children, meta, span: Span::default() });
                 }
             }
             Ok(routes)
@@ -50,288 +129,24 @@ fn parse_routes(value: &HxoValue) -> Result<Vec<Route>> {
     }
 }
 
-struct YamlParserImpl<'a, 'b> {
-    state: &'a mut ParseState<'b>,
-}
-
-impl<'a, 'b> YamlParserImpl<'a, 'b> {
-    pub fn parse_yaml(&mut self) -> Result<HxoValue> {
-        self.state.cursor.skip_whitespace();
-        if self.state.cursor.is_eof() {
-            return Ok(HxoValue::Null);
-        }
-
-        let first = self.state.cursor.peek();
-        if first == '-' {
-            self.parse_list()
-        }
-        else if first == '{' {
-            self.parse_json_object()
-        }
-        else if first == '[' {
-            self.parse_json_array()
-        }
-        else {
-            // Check if it's a map
-            let indent = self.get_indent();
-            let line = self.peek_line();
-            if line.contains(':') { self.parse_map(indent) } else { self.parse_scalar() }
-        }
-    }
-
-    fn parse_map(&mut self, indent: usize) -> Result<HxoValue> {
-        let mut map = HashMap::new();
-        while !self.state.cursor.is_eof() {
-            self.state.cursor.skip_spaces();
-            if self.state.cursor.is_eof() || self.state.cursor.peek() == '\n' {
-                if !self.state.cursor.is_eof() {
-                    self.state.cursor.consume(); // \n
-                }
-                continue;
-            }
-
-            let current_indent = self.get_indent();
-            if current_indent < indent {
-                break;
-            }
-
-            if self.state.cursor.peek() == '#' {
-                self.skip_line();
-                continue;
-            }
-
-            // If we hit a '-' at the same indent as the current map, it's likely we're inside a list item
-            if self.state.cursor.peek() == '-' && current_indent == indent {
-                break;
-            }
-
-            let key = self.consume_key()?;
-            if key.is_empty() {
-                break;
-            }
-
-            self.state.cursor.skip_spaces();
-            if self.state.cursor.peek() == ':' {
-                self.state.cursor.consume();
-                self.state.cursor.skip_spaces();
-
-                let value = if self.state.cursor.peek() == '\n' || self.state.cursor.is_eof() {
-                    self.state.cursor.skip_whitespace();
-                    let next_indent = self.get_indent();
-                    if self.state.cursor.peek() == '-' {
-                        self.parse_list()?
-                    }
-                    else if next_indent > current_indent {
-                        self.parse_map(next_indent)?
-                    }
-                    else {
-                        HxoValue::Null
-                    }
-                }
-                else if self.state.cursor.peek() == '-' {
-                    self.parse_list()?
-                }
-                else {
-                    let line = self.peek_line();
-                    if line.contains(':')
-                        && !line.starts_with('"')
-                        && !line.starts_with('\'')
-                        && !line.starts_with('{')
-                        && !line.starts_with('[')
-                    {
-                        let next_indent = self.get_indent();
-                        self.parse_map(next_indent)?
-                    }
-                    else {
-                        self.parse_scalar()?
-                    }
-                };
-                map.insert(key, value);
-            }
-            else {
-                break;
-            }
-        }
-        Ok(HxoValue::Object(map))
-    }
-
-    fn parse_list(&mut self) -> Result<HxoValue> {
-        let mut list = Vec::new();
-        let indent = self.get_indent();
-
-        while !self.state.cursor.is_eof() {
-            self.state.cursor.skip_spaces();
-            if self.state.cursor.is_eof() || self.state.cursor.peek() == '\n' {
-                if !self.state.cursor.is_eof() {
-                    self.state.cursor.consume(); // \n
-                }
-                continue;
-            }
-
-            let current_indent = self.get_indent();
-            if current_indent < indent {
-                break;
-            }
-
-            if self.state.cursor.peek() == '-' {
-                self.state.cursor.consume(); // -
-                self.state.cursor.skip_spaces();
-
-                let value = if self.state.cursor.peek() == '\n' || self.state.cursor.is_eof() {
-                    self.state.cursor.skip_whitespace();
-                    let next_indent = self.get_indent();
-                    if next_indent > current_indent { self.parse_map(next_indent)? } else { HxoValue::Null }
-                }
-                else {
-                    let line = self.peek_line();
-                    if line.contains(':')
-                        && !line.starts_with('"')
-                        && !line.starts_with('\'')
-                        && !line.starts_with('{')
-                        && !line.starts_with('[')
-                    {
-                        let next_indent = self.get_indent();
-                        self.parse_map(next_indent)?
-                    }
-                    else {
-                        self.parse_scalar()?
-                    }
-                };
-                list.push(value);
-            }
-            else {
-                break;
-            }
-        }
-        Ok(HxoValue::Array(list))
-    }
-
-    fn parse_scalar(&mut self) -> Result<HxoValue> {
-        let s = self.consume_value()?;
-        if s == "true" {
-            Ok(HxoValue::Bool(true))
-        }
-        else if s == "false" {
-            Ok(HxoValue::Bool(false))
-        }
-        else if s == "null" || s == "~" {
-            Ok(HxoValue::Null)
-        }
-        else if let Ok(n) = s.parse::<f64>() {
-            Ok(HxoValue::Number(n))
-        }
-        else {
-            // Handle quoted strings
-            if (s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')) {
-                Ok(HxoValue::String(s[1..s.len() - 1].to_string()))
-            }
-            else {
-                Ok(HxoValue::String(s.to_string()))
-            }
-        }
-    }
-
-    fn get_indent(&self) -> usize {
-        let mut pos = self.state.cursor.pos;
-        let mut count = 0;
-        while pos > 0 {
-            pos -= 1;
-            let c = self.state.cursor.source.as_bytes()[pos] as char;
-            if c == '\n' {
-                break;
-            }
-            if c == ' ' {
-                count += 1;
-            }
-            else {
-                count = 0;
-            }
-        }
-        count
-    }
-
-    fn peek_line(&self) -> String {
-        let mut pos = self.state.cursor.pos;
-        let start = pos;
-        while pos < self.state.cursor.source.len() && self.state.cursor.source.as_bytes()[pos] != b'\n' {
-            pos += 1;
-        }
-        self.state.cursor.source[start..pos].to_string()
-    }
-
-    fn parse_json_object(&mut self) -> Result<HxoValue> {
-        let start = self.state.cursor.pos;
-        let mut depth = 0;
-        while !self.state.cursor.is_eof() {
-            let c = self.state.cursor.peek();
-            if c == '{' {
-                depth += 1;
-            }
-            else if c == '}' {
-                depth -= 1;
-            }
-            self.state.cursor.consume();
-            if depth == 0 {
-                break;
-            }
-        }
-        Ok(HxoValue::String(self.state.cursor.source[start..self.state.cursor.pos].to_string()))
-    }
-
-    fn parse_json_array(&mut self) -> Result<HxoValue> {
-        let start = self.state.cursor.pos;
-        let mut depth = 0;
-        while !self.state.cursor.is_eof() {
-            let c = self.state.cursor.peek();
-            if c == '[' {
-                depth += 1;
-            }
-            else if c == ']' {
-                depth -= 1;
-            }
-            self.state.cursor.consume();
-            if depth == 0 {
-                break;
-            }
-        }
-        Ok(HxoValue::String(self.state.cursor.source[start..self.state.cursor.pos].to_string()))
-    }
-
-    fn consume_key(&mut self) -> Result<String> {
-        let start = self.state.cursor.pos;
-        while !self.state.cursor.is_eof() && self.state.cursor.peek() != ':' && !self.state.cursor.peek().is_whitespace() {
-            self.state.cursor.consume();
-        }
-        Ok(self.state.cursor.source[start..self.state.cursor.pos].to_string())
-    }
-
-    fn consume_value(&mut self) -> Result<String> {
-        self.state.cursor.skip_spaces();
-        let start = self.state.cursor.pos;
-        while !self.state.cursor.is_eof() && self.state.cursor.peek() != '\n' && self.state.cursor.peek() != '#' {
-            self.state.cursor.consume();
-        }
-        Ok(self.state.cursor.source[start..self.state.cursor.pos].trim().to_string())
-    }
-
-    fn skip_line(&mut self) {
-        while !self.state.cursor.is_eof() && self.state.cursor.peek() != '\n' {
-            self.state.cursor.consume();
-        }
-        if !self.state.cursor.is_eof() {
-            self.state.cursor.consume();
-        }
+/// A stream's value: `null` when empty, its document, or a list when it holds several documents.
+fn single(mut documents: Vec<HxoValue>) -> HxoValue {
+    match documents.len() {
+        0 => HxoValue::Null,
+        1 => documents.remove(0),
+        _ => HxoValue::Array(documents),
     }
 }
 
 pub fn parse(source: &str) -> Result<HxoValue> {
-    let mut state = ParseState::new(source);
-    let mut parser = YamlParserImpl { state: &mut state };
-    parser.parse_yaml()
+    Ok(single(parse_documents(source)?))
 }
 
 pub fn parse_yaml(source: &str) -> Result<HxoValue> {
-    let mut state = ParseState::new(source);
-    let mut parser = YamlParserImpl { state: &mut state };
-    parser.parse_yaml()
+    parse(source)
+}
+
+/// Every document of a `---` separated stream.
+pub fn parse_documents(source: &str) -> Result<Vec<HxoValue>> {
+    parser::parse_stream(&mut Cursor::new(source))
 }
@@ -0,0 +1,1068 @@
+//! YAML 1.2 with the core schema: block and flow collections, plain, quoted and block scalars,
+//! anchors, aliases and `<<` merge keys, tags with `%TAG` handles, and multi-document streams.
+
+use hxo_types::{Cursor, Error, HxoValue, Result, Span};
+use std::collections::HashMap;
+
+const CORE: &str = "tag:yaml.org,2002:";
+const FLOW_INDICATORS: &str = ",[]{}";
+
+/// Parses every document of a stream; anchors do not cross documents.
+pub fn parse_stream(cursor: &mut Cursor) -> Result<Vec<HxoValue>> {
+    YamlParserImpl { cursor, anchors: HashMap::new(), handles: HashMap::new() }.parse_stream()
+}
+
+/// How a scalar was written; only plain scalars resolve to numbers, booleans and null.
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Plain,
+    Quoted,
+    Block,
+}
+
+/// A node before its tag applies: scalars keep their text until the tag or the core schema types them.
+enum Node {
+    Scalar(String, Style),
+    Value(HxoValue),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Context {
+    /// After `---`, where no block collection may start on the same line.
+    Document,
+    /// After `key:`; a block sequence may sit at the key's own indentation.
+    MappingValue,
+    /// After `-`, `?` or the `:` of an explicit key, where a compact collection may follow.
+    Entry,
+}
+
+#[derive(Clone, Copy)]
+enum Chomp {
+    Strip,
+    Clip,
+    Keep,
+}
+
+#[derive(Default)]
+struct Properties {
+    tag: Option<String>,
+    anchor: Option<String>,
+}
+
+struct Key {
+    name: String,
+    span: Span,
+    /// A plain `<<`.
+    merge: bool,
+}
+
+/// A cursor state to backtrack to.
+#[derive(Clone, Copy)]
+struct Mark {
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+struct YamlParserImpl<'a, 'b> {
+    cursor: &'a mut Cursor<'b>,
+    anchors: HashMap<String, HxoValue>,
+    /// `%TAG` handles of the current document, such as `!e!`, to their prefixes.
+    handles: HashMap<String, String>,
+}
+
+impl<'a, 'b> YamlParserImpl<'a, 'b> {
+    fn parse_stream(&mut self) -> Result<Vec<HxoValue>> {
+        let mut documents = Vec::new();
+        loop {
+            self.anchors.clear();
+            self.handles.clear();
+            self.skip_blank_lines();
+            let mut directives = false;
+            while self.cursor.peek() == '%' {
+                self.parse_directive()?;
+                self.skip_blank_lines();
+                directives = true;
+            }
+            if self.cursor.is_eof() {
+                if directives {
+                    return self.error("expected `---` after the directives");
+                }
+                break;
+            }
+            let value = if self.at_marker("---") {
+                self.cursor.consume_n(3);
+                self.parse_node(-1, Context::Document)?
+            }
+            else if self.at_marker("...") {
+                self.cursor.consume_n(3);
+                self.finish_line()?;
+                continue;
+            }
+            else {
+                if directives {
+                    return self.error("expected `---` after the directives");
+                }
+                self.peek_line_indent()?;
+                self.skip_indent();
+                let start = self.cursor.position();
+                let node = self.parse_content(-1, true)?;
+                self.resolve(Properties::default(), node, self.cursor.span_from(start))?
+            };
+            documents.push(value);
+            self.skip_blank_lines();
+            if self.at_marker("...") {
+                self.cursor.consume_n(3);
+                self.finish_line()?;
+            }
+            else if !self.cursor.is_eof() && !self.at_marker("---") {
+                self.skip_indent();
+                return self.error("unexpected content after the document; check the indentation");
+            }
+        }
+        Ok(documents)
+    }
+
+    fn parse_directive(&mut self) -> Result<()> {
+        let start = self.cursor.position();
+        self.cursor.consume();
+        let name = self.cursor.consume_while(|c| !c.is_whitespace());
+        self.cursor.skip_spaces();
+        match name.as_str() {
+            "YAML" => {
+                let version = self.cursor.consume_while(|c| !c.is_whitespace());
+                if !version.starts_with("1.") {
+                    return Err(Error::parse_error(
+                        format!("unsupported YAML version `{}`", version),
+                        self.cursor.span_from(start),
+                    ));
+                }
+            }
+            "TAG" => {
+                let handle = self.cursor.consume_while(|c| !c.is_whitespace());
+                self.cursor.skip_spaces();
+                let prefix = self.cursor.consume_while(|c| !c.is_whitespace());
+                if !handle.starts_with('!') || !handle.ends_with('!') || prefix.is_empty() {
+                    return Err(Error::parse_error(
+                        "expected `%TAG !handle! prefix`".to_string(),
+                        self.cursor.span_from(start),
+                    ));
+                }
+                self.handles.insert(handle, prefix);
+            }
+            // Reserved directives are ignored
+            _ => {
+                self.cursor.consume_while(|c| c != '\n');
+            }
+        }
+        self.finish_line()
+    }
+
+    /// A node after an indicator (`-`, `:`, `?`, `---`), on the same line or indented below it. Returns at
+    /// the start of the next content line.
+    fn parse_node(&mut self, parent: i32, context: Context) -> Result<HxoValue> {
+        self.cursor.skip_spaces();
+        let start = self.cursor.position();
+        let node = if self.at_comment_or_line_end() {
+            self.finish_line()?;
+            self.skip_blank_lines();
+            match self.peek_line_indent()? {
+                Some(indent)
+                    if indent as i32 > parent
+                        || (context == Context::MappingValue
+                            && indent as i32 == parent
+                            && self.indicator_after(indent, '-')) =>
+                {
+                    self.skip_indent();
+                    self.parse_content(parent, true)?
+                }
+                _ => Node::Scalar(String::new(), Style::Plain),
+            }
+        }
+        else {
+            self.parse_content(parent, context == Context::Entry)?
+        };
+        self.resolve(Properties::default(), node, self.cursor.span_from(start))
+    }
+
+    /// The node starting at the cursor. `block` allows block collections, whose indentation is the
+    /// cursor's column; scalars continue on lines indented deeper than `parent`.
+    fn parse_content(&mut self, parent: i32, block: bool) -> Result<Node> {
+        let start = self.cursor.position();
+        let column = self.column();
+        if block {
+            if self.at_indicator('-') {
+                return Ok(Node::Value(self.parse_block_sequence(column)?));
+            }
+            if self.at_indicator('?') {
+                return Ok(Node::Value(self.parse_block_mapping(column, None)?));
+            }
+            let mark = self.mark();
+            if let Ok(Some(key)) = self.implicit_key() {
+                return Ok(Node::Value(self.parse_block_mapping(column, Some(key))?));
+            }
+            self.reset(mark);
+        }
+        let properties = self.parse_properties()?;
+        let node = if (properties.tag.is_some() || properties.anchor.is_some()) && self.at_comment_or_line_end() {
+            // Properties alone on their line belong to the node below them
+            self.finish_line()?;
+            self.skip_blank_lines();
+            match self.peek_line_indent()? {
+                Some(indent) if indent as i32 > parent => {
+                    self.skip_indent();
+                    self.parse_content(parent, true)?
+                }
+                _ => Node::Scalar(String::new(), Style::Plain),
+            }
+        }
+        else {
+            match self.cursor.peek() {
+                '|' | '>' => Node::Scalar(self.parse_block_scalar(parent)?, Style::Block),
+                '[' | '{' => {
+                    let value = self.parse_flow_collection()?;
+                    self.end_line()?;
+                    Node::Value(value)
+                }
+                '*' => {
+                    let value = self.parse_alias()?;
+                    self.end_line()?;
+                    Node::Value(value)
+                }
+                '"' | '\'' => {
+                    let text = self.parse_quoted()?;
+                    self.end_line()?;
+                    Node::Scalar(text, Style::Quoted)
+                }
+                '-' | '?' if self.at_indicator(self.cursor.peek()) => {
+                    return self.error("a block collection cannot start on this line");
+                }
+                c @ ('@' | '`') => return self.error(format!("`{}` is reserved and cannot start a plain scalar", c)),
+                _ => {
+                    let text = self.parse_plain(parent, false)?;
+                    self.end_line()?;
+                    Node::Scalar(text, Style::Plain)
+                }
+            }
+        };
+        if properties.tag.is_none() && properties.anchor.is_none() {
+            return Ok(node);
+        }
+        Ok(Node::Value(self.resolve(properties, node, self.cursor.span_from(start))?))
+    }
+
+    fn parse_block_sequence(&mut self, indent: usize) -> Result<HxoValue> {
+        let mut items = Vec::new();
+        loop {
+            self.cursor.consume();
+            items.push(self.parse_node(indent as i32, Context::Entry)?);
+            match self.peek_line_indent()? {
+                Some(next) if next == indent && self.indicator_after(next, '-') => self.skip_indent(),
+                Some(next) if next > indent => {
+                    self.skip_indent();
+                    return self.error("bad indentation; this line is deeper than the sequence entries");
+                }
+                _ => break,
+            }
+        }
+        Ok(HxoValue::Array(items))
+    }
+
+    /// A block mapping at `indent`, whose `first` key may already be read.
+    fn parse_block_mapping(&mut self, indent: usize, first: Option<Key>) -> Result<HxoValue> {
+        let mut map = HashMap::new();
+        let mut merges = Vec::new();
+        let mut first = first;
+        loop {
+            let (key, value) = match first.take() {
+                Some(key) => (key, self.parse_node(indent as i32, Context::MappingValue)?),
+                None if self.at_indicator('?') => self.parse_explicit_entry(indent)?,
+                None => match self.implicit_key()? {
+                    Some(key) => (key, self.parse_node(indent as i32, Context::MappingValue)?),
+                    None => return self.error("expected a mapping key followed by `:`"),
+                },
+            };
+            insert_entry(&mut map, &mut merges, key, value)?;
+            match self.peek_line_indent()? {
+                Some(next) if next == indent => self.skip_indent(),
+                Some(next) if next > indent => {
+                    self.skip_indent();
+                    return self.error("bad indentation; this line is deeper than the mapping keys");
+                }
+                _ => break,
+            }
+        }
+        merge(map, merges)
+    }
+
+    /// `? key` with an optional `: value` at the same indentation.
+    fn parse_explicit_entry(&mut self, indent: usize) -> Result<(Key, HxoValue)> {
+        let start = self.cursor.position();
+        self.cursor.consume();
+        let key = self.parse_node(indent as i32, Context::Entry)?;
+        let span = self.cursor.span_from(start);
+        let Some(name) = key_string(&key)
+        else {
+            return Err(Error::parse_error("only scalars can be mapping keys".to_string(), span));
+        };
+        let value = match self.peek_line_indent()? {
+            Some(next) if next == indent && self.indicator_after(next, ':') => {
+                self.skip_indent();
+                self.cursor.consume();
+                self.parse_node(indent as i32, Context::Entry)?
+            }
+            _ => HxoValue::Null,
+        };
+        Ok((Key { name, span, merge: false }, value))
+    }
+
+    /// A single-line key followed by `:`, consumed with the `:`; `None` if the cursor is not at one.
+    fn implicit_key(&mut self) -> Result<Option<Key>> {
+        let properties = self.parse_properties()?;
+        let start = self.cursor.position();
+        let line = self.cursor.line;
+        let (name, merge) = match self.cursor.peek() {
+            '*' => match key_string(&self.parse_alias()?) {
+                Some(name) => (name, false),
+                None => return Ok(None),
+            },
+            '"' | '\'' => {
+                let text = self.parse_quoted()?;
+                if self.cursor.line != line {
+                    return Ok(None);
+                }
+                (text, false)
+            }
+            '[' | '{' | '|' | '>' | '#' => return Ok(None),
+            c if self.at_indicator(c) => return Ok(None),
+            _ => {
+                let text = self.plain_segment(false);
+                if text.is_empty() {
+                    return Ok(None);
+                }
+                let merge = text == "<<";
+                (text, merge)
+            }
+        };
+        let span = self.cursor.span_from(start);
+        self.cursor.skip_spaces();
+        if !self.at_indicator(':') {
+            return Ok(None);
+        }
+        self.cursor.consume();
+        if let Some(anchor) = properties.anchor {
+            self.anchors.insert(anchor, HxoValue::String(name.clone()));
+        }
+        Ok(Some(Key { name, span, merge }))
+    }
+
+    fn parse_properties(&mut self) -> Result<Properties> {
+        let mut properties = Properties::default();
+        loop {
+            match self.cursor.peek() {
+                '&' if properties.anchor.is_none() => {
+                    self.cursor.consume();
+                    let name = self.anchor_name();
+                    if name.is_empty() {
+                        return self.error("expected an anchor name after `&`");
+                    }
+                    properties.anchor = Some(name);
+                }
+                '!' if properties.tag.is_none() => properties.tag = Some(self.parse_tag()?),
+                _ => break,
+            }
+            self.cursor.skip_spaces();
+        }
+        Ok(properties)
+    }
+
+    /// A tag, resolved to its full name: `!!int` is `tag:yaml.org,2002:int`, `!` stays non-specific.
+    fn parse_tag(&mut self) -> Result<String> {
+        let start = self.cursor.position();
+        self.cursor.consume();
+        if self.cursor.consume_str("<") {
+            let tag = self.cursor.consume_while(|c| c != '>' && c != '\n');
+            self.cursor.expect('>')?;
+            return Ok(tag);
+        }
+        let text = self.cursor.consume_while(|c| !c.is_whitespace() && !FLOW_INDICATORS.contains(c));
+        let (handle, suffix) = match text.find('!') {
+            Some(index) => (format!("!{}", &text[..=index]), &text[index + 1..]),
+            None => ("!".to_string(), text.as_str()),
+        };
+        if handle == "!" && suffix.is_empty() {
+            return Ok("!".to_string());
+        }
+        let prefix = match self.handles.get(&handle) {
+            Some(prefix) => prefix.clone(),
+            None if handle == "!!" => CORE.to_string(),
+            None if handle == "!" => "!".to_string(),
+            None => return Err(Error::parse_error(format!("undefined tag handle `{}`", handle), self.cursor.span_from(start))),
+        };
+        Ok(format!("{}{}", prefix, suffix))
+    }
+
+    fn parse_alias(&mut self) -> Result<HxoValue> {
+        let start = self.cursor.position();
+        self.cursor.consume();
+        let name = self.anchor_name();
+        match self.anchors.get(&name) {
+            Some(value) => Ok(value.clone()),
+            None => Err(Error::parse_error(format!("undefined alias `*{}`", name), self.cursor.span_from(start))),
+        }
+    }
+
+    fn anchor_name(&mut self) -> String {
+        self.cursor.consume_while(|c| !c.is_whitespace() && !FLOW_INDICATORS.contains(c))
+    }
+
+    /// A plain scalar, folding continuation lines indented deeper than `parent` (any line in flow).
+    fn parse_plain(&mut self, parent: i32, flow: bool) -> Result<String> {
+        let mut text = self.plain_segment(flow);
+        while matches!(self.cursor.peek(), '\n' | '\r') {
+            let mark = self.mark();
+            self.consume_break();
+            let mut blanks = 0;
+            loop {
+                self.cursor.consume_while(|c| c == ' ' || c == '\t');
+                if !matches!(self.cursor.peek(), '\n' | '\r') {
+                    break;
+                }
+                self.consume_break();
+                blanks += 1;
+            }
+            let ends = self.cursor.is_eof()
+                || self.cursor.peek() == '#'
+                || (!flow && self.column() as i32 <= parent)
+                || (self.column() == 0 && (self.at_marker("---") || self.at_marker("...")));
+            let segment = if ends { String::new() } else { self.plain_segment(flow) };
+            if segment.is_empty() {
+                self.reset(mark);
+                break;
+            }
+            if blanks == 0 {
+                text.push(' ');
+            }
+            else {
+                text.push_str(&"\n".repeat(blanks));
+            }
+            text.push_str(&segment);
+        }
+        Ok(text)
+    }
+
+    /// Plain text up to the end of the line, `: `, ` #`, or a flow indicator in flow context; trailing
+    /// spaces are consumed but not returned.
+    fn plain_segment(&mut self, flow: bool) -> String {
+        let start = self.cursor.pos;
+        let mut end = start;
+        while !self.cursor.is_eof() {
+            let c = self.cursor.peek();
+            let next = self.cursor.peek_n(1);
+            let stops = matches!(c, '\n' | '\r')
+                || (c == ':' && (is_blank(next) || (flow && FLOW_INDICATORS.contains(next))))
+                || (c == '#' && self.cursor.source[..self.cursor.pos].ends_with([' ', '\t']))
+                || (flow && FLOW_INDICATORS.contains(c));
+            if stops {
+                break;
+            }
+            self.cursor.consume();
+            if !matches!(c, ' ' | '\t') {
+                end = self.cursor.pos;
+            }
+        }
+        self.cursor.source[start..end].to_string()
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let start = self.cursor.position();
+        let quote = self.cursor.consume();
+        let mut text = String::new();
+        // Escaped characters before this length keep their spaces when a line folds
+        let mut kept = 0;
+        loop {
+            if self.cursor.is_eof() {
+                let kind = if quote == '"' { "double-quoted" } else { "single-quoted" };
+                return Err(Error::parse_error(format!("unclosed {} string", kind), self.cursor.span_from(start)));
+            }
+            match self.cursor.peek() {
+                '\n' | '\r' => {
+                    let end = text.trim_end_matches([' ', '\t']).len().max(kept);
+                    text.truncate(end);
+                    self.fold_break(&mut text, true);
+                }
+                '\'' if quote == '\'' => {
+                    self.cursor.consume();
+                    if self.cursor.peek() != '\'' {
+                        break;
+                    }
+                    self.cursor.consume();
+                    text.push('\'');
+                    kept = text.len();
+                }
+                '"' if quote == '"' => {
+                    self.cursor.consume();
+                    break;
+                }
+                '\\' if quote == '"' => {
+                    self.cursor.consume();
+                    if matches!(self.cursor.peek(), '\n' | '\r') {
+                        self.fold_break(&mut text, false);
+                    }
+                    else {
+                        let c = self.parse_escape()?;
+                        text.push(c);
+                    }
+                    kept = text.len();
+                }
+                c => {
+                    self.cursor.consume();
+                    text.push(c);
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    /// Folds a line break inside a quoted scalar: a space (unless escaped), or one `\n` per empty line.
+    fn fold_break(&mut self, text: &mut String, space: bool) {
+        self.consume_break();
+        let mut blanks = 0;
+        loop {
+            self.cursor.consume_while(|c| c == ' ' || c == '\t');
+            if !matches!(self.cursor.peek(), '\n' | '\r') {
+                break;
+            }
+            self.consume_break();
+            blanks += 1;
+        }
+        if blanks > 0 {
+            text.push_str(&"\n".repeat(blanks));
+        }
+        else if space {
+            text.push(' ');
+        }
+    }
+
+    /// The character of an escape, after its `\`.
+    fn parse_escape(&mut self) -> Result<char> {
+        let start = self.cursor.position();
+        let c = self.cursor.consume();
+        let escaped = match c {
+            '0' => '\0',
+            'a' => '\x07',
+            'b' => '\x08',
+            't' | '\t' => '\t',
+            'n' => '\n',
+            'v' => '\x0b',
+            'f' => '\x0c',
+            'r' => '\r',
+            'e' => '\x1b',
+            ' ' | '"' | '/' | '\\' => c,
+            'N' => '\u{85}',
+            '_' => '\u{a0}',
+            'L' => '\u{2028}',
+            'P' => '\u{2029}',
+            'x' | 'u' | 'U' => {
+                let mut code = self.hex_digits(c, start)?;
+                // A UTF-16 surrogate pair, as JSON writes characters outside the BMP
+                if (0xD800..0xDC00).contains(&code) && self.cursor.peek_str("\\u") {
+                    self.cursor.consume_n(2);
+                    let low = self.hex_digits('u', start)?;
+                    code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                }
+                match char::from_u32(code) {
+                    Some(c) => c,
+                    None => {
+                        return Err(Error::parse_error(
+                            format!("invalid character code `{:X}`", code),
+                            self.cursor.span_from(start),
+                        ));
+                    }
+                }
+            }
+            _ => return Err(Error::parse_error(format!("invalid escape `\\{}`", c), self.cursor.span_from(start))),
+        };
+        Ok(escaped)
+    }
+
+    fn hex_digits(&mut self, kind: char, start: hxo_types::Position) -> Result<u32> {
+        let count = match kind {
+            'x' => 2,
+            'u' => 4,
+            _ => 8,
+        };
+        let digits = self.cursor.consume_while(|c| c.is_ascii_hexdigit());
+        let digits: String = digits.chars().take(count).collect();
+        if digits.len() != count {
+            return Err(Error::parse_error(format!("`\\{}` needs {} hex digits", kind, count), self.cursor.span_from(start)));
+        }
+        Ok(u32::from_str_radix(&digits, 16).unwrap_or_default())
+    }
+
+    /// `|` or `>` with optional chomping (`-`, `+`) and indentation indicators, then the indented lines.
+    fn parse_block_scalar(&mut self, parent: i32) -> Result<String> {
+        let literal = self.cursor.consume() == '|';
+        let mut chomp = Chomp::Clip;
+        let mut indent = None;
+        for _ in 0..2 {
+            match self.cursor.peek() {
+                '-' => chomp = Chomp::Strip,
+                '+' => chomp = Chomp::Keep,
+                c @ '1'..='9' => indent = c.to_digit(10).map(|digits| (parent + digits as i32).max(0) as usize),
+                _ => break,
+            }
+            self.cursor.consume();
+        }
+        self.finish_line()?;
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut last_break = false;
+        while !self.cursor.is_eof() {
+            let mark = self.mark();
+            let spaces = self.cursor.consume_while(|c| c == ' ').len();
+            if self.cursor.is_eof() {
+                break;
+            }
+            if matches!(self.cursor.peek(), '\n' | '\r') {
+                last_break = self.consume_break();
+                lines.push(String::new());
+                continue;
+            }
+            // The first non-empty line sets the indentation unless an indicator did
+            let required = match indent {
+                Some(required) => required,
+                None if spaces as i32 > parent => *indent.insert(spaces),
+                None => 0,
+            };
+            if spaces < required || (spaces as i32) <= parent || (spaces == 0 && self.at_marker_line()) {
+                self.reset(mark);
+                break;
+            }
+            let mut line = " ".repeat(spaces - required);
+            line.push_str(&self.cursor.consume_while(|c| c != '\n' && c != '\r'));
+            last_break = self.consume_break();
+            lines.push(line);
+        }
+
+        let trailing = lines.iter().rev().take_while(|line| line.is_empty()).count();
+        let body = &lines[..lines.len() - trailing];
+        let mut text = String::new();
+        let mut empties = 0;
+        // Whether the previous line was more indented, which folding leaves alone
+        let mut previous: Option<bool> = None;
+        for line in body {
+            if line.is_empty() {
+                empties += 1;
+                continue;
+            }
+            let more = line.starts_with([' ', '\t']);
+            match previous {
+                None => text.push_str(&"\n".repeat(empties)),
+                Some(previous_more) if literal || more || previous_more => text.push_str(&"\n".repeat(empties + 1)),
+                Some(_) if empties == 0 => text.push(' '),
+                Some(_) => text.push_str(&"\n".repeat(empties)),
+            }
+            text.push_str(line);
+            empties = 0;
+            previous = Some(more);
+        }
+        let ends_with_break = !body.is_empty() && (trailing > 0 || last_break);
+        match chomp {
+            Chomp::Strip => {}
+            Chomp::Clip if ends_with_break => text.push('\n'),
+            Chomp::Clip => {}
+            Chomp::Keep => text.push_str(&"\n".repeat(trailing + ends_with_break as usize)),
+        }
+        Ok(text)
+    }
+
+    fn parse_flow_collection(&mut self) -> Result<HxoValue> {
+        let start = self.cursor.position();
+        let open = self.cursor.consume();
+        let close = if open == '[' { ']' } else { '}' };
+        let mut items = Vec::new();
+        let mut map = HashMap::new();
+        let mut merges = Vec::new();
+        loop {
+            self.skip_flow_space();
+            if self.cursor.is_eof() {
+                return Err(Error::parse_error(format!("unclosed `{}`", open), self.cursor.span_from(start)));
+            }
+            if self.cursor.peek() == close {
+                self.cursor.consume();
+                break;
+            }
+            let entry_start = self.cursor.position();
+            let explicit = self.at_indicator('?');
+            if explicit {
+                self.cursor.consume();
+                self.skip_flow_space();
+            }
+            let node = self.parse_flow_node()?;
+            self.skip_flow_space();
+            let value = if self.cursor.peek() == ':' {
+                self.cursor.consume();
+                self.skip_flow_space();
+                if matches!(self.cursor.peek(), ',' | ']' | '}') {
+                    Some(HxoValue::Null)
+                }
+                else {
+                    let value_start = self.cursor.position();
+                    let node = self.parse_flow_node()?;
+                    Some(self.resolve(Properties::default(), node, self.cursor.span_from(value_start))?)
+                }
+            }
+            else if explicit || open == '{' {
+                Some(HxoValue::Null)
+            }
+            else {
+                None
+            };
+            let span = self.cursor.span_from(entry_start);
+            match value {
+                None => items.push(self.resolve(Properties::default(), node, span)?),
+                Some(value) => {
+                    let merge = matches!(&node, Node::Scalar(text, Style::Plain) if text == "<<");
+                    let name = key_name(node, span)?;
+                    if open == '[' {
+                        items.push(HxoValue::Object(HashMap::from([(name, value)])));
+                    }
+                    else {
+                        insert_entry(&mut map, &mut merges, Key { name, span, merge }, value)?;
+                    }
+                }
+            }
+            self.skip_flow_space();
+            match self.cursor.peek() {
+                ',' => {
+                    self.cursor.consume();
+                }
+                c if c == close || self.cursor.is_eof() => {}
+                c => return self.error(format!("expected `,` or `{}`, found `{}`", close, c)),
+            }
+        }
+        if open == '[' { Ok(HxoValue::Array(items)) } else { merge(map, merges) }
+    }
+
+    fn parse_flow_node(&mut self) -> Result<Node> {
+        let start = self.cursor.position();
+        let properties = self.parse_properties()?;
+        self.skip_flow_space();
+        let node = match self.cursor.peek() {
+            '[' | '{' => Node::Value(self.parse_flow_collection()?),
+            '*' => Node::Value(self.parse_alias()?),
+            '"' | '\'' => Node::Scalar(self.parse_quoted()?, Style::Quoted),
+            _ => Node::Scalar(self.parse_plain(-1, true)?, Style::Plain),
+        };
+        if properties.tag.is_none() && properties.anchor.is_none() {
+            return Ok(node);
+        }
+        Ok(Node::Value(self.resolve(properties, node, self.cursor.span_from(start))?))
+    }
+
+    /// Applies a tag, or the core schema to untagged plain scalars, and records the anchor.
+    fn resolve(&mut self, properties: Properties, node: Node, span: Span) -> Result<HxoValue> {
+        let tag = properties.tag.as_deref();
+        let core = tag.and_then(|tag| tag.strip_prefix(CORE));
+        let value = match (node, core) {
+            (Node::Scalar(text, style), None) => {
+                if style == Style::Plain && tag != Some("!") {
+                    resolve_plain(&text)
+                }
+                else {
+                    HxoValue::String(text)
+                }
+            }
+            (Node::Scalar(text, _), Some(kind)) => {
+                let invalid = || Error::parse_error(format!("`{}` is not a valid `!!{}`", text, kind), span);
+                match kind {
+                    "str" | "timestamp" => HxoValue::String(text.clone()),
+                    "null" => HxoValue::Null,
+                    "bool" => match text.as_str() {
+                        "true" | "True" | "TRUE" => HxoValue::Bool(true),
+                        "false" | "False" | "FALSE" => HxoValue::Bool(false),
+                        _ => return Err(invalid()),
+                    },
+                    "int" => HxoValue::Number(parse_int(&text).ok_or_else(invalid)?),
+                    "float" => HxoValue::Number(parse_float(&text).or_else(|| parse_int(&text)).ok_or_else(invalid)?),
+                    "binary" => HxoValue::Binary(decode_base64(&text).ok_or_else(invalid)?),
+                    "map" | "set" | "seq" | "omap" | "pairs" => {
+                        return Err(Error::parse_error(format!("`!!{}` needs a collection, not a scalar", kind), span));
+                    }
+                    _ => return Err(Error::parse_error(format!("unknown tag `!!{}`", kind), span)),
+                }
+            }
+            (Node::Value(value), None) => value,
+            (Node::Value(value), Some(kind)) => match (kind, &value) {
+                ("map" | "set", HxoValue::Object(_)) | ("seq" | "omap" | "pairs", HxoValue::Array(_)) => value,
+                (_, HxoValue::Object(_) | HxoValue::Array(_)) => {
+                    return Err(Error::parse_error(format!("`!!{}` cannot tag this collection", kind), span));
+                }
+                _ => value,
+            },
+        };
+        if let Some(anchor) = properties.anchor {
+            self.anchors.insert(anchor, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Consumes the rest of the line (spaces and a comment) and the blank lines after it.
+    fn end_line(&mut self) -> Result<()> {
+        self.finish_line()?;
+        self.skip_blank_lines();
+        Ok(())
+    }
+
+    /// Consumes spaces, a comment and the line break; anything else is an error.
+    fn finish_line(&mut self) -> Result<()> {
+        self.cursor.skip_spaces();
+        if self.cursor.peek() == '#' {
+            self.cursor.consume_while(|c| c != '\n' && c != '\r');
+        }
+        match self.cursor.peek() {
+            _ if self.cursor.is_eof() => Ok(()),
+            '\n' | '\r' => {
+                self.consume_break();
+                Ok(())
+            }
+            ':' => self.error("unexpected `:`; quote text that contains `: `"),
+            c => self.error(format!("unexpected `{}`", c)),
+        }
+    }
+
+    /// Skips empty and comment-only lines, stopping at the start of the next content line.
+    fn skip_blank_lines(&mut self) {
+        while !self.cursor.is_eof() {
+            let mark = self.mark();
+            self.cursor.skip_spaces();
+            if self.cursor.peek() == '#' {
+                self.cursor.consume_while(|c| c != '\n' && c != '\r');
+            }
+            if self.cursor.is_eof() {
+                return;
+            }
+            if !self.consume_break() {
+                self.reset(mark);
+                return;
+            }
+        }
+    }
+
+    /// Flow collections ignore indentation: spaces, line breaks and comments separate their entries.
+    fn skip_flow_space(&mut self) {
+        loop {
+            self.cursor.skip_spaces();
+            if self.cursor.peek() == '#' {
+                self.cursor.consume_while(|c| c != '\n' && c != '\r');
+            }
+            if !self.consume_break() {
+                return;
+            }
+        }
+    }
+
+    fn consume_break(&mut self) -> bool {
+        let carriage = self.cursor.consume_str("\r");
+        self.cursor.consume_str("\n") || carriage
+    }
+
+    /// The indentation of the content line at the cursor, `None` at the end of the stream or document.
+    fn peek_line_indent(&mut self) -> Result<Option<usize>> {
+        if self.cursor.is_eof() || self.at_marker_line() {
+            return Ok(None);
+        }
+        let rest = &self.cursor.source[self.cursor.pos..];
+        let spaces = rest.chars().take_while(|c| *c == ' ').count();
+        if rest[spaces..].starts_with('\t') {
+            self.skip_indent();
+            return self.error("tabs are not allowed in indentation");
+        }
+        Ok(Some(spaces))
+    }
+
+    fn skip_indent(&mut self) {
+        self.cursor.consume_while(|c| c == ' ');
+    }
+
+    /// Characters between the start of the line and the cursor.
+    fn column(&self) -> usize {
+        let before = &self.cursor.source[..self.cursor.pos];
+        before[before.rfind('\n').map_or(0, |index| index + 1)..].chars().count()
+    }
+
+    fn at_comment_or_line_end(&self) -> bool {
+        self.cursor.is_eof() || matches!(self.cursor.peek(), '\n' | '\r' | '#')
+    }
+
+    /// `c` followed by a space or line break, as `-`, `?` and `:` must be to act as indicators.
+    fn at_indicator(&self, c: char) -> bool {
+        self.cursor.peek() == c && is_blank(self.cursor.peek_n(1))
+    }
+
+    /// Whether the line at the cursor continues with the indicator `c` after `indent` spaces.
+    fn indicator_after(&self, indent: usize, c: char) -> bool {
+        self.cursor.peek_n(indent) == c && is_blank(self.cursor.peek_n(indent + 1))
+    }
+
+    fn at_marker(&self, marker: &str) -> bool {
+        self.column() == 0 && self.cursor.peek_str(marker) && is_blank(self.cursor.peek_n(3))
+    }
+
+    fn at_marker_line(&self) -> bool {
+        self.at_marker("---") || self.at_marker("...")
+    }
+
+    fn mark(&self) -> Mark {
+        Mark { pos: self.cursor.pos, line: self.cursor.line, column: self.cursor.column }
+    }
+
+    fn reset(&mut self, mark: Mark) {
+        self.cursor.pos = mark.pos;
+        self.cursor.line = mark.line;
+        self.cursor.column = mark.column;
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T> {
+        Err(Error::parse_error(message.into(), self.cursor.span_at_current()))
+    }
+}
+
+fn is_blank(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\0')
+}
+
+fn insert_entry(
+    map: &mut HashMap<String, HxoValue>,
+    merges: &mut Vec<(HxoValue, Span)>,
+    key: Key,
+    value: HxoValue,
+) -> Result<()> {
+    if key.merge {
+        merges.push((value, key.span));
+        return Ok(());
+    }
+    if map.contains_key(&key.name) {
+        return Err(Error::parse_error(format!("duplicate key `{}`", key.name), key.span));
+    }
+    map.insert(key.name, value);
+    Ok(())
+}
+
+/// Applies `<<` merges: keys written in the mapping win, then earlier merged mappings over later ones.
+fn merge(mut map: HashMap<String, HxoValue>, merges: Vec<(HxoValue, Span)>) -> Result<HxoValue> {
+    for (value, span) in merges {
+        let sources = match value {
+            HxoValue::Array(items) => items,
+            value => vec![value],
+        };
+        for source in sources {
+            let HxoValue::Object(entries) = source
+            else {
+                return Err(Error::parse_error("`<<` merges a mapping or a list of mappings".to_string(), span));
+            };
+            for (key, value) in entries {
+                map.entry(key).or_insert(value);
+            }
+        }
+    }
+    Ok(HxoValue::Object(map))
+}
+
+fn key_name(node: Node, span: Span) -> Result<String> {
+    match node {
+        Node::Scalar(text, _) => Ok(text),
+        Node::Value(value) => {
+            key_string(&value).ok_or_else(|| Error::parse_error("only scalars can be mapping keys".to_string(), span))
+        }
+    }
+}
+
+fn key_string(value: &HxoValue) -> Option<String> {
+    match value {
+        HxoValue::String(text) => Some(text.clone()),
+        HxoValue::Number(number) => Some(number.to_string()),
+        HxoValue::Bool(flag) => Some(flag.to_string()),
+        HxoValue::Null => Some("null".to_string()),
+        _ => None,
+    }
+}
+
+/// The core schema: null, booleans, integers (decimal, `0o` octal, `0x` hex) and floats (with `.inf`
+/// and `.nan`); anything else is text.
+fn resolve_plain(text: &str) -> HxoValue {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => HxoValue::Null,
+        "true" | "True" | "TRUE" => HxoValue::Bool(true),
+        "false" | "False" | "FALSE" => HxoValue::Bool(false),
+        _ => match parse_int(text).or_else(|| parse_float(text)) {
+            Some(number) => HxoValue::Number(number),
+            None => HxoValue::String(text.to_string()),
+        },
+    }
+}
+
+fn parse_int(text: &str) -> Option<f64> {
+    let radix = |digits: &str, radix: u32| {
+        if digits.is_empty() { None } else { u64::from_str_radix(digits, radix).ok().map(|number| number as f64) }
+    };
+    if let Some(octal) = text.strip_prefix("0o") {
+        return radix(octal, 8);
+    }
+    if let Some(hex) = text.strip_prefix("0x") {
+        return radix(hex, 16);
+    }
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn parse_float(text: &str) -> Option<f64> {
+    match text {
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => return Some(f64::INFINITY),
+        "-.inf" | "-.Inf" | "-.INF" => return Some(f64::NEG_INFINITY),
+        ".nan" | ".NaN" | ".NAN" => return Some(f64::NAN),
+        _ => {}
+    }
+    let body = text.strip_prefix(['-', '+']).unwrap_or(text);
+    let (mantissa, exponent) = match body.find(['e', 'E']) {
+        Some(index) => (&body[..index], Some(&body[index + 1..])),
+        None => (body, None),
+    };
+    let mantissa_ok = mantissa.chars().any(|c| c.is_ascii_digit())
+        && mantissa.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && mantissa.matches('.').count() <= 1;
+    let exponent_ok = exponent.is_none_or(|exponent| {
+        let digits = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    });
+    if mantissa_ok && exponent_ok { text.parse().ok() } else { None }
+}
+
+/// Standard base64, ignoring whitespace, as `!!binary` holds it.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' => 62,
+            '/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
@@ -1,5 +1,7 @@
-use hxo_parser_yaml::{parse_router, parse_yaml};
+use hxo_parser::MetadataSerializer;
+use hxo_parser_yaml::{YamlParser, parse_documents, parse_router, parse_yaml};
 use hxo_types::HxoValue;
+use std::collections::HashMap;
 
 #[test]
 fn test_parse_basic_yaml() {
@@ -73,3 +75,132 @@ routes:
     assert_eq!(user_route.children.as_ref().unwrap().len(), 2);
     assert_eq!(user_route.children.as_ref().unwrap()[0].path, "profile");
 }
+
+#[test]
+fn test_serialize_nested_tables() {
+    let value = HxoValue::Object(HashMap::from([
+        ("title".to_string(), HxoValue::String("Welcome".to_string())),
+        ("nav".to_string(), HxoValue::Object(HashMap::from([("home".to_string(), HxoValue::String("Home".to_string()))]))),
+    ]));
+    let text = YamlParser.serialize(&value, "yaml").unwrap();
+    assert_eq!(text, "nav:\n  home: \"Home\"\ntitle: \"Welcome\"\n");
+    assert_eq!(parse_yaml(&text).unwrap(), value);
+}
+
+fn get<'a>(value: &'a HxoValue, path: &str) -> &'a HxoValue {
+    path.split('.').fold(value, |value, key| match value {
+        HxoValue::Object(map) => &map[key],
+        HxoValue::Array(items) => &items[key.parse::<usize>().unwrap()],
+        _ => panic!("`{}` is not a collection", key),
+    })
+}
+
+#[test]
+fn test_block_scalars_and_chomping() {
+    let yaml = "literal: |\n  line one\n    indented\n\n  line two\nfolded: >\n  folded\n  text\n\n  new paragraph\nstrip: |-\n  no newline\n\nkeep: |+\n  kept\n\nindicator: |2\n    two extra\nlast: done\n";
+    let value = parse_yaml(yaml).unwrap();
+    assert_eq!(get(&value, "literal").as_str(), Some("line one\n  indented\n\nline two\n"));
+    assert_eq!(get(&value, "folded").as_str(), Some("folded text\nnew paragraph\n"));
+    assert_eq!(get(&value, "strip").as_str(), Some("no newline"));
+    assert_eq!(get(&value, "keep").as_str(), Some("kept\n\n"));
+    assert_eq!(get(&value, "indicator").as_str(), Some("  two extra\n"));
+    assert_eq!(get(&value, "last").as_str(), Some("done"));
+}
+
+#[test]
+fn test_anchors_aliases_and_merge_keys() {
+    let yaml = "
+base: &base
+  layout: default
+  auth: false
+extra: &extra { auth: true, cache: 60 }
+page:
+  <<: [*base, *extra]
+  auth: required
+copy: *base
+names:
+  - &first Ada
+  - *first
+";
+    let value = parse_yaml(yaml).unwrap();
+    assert_eq!(get(&value, "page.layout").as_str(), Some("default"));
+    assert_eq!(get(&value, "page.auth").as_str(), Some("required"));
+    assert_eq!(get(&value, "page.cache").as_number(), Some(60.0));
+    assert_eq!(get(&value, "copy"), get(&value, "base"));
+    assert_eq!(get(&value, "names.1").as_str(), Some("Ada"));
+}
+
+#[test]
+fn test_core_schema_tags_and_quoting() {
+    let yaml = r#"
+octal: 0o17
+hex: 0xFF
+negative: -12
+float: 1.5e3
+infinity: -.inf
+nothing: ~
+empty:
+yes: True
+text: !!str 123
+number: !!float "2"
+version: "1.0"
+single: 'it''s'
+escapes: "tab\there\u00e9 \x41"
+folded: "one
+  two
+
+  three"
+comment: value # not part of it
+hash: a#b
+flow: [1, "two", {three: 3}]
+"#;
+    let value = parse_yaml(yaml).unwrap();
+    assert_eq!(get(&value, "octal").as_number(), Some(15.0));
+    assert_eq!(get(&value, "hex").as_number(), Some(255.0));
+    assert_eq!(get(&value, "negative").as_number(), Some(-12.0));
+    assert_eq!(get(&value, "float").as_number(), Some(1500.0));
+    assert_eq!(get(&value, "infinity").as_number(), Some(f64::NEG_INFINITY));
+    assert_eq!(get(&value, "nothing"), &HxoValue::Null);
+    assert_eq!(get(&value, "empty"), &HxoValue::Null);
+    assert_eq!(get(&value, "yes").as_bool(), Some(true));
+    assert_eq!(get(&value, "text").as_str(), Some("123"));
+    assert_eq!(get(&value, "number").as_number(), Some(2.0));
+    assert_eq!(get(&value, "version").as_str(), Some("1.0"));
+    assert_eq!(get(&value, "single").as_str(), Some("it's"));
+    assert_eq!(get(&value, "escapes").as_str(), Some("tab\there\u{e9} A"));
+    assert_eq!(get(&value, "folded").as_str(), Some("one two\nthree"));
+    assert_eq!(get(&value, "comment").as_str(), Some("value"));
+    assert_eq!(get(&value, "hash").as_str(), Some("a#b"));
+    assert_eq!(get(&value, "flow.1").as_str(), Some("two"));
+    assert_eq!(get(&value, "flow.2.three").as_number(), Some(3.0));
+}
+
+#[test]
+fn test_multi_document_stream() {
+    let yaml = "%YAML 1.2\n---\nname: first\n...\n--- # second\n- a\n- b: c\n  d: e\n---\nplain text\n  folded on\n";
+    let documents = parse_documents(yaml).unwrap();
+    assert_eq!(documents.len(), 3);
+    assert_eq!(get(&documents[0], "name").as_str(), Some("first"));
+    assert_eq!(get(&documents[1], "1.d").as_str(), Some("e"));
+    assert_eq!(documents[2].as_str(), Some("plain text folded on"));
+    assert!(matches!(parse_yaml(yaml).unwrap(), HxoValue::Array(items) if items.len() == 3));
+}
+
+#[test]
+fn test_errors_carry_spans() {
+    let error = parse_yaml("a: 1\nb: *missing\n").unwrap_err();
+    assert!(error.to_string().contains("undefined alias `*missing`"), "{}", error);
+    assert_eq!(error.span().start.line, 2);
+    assert_eq!(error.span().start.column, 4);
+
+    let error = parse_yaml("a: 1\na: 2\n").unwrap_err();
+    assert!(error.to_string().contains("duplicate key `a`"), "{}", error);
+    assert_eq!(error.span().start.line, 2);
+
+    let error = parse_yaml("a:\n    b: 1\n  c: 2\n").unwrap_err();
+    assert!(error.to_string().contains("bad indentation"), "{}", error);
+    assert_eq!(error.span().start.line, 3);
+
+    let error = parse_yaml("a: \"open\n").unwrap_err();
+    assert!(error.to_string().contains("unclosed double-quoted string"), "{}", error);
+}
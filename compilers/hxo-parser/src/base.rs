@@ -3,19 +3,26 @@ use std::collections::HashMap;
 
 pub struct ParseState<'a> {
     pub cursor: Cursor<'a>,
+    /// Locale of the messages being parsed, when known (`<i18n locale>`, `en.ftl`).
+    pub locale: Option<String>,
 }
 
 impl<'a> ParseState<'a> {
     pub fn new(source: &'a str) -> Self {
-        Self { cursor: Cursor::new(source) }
+        Self { cursor: Cursor::new(source), locale: None }
     }
 
     pub fn new_with_pos(source: &'a str, pos: Position) -> Self {
-        Self { cursor: Cursor::with_position(source, pos) }
+        Self { cursor: Cursor::with_position(source, pos), locale: None }
     }
 
     pub fn with_cursor(cursor: Cursor<'a>) -> Self {
-        Self { cursor }
+        Self { cursor, locale: None }
+    }
+
+    pub fn with_locale(mut self, locale: Option<&str>) -> Self {
+        self.locale = locale.map(str::to_string);
+        self
     }
 
     pub fn unexpected_char(&self) -> Error {
@@ -0,0 +1,47 @@
+use hxo_types::{Cursor, Position};
+
+/// A `---` (YAML) or `+++` (TOML) block leading a Markdown template.
+pub struct FrontMatter<'a> {
+    pub lang: &'static str,
+    pub content: &'a str,
+    pub start: Position,
+    /// Byte offset in the template where the Markdown starts, after the closing fence.
+    pub body_offset: usize,
+    pub body_start: Position,
+}
+
+/// Splits front matter off the start of a Markdown template. Without a closing fence there is none,
+/// and a leading `---` stays a thematic break.
+pub fn split(template: &str, start: Position) -> Option<FrontMatter<'_>> {
+    let mut cursor = Cursor::with_sliced_source(template, start);
+    cursor.skip_whitespace();
+    let (fence, lang) = if cursor.peek_str("---") { ("---", "yaml") } else { ("+++", "toml") };
+    if !cursor.consume_str(fence) {
+        return None;
+    }
+    cursor.skip_spaces();
+    cursor.consume_str("\r");
+    if !cursor.consume_str("\n") {
+        return None;
+    }
+
+    let content_start = cursor.pos;
+    let content_position = cursor.position();
+    while !cursor.is_eof() {
+        let line_start = cursor.pos;
+        let line = cursor.consume_while(|c| c != '\n');
+        let line = line.trim_end();
+        if line == fence || (lang == "yaml" && line == "...") {
+            cursor.consume();
+            return Some(FrontMatter {
+                lang,
+                content: &template[content_start..line_start],
+                start: content_position,
+                body_offset: cursor.pos,
+                body_start: cursor.position(),
+            });
+        }
+        cursor.consume();
+    }
+    None
+}
@@ -0,0 +1,209 @@
+//! Message tables of `<i18n>` blocks and project locale files. Messages are flattened to dotted keys
+//! (`{ "nav": { "home": "Home" } }` becomes `nav.home`) and merged per locale, later sources winning.
+
+use crate::{ParseState, ParserRegistry};
+use hxo_ir::I18nMessage;
+use hxo_types::{Cursor, Error, HxoValue, Position, Result, Span};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Locale to message key to message.
+pub type Messages = HashMap<String, HashMap<String, I18nMessage>>;
+
+/// The metadata parser for a locale file extension.
+pub fn lang_for_extension(extension: &str) -> Option<&'static str> {
+    match extension {
+        "json" => Some("json"),
+        "yaml" | "yml" => Some("yaml"),
+        "toml" => Some("toml"),
+        "ftl" => Some("ftl"),
+        "properties" => Some("properties"),
+        _ => None,
+    }
+}
+
+/// Parses `source` with the metadata parser registered for `lang`.
+pub fn parse_i18n(registry: &ParserRegistry, source: &str, lang: &str) -> Result<HashMap<String, HxoValue>> {
+    parse_value(registry, Cursor::new(source), lang, None, Span::default())
+}
+
+/// Parses with the metadata parser for `lang`; `locale` lets Fluent compile plural rules for it.
+fn parse_value(
+    registry: &ParserRegistry,
+    cursor: Cursor,
+    lang: &str,
+    locale: Option<&str>,
+    span: Span,
+) -> Result<HashMap<String, HxoValue>> {
+    let Some(parser) = registry.get_metadata_parser(lang)
+    else {
+        return Err(Error::unsupported_i18n_format(lang.to_string(), span));
+    };
+    match parser.parse(&mut ParseState::with_cursor(cursor).with_locale(locale), lang)? {
+        HxoValue::Object(map) => Ok(map),
+        _ => Err(Error::invalid_i18n(format!("{}: expected a table of messages", lang), span)),
+    }
+}
+
+/// An `<i18n>` block, whose messages are in `content` starting at `start`.
+///
+/// With a `locale` attribute the block holds that locale's messages; otherwise its top-level keys are
+/// locales (`en.nav.home = Home` in `.properties`). A `src` file, relative to `base_dir`, is read first
+/// and the block's own content overrides it. `lang` defaults to the extension of `src`, then to JSON.
+pub fn parse_block(
+    registry: &ParserRegistry,
+    content: &str,
+    start: Position,
+    attributes: &HashMap<String, String>,
+    base_dir: &Path,
+    span: Span,
+) -> Result<Messages> {
+    let src = attributes.get("src");
+    let lang = match attributes.get("lang") {
+        Some(lang) => lang.as_str(),
+        None => src
+            .and_then(|src| Path::new(src).extension())
+            .and_then(|extension| lang_for_extension(&extension.to_string_lossy()))
+            .unwrap_or("json"),
+    };
+    let locale = attributes.get("locale").map(String::as_str);
+    let mut messages = HashMap::new();
+    if let Some(src) = src {
+        let path = base_dir.join(src);
+        let source = read(&path, span)?;
+        flatten_messages(&parse_value(registry, Cursor::new(&source), lang, locale, span)?, lang, &mut messages);
+    }
+    if !content.trim().is_empty() {
+        let value = parse_value(registry, Cursor::with_sliced_source(content, start), lang, locale, span)?;
+        flatten_messages(&value, lang, &mut messages);
+    }
+    let messages = match locale {
+        Some(locale) => HashMap::from([(locale.to_string(), messages)]),
+        None => split_locales(messages, lang, span)?,
+    };
+    compile_icu(messages, lang, span)
+}
+
+/// Reads the locale files directly in `dir`, each named after its locale (`en.json`, `zh-CN.ftl`).
+/// Files of the same locale are merged in name order.
+pub fn load_locale_dir(registry: &ParserRegistry, dir: &Path) -> Result<Messages> {
+    let mut paths =
+        fs::read_dir(dir)?.map(|entry| entry.map(|entry| entry.path())).collect::<std::io::Result<Vec<PathBuf>>>()?;
+    paths.sort();
+    let mut all = Messages::new();
+    for path in paths {
+        let lang = path.extension().and_then(|extension| lang_for_extension(&extension.to_string_lossy()));
+        let (Some(lang), Some(locale)) = (lang, path.file_stem())
+        else {
+            continue;
+        };
+        let (locale, span) = (locale.to_string_lossy().into_owned(), Span::default());
+        let value = parse_value(registry, Cursor::new(&read(&path, span)?), lang, Some(&locale), span)?;
+        let mut messages = HashMap::new();
+        flatten_messages(&value, lang, &mut messages);
+        merge(&mut all, compile_icu(HashMap::from([(locale, messages)]), lang, span)?);
+    }
+    Ok(all)
+}
+
+/// The messages of one locale file, flattened to dotted keys but not compiled, as catalog tools see them.
+pub fn parse_catalog(registry: &ParserRegistry, source: &str, lang: &str) -> Result<HashMap<String, I18nMessage>> {
+    let mut messages = HashMap::new();
+    flatten_messages(&parse_value(registry, Cursor::new(source), lang, None, Span::default())?, lang, &mut messages);
+    Ok(messages)
+}
+
+/// Adds `from` to `into`; a message defined in both takes the value from `from`.
+pub fn merge(into: &mut Messages, from: Messages) {
+    for (locale, messages) in from {
+        into.entry(locale).or_default().extend(messages);
+    }
+}
+
+/// Flattens nested tables and lists to dotted keys. Numbers and booleans become text, raw code (from
+/// Fluent) a formatter.
+pub fn flatten(value: &HxoValue, prefix: &str, out: &mut HashMap<String, I18nMessage>) {
+    let key = |name: &str| if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+    match value {
+        HxoValue::Object(map) => {
+            for (name, value) in map {
+                flatten(value, &key(name), out);
+            }
+        }
+        HxoValue::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten(value, &key(&index.to_string()), out);
+            }
+        }
+        HxoValue::String(text) => {
+            out.insert(prefix.to_string(), I18nMessage::Text(text.clone()));
+        }
+        HxoValue::Raw(code) => {
+            out.insert(prefix.to_string(), I18nMessage::Formatter(code.clone()));
+        }
+        HxoValue::Number(number) => {
+            out.insert(prefix.to_string(), I18nMessage::Text(number.to_string()));
+        }
+        HxoValue::Bool(flag) => {
+            out.insert(prefix.to_string(), I18nMessage::Text(flag.to_string()));
+        }
+        _ => {}
+    }
+}
+
+/// Fluent messages with attributes come as `{ val, attribute... }`: the value is the message itself
+/// and attributes are `id.attribute`.
+fn flatten_messages(map: &HashMap<String, HxoValue>, lang: &str, out: &mut HashMap<String, I18nMessage>) {
+    for (id, value) in map {
+        match value {
+            HxoValue::Object(message) if matches!(lang, "ftl" | "fluent") => {
+                for (attribute, value) in message {
+                    let key = if attribute == "val" { id.clone() } else { format!("{}.{}", id, attribute) };
+                    flatten(value, &key, out);
+                }
+            }
+            _ => flatten(value, id, out),
+        }
+    }
+}
+
+fn split_locales(messages: HashMap<String, I18nMessage>, lang: &str, span: Span) -> Result<Messages> {
+    let mut locales = Messages::new();
+    for (key, message) in messages {
+        let Some((locale, key)) = key.split_once('.')
+        else {
+            let reason = format!("{}: message `{}` is not under a locale; add a `locale` attribute to the block", lang, key);
+            return Err(Error::invalid_i18n(reason, span));
+        };
+        locales.entry(locale.to_string()).or_default().insert(key.to_string(), message);
+    }
+    Ok(locales)
+}
+
+/// Text messages of every format but Fluent are ICU MessageFormat, compiled for their locale.
+fn compile_icu(messages: Messages, lang: &str, span: Span) -> Result<Messages> {
+    if matches!(lang, "ftl" | "fluent") {
+        return Ok(messages);
+    }
+    let mut compiled = Messages::new();
+    for (locale, table) in messages {
+        let mut out = HashMap::new();
+        for (key, message) in table {
+            let message = match message {
+                I18nMessage::Text(text) => hxo_parser_icu::compile_message(&text, &locale)
+                    .map_err(|err| Error::invalid_i18n(format!("message `{}` ({}): {}", key, locale, err), span))?,
+                formatter => formatter,
+            };
+            out.insert(key, message);
+        }
+        compiled.insert(locale, out);
+    }
+    Ok(compiled)
+}
+
+fn read(path: &Path, span: Span) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| Error::parse_error(format!("cannot read {}: {}", path.display(), err), span))
+}
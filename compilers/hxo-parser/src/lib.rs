@@ -1,31 +1,43 @@
-use hxo_ir::{CustomBlockIR, IRModule, StyleIR, TemplateIR};
+use hxo_ir::{CustomBlockIR, IRModule, StyleIR, TemplateIR, TocEntry};
 use hxo_types::{HxoValue, Result, Span};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 mod base;
+mod front_matter;
+pub mod i18n;
 mod registry;
 
 pub use base::ParseState;
 pub use hxo_types::Cursor;
-pub use registry::{MetadataParser, ParserRegistry, ScriptParser, StyleParser, TemplateParser};
+pub use i18n::parse_i18n;
+pub use registry::{MetadataParser, MetadataSerializer, ParserRegistry, ScriptParser, StyleParser, TemplateParser};
 
 pub struct Parser<'a> {
     name: String,
     state: ParseState<'a>,
     registry: Arc<ParserRegistry>,
+    base_dir: PathBuf,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(name: String, source: &'a str, registry: Arc<ParserRegistry>) -> Self {
-        Self { name, state: ParseState::new(source), registry }
+        Self { name, state: ParseState::new(source), registry, base_dir: PathBuf::from(".") }
+    }
+
+    /// Directory that `src` attributes are relative to, usually the component's own.
+    pub fn with_base_dir(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = base_dir.into();
+        self
     }
 
     pub fn parse_all(&mut self) -> Result<IRModule> {
         let mut template_nodes = Vec::new();
+        let mut toc = Vec::new();
         let mut script = None;
         let mut ir_styles = Vec::new();
         let mut metadata = HashMap::new();
         let mut custom_blocks = Vec::new();
+        let mut messages = i18n::Messages::new();
 
         let state = &mut self.state;
 
@@ -42,12 +54,30 @@ impl<'a> Parser<'a> {
                 while !state.cursor.is_eof() && !state.cursor.peek_str("</template>") {
                     state.cursor.consume();
                 }
-                let content = &state.cursor.source[start_offset..state.cursor.pos];
+                let mut content = &state.cursor.source[start_offset..state.cursor.pos];
+                let mut start_pos = start_pos;
                 state.cursor.consume_str("</template>");
 
+                if lang == "md" || lang == "markdown" {
+                    if let Some(front) = front_matter::split(content, start_pos) {
+                        if let Some(metadata_parser) = self.registry.get_metadata_parser(front.lang) {
+                            let mut sub_state = ParseState::with_cursor(Cursor::with_sliced_source(front.content, front.start));
+                            if let HxoValue::Object(map) = metadata_parser.parse(&mut sub_state, front.lang)? {
+                                metadata.extend(map);
+                            }
+                        }
+                        content = &content[front.body_offset..];
+                        start_pos = front.body_start;
+                    }
+                }
+
                 if let Some(template_parser) = self.registry.get_template_parser(&lang) {
                     let mut sub_state = ParseState::with_cursor(Cursor::with_sliced_source(content, start_pos));
-                    template_nodes.extend(template_parser.parse(&mut sub_state, &lang)?);
+                    let nodes = template_parser.parse(&mut sub_state, &lang)?;
+                    if lang == "md" || lang == "markdown" {
+                        toc.extend(TocEntry::outline(&nodes));
+                    }
+                    template_nodes.extend(nodes);
                 }
             }
             else if state.cursor.peek_str("<script") {
@@ -75,6 +105,7 @@ impl<'a> Parser<'a> {
                 state.cursor.expect('>')?;
                 let lang = attrs.get("lang").cloned().unwrap_or_else(|| "css".to_string());
                 let scoped = attrs.contains_key("scoped");
+                let module = attrs.get("module").map(|name| if name == "true" { "$style".to_string() } else { name.clone() });
 
                 let start_pos = state.cursor.position();
                 let start_offset = state.cursor.pos;
@@ -88,7 +119,16 @@ impl<'a> Parser<'a> {
                 if let Some(style_parser) = self.registry.get_style_parser(&lang) {
                     let mut sub_state = ParseState::with_cursor(Cursor::with_sliced_source(content, start_pos));
                     if let Ok(code) = style_parser.parse(&mut sub_state, &lang) {
-                        ir_styles.push(StyleIR { code, lang, scoped, span });
+                        let mut sub_state = ParseState::with_cursor(Cursor::with_sliced_source(content, start_pos));
+                        let stylesheet = match style_parser.parse_stylesheet(&mut sub_state, &lang) {
+                            Ok(Some(stylesheet)) => Some(stylesheet),
+                            // Spans in generated CSS would point at the wrong lines, so they are dropped
+                            _ => self.registry.get_style_parser("css").and_then(|css| {
+                                let mut generated = ParseState::new(&code);
+                                css.parse_stylesheet(&mut generated, "css").ok().flatten().map(|s| s.without_spans())
+                            }),
+                        };
+                        ir_styles.push(StyleIR { code, lang, scoped, module, classes: HashMap::new(), stylesheet, span });
                     }
                 }
             }
@@ -113,6 +153,24 @@ impl<'a> Parser<'a> {
                     }
                 }
             }
+            else if state.cursor.peek_str("<i18n") {
+                state.cursor.consume_str("<i18n");
+                let attrs = state.parse_tag_attributes();
+                state.cursor.expect('>')?;
+
+                let start_pos = state.cursor.position();
+                let start_offset = state.cursor.pos;
+                while !state.cursor.is_eof() && !state.cursor.peek_str("</i18n>") {
+                    state.cursor.consume();
+                }
+                let content = &state.cursor.source[start_offset..state.cursor.pos];
+                let span = state.cursor.span_from(start_pos);
+                state.cursor.consume_str("</i18n>");
+
+                // Later blocks override earlier ones
+                let block = i18n::parse_block(&self.registry, content, start_pos, &attrs, &self.base_dir, span)?;
+                i18n::merge(&mut messages, block);
+            }
             else if state.cursor.peek_str("<") {
                 // Handle custom blocks
                 state.cursor.consume(); // consume '<'
@@ -146,25 +204,13 @@ impl<'a> Parser<'a> {
                 None
             }
             else {
-                Some(TemplateIR { nodes: template_nodes, span: Span::default() })
+                Some(TemplateIR { nodes: template_nodes, toc, span: Span::default() })
             },
             styles: ir_styles,
-            i18n: None,
+            i18n: if messages.is_empty() { None } else { Some(messages) },
             wasm: Vec::new(),
             custom_blocks,
             span: Span::default(),
         })
     }
 }
-
-pub fn parse_i18n(source: &str, lang: &str) -> Result<HashMap<String, HxoValue>> {
-    let registry = ParserRegistry::new();
-    if let Some(parser) = registry.get_metadata_parser(lang) {
-        let mut state = ParseState::new(source);
-        let val = parser.parse(&mut state, lang)?;
-        if let HxoValue::Object(map) = val {
-            return Ok(map);
-        }
-    }
-    Err(hxo_types::Error::parse_error(format!("Unsupported i18n lang: {}", lang), Span::default()))
-}
@@ -1,5 +1,5 @@
 use crate::ParseState;
-use hxo_ir::{JsProgram, TemplateNodeIR};
+use hxo_ir::{JsProgram, Stylesheet, TemplateNodeIR};
 use hxo_types::{HxoValue, Result};
 use std::{collections::HashMap, sync::Arc};
 
@@ -13,18 +13,36 @@ pub trait ScriptParser: Send + Sync {
 
 pub trait StyleParser: Send + Sync {
     fn parse(&self, state: &mut ParseState, lang: &str) -> Result<String>;
+
+    /// Parses the block straight into a CSS AST whose spans point into the component file. Parsers
+    /// that compile another language return `None` and their output is read by the `css` parser.
+    fn parse_stylesheet(&self, _state: &mut ParseState, _lang: &str) -> Result<Option<Stylesheet>> {
+        Ok(None)
+    }
 }
 
 pub trait MetadataParser: Send + Sync {
     fn parse(&self, state: &mut ParseState, lang: &str) -> Result<HxoValue>;
 }
 
-#[derive(Default)]
+/// Writes a table in a metadata format, such that its `MetadataParser` reads the same table back.
+pub trait MetadataSerializer: Send + Sync {
+    fn serialize(&self, value: &HxoValue, lang: &str) -> Result<String>;
+
+    /// Whether the output can be appended to an existing file of the format, keeping its comments and
+    /// layout, instead of rewriting the whole file.
+    fn appends(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Default, Clone)]
 pub struct ParserRegistry {
     template_parsers: HashMap<String, Arc<dyn TemplateParser>>,
     script_parsers: HashMap<String, Arc<dyn ScriptParser>>,
     style_parsers: HashMap<String, Arc<dyn StyleParser>>,
     metadata_parsers: HashMap<String, Arc<dyn MetadataParser>>,
+    metadata_serializers: HashMap<String, Arc<dyn MetadataSerializer>>,
 }
 
 impl ParserRegistry {
@@ -48,6 +66,10 @@ impl ParserRegistry {
         self.metadata_parsers.insert(lang.to_string(), parser);
     }
 
+    pub fn register_metadata_serializer(&mut self, lang: &str, serializer: Arc<dyn MetadataSerializer>) {
+        self.metadata_serializers.insert(lang.to_string(), serializer);
+    }
+
     pub fn get_template_parser(&self, lang: &str) -> Option<Arc<dyn TemplateParser>> {
         self.template_parsers.get(lang).cloned()
     }
@@ -63,4 +85,8 @@ impl ParserRegistry {
     pub fn get_metadata_parser(&self, lang: &str) -> Option<Arc<dyn MetadataParser>> {
         self.metadata_parsers.get(lang).cloned()
     }
+
+    pub fn get_metadata_serializer(&self, lang: &str) -> Option<Arc<dyn MetadataSerializer>> {
+        self.metadata_serializers.get(lang).cloned()
+    }
 }
@@ -1,5 +1,5 @@
 use hxo_ir::TemplateNodeIR;
-use hxo_parser::{MetadataParser, ParseState, Parser, ParserRegistry, TemplateParser};
+use hxo_parser::{MetadataParser, ParseState, Parser, ParserRegistry, TemplateParser, parse_i18n};
 use hxo_types::{HxoValue, Result, Span};
 use std::{collections::HashMap, sync::Arc};
 
@@ -49,8 +49,6 @@ const x = 1;
 
 #[test]
 fn test_parse_i18n_json() {
-    // We need to modify parse_i18n to support registry or register it globally
-    // For now, let's mock it by calling a modified version or just testing the registry
     let mut registry = ParserRegistry::new();
     registry.register_metadata_parser("json", Arc::new(MockMetadataParser));
 
@@ -59,15 +57,68 @@ fn test_parse_i18n_json() {
         "zh": { "hello": "你好" }
     }"#;
 
-    let mut state = ParseState::new(source);
-    let parser = registry.get_metadata_parser("json").unwrap();
-    let val = parser.parse(&mut state, "json").unwrap();
+    let i18n = parse_i18n(&registry, source, "json").unwrap();
+    assert_eq!(i18n.get("en").unwrap().get("hello").unwrap().as_str().unwrap(), "Hello");
+    assert_eq!(i18n.get("zh").unwrap().get("hello").unwrap().as_str().unwrap(), "你好");
+    assert!(parse_i18n(&registry, source, "xliff").is_err());
+}
+
+#[test]
+fn test_i18n_blocks_fill_module_messages() {
+    let source = r#"
+<i18n lang="json">{}</i18n>
+<i18n lang="json" locale="fr">{}</i18n>
+<docs>Not a message</docs>
+"#;
+    let mut registry = ParserRegistry::new();
+    registry.register_metadata_parser("json", Arc::new(MockMetadataParser));
+    let ir = Parser::new("Test".to_string(), source, Arc::new(registry)).parse_all().unwrap();
 
-    if let HxoValue::Object(i18n) = val {
-        assert_eq!(i18n.get("en").unwrap().get("hello").unwrap().as_str().unwrap(), "Hello");
-        assert_eq!(i18n.get("zh").unwrap().get("hello").unwrap().as_str().unwrap(), "你好");
+    let i18n = ir.i18n.unwrap();
+    assert_eq!(i18n["en"]["hello"], "Hello");
+    assert_eq!(i18n["zh"]["hello"], "你好");
+    assert_eq!(i18n["fr"]["en.hello"], "Hello");
+    assert_eq!(ir.custom_blocks.len(), 1);
+}
+
+/// Echoes the source it was handed, starting where the parser put it.
+struct SourceParser;
+impl TemplateParser for SourceParser {
+    fn parse(&self, state: &mut ParseState, _lang: &str) -> Result<Vec<TemplateNodeIR>> {
+        let start = state.cursor.position();
+        let text = state.cursor.consume_while(|_| true);
+        Ok(vec![TemplateNodeIR::Text(text, Span { start, end: state.cursor.position() })])
     }
-    else {
-        panic!("Expected object");
+}
+impl MetadataParser for SourceParser {
+    fn parse(&self, state: &mut ParseState, lang: &str) -> Result<HxoValue> {
+        let source = state.cursor.consume_while(|_| true);
+        Ok(HxoValue::Object(HashMap::from([(lang.to_string(), HxoValue::String(source))])))
     }
 }
+
+#[test]
+fn test_markdown_front_matter_fills_metadata() {
+    let mut registry = ParserRegistry::new();
+    registry.register_template_parser("md", Arc::new(SourceParser));
+    registry.register_metadata_parser("yaml", Arc::new(SourceParser));
+    registry.register_metadata_parser("toml", Arc::new(SourceParser));
+    let registry = Arc::new(registry);
+    let parse = |source: &str| Parser::new("Doc".to_string(), source, registry.clone()).parse_all().unwrap();
+
+    let ir = parse("<template lang=\"md\">\n---\ntitle: Guide\n---\n# Intro\n</template>");
+    assert_eq!(ir.metadata["yaml"], HxoValue::String("title: Guide\n".to_string()));
+    let TemplateNodeIR::Text(body, span) = &ir.template.unwrap().nodes[0]
+    else {
+        panic!("expected the markdown body");
+    };
+    assert_eq!(body, "# Intro\n");
+    assert_eq!((span.start.line, span.start.column), (5, 1));
+
+    let ir = parse("<template lang=\"md\">\n+++\ntitle = \"Guide\"\n+++\n# Intro\n</template>");
+    assert_eq!(ir.metadata["toml"], HxoValue::String("title = \"Guide\"\n".to_string()));
+
+    // Without a closing fence the `---` is a thematic break.
+    let ir = parse("<template lang=\"md\">\n---\n# Intro\n</template>");
+    assert!(ir.metadata.is_empty());
+}
@@ -4,6 +4,8 @@ use std::collections::HashSet;
 
 pub struct ScriptMetadata {
     pub signals: HashSet<String>,
+    /// Subset of `signals` declared through `createComputed`.
+    pub computed: HashSet<String>,
     pub props: HashSet<String>,
     pub emits: HashSet<String>,
 }
@@ -18,6 +20,7 @@ impl ScriptAnalyzer {
 
     pub fn analyze(&self, program: &JsProgram) -> Result<ScriptMetadata> {
         let mut signals = HashSet::new();
+        let mut computed = HashSet::new();
         let props = HashSet::new();
         let emits = HashSet::new();
 
@@ -45,12 +48,15 @@ impl ScriptAnalyzer {
                             if name == "createComputed" || name == "createSignal" {
                                 signals.insert(id.clone());
                             }
+                            if name == "createComputed" {
+                                computed.insert(id.clone());
+                            }
                         }
                     }
                 }
             }
         }
 
-        Ok(ScriptMetadata { signals, props, emits })
+        Ok(ScriptMetadata { signals, computed, props, emits })
     }
 }
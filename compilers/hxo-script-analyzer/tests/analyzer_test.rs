@@ -36,4 +36,6 @@ fn test_analyze_signals() {
     assert!(meta.signals.contains("count"));
     assert!(meta.signals.contains("doubleCount"));
     assert_eq!(meta.signals.len(), 2);
+    assert!(meta.computed.contains("doubleCount"));
+    assert!(!meta.computed.contains("count"));
 }
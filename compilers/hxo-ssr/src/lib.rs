@@ -1,5 +1,5 @@
 use hxo_ir::{IRModule, TemplateNodeIR};
-use hxo_target_js::JsWriter;
+use hxo_target_js::{JsWriter, css_module_literal, write_toc};
 use hxo_types::Result;
 use std::collections::HashSet;
 
@@ -43,6 +43,16 @@ impl SsrBackend {
     }
 
     fn generate_ssr_body(&self, ir: &IRModule, writer: &mut JsWriter, _used_core: &mut HashSet<String>) -> Result<()> {
+        // CSS Modules: template expressions such as `$style.card` read these directly
+        let css_modules = ir.css_modules();
+        let module_bindings: Vec<&str> = css_modules.iter().map(|(binding, _)| binding.as_str()).collect();
+        for (binding, classes) in &css_modules {
+            writer.write_line(&format!("export const {} = {};", binding, css_module_literal(classes)));
+        }
+        if !css_modules.is_empty() {
+            writer.newline();
+        }
+
         writer.write_block("export function render(ctx)", |writer| {
             writer.write("let html = '';");
             writer.newline();
@@ -50,16 +60,17 @@ impl SsrBackend {
             if let Some(template) = &ir.template {
                 let mut node_index = 0;
                 for node in &template.nodes {
-                    Self::generate_node_ssr(node, writer, &mut node_index);
+                    Self::generate_node_ssr(node, writer, &mut node_index, &module_bindings);
                 }
             }
 
             writer.write_line("return html;");
         });
+        write_toc(writer, ir.toc());
         Ok(())
     }
 
-    fn generate_node_ssr(node: &TemplateNodeIR, writer: &mut JsWriter, node_index: &mut usize) {
+    fn generate_node_ssr(node: &TemplateNodeIR, writer: &mut JsWriter, node_index: &mut usize, module_bindings: &[&str]) {
         match node {
             TemplateNodeIR::Element(el) => {
                 let current_index = *node_index;
@@ -73,19 +84,23 @@ impl SsrBackend {
                 }
 
                 for attr in &el.attributes {
-                    if !attr.is_directive {
-                        if attr.is_dynamic {
-                            start_tag.push_str(&format!(
-                                " {}=\"' + ({}) + '\"",
-                                attr.name,
-                                attr.value.as_deref().unwrap_or("")
-                            ));
+                    if attr.is_directive {
+                        // Only `:class` bindings that read a CSS Module render here, from the exported classes
+                        let value = attr.value.as_deref().filter(|value| reads_css_module(value, module_bindings));
+                        if let (":class", Some(value)) = (attr.name.as_str(), value) {
+                            start_tag.push_str(&format!(" class=\"' + ({}) + '\"", value));
                         }
-                        else {
-                            match &attr.value {
-                                Some(v) => start_tag.push_str(&format!(" {}=\"{}\"", attr.name, v)),
-                                None => start_tag.push_str(&format!(" {}", attr.name)),
+                    }
+                    else if attr.is_dynamic {
+                        start_tag.push_str(&format!(" {}=\"' + ({}) + '\"", attr.name, attr.value.as_deref().unwrap_or("")));
+                    }
+                    else {
+                        match &attr.value {
+                            Some(v) => {
+                                let v = v.replace('\\', "\\\\").replace('\'', "\\'").replace('"', "&quot;");
+                                start_tag.push_str(&format!(" {}=\"{}\"", attr.name, v));
                             }
+                            None => start_tag.push_str(&format!(" {}", attr.name)),
                         }
                     }
                 }
@@ -93,7 +108,7 @@ impl SsrBackend {
                 writer.write_line(&start_tag);
 
                 for child in &el.children {
-                    Self::generate_node_ssr(child, writer, node_index);
+                    Self::generate_node_ssr(child, writer, node_index, module_bindings);
                 }
 
                 writer.write_line(&format!("html += '</{}>';", el.tag));
@@ -116,3 +131,15 @@ impl SsrBackend {
         }
     }
 }
+
+/// Whether `expr` reads one of the CSS Module `bindings`, as in `$style.card` or `$style['is-active']`.
+fn reads_css_module(expr: &str, bindings: &[&str]) -> bool {
+    bindings.iter().any(|binding| {
+        expr.match_indices(binding).any(|(start, _)| {
+            let before = expr[..start].chars().next_back();
+            let after = expr[start + binding.len()..].chars().next();
+            !before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.')
+                && matches!(after, Some('.') | Some('['))
+        })
+    })
+}
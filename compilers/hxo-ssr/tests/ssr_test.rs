@@ -26,6 +26,7 @@ fn test_ssr_generation() {
                 is_static: true,
                 span: Span::default(),
             })],
+            toc: vec![],
             span: Span::default(),
         }),
         styles: vec![],
@@ -0,0 +1,267 @@
+use crate::{
+    StyleContext, StylePlugin, Version, is_ident_char, matching_paren, string_end, targets::Browser, walk_declarations,
+};
+use hxo_ir::Stylesheet;
+use hxo_types::Result;
+
+/// `rgb(0 0 0 / 50%)` and `hsl(0 0% 0%)`: space-separated arguments and a slash before alpha.
+const SPACE_SEPARATED: &[(Browser, Version)] = &[
+    (Browser::Chrome, Version::new(65, 0)),
+    (Browser::Edge, Version::new(79, 0)),
+    (Browser::Firefox, Version::new(52, 0)),
+    (Browser::Safari, Version::new(12, 1)),
+    (Browser::IosSafari, Version::new(12, 2)),
+    (Browser::Opera, Version::new(52, 0)),
+    (Browser::Samsung, Version::new(9, 2)),
+];
+
+/// `#rrggbbaa` and `#rgba`.
+const HEX_ALPHA: &[(Browser, Version)] = &[
+    (Browser::Chrome, Version::new(62, 0)),
+    (Browser::Edge, Version::new(79, 0)),
+    (Browser::Firefox, Version::new(49, 0)),
+    (Browser::Safari, Version::new(10, 0)),
+    (Browser::IosSafari, Version::new(10, 0)),
+    (Browser::Opera, Version::new(49, 0)),
+    (Browser::Samsung, Version::new(8, 0)),
+];
+
+/// `oklch()` and `oklab()`.
+const OKLAB: &[(Browser, Version)] = &[
+    (Browser::Chrome, Version::new(111, 0)),
+    (Browser::Edge, Version::new(111, 0)),
+    (Browser::Firefox, Version::new(113, 0)),
+    (Browser::Safari, Version::new(15, 4)),
+    (Browser::IosSafari, Version::new(15, 4)),
+    (Browser::Opera, Version::new(97, 0)),
+    (Browser::Samsung, Version::new(22, 0)),
+];
+
+/// Rewrites modern color syntax the targets cannot read into comma-separated `rgb()`/`rgba()`/`hsl()`/
+/// `hsla()`. Colors built from `var()` or `calc()` are left alone since they cannot be computed here.
+pub struct LowerColors;
+
+#[derive(Clone, Copy)]
+struct Lowering {
+    space_separated: bool,
+    hex_alpha: bool,
+    oklab: bool,
+}
+
+impl StylePlugin for LowerColors {
+    fn name(&self) -> &str {
+        "lower-colors"
+    }
+
+    fn process(&self, sheet: &mut Stylesheet, context: &StyleContext) -> Result<()> {
+        let targets = context.targets;
+        let lowering = Lowering {
+            space_separated: targets.lacks(SPACE_SEPARATED),
+            hex_alpha: targets.lacks(HEX_ALPHA),
+            oklab: targets.lacks(OKLAB),
+        };
+        if lowering.space_separated || lowering.hex_alpha || lowering.oklab {
+            walk_declarations(&mut sheet.nodes, &mut |declaration| {
+                declaration.value = lower_value(&declaration.value, lowering);
+            });
+        }
+        Ok(())
+    }
+}
+
+fn lower_value(value: &str, lowering: Lowering) -> String {
+    let mut out = String::with_capacity(value.len());
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < value.len() {
+        let c = value[i..].chars().next().unwrap_or_default();
+        if c == '"' || c == '\'' {
+            let end = string_end(value, i);
+            out.push_str(&value[i..end]);
+            i = end;
+        }
+        else if c == '#' && (i == 0 || !is_ident_char(bytes[i - 1] as char)) {
+            let len = value[i + 1..].chars().take_while(|c| is_ident_char(*c)).map(char::len_utf8).sum::<usize>();
+            let hex = &value[i + 1..i + 1 + len];
+            match lower_hex(hex).filter(|_| lowering.hex_alpha) {
+                Some(color) => out.push_str(&color),
+                None => out.push_str(&value[i..i + 1 + len]),
+            }
+            i += 1 + len;
+        }
+        else if c.is_ascii_alphabetic() && (i == 0 || !is_ident_char(bytes[i - 1] as char)) {
+            let len = value[i..].chars().take_while(|c| is_ident_char(*c)).map(char::len_utf8).sum::<usize>();
+            let name = &value[i..i + len];
+            if value[i + len..].starts_with('(') {
+                let close = matching_paren(value, i + len);
+                let end = (close + 1).min(value.len());
+                if name.eq_ignore_ascii_case("url") {
+                    out.push_str(&value[i..end]);
+                }
+                else {
+                    let args = lower_value(&value[i + len + 1..close], lowering);
+                    match lower_function(&name.to_ascii_lowercase(), &args, lowering) {
+                        Some(color) => out.push_str(&color),
+                        None => {
+                            out.push_str(&value[i..i + len + 1]);
+                            out.push_str(&args);
+                            out.push_str(&value[close..end]);
+                        }
+                    }
+                }
+                i = end;
+            }
+            else {
+                out.push_str(name);
+                i += len;
+            }
+        }
+        else {
+            out.push(c);
+            i += c.len_utf8();
+        }
+    }
+    out
+}
+
+fn lower_function(name: &str, args: &str, lowering: Lowering) -> Option<String> {
+    if args.contains('(') {
+        return None;
+    }
+    match name {
+        "rgb" | "rgba" | "hsl" | "hsla" if lowering.space_separated && !args.contains(',') => {
+            let (channels, alpha) = space_separated(args)?;
+            let alpha = match alpha {
+                Some(alpha) => Some(parse_alpha(alpha)?),
+                None => None,
+            };
+            let channels = if name.starts_with("rgb") {
+                channels.iter().map(|c| rgb_channel(c).map(|c| format_number(c.round()))).collect::<Option<Vec<_>>>()?
+            }
+            else {
+                let [h, s, l] = channels.as_slice()
+                else {
+                    return None;
+                };
+                vec![format_number(hue(h)?), percentage(s)?, percentage(l)?]
+            };
+            let base = &name[..3];
+            Some(legacy(base, &channels, alpha))
+        }
+        "oklch" | "oklab" if lowering.oklab => {
+            let (channels, alpha) = space_separated(args)?;
+            let [l, a, b] = channels.as_slice()
+            else {
+                return None;
+            };
+            let l = number_or_percent(l, 1.0)?;
+            let (a, b) = if name == "oklch" {
+                let (c, h) = (number_or_percent(a, 0.4)?, hue(b)?.to_radians());
+                (c * h.cos(), c * h.sin())
+            }
+            else {
+                (number_or_percent(a, 0.4)?, number_or_percent(b, 0.4)?)
+            };
+            let alpha = match alpha {
+                Some(alpha) => Some(parse_alpha(alpha)?),
+                None => None,
+            };
+            let rgb = oklab_to_srgb(l, a, b).map(|c| format_number((c * 255.0).round()));
+            Some(legacy("rgb", &rgb, alpha))
+        }
+        _ => None,
+    }
+}
+
+fn lower_hex(hex: &str) -> Option<String> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let expanded: String = match hex.len() {
+        4 => hex.chars().flat_map(|c| [c, c]).collect(),
+        8 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| u8::from_str_radix(&expanded[i..i + 2], 16).map(f64::from).ok();
+    let rgb = [channel(0)?, channel(2)?, channel(4)?].map(format_number);
+    Some(legacy("rgb", &rgb, Some(channel(6)? / 255.0)))
+}
+
+/// `rgb(r, g, b)` or, with an alpha below 1, `rgba(r, g, b, a)`.
+fn legacy(name: &str, channels: &[String], alpha: Option<f64>) -> String {
+    match alpha {
+        Some(alpha) if alpha < 1.0 => format!("{}a({}, {})", name, channels.join(", "), format_number(alpha)),
+        _ => format!("{}({})", name, channels.join(", ")),
+    }
+}
+
+/// Three channels and an optional alpha from `a b c` or `a b c / alpha`.
+fn space_separated(args: &str) -> Option<(Vec<String>, Option<&str>)> {
+    let (channels, alpha) = match args.split_once('/') {
+        Some((channels, alpha)) => (channels, Some(alpha.trim())),
+        None => (args, None),
+    };
+    let channels: Vec<String> = channels.split_whitespace().map(str::to_string).collect();
+    (channels.len() == 3).then_some((channels, alpha))
+}
+
+fn parse_alpha(alpha: &str) -> Option<f64> {
+    number_or_percent(alpha, 1.0).map(|alpha| alpha.clamp(0.0, 1.0))
+}
+
+fn rgb_channel(channel: &str) -> Option<f64> {
+    number_or_percent(channel, 255.0).map(|c| c.clamp(0.0, 255.0))
+}
+
+/// A number, or a percentage of `full`; `none` is zero.
+fn number_or_percent(value: &str, full: f64) -> Option<f64> {
+    if value.eq_ignore_ascii_case("none") {
+        return Some(0.0);
+    }
+    match value.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok().map(|p| p / 100.0 * full),
+        None => value.parse().ok(),
+    }
+}
+
+/// A hue in degrees from a bare number or a `deg`, `grad`, `rad` or `turn` angle.
+fn hue(value: &str) -> Option<f64> {
+    let units = [("deg", 1.0), ("grad", 0.9), ("rad", 180.0 / std::f64::consts::PI), ("turn", 360.0)];
+    for (unit, factor) in units {
+        if let Some(number) = value.strip_suffix(unit) {
+            return number.parse::<f64>().ok().map(|n| n * factor);
+        }
+    }
+    number_or_percent(value, 1.0)
+}
+
+/// Saturation and lightness as legacy `hsl()` wants them: always a percentage.
+fn percentage(value: &str) -> Option<String> {
+    if value.ends_with('%') {
+        return Some(value.to_string());
+    }
+    number_or_percent(value, 1.0).map(|n| format!("{}%", format_number(n)))
+}
+
+/// OKLab to gamma-encoded sRGB channels in `0..=1`, clipped to the sRGB gamut.
+fn oklab_to_srgb(l: f64, a: f64, b: f64) -> [f64; 3] {
+    let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+    let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+    let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+    let linear = [
+        4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+        -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+        -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_,
+    ];
+    linear.map(|c| {
+        let c = c.clamp(0.0, 1.0);
+        if c <= 0.0031308 { 12.92 * c } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 }
+    })
+}
+
+/// Up to three decimals without trailing zeros.
+fn format_number(n: f64) -> String {
+    let s = format!("{:.3}", n);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".to_string() } else { s.to_string() }
+}
@@ -0,0 +1,125 @@
+use crate::{StyleContext, StylePlugin};
+use hxo_ir::{AtRule, AtRuleKind, CssNode, Stylesheet};
+use hxo_types::{Error, Result};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Loads the stylesheets `@import` refers to.
+pub trait ImportResolver: Send + Sync {
+    /// Loads `url` as imported from the file `from` (`None` for a stylesheet without a path). Returns
+    /// an id for the loaded file, used to resolve its own imports and to detect cycles, and its source;
+    /// `None` keeps the `@import` as it is.
+    fn load(&self, url: &str, from: Option<&str>) -> Result<Option<(String, String)>>;
+}
+
+/// Resolves `@import`s against the file system: relative urls from the importing file's directory and
+/// `/`-rooted ones from `root`. Remote urls and missing files are left to the browser.
+pub struct FsImportResolver {
+    root: PathBuf,
+}
+
+impl FsImportResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl ImportResolver for FsImportResolver {
+    fn load(&self, url: &str, from: Option<&str>) -> Result<Option<(String, String)>> {
+        if url.starts_with("//") || url.contains(':') {
+            return Ok(None);
+        }
+        let path = match (url.strip_prefix('/'), from) {
+            (Some(rooted), _) => self.root.join(rooted),
+            (None, Some(from)) => self.root.join(from).parent().unwrap_or(Path::new("")).join(url),
+            (None, None) => self.root.join(url),
+        };
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let source = std::fs::read_to_string(&path)?;
+        Ok(Some((path.to_string_lossy().into_owned(), source)))
+    }
+}
+
+/// Replaces the leading `@import`s of a stylesheet with the rules they load, wrapped in the
+/// `@media`, `@supports` and `@layer` blocks the import asked for. `@import`s after the first rule are
+/// ignored by browsers and stay as they are.
+pub struct InlineImports {
+    resolver: Arc<dyn ImportResolver>,
+}
+
+impl InlineImports {
+    pub fn new(resolver: Arc<dyn ImportResolver>) -> Self {
+        Self { resolver }
+    }
+
+    fn inline(&self, nodes: Vec<CssNode>, from: Option<&str>, stack: &mut Vec<String>) -> Result<Vec<CssNode>> {
+        let mut inlined = Vec::with_capacity(nodes.len());
+        let mut leading = true;
+        for node in nodes {
+            let CssNode::AtRule(rule) = &node
+            else {
+                leading = false;
+                inlined.push(node);
+                continue;
+            };
+            let (url, layer, supports, media) = match rule.kind() {
+                AtRuleKind::Import { url, layer, supports, media } if leading => (url, layer, supports, media),
+                AtRuleKind::Layer(_) | AtRuleKind::Other if rule.block.is_none() => {
+                    inlined.push(node);
+                    continue;
+                }
+                _ => {
+                    leading = false;
+                    inlined.push(node);
+                    continue;
+                }
+            };
+            let Some((id, source)) = self.resolver.load(url, from)?
+            else {
+                inlined.push(node);
+                continue;
+            };
+            if stack.contains(&id) {
+                return Err(Error::parse_error(format!("circular `@import` of `{}`", url), rule.span));
+            }
+
+            stack.push(id.clone());
+            let imported = hxo_parser_css::parse_stylesheet(&source)?.without_spans();
+            let mut block = self.inline(imported.nodes, Some(&id), stack)?;
+            stack.pop();
+
+            block.retain(|node| !matches!(node, CssNode::AtRule(rule) if rule.name.eq_ignore_ascii_case("charset")));
+            let wrap = |name: &str, prelude: String, block| {
+                vec![CssNode::AtRule(AtRule { name: name.to_string(), prelude, block: Some(block), span: rule.span })]
+            };
+            if let Some(layer) = layer {
+                block = wrap("layer", layer.to_string(), block);
+            }
+            if let Some(condition) = supports {
+                let wrapped = condition.starts_with('(') || condition.starts_with("not ") || condition.contains("selector(");
+                block = wrap("supports", if wrapped { condition.to_string() } else { format!("({})", condition) }, block);
+            }
+            if let Some(media) = media {
+                block = wrap("media", media.to_string(), block);
+            }
+            inlined.extend(block);
+        }
+        Ok(inlined)
+    }
+}
+
+impl StylePlugin for InlineImports {
+    fn name(&self) -> &str {
+        "inline-imports"
+    }
+
+    fn process(&self, sheet: &mut Stylesheet, context: &StyleContext) -> Result<()> {
+        let mut stack = context.filename.map(|filename| vec![filename.to_string()]).unwrap_or_default();
+        sheet.nodes = self.inline(std::mem::take(&mut sheet.nodes), context.filename, &mut stack)?;
+        Ok(())
+    }
+}
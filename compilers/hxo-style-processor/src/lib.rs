@@ -1,21 +1,202 @@
+use hxo_ir::{CssNode, Declaration, Stylesheet};
 use hxo_types::Result;
+use std::sync::Arc;
 
-#[derive(Default)]
-pub struct StyleProcessor;
+mod color;
+mod import;
+mod minify;
+mod nesting;
+mod prefix;
+mod targets;
+
+pub use color::LowerColors;
+pub use import::{FsImportResolver, ImportResolver, InlineImports};
+pub use minify::{Minify, minify_css, minify_stylesheet};
+pub use nesting::LowerNesting;
+pub use prefix::Prefixer;
+pub use targets::{Browser, Support, Targets, Version};
+
+/// What a [`StylePlugin`] knows about the stylesheet it transforms.
+pub struct StyleContext<'a> {
+    pub targets: &'a Targets,
+    /// Path of the stylesheet, used to resolve relative `@import`s.
+    pub filename: Option<&'a str>,
+}
+
+/// One step of the [`StyleProcessor`] pipeline.
+pub trait StylePlugin: Send + Sync {
+    fn name(&self) -> &str;
+    fn process(&self, sheet: &mut Stylesheet, context: &StyleContext) -> Result<()>;
+}
+
+/// Post-processes CSS in the spirit of PostCSS: parse once, run each plugin over the AST, print.
+///
+/// [`StyleProcessor::new`] runs nesting lowering, color lowering and vendor prefixing for the
+/// configured [`Targets`]; `@import` inlining is added with
+/// [`with_import_resolver`](Self::with_import_resolver) and minification with
+/// [`with_minify`](Self::with_minify).
+#[derive(Clone)]
+pub struct StyleProcessor {
+    plugins: Vec<Arc<dyn StylePlugin>>,
+    targets: Targets,
+    filename: Option<String>,
+    minify: bool,
+}
+
+impl Default for StyleProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl StyleProcessor {
     pub fn new() -> Self {
-        Self
+        Self {
+            plugins: vec![Arc::new(LowerNesting), Arc::new(LowerColors), Arc::new(Prefixer)],
+            targets: Targets::default(),
+            filename: None,
+            minify: false,
+        }
+    }
+
+    /// A processor without any plugin.
+    pub fn empty() -> Self {
+        Self { plugins: Vec::new(), ..Self::new() }
+    }
+
+    pub fn with_targets(mut self, targets: Targets) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    pub fn with_filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Inlines `@import`s that `resolver` can load, before any other plugin runs.
+    pub fn with_import_resolver(mut self, resolver: Arc<dyn ImportResolver>) -> Self {
+        self.plugins.insert(0, Arc::new(InlineImports::new(resolver)));
+        self
+    }
+
+    /// Appends `plugin` to the pipeline.
+    pub fn with_plugin(mut self, plugin: Arc<dyn StylePlugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    /// Minifies after the last plugin and prints without whitespace.
+    pub fn with_minify(mut self, minify: bool) -> Self {
+        self.minify = minify;
+        self
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = &str> {
+        self.plugins.iter().map(|plugin| plugin.name())
     }
 
-    /// 模拟 PostCSS 的转换过程
     pub fn process(&self, css: &str) -> Result<String> {
-        // 在这里实现 CSS 转换逻辑，如自动补全前缀、压缩等
-        let processed = format!("/* Processed by HXO Style Processor */\n{}", css);
-        Ok(processed)
+        let mut sheet = hxo_parser_css::parse_stylesheet(css)?;
+        self.process_stylesheet(&mut sheet)?;
+        Ok(sheet.to_css(self.minify))
+    }
+
+    pub fn process_stylesheet(&self, sheet: &mut Stylesheet) -> Result<()> {
+        let context = StyleContext { targets: &self.targets, filename: self.filename.as_deref() };
+        for plugin in &self.plugins {
+            plugin.process(sheet, &context)?;
+        }
+        if self.minify {
+            minify_stylesheet(sheet);
+        }
+        Ok(())
     }
 }
 
+/// Runs the default pipeline for browserslist's `defaults`.
 pub fn process(css: &str) -> Result<String> {
-    StyleProcessor::new().process(css)
+    StyleProcessor::new().with_targets(Targets::defaults()).process(css)
+}
+
+/// Splits `s` on `separator` outside parentheses, brackets and strings, trimming each part.
+pub(crate) fn split_top_level(s: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(s[start..i].trim().to_string());
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(s[start..].trim().to_string());
+    parts
+}
+
+/// Calls `f` on every declaration, however deeply nested.
+pub(crate) fn walk_declarations(nodes: &mut [CssNode], f: &mut impl FnMut(&mut Declaration)) {
+    for node in nodes {
+        match node {
+            CssNode::Declaration(declaration) => f(declaration),
+            CssNode::Rule(rule) => walk_declarations(&mut rule.children, f),
+            CssNode::AtRule(rule) => {
+                if let Some(block) = &mut rule.block {
+                    walk_declarations(block, f);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Index just past the string that starts at `start`.
+pub(crate) fn string_end(value: &str, start: usize) -> usize {
+    let quote = value.as_bytes()[start];
+    let mut escaped = false;
+    for (i, b) in value.bytes().enumerate().skip(start + 1) {
+        match b {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            _ if b == quote => return i + 1,
+            _ => {}
+        }
+    }
+    value.len()
+}
+
+/// Index of the `)` closing the `(` at `open`, or the length of `value` when it is unclosed.
+pub(crate) fn matching_paren(value: &str, open: usize) -> usize {
+    let mut depth = 0;
+    let mut i = open;
+    while i < value.len() {
+        match value.as_bytes()[i] {
+            b'"' | b'\'' => {
+                i = string_end(value, i);
+                continue;
+            }
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    value.len()
 }
@@ -0,0 +1,204 @@
+use crate::{StyleContext, StylePlugin, is_ident_char, matching_paren, string_end};
+use hxo_ir::{AtRuleKind, CssNode, Stylesheet};
+use hxo_types::Result;
+
+/// Compacts a stylesheet without changing what it means; see [`minify_stylesheet`].
+pub struct Minify;
+
+impl StylePlugin for Minify {
+    fn name(&self) -> &str {
+        "minify"
+    }
+
+    fn process(&self, sheet: &mut Stylesheet, _context: &StyleContext) -> Result<()> {
+        minify_stylesheet(sheet);
+        Ok(())
+    }
+}
+
+/// Parses, minifies and prints `css` without whitespace.
+pub fn minify_css(css: &str) -> Result<String> {
+    let mut sheet = hxo_parser_css::parse_stylesheet(css)?;
+    minify_stylesheet(&mut sheet);
+    Ok(sheet.to_css(true))
+}
+
+/// Removes the whitespace the printer would keep, shortens numbers and hex colors and drops empty rules.
+///
+/// Works on tokens rather than text: strings, `url()`s and custom property values are kept as written,
+/// descendant combinators keep their space (`a :hover` is not `a:hover`) and the operators of `calc()`
+/// keep theirs.
+pub fn minify_stylesheet(sheet: &mut Stylesheet) {
+    minify_nodes(&mut sheet.nodes);
+}
+
+fn minify_nodes(nodes: &mut Vec<CssNode>) {
+    for node in nodes.iter_mut() {
+        match node {
+            CssNode::Declaration(declaration) => {
+                declaration.value = if declaration.property.starts_with("--") {
+                    declaration.value.trim().to_string()
+                }
+                else {
+                    minify_value(&declaration.value)
+                };
+            }
+            CssNode::Rule(rule) => {
+                rule.selector = minify_selector(&rule.selector);
+                minify_nodes(&mut rule.children);
+            }
+            CssNode::AtRule(rule) => {
+                rule.prelude = minify_prelude(&rule.prelude);
+                if let Some(block) = &mut rule.block {
+                    minify_nodes(block);
+                }
+            }
+        }
+    }
+    nodes.retain(|node| match node {
+        CssNode::Rule(rule) => !rule.children.is_empty(),
+        CssNode::AtRule(rule) => {
+            !rule.block.as_ref().is_some_and(Vec::is_empty)
+                || !matches!(rule.kind(), AtRuleKind::Media(_) | AtRuleKind::Supports(_) | AtRuleKind::Container { .. })
+        }
+        CssNode::Declaration(_) => true,
+    });
+}
+
+/// Collapses whitespace, drops it around `,`, `>`, `+` and `~`, and keeps the rest: a space between
+/// compound selectors is the descendant combinator. Attribute selectors and strings are left alone.
+fn minify_selector(selector: &str) -> String {
+    let mut out = String::with_capacity(selector.len());
+    let mut pending_space = false;
+    let mut i = 0;
+    while i < selector.len() {
+        let c = selector[i..].chars().next().unwrap_or_default();
+        if c.is_whitespace() {
+            pending_space = true;
+            i += c.len_utf8();
+            continue;
+        }
+        let is_combinator = matches!(c, ',' | '>' | '+' | '~');
+        if pending_space && !is_combinator && !out.is_empty() && !out.ends_with([',', '>', '+', '~', '(']) {
+            out.push(' ');
+        }
+        pending_space = false;
+        let end = match c {
+            '"' | '\'' => string_end(selector, i),
+            '[' => selector[i..].find(']').map_or(selector.len(), |j| i + j + 1),
+            '\\' => (i + 1 + selector[i + 1..].chars().next().map_or(0, char::len_utf8)).min(selector.len()),
+            _ => i + c.len_utf8(),
+        };
+        out.push_str(&selector[i..end]);
+        i = end;
+    }
+    out
+}
+
+/// At-rule preludes only lose the space after `,` and after `:` inside a parenthesized feature; a
+/// space before `(` is significant in `and (min-width: 1px)`.
+fn minify_prelude(prelude: &str) -> String {
+    let mut out = String::with_capacity(prelude.len());
+    let mut depth = 0;
+    let mut i = 0;
+    while i < prelude.len() {
+        let c = prelude[i..].chars().next().unwrap_or_default();
+        match c {
+            '"' | '\'' => {
+                let end = string_end(prelude, i);
+                out.push_str(&prelude[i..end]);
+                i = end;
+                continue;
+            }
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if out.ends_with(' ') => {
+                out.pop();
+            }
+            c if c.is_whitespace() && (out.ends_with(',') || (depth > 0 && out.ends_with(':'))) => {
+                i += c.len_utf8();
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+/// Minifies a declaration value; see [`minify_stylesheet`].
+fn minify_value(value: &str) -> String {
+    let value = value.trim();
+    let mut out = String::with_capacity(value.len());
+    let mut pending_space = false;
+    let mut i = 0;
+    while i < value.len() {
+        let c = value[i..].chars().next().unwrap_or_default();
+        if c.is_whitespace() {
+            pending_space = true;
+            i += c.len_utf8();
+            continue;
+        }
+        if pending_space && !matches!(c, ',' | ')') && !out.ends_with([',', '(']) {
+            out.push(' ');
+        }
+        pending_space = false;
+
+        let at_token_start = out.is_empty() || out.ends_with([' ', ',', '(', '/']);
+        match c {
+            '"' | '\'' => {
+                let end = string_end(value, i);
+                out.push_str(&value[i..end]);
+                i = end;
+            }
+            '#' if at_token_start => {
+                let len = value[i + 1..].chars().take_while(|c| is_ident_char(*c)).map(char::len_utf8).sum::<usize>();
+                out.push('#');
+                out.push_str(&shorten_hex(&value[i + 1..i + 1 + len]));
+                i += 1 + len;
+            }
+            '0' | '-' if at_token_start && leading_zero(&value[i..]) => {
+                // `0.5` is `.5` and `-0.5` is `-.5`
+                if c == '-' {
+                    out.push('-');
+                }
+                i += if c == '-' { 2 } else { 1 };
+            }
+            c if c.is_ascii_alphabetic() && value[i..].get(..4).is_some_and(|s| s.eq_ignore_ascii_case("url(")) => {
+                let end = (matching_paren(value, i + 3) + 1).min(value.len());
+                out.push_str(&value[i..end]);
+                i = end;
+            }
+            c if is_ident_char(c) => {
+                let len = value[i..].chars().take_while(|c| is_ident_char(*c) || *c == '.').map(char::len_utf8).sum::<usize>();
+                out.push_str(&value[i..i + len]);
+                i += len;
+            }
+            c => {
+                out.push(c);
+                i += c.len_utf8();
+            }
+        }
+    }
+    out
+}
+
+/// Whether `rest` starts with a number whose integer part is a lone zero, like `0.5` or `-0.5`.
+fn leading_zero(rest: &str) -> bool {
+    let rest = rest.strip_prefix('-').unwrap_or(rest);
+    rest.strip_prefix("0.").is_some_and(|fraction| fraction.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Lowercases a hex color and uses the 3 or 4 digit form when every channel repeats its digit.
+fn shorten_hex(hex: &str) -> String {
+    if !matches!(hex.len(), 3 | 4 | 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return hex.to_string();
+    }
+    let hex = hex.to_ascii_lowercase();
+    let bytes = hex.as_bytes();
+    if hex.len() >= 6 && bytes.chunks(2).all(|pair| pair[0] == pair[1]) {
+        return bytes.chunks(2).map(|pair| pair[0] as char).collect();
+    }
+    hex
+}
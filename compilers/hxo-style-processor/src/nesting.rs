@@ -0,0 +1,101 @@
+use crate::{StyleContext, StylePlugin, Version, split_top_level, targets::Browser};
+use hxo_ir::{AtRule, AtRuleKind, CssNode, StyleRule, Stylesheet};
+use hxo_types::Result;
+
+/// Native CSS nesting with the relaxed syntax (nested selectors that start with an element name).
+const NESTING: &[(Browser, Version)] = &[
+    (Browser::Chrome, Version::new(120, 0)),
+    (Browser::Edge, Version::new(120, 0)),
+    (Browser::Firefox, Version::new(117, 0)),
+    (Browser::Safari, Version::new(17, 2)),
+    (Browser::IosSafari, Version::new(17, 2)),
+    (Browser::Opera, Version::new(106, 0)),
+    (Browser::Samsung, Version::new(25, 0)),
+];
+
+/// Flattens nested rules for targets without CSS nesting: `.a { &:hover { } .b { } }` becomes
+/// `.a:hover { }` and `.a .b { }`, and at-rules nested in a rule wrap a copy of the rule.
+pub struct LowerNesting;
+
+impl StylePlugin for LowerNesting {
+    fn name(&self) -> &str {
+        "lower-nesting"
+    }
+
+    fn process(&self, sheet: &mut Stylesheet, context: &StyleContext) -> Result<()> {
+        if context.targets.lacks(NESTING) {
+            sheet.nodes = lower_nodes(std::mem::take(&mut sheet.nodes));
+        }
+        Ok(())
+    }
+}
+
+fn lower_nodes(nodes: Vec<CssNode>) -> Vec<CssNode> {
+    let mut lowered = Vec::new();
+    for node in nodes {
+        match node {
+            CssNode::Rule(rule) => {
+                let selectors = split_top_level(&rule.selector, ',');
+                lower_rule(rule, &selectors, &mut lowered);
+            }
+            CssNode::AtRule(mut rule) if contains_rules(&rule) => {
+                rule.block = rule.block.map(lower_nodes);
+                lowered.push(CssNode::AtRule(rule));
+            }
+            node => lowered.push(node),
+        }
+    }
+    lowered
+}
+
+/// Emits `rule` with its resolved `selectors`, followed by everything nested in it.
+fn lower_rule(rule: StyleRule, selectors: &[String], out: &mut Vec<CssNode>) {
+    let (declarations, nested): (Vec<_>, Vec<_>) =
+        rule.children.into_iter().partition(|node| matches!(node, CssNode::Declaration(_)));
+    if !declarations.is_empty() {
+        out.push(CssNode::Rule(StyleRule { selector: selectors.join(", "), children: declarations, ..rule }));
+    }
+    for node in nested {
+        match node {
+            CssNode::Rule(child) => {
+                let resolved = resolve_selectors(selectors, &child.selector);
+                lower_rule(child, &resolved, out);
+            }
+            CssNode::AtRule(mut at_rule) if contains_rules(&at_rule) => {
+                // `.a { @media q { color: red } }` is `@media q { .a { color: red } }`
+                let body = StyleRule {
+                    selector: String::new(),
+                    children: at_rule.block.take().unwrap_or_default(),
+                    selector_span: rule.selector_span,
+                    span: at_rule.span,
+                };
+                let mut block = Vec::new();
+                lower_rule(body, selectors, &mut block);
+                at_rule.block = Some(block);
+                out.push(CssNode::AtRule(at_rule));
+            }
+            node => out.push(node),
+        }
+    }
+}
+
+/// At-rules whose block holds rules (and, inside a style rule, declarations).
+fn contains_rules(rule: &AtRule) -> bool {
+    rule.block.is_some()
+        && (matches!(
+            rule.kind(),
+            AtRuleKind::Media(_) | AtRuleKind::Supports(_) | AtRuleKind::Container { .. } | AtRuleKind::Layer(_)
+        ) || matches!(rule.name.as_str(), "scope" | "starting-style" | "document"))
+}
+
+/// Resolves a nested selector list against its parent's: `&` is replaced by each parent selector and a
+/// selector without `&` is a descendant (or, with a leading combinator, relative) of the parent.
+fn resolve_selectors(parents: &[String], nested: &str) -> Vec<String> {
+    let mut resolved = Vec::new();
+    for child in split_top_level(nested, ',') {
+        for parent in parents {
+            resolved.push(if child.contains('&') { child.replace('&', parent) } else { format!("{} {}", parent, child) });
+        }
+    }
+    resolved
+}
@@ -0,0 +1,202 @@
+use crate::{StyleContext, StylePlugin, Targets, Version, targets::Browser};
+use hxo_ir::{AtRule, AtRuleKind, CssNode, Declaration, Stylesheet};
+use hxo_types::Result;
+
+/// A prefix one browser needs, until the version that reads the standard syntax (`None`: every version).
+type Prefixes = &'static [(Browser, &'static str, Option<Version>)];
+
+const fn v(major: u32, minor: u32) -> Option<Version> {
+    Some(Version::new(major, minor))
+}
+
+const TRANSFORMS: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(36, 0)),
+    (Browser::Safari, "-webkit-", v(9, 0)),
+    (Browser::IosSafari, "-webkit-", v(9, 0)),
+    (Browser::Opera, "-webkit-", v(23, 0)),
+    (Browser::Firefox, "-moz-", v(16, 0)),
+    (Browser::Ie, "-ms-", v(10, 0)),
+];
+
+const USER_SELECT: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(54, 0)),
+    (Browser::Opera, "-webkit-", v(41, 0)),
+    (Browser::Samsung, "-webkit-", v(6, 2)),
+    (Browser::Safari, "-webkit-", None),
+    (Browser::IosSafari, "-webkit-", None),
+    (Browser::Firefox, "-moz-", v(69, 0)),
+    (Browser::Ie, "-ms-", None),
+];
+
+const APPEARANCE: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(84, 0)),
+    (Browser::Edge, "-webkit-", v(84, 0)),
+    (Browser::Opera, "-webkit-", v(70, 0)),
+    (Browser::Samsung, "-webkit-", v(14, 0)),
+    (Browser::Safari, "-webkit-", v(15, 4)),
+    (Browser::IosSafari, "-webkit-", v(15, 4)),
+    (Browser::Firefox, "-moz-", v(80, 0)),
+];
+
+const MASK: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(120, 0)),
+    (Browser::Edge, "-webkit-", v(120, 0)),
+    (Browser::Opera, "-webkit-", v(106, 0)),
+    (Browser::Samsung, "-webkit-", v(25, 0)),
+    (Browser::Safari, "-webkit-", v(15, 4)),
+    (Browser::IosSafari, "-webkit-", v(15, 4)),
+];
+
+const SAFARI_ONLY: Prefixes = &[(Browser::Safari, "-webkit-", v(18, 0)), (Browser::IosSafari, "-webkit-", v(18, 0))];
+
+const HYPHENS: Prefixes = &[
+    (Browser::Safari, "-webkit-", v(17, 0)),
+    (Browser::IosSafari, "-webkit-", v(17, 0)),
+    (Browser::Firefox, "-moz-", v(43, 0)),
+    (Browser::Ie, "-ms-", None),
+];
+
+const CLIP_PATH: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(55, 0)),
+    (Browser::Safari, "-webkit-", v(13, 1)),
+    (Browser::IosSafari, "-webkit-", v(13, 4)),
+];
+
+const BACKGROUND_CLIP_TEXT: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(120, 0)),
+    (Browser::Edge, "-webkit-", v(120, 0)),
+    (Browser::Opera, "-webkit-", v(106, 0)),
+    (Browser::Samsung, "-webkit-", v(25, 0)),
+    (Browser::Safari, "-webkit-", None),
+    (Browser::IosSafari, "-webkit-", None),
+];
+
+const TEXT_SIZE_ADJUST: Prefixes = &[(Browser::IosSafari, "-webkit-", None)];
+
+const BOX_DECORATION_BREAK: Prefixes = &[
+    (Browser::Chrome, "-webkit-", v(130, 0)),
+    (Browser::Edge, "-webkit-", v(130, 0)),
+    (Browser::Safari, "-webkit-", None),
+    (Browser::IosSafari, "-webkit-", None),
+];
+
+const STICKY: Prefixes = &[(Browser::Safari, "-webkit-", v(13, 0)), (Browser::IosSafari, "-webkit-", v(13, 0))];
+
+/// Properties that get a prefixed copy, optionally only for one value.
+const PROPERTIES: &[(&str, Option<&str>, Prefixes)] = &[
+    ("transform", None, TRANSFORMS),
+    ("transform-origin", None, TRANSFORMS),
+    ("transition", None, TRANSFORMS),
+    ("animation", None, TRANSFORMS),
+    ("animation-name", None, TRANSFORMS),
+    ("animation-duration", None, TRANSFORMS),
+    ("user-select", None, USER_SELECT),
+    ("appearance", None, APPEARANCE),
+    ("mask", None, MASK),
+    ("mask-image", None, MASK),
+    ("mask-size", None, MASK),
+    ("mask-position", None, MASK),
+    ("mask-repeat", None, MASK),
+    ("backdrop-filter", None, SAFARI_ONLY),
+    ("hyphens", None, HYPHENS),
+    ("clip-path", None, CLIP_PATH),
+    ("background-clip", Some("text"), BACKGROUND_CLIP_TEXT),
+    ("text-size-adjust", None, TEXT_SIZE_ADJUST),
+    ("box-decoration-break", None, BOX_DECORATION_BREAK),
+];
+
+/// Values that get a prefixed copy: `position: -webkit-sticky` before `position: sticky`.
+const VALUES: &[(&str, &str, Prefixes)] = &[("position", "sticky", STICKY)];
+
+/// Adds the vendor-prefixed declarations and `@keyframes` the targets need, in front of the standard
+/// ones so the standard syntax wins where both are understood. Prefixes already written by hand are
+/// not duplicated.
+pub struct Prefixer;
+
+impl StylePlugin for Prefixer {
+    fn name(&self) -> &str {
+        "prefixer"
+    }
+
+    fn process(&self, sheet: &mut Stylesheet, context: &StyleContext) -> Result<()> {
+        if !context.targets.is_empty() {
+            prefix_nodes(&mut sheet.nodes, context.targets);
+        }
+        Ok(())
+    }
+}
+
+fn prefix_nodes(nodes: &mut Vec<CssNode>, targets: &Targets) {
+    let existing: Vec<String> = nodes
+        .iter()
+        .filter_map(|node| match node {
+            CssNode::Declaration(declaration) => Some(format!("{}:{}", declaration.property, declaration.value)),
+            CssNode::AtRule(rule) => Some(format!("@{} {}", rule.name, rule.prelude)),
+            CssNode::Rule(_) => None,
+        })
+        .collect();
+    let mut prefixed = Vec::with_capacity(nodes.len());
+    for mut node in std::mem::take(nodes) {
+        match &mut node {
+            CssNode::Declaration(declaration) => {
+                for copy in prefixed_declarations(declaration, targets) {
+                    if !existing.contains(&format!("{}:{}", copy.property, copy.value)) {
+                        prefixed.push(CssNode::Declaration(copy));
+                    }
+                }
+            }
+            CssNode::Rule(rule) => prefix_nodes(&mut rule.children, targets),
+            CssNode::AtRule(rule) => {
+                if let Some(block) = &mut rule.block {
+                    prefix_nodes(block, targets);
+                }
+                if let AtRuleKind::Keyframes(_) = rule.kind() {
+                    for prefix in needed(TRANSFORMS, targets) {
+                        let name = format!("{}{}", prefix, rule.name);
+                        if !rule.name.starts_with('-') && !existing.contains(&format!("@{} {}", name, rule.prelude)) {
+                            prefixed.push(CssNode::AtRule(AtRule { name, ..rule.clone() }));
+                        }
+                    }
+                }
+            }
+        }
+        prefixed.push(node);
+    }
+    *nodes = prefixed;
+}
+
+fn prefixed_declarations(declaration: &Declaration, targets: &Targets) -> Vec<Declaration> {
+    let property = declaration.property.to_ascii_lowercase();
+    let value = declaration.value.trim().to_ascii_lowercase();
+    let mut copies = Vec::new();
+    for (name, only, prefixes) in PROPERTIES {
+        if *name == property && only.is_none_or(|only| only == value) {
+            for prefix in needed(prefixes, targets) {
+                copies.push(Declaration { property: format!("{}{}", prefix, declaration.property), ..declaration.clone() });
+            }
+        }
+    }
+    for (name, standard, prefixes) in VALUES {
+        if *name == property && *standard == value {
+            for prefix in needed(prefixes, targets) {
+                copies.push(Declaration { value: format!("{}{}", prefix, declaration.value.trim()), ..declaration.clone() });
+            }
+        }
+    }
+    copies
+}
+
+/// The distinct prefixes of `prefixes` that some target needs.
+fn needed(prefixes: Prefixes, targets: &Targets) -> Vec<&'static str> {
+    let mut needed = Vec::new();
+    for &(browser, prefix, unprefixed_since) in prefixes {
+        let Some(version) = targets.get(browser)
+        else {
+            continue;
+        };
+        if unprefixed_since.is_none_or(|since| version < since) && !needed.contains(&prefix) {
+            needed.push(prefix);
+        }
+    }
+    needed
+}
@@ -0,0 +1,137 @@
+use hxo_types::{Error, Result, Span};
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Browser {
+    Chrome,
+    Edge,
+    Firefox,
+    Safari,
+    IosSafari,
+    Opera,
+    Samsung,
+    Ie,
+}
+
+impl Browser {
+    /// Accepts browserslist names (`chrome`, `ios_saf`, `samsung`, ...).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "chrome" | "and_chr" => Browser::Chrome,
+            "edge" => Browser::Edge,
+            "firefox" | "ff" | "and_ff" => Browser::Firefox,
+            "safari" => Browser::Safari,
+            "ios_saf" | "ios" | "ios_safari" => Browser::IosSafari,
+            "opera" => Browser::Opera,
+            "samsung" => Browser::Samsung,
+            "ie" | "explorer" => Browser::Ie,
+            _ => return None,
+        })
+    }
+}
+
+/// A `major.minor` browser version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    pub fn parse(version: &str) -> Option<Self> {
+        let (major, minor) = version.split_once('.').unwrap_or((version, "0"));
+        let minor = minor.split('.').next().unwrap_or("0");
+        Some(Self { major: major.parse().ok()?, minor: minor.parse().ok()? })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// First version of each browser supporting a feature; browsers that are missing never support it.
+pub type Support = &'static [(Browser, Version)];
+
+/// The oldest version of each browser the CSS has to work in.
+///
+/// Empty targets mean "current browsers": no transform that only exists for older browsers runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Targets {
+    browsers: BTreeMap<Browser, Version>,
+}
+
+/// What browserslist's `defaults` query resolves to, rounded to the oldest version of each browser.
+const DEFAULTS: &[(Browser, Version)] = &[
+    (Browser::Chrome, Version::new(109, 0)),
+    (Browser::Edge, Version::new(120, 0)),
+    (Browser::Firefox, Version::new(115, 0)),
+    (Browser::Safari, Version::new(15, 6)),
+    (Browser::IosSafari, Version::new(15, 6)),
+    (Browser::Opera, Version::new(104, 0)),
+    (Browser::Samsung, Version::new(23, 0)),
+];
+
+impl Targets {
+    /// Parses a browserslist-style query: comma-separated `defaults`, `<browser> <version>` or
+    /// `<browser> >= <version>` entries, e.g. `"chrome >= 87, safari 14, firefox 78"`. When a browser
+    /// is listed twice the older version wins.
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut targets = Self::default();
+        for entry in query.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            if entry.eq_ignore_ascii_case("defaults") {
+                for &(browser, version) in DEFAULTS {
+                    targets.add(browser, version);
+                }
+                continue;
+            }
+            let invalid = || Error::parse_error(format!("invalid browser target `{}`", entry), Span::unknown());
+            let mut words = entry.split_whitespace();
+            let browser = words.next().and_then(Browser::from_name).ok_or_else(invalid)?;
+            let version = match (words.next(), words.next()) {
+                (Some(">="), Some(version)) | (Some(version), None) => Version::parse(version).ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            };
+            if words.next().is_some() {
+                return Err(invalid());
+            }
+            targets.add(browser, version);
+        }
+        Ok(targets)
+    }
+
+    /// browserslist's `defaults`.
+    pub fn defaults() -> Self {
+        Self { browsers: DEFAULTS.iter().copied().collect() }
+    }
+
+    pub fn add(&mut self, browser: Browser, version: Version) {
+        let entry = self.browsers.entry(browser).or_insert(version);
+        *entry = (*entry).min(version);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.browsers.is_empty()
+    }
+
+    pub fn get(&self, browser: Browser) -> Option<Version> {
+        self.browsers.get(&browser).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Browser, Version)> + '_ {
+        self.browsers.iter().map(|(browser, version)| (*browser, *version))
+    }
+
+    /// Whether some target browser is older than the first version listed in `support`.
+    pub fn lacks(&self, support: Support) -> bool {
+        self.iter().any(|(browser, version)| match support.iter().find(|(b, _)| *b == browser) {
+            Some((_, since)) => version < *since,
+            None => true,
+        })
+    }
+}
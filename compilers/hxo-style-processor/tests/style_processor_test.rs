@@ -1,4 +1,7 @@
-use hxo_style_processor::StyleProcessor;
+use hxo_ir::Stylesheet;
+use hxo_style_processor::{Browser, ImportResolver, StyleContext, StylePlugin, StyleProcessor, Targets, Version, minify_css};
+use hxo_types::Result;
+use std::{collections::HashMap, sync::Arc};
 
 #[test]
 fn test_process_style() {
@@ -6,6 +9,132 @@ fn test_process_style() {
     let css = ".test { color: red; }";
     let processed = processor.process(css).unwrap();
 
-    assert!(processed.contains(".test { color: red; }"));
-    assert!(processed.contains("/* Processed by HXO Style Processor */"));
+    assert_eq!(processed, ".test {\n  color: red;\n}\n");
+    assert_eq!(processor.plugins().collect::<Vec<_>>(), vec!["lower-nesting", "lower-colors", "prefixer"]);
+}
+
+#[test]
+fn test_targets_query() {
+    let targets = Targets::parse("chrome >= 87, safari 14.1, ios_saf 14, chrome 90").unwrap();
+    assert_eq!(targets.get(Browser::Chrome), Some(Version::new(87, 0)));
+    assert_eq!(targets.get(Browser::Safari), Some(Version::new(14, 1)));
+    assert_eq!(targets.get(Browser::Firefox), None);
+    assert_eq!(Targets::parse("defaults").unwrap(), Targets::defaults());
+    assert!(Targets::parse("netscape 4").is_err());
+    assert!(Targets::parse("chrome <= 80").is_err());
+}
+
+#[test]
+fn test_prefixes_follow_targets() {
+    let css = ".a { user-select: none; transform: scale(2); position: sticky; -webkit-appearance: none; appearance: none; }\n@keyframes spin { to { transform: rotate(1turn); } }";
+
+    let old = StyleProcessor::new().with_targets(Targets::parse("safari 8, firefox 60").unwrap()).process(css).unwrap();
+    assert!(old.contains("  -webkit-user-select: none;\n  -moz-user-select: none;\n  user-select: none;"), "{}", old);
+    assert!(old.contains("-webkit-transform: scale(2);\n  transform: scale(2);"));
+    assert!(old.contains("position: -webkit-sticky;\n  position: sticky;"));
+    assert_eq!(old.matches("-webkit-appearance").count(), 1);
+    assert!(old.contains("@-webkit-keyframes spin {\n  to {\n    -webkit-transform: rotate(1turn);"));
+
+    let modern = StyleProcessor::new().with_targets(Targets::parse("chrome 120, firefox 120").unwrap()).process(css).unwrap();
+    assert!(!modern.contains("-moz-") && !modern.contains("-webkit-keyframes") && !modern.contains("-webkit-transform"));
+}
+
+#[test]
+fn test_lower_nesting() {
+    let css = ".card, .panel {\n  color: red;\n  &:hover { color: blue; }\n  > .title { font-weight: 700; }\n  @media (min-width: 640px) {\n    padding: 1rem;\n    .icon { width: 2rem; }\n  }\n}";
+    let lowered = StyleProcessor::new().with_targets(Targets::parse("chrome 100").unwrap()).process(css).unwrap();
+    assert_eq!(
+        lowered,
+        ".card, .panel {\n  color: red;\n}\n\n.card:hover, .panel:hover {\n  color: blue;\n}\n\n.card > .title, .panel > .title {\n  font-weight: 700;\n}\n\n@media (min-width: 640px) {\n  .card, .panel {\n    padding: 1rem;\n  }\n  .card .icon, .panel .icon {\n    width: 2rem;\n  }\n}\n"
+    );
+
+    let kept = StyleProcessor::new().with_targets(Targets::parse("chrome 120, safari 17.2").unwrap()).process(css).unwrap();
+    assert!(kept.contains("  &:hover {"));
+}
+
+#[test]
+fn test_lower_colors() {
+    let css = ".a { color: rgb(59 130 246 / 50%); background: hsl(210deg 40% 50%); border-color: #ff000080; outline-color: oklch(62.8% 0.2577 29.23); fill: rgb(var(--c) / 50%); }";
+    let lowered = StyleProcessor::new().with_targets(Targets::parse("safari 10, chrome 60").unwrap()).process(css).unwrap();
+    assert!(lowered.contains("color: rgba(59, 130, 246, 0.5);"), "{}", lowered);
+    assert!(lowered.contains("background: hsl(210, 40%, 50%);"));
+    assert!(lowered.contains("border-color: rgba(255, 0, 0, 0.502);"));
+    assert!(lowered.contains("outline-color: rgb(255, 0, 0);"));
+    assert!(lowered.contains("fill: rgb(var(--c) / 50%);"));
+
+    let kept = StyleProcessor::new().with_targets(Targets::defaults()).process(css).unwrap();
+    assert!(kept.contains("rgb(59 130 246 / 50%)") && kept.contains("#ff000080"));
+}
+
+struct MemoryResolver(HashMap<&'static str, &'static str>);
+
+impl ImportResolver for MemoryResolver {
+    fn load(&self, url: &str, _from: Option<&str>) -> Result<Option<(String, String)>> {
+        Ok(self.0.get(url).map(|source| (url.to_string(), source.to_string())))
+    }
+}
+
+#[test]
+fn test_inline_imports() {
+    let resolver = MemoryResolver(HashMap::from([
+        ("base.css", "@charset \"utf-8\";\nbody { margin: 0; }"),
+        ("print.css", "@import \"base.css\";\n.nav { display: none; }"),
+        ("loop.css", "@import \"loop.css\";"),
+    ]));
+    let processor = StyleProcessor::new().with_import_resolver(Arc::new(resolver));
+    let css = "@import \"base.css\" layer(reset);\n@import url(print.css) print;\n@import \"https://example.com/x.css\";\n.a { color: red; }";
+    let inlined = processor.process(css).unwrap();
+    assert_eq!(
+        inlined,
+        "@layer reset {\n  body {\n    margin: 0;\n  }\n}\n\n@media print {\n  body {\n    margin: 0;\n  }\n  .nav {\n    display: none;\n  }\n}\n@import \"https://example.com/x.css\";\n\n.a {\n  color: red;\n}\n"
+    );
+
+    let err = processor.process("@import \"loop.css\";").unwrap_err();
+    assert!(err.to_string().contains("circular"), "{}", err);
+}
+
+struct Uppercase;
+
+impl StylePlugin for Uppercase {
+    fn name(&self) -> &str {
+        "uppercase"
+    }
+
+    fn process(&self, sheet: &mut Stylesheet, _context: &StyleContext) -> Result<()> {
+        for node in &mut sheet.nodes {
+            if let hxo_ir::CssNode::Rule(rule) = node {
+                rule.selector = rule.selector.to_uppercase();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_custom_plugin_and_minify() {
+    let processor = StyleProcessor::empty().with_plugin(Arc::new(Uppercase)).with_minify(true);
+    assert_eq!(processor.process("a { color: red; }").unwrap(), "A{color:red;}");
+}
+
+#[test]
+fn test_minify_is_safe() {
+    let css = r#"
+/* comment */
+a :hover, ul  >  li ~ p , [title="a  >  b"] {
+  width: calc( 100% - 2 * 0.5rem );
+  content: "a  ,  b";
+  background: URL( "x  y.png" ), #FFFFFF;
+  margin: -0.5em 0 0.25em;
+  color: rgb( 0 0 0 / 0.5 ) !important;
+  --spacing:  1px  2px ;
+}
+.empty { }
+@media screen and (min-width: 640px) , print {
+  .b { transition: opacity 0.3s, transform 0.3s; }
+}
+"#;
+    assert_eq!(
+        minify_css(css).unwrap(),
+        r#"a :hover,ul>li~p,[title="a  >  b"]{width:calc(100% - 2 * .5rem);content:"a  ,  b";background:URL( "x  y.png" ),#fff;margin:-.5em 0 .25em;color:rgb(0 0 0 / .5)!important;--spacing:1px  2px;}@media screen and (min-width:640px),print{.b{transition:opacity .3s,transform .3s;}}"#
+    );
 }
@@ -1,4 +1,5 @@
 use hxo_ir::{IRModule, StyleIR};
+use hxo_style_processor::{minify_css, minify_stylesheet};
 use hxo_types::{CodeWriter, Result};
 
 #[derive(Default)]
@@ -44,18 +45,8 @@ impl CssCompiler {
         Self {}
     }
 
-    pub fn minify(&self, css: &str) -> String {
-        css.trim()
-            .split('\n')
-            .map(|s| s.trim())
-            .collect::<Vec<_>>()
-            .join("")
-            .replace(" {", "{")
-            .replace("{ ", "{")
-            .replace(" }", "}")
-            .replace("} ", "}")
-            .replace(": ", ":")
-            .replace("; ", ";")
+    pub fn minify(&self, css: &str) -> Result<String> {
+        minify_css(css)
     }
 }
 
@@ -68,9 +59,8 @@ impl CssBackend {
         Self { minify }
     }
 
-    pub fn minify(&self, css: &str) -> String {
-        // Basic minification for now, can be improved with lightningcss
-        css.split_whitespace().collect::<Vec<_>>().join(" ")
+    pub fn minify(&self, css: &str) -> Result<String> {
+        minify_css(css)
     }
 
     pub fn generate(&self, ir: &IRModule) -> Result<String> {
@@ -94,11 +84,15 @@ impl CssBackend {
             }
         }
 
-        if self.minify {
-            writer.write(style.code.trim());
-        }
-        else {
-            writer.write_line(&style.code);
+        match &style.stylesheet {
+            Some(sheet) if self.minify => {
+                let mut sheet = sheet.clone();
+                minify_stylesheet(&mut sheet);
+                writer.write(&sheet.to_css(true));
+            }
+            Some(sheet) => writer.write_line(sheet.to_css(false).trim_end()),
+            None if self.minify => writer.write(&self.minify(&style.code)?),
+            None => writer.write_line(&style.code),
         }
         Ok(())
     }
@@ -1,5 +1,5 @@
-use hxo_ir::{IRModule, StyleIR};
-use hxo_target_css::CssBackend;
+use hxo_ir::{CssNode, Declaration, IRModule, StyleIR, StyleRule, Stylesheet};
+use hxo_target_css::{CssBackend, CssCompiler};
 use hxo_types::Span;
 use std::collections::HashMap;
 
@@ -21,6 +21,9 @@ fn test_generate_css() {
         lang: "css".to_string(),
         code: ".test { color: red; }".to_string(),
         scoped: false,
+        module: None,
+        classes: HashMap::new(),
+        stylesheet: None,
         span: Span::default(),
     });
 
@@ -49,6 +52,9 @@ fn test_generate_scoped_css() {
         lang: "css".to_string(),
         code: ".test { color: blue; }".to_string(),
         scoped: true,
+        module: None,
+        classes: HashMap::new(),
+        stylesheet: None,
         span: Span::default(),
     });
 
@@ -58,3 +64,51 @@ fn test_generate_scoped_css() {
     assert!(result.contains(".test { color: blue; }"));
     assert!(result.contains("/* Scoped CSS (lang: css) */"));
 }
+
+#[test]
+fn test_generate_css_from_stylesheet() {
+    let mut ir = IRModule {
+        name: "MyComponent".to_string(),
+        metadata: HashMap::new(),
+        script: None,
+        script_meta: None,
+        template: None,
+        styles: vec![],
+        i18n: None,
+        wasm: vec![],
+        custom_blocks: vec![],
+        span: Span::default(),
+    };
+    let sheet = Stylesheet {
+        nodes: vec![CssNode::Rule(StyleRule {
+            selector: "a :hover".to_string(),
+            children: vec![CssNode::Declaration(Declaration::new("color", "red !important"))],
+            selector_span: Span::default(),
+            span: Span::default(),
+        })],
+    };
+    ir.styles.push(StyleIR {
+        lang: "css".to_string(),
+        code: "/* stale */".to_string(),
+        scoped: false,
+        module: None,
+        classes: HashMap::new(),
+        stylesheet: Some(sheet),
+        span: Span::default(),
+    });
+
+    let result = CssBackend::new(true).generate(&ir).expect("Failed to generate CSS");
+    assert!(result.contains("a :hover{color:red!important;}"), "{result}");
+    assert!(!result.contains("stale"));
+
+    let result = CssBackend::new(false).generate(&ir).expect("Failed to generate CSS");
+    assert!(result.contains("a :hover {\n  color: red !important;\n}"), "{result}");
+}
+
+#[test]
+fn test_minify_keeps_descendant_combinators() {
+    let css = "a :hover { color: red; }\n.b > .c { content: \"x : y\"; width: calc(100% - 1px); }";
+    let expected = "a :hover{color:red;}.b>.c{content:\"x : y\";width:calc(100% - 1px);}";
+    assert_eq!(CssCompiler::new().minify(css).unwrap(), expected);
+    assert_eq!(CssBackend::new(true).minify(css).unwrap(), expected);
+}
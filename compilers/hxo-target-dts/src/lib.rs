@@ -134,6 +134,25 @@ impl DtsBackend {
             writer.newline();
         }
 
+        // CSS Modules class maps
+        let css_modules: Vec<(String, String, Vec<String>)> = ir
+            .css_modules()
+            .into_iter()
+            .map(|(binding, classes)| {
+                let suffix = if binding == "$style" { String::new() } else { pascal_case(&binding) };
+                let type_name = format!("{}{}CssModule", ir.name, suffix);
+                (binding, type_name, classes.into_iter().map(|(class, _)| class).collect())
+            })
+            .collect();
+        for (_, type_name, classes) in &css_modules {
+            writer.write_interface(type_name, |writer| {
+                for class in classes {
+                    writer.write_line(&format!("readonly '{}': string;", class));
+                }
+            });
+            writer.newline();
+        }
+
         // Component Instance (the 'this' or 'ctx' in render)
         writer.write_interface("ComponentInstance", |writer| {
             for signal in &signals {
@@ -143,6 +162,9 @@ impl DtsBackend {
             for prop in &props {
                 writer.write_line(&format!("{}: any;", prop));
             }
+            for (binding, type_name, _) in &css_modules {
+                writer.write_line(&format!("{}: {};", binding, type_name));
+            }
             writer.write_line(&format!("$props: {};", props_name));
             if !emits.is_empty() {
                 writer.write_line(&format!("$emit: {};", emits_name));
@@ -162,6 +184,30 @@ impl DtsBackend {
 
         writer.write_export_default("component");
 
+        if !ir.toc().is_empty() {
+            writer.newline();
+            let levels: Vec<String> = (1..=6).map(|level| level.to_string()).collect();
+            writer.write_interface("TocEntry", |writer| {
+                writer.write_line(&format!("level: {};", levels.join(" | ")));
+                writer.write_line("text: string;");
+                writer.write_line("slug: string;");
+            });
+            writer.write_line("export declare const toc: TocEntry[];");
+        }
+
         Ok(writer.finish())
     }
 }
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
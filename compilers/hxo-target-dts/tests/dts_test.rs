@@ -1,4 +1,4 @@
-use hxo_ir::IRModule;
+use hxo_ir::{AttributeIR, ElementIR, IRModule, StyleIR, TemplateIR, TemplateNodeIR, TocEntry};
 use hxo_target_dts::DtsBackend;
 use hxo_types::{HxoValue, Span};
 use std::collections::HashMap;
@@ -29,3 +29,88 @@ fn test_generate_dts() {
     assert!(dts.contains("export interface CounterEmits {"));
     assert!(dts.contains("(e: 'change', ...args: any[]): void;"));
 }
+
+#[test]
+fn test_generate_dts_css_modules() {
+    let style = |module: &str, classes: &[(&str, &str)]| StyleIR {
+        code: String::new(),
+        lang: "css".to_string(),
+        scoped: false,
+        module: Some(module.to_string()),
+        classes: classes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        stylesheet: None,
+        span: Span::default(),
+    };
+    let ir = IRModule {
+        name: "Card".to_string(),
+        metadata: HashMap::new(),
+        script: None,
+        script_meta: None,
+        template: None,
+        styles: vec![
+            style("$style", &[("title", "title_1"), ("card", "card_1")]),
+            style("classes", &[("is-active", "is-active_1")]),
+        ],
+        i18n: None,
+        wasm: vec![],
+        custom_blocks: vec![],
+        span: Span::default(),
+    };
+
+    let dts = DtsBackend::new().generate(&ir).unwrap();
+    assert!(
+        dts.contains("export interface CardCssModule {\n  readonly 'card': string;\n  readonly 'title': string;\n}"),
+        "{}",
+        dts
+    );
+    assert!(dts.contains("export interface CardClassesCssModule {"));
+    assert!(dts.contains("$style: CardCssModule;"));
+    assert!(dts.contains("classes: CardClassesCssModule;"));
+}
+
+#[test]
+fn test_generate_dts_toc() {
+    let heading = |tag: &str, id: &str, text: &str| {
+        TemplateNodeIR::Element(ElementIR {
+            tag: tag.to_string(),
+            attributes: vec![AttributeIR {
+                name: "id".to_string(),
+                value: Some(id.to_string()),
+                value_ast: None,
+                is_directive: false,
+                is_dynamic: false,
+                span: Span::default(),
+            }],
+            children: vec![TemplateNodeIR::Text(text.to_string(), Span::default())],
+            is_static: false,
+            span: Span::default(),
+        })
+    };
+    let nodes = vec![heading("h1", "guide", "Guide"), heading("h2", "setup", " Setup ")];
+    let mut ir = IRModule {
+        name: "Guide".to_string(),
+        metadata: HashMap::new(),
+        script: None,
+        script_meta: None,
+        template: Some(TemplateIR { toc: TocEntry::outline(&nodes), nodes, span: Span::default() }),
+        styles: vec![],
+        i18n: None,
+        wasm: vec![],
+        custom_blocks: vec![],
+        span: Span::default(),
+    };
+
+    let toc = ir.toc();
+    assert_eq!((toc[1].level, toc[1].text.as_str(), toc[1].slug.as_str()), (2, "Setup", "setup"));
+    let dts = DtsBackend::new().generate(&ir).unwrap();
+    assert!(
+        dts.contains("export interface TocEntry {\n  level: 1 | 2 | 3 | 4 | 5 | 6;\n  text: string;\n  slug: string;\n}"),
+        "{}",
+        dts
+    );
+    assert!(dts.contains("export declare const toc: TocEntry[];"));
+
+    // Headings of other template languages are not an outline
+    ir.template.as_mut().unwrap().toc.clear();
+    assert!(!DtsBackend::new().generate(&ir).unwrap().contains("toc"));
+}
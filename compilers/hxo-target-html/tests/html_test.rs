@@ -26,6 +26,7 @@ fn test_generate_simple_html() {
                 is_static: true,
                 span: Span::default(),
             })],
+            toc: vec![],
             span: Span::default(),
         }),
         styles: vec![],
@@ -64,6 +65,7 @@ fn test_self_closing_tags() {
                 is_static: true,
                 span: Span::default(),
             })],
+            toc: vec![],
             span: Span::default(),
         }),
         styles: vec![],
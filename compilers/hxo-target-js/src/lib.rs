@@ -1,7 +1,10 @@
-use hxo_ir::{IRModule, JsExpr, JsStmt, TemplateNodeIR};
+use hxo_ir::{I18nMessage, IRModule, JsExpr, JsStmt, TemplateNodeIR, TocEntry};
 use hxo_source_map::{SourceMap, SourceMapBuilder};
 use hxo_types::{CodeWriter, Position, Result, Span};
-use std::collections::HashSet;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
 
 #[derive(Clone, Default)]
 pub struct JsWriter {
@@ -109,6 +112,121 @@ pub struct JsBackend {
     pub runtime_path: String,
 }
 
+/// Object literal for a CSS Modules class map, e.g. `{ 'card': 'card_1a2b3c4d' }`.
+pub fn css_module_literal(classes: &[(String, String)]) -> String {
+    if classes.is_empty() {
+        return "{}".to_string();
+    }
+    let entries: Vec<String> = classes.iter().map(|(class, hashed)| format!("'{}': '{}'", class, hashed)).collect();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+/// `export const toc = [...]`, the heading outline of Markdown pages for tables of contents and sidebars.
+/// Nothing is written for other templates.
+pub fn write_toc(writer: &mut JsWriter, toc: &[TocEntry]) {
+    if toc.is_empty() {
+        return;
+    }
+    writer.newline();
+    writer.write_line("export const toc = [");
+    writer.indent();
+    for entry in toc {
+        let (text, slug) = (js_string(&entry.text), js_string(&entry.slug));
+        writer.write_line(&format!("{{ level: {}, text: {}, slug: {} }},", entry.level, text, slug));
+    }
+    writer.dedent();
+    writer.write_line("];");
+}
+
+/// The `i18n` option of a component: locales and keys sorted, keys quoted since they are dotted paths.
+/// Formatters are written as the functions they are.
+pub fn i18n_literal(i18n: &HashMap<String, HashMap<String, I18nMessage>>) -> String {
+    let mut locales: Vec<_> = i18n.iter().collect();
+    locales.sort_by(|a, b| a.0.cmp(b.0));
+    let entries: Vec<String> = locales
+        .into_iter()
+        .map(|(locale, messages)| {
+            let mut messages: Vec<_> = messages.iter().collect();
+            messages.sort_by(|a, b| a.0.cmp(b.0));
+            let messages: Vec<String> = messages
+                .into_iter()
+                .map(|(key, message)| match message {
+                    I18nMessage::Text(text) => format!("{}: {}", js_string(key), js_string(text)),
+                    I18nMessage::Formatter(code) => format!("{}: {}", js_string(key), code),
+                })
+                .collect();
+            format!("{}: {{ {} }}", js_string(locale), messages.join(", "))
+        })
+        .collect();
+    format!("{{ {} }}", entries.join(", "))
+}
+
+/// `text` as a single-quoted JavaScript string literal.
+pub fn js_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('\'');
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{2028}' => out.push_str("\\u2028"),
+            '\u{2029}' => out.push_str("\\u2029"),
+            c => out.push(c),
+        }
+    }
+    out.push('\'');
+    out
+}
+
+/// `nodes` without the whitespace that only formats the template: a line break at the edge of the
+/// parent or between two elements is dropped, one between inline content becomes a space, and
+/// whitespace without a line break (`Hi {{ name }}!`) is kept, so text renders as it does from the
+/// SSR output.
+fn significant_children(nodes: &[TemplateNodeIR]) -> Vec<Cow<'_, TemplateNodeIR>> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, node)| {
+            let TemplateNodeIR::Text(text, span) = node
+            else {
+                return Some(Cow::Borrowed(node));
+            };
+            let content = text.trim();
+            if content.is_empty() {
+                let element = |node: Option<&TemplateNodeIR>| {
+                    matches!(node, Some(TemplateNodeIR::Element(_) | TemplateNodeIR::Comment(..)))
+                };
+                let between_elements = index > 0 && element(nodes.get(index - 1)) && element(nodes.get(index + 1));
+                return match whitespace_run(text, index == 0 || index + 1 == nodes.len() || between_elements) {
+                    "" => None,
+                    run if run == text => Some(Cow::Borrowed(node)),
+                    run => Some(Cow::Owned(TemplateNodeIR::Text(run.to_string(), *span))),
+                };
+            }
+            let leading = whitespace_run(&text[..text.len() - text.trim_start().len()], index == 0);
+            let trailing = whitespace_run(&text[text.trim_end().len()..], index + 1 == nodes.len());
+            let normalized = format!("{}{}{}", leading, content, trailing);
+            Some(if normalized == *text { Cow::Borrowed(node) } else { Cow::Owned(TemplateNodeIR::Text(normalized, *span)) })
+        })
+        .collect()
+}
+
+/// A leading or trailing whitespace run of template text: kept without a line break, otherwise
+/// dropped at the edge of the parent and a single space elsewhere.
+fn whitespace_run(run: &str, at_edge: bool) -> &str {
+    if !run.contains('\n') {
+        run
+    }
+    else if at_edge {
+        ""
+    }
+    else {
+        " "
+    }
+}
+
 impl JsBackend {
     pub fn new(minify: bool, is_prod: bool, target: Option<String>) -> Self {
         Self { minify, is_prod, target, runtime_path: "@hxo".to_string() }
@@ -161,18 +279,19 @@ impl JsBackend {
         render_writer.write_block("render(ctx)", |writer| {
             writer.write("return ");
             if let Some(template) = &ir.template {
-                if template.nodes.is_empty() {
+                let nodes = significant_children(&template.nodes);
+                if nodes.is_empty() {
                     writer.write("null");
                 }
-                else if template.nodes.len() == 1 {
-                    Self::generate_node_with_hoisting(&template.nodes[0], writer, ir, used_core, used_dom, &mut hoisted_nodes);
+                else if nodes.len() == 1 {
+                    Self::generate_node_with_hoisting(&nodes[0], writer, ir, used_core, used_dom, &mut hoisted_nodes);
                 }
                 else {
                     used_dom.insert("h".to_string());
                     used_core.insert("Fragment".to_string());
                     writer.write_line("h(Fragment, null, [");
                     writer.indent();
-                    for node in &template.nodes {
+                    for node in &nodes {
                         Self::generate_node_with_hoisting(node, writer, ir, used_core, used_dom, &mut hoisted_nodes);
                         writer.write_line(",");
                     }
@@ -200,6 +319,15 @@ impl JsBackend {
             writer.newline();
         }
 
+        // CSS Modules class maps, shared by setup and render
+        let css_modules = ir.css_modules();
+        for (binding, classes) in &css_modules {
+            writer.write_line(&format!("const {} = {};", binding, css_module_literal(classes)));
+        }
+        if !css_modules.is_empty() {
+            writer.newline();
+        }
+
         // Component Definition
         writer.write_block("export default", |writer| {
             writer.write_line(&format!("name: '{}',", ir.name));
@@ -208,28 +336,7 @@ impl JsBackend {
             // i18n Data
             if let Some(i18n) = &ir.i18n {
                 writer.write("i18n: ");
-                let mut i18n_writer = JsWriter::new();
-                let i18n_val = hxo_types::HxoValue::Object(
-                    i18n.iter()
-                        .map(|(lang, msgs)| {
-                            (
-                                lang.clone(),
-                                hxo_types::HxoValue::Object(
-                                    msgs.iter().map(|(k, v)| (k.clone(), hxo_types::HxoValue::String(v.clone()))).collect(),
-                                ),
-                            )
-                        })
-                        .collect(),
-                );
-                Self::generate_expr(
-                    &hxo_ir::JsExpr::Literal(i18n_val, hxo_types::Span::unknown()),
-                    &mut i18n_writer,
-                    ir,
-                    used_core,
-                    used_dom,
-                    false,
-                );
-                writer.append(i18n_writer);
+                writer.write(&i18n_literal(i18n));
                 writer.write_line(",");
                 writer.newline();
             }
@@ -276,6 +383,7 @@ impl JsBackend {
                     if ir.i18n.is_some() {
                         returned_ids.push("$t".to_string());
                     }
+                    returned_ids.extend(css_modules.iter().map(|(binding, _)| binding.clone()));
 
                     writer.write("return { ");
                     for (i, id) in returned_ids.iter().enumerate() {
@@ -286,6 +394,10 @@ impl JsBackend {
                     }
                     writer.write_line(" };");
                 }
+                else if !css_modules.is_empty() {
+                    let bindings: Vec<&str> = css_modules.iter().map(|(binding, _)| binding.as_str()).collect();
+                    writer.write_line(&format!("return {{ {} }};", bindings.join(", ")));
+                }
                 else {
                     writer.write_line("return {};");
                 }
@@ -296,6 +408,7 @@ impl JsBackend {
             writer.append(render_writer);
         });
         writer.write_line(";");
+        write_toc(writer, ir.toc());
         Ok(())
     }
 
@@ -323,7 +436,8 @@ impl JsBackend {
         match node {
             TemplateNodeIR::Element(el) => el.is_static,
             TemplateNodeIR::Text(_, _) => true,
-            TemplateNodeIR::Interpolation(_) => false,
+            // An inlined message
+            TemplateNodeIR::Interpolation(exp) => matches!(exp.ast, Some(JsExpr::Literal(..))),
             TemplateNodeIR::Comment(_, _) => true,
         }
     }
@@ -608,7 +722,11 @@ impl JsBackend {
                         if i > 0 {
                             writer.write(", ");
                         }
-                        if attr.name.starts_with('@') || attr.name.starts_with(':') {
+                        if let (Some(name), Some(ast)) = (attr.name.strip_prefix(':'), &attr.value_ast) {
+                            writer.write_with_span(&format!("'{}': ", name), attr.span);
+                            Self::generate_expr(ast, writer, ir, used_core, used_dom, true);
+                        }
+                        else if attr.name.starts_with('@') || attr.name.starts_with(':') {
                             let value = attr.value.as_deref().unwrap_or("");
                             let name = if attr.name.starts_with('@') {
                                 let event = &attr.name[1..];
@@ -652,7 +770,7 @@ impl JsBackend {
                         }
                         else {
                             match &attr.value {
-                                Some(v) => writer.write_with_span(&format!("'{}': '{}'", attr.name, v), attr.span),
+                                Some(v) => writer.write_with_span(&format!("'{}': {}", attr.name, js_string(v)), attr.span),
                                 None => writer.write_with_span(&format!("'{}': true", attr.name), attr.span),
                             }
                         }
@@ -660,13 +778,14 @@ impl JsBackend {
                     writer.write(" }");
                 }
 
-                if el.children.is_empty() {
+                let children = significant_children(&el.children);
+                if children.is_empty() {
                     writer.write(")");
                 }
                 else {
                     writer.write_line(", [");
                     writer.indent();
-                    for child in &el.children {
+                    for child in &children {
                         Self::generate_node_with_hoisting(child, writer, ir, used_core, used_dom, hoisted_nodes);
                         writer.write_line(",");
                     }
@@ -676,7 +795,7 @@ impl JsBackend {
             }
             TemplateNodeIR::Text(t, span) => {
                 used_dom.insert("createTextVNode".to_string());
-                writer.write_with_span(&format!("createTextVNode('{}')", t.trim()), *span);
+                writer.write_with_span(&format!("createTextVNode({})", js_string(t)), *span);
             }
             TemplateNodeIR::Interpolation(exp) => {
                 used_dom.insert("createTextVNode".to_string());
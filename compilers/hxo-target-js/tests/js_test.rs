@@ -1,4 +1,4 @@
-use hxo_ir::{AttributeIR, ElementIR, IRModule, JsExpr, JsProgram, JsStmt, TemplateIR, TemplateNodeIR};
+use hxo_ir::{AttributeIR, ElementIR, ExpressionIR, IRModule, JsExpr, JsProgram, JsStmt, TemplateIR, TemplateNodeIR};
 use hxo_target_js::JsBackend;
 use hxo_types::{HxoValue, Span};
 use std::collections::HashMap;
@@ -77,6 +77,7 @@ fn test_generate_with_template() {
                 is_static: true,
                 span: Span::default(),
             })],
+            toc: vec![],
             span: Span::default(),
         }),
         styles: Vec::new(),
@@ -96,3 +97,34 @@ fn test_generate_with_template() {
     assert!(output.0.contains("render(ctx) {"));
     assert!(output.0.contains("return _hoisted_2;"));
 }
+
+#[test]
+fn test_inline_text_keeps_significant_whitespace() {
+    let text = |t: &str| TemplateNodeIR::Text(t.to_string(), Span::default());
+    let name = ExpressionIR { code: "name".to_string(), ast: None, span: Span::default() };
+    let paragraph = TemplateNodeIR::Element(ElementIR {
+        tag: "p".to_string(),
+        attributes: vec![],
+        children: vec![text("\n  Hi "), TemplateNodeIR::Interpolation(name), text(" there\n")],
+        is_static: false,
+        span: Span::default(),
+    });
+    let ir = IRModule {
+        name: "Test".to_string(),
+        metadata: HashMap::new(),
+        script: None,
+        script_meta: None,
+        template: Some(TemplateIR { nodes: vec![text("\n"), paragraph, text("\n")], toc: vec![], span: Span::default() }),
+        styles: Vec::new(),
+        i18n: None,
+        wasm: Vec::new(),
+        custom_blocks: Vec::new(),
+        span: Span::default(),
+    };
+
+    let code = JsBackend::new(false, false, None).generate(&ir).unwrap().0;
+    assert!(code.contains("createTextVNode('Hi ')"), "{}", code);
+    assert!(code.contains("createTextVNode(ctx.name)"), "{}", code);
+    assert!(code.contains("createTextVNode(' there')"), "{}", code);
+    assert!(!code.contains("createTextVNode('')") && !code.contains("Fragment"), "{}", code);
+}
@@ -48,6 +48,7 @@ fn test_wasm_backend_complex_template() {
                 is_static: true,
                 span: Span::default(),
             })],
+            toc: vec![],
             span: Span::default(),
         }),
         styles: vec![],
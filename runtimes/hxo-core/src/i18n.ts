@@ -1,7 +1,10 @@
 import { createSignal, type Signal } from "./reactivity";
 
+/** Text with `{name}` placeholders, or a formatter compiled from a message with selectors. */
+export type I18nMessage = string | ((args: Record<string, unknown>, locale: string) => string);
+
 export interface I18nMessages {
-    [key: string]: string | I18nMessages;
+    [key: string]: I18nMessage | I18nMessages;
 }
 
 export interface I18nData {
@@ -10,7 +13,7 @@ export interface I18nData {
 
 export interface I18nInstance {
     locale: Signal<string>;
-    t: (key: string) => string;
+    t: (key: string, args?: Record<string, unknown>) => string;
 }
 
 const currentLocale = createSignal("en");
@@ -20,10 +23,18 @@ export function setLocale(locale: string) {
 }
 
 export function useI18n(data?: I18nData): I18nInstance {
-    const t = (key: string): string => {
+    const t = (key: string, args: Record<string, unknown> = {}): string => {
         const locale = currentLocale.get();
-        const messages = data?.[locale] || {};
-        return (messages[key] as string) || key;
+        const message = data?.[locale]?.[key];
+        if (typeof message === "function") {
+            return message(args, locale);
+        }
+        if (typeof message !== "string" || !message) {
+            return key;
+        }
+        return message.replace(/\{\s*([\w$]+)\s*\}/g, (placeholder, name: string) =>
+            name in args ? String(args[name]) : placeholder,
+        );
     };
 
     return {